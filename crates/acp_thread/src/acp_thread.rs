@@ -2391,6 +2391,32 @@ impl AcpThread {
         })
     }
 
+    /// Restores the project to the git checkpoint taken before the given user
+    /// message, leaving the thread's entries untouched.
+    pub fn restore_git_checkpoint(
+        &mut self,
+        id: &UserMessageId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let Some((_, message)) = self.user_message_mut(id) else {
+            return Task::ready(Err(anyhow!("message not found")));
+        };
+        let Some(checkpoint) = message
+            .checkpoint
+            .as_ref()
+            .map(|c| c.git_checkpoint.clone())
+        else {
+            return Task::ready(Err(anyhow!("message has no checkpoint")));
+        };
+
+        let git_store = self.project.read(cx).git_store().clone();
+        cx.spawn(async move |_, cx| {
+            git_store
+                .update(cx, |git, cx| git.restore_checkpoint(checkpoint, cx))
+                .await
+        })
+    }
+
     /// Rewinds this thread to before the entry at `index`, removing it and all
     /// subsequent entries while rejecting any action_log changes made from that point.
     /// Unlike `restore_checkpoint`, this method does not restore from git.
@@ -2685,6 +2711,7 @@ impl AcpThread {
         args: Vec<String>,
         extra_env: Vec<acp::EnvVariable>,
         cwd: Option<PathBuf>,
+        shell: Shell,
         output_byte_limit: Option<u64>,
         cx: &mut Context<Self>,
     ) -> Task<Result<Entity<Terminal>>> {
@@ -2715,17 +2742,21 @@ impl AcpThread {
             let terminal_id = terminal_id.clone();
             async move |_this, cx| {
                 let env = env.await;
-                let shell = project
-                    .update(cx, |project, cx| {
+                let shell = match shell {
+                    Shell::System => Shell::Program(
                         project
-                            .remote_client()
-                            .and_then(|r| r.read(cx).default_system_shell())
-                    })
-                    .unwrap_or_else(|| get_default_system_shell_preferring_bash());
-                let (task_command, task_args) =
-                    ShellBuilder::new(&Shell::Program(shell), is_windows)
-                        .redirect_stdin_to_dev_null()
-                        .build(Some(command.clone()), &args);
+                            .update(cx, |project, cx| {
+                                project
+                                    .remote_client()
+                                    .and_then(|r| r.read(cx).default_system_shell())
+                            })
+                            .unwrap_or_else(|| get_default_system_shell_preferring_bash()),
+                    ),
+                    shell => shell,
+                };
+                let (task_command, task_args) = ShellBuilder::new(&shell, is_windows)
+                    .redirect_stdin_to_dev_null()
+                    .build(Some(command.clone()), &args);
                 let terminal = project
                     .update(cx, |project, cx| {
                         project.create_terminal_task(
@@ -59,6 +59,11 @@ pub enum MentionUri {
     MergeConflict {
         file_path: String,
     },
+    McpResource {
+        server_id: String,
+        uri: String,
+        name: String,
+    },
 }
 
 impl MentionUri {
@@ -211,6 +216,24 @@ impl MentionUri {
                 } else if path.starts_with("/agent/merge-conflict") {
                     let file_path = single_query_param(&url, "path")?.unwrap_or_default();
                     Ok(Self::MergeConflict { file_path })
+                } else if path.starts_with("/agent/mcp-resource") {
+                    let mut server_id = None;
+                    let mut uri = None;
+                    let mut name = None;
+                    for (key, value) in url.query_pairs() {
+                        match key.as_ref() {
+                            "server" => server_id = Some(value.into_owned()),
+                            "uri" => uri = Some(value.into_owned()),
+                            "name" => name = Some(value.into_owned()),
+                            _ => bail!("invalid query parameter"),
+                        }
+                    }
+                    let uri = uri.context("Missing resource uri")?;
+                    Ok(Self::McpResource {
+                        server_id: server_id.context("Missing context server id")?,
+                        name: name.unwrap_or_else(|| uri.clone()),
+                        uri,
+                    })
                 } else {
                     bail!("invalid zed url: {:?}", input);
                 }
@@ -253,6 +276,7 @@ impl MentionUri {
                 ..
             } => selection_name(path.as_deref(), line_range),
             MentionUri::Fetch { url } => url.to_string(),
+            MentionUri::McpResource { name, .. } => name.clone(),
         }
     }
 
@@ -287,6 +311,9 @@ impl MentionUri {
                 )
                 .into(),
             ),
+            MentionUri::McpResource { server_id, uri, .. } => {
+                Some(format!("{uri} ({server_id})").into())
+            }
             _ => None,
         }
     }
@@ -308,6 +335,7 @@ impl MentionUri {
             MentionUri::Fetch { .. } => IconName::ToolWeb.path().into(),
             MentionUri::GitDiff { .. } => IconName::GitBranch.path().into(),
             MentionUri::MergeConflict { .. } => IconName::GitMergeConflict.path().into(),
+            MentionUri::McpResource { .. } => IconName::Server.path().into(),
         }
     }
 
@@ -407,6 +435,18 @@ impl MentionUri {
                 url.query_pairs_mut().append_pair("path", file_path);
                 url
             }
+            MentionUri::McpResource {
+                server_id,
+                uri,
+                name,
+            } => {
+                let mut url = Url::parse("zed:///agent/mcp-resource").unwrap();
+                url.query_pairs_mut()
+                    .append_pair("server", server_id)
+                    .append_pair("uri", uri)
+                    .append_pair("name", name);
+                url
+            }
         }
     }
 }
@@ -730,4 +770,36 @@ mod tests {
         let parsed_single = MentionUri::parse(single_line_uri, PathStyle::local()).unwrap();
         assert_eq!(parsed_single.name(), "Terminal (1 line)");
     }
+
+    #[test]
+    fn test_parse_mcp_resource_uri() {
+        let mention = MentionUri::McpResource {
+            server_id: "github".into(),
+            uri: "repo://zed-industries/zed/README.md?ref=main".into(),
+            name: "README.md".into(),
+        };
+        let uri = mention.to_uri().to_string();
+        assert_eq!(
+            uri,
+            "zed:///agent/mcp-resource?server=github&uri=repo%3A%2F%2Fzed-industries%2Fzed%2FREADME.md%3Fref%3Dmain&name=README.md"
+        );
+        assert_eq!(
+            MentionUri::parse(&uri, PathStyle::local()).unwrap(),
+            mention
+        );
+        assert_eq!(mention.name(), "README.md");
+
+        let without_name = "zed:///agent/mcp-resource?server=db&uri=postgres%3A%2F%2Fusers";
+        assert_eq!(
+            MentionUri::parse(without_name, PathStyle::local()).unwrap(),
+            MentionUri::McpResource {
+                server_id: "db".into(),
+                uri: "postgres://users".into(),
+                name: "postgres://users".into(),
+            }
+        );
+        assert!(
+            MentionUri::parse("zed:///agent/mcp-resource?server=db", PathStyle::local()).is_err()
+        );
+    }
 }
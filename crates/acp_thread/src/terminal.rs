@@ -144,7 +144,11 @@ impl Terminal {
 
     fn truncated_output(&self, cx: &App) -> (String, usize) {
         let terminal = self.terminal.read(cx);
-        let mut content = terminal.get_content();
+        // When the shell reports command boundaries, leave out prompts and anything
+        // printed before the command started.
+        let mut content = terminal
+            .last_command_output()
+            .unwrap_or_else(|| terminal.get_content());
 
         let original_content_len = content.len();
 
@@ -8,7 +8,7 @@ use gpui::{
     App, AppContext, AsyncApp, Context, Entity, SharedString, Subscription, Task, WeakEntity,
 };
 use language::{Anchor, Buffer, BufferEvent, Point, ToOffset, ToPoint};
-use project::{Project, ProjectItem, lsp_store::OpenLspBufferHandle};
+use project::{LocalHistoryReason, Project, ProjectItem, lsp_store::OpenLspBufferHandle};
 use std::{
     cmp,
     ops::Range,
@@ -153,6 +153,8 @@ impl ActionLog {
             .entry(buffer.clone())
             .or_insert_with(|| {
                 let open_lsp_handle = self.project.update(cx, |project, cx| {
+                    // Keep the contents from before the agent touched the buffer in the local history.
+                    project.record_local_history(&buffer, LocalHistoryReason::BeforeAgentEdit, cx);
                     project.register_buffer_with_language_servers(&buffer, cx)
                 });
 
@@ -1,5 +1,6 @@
 mod db;
 mod edit_agent;
+mod hooks;
 mod legacy_thread;
 mod native_agent_server;
 pub mod outline;
@@ -8,9 +9,11 @@ mod templates;
 #[cfg(test)]
 mod tests;
 mod thread;
+mod thread_export;
 mod thread_store;
 mod tool_permissions;
 mod tools;
+mod usage_ledger;
 
 use context_server::ContextServerId;
 pub use db::*;
@@ -20,9 +23,12 @@ pub use pattern_extraction::*;
 pub use shell_command_parser::extract_commands;
 pub use templates::*;
 pub use thread::*;
+pub use thread_export::*;
 pub use thread_store::*;
 pub use tool_permissions::*;
 pub use tools::*;
+pub use usage_ledger::*;
+pub use web_search::{WebSearchApiKeyStatus, WebSearchProvider, WebSearchRegistry};
 
 use acp_thread::{
     AcpThread, AgentModelSelector, AgentSessionInfo, AgentSessionList, AgentSessionListRequest,
@@ -52,6 +58,7 @@ use std::any::Any;
 use std::path::PathBuf;
 use std::rc::Rc;
 use std::sync::{Arc, LazyLock};
+use task::{Shell, SpawnInTerminal};
 use util::ResultExt;
 use util::path_list::PathList;
 use util::rel_path::RelPath;
@@ -785,7 +792,9 @@ impl NativeAgent {
         cx: &mut Context<Self>,
     ) {
         match event {
-            ContextServerRegistryEvent::ToolsChanged => {}
+            ContextServerRegistryEvent::ToolsChanged
+            | ContextServerRegistryEvent::ResourcesChanged
+            | ContextServerRegistryEvent::ResourceUpdated { .. } => {}
             ContextServerRegistryEvent::PromptsChanged => {
                 let project_id = self.projects.iter().find_map(|(id, state)| {
                     if state.context_server_registry == registry {
@@ -1018,6 +1027,47 @@ impl NativeAgent {
         });
     }
 
+    /// Saves a new thread that shares the given session's history up to
+    /// `message_id`, returning the new thread's session id.
+    pub fn fork_thread(
+        &mut self,
+        session_id: &acp::SessionId,
+        message_id: &UserMessageId,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<acp::SessionId>> {
+        let Some(session) = self.sessions.get(session_id) else {
+            return Task::ready(Err(anyhow!("Session not found")));
+        };
+        let Some(state) = self.projects.get(&session.project_id) else {
+            return Task::ready(Err(anyhow!("Project not found")));
+        };
+
+        let folder_paths = PathList::new(
+            &state
+                .project
+                .read(cx)
+                .visible_worktrees(cx)
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+                .collect::<Vec<_>>(),
+        );
+        let db_thread = match session.thread.read(cx).to_forked_db(message_id, cx) {
+            Ok(db_thread) => db_thread,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        let fork_id = acp::SessionId::new(uuid::Uuid::new_v4().to_string());
+        let thread_store = self.thread_store.clone();
+        cx.spawn(async move |_, cx| {
+            let db_thread = db_thread.await;
+            thread_store
+                .update(cx, |store, cx| {
+                    store.save_thread(fork_id.clone(), db_thread, folder_paths, cx)
+                })
+                .await?;
+            Ok(fork_id)
+        })
+    }
+
     fn send_mcp_prompt(
         &self,
         message_id: UserMessageId,
@@ -1145,6 +1195,16 @@ impl NativeAgentConnection {
             .update(cx, |this, cx| this.load_thread(id, project, cx))
     }
 
+    pub fn fork_thread(
+        &self,
+        session_id: &acp::SessionId,
+        message_id: &UserMessageId,
+        cx: &mut App,
+    ) -> Task<Result<acp::SessionId>> {
+        self.0
+            .update(cx, |this, cx| this.fork_thread(session_id, message_id, cx))
+    }
+
     fn run_turn(
         &self,
         session_id: acp::SessionId,
@@ -1871,18 +1931,19 @@ impl NativeThreadEnvironment {
             parent_thread_entity,
         )) as _)
     }
-}
 
-impl ThreadEnvironment for NativeThreadEnvironment {
-    fn create_terminal(
+    fn spawn_terminal(
         &self,
         command: String,
+        args: Vec<String>,
+        env: Vec<acp::EnvVariable>,
         cwd: Option<PathBuf>,
+        shell: Shell,
         output_byte_limit: Option<u64>,
         cx: &mut AsyncApp,
     ) -> Task<Result<Rc<dyn TerminalHandle>>> {
         let task = self.acp_thread.update(cx, |thread, cx| {
-            thread.create_terminal(command, vec![], vec![], cwd, output_byte_limit, cx)
+            thread.create_terminal(command, args, env, cwd, shell, output_byte_limit, cx)
         });
 
         let acp_thread = self.acp_thread.clone();
@@ -1906,6 +1967,51 @@ impl ThreadEnvironment for NativeThreadEnvironment {
             Ok(Rc::new(handle) as _)
         })
     }
+}
+
+impl ThreadEnvironment for NativeThreadEnvironment {
+    fn create_terminal(
+        &self,
+        command: String,
+        cwd: Option<PathBuf>,
+        output_byte_limit: Option<u64>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<Rc<dyn TerminalHandle>>> {
+        self.spawn_terminal(
+            command,
+            Vec::new(),
+            Vec::new(),
+            cwd,
+            Shell::System,
+            output_byte_limit,
+            cx,
+        )
+    }
+
+    fn create_task_terminal(
+        &self,
+        task: SpawnInTerminal,
+        output_byte_limit: Option<u64>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<Rc<dyn TerminalHandle>>> {
+        let Some(command) = task.command else {
+            return Task::ready(Err(anyhow!("Task {:?} has no command", task.label)));
+        };
+        let env = task
+            .env
+            .into_iter()
+            .map(|(name, value)| acp::EnvVariable::new(name, value))
+            .collect();
+        self.spawn_terminal(
+            command,
+            task.args,
+            env,
+            task.cwd,
+            task.shell,
+            output_byte_limit,
+            cx,
+        )
+    }
 
     fn create_subagent(&self, label: String, cx: &mut App) -> Result<Rc<dyn SubagentHandle>> {
         self.create_subagent_thread(label, cx)
@@ -3,12 +3,12 @@ use acp_thread::UserMessageId;
 use agent_client_protocol as acp;
 use agent_settings::AgentProfileId;
 use anyhow::{Result, anyhow};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use collections::{HashMap, IndexMap};
 use futures::{FutureExt, future::Shared};
 use gpui::{BackgroundExecutor, Global, Task};
 use indoc::indoc;
-use language_model::Speed;
+use language_model::{Speed, TokenUsage};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use sqlez::{
@@ -37,6 +37,41 @@ pub struct DbThreadMetadata {
     pub folder_paths: PathList,
 }
 
+/// The tokens used by a single completion request, as stored in the usage
+/// ledger. Records outlive the threads they were made in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbUsageRecord {
+    /// The thread the usage is attributed to. Subagents' usage is attributed
+    /// to the thread that started them.
+    pub session_id: acp::SessionId,
+    /// The title of the thread, or `None` when it has been deleted.
+    pub thread_title: Option<SharedString>,
+    pub folder_paths: PathList,
+    pub provider: SharedString,
+    pub model: SharedString,
+    pub usage: TokenUsage,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Which recorded usage [`ThreadsDatabase::usage_totals`] sums up. Usage
+/// matches when it matches every field that is set.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DbUsageFilter {
+    pub since: Option<DateTime<Utc>>,
+    pub session_id: Option<acp::SessionId>,
+    pub folder_paths: Option<PathList>,
+    pub provider: Option<SharedString>,
+}
+
+/// The tokens used with a single model, summed over the recorded usage that
+/// matches a [`DbUsageFilter`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbUsageTotal {
+    pub provider: SharedString,
+    pub model: SharedString,
+    pub usage: TokenUsage,
+}
+
 impl From<&DbThreadMetadata> for acp_thread::AgentSessionInfo {
     fn from(meta: &DbThreadMetadata) -> Self {
         Self {
@@ -55,6 +90,9 @@ pub struct DbThread {
     pub title: SharedString,
     pub messages: Vec<DbMessage>,
     pub updated_at: DateTime<Utc>,
+    /// Where the messages sent to the model start after compaction.
+    #[serde(default)]
+    pub compaction: Option<crate::ThreadCompaction>,
     #[serde(default)]
     pub detailed_summary: Option<SharedString>,
     #[serde(default)]
@@ -71,6 +109,9 @@ pub struct DbThread {
     pub imported: bool,
     #[serde(default)]
     pub subagent_context: Option<crate::SubagentContext>,
+    /// The thread this one was forked from, if any.
+    #[serde(default)]
+    pub forked_from: Option<acp::SessionId>,
     #[serde(default)]
     pub speed: Option<Speed>,
     #[serde(default)]
@@ -97,6 +138,32 @@ pub struct SharedThread {
     #[serde(default)]
     pub model: Option<DbLanguageModel>,
     pub version: String,
+    /// The rest of the thread's state, which threads exported to a file carry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<SharedThreadDetails>,
+}
+
+/// The state of a shared thread beyond its messages.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SharedThreadDetails {
+    #[serde(default)]
+    pub compaction: Option<crate::ThreadCompaction>,
+    #[serde(default)]
+    pub detailed_summary: Option<SharedString>,
+    #[serde(default)]
+    pub initial_project_snapshot: Option<Arc<crate::ProjectSnapshot>>,
+    #[serde(default)]
+    pub cumulative_token_usage: TokenUsage,
+    #[serde(default)]
+    pub request_token_usage: HashMap<UserMessageId, TokenUsage>,
+    #[serde(default)]
+    pub profile: Option<AgentProfileId>,
+    #[serde(default)]
+    pub speed: Option<Speed>,
+    #[serde(default)]
+    pub thinking_enabled: bool,
+    #[serde(default)]
+    pub thinking_effort: Option<String>,
 }
 
 impl SharedThread {
@@ -109,25 +176,47 @@ impl SharedThread {
             updated_at: thread.updated_at,
             model: thread.model.clone(),
             version: Self::VERSION.to_string(),
+            details: None,
+        }
+    }
+
+    /// Like [`Self::from_db_thread`], but including the thread's details.
+    pub fn from_db_thread_with_details(thread: &DbThread) -> Self {
+        Self {
+            details: Some(SharedThreadDetails {
+                compaction: thread.compaction.clone(),
+                detailed_summary: thread.detailed_summary.clone(),
+                initial_project_snapshot: thread.initial_project_snapshot.clone(),
+                cumulative_token_usage: thread.cumulative_token_usage,
+                request_token_usage: thread.request_token_usage.clone(),
+                profile: thread.profile.clone(),
+                speed: thread.speed,
+                thinking_enabled: thread.thinking_enabled,
+                thinking_effort: thread.thinking_effort.clone(),
+            }),
+            ..Self::from_db_thread(thread)
         }
     }
 
     pub fn to_db_thread(self) -> DbThread {
+        let details = self.details.unwrap_or_default();
         DbThread {
             title: format!("🔗 {}", self.title).into(),
             messages: self.messages,
             updated_at: self.updated_at,
-            detailed_summary: None,
-            initial_project_snapshot: None,
-            cumulative_token_usage: Default::default(),
-            request_token_usage: Default::default(),
+            compaction: details.compaction,
+            detailed_summary: details.detailed_summary,
+            initial_project_snapshot: details.initial_project_snapshot,
+            cumulative_token_usage: details.cumulative_token_usage,
+            request_token_usage: details.request_token_usage,
             model: self.model,
-            profile: None,
+            profile: details.profile,
             imported: true,
             subagent_context: None,
-            speed: None,
-            thinking_enabled: false,
-            thinking_effort: None,
+            forked_from: None,
+            speed: details.speed,
+            thinking_enabled: details.thinking_enabled,
+            thinking_effort: details.thinking_effort,
             draft_prompt: None,
             ui_scroll_position: None,
         }
@@ -292,6 +381,7 @@ impl DbThread {
             title: thread.summary,
             messages,
             updated_at: thread.updated_at,
+            compaction: None,
             detailed_summary: match thread.detailed_summary_state {
                 crate::legacy_thread::DetailedSummaryState::NotGenerated
                 | crate::legacy_thread::DetailedSummaryState::Generating => None,
@@ -304,6 +394,7 @@ impl DbThread {
             profile: thread.profile,
             imported: false,
             subagent_context: None,
+            forked_from: None,
             speed: None,
             thinking_enabled: false,
             thinking_effort: None,
@@ -432,6 +523,24 @@ impl ThreadsDatabase {
             }
         }
 
+        connection.exec(indoc! {"
+            CREATE TABLE IF NOT EXISTS usage (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                session_id TEXT NOT NULL,
+                folder_paths TEXT,
+                folder_paths_order TEXT,
+                provider TEXT NOT NULL,
+                model TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                cache_creation_input_tokens INTEGER NOT NULL,
+                cache_read_input_tokens INTEGER NOT NULL,
+                recorded_at TEXT NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS usage_recorded_at ON usage (recorded_at);
+        "})?()
+        .map_err(|e| anyhow!("Failed to create usage table: {}", e))?;
+
         let db = Self {
             executor,
             connection: Arc::new(Mutex::new(connection)),
@@ -612,6 +721,145 @@ impl ThreadsDatabase {
         })
     }
 
+    pub fn record_usage(&self, record: DbUsageRecord) -> Task<Result<()>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+
+            let (folder_paths, folder_paths_order) = if record.folder_paths.is_empty() {
+                (None, None)
+            } else {
+                let serialized = record.folder_paths.serialize();
+                (Some(serialized.paths), Some(serialized.order))
+            };
+            let usage = record.usage;
+
+            let mut insert = connection.exec_bound::<(Arc<str>, Option<String>, Option<String>, String, String, (u64, u64, u64, u64), String)>(indoc! {"
+                INSERT INTO usage (session_id, folder_paths, folder_paths_order, provider, model, input_tokens, output_tokens, cache_creation_input_tokens, cache_read_input_tokens, recorded_at)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            "})?;
+
+            insert((
+                record.session_id.0,
+                folder_paths,
+                folder_paths_order,
+                record.provider.to_string(),
+                record.model.to_string(),
+                (
+                    usage.input_tokens,
+                    usage.output_tokens,
+                    usage.cache_creation_input_tokens,
+                    usage.cache_read_input_tokens,
+                ),
+                format_timestamp(record.recorded_at),
+            ))?;
+
+            Ok(())
+        })
+    }
+
+    /// Lists the usage recorded since the given time, oldest first.
+    pub fn list_usage(&self, since: Option<DateTime<Utc>>) -> Task<Result<Vec<DbUsageRecord>>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+
+            let mut select = connection
+                .select_bound::<String, (Arc<str>, Option<String>, Option<String>, Option<String>, String, String, (u64, u64, u64, u64), String)>(indoc! {"
+                SELECT usage.session_id, threads.summary, usage.folder_paths, usage.folder_paths_order, usage.provider, usage.model,
+                    usage.input_tokens, usage.output_tokens, usage.cache_creation_input_tokens, usage.cache_read_input_tokens, usage.recorded_at
+                FROM usage LEFT JOIN threads ON threads.id = usage.session_id
+                WHERE usage.recorded_at >= ?
+                ORDER BY usage.recorded_at ASC, usage.id ASC
+            "})?;
+
+            let since = since.map(format_timestamp).unwrap_or_default();
+            let rows = select(since)?;
+            let mut records = Vec::with_capacity(rows.len());
+
+            for (session_id, title, folder_paths, folder_paths_order, provider, model, tokens, recorded_at) in rows {
+                let folder_paths = folder_paths
+                    .map(|paths| {
+                        PathList::deserialize(&util::path_list::SerializedPathList {
+                            paths,
+                            order: folder_paths_order.unwrap_or_default(),
+                        })
+                    })
+                    .unwrap_or_default();
+                let (input_tokens, output_tokens, cache_creation_input_tokens, cache_read_input_tokens) = tokens;
+
+                records.push(DbUsageRecord {
+                    session_id: acp::SessionId::new(session_id),
+                    thread_title: title.map(SharedString::from),
+                    folder_paths,
+                    provider: provider.into(),
+                    model: model.into(),
+                    usage: TokenUsage {
+                        input_tokens,
+                        output_tokens,
+                        cache_creation_input_tokens,
+                        cache_read_input_tokens,
+                    },
+                    recorded_at: DateTime::parse_from_rfc3339(&recorded_at)?.with_timezone(&Utc),
+                });
+            }
+
+            Ok(records)
+        })
+    }
+
+    /// Sums the usage matching the filter, per provider and model.
+    pub fn usage_totals(&self, filter: DbUsageFilter) -> Task<Result<Vec<DbUsageTotal>>> {
+        let connection = self.connection.clone();
+
+        self.executor.spawn(async move {
+            let connection = connection.lock();
+
+            let mut select = connection
+                .select_bound::<(String, Option<Arc<str>>, bool, Option<String>, Option<String>), (String, String, (u64, u64, u64, u64))>(indoc! {"
+                SELECT provider, model,
+                    SUM(input_tokens), SUM(output_tokens), SUM(cache_creation_input_tokens), SUM(cache_read_input_tokens)
+                FROM usage
+                WHERE recorded_at >= ?1
+                    AND (?2 IS NULL OR session_id = ?2)
+                    AND (?3 = 0 OR folder_paths IS ?4)
+                    AND (?5 IS NULL OR provider = ?5)
+                GROUP BY provider, model
+                ORDER BY provider, model
+            "})?;
+
+            let folder_paths = filter.folder_paths.as_ref().and_then(|folder_paths| {
+                (!folder_paths.is_empty()).then(|| folder_paths.serialize().paths)
+            });
+            let rows = select((
+                filter.since.map(format_timestamp).unwrap_or_default(),
+                filter.session_id.map(|session_id| session_id.0),
+                filter.folder_paths.is_some(),
+                folder_paths,
+                filter.provider.map(|provider| provider.to_string()),
+            ))?;
+
+            Ok(rows
+                .into_iter()
+                .map(|(provider, model, tokens)| {
+                    let (input_tokens, output_tokens, cache_creation_input_tokens, cache_read_input_tokens) = tokens;
+                    DbUsageTotal {
+                        provider: provider.into(),
+                        model: model.into(),
+                        usage: TokenUsage {
+                            input_tokens,
+                            output_tokens,
+                            cache_creation_input_tokens,
+                            cache_read_input_tokens,
+                        },
+                    }
+                })
+                .collect())
+        })
+    }
+
     pub fn delete_threads(&self) -> Task<Result<()>> {
         let connection = self.connection.clone();
 
@@ -629,6 +877,12 @@ impl ThreadsDatabase {
     }
 }
 
+/// Formats usage timestamps with a fixed precision, so that they sort
+/// chronologically when compared as text.
+fn format_timestamp(timestamp: DateTime<Utc>) -> String {
+    timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -645,6 +899,7 @@ mod tests {
             updated_at: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
             model: None,
             version: SharedThread::VERSION.to_string(),
+            details: None,
         };
 
         let bytes = original.to_bytes().expect("Failed to serialize");
@@ -681,6 +936,7 @@ mod tests {
             title: title.to_string().into(),
             messages: Vec::new(),
             updated_at,
+            compaction: None,
             detailed_summary: None,
             initial_project_snapshot: None,
             cumulative_token_usage: Default::default(),
@@ -689,6 +945,7 @@ mod tests {
             profile: None,
             imported: false,
             subagent_context: None,
+            forked_from: None,
             speed: None,
             thinking_enabled: false,
             thinking_effort: None,
@@ -949,4 +1206,132 @@ mod tests {
         assert_eq!(scroll.item_ix, 42);
         assert!((scroll.offset_in_item - 13.5).abs() < f32::EPSILON);
     }
+
+    #[gpui::test]
+    async fn test_usage_outlives_deleted_threads(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(cx.executor()).unwrap();
+
+        let thread_id = session_id("thread-with-usage");
+        let folder_paths = PathList::new(&[std::path::PathBuf::from("/home/user/project")]);
+        database
+            .save_thread(
+                thread_id.clone(),
+                make_thread("Usage", Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()),
+                folder_paths.clone(),
+            )
+            .await
+            .unwrap();
+
+        let record = |hour, input_tokens| DbUsageRecord {
+            session_id: thread_id.clone(),
+            thread_title: None,
+            folder_paths: folder_paths.clone(),
+            provider: "anthropic".into(),
+            model: "claude-sonnet-4".into(),
+            usage: TokenUsage {
+                input_tokens,
+                output_tokens: 10,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: 5,
+            },
+            recorded_at: Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap(),
+        };
+        database.record_usage(record(9, 100)).await.unwrap();
+        database.record_usage(record(12, 200)).await.unwrap();
+
+        let usage = database.list_usage(None).await.unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].thread_title, Some("Usage".into()));
+        assert_eq!(usage[0].folder_paths, folder_paths);
+        assert_eq!(usage[1].usage.input_tokens, 200);
+
+        let since = Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap();
+        let usage = database.list_usage(Some(since)).await.unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].usage.input_tokens, 200);
+
+        database.delete_thread(thread_id).await.unwrap();
+        let usage = database.list_usage(None).await.unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!(usage[0].thread_title, None);
+    }
+
+    #[gpui::test]
+    async fn test_usage_totals(cx: &mut TestAppContext) {
+        let database = ThreadsDatabase::new(cx.executor()).unwrap();
+
+        let project = PathList::new(&[std::path::PathBuf::from("/home/user/project")]);
+        let record = |session: &str, folder_paths: &PathList, model: &str, hour| DbUsageRecord {
+            session_id: session_id(session),
+            thread_title: None,
+            folder_paths: folder_paths.clone(),
+            provider: "anthropic".into(),
+            model: model.to_string().into(),
+            usage: TokenUsage {
+                input_tokens: 100,
+                output_tokens: 10,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: 5,
+            },
+            recorded_at: Utc.with_ymd_and_hms(2024, 1, 1, hour, 0, 0).unwrap(),
+        };
+        for record in [
+            record("a", &project, "sonnet", 9),
+            record("a", &project, "sonnet", 10),
+            record("a", &project, "haiku", 11),
+            record("b", &project, "sonnet", 12),
+            record("c", &PathList::default(), "sonnet", 13),
+        ] {
+            database.record_usage(record).await.unwrap();
+        }
+
+        let totals = |filter| {
+            let totals = database.usage_totals(filter);
+            async move {
+                totals
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|total| (total.model.to_string(), total.usage.input_tokens))
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            totals(DbUsageFilter::default()).await,
+            [("haiku".into(), 100), ("sonnet".into(), 400)]
+        );
+        assert_eq!(
+            totals(DbUsageFilter {
+                session_id: Some(session_id("a")),
+                since: Some(Utc.with_ymd_and_hms(2024, 1, 1, 10, 0, 0).unwrap()),
+                ..Default::default()
+            })
+            .await,
+            [("haiku".into(), 100), ("sonnet".into(), 100)]
+        );
+        assert_eq!(
+            totals(DbUsageFilter {
+                folder_paths: Some(project),
+                ..Default::default()
+            })
+            .await,
+            [("haiku".into(), 100), ("sonnet".into(), 300)]
+        );
+        assert_eq!(
+            totals(DbUsageFilter {
+                folder_paths: Some(PathList::default()),
+                ..Default::default()
+            })
+            .await,
+            [("sonnet".into(), 100)]
+        );
+        assert_eq!(
+            totals(DbUsageFilter {
+                provider: Some("openai".into()),
+                ..Default::default()
+            })
+            .await,
+            []
+        );
+    }
 }
@@ -0,0 +1,345 @@
+use crate::AgentToolOutput;
+use agent_client_protocol as acp;
+use agent_settings::{AgentHook, AgentHooks};
+use anyhow::{Context as _, Result, anyhow};
+use futures::{AsyncWriteExt as _, FutureExt as _};
+use gpui::{App, AsyncApp, BackgroundExecutor, Entity, WeakEntity};
+use language_model::LanguageModelToolResultContent;
+use project::Project;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+use task::{Shell, ShellBuilder};
+use util::command::{Stdio, new_command};
+
+/// The agent activity a hook is run for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum HookEvent {
+    UserPromptSubmit,
+    PreToolCall,
+    PostToolCall,
+    PostEdit,
+    Stop,
+}
+
+/// The hooks configured for calls to a single tool.
+#[derive(Default)]
+pub(crate) struct ToolHooks {
+    pub pre_tool_call: Vec<AgentHook>,
+    pub post_tool_call: Vec<AgentHook>,
+    pub post_edit: Vec<AgentHook>,
+}
+
+impl ToolHooks {
+    pub fn new(hooks: &AgentHooks, tool_name: &str) -> Self {
+        let matching = |hooks: &[AgentHook]| {
+            hooks
+                .iter()
+                .filter(|hook| hook.applies_to(tool_name))
+                .cloned()
+                .collect()
+        };
+        Self {
+            pre_tool_call: matching(&hooks.pre_tool_call),
+            post_tool_call: matching(&hooks.post_tool_call),
+            post_edit: matching(&hooks.post_edit),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pre_tool_call.is_empty() && self.post_tool_call.is_empty() && self.post_edit.is_empty()
+    }
+}
+
+/// The machine hook commands run on.
+#[derive(Clone)]
+enum HookHost {
+    Local,
+    /// The host of a remote project, reached through its remote connection.
+    Remote(WeakEntity<Project>),
+}
+
+/// Runs the hook commands of a thread in the root of its project.
+#[derive(Clone)]
+pub(crate) struct HookRunner {
+    session_id: acp::SessionId,
+    cwd: Option<PathBuf>,
+    host: HookHost,
+    executor: BackgroundExecutor,
+}
+
+impl HookRunner {
+    /// Hooks run on the machine the project's files are on. That's not possible
+    /// for projects shared by collaborators, so their hooks are refused rather
+    /// than skipped.
+    pub fn new(project: &Entity<Project>, session_id: acp::SessionId, cx: &App) -> Result<Self> {
+        let host = if project.read(cx).is_local() {
+            HookHost::Local
+        } else if project.read(cx).remote_client().is_some() {
+            HookHost::Remote(project.downgrade())
+        } else {
+            return Err(anyhow!(
+                "Agent hooks are configured, but they can't run in a project shared by a collaborator"
+            ));
+        };
+        let cwd = project
+            .read(cx)
+            .visible_worktrees(cx)
+            .next()
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf());
+        Ok(Self {
+            session_id,
+            cwd,
+            host,
+            executor: cx.background_executor().clone(),
+        })
+    }
+
+    /// Runs a hook, passing it a JSON object describing the event on stdin.
+    /// The fields of `input` are added to that object.
+    pub async fn run(
+        &self,
+        hook: &AgentHook,
+        event: HookEvent,
+        input: serde_json::Value,
+        cx: &AsyncApp,
+    ) -> HookRun {
+        let mut payload = serde_json::json!({
+            "event": event,
+            "session_id": self.session_id,
+            "cwd": self.cwd,
+        });
+        if let (Some(payload), serde_json::Value::Object(fields)) = (payload.as_object_mut(), input)
+        {
+            payload.extend(fields);
+        }
+
+        let timeout = self.executor.timer(hook.timeout);
+        let result = futures::select! {
+            result = self.execute(&hook.command, &payload, cx).fuse() => result,
+            _ = timeout.fuse() => Err(anyhow!("timed out after {}ms", hook.timeout.as_millis())),
+        };
+
+        match result {
+            Ok(output) => HookRun {
+                command: hook.command.clone(),
+                success: output.status.success(),
+                stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            },
+            Err(error) => {
+                log::error!("Failed to run agent hook `{}`: {error:#}", hook.command);
+                // A hook that can't run fails like a hook that rejects the
+                // event, so that policies enforced by hooks can't be bypassed.
+                HookRun {
+                    command: hook.command.clone(),
+                    success: false,
+                    stdout: String::new(),
+                    stderr: format!("The hook could not be run: {error:#}"),
+                }
+            }
+        }
+    }
+
+    async fn execute(
+        &self,
+        command: &str,
+        payload: &serde_json::Value,
+        cx: &AsyncApp,
+    ) -> Result<std::process::Output> {
+        let mut child = match &self.host {
+            HookHost::Local => {
+                let (program, args) = ShellBuilder::new(&Shell::System, cfg!(windows))
+                    .non_interactive()
+                    .build(Some(command.to_string()), &[]);
+                let mut child = new_command(program);
+                child.args(args);
+                if let Some(cwd) = &self.cwd {
+                    child.current_dir(cwd);
+                }
+                child
+            }
+            HookHost::Remote(project) => {
+                let cwd = self.cwd.as_ref().map(|cwd| cwd.display().to_string());
+                let remote_command = project
+                    .read_with(cx, |project, cx| {
+                        project.build_remote_shell_command(command.to_string(), cwd, cx)
+                    })?
+                    .context("the project is no longer remote")??;
+                let mut child = new_command(remote_command.program);
+                child.args(remote_command.args).envs(remote_command.env);
+                child
+            }
+        };
+        child
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        let mut child = child
+            .spawn()
+            .with_context(|| format!("failed to spawn `{command}`"))?;
+
+        let input = serde_json::to_vec(payload)?;
+        let stdin = child.stdin.take();
+        let write_input = async move {
+            if let Some(mut stdin) = stdin {
+                // Hooks don't have to read their input, so a closed pipe isn't an error.
+                stdin.write_all(&input).await.ok();
+            }
+        };
+        let (output, ()) = futures::join!(child.output(), write_input);
+        Ok(output?)
+    }
+}
+
+/// The outcome of running a hook command.
+#[derive(Debug)]
+pub(crate) struct HookRun {
+    pub command: String,
+    pub success: bool,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl HookRun {
+    /// The message shown to the model when this hook blocks an action.
+    pub fn rejection(&self) -> String {
+        match self.output() {
+            Some(output) => format!("Blocked by the `{}` hook:\n{output}", self.command),
+            None => format!("Blocked by the `{}` hook.", self.command),
+        }
+    }
+
+    /// What the hook printed, if anything, for the model to see.
+    pub fn report(&self) -> Option<String> {
+        if self.success {
+            let stdout = self.stdout.trim();
+            (!stdout.is_empty())
+                .then(|| format!("Output of the `{}` hook:\n{stdout}", self.command))
+        } else {
+            Some(match self.output() {
+                Some(output) => format!("The `{}` hook failed:\n{output}", self.command),
+                None => format!("The `{}` hook failed.", self.command),
+            })
+        }
+    }
+
+    /// The input a `pre_tool_call` hook replaces the tool's input with,
+    /// which it prints as `{"tool_input": ...}`.
+    pub fn replaced_tool_input(&self) -> Option<serde_json::Value> {
+        #[derive(Deserialize)]
+        struct PreToolCallResponse {
+            tool_input: serde_json::Value,
+        }
+
+        let response = serde_json::from_str::<PreToolCallResponse>(self.stdout.trim()).ok()?;
+        Some(response.tool_input)
+    }
+
+    fn output(&self) -> Option<String> {
+        let output = [self.stdout.trim(), self.stderr.trim()]
+            .into_iter()
+            .filter(|output| !output.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n");
+        (!output.is_empty()).then_some(output)
+    }
+}
+
+/// Adds the reports of the hooks that ran around a tool call to its output.
+pub(crate) fn append_hook_reports(output: &mut AgentToolOutput, reports: &[String]) {
+    if reports.is_empty() {
+        return;
+    }
+    match &output.llm_output {
+        LanguageModelToolResultContent::Text(text) => {
+            let mut text = text.to_string();
+            for report in reports {
+                if !text.is_empty() {
+                    text.push_str("\n\n");
+                }
+                text.push_str(report);
+            }
+            output.llm_output = LanguageModelToolResultContent::Text(Arc::from(text));
+        }
+        LanguageModelToolResultContent::Image(_) => {
+            log::warn!("Dropping agent hook output for a tool that returned an image");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hook_run(success: bool, stdout: &str, stderr: &str) -> HookRun {
+        HookRun {
+            command: "./check.sh".to_string(),
+            success,
+            stdout: stdout.to_string(),
+            stderr: stderr.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_hook_run_messages() {
+        let run = hook_run(false, "", "generated files can't be edited\n");
+        assert_eq!(
+            run.rejection(),
+            "Blocked by the `./check.sh` hook:\ngenerated files can't be edited"
+        );
+        assert_eq!(
+            run.report().as_deref(),
+            Some("The `./check.sh` hook failed:\ngenerated files can't be edited")
+        );
+        assert_eq!(
+            hook_run(false, "", "").rejection(),
+            "Blocked by the `./check.sh` hook."
+        );
+
+        assert_eq!(hook_run(true, " \n", "warning").report(), None);
+        assert_eq!(
+            hook_run(true, "all good\n", "").report().as_deref(),
+            Some("Output of the `./check.sh` hook:\nall good")
+        );
+    }
+
+    #[test]
+    fn test_replaced_tool_input() {
+        let run = hook_run(true, r#"{"tool_input": {"path": "src/lib.rs"}}"#, "");
+        assert_eq!(
+            run.replaced_tool_input(),
+            Some(serde_json::json!({"path": "src/lib.rs"}))
+        );
+        assert_eq!(hook_run(true, "looks fine", "").replaced_tool_input(), None);
+        assert_eq!(
+            hook_run(true, r#"{"decision": "allow"}"#, "").replaced_tool_input(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_tool_hooks_match_tool_names() {
+        let hook = |command: &str, tools: Option<&[&str]>| AgentHook {
+            command: command.to_string(),
+            tools: tools.map(|tools| tools.iter().map(|tool| tool.to_string()).collect()),
+            timeout: AgentHook::DEFAULT_TIMEOUT,
+        };
+        let hooks = AgentHooks {
+            pre_tool_call: vec![hook("all", None), hook("edits", Some(&["edit_file"]))],
+            post_edit: vec![hook("fmt", Some(&["edit_file", "rename_symbol"]))],
+            ..Default::default()
+        };
+
+        let edit_hooks = ToolHooks::new(&hooks, "edit_file");
+        assert_eq!(edit_hooks.pre_tool_call.len(), 2);
+        assert_eq!(edit_hooks.post_edit.len(), 1);
+
+        let terminal_hooks = ToolHooks::new(&hooks, "terminal");
+        assert_eq!(terminal_hooks.pre_tool_call.len(), 1);
+        assert!(terminal_hooks.post_edit.is_empty());
+
+        assert!(ToolHooks::new(&AgentHooks::default(), "terminal").is_empty());
+    }
+}
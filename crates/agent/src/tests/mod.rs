@@ -28,8 +28,9 @@ use language_model::{
     CompletionIntent, LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent,
     LanguageModelId, LanguageModelProviderName, LanguageModelRegistry, LanguageModelRequest,
     LanguageModelRequestMessage, LanguageModelToolResult, LanguageModelToolSchemaFormat,
-    LanguageModelToolUse, MessageContent, Role, StopReason, TokenUsage,
+    LanguageModelToolUse, LanguageModelToolUseId, MessageContent, Role, StopReason, TokenUsage,
     fake_provider::FakeLanguageModel,
+    replay::{RecordingLanguageModel, ReplayFixture, ReplayLanguageModel},
 };
 use pretty_assertions::assert_eq;
 use project::{
@@ -212,6 +213,15 @@ impl crate::ThreadEnvironment for FakeThreadEnvironment {
         Task::ready(Ok(handle as Rc<dyn crate::TerminalHandle>))
     }
 
+    fn create_task_terminal(
+        &self,
+        task: task::SpawnInTerminal,
+        output_byte_limit: Option<u64>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<Rc<dyn crate::TerminalHandle>>> {
+        self.create_terminal(task.command_label, task.cwd, output_byte_limit, cx)
+    }
+
     fn create_subagent(&self, _label: String, _cx: &mut App) -> Result<Rc<dyn SubagentHandle>> {
         Ok(self
             .subagent_handle
@@ -251,6 +261,15 @@ impl crate::ThreadEnvironment for MultiTerminalEnvironment {
         Task::ready(Ok(handle as Rc<dyn crate::TerminalHandle>))
     }
 
+    fn create_task_terminal(
+        &self,
+        task: task::SpawnInTerminal,
+        output_byte_limit: Option<u64>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<Rc<dyn crate::TerminalHandle>>> {
+        self.create_terminal(task.command_label, task.cwd, output_byte_limit, cx)
+    }
+
     fn create_subagent(&self, _label: String, _cx: &mut App) -> Result<Rc<dyn SubagentHandle>> {
         unimplemented!()
     }
@@ -2873,6 +2892,227 @@ async fn test_refusal(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_usage_budget_hard_limit_pauses_thread(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    cx.update(|cx| {
+        UsageLedger::init_global(cx);
+        let mut settings = agent_settings::AgentSettings::get_global(cx).clone();
+        settings.usage.budgets = vec![agent_settings::UsageBudget {
+            scope: settings::UsageBudgetScope::Thread,
+            provider: None,
+            period: settings::UsageBudgetPeriod::AllTime,
+            unit: settings::UsageBudgetUnit::Tokens,
+            soft_limit: None,
+            hard_limit: Some(1_000.),
+        }];
+        agent_settings::AgentSettings::override_global(settings, cx);
+    });
+
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Hello"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Hey!");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        TokenUsage {
+            input_tokens: 800,
+            output_tokens: 400,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        },
+    ));
+    fake_model
+        .send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(StopReason::EndTurn));
+    fake_model.end_last_completion_stream();
+    let events = events.collect::<Vec<_>>().await;
+    assert_eq!(stop_events(events), vec![acp::StopReason::EndTurn]);
+    cx.run_until_parked();
+
+    let usage = cx
+        .update(|cx| UsageLedger::global(cx).update(cx, |ledger, cx| ledger.usage(None, cx)))
+        .await
+        .unwrap();
+    let thread_id = thread.read_with(cx, |thread, _| thread.id().clone());
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0].session_id, thread_id);
+    assert_eq!(usage[0].usage.total_tokens(), 1_200);
+
+    // The budget is spent, so the next message doesn't reach the model.
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Again"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    assert!(fake_model.pending_completions().is_empty());
+    let events = events.collect::<Vec<_>>().await;
+    let error = events
+        .into_iter()
+        .find_map(|event| event.err())
+        .expect("the turn should fail");
+    assert!(
+        error
+            .to_string()
+            .contains("reaching its hard limit of 1000 tokens"),
+        "{error}"
+    );
+}
+
+#[gpui::test]
+async fn test_compaction_usage_is_recorded(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    cx.update(|cx| UsageLedger::init_global(cx));
+
+    for message in ["Message 1", "Message 2"] {
+        let send = thread
+            .update(cx, |thread, cx| {
+                thread.send(UserMessageId::new(), [message], cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        fake_model.send_last_completion_stream_text_chunk("Response");
+        fake_model.end_last_completion_stream();
+        send.collect::<Vec<_>>().await;
+    }
+
+    let compact = thread.update(cx, |thread, cx| thread.compact(cx));
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Summary");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        TokenUsage {
+            input_tokens: 300,
+            output_tokens: 20,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    compact.await.unwrap();
+    cx.run_until_parked();
+
+    let usage = cx
+        .update(|cx| UsageLedger::global(cx).update(cx, |ledger, cx| ledger.usage(None, cx)))
+        .await
+        .unwrap();
+    let thread_id = thread.read_with(cx, |thread, _| thread.id().clone());
+    assert_eq!(usage.len(), 1);
+    assert_eq!(usage[0].session_id, thread_id);
+    assert_eq!(usage[0].usage.total_tokens(), 320);
+}
+
+#[gpui::test]
+async fn test_record_and_replay_turn(cx: &mut TestAppContext) {
+    let ThreadTest {
+        model,
+        thread,
+        project_context,
+        context_server_store,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+    let recording = Arc::new(RecordingLanguageModel::new(model.clone()));
+
+    let events = thread
+        .update(cx, |thread, cx| {
+            thread.set_model(recording.clone(), cx);
+            thread.add_tool(EchoTool);
+            thread.send(UserMessageId::new(), ["Use the echo tool"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_1".into(),
+            name: EchoTool::NAME.into(),
+            raw_input: json!({"text": "test"}).to_string(),
+            input: json!({"text": "test"}),
+            is_input_complete: true,
+            thought_signature: None,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("The echo tool works.");
+    fake_model
+        .send_last_completion_stream_event(LanguageModelCompletionEvent::Stop(StopReason::EndTurn));
+    fake_model.end_last_completion_stream();
+    let events = events.collect::<Vec<_>>().await;
+    assert_eq!(stop_events(events), vec![acp::StopReason::EndTurn]);
+
+    let fixture = recording.fixture();
+    assert_eq!(fixture.completions.len(), 2);
+    let fixture =
+        serde_json::from_str::<ReplayFixture>(&serde_json::to_string(&fixture).unwrap()).unwrap();
+
+    let new_thread = |model: Arc<dyn LanguageModel>, cx: &mut TestAppContext| {
+        let project = thread.read_with(cx, |thread, _| thread.project().clone());
+        let context_server_registry =
+            cx.new(|cx| ContextServerRegistry::new(context_server_store.clone(), cx));
+        cx.new(|cx| {
+            let mut thread = Thread::new(
+                project,
+                project_context.clone(),
+                context_server_registry,
+                Templates::new(),
+                Some(model),
+                cx,
+            );
+            thread.add_tool(EchoTool);
+            thread
+        })
+    };
+
+    // Replaying the same conversation produces the same thread, without the model.
+    let replay = Arc::new(ReplayLanguageModel::new(fixture.clone()));
+    let replayed_thread = new_thread(replay.clone(), cx);
+    let events = replayed_thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Use the echo tool"], cx)
+        })
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(stop_events(events), vec![acp::StopReason::EndTurn]);
+    assert_eq!(replay.remaining_completions(), 0);
+    assert_eq!(
+        replayed_thread.read_with(cx, |thread, _| thread.to_markdown()),
+        thread.read_with(cx, |thread, _| thread.to_markdown())
+    );
+
+    // A request that wasn't recorded fails the turn.
+    let replay = Arc::new(ReplayLanguageModel::new(fixture));
+    let changed_thread = new_thread(replay.clone(), cx);
+    let mut events = changed_thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Use the echo tool twice"], cx)
+        })
+        .unwrap();
+    for _ in 0..3 {
+        cx.executor().advance_clock(Duration::from_secs(60));
+        cx.run_until_parked();
+    }
+    let mut errors = Vec::new();
+    while let Some(event) = events.next().await {
+        if let Err(error) = event {
+            errors.push(error);
+        }
+    }
+    assert_eq!(errors.len(), 1);
+    assert!(
+        errors[0]
+            .to_string()
+            .contains("the request doesn't match any recorded completion"),
+        "{}",
+        errors[0]
+    );
+    assert_eq!(replay.remaining_completions(), 2);
+}
+
 #[gpui::test]
 async fn test_truncate_first_message(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
@@ -3110,6 +3350,80 @@ async fn test_truncate_second_message(cx: &mut TestAppContext) {
     assert_first_message_state(cx);
 }
 
+#[gpui::test]
+async fn test_fork_thread(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    let first_message_id = UserMessageId::new();
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(first_message_id.clone(), ["Message 1"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Message 1 response");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 32_000,
+            output_tokens: 16_000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let second_message_id = UserMessageId::new();
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(second_message_id.clone(), ["Message 2"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Message 2 response");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 40_000,
+            output_tokens: 20_000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    let original_markdown = thread.read_with(cx, |thread, _| thread.to_markdown());
+    let fork = thread
+        .read_with(cx, |thread, cx| thread.to_forked_db(&second_message_id, cx))
+        .unwrap()
+        .await;
+
+    // The fork keeps the history before the chosen message and offers the
+    // message itself as a draft, while the original thread is left intact.
+    assert_eq!(fork.messages.len(), 2);
+    assert_eq!(
+        fork.forked_from.as_ref(),
+        Some(thread.read_with(cx, |thread, _| thread.id().clone())).as_ref()
+    );
+    assert_eq!(
+        fork.draft_prompt,
+        Some(vec![acp::ContentBlock::from("Message 2".to_string())])
+    );
+    assert_eq!(
+        fork.request_token_usage.keys().collect::<Vec<_>>(),
+        vec![&first_message_id]
+    );
+    thread.read_with(cx, |thread, _| {
+        assert_eq!(thread.to_markdown(), original_markdown);
+    });
+
+    let missing = thread.read_with(cx, |thread, cx| {
+        thread.to_forked_db(&UserMessageId::new(), cx).map(|_| ())
+    });
+    assert!(missing.is_err());
+}
+
 #[gpui::test]
 async fn test_title_generation(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
@@ -3161,57 +3475,356 @@ async fn test_title_generation(cx: &mut TestAppContext) {
 }
 
 #[gpui::test]
-async fn test_building_request_with_pending_tools(cx: &mut TestAppContext) {
+async fn test_auto_compaction(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
     let fake_model = model.as_fake();
 
-    let _events = thread
+    let summary_model = Arc::new(FakeLanguageModel::default());
+    thread.update(cx, |thread, cx| {
+        thread.set_summarization_model(Some(summary_model.clone()), cx)
+    });
+
+    let send = thread
         .update(cx, |thread, cx| {
-            thread.add_tool(ToolRequiringPermission);
-            thread.add_tool(EchoTool);
-            thread.send(UserMessageId::new(), ["Hey!"], cx)
+            thread.send(UserMessageId::new(), ["Message 1"], cx)
         })
         .unwrap();
     cx.run_until_parked();
-
-    let permission_tool_use = LanguageModelToolUse {
-        id: "tool_id_1".into(),
-        name: ToolRequiringPermission::NAME.into(),
-        raw_input: "{}".into(),
-        input: json!({}),
-        is_input_complete: true,
-        thought_signature: None,
-    };
-    let echo_tool_use = LanguageModelToolUse {
-        id: "tool_id_2".into(),
-        name: EchoTool::NAME.into(),
-        raw_input: json!({"text": "test"}).to_string(),
-        input: json!({"text": "test"}),
-        is_input_complete: true,
-        thought_signature: None,
-    };
-    fake_model.send_last_completion_stream_text_chunk("Hi!");
-    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
-        permission_tool_use,
-    ));
-    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
-        echo_tool_use.clone(),
+    fake_model.send_last_completion_stream_text_chunk("Response 1");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::UsageUpdate(
+        language_model::TokenUsage {
+            input_tokens: 900_000,
+            output_tokens: 1_000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 0,
+        },
     ));
     fake_model.end_last_completion_stream();
     cx.run_until_parked();
+    summary_model.send_last_completion_stream_text_chunk("Title");
+    summary_model.end_last_completion_stream();
+    send.collect::<Vec<_>>().await;
+    cx.run_until_parked();
 
-    // Ensure pending tools are skipped when building a request.
-    let request = thread
-        .read_with(cx, |thread, cx| {
-            thread.build_completion_request(CompletionIntent::EditFile, cx)
+    // The previous request used most of the context window, so the next turn
+    // compacts the thread before calling the model.
+    let message_2_id = UserMessageId::new();
+    let send = thread
+        .update(cx, |thread, cx| {
+            thread.send(message_2_id.clone(), ["Message 2"], cx)
         })
         .unwrap();
+    cx.run_until_parked();
+    assert_eq!(fake_model.pending_completions(), Vec::new());
+    thread.read_with(cx, |thread, _| assert!(thread.is_compacting()));
+
+    let compaction_request = summary_model.pending_completions().pop().unwrap();
     assert_eq!(
-        request.messages[1..],
+        compaction_request
+            .messages
+            .iter()
+            .map(|message| message.string_contents())
+            .collect::<Vec<_>>(),
         vec![
-            LanguageModelRequestMessage {
-                role: Role::User,
-                content: vec!["Hey!".into()],
+            "Message 1".to_string(),
+            "Response 1".to_string(),
+            agent_settings::COMPACT_THREAD_PROMPT.to_string(),
+        ]
+    );
+    summary_model.send_last_completion_stream_text_chunk("Summary of ");
+    summary_model.send_last_completion_stream_text_chunk("message 1");
+    summary_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    thread.read_with(cx, |thread, _| {
+        let compaction = thread.compaction().unwrap();
+        assert_eq!(compaction.message_count, 2);
+        assert_eq!(compaction.first_kept_message_id, message_2_id);
+        assert_eq!(compaction.summary, "Summary of message 1");
+        assert!(!thread.is_compacting());
+        // The original messages are kept around.
+        assert!(thread.to_markdown().contains("Message 1"));
+    });
+
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(completion.messages.len(), 3);
+    assert_eq!(completion.messages[1].role, Role::User);
+    assert!(
+        completion.messages[1]
+            .string_contents()
+            .contains("Summary of message 1")
+    );
+    assert_eq!(
+        completion.messages[2],
+        LanguageModelRequestMessage {
+            role: Role::User,
+            content: vec!["Message 2".into()],
+            cache: true,
+            reasoning_details: None,
+        }
+    );
+
+    // Usage reported before compacting doesn't trigger another compaction.
+    fake_model.send_last_completion_stream_text_chunk("Response 2");
+    fake_model.end_last_completion_stream();
+    send.collect::<Vec<_>>().await;
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Message 3"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    assert_eq!(summary_model.pending_completions(), Vec::new());
+    assert_eq!(fake_model.pending_completions().len(), 1);
+}
+
+#[gpui::test]
+async fn test_compaction_leaves_out_turns_that_dont_fit(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    // The first message alone takes up more of the summarization model's
+    // context window than a compaction request can.
+    let long_message = "a".repeat(model.max_token_count() as usize * 4);
+    for (message, response) in [
+        (long_message.as_str(), "Response 1"),
+        ("Message 2", "Response 2"),
+        ("Message 3", "Response 3"),
+    ] {
+        let send = thread
+            .update(cx, |thread, cx| {
+                thread.send(UserMessageId::new(), [message], cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        fake_model.send_last_completion_stream_text_chunk(response);
+        fake_model.end_last_completion_stream();
+        send.collect::<Vec<_>>().await;
+    }
+
+    let compact = thread.update(cx, |thread, cx| thread.compact(cx));
+    cx.run_until_parked();
+    let contents = fake_model
+        .pending_completions()
+        .pop()
+        .unwrap()
+        .messages
+        .iter()
+        .map(|message| message.string_contents())
+        .collect::<Vec<_>>();
+    assert_eq!(contents.len(), 4, "{contents:?}");
+    assert!(
+        contents[0].starts_with("[The oldest messages"),
+        "{}",
+        contents[0]
+    );
+    assert_eq!(
+        contents[1..],
+        [
+            "Message 2".to_string(),
+            "Response 2".to_string(),
+            agent_settings::COMPACT_THREAD_PROMPT.to_string(),
+        ]
+    );
+
+    // Failures are kept on the thread, so that they can be shown.
+    fake_model.send_last_completion_stream_error(anyhow::anyhow!("overloaded"));
+    fake_model.end_last_completion_stream();
+    assert!(compact.await.is_err());
+    thread.update(cx, |thread, cx| {
+        assert!(thread.compaction().is_none());
+        let error = thread.compaction_error().unwrap();
+        assert!(error.starts_with("Failed to compact the thread"), "{error}");
+        assert!(error.contains("overloaded"), "{error}");
+        thread.dismiss_compaction_error(cx);
+        assert_eq!(thread.compaction_error(), None);
+    });
+}
+
+#[gpui::test]
+async fn test_compaction_fails_when_no_turns_fit(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    let long_message = "a".repeat(model.max_token_count() as usize * 4);
+    for (message, response) in [
+        (long_message.as_str(), "Response 1"),
+        ("Message 2", "Response 2"),
+    ] {
+        let send = thread
+            .update(cx, |thread, cx| {
+                thread.send(UserMessageId::new(), [message], cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        fake_model.send_last_completion_stream_text_chunk(response);
+        fake_model.end_last_completion_stream();
+        send.collect::<Vec<_>>().await;
+    }
+
+    // The only turn to compact doesn't fit, so nothing is summarized.
+    let compact = thread.update(cx, |thread, cx| thread.compact(cx));
+    cx.run_until_parked();
+    assert_eq!(fake_model.pending_completions(), Vec::new());
+    assert!(compact.await.is_err());
+    thread.read_with(cx, |thread, _| {
+        assert!(thread.compaction().is_none());
+        assert!(!thread.is_compacting());
+        let error = thread.compaction_error().unwrap();
+        assert!(error.contains("fit in the summarization model"), "{error}");
+    });
+}
+
+#[gpui::test]
+async fn test_compaction_preserves_plan(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    thread.update(cx, |thread, _cx| thread.add_tool(UpdatePlanTool));
+    let fake_model = model.as_fake();
+
+    let send = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Make a plan"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    let input = json!({
+        "plan": [
+            {
+                "step": "Run tests",
+                "status": "pending",
+            }
+        ]
+    });
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "plan_1".into(),
+            name: UpdatePlanTool::NAME.into(),
+            raw_input: input.to_string(),
+            input,
+            is_input_complete: true,
+            thought_signature: None,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Planned");
+    fake_model.end_last_completion_stream();
+    send.collect::<Vec<_>>().await;
+
+    let message_2_id = UserMessageId::new();
+    let send = thread
+        .update(cx, |thread, cx| {
+            thread.send(message_2_id.clone(), ["Message 2"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Response 2");
+    fake_model.end_last_completion_stream();
+    send.collect::<Vec<_>>().await;
+
+    // Without a summarization model, the thread's own model is used.
+    let compact = thread.update(cx, |thread, cx| thread.compact(cx));
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_text_chunk("Summary");
+    fake_model.end_last_completion_stream();
+    compact.await.unwrap();
+
+    thread.read_with(cx, |thread, _| {
+        let compaction = thread.compaction().unwrap();
+        assert_eq!(compaction.first_kept_message_id, message_2_id);
+        assert_eq!(
+            compaction.preserved_tool_use_ids,
+            vec![LanguageModelToolUseId::from("plan_1")]
+        );
+        assert!(!thread.can_compact());
+    });
+
+    thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Message 3"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    let completion = fake_model.pending_completions().pop().unwrap();
+    assert_eq!(
+        completion.messages[1..]
+            .iter()
+            .map(|message| message.role)
+            .collect::<Vec<_>>(),
+        vec![
+            Role::User,
+            Role::Assistant,
+            Role::User,
+            Role::User,
+            Role::Assistant,
+            Role::User,
+        ]
+    );
+    assert!(completion.messages[1].string_contents().contains("Summary"));
+    assert!(matches!(
+        &completion.messages[2].content[..],
+        [MessageContent::ToolUse(tool_use)] if tool_use.id.to_string() == "plan_1"
+    ));
+    assert!(matches!(
+        &completion.messages[3].content[..],
+        [MessageContent::ToolResult(tool_result)] if tool_result.tool_use_id.to_string() == "plan_1"
+    ));
+    assert_eq!(completion.messages[4].string_contents(), "Message 2");
+    assert_eq!(completion.messages[6].string_contents(), "Message 3");
+}
+
+#[gpui::test]
+async fn test_building_request_with_pending_tools(cx: &mut TestAppContext) {
+    let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
+    let fake_model = model.as_fake();
+
+    let _events = thread
+        .update(cx, |thread, cx| {
+            thread.add_tool(ToolRequiringPermission);
+            thread.add_tool(EchoTool);
+            thread.send(UserMessageId::new(), ["Hey!"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+
+    let permission_tool_use = LanguageModelToolUse {
+        id: "tool_id_1".into(),
+        name: ToolRequiringPermission::NAME.into(),
+        raw_input: "{}".into(),
+        input: json!({}),
+        is_input_complete: true,
+        thought_signature: None,
+    };
+    let echo_tool_use = LanguageModelToolUse {
+        id: "tool_id_2".into(),
+        name: EchoTool::NAME.into(),
+        raw_input: json!({"text": "test"}).to_string(),
+        input: json!({"text": "test"}),
+        is_input_complete: true,
+        thought_signature: None,
+    };
+    fake_model.send_last_completion_stream_text_chunk("Hi!");
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        permission_tool_use,
+    ));
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        echo_tool_use.clone(),
+    ));
+    fake_model.end_last_completion_stream();
+    cx.run_until_parked();
+
+    // Ensure pending tools are skipped when building a request.
+    let request = thread
+        .read_with(cx, |thread, cx| {
+            thread.build_completion_request(CompletionIntent::EditFile, cx)
+        })
+        .unwrap();
+    assert_eq!(
+        request.messages[1..],
+        vec![
+            LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec!["Hey!".into()],
                 cache: true,
                 reasoning_details: None,
             },
@@ -3902,6 +4515,145 @@ async fn test_streaming_tool_completes_when_llm_stream_ends_without_final_input(
     });
 }
 
+#[gpui::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn test_pre_tool_call_hook_vetoes_tool_call(cx: &mut TestAppContext) {
+    let (thread, model, _dir) = setup_hooks(
+        cx,
+        agent_settings::AgentHooks {
+            pre_tool_call: vec![agent_settings::AgentHook {
+                command: "echo 'echoing is not allowed'; exit 1".into(),
+                tools: Some(vec![EchoTool::NAME.into()]),
+                timeout: agent_settings::AgentHook::DEFAULT_TIMEOUT,
+            }],
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let result = run_echo_tool_call(&thread, model.as_fake(), "hello", cx).await;
+    assert!(result.is_error);
+    let language_model::LanguageModelToolResultContent::Text(text) = &result.content else {
+        panic!("expected a text result, got {:?}", result.content);
+    };
+    assert!(text.starts_with("Blocked by the `echo"), "{text}");
+    assert!(text.contains("echoing is not allowed"), "{text}");
+}
+
+#[gpui::test]
+#[cfg_attr(target_os = "windows", ignore)]
+async fn test_pre_tool_call_hook_replaces_tool_input(cx: &mut TestAppContext) {
+    let (thread, model, _dir) = setup_hooks(
+        cx,
+        agent_settings::AgentHooks {
+            pre_tool_call: vec![agent_settings::AgentHook {
+                command: r#"echo '{"tool_input": {"text": "replaced"}}'"#.into(),
+                tools: None,
+                timeout: agent_settings::AgentHook::DEFAULT_TIMEOUT,
+            }],
+            ..Default::default()
+        },
+    )
+    .await;
+
+    let result = run_echo_tool_call(&thread, model.as_fake(), "hello", cx).await;
+    assert!(!result.is_error);
+    let language_model::LanguageModelToolResultContent::Text(text) = &result.content else {
+        panic!("expected a text result, got {:?}", result.content);
+    };
+    assert!(text.starts_with("replaced\n\n"), "{text}");
+    assert!(
+        text.contains("changed the input of this tool call"),
+        "{text}"
+    );
+    assert!(!text.contains("hello"), "{text}");
+}
+
+/// Sets up a thread with the given hooks, in a project rooted in a real
+/// directory, since hooks are commands run in the project's root.
+async fn setup_hooks(
+    cx: &mut TestAppContext,
+    hooks: agent_settings::AgentHooks,
+) -> (Entity<Thread>, Arc<dyn LanguageModel>, tempfile::TempDir) {
+    let ThreadTest { model, fs, .. } = setup(cx, TestModel::Fake).await;
+    cx.update(|cx| {
+        let mut settings = agent_settings::AgentSettings::get_global(cx).clone();
+        settings.hooks = hooks;
+        agent_settings::AgentSettings::override_global(settings, cx);
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    fs.insert_tree(dir.path(), json!({})).await;
+    let project = Project::test(fs, [dir.path()], cx).await;
+    let context_server_registry =
+        cx.new(|cx| ContextServerRegistry::new(project.read(cx).context_server_store(), cx));
+    let thread = cx.new(|cx| {
+        Thread::new(
+            project,
+            cx.new(|_cx| ProjectContext::default()),
+            context_server_registry,
+            Templates::new(),
+            Some(model.clone()),
+            cx,
+        )
+    });
+    thread.update(cx, |thread, _| thread.add_tool(EchoTool));
+    (thread, model, dir)
+}
+
+/// Has the model call the echo tool, returning the result sent back to it.
+async fn run_echo_tool_call(
+    thread: &Entity<Thread>,
+    fake_model: &FakeLanguageModel,
+    text: &str,
+    cx: &mut TestAppContext,
+) -> LanguageModelToolResult {
+    let mut events = thread
+        .update(cx, |thread, cx| {
+            thread.send(UserMessageId::new(), ["Use the echo tool"], cx)
+        })
+        .unwrap();
+    cx.run_until_parked();
+    fake_model.send_last_completion_stream_event(LanguageModelCompletionEvent::ToolUse(
+        LanguageModelToolUse {
+            id: "tool_1".into(),
+            name: EchoTool::NAME.into(),
+            raw_input: json!({ "text": text }).to_string(),
+            input: json!({ "text": text }),
+            is_input_complete: true,
+            thought_signature: None,
+        },
+    ));
+    fake_model.end_last_completion_stream();
+
+    // The hooks are real processes, so wait for the tool call to finish.
+    while let Some(event) = events.next().await {
+        if let Ok(ThreadEvent::ToolCallUpdate(acp_thread::ToolCallUpdate::UpdateFields(update))) =
+            event
+            && matches!(
+                update.fields.status,
+                Some(acp::ToolCallStatus::Completed | acp::ToolCallStatus::Failed)
+            )
+        {
+            break;
+        }
+    }
+    cx.run_until_parked();
+
+    let completion = fake_model.pending_completions().pop().unwrap();
+    completion
+        .messages
+        .last()
+        .unwrap()
+        .content
+        .iter()
+        .find_map(|content| match content {
+            MessageContent::ToolResult(result) => Some(result.clone()),
+            _ => None,
+        })
+        .expect("the tool result should be sent to the model")
+}
+
 /// Filters out the stop events for asserting against in tests
 fn stop_events(result_events: Vec<Result<ThreadEvent>>) -> Vec<acp::StopReason> {
     result_events
@@ -4161,6 +4913,109 @@ fn setup_context_server(
     mcp_tool_calls_rx
 }
 
+#[gpui::test]
+async fn test_resource_watches_unsubscribe_when_last_one_drops(cx: &mut TestAppContext) {
+    let ThreadTest {
+        context_server_store,
+        ..
+    } = setup(cx, TestModel::Fake).await;
+    let name = "resource_server";
+    cx.update(|cx| {
+        let mut settings = ProjectSettings::get_global(cx).clone();
+        settings.context_servers.insert(
+            name.into(),
+            project::project_settings::ContextServerSettings::Stdio {
+                enabled: true,
+                remote: false,
+                command: ContextServerCommand {
+                    path: "somebinary".into(),
+                    args: Vec::new(),
+                    env: None,
+                    timeout: None,
+                },
+            },
+        );
+        ProjectSettings::override_global(settings, cx);
+    });
+
+    let (requests_tx, mut requests_rx) = mpsc::unbounded();
+    let fake_transport = context_server::test::create_fake_transport(name, cx.executor())
+        .on_request::<context_server::types::requests::Initialize, _>(move |_params| async move {
+            context_server::types::InitializeResponse {
+                protocol_version: context_server::types::ProtocolVersion(
+                    context_server::types::LATEST_PROTOCOL_VERSION.to_string(),
+                ),
+                server_info: context_server::types::Implementation {
+                    name: name.into(),
+                    version: "1.0.0".to_string(),
+                },
+                capabilities: context_server::types::ServerCapabilities {
+                    resources: Some(context_server::types::ResourcesCapabilities {
+                        subscribe: Some(true),
+                        list_changed: None,
+                    }),
+                    ..Default::default()
+                },
+                meta: None,
+            }
+        })
+        .on_request::<context_server::types::requests::ResourcesSubscribe, _>({
+            let requests_tx = requests_tx.clone();
+            move |params| {
+                requests_tx
+                    .unbounded_send(format!("subscribe {}", params.uri))
+                    .unwrap();
+                async move { context_server::types::EmptyResult::default() }
+            }
+        })
+        .on_request::<context_server::types::requests::ResourcesUnsubscribe, _>(move |params| {
+            requests_tx
+                .unbounded_send(format!("unsubscribe {}", params.uri))
+                .unwrap();
+            async move { context_server::types::EmptyResult::default() }
+        });
+    context_server_store.update(cx, |store, cx| {
+        store.start_server(
+            Arc::new(ContextServer::new(
+                ContextServerId(name.into()),
+                Arc::new(fake_transport),
+            )),
+            cx,
+        );
+    });
+    cx.run_until_parked();
+
+    let registry = cx.new(|cx| ContextServerRegistry::new(context_server_store.clone(), cx));
+    let watch = |cx: &mut TestAppContext| {
+        registry.update(cx, |registry, cx| {
+            registry.watch_resource(
+                ContextServerId(name.into()),
+                "file:///notes.md".to_string(),
+                cx,
+            )
+        })
+    };
+    let first_watch = watch(cx);
+    let second_watch = watch(cx);
+    cx.run_until_parked();
+    assert_eq!(
+        requests_rx.try_next().unwrap(),
+        Some("subscribe file:///notes.md".to_string())
+    );
+    assert!(requests_rx.try_next().is_err());
+
+    drop(first_watch);
+    cx.run_until_parked();
+    assert!(requests_rx.try_next().is_err());
+
+    drop(second_watch);
+    cx.run_until_parked();
+    assert_eq!(
+        requests_rx.try_next().unwrap(),
+        Some("unsubscribe file:///notes.md".to_string())
+    );
+}
+
 #[gpui::test]
 async fn test_tokens_before_message(cx: &mut TestAppContext) {
     let ThreadTest { model, thread, .. } = setup(cx, TestModel::Fake).await;
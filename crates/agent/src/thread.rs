@@ -1,10 +1,14 @@
 use crate::{
-    ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DbLanguageModel, DbThread,
-    DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool, FindPathTool, GrepTool,
-    ListDirectoryTool, MovePathTool, NowTool, OpenTool, ProjectSnapshot, ReadFileTool,
-    RestoreFileFromDiskTool, SaveFileTool, SpawnAgentTool, StreamingEditFileTool,
+    BudgetContext, ContextServerRegistry, CopyPathTool, CreateDirectoryTool, DbLanguageModel,
+    DbThread, DbUsageRecord, DeletePathTool, DiagnosticsTool, EditFileTool, FetchTool,
+    FindPathTool, FindReferencesTool, GitBlameTool, GitCommitTool, GitDiffTool, GitLogTool,
+    GitStatusTool, GoToDefinitionTool, GrepTool, HoverTool, ListDirectoryTool, ListTasksTool,
+    MovePathTool, NowTool, OpenTool, ProjectSnapshot, ReadFileTool, RenameSymbolTool,
+    RestoreFileFromDiskTool, RunTaskTool, SaveFileTool, SpawnAgentTool, StreamingEditFileTool,
     SystemPromptTemplate, Template, Templates, TerminalTool, ToolPermissionDecision,
-    UpdatePlanTool, WebSearchTool, decide_permission_from_settings,
+    UpdatePlanTool, UsageLedger, WebSearchTool, WorkspaceSymbolsTool,
+    decide_permission_from_settings,
+    hooks::{HookEvent, HookRunner, ToolHooks, append_hook_reports},
 };
 use acp_thread::{MentionUri, UserMessageId};
 use action_log::ActionLog;
@@ -14,7 +18,8 @@ use feature_flags::{
 
 use agent_client_protocol as acp;
 use agent_settings::{
-    AgentProfileId, AgentSettings, SUMMARIZE_THREAD_DETAILED_PROMPT, SUMMARIZE_THREAD_PROMPT,
+    AgentProfileId, AgentSettings, COMPACT_THREAD_PROMPT, SUMMARIZE_THREAD_DETAILED_PROMPT,
+    SUMMARIZE_THREAD_PROMPT,
 };
 use anyhow::{Context as _, Result, anyhow};
 use chrono::{DateTime, Utc};
@@ -58,10 +63,24 @@ use std::{
     time::{Duration, Instant},
 };
 use std::{fmt::Write, path::PathBuf};
-use util::{ResultExt, debug_panic, markdown::MarkdownCodeBlock, paths::PathStyle};
+use task::SpawnInTerminal;
+use util::{
+    ResultExt, debug_panic, markdown::MarkdownCodeBlock, path_list::PathList, paths::PathStyle,
+};
 use uuid::Uuid;
 
 const TOOL_CANCELED_MESSAGE: &str = "Tool canceled by user";
+const COMPACTED_CONTEXT_OPEN_TAG: &str = "<compacted_context>\n\
+    The earlier part of this conversation was compacted to save space. \
+    Here is a summary of it:\n";
+const COMPACTED_CONTEXT_CLOSE_TAG: &str = "</compacted_context>";
+const OMITTED_CONTEXT_NOTE: &str = "[The oldest messages of the conversation were omitted \
+    because they don't fit in the context window.]";
+/// The share of the summarization model's context window that the messages
+/// being compacted can take up, leaving room for the prompt and the summary.
+const COMPACTION_INPUT_RATIO: f64 = 0.75;
+/// How many times `stop` hooks can send the agent back to work in a single turn.
+const MAX_STOP_HOOK_CONTINUATIONS: usize = 3;
 pub const MAX_TOOL_NAME_LENGTH: usize = 64;
 pub const MAX_SUBAGENT_DEPTH: u8 = 1;
 
@@ -75,6 +94,26 @@ pub struct SubagentContext {
     pub depth: u8,
 }
 
+/// Records that the leading messages of a thread have been replaced by a
+/// summary when building completion requests.
+///
+/// The original messages are kept in the thread so they can still be viewed,
+/// they just aren't sent to the model anymore.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ThreadCompaction {
+    /// The number of leading messages covered by the summary.
+    pub message_count: usize,
+    /// The first user message that is still sent to the model verbatim.
+    pub first_kept_message_id: UserMessageId,
+    pub summary: SharedString,
+    /// Tool calls within the compacted messages that are still relevant, such
+    /// as the latest plan and edits to files with unreviewed changes. They are
+    /// sent verbatim after the summary.
+    #[serde(default)]
+    pub preserved_tool_use_ids: Vec<LanguageModelToolUseId>,
+    pub compacted_at: DateTime<Utc>,
+}
+
 /// The ID of the user prompt that initiated a request.
 ///
 /// This equates to the user physically submitting a message to the model (e.g., by pressing the Enter key).
@@ -220,6 +259,7 @@ impl UserMessage {
         const OPEN_DIAGNOSTICS_TAG: &str = "<diagnostics>";
         const OPEN_DIFFS_TAG: &str = "<diffs>";
         const MERGE_CONFLICT_TAG: &str = "<merge_conflicts>";
+        const OPEN_RESOURCES_TAG: &str = "<mcp_resources>";
 
         let mut file_context = OPEN_FILES_TAG.to_string();
         let mut directory_context = OPEN_DIRECTORIES_TAG.to_string();
@@ -231,6 +271,7 @@ impl UserMessage {
         let mut diagnostics_context = OPEN_DIAGNOSTICS_TAG.to_string();
         let mut diffs_context = OPEN_DIFFS_TAG.to_string();
         let mut merge_conflict_context = MERGE_CONFLICT_TAG.to_string();
+        let mut resource_context = OPEN_RESOURCES_TAG.to_string();
 
         for chunk in &self.content {
             let chunk = match chunk {
@@ -347,6 +388,19 @@ impl UserMessage {
                             )
                             .ok();
                         }
+                        MentionUri::McpResource { server_id, uri, .. } => {
+                            write!(
+                                &mut resource_context,
+                                "\nResource {} from {}:\n{}",
+                                uri,
+                                server_id,
+                                MarkdownCodeBlock {
+                                    tag: "",
+                                    text: content
+                                }
+                            )
+                            .ok();
+                        }
                     }
 
                     language_model::MessageContent::Text(uri.as_link().to_string())
@@ -428,6 +482,13 @@ impl UserMessage {
                 .push(language_model::MessageContent::Text(merge_conflict_context));
         }
 
+        if resource_context.len() > OPEN_RESOURCES_TAG.len() {
+            resource_context.push_str("</mcp_resources>\n");
+            message
+                .content
+                .push(language_model::MessageContent::Text(resource_context));
+        }
+
         if message.content.len() > len_before_context {
             message.content.insert(
                 len_before_context,
@@ -639,6 +700,14 @@ pub trait ThreadEnvironment {
         cx: &mut AsyncApp,
     ) -> Task<Result<Rc<dyn TerminalHandle>>>;
 
+    /// Creates a terminal running a resolved task, with the task's arguments and environment.
+    fn create_task_terminal(
+        &self,
+        task: SpawnInTerminal,
+        output_byte_limit: Option<u64>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<Rc<dyn TerminalHandle>>>;
+
     fn create_subagent(&self, label: String, cx: &mut App) -> Result<Rc<dyn SubagentHandle>>;
 
     fn resume_subagent(
@@ -811,6 +880,7 @@ impl ToolPermissionContext {
                 || tool_name == DeletePathTool::NAME
                 || tool_name == CreateDirectoryTool::NAME
                 || tool_name == SaveFileTool::NAME
+                || tool_name == RenameSymbolTool::NAME
             {
                 (
                     extract_path_pattern(value),
@@ -931,6 +1001,14 @@ pub struct Thread {
     pending_summary_generation: Option<Shared<Task<Option<SharedString>>>>,
     summary: Option<SharedString>,
     messages: Vec<Message>,
+    compaction: Option<ThreadCompaction>,
+    compacting: bool,
+    /// Why the latest compaction failed, until it's dismissed or the thread
+    /// is compacted.
+    compaction_error: Option<SharedString>,
+    /// Set after compacting until the next request reports its token usage,
+    /// so that stale usage doesn't immediately trigger another compaction.
+    compacted_since_last_request: bool,
     user_store: Entity<UserStore>,
     /// Holds the task that handles agent interaction until the end of the turn.
     /// Survives across multiple requests as the model performs tool calls and
@@ -942,8 +1020,10 @@ pub struct Thread {
     pending_message: Option<AgentMessage>,
     pub(crate) tools: BTreeMap<SharedString, Arc<dyn AnyAgentTool>>,
     request_token_usage: HashMap<UserMessageId, language_model::TokenUsage>,
-    #[allow(unused)]
     cumulative_token_usage: TokenUsage,
+    /// The usage reported so far by the completion request in flight, which
+    /// is recorded in the usage ledger when the request ends.
+    current_request_usage: Option<TokenUsage>,
     #[allow(unused)]
     initial_project_snapshot: Shared<Task<Option<Arc<ProjectSnapshot>>>>,
     pub(crate) context_server_registry: Entity<ContextServerRegistry>,
@@ -963,6 +1043,8 @@ pub struct Thread {
     imported: bool,
     /// If this is a subagent thread, contains context about the parent
     subagent_context: Option<SubagentContext>,
+    /// The thread this one was forked from, if any.
+    forked_from: Option<acp::SessionId>,
     /// The user's unsent prompt text, persisted so it can be restored when reloading the thread.
     draft_prompt: Option<Vec<acp::ContentBlock>>,
     ui_scroll_position: Option<gpui::ListOffset>,
@@ -1052,6 +1134,10 @@ impl Thread {
             pending_summary_generation: None,
             summary: None,
             messages: Vec::new(),
+            compaction: None,
+            compacting: false,
+            compaction_error: None,
+            compacted_since_last_request: false,
             user_store: project.read(cx).user_store(),
             running_turn: None,
             has_queued_message: false,
@@ -1059,6 +1145,7 @@ impl Thread {
             tools: BTreeMap::default(),
             request_token_usage: HashMap::default(),
             cumulative_token_usage: TokenUsage::default(),
+            current_request_usage: None,
             initial_project_snapshot: {
                 let project_snapshot = Self::project_snapshot(project.clone(), cx);
                 cx.foreground_executor()
@@ -1080,6 +1167,7 @@ impl Thread {
             action_log,
             imported: false,
             subagent_context: None,
+            forked_from: None,
             draft_prompt: None,
             ui_scroll_position: None,
             running_subagents: Vec::new(),
@@ -1272,6 +1360,10 @@ impl Thread {
             pending_summary_generation: None,
             summary: db_thread.detailed_summary,
             messages: db_thread.messages,
+            compaction: db_thread.compaction,
+            compacting: false,
+            compaction_error: None,
+            compacted_since_last_request: false,
             user_store: project.read(cx).user_store(),
             running_turn: None,
             has_queued_message: false,
@@ -1279,6 +1371,7 @@ impl Thread {
             tools: BTreeMap::default(),
             request_token_usage: db_thread.request_token_usage.clone(),
             cumulative_token_usage: db_thread.cumulative_token_usage,
+            current_request_usage: None,
             initial_project_snapshot: Task::ready(db_thread.initial_project_snapshot).shared(),
             context_server_registry,
             profile_id,
@@ -1296,6 +1389,7 @@ impl Thread {
             prompt_capabilities_rx,
             imported: db_thread.imported,
             subagent_context: db_thread.subagent_context,
+            forked_from: db_thread.forked_from,
             draft_prompt: db_thread.draft_prompt,
             ui_scroll_position: db_thread.ui_scroll_position.map(|sp| gpui::ListOffset {
                 item_ix: sp.item_ix,
@@ -1310,6 +1404,7 @@ impl Thread {
         let mut thread = DbThread {
             title: self.title().unwrap_or_default(),
             messages: self.messages.clone(),
+            compaction: self.compaction.clone(),
             updated_at: self.updated_at,
             detailed_summary: self.summary.clone(),
             initial_project_snapshot: None,
@@ -1322,6 +1417,7 @@ impl Thread {
             profile: Some(self.profile_id.clone()),
             imported: self.imported,
             subagent_context: self.subagent_context.clone(),
+            forked_from: self.forked_from.clone(),
             speed: self.speed,
             thinking_enabled: self.thinking_enabled,
             thinking_effort: self.thinking_effort.clone(),
@@ -1341,6 +1437,65 @@ impl Thread {
         })
     }
 
+    /// Builds a new thread that shares this thread's history up to the given
+    /// user message. The message itself becomes the fork's draft prompt, so it
+    /// can be edited before being sent on the new branch.
+    pub fn to_forked_db(&self, message_id: &UserMessageId, cx: &App) -> Result<Task<DbThread>> {
+        let Some((position, message)) =
+            self.messages
+                .iter()
+                .enumerate()
+                .find_map(|(ix, message)| match message {
+                    Message::User(message) if &message.id == message_id => Some((ix, message)),
+                    _ => None,
+                })
+        else {
+            return Err(anyhow!("Message not found"));
+        };
+
+        let draft_prompt = message
+            .content
+            .iter()
+            .cloned()
+            .map(acp::ContentBlock::from)
+            .collect::<Vec<_>>();
+        let messages = self.messages[..position].to_vec();
+        let request_token_usage = messages
+            .iter()
+            .filter_map(|message| match message {
+                Message::User(message) => self
+                    .request_token_usage
+                    .get(&message.id)
+                    .map(|usage| (message.id.clone(), *usage)),
+                Message::Agent(_) | Message::Resume => None,
+            })
+            .collect();
+        let compaction = self
+            .compaction
+            .clone()
+            .filter(|compaction| position > compaction.message_count);
+        let title = self
+            .title()
+            .map(|title| SharedString::from(format!("{title} (Fork)")))
+            .unwrap_or_default();
+
+        let db_thread = self.to_db(cx);
+        let forked_from = self.id.clone();
+        Ok(cx.background_spawn(async move {
+            let mut thread = db_thread.await;
+            thread.title = title;
+            thread.messages = messages;
+            thread.compaction = compaction;
+            thread.detailed_summary = None;
+            thread.request_token_usage = request_token_usage;
+            thread.forked_from = Some(forked_from);
+            thread.draft_prompt = (!draft_prompt.is_empty()).then_some(draft_prompt);
+            thread.ui_scroll_position = None;
+            thread.updated_at = Utc::now();
+            thread
+        }))
+    }
+
     /// Create a snapshot of the current project state including git information and unsaved buffers.
     fn project_snapshot(
         project: Entity<Project>,
@@ -1373,6 +1528,10 @@ impl Thread {
         self.messages.is_empty() && self.title.is_none()
     }
 
+    pub fn forked_from(&self) -> Option<&acp::SessionId> {
+        self.forked_from.as_ref()
+    }
+
     pub fn draft_prompt(&self) -> Option<&[acp::ContentBlock]> {
         self.draft_prompt.as_deref()
     }
@@ -1523,8 +1682,17 @@ impl Thread {
         ));
         self.add_tool(FetchTool::new(self.project.read(cx).client().http_client()));
         self.add_tool(FindPathTool::new(self.project.clone()));
+        self.add_tool(FindReferencesTool::new(self.project.clone()));
+        self.add_tool(GitBlameTool::new(self.project.clone()));
+        self.add_tool(GitCommitTool::new(self.project.clone()));
+        self.add_tool(GitDiffTool::new(self.project.clone()));
+        self.add_tool(GitLogTool::new(self.project.clone()));
+        self.add_tool(GitStatusTool::new(self.project.clone()));
+        self.add_tool(GoToDefinitionTool::new(self.project.clone()));
         self.add_tool(GrepTool::new(self.project.clone()));
+        self.add_tool(HoverTool::new(self.project.clone()));
         self.add_tool(ListDirectoryTool::new(self.project.clone()));
+        self.add_tool(ListTasksTool::new(self.project.clone()));
         self.add_tool(MovePathTool::new(self.project.clone()));
         self.add_tool(NowTool);
         self.add_tool(OpenTool::new(self.project.clone()));
@@ -1536,10 +1704,16 @@ impl Thread {
             self.action_log.clone(),
             update_agent_location,
         ));
+        self.add_tool(RenameSymbolTool::new(
+            self.project.clone(),
+            self.action_log.clone(),
+        ));
         self.add_tool(SaveFileTool::new(self.project.clone()));
         self.add_tool(RestoreFileFromDiskTool::new(self.project.clone()));
+        self.add_tool(RunTaskTool::new(self.project.clone(), environment.clone()));
         self.add_tool(TerminalTool::new(self.project.clone(), environment.clone()));
         self.add_tool(WebSearchTool);
+        self.add_tool(WorkspaceSymbolsTool::new(self.project.clone()));
 
         if self.depth() < MAX_SUBAGENT_DEPTH {
             self.add_tool(SpawnAgentTool::new(environment));
@@ -1621,10 +1795,81 @@ impl Thread {
 
         self.request_token_usage
             .insert(last_user_message.id.clone(), update);
+        self.compacted_since_last_request = false;
         cx.emit(TokenUsageUpdated(self.latest_token_usage()));
         cx.notify();
     }
 
+    /// The thread that usage is charged to. Subagents' usage is charged to the
+    /// thread that started them.
+    fn usage_session_id(&self) -> acp::SessionId {
+        self.parent_thread_id().unwrap_or_else(|| self.id.clone())
+    }
+
+    fn usage_folder_paths(&self, cx: &App) -> PathList {
+        PathList::new(
+            &self
+                .project
+                .read(cx)
+                .visible_worktrees(cx)
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    fn check_usage_budgets(
+        &self,
+        model: &Arc<dyn LanguageModel>,
+        cx: &mut Context<Self>,
+    ) -> Option<Task<Result<()>>> {
+        let ledger = UsageLedger::try_global(cx)?;
+        let context = BudgetContext {
+            session_id: self.usage_session_id(),
+            folder_paths: self.usage_folder_paths(cx),
+            provider: model.provider_id().0,
+            model: model.id().0,
+        };
+        Some(ledger.update(cx, |ledger, cx| ledger.check_budgets(context, cx)))
+    }
+
+    fn record_turn_request_usage(
+        &mut self,
+        model: &Arc<dyn LanguageModel>,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(usage) = self.current_request_usage.take() else {
+            return;
+        };
+        self.cumulative_token_usage = self.cumulative_token_usage + usage;
+        self.record_request_usage(model, usage, cx);
+    }
+
+    /// Records the usage of a request in the usage ledger. Every request made
+    /// on the thread's behalf goes through here, including the ones that
+    /// generate its title and summaries, so that they count towards budgets.
+    fn record_request_usage(
+        &mut self,
+        model: &Arc<dyn LanguageModel>,
+        usage: TokenUsage,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(ledger) = UsageLedger::try_global(cx) else {
+            return;
+        };
+        let record = DbUsageRecord {
+            session_id: self.usage_session_id(),
+            thread_title: None,
+            folder_paths: self.usage_folder_paths(cx),
+            provider: model.provider_id().0,
+            model: model.id().0,
+            usage,
+            recorded_at: Utc::now(),
+        };
+        ledger
+            .update(cx, |ledger, cx| ledger.record(record, cx))
+            .detach_and_log_err(cx);
+    }
+
     pub fn truncate(&mut self, message_id: UserMessageId, cx: &mut Context<Self>) -> Result<()> {
         self.cancel(cx).detach();
         // Clear pending message since cancel will try to flush it asynchronously,
@@ -1644,6 +1889,13 @@ impl Thread {
                 Message::Agent(_) | Message::Resume => {}
             }
         }
+        if self
+            .compaction
+            .as_ref()
+            .is_some_and(|compaction| position <= compaction.message_count)
+        {
+            self.compaction = None;
+        }
         self.clear_summary();
         cx.notify();
         Ok(())
@@ -1872,9 +2124,25 @@ impl Thread {
         mut cancellation_rx: watch::Receiver<bool>,
         cx: &mut AsyncApp,
     ) -> Result<()> {
+        Self::run_prompt_submit_hooks(this, cx).await?;
+
         let mut attempt = 0;
         let mut intent = CompletionIntent::UserPrompt;
+        let mut stop_hook_continuations = 0;
         loop {
+            // Compact older turns before the request would overflow the context window.
+            let compaction = this.update(cx, |this, cx| this.auto_compact(cx))?;
+            if let Some(compaction) = compaction {
+                compaction
+                    .await
+                    .context("failed to compact thread")
+                    .log_err();
+                if *cancellation_rx.borrow() {
+                    log::debug!("Turn cancelled during compaction, exiting");
+                    return Ok(());
+                }
+            }
+
             // Re-read the model and refresh tools on each iteration so that
             // mid-turn changes (e.g. the user switches model, toggles tools,
             // or changes profile) take effect between tool-call rounds.
@@ -1885,6 +2153,12 @@ impl Thread {
                 anyhow::Ok((model, request))
             })??;
 
+            // Stop before the request if it would exceed a hard budget limit.
+            let budget_check = this.update(cx, |this, cx| this.check_usage_budgets(&model, cx))?;
+            if let Some(budget_check) = budget_check {
+                budget_check.await?;
+            }
+
             telemetry::event!(
                 "Agent Thread Completion",
                 thread_id = this.read_with(cx, |this, _| this.id.to_string())?,
@@ -1999,6 +2273,7 @@ impl Thread {
             // tool execution, which could cause deadlocks when tools spawn subagents
             // that need their own permits.
             drop(events);
+            this.update(cx, |this, cx| this.record_turn_request_usage(&model, cx))?;
 
             // Drop streaming tool input senders that never received their final input.
             // This prevents deadlock when the LLM stream ends (e.g. because of an error)
@@ -2060,7 +2335,24 @@ impl Thread {
                     }
                 })?;
             } else if end_turn {
-                return Ok(());
+                let feedback = Self::run_stop_hooks(this, cx).await?;
+                match feedback {
+                    Some(feedback) if stop_hook_continuations < MAX_STOP_HOOK_CONTINUATIONS => {
+                        stop_hook_continuations += 1;
+                        this.update(cx, |this, cx| {
+                            let message = UserMessage {
+                                id: UserMessageId::new(),
+                                content: vec![UserMessageContent::Text(feedback)],
+                            };
+                            event_stream.send_user_message(&message);
+                            this.messages.push(Message::User(message));
+                            cx.notify();
+                        })?;
+                        intent = CompletionIntent::UserPrompt;
+                        attempt = 0;
+                    }
+                    _ => return Ok(()),
+                }
             } else {
                 let has_queued = this.update(cx, |this, _| this.has_queued_message())?;
                 if has_queued {
@@ -2073,6 +2365,120 @@ impl Thread {
         }
     }
 
+    /// Runs the `user_prompt_submit` hooks for the message that started the
+    /// turn. A hook that fails removes the message from the thread, and the
+    /// output of the others is added to it.
+    async fn run_prompt_submit_hooks(this: &WeakEntity<Self>, cx: &mut AsyncApp) -> Result<()> {
+        let Some((runner, hooks, message_ix, prompt)) = this.read_with(cx, |this, cx| {
+            let hooks = AgentSettings::get_global(cx)
+                .hooks
+                .user_prompt_submit
+                .clone();
+            if hooks.is_empty() {
+                return None;
+            }
+            let message_ix = this.messages.len().checked_sub(1)?;
+            let Message::User(message) = &this.messages[message_ix] else {
+                return None;
+            };
+            Some((
+                this.hook_runner(cx),
+                hooks,
+                message_ix,
+                message.to_markdown(),
+            ))
+        })?
+        else {
+            return Ok(());
+        };
+        let runner = match runner {
+            Ok(runner) => runner,
+            Err(error) => {
+                this.update(cx, |this, cx| {
+                    this.messages.truncate(message_ix);
+                    cx.notify();
+                })?;
+                return Err(error);
+            }
+        };
+
+        let mut reports = Vec::new();
+        for hook in &hooks {
+            let run = runner
+                .run(
+                    hook,
+                    HookEvent::UserPromptSubmit,
+                    serde_json::json!({ "prompt": prompt }),
+                    cx,
+                )
+                .await;
+            if !run.success {
+                this.update(cx, |this, cx| {
+                    this.messages.truncate(message_ix);
+                    cx.notify();
+                })?;
+                return Err(anyhow!(run.rejection()));
+            }
+            reports.extend(run.report());
+        }
+
+        if !reports.is_empty() {
+            this.update(cx, |this, _cx| {
+                if let Some(Message::User(message)) = this.messages.get_mut(message_ix) {
+                    message
+                        .content
+                        .push(UserMessageContent::Text(reports.join("\n\n")));
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Runs the `stop` hooks when the model ends its turn, returning the
+    /// message to continue the turn with if any of them failed.
+    async fn run_stop_hooks(this: &WeakEntity<Self>, cx: &mut AsyncApp) -> Result<Option<String>> {
+        let Some((runner, hooks, last_message)) = this.read_with(cx, |this, cx| {
+            let hooks = AgentSettings::get_global(cx).hooks.stop.clone();
+            if hooks.is_empty() {
+                return None;
+            }
+            let last_message = this
+                .messages
+                .iter()
+                .rev()
+                .find_map(|message| match message {
+                    Message::Agent(message) => Some(message.to_markdown()),
+                    Message::User(_) | Message::Resume => None,
+                })
+                .unwrap_or_default();
+            Some((this.hook_runner(cx), hooks, last_message))
+        })?
+        else {
+            return Ok(None);
+        };
+        let runner = runner?;
+
+        let mut failures = Vec::new();
+        for hook in &hooks {
+            let run = runner
+                .run(
+                    hook,
+                    HookEvent::Stop,
+                    serde_json::json!({ "last_agent_message": last_message }),
+                    cx,
+                )
+                .await;
+            if !run.success {
+                failures.extend(run.report());
+            }
+        }
+        Ok((!failures.is_empty()).then(|| failures.join("\n\n")))
+    }
+
+    fn hook_runner(&self, cx: &App) -> Result<HookRunner> {
+        HookRunner::new(&self.project, self.id.clone(), cx)
+    }
+
     fn process_tool_result(
         this: &WeakEntity<Thread>,
         event_stream: &ThreadEventStream,
@@ -2218,6 +2624,7 @@ impl Thread {
                     cache_creation_input_tokens = usage.cache_creation_input_tokens,
                     cache_read_input_tokens = usage.cache_read_input_tokens,
                 );
+                self.current_request_usage = Some(usage);
                 self.update_token_usage(usage, cx);
             }
             Stop(StopReason::Refusal) => return Err(CompletionError::Refusal.into()),
@@ -2375,7 +2782,25 @@ impl Thread {
             acp::ToolCallUpdateFields::new().status(acp::ToolCallStatus::InProgress),
         );
         let supports_images = self.model().is_some_and(|model| model.supports_images());
-        let tool_result = tool.run(tool_input, tool_event_stream, cx);
+        let tool_hooks = ToolHooks::new(&AgentSettings::get_global(cx).hooks, &tool_name);
+        let hook_runner = if tool_hooks.is_empty() {
+            None
+        } else {
+            Some(self.hook_runner(cx))
+        };
+        let tool_result = match hook_runner {
+            Some(Ok(runner)) => self.run_tool_with_hooks(
+                tool,
+                tool_input,
+                tool_name.clone(),
+                tool_event_stream,
+                runner,
+                tool_hooks,
+                cx,
+            ),
+            Some(Err(error)) => Task::ready(Err(AgentToolOutput::from_error(error.to_string()))),
+            None => tool.run(tool_input, tool_event_stream, cx),
+        };
         cx.foreground_executor().spawn(async move {
             let (is_error, output) = match tool_result.await {
                 Ok(mut output) => {
@@ -2403,6 +2828,146 @@ impl Thread {
         })
     }
 
+    /// Runs a tool between the `pre_tool_call`, `post_edit` and `post_tool_call`
+    /// hooks that apply to it.
+    fn run_tool_with_hooks(
+        &self,
+        tool: Arc<dyn AnyAgentTool>,
+        tool_input: ToolInput<serde_json::Value>,
+        tool_name: Arc<str>,
+        event_stream: ToolCallEventStream,
+        runner: HookRunner,
+        hooks: ToolHooks,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<AgentToolOutput, AgentToolOutput>> {
+        let action_log = self.action_log.clone();
+        cx.spawn(async move |_this, cx| {
+            let mut input = tool_input.recv().await.map_err(|error| {
+                AgentToolOutput::from_error(format!("Failed to receive tool input: {error}"))
+            })?;
+
+            let mut reports = Vec::new();
+            for hook in &hooks.pre_tool_call {
+                let run = runner
+                    .run(
+                        hook,
+                        HookEvent::PreToolCall,
+                        serde_json::json!({ "tool_name": tool_name, "tool_input": input }),
+                        cx,
+                    )
+                    .await;
+                if !run.success {
+                    return Err(AgentToolOutput::from_error(run.rejection()));
+                }
+                if let Some(replaced_input) = run.replaced_tool_input() {
+                    input = replaced_input;
+                    event_stream
+                        .update_fields(acp::ToolCallUpdateFields::new().raw_input(input.clone()));
+                    reports.push(format!(
+                        "The `{}` hook changed the input of this tool call to:\n{input}",
+                        hook.command
+                    ));
+                } else {
+                    reports.extend(run.report());
+                }
+            }
+
+            // Remember the state of the buffers the agent already changed, so
+            // that the buffers this tool call edits can be told apart.
+            let versions_before = action_log.read_with(cx, |action_log, cx| {
+                action_log
+                    .changed_buffers(cx)
+                    .into_keys()
+                    .map(|buffer| {
+                        let version = buffer.read(cx).version();
+                        (buffer, version)
+                    })
+                    .collect::<HashMap<_, _>>()
+            });
+
+            let mut result = cx
+                .update(|cx| tool.run(ToolInput::ready(input.clone()), event_stream, cx))
+                .await;
+            let is_error = result.is_err();
+            let output = match &mut result {
+                Ok(output) | Err(output) => output,
+            };
+
+            if !hooks.post_edit.is_empty() {
+                let edited_buffers = action_log.read_with(cx, |action_log, cx| {
+                    action_log
+                        .changed_buffers(cx)
+                        .into_keys()
+                        .filter(|buffer| {
+                            versions_before.get(buffer) != Some(&buffer.read(cx).version())
+                        })
+                        .collect::<Vec<_>>()
+                });
+                if !edited_buffers.is_empty() {
+                    // The edits aren't saved for the hooks, since that's up to
+                    // the user, so the contents of unsaved files are passed along.
+                    let (paths, unsaved_files) = cx.update(|cx| {
+                        let mut paths = Vec::new();
+                        let mut unsaved_files = Vec::new();
+                        for buffer in &edited_buffers {
+                            let buffer = buffer.read(cx);
+                            // Paths on a remote project's host, where the hooks run.
+                            let Some(path) = project::File::from_dyn(buffer.file())
+                                .map(|file| file.worktree.read(cx).absolutize(&file.path))
+                            else {
+                                continue;
+                            };
+                            if buffer.is_dirty() {
+                                unsaved_files.push(serde_json::json!({
+                                    "path": path,
+                                    "text": buffer.text(),
+                                }));
+                            }
+                            paths.push(path);
+                        }
+                        (paths, unsaved_files)
+                    });
+                    for hook in &hooks.post_edit {
+                        let run = runner
+                            .run(
+                                hook,
+                                HookEvent::PostEdit,
+                                serde_json::json!({
+                                    "tool_name": tool_name,
+                                    "tool_input": input,
+                                    "paths": paths,
+                                    "unsaved_files": unsaved_files,
+                                }),
+                                cx,
+                            )
+                            .await;
+                        reports.extend(run.report());
+                    }
+                }
+            }
+
+            for hook in &hooks.post_tool_call {
+                let run = runner
+                    .run(
+                        hook,
+                        HookEvent::PostToolCall,
+                        serde_json::json!({
+                            "tool_name": tool_name,
+                            "tool_input": input,
+                            "tool_output": output.raw_output,
+                            "is_error": is_error,
+                        }),
+                        cx,
+                    )
+                    .await;
+                reports.extend(run.report());
+            }
+
+            append_hook_reports(output, &reports);
+            result
+        })
+    }
+
     fn handle_tool_use_json_parse_error_event(
         &mut self,
         tool_use_id: LanguageModelToolUseId,
@@ -2508,9 +3073,9 @@ impl Thread {
             ..Default::default()
         };
 
-        for message in &self.messages {
-            request.messages.extend(message.to_request());
-        }
+        request
+            .messages
+            .extend(self.history_request_messages(self.messages.len()));
 
         request.messages.push(LanguageModelRequestMessage {
             role: Role::User,
@@ -2522,17 +3087,29 @@ impl Thread {
         let task = cx
             .spawn(async move |this, cx| {
                 let mut summary = String::new();
-                let mut messages = model.stream_completion(request, cx).await.log_err()?;
-                while let Some(event) = messages.next().await {
-                    let event = event.log_err()?;
-                    let text = match event {
-                        LanguageModelCompletionEvent::Text(text) => text,
-                        _ => continue,
-                    };
-
-                    let mut lines = text.lines();
-                    summary.extend(lines.next());
+                let mut usage = None;
+                let generate = async {
+                    let mut messages = model.stream_completion(request, cx).await?;
+                    while let Some(event) = messages.next().await {
+                        match event? {
+                            LanguageModelCompletionEvent::Text(text) => {
+                                let mut lines = text.lines();
+                                summary.extend(lines.next());
+                            }
+                            LanguageModelCompletionEvent::UsageUpdate(update) => {
+                                usage = Some(update);
+                            }
+                            _ => {}
+                        }
+                    }
+                    anyhow::Ok(())
                 }
+                .await;
+                if let Some(usage) = usage {
+                    this.update(cx, |this, cx| this.record_request_usage(&model, usage, cx))
+                        .ok();
+                }
+                generate.log_err()?;
 
                 log::debug!("Setting summary: {}", summary);
                 let summary = SharedString::from(summary);
@@ -2551,6 +3128,372 @@ impl Thread {
         task
     }
 
+    pub fn compaction(&self) -> Option<&ThreadCompaction> {
+        self.compaction.as_ref()
+    }
+
+    pub fn is_compacting(&self) -> bool {
+        self.compacting
+    }
+
+    pub fn compaction_error(&self) -> Option<&SharedString> {
+        self.compaction_error.as_ref()
+    }
+
+    pub fn dismiss_compaction_error(&mut self, cx: &mut Context<Self>) {
+        if self.compaction_error.take().is_some() {
+            cx.notify();
+        }
+    }
+
+    /// Returns true if there are turns that haven't been compacted yet, other
+    /// than the most recent one.
+    pub fn can_compact(&self) -> bool {
+        self.compaction_boundary().is_some()
+    }
+
+    /// Summarizes every turn before the latest user message with the
+    /// summarization model. Subsequent requests send the summary in place of
+    /// those messages, while the messages themselves are kept in the thread.
+    /// The oldest turns are left out of the summary if they don't fit in the
+    /// summarization model's context window.
+    pub fn compact(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        if self.compacting {
+            return Task::ready(Err(anyhow!("Thread is already being compacted")));
+        }
+        let Some(model) = self
+            .summarization_model
+            .clone()
+            .or_else(|| self.model.clone())
+        else {
+            return Task::ready(Err(anyhow!("No language model configured")));
+        };
+        let Some((message_count, first_kept_message_id)) = self.compaction_boundary() else {
+            return Task::ready(Err(anyhow!("There are no older messages to compact")));
+        };
+
+        let mut request = LanguageModelRequest {
+            thread_id: Some(self.id.to_string()),
+            prompt_id: Some(self.prompt_id.to_string()),
+            intent: Some(CompletionIntent::ThreadContextSummarization),
+            temperature: AgentSettings::temperature_for_model(&model, cx),
+            ..Default::default()
+        };
+        let max_input_tokens = (model.max_token_count() as f64 * COMPACTION_INPUT_RATIO) as u64;
+        match self.compaction_request_messages(
+            message_count,
+            max_input_tokens.saturating_sub(estimate_tokens(COMPACT_THREAD_PROMPT)),
+        ) {
+            Ok(messages) => request.messages.extend(messages),
+            Err(error) => {
+                self.compaction_error =
+                    Some(format!("Failed to compact the thread: {error:#}").into());
+                cx.notify();
+                return Task::ready(Err(error));
+            }
+        }
+        request.messages.push(LanguageModelRequestMessage {
+            role: Role::User,
+            content: vec![COMPACT_THREAD_PROMPT.into()],
+            cache: false,
+            reasoning_details: None,
+        });
+
+        let preserved_tool_use_ids = self.relevant_tool_use_ids(message_count, cx);
+
+        log::debug!(
+            "Compacting {} of {} messages with model: {}",
+            message_count,
+            self.messages.len(),
+            model.name().0
+        );
+        self.compacting = true;
+        self.compaction_error = None;
+        cx.notify();
+
+        cx.spawn(async move |this, cx| {
+            let mut usage = None;
+            let summary = async {
+                let mut summary = String::new();
+                let mut events = model.stream_completion(request, cx).await?;
+                while let Some(event) = events.next().await {
+                    match event? {
+                        LanguageModelCompletionEvent::Text(text) => summary.push_str(&text),
+                        LanguageModelCompletionEvent::UsageUpdate(update) => usage = Some(update),
+                        _ => {}
+                    }
+                }
+                anyhow::ensure!(
+                    !summary.trim().is_empty(),
+                    "Model returned an empty summary"
+                );
+                anyhow::Ok(summary)
+            }
+            .await;
+
+            this.update(cx, |this, cx| {
+                if let Some(usage) = usage {
+                    this.record_request_usage(&model, usage, cx);
+                }
+                this.compacting = false;
+                cx.notify();
+                let summary = match summary {
+                    Ok(summary) => summary,
+                    Err(error) => {
+                        this.compaction_error =
+                            Some(format!("Failed to compact the thread: {error:#}").into());
+                        return Err(error);
+                    }
+                };
+
+                // The thread may have been truncated while we were summarizing.
+                let boundary_is_valid = matches!(
+                    this.messages.get(message_count),
+                    Some(Message::User(message)) if message.id == first_kept_message_id
+                );
+                anyhow::ensure!(boundary_is_valid, "Thread changed while compacting");
+
+                this.compaction = Some(ThreadCompaction {
+                    message_count,
+                    first_kept_message_id,
+                    summary: summary.trim().to_string().into(),
+                    preserved_tool_use_ids,
+                    compacted_at: Utc::now(),
+                });
+                this.compacted_since_last_request = true;
+                this.updated_at = Utc::now();
+                this.clear_summary();
+                Ok(())
+            })?
+        })
+    }
+
+    /// Starts compacting the thread if the latest request used more of the
+    /// model's context window than the configured threshold.
+    fn auto_compact(&mut self, cx: &mut Context<Self>) -> Option<Task<Result<()>>> {
+        let settings = AgentSettings::get_global(cx);
+        if !settings.auto_compact_threads
+            || self.compacting
+            || self.compacted_since_last_request
+            || self.is_subagent()
+        {
+            return None;
+        }
+
+        let max_tokens = self.model.as_ref()?.max_token_count();
+        let used_tokens = self.last_known_token_usage()?.total_tokens();
+        if max_tokens == 0
+            || (used_tokens as f64) < max_tokens as f64 * settings.auto_compact_threshold as f64
+        {
+            return None;
+        }
+        if !self.can_compact() {
+            return None;
+        }
+
+        log::info!(
+            "Automatically compacting thread {} at {} of {} tokens",
+            self.id,
+            used_tokens,
+            max_tokens
+        );
+        Some(self.compact(cx))
+    }
+
+    /// The token usage reported for the most recent request, even if the
+    /// latest user message hasn't been sent yet.
+    fn last_known_token_usage(&self) -> Option<language_model::TokenUsage> {
+        self.messages
+            .iter()
+            .rev()
+            .find_map(|message| match message {
+                Message::User(message) => self.request_token_usage.get(&message.id).copied(),
+                Message::Agent(_) | Message::Resume => None,
+            })
+    }
+
+    /// Compaction summarizes everything before the latest user message, so
+    /// that the ongoing turn is always sent verbatim.
+    fn compaction_boundary(&self) -> Option<(usize, UserMessageId)> {
+        let compacted_count = self
+            .compaction
+            .as_ref()
+            .map_or(0, |compaction| compaction.message_count);
+        let (ix, message) = self
+            .messages
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(ix, message)| match message {
+                Message::User(message) => Some((ix, message)),
+                Message::Agent(_) | Message::Resume => None,
+            })?;
+        (ix > compacted_count).then(|| (ix, message.id.clone()))
+    }
+
+    /// Finds the tool calls before `message_count` whose results are still
+    /// relevant after compacting: the latest plan, and the latest edit of each
+    /// file that still has changes the user hasn't reviewed.
+    fn relevant_tool_use_ids(&self, message_count: usize, cx: &App) -> Vec<LanguageModelToolUseId> {
+        let changed_paths = self
+            .action_log
+            .read(cx)
+            .changed_buffers(cx)
+            .into_keys()
+            .filter_map(|buffer| Some(buffer.read(cx).file()?.full_path(cx)))
+            .collect::<HashSet<_>>();
+
+        let mut plan = None;
+        let mut edits = IndexMap::default();
+        for message in &self.messages[..message_count] {
+            let Message::Agent(message) = message else {
+                continue;
+            };
+            for content in &message.content {
+                let AgentMessageContent::ToolUse(tool_use) = content else {
+                    continue;
+                };
+                if message
+                    .tool_results
+                    .get(&tool_use.id)
+                    .is_none_or(|result| result.is_error)
+                {
+                    continue;
+                }
+
+                let name = tool_use.name.as_ref();
+                if name == UpdatePlanTool::NAME {
+                    plan = Some(tool_use.id.clone());
+                } else if (name == EditFileTool::NAME || name == StreamingEditFileTool::NAME)
+                    && let Some(path) = tool_use.input.get("path").and_then(|path| path.as_str())
+                    && changed_paths.contains(Path::new(path))
+                {
+                    edits.shift_remove(path);
+                    edits.insert(path.to_string(), tool_use.id.clone());
+                }
+            }
+        }
+
+        plan.into_iter().chain(edits.into_values()).collect()
+    }
+
+    /// Builds the request messages for the first `end` messages of the thread,
+    /// replacing compacted messages with their summary.
+    fn history_request_messages(&self, end: usize) -> Vec<LanguageModelRequestMessage> {
+        let (start, mut messages) = self.compacted_context_request_messages(end);
+        for message in &self.messages[start..end] {
+            messages.extend(message.to_request());
+        }
+        messages
+    }
+
+    /// Like [`Self::history_request_messages`], but without the oldest
+    /// messages that would take the request over the given number of tokens.
+    fn compaction_request_messages(
+        &self,
+        end: usize,
+        max_tokens: u64,
+    ) -> Result<Vec<LanguageModelRequestMessage>> {
+        let (start, mut messages) = self.compacted_context_request_messages(end);
+        let mut budget = max_tokens.saturating_sub(estimate_request_tokens(&messages));
+        let mut kept = Vec::new();
+        for (ix, message) in self.messages[start..end].iter().enumerate().rev() {
+            let request_messages = message.to_request();
+            let tokens = estimate_request_tokens(&request_messages);
+            if tokens > budget {
+                break;
+            }
+            budget -= tokens;
+            kept.push((start + ix, request_messages));
+        }
+        // Tool results are sent in the message after their tool use, so the
+        // history has to start at a user message to stay valid.
+        while let Some((ix, _)) = kept.last()
+            && !matches!(self.messages[*ix], Message::User(_))
+        {
+            kept.pop();
+        }
+        // Summarizing nothing but an earlier summary would lose the turns
+        // that didn't fit without anything to show for it.
+        let Some(first_kept_ix) = kept.last().map(|(ix, _)| *ix) else {
+            return Err(anyhow!(
+                "None of the turns to compact fit in the summarization model's context window"
+            ));
+        };
+
+        if first_kept_ix > start {
+            log::info!(
+                "Leaving {} of {} messages out of the compaction request",
+                first_kept_ix - start,
+                end - start
+            );
+            messages.push(LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![OMITTED_CONTEXT_NOTE.into()],
+                cache: false,
+                reasoning_details: None,
+            });
+        }
+        for (_, request_messages) in kept.into_iter().rev() {
+            messages.extend(request_messages);
+        }
+        Ok(messages)
+    }
+
+    /// The summary of the compacted messages before `end` and the tool calls
+    /// preserved with it, along with the index of the first message after them.
+    fn compacted_context_request_messages(
+        &self,
+        end: usize,
+    ) -> (usize, Vec<LanguageModelRequestMessage>) {
+        let mut messages = Vec::new();
+        let mut start = 0;
+        if let Some(compaction) = self
+            .compaction
+            .as_ref()
+            .filter(|compaction| compaction.message_count <= end)
+        {
+            start = compaction.message_count;
+            messages.push(LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![
+                    format!(
+                        "{COMPACTED_CONTEXT_OPEN_TAG}\n{}\n{COMPACTED_CONTEXT_CLOSE_TAG}",
+                        compaction.summary
+                    )
+                    .into(),
+                ],
+                cache: false,
+                reasoning_details: None,
+            });
+
+            for tool_use_id in &compaction.preserved_tool_use_ids {
+                let preserved = self.messages[..start].iter().find_map(|message| {
+                    let message = message.as_agent_message()?;
+                    let tool_result = message.tool_results.get(tool_use_id)?;
+                    let tool_use = message.content.iter().find_map(|content| match content {
+                        AgentMessageContent::ToolUse(tool_use) if &tool_use.id == tool_use_id => {
+                            Some(tool_use.clone())
+                        }
+                        _ => None,
+                    })?;
+                    Some(AgentMessage {
+                        content: vec![AgentMessageContent::ToolUse(tool_use)],
+                        tool_results: IndexMap::from_iter([(
+                            tool_use_id.clone(),
+                            tool_result.clone(),
+                        )]),
+                        reasoning_details: None,
+                    })
+                });
+                if let Some(preserved) = preserved {
+                    messages.extend(preserved.to_request());
+                }
+            }
+        }
+        (start, messages)
+    }
+
     pub fn generate_title(&mut self, cx: &mut Context<Self>) {
         let Some(model) = self.summarization_model.clone() else {
             return;
@@ -2578,13 +3521,17 @@ impl Thread {
         });
         self.pending_title_generation = Some(cx.spawn(async move |this, cx| {
             let mut title = String::new();
+            let mut usage = None;
 
             let generate = async {
                 let mut messages = model.stream_completion(request, cx).await?;
                 while let Some(event) = messages.next().await {
-                    let event = event?;
-                    let text = match event {
+                    let text = match event? {
                         LanguageModelCompletionEvent::Text(text) => text,
+                        LanguageModelCompletionEvent::UsageUpdate(update) => {
+                            usage = Some(update);
+                            continue;
+                        }
                         _ => continue,
                     };
 
@@ -2599,8 +3546,11 @@ impl Thread {
                 anyhow::Ok(())
             };
 
-            if generate
-                .await
+            let generated = generate.await;
+            if let Some(usage) = usage {
+                _ = this.update(cx, |this, cx| this.record_request_usage(&model, usage, cx));
+            }
+            if generated
                 .context("failed to generate thread title")
                 .log_err()
                 .is_some()
@@ -2774,7 +3724,7 @@ impl Thread {
                     tool_name.as_ref()
                 };
 
-                if tool.supports_provider(&model.provider_id())
+                if tool.supports_provider(&model.provider_id(), cx)
                     && profile.is_tool_enabled(profile_tool_name)
                 {
                     match (tool_name.as_ref(), use_streaming_edit_tool) {
@@ -2926,9 +3876,7 @@ impl Thread {
             cache: false,
             reasoning_details: None,
         }];
-        for message in &self.messages {
-            messages.extend(message.to_request());
-        }
+        messages.extend(self.history_request_messages(self.messages.len()));
 
         if let Some(last_message) = messages.last_mut() {
             last_message.cache = true;
@@ -3249,7 +4197,7 @@ where
 
     /// Some tools rely on a provider for the underlying billing or other reasons.
     /// Allow the tool to check if they are compatible, or should be filtered out.
-    fn supports_provider(_provider: &LanguageModelProviderId) -> bool {
+    fn supports_provider(_provider: &LanguageModelProviderId, _cx: &App) -> bool {
         true
     }
 
@@ -3310,7 +4258,7 @@ pub trait AnyAgentTool {
     fn supports_input_streaming(&self) -> bool {
         false
     }
-    fn supports_provider(&self, _provider: &LanguageModelProviderId) -> bool {
+    fn supports_provider(&self, _provider: &LanguageModelProviderId, _cx: &App) -> bool {
         true
     }
     /// See [`AgentTool::run`] for why this returns `Result<AgentToolOutput, AgentToolOutput>`.
@@ -3360,8 +4308,8 @@ where
         Ok(json)
     }
 
-    fn supports_provider(&self, provider: &LanguageModelProviderId) -> bool {
-        T::supports_provider(provider)
+    fn supports_provider(&self, provider: &LanguageModelProviderId, cx: &App) -> bool {
+        T::supports_provider(provider, cx)
     }
 
     fn run(
@@ -4027,6 +4975,31 @@ fn convert_image(image_content: acp::ImageContent) -> LanguageModelImage {
     }
 }
 
+/// A rough guess of how many tokens some text takes up, without a tokenizer.
+fn estimate_tokens(text: &str) -> u64 {
+    text.len().div_ceil(4) as u64
+}
+
+fn estimate_request_tokens(messages: &[LanguageModelRequestMessage]) -> u64 {
+    messages
+        .iter()
+        .flat_map(|message| &message.content)
+        .map(|content| match content {
+            language_model::MessageContent::Text(text)
+            | language_model::MessageContent::Thinking { text, .. }
+            | language_model::MessageContent::RedactedThinking(text) => estimate_tokens(text),
+            language_model::MessageContent::Image(image) => image.estimate_tokens() as u64,
+            language_model::MessageContent::ToolUse(tool_use) => {
+                estimate_tokens(&tool_use.name) + estimate_tokens(&tool_use.raw_input)
+            }
+            language_model::MessageContent::ToolResult(tool_result) => match &tool_result.content {
+                LanguageModelToolResultContent::Text(text) => estimate_tokens(text),
+                LanguageModelToolResultContent::Image(image) => image.estimate_tokens() as u64,
+            },
+        })
+        .sum()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,593 @@
+use crate::{
+    AgentMessage, AgentMessageContent, DbThread, Message, ProjectSnapshot, SharedThread,
+    UserMessage, UserMessageContent,
+};
+use acp_thread::MentionUri;
+use anyhow::{Context as _, Result};
+use language_model::{LanguageModelToolResult, LanguageModelToolResultContent};
+use std::{fmt::Write as _, sync::Arc};
+use util::markdown::MarkdownCodeBlock;
+
+/// A thread exported to a file, so that it can be handed to someone else and
+/// imported into their thread history.
+///
+/// Exports are [`SharedThread`]s that carry the thread's details too, so they
+/// follow the same format and version as shared threads.
+#[derive(Debug, Clone)]
+pub struct ThreadExport {
+    pub thread: SharedThread,
+}
+
+impl ThreadExport {
+    /// Exports the thread. The uncommitted changes and git remote URLs of the
+    /// project when the thread started are left out unless `include_git_state`
+    /// is set, since they're easy to share by accident.
+    pub fn from_db_thread(thread: &DbThread, include_git_state: bool) -> Self {
+        let mut thread = SharedThread::from_db_thread_with_details(thread);
+        if !include_git_state
+            && let Some(details) = &mut thread.details
+            && let Some(snapshot) = &mut details.initial_project_snapshot
+        {
+            let mut snapshot_without_git_state = ProjectSnapshot::clone(snapshot);
+            for worktree in &mut snapshot_without_git_state.worktree_snapshots {
+                if let Some(git_state) = &mut worktree.git_state {
+                    git_state.diff = None;
+                    git_state.remote_url = None;
+                }
+            }
+            *snapshot = Arc::new(snapshot_without_git_state);
+        }
+        Self { thread }
+    }
+
+    /// Converts the export into a new thread, marked as imported.
+    pub fn into_db_thread(self) -> DbThread {
+        self.thread.to_db_thread()
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(&self.thread)?)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        let export =
+            serde_json::from_str::<serde_json::Value>(json).context("the file isn't valid JSON")?;
+        let version = export
+            .get("version")
+            .and_then(|version| version.as_str())
+            .context("the file isn't an exported agent thread")?;
+        let major_version = |version: &str| version.split('.').next()?.parse::<u32>().ok();
+        anyhow::ensure!(
+            major_version(version) <= major_version(SharedThread::VERSION),
+            "the thread was exported by a newer version of Zed (format version {version})"
+        );
+        let thread = serde_json::from_value(export).context("the exported thread is invalid")?;
+        Ok(Self { thread })
+    }
+
+    /// Renders the thread as a Markdown transcript.
+    pub fn to_markdown(&self) -> String {
+        let mut writer = MarkdownWriter::default();
+        self.write_transcript(&mut writer);
+        writer.output
+    }
+
+    /// Renders the thread as a standalone HTML page.
+    pub fn to_html(&self) -> String {
+        let mut writer = HtmlWriter::default();
+        self.write_transcript(&mut writer);
+        writer.finish()
+    }
+
+    fn write_transcript(&self, writer: &mut impl TranscriptWriter) {
+        let thread = &self.thread;
+        let thread_details = thread.details.clone().unwrap_or_default();
+        writer.title(&thread.title);
+
+        let mut details = Vec::new();
+        if let Some(model) = &thread.model {
+            details.push(("Model", format!("{}/{}", model.provider, model.model)));
+        }
+        if let Some(profile) = &thread_details.profile {
+            details.push(("Profile", profile.to_string()));
+        }
+        details.push((
+            "Last updated",
+            thread.updated_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+        ));
+        let usage = thread_details.cumulative_token_usage;
+        if usage.total_tokens() > 0 {
+            details.push((
+                "Tokens",
+                format!(
+                    "{} input, {} output",
+                    usage.input_tokens
+                        + usage.cache_creation_input_tokens
+                        + usage.cache_read_input_tokens,
+                    usage.output_tokens
+                ),
+            ));
+        }
+        writer.details(&details);
+
+        if let Some(snapshot) = &thread_details.initial_project_snapshot
+            && !snapshot.worktree_snapshots.is_empty()
+        {
+            writer.heading("Project");
+            for worktree in &snapshot.worktree_snapshots {
+                let mut details = vec![("Path", worktree.worktree_path.clone())];
+                let git_state = worktree.git_state.as_ref();
+                if let Some(branch) = git_state.and_then(|git| git.current_branch.clone()) {
+                    details.push(("Branch", branch));
+                }
+                if let Some(head_sha) = git_state.and_then(|git| git.head_sha.clone()) {
+                    details.push(("Commit", head_sha));
+                }
+                if let Some(remote_url) = git_state.and_then(|git| git.remote_url.clone()) {
+                    details.push(("Remote", remote_url));
+                }
+                writer.details(&details);
+                if let Some(diff) = git_state.and_then(|git| git.diff.as_deref())
+                    && !diff.trim().is_empty()
+                {
+                    writer.code("Uncommitted changes when the thread started", "diff", diff);
+                }
+            }
+        }
+
+        if let Some(summary) = &thread_details.detailed_summary {
+            writer.heading("Summary");
+            writer.text(summary);
+        }
+
+        for message in &thread.messages {
+            match message {
+                Message::User(message) => write_user_message(message, writer),
+                Message::Agent(message) => write_agent_message(message, writer),
+                Message::Resume => writer.note("The agent was asked to continue."),
+            }
+        }
+    }
+}
+
+fn write_user_message(message: &UserMessage, writer: &mut impl TranscriptWriter) {
+    writer.heading("User");
+    for content in &message.content {
+        match content {
+            UserMessageContent::Text(text) => writer.text(text),
+            UserMessageContent::Mention { uri, .. } => writer.mention(uri),
+            UserMessageContent::Image(_) => writer.note("Image"),
+        }
+    }
+}
+
+fn write_agent_message(message: &AgentMessage, writer: &mut impl TranscriptWriter) {
+    writer.heading("Agent");
+    for content in &message.content {
+        match content {
+            AgentMessageContent::Text(text) => writer.text(text),
+            AgentMessageContent::Thinking { text, .. } => writer.thinking(text),
+            AgentMessageContent::RedactedThinking(_) => writer.note("Redacted thinking"),
+            AgentMessageContent::ToolUse(tool_use) => {
+                writer.code(
+                    &format!("Tool call: {}", tool_use.name),
+                    "json",
+                    &format!("{:#}", tool_use.input),
+                );
+                if let Some(result) = message.tool_results.get(&tool_use.id) {
+                    write_tool_result(result, writer);
+                }
+            }
+        }
+    }
+}
+
+fn write_tool_result(result: &LanguageModelToolResult, writer: &mut impl TranscriptWriter) {
+    let label = if result.is_error { "Error" } else { "Result" };
+
+    // The output of tools that edit files has the diff of their edits, which
+    // reads better than the message sent to the model.
+    let diff = result
+        .output
+        .as_ref()
+        .and_then(|output| output.get("diff")?.as_str())
+        .filter(|diff| !diff.trim().is_empty());
+    if let Some(diff) = diff {
+        writer.code(label, "diff", diff);
+        return;
+    }
+
+    match &result.content {
+        LanguageModelToolResultContent::Text(text) => writer.code(label, "", text),
+        LanguageModelToolResultContent::Image(_) => writer.note("Image"),
+    }
+}
+
+trait TranscriptWriter {
+    fn title(&mut self, title: &str);
+    fn details(&mut self, details: &[(&str, String)]);
+    fn heading(&mut self, heading: &str);
+    fn text(&mut self, text: &str);
+    fn thinking(&mut self, text: &str);
+    fn mention(&mut self, uri: &MentionUri);
+    fn note(&mut self, note: &str);
+    fn code(&mut self, label: &str, language: &str, code: &str);
+}
+
+#[derive(Default)]
+struct MarkdownWriter {
+    output: String,
+}
+
+impl TranscriptWriter for MarkdownWriter {
+    fn title(&mut self, title: &str) {
+        writeln!(self.output, "# {title}\n").ok();
+    }
+
+    fn details(&mut self, details: &[(&str, String)]) {
+        for (label, value) in details {
+            writeln!(self.output, "- **{label}:** {value}").ok();
+        }
+        self.output.push('\n');
+    }
+
+    fn heading(&mut self, heading: &str) {
+        writeln!(self.output, "## {heading}\n").ok();
+    }
+
+    fn text(&mut self, text: &str) {
+        writeln!(self.output, "{}\n", text.trim_end()).ok();
+    }
+
+    fn thinking(&mut self, text: &str) {
+        self.output.push_str("> **Thinking**\n>\n");
+        for line in text.trim_end().lines() {
+            writeln!(self.output, "> {line}").ok();
+        }
+        self.output.push('\n');
+    }
+
+    fn mention(&mut self, uri: &MentionUri) {
+        writeln!(self.output, "{}\n", uri.as_link()).ok();
+    }
+
+    fn note(&mut self, note: &str) {
+        writeln!(self.output, "_{note}_\n").ok();
+    }
+
+    fn code(&mut self, label: &str, language: &str, code: &str) {
+        writeln!(
+            self.output,
+            "**{label}**\n\n{}",
+            MarkdownCodeBlock {
+                tag: language,
+                text: code.trim_end(),
+            }
+        )
+        .ok();
+    }
+}
+
+#[derive(Default)]
+struct HtmlWriter {
+    title: String,
+    body: String,
+}
+
+impl HtmlWriter {
+    const STYLE: &str = "body { font-family: system-ui, sans-serif; line-height: 1.5; max-width: 52rem; margin: 2rem auto; padding: 0 1rem; color: #1f2328; }
+h2 { border-bottom: 1px solid #d0d7de; padding-bottom: 0.25rem; margin-top: 2rem; }
+dl { display: grid; grid-template-columns: max-content auto; gap: 0.25rem 1rem; }
+dt { font-weight: 600; }
+dd { margin: 0; }
+.text { white-space: pre-wrap; }
+.note { color: #59636e; font-style: italic; }
+blockquote { margin: 0 0 1rem; padding: 0 1rem; border-left: 0.25rem solid #d0d7de; color: #59636e; }
+details { margin-bottom: 1rem; }
+summary { cursor: pointer; font-weight: 600; }
+pre { background: #f6f8fa; padding: 0.75rem; border-radius: 6px; overflow-x: auto; }";
+
+    fn finish(self) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+            escape_html(&self.title),
+            Self::STYLE,
+            self.body
+        )
+    }
+}
+
+impl TranscriptWriter for HtmlWriter {
+    fn title(&mut self, title: &str) {
+        self.title = title.to_string();
+        writeln!(self.body, "<h1>{}</h1>", escape_html(title)).ok();
+    }
+
+    fn details(&mut self, details: &[(&str, String)]) {
+        self.body.push_str("<dl>\n");
+        for (label, value) in details {
+            writeln!(
+                self.body,
+                "<dt>{}</dt><dd>{}</dd>",
+                escape_html(label),
+                escape_html(value)
+            )
+            .ok();
+        }
+        self.body.push_str("</dl>\n");
+    }
+
+    fn heading(&mut self, heading: &str) {
+        writeln!(self.body, "<h2>{}</h2>", escape_html(heading)).ok();
+    }
+
+    fn text(&mut self, text: &str) {
+        writeln!(
+            self.body,
+            "<div class=\"text\">{}</div>",
+            escape_html(text.trim_end())
+        )
+        .ok();
+    }
+
+    fn thinking(&mut self, text: &str) {
+        writeln!(
+            self.body,
+            "<blockquote><details><summary>Thinking</summary><div class=\"text\">{}</div></details></blockquote>",
+            escape_html(text.trim_end())
+        )
+        .ok();
+    }
+
+    fn mention(&mut self, uri: &MentionUri) {
+        writeln!(
+            self.body,
+            "<p><a href=\"{}\">@{}</a></p>",
+            escape_html(uri.to_uri().as_str()),
+            escape_html(&uri.name())
+        )
+        .ok();
+    }
+
+    fn note(&mut self, note: &str) {
+        writeln!(self.body, "<p class=\"note\">{}</p>", escape_html(note)).ok();
+    }
+
+    fn code(&mut self, label: &str, language: &str, code: &str) {
+        let class = if language.is_empty() {
+            String::new()
+        } else {
+            format!(" class=\"language-{}\"", escape_html(language))
+        };
+        writeln!(
+            self.body,
+            "<details open><summary>{}</summary><pre><code{class}>{}</code></pre></details>",
+            escape_html(label),
+            escape_html(code.trim_end())
+        )
+        .ok();
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DbLanguageModel;
+    use acp_thread::UserMessageId;
+    use agent_settings::AgentProfileId;
+    use chrono::{DateTime, Utc};
+    use collections::{HashMap, IndexMap};
+    use indoc::indoc;
+    use language_model::{LanguageModelToolUse, LanguageModelToolUseId, TokenUsage};
+    use pretty_assertions::assert_eq;
+    use project::telemetry_snapshot::{GitState, TelemetryWorktreeSnapshot};
+
+    fn thread() -> DbThread {
+        let tool_use_id = LanguageModelToolUseId::from("tool_1");
+        DbThread {
+            title: "Fix <the> bug".into(),
+            messages: vec![
+                Message::User(UserMessage {
+                    id: UserMessageId::new(),
+                    content: vec![UserMessageContent::Text("Fix the typo".into())],
+                }),
+                Message::Agent(AgentMessage {
+                    content: vec![
+                        AgentMessageContent::Thinking {
+                            text: "It's in main.rs".into(),
+                            signature: None,
+                        },
+                        AgentMessageContent::ToolUse(LanguageModelToolUse {
+                            id: tool_use_id.clone(),
+                            name: "edit_file".into(),
+                            raw_input: r#"{"path":"src/main.rs"}"#.into(),
+                            input: serde_json::json!({ "path": "src/main.rs" }),
+                            is_input_complete: true,
+                            thought_signature: None,
+                        }),
+                        AgentMessageContent::Text("Fixed it.".into()),
+                    ],
+                    tool_results: IndexMap::from_iter([(
+                        tool_use_id.clone(),
+                        LanguageModelToolResult {
+                            tool_use_id,
+                            tool_name: "edit_file".into(),
+                            is_error: false,
+                            content: "Edited src/main.rs".into(),
+                            output: Some(serde_json::json!({
+                                "input_path": "src/main.rs",
+                                "diff": "-helo\n+hello",
+                            })),
+                        },
+                    )]),
+                    reasoning_details: None,
+                }),
+            ],
+            updated_at: DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            compaction: None,
+            detailed_summary: None,
+            initial_project_snapshot: None,
+            cumulative_token_usage: TokenUsage {
+                input_tokens: 100,
+                output_tokens: 20,
+                ..Default::default()
+            },
+            request_token_usage: HashMap::default(),
+            model: Some(DbLanguageModel {
+                provider: "anthropic".into(),
+                model: "claude-sonnet".into(),
+            }),
+            profile: Some(AgentProfileId("write".into())),
+            imported: false,
+            subagent_context: None,
+            forked_from: None,
+            speed: None,
+            thinking_enabled: true,
+            thinking_effort: None,
+            draft_prompt: None,
+            ui_scroll_position: None,
+        }
+    }
+
+    #[test]
+    fn test_thread_export_roundtrip() {
+        let thread = thread();
+        let json = ThreadExport::from_db_thread(&thread, false)
+            .to_json()
+            .unwrap();
+        let imported = ThreadExport::from_json(&json).unwrap().into_db_thread();
+        assert_eq!(imported.title.as_ref(), "🔗 Fix <the> bug");
+        assert_eq!(imported.messages, thread.messages);
+        assert_eq!(imported.model, thread.model);
+        assert_eq!(imported.profile, thread.profile);
+        assert!(imported.thinking_enabled);
+        assert!(imported.imported);
+
+        // Shared threads can be imported too, without their details.
+        let shared = serde_json::to_string(&SharedThread::from_db_thread(&thread)).unwrap();
+        let imported = ThreadExport::from_json(&shared).unwrap().into_db_thread();
+        assert_eq!(imported.messages, thread.messages);
+        assert_eq!(imported.profile, None);
+
+        let error = ThreadExport::from_json(r#"{"title": "Not an export"}"#).unwrap_err();
+        assert_eq!(error.to_string(), "the file isn't an exported agent thread");
+        let newer = json.replacen(
+            &format!("\"version\": \"{}\"", SharedThread::VERSION),
+            "\"version\": \"2.0.0\"",
+            1,
+        );
+        let error = ThreadExport::from_json(&newer).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the thread was exported by a newer version of Zed (format version 2.0.0)"
+        );
+    }
+
+    #[test]
+    fn test_thread_export_leaves_out_git_state_by_default() {
+        let mut thread = thread();
+        thread.initial_project_snapshot = Some(Arc::new(ProjectSnapshot {
+            worktree_snapshots: vec![TelemetryWorktreeSnapshot {
+                worktree_path: "/code/app".into(),
+                git_state: Some(GitState {
+                    remote_url: Some("https://token@example.com/app.git".into()),
+                    head_sha: Some("abc123".into()),
+                    current_branch: Some("main".into()),
+                    diff: Some("+SECRET=1".into()),
+                }),
+            }],
+            timestamp: thread.updated_at,
+        }));
+
+        let git_state = |export: &ThreadExport| {
+            let details = export.thread.details.as_ref().unwrap();
+            let snapshot = details.initial_project_snapshot.as_ref().unwrap();
+            snapshot.worktree_snapshots[0].git_state.clone().unwrap()
+        };
+
+        let export = ThreadExport::from_db_thread(&thread, false);
+        let state = git_state(&export);
+        assert_eq!(state.diff, None);
+        assert_eq!(state.remote_url, None);
+        assert_eq!(state.current_branch.as_deref(), Some("main"));
+        assert_eq!(state.head_sha.as_deref(), Some("abc123"));
+        let markdown = export.to_markdown();
+        assert!(!markdown.contains("SECRET"), "{markdown}");
+        assert!(!markdown.contains("token@"), "{markdown}");
+
+        let export = ThreadExport::from_db_thread(&thread, true);
+        let state = git_state(&export);
+        assert_eq!(state.diff.as_deref(), Some("+SECRET=1"));
+        assert!(export.to_markdown().contains("+SECRET=1"));
+    }
+
+    #[test]
+    fn test_thread_export_markdown() {
+        let export = ThreadExport::from_db_thread(&thread(), false);
+        assert_eq!(
+            export.to_markdown(),
+            indoc! {r#"
+                # Fix <the> bug
+
+                - **Model:** anthropic/claude-sonnet
+                - **Profile:** write
+                - **Last updated:** 2026-01-02 03:04 UTC
+                - **Tokens:** 100 input, 20 output
+
+                ## User
+
+                Fix the typo
+
+                ## Agent
+
+                > **Thinking**
+                >
+                > It's in main.rs
+
+                **Tool call: edit_file**
+
+                ```json
+                {
+                  "path": "src/main.rs"
+                }
+                ```
+
+                **Result**
+
+                ```diff
+                -helo
+                +hello
+                ```
+
+                Fixed it.
+
+            "#}
+        );
+    }
+
+    #[test]
+    fn test_thread_export_html_is_escaped() {
+        let html = ThreadExport::from_db_thread(&thread(), false).to_html();
+        assert!(html.contains("<title>Fix &lt;the&gt; bug</title>"));
+        assert!(html.contains("<h1>Fix &lt;the&gt; bug</h1>"));
+        assert!(html.contains("<code class=\"language-diff\">-helo\n+hello</code>"));
+        assert!(html.contains("<div class=\"text\">It&#39;s in main.rs</div>"));
+    }
+}
@@ -65,6 +65,21 @@ impl ThreadStore {
         })
     }
 
+    /// Saves an imported thread as a new thread, returning its id.
+    pub fn import_thread(
+        &mut self,
+        thread: DbThread,
+        folder_paths: PathList,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<acp::SessionId>> {
+        let id = acp::SessionId::new(uuid::Uuid::new_v4().to_string());
+        let save = self.save_thread(id.clone(), thread, folder_paths, cx);
+        cx.background_spawn(async move {
+            save.await?;
+            Ok(id)
+        })
+    }
+
     pub fn delete_thread(
         &mut self,
         id: acp::SessionId,
@@ -122,6 +137,7 @@ impl ThreadStore {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::ThreadExport;
     use chrono::{DateTime, TimeZone, Utc};
     use collections::HashMap;
     use gpui::TestAppContext;
@@ -136,6 +152,7 @@ mod tests {
             title: title.to_string().into(),
             messages: Vec::new(),
             updated_at,
+            compaction: None,
             detailed_summary: None,
             initial_project_snapshot: None,
             cumulative_token_usage: Default::default(),
@@ -144,6 +161,7 @@ mod tests {
             profile: None,
             imported: false,
             subagent_context: None,
+            forked_from: None,
             speed: None,
             thinking_enabled: false,
             thinking_effort: None,
@@ -292,4 +310,42 @@ mod tests {
         assert_eq!(entries[0].id, first_id);
         assert_eq!(entries[1].id, second_id);
     }
+
+    #[gpui::test]
+    async fn test_import_thread_creates_new_thread(cx: &mut TestAppContext) {
+        let thread_store = cx.new(|cx| ThreadStore::new(cx));
+        cx.run_until_parked();
+
+        let export = ThreadExport::from_db_thread(
+            &make_thread(
+                "Exported",
+                Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+            ),
+            false,
+        )
+        .into_db_thread();
+        let first_id = thread_store
+            .update(cx, |store, cx| {
+                store.import_thread(export.clone(), PathList::default(), cx)
+            })
+            .await
+            .unwrap();
+        let second_id = thread_store
+            .update(cx, |store, cx| {
+                store.import_thread(export, PathList::default(), cx)
+            })
+            .await
+            .unwrap();
+        assert_ne!(first_id, second_id);
+        cx.run_until_parked();
+
+        let entries: Vec<_> = thread_store.read_with(cx, |store, _cx| store.entries().collect());
+        assert_eq!(entries.len(), 2);
+        let imported = thread_store
+            .update(cx, |store, cx| store.load_thread(first_id, cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(imported.title.as_ref(), "Exported");
+    }
 }
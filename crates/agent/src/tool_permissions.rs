@@ -595,6 +595,10 @@ mod tests {
             message_editor_min_lines: 1,
             tool_permissions,
             show_turn_stats: false,
+            auto_compact_threads: false,
+            auto_compact_threshold: 0.85,
+            hooks: Default::default(),
+            usage: Default::default(),
             new_thread_location: Default::default(),
             sidebar_side: Default::default(),
             thinking_display: Default::default(),
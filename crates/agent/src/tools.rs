@@ -1,3 +1,4 @@
+mod code_navigation;
 mod context_server_registry;
 mod copy_path_tool;
 mod create_directory_tool;
@@ -8,13 +9,26 @@ mod edit_file_tool;
 mod evals;
 mod fetch_tool;
 mod find_path_tool;
+mod find_references_tool;
+mod git_blame_tool;
+mod git_commit_tool;
+mod git_diff_tool;
+mod git_log_tool;
+mod git_repository;
+mod git_status_tool;
+mod go_to_definition_tool;
 mod grep_tool;
+mod hover_tool;
 mod list_directory_tool;
+mod list_tasks_tool;
 mod move_path_tool;
 mod now_tool;
 mod open_tool;
+mod project_tasks;
 mod read_file_tool;
+mod rename_symbol_tool;
 mod restore_file_from_disk_tool;
+mod run_task_tool;
 mod save_file_tool;
 mod spawn_agent_tool;
 mod streaming_edit_file_tool;
@@ -23,6 +37,7 @@ mod tool_edit_parser;
 mod tool_permissions;
 mod update_plan_tool;
 mod web_search_tool;
+mod workspace_symbols_tool;
 
 use crate::AgentTool;
 use language_model::{LanguageModelRequestTool, LanguageModelToolSchemaFormat};
@@ -35,13 +50,24 @@ pub use diagnostics_tool::*;
 pub use edit_file_tool::*;
 pub use fetch_tool::*;
 pub use find_path_tool::*;
+pub use find_references_tool::*;
+pub use git_blame_tool::*;
+pub use git_commit_tool::*;
+pub use git_diff_tool::*;
+pub use git_log_tool::*;
+pub use git_status_tool::*;
+pub use go_to_definition_tool::*;
 pub use grep_tool::*;
+pub use hover_tool::*;
 pub use list_directory_tool::*;
+pub use list_tasks_tool::*;
 pub use move_path_tool::*;
 pub use now_tool::*;
 pub use open_tool::*;
 pub use read_file_tool::*;
+pub use rename_symbol_tool::*;
 pub use restore_file_from_disk_tool::*;
+pub use run_task_tool::*;
 pub use save_file_tool::*;
 pub use spawn_agent_tool::*;
 pub use streaming_edit_file_tool::*;
@@ -49,6 +75,7 @@ pub use terminal_tool::*;
 pub use tool_permissions::*;
 pub use update_plan_tool::*;
 pub use web_search_tool::*;
+pub use workspace_symbols_tool::*;
 
 macro_rules! tools {
     ($($tool:ty),* $(,)?) => {
@@ -89,10 +116,14 @@ macro_rules! tools {
         };
 
         /// Returns whether the tool with the given name supports the given provider.
-        pub fn tool_supports_provider(name: &str, provider: &language_model::LanguageModelProviderId) -> bool {
+        pub fn tool_supports_provider(
+            name: &str,
+            provider: &language_model::LanguageModelProviderId,
+            cx: &gpui::App,
+        ) -> bool {
             $(
                 if name == <$tool>::NAME {
-                    return <$tool>::supports_provider(provider);
+                    return <$tool>::supports_provider(provider, cx);
                 }
             )*
             false
@@ -126,16 +157,28 @@ tools! {
     EditFileTool,
     FetchTool,
     FindPathTool,
+    FindReferencesTool,
+    GitBlameTool,
+    GitCommitTool,
+    GitDiffTool,
+    GitLogTool,
+    GitStatusTool,
+    GoToDefinitionTool,
     GrepTool,
+    HoverTool,
     ListDirectoryTool,
+    ListTasksTool,
     MovePathTool,
     NowTool,
     OpenTool,
     ReadFileTool,
+    RenameSymbolTool,
     RestoreFileFromDiskTool,
+    RunTaskTool,
     SaveFileTool,
     SpawnAgentTool,
     TerminalTool,
     UpdatePlanTool,
     WebSearchTool,
+    WorkspaceSymbolsTool,
 }
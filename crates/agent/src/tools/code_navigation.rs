@@ -0,0 +1,282 @@
+//! Helpers shared by the tools that answer questions using the project's language servers.
+
+use crate::ToolCallEventStream;
+use futures::FutureExt as _;
+use gpui::{App, AsyncApp, Entity};
+use language::{Buffer, BufferSnapshot, Location, Point, ToPoint as _};
+use project::{Project, ProjectPath, WorktreeSettings, lsp_store::OpenLspBufferHandle};
+use settings::Settings;
+use std::{fmt::Write, path::PathBuf};
+
+/// A symbol occurrence resolved from a tool's `path`, `line` and `symbol` input.
+pub(crate) struct ResolvedSymbol {
+    pub buffer: Entity<Buffer>,
+    pub position: Point,
+    /// Keeps the buffer registered with its language servers while the request runs.
+    pub _lsp_handle: OpenLspBufferHandle,
+}
+
+/// Opens the file at `path` and finds `symbol` on the given 1-based `line`.
+///
+/// The error is suitable for returning to the model.
+pub(crate) async fn resolve_symbol(
+    project: &Entity<Project>,
+    path: &str,
+    line: u32,
+    symbol: &str,
+    event_stream: &ToolCallEventStream,
+    cx: &mut AsyncApp,
+) -> Result<ResolvedSymbol, String> {
+    if symbol.is_empty() {
+        return Err("The symbol must not be empty".to_string());
+    }
+
+    let open_buffer_task = project.update(cx, |project, cx| {
+        let project_path = project
+            .find_project_path(path, cx)
+            .ok_or_else(|| format!("Could not find path {path} in project"))?;
+        if is_hidden_path(&project_path, cx) {
+            return Err(format!(
+                "Cannot access {path} because it matches the `file_scan_exclusions` or `private_files` setting"
+            ));
+        }
+        Ok(project.open_buffer(project_path, cx))
+    })?;
+
+    let buffer = futures::select! {
+        result = open_buffer_task.fuse() => result.map_err(|error| error.to_string())?,
+        _ = event_stream.cancelled_by_user().fuse() => {
+            return Err("Cancelled by user".to_string());
+        }
+    };
+
+    let lsp_handle = project.update(cx, |project, cx| {
+        project.register_buffer_with_language_servers(&buffer, cx)
+    });
+
+    let position = buffer.read_with(cx, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        let row = line.checked_sub(1).ok_or("Line numbers start at 1")?;
+        if row > snapshot.max_point().row {
+            return Err(format!(
+                "Line {line} is out of range, {path} has {} lines",
+                snapshot.max_point().row + 1
+            ));
+        }
+        let line_text = line_text(&snapshot, row);
+        let column = find_symbol_in_line(&line_text, symbol).ok_or_else(|| {
+            format!(
+                "Could not find `{symbol}` on line {line} of {path}. The line reads: `{}`",
+                line_text.trim()
+            )
+        })?;
+        Ok(Point::new(row, column as u32))
+    })?;
+
+    Ok(ResolvedSymbol {
+        buffer,
+        position,
+        _lsp_handle: lsp_handle,
+    })
+}
+
+/// Returns the byte offset of `symbol` in `line`, preferring an occurrence
+/// that isn't part of a longer identifier.
+fn find_symbol_in_line(line: &str, symbol: &str) -> Option<usize> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_';
+    let mut fallback = None;
+    for (offset, _) in line.match_indices(symbol) {
+        let before = line[..offset].chars().next_back();
+        let after = line[offset + symbol.len()..].chars().next();
+        if !before.is_some_and(is_word_char) && !after.is_some_and(is_word_char) {
+            return Some(offset);
+        }
+        fallback.get_or_insert(offset);
+    }
+    fallback
+}
+
+fn line_text(snapshot: &BufferSnapshot, row: u32) -> String {
+    snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+        .collect()
+}
+
+/// Whether the agent shouldn't see the contents of the given path.
+pub(crate) fn is_hidden_path(project_path: &ProjectPath, cx: &App) -> bool {
+    let global_settings = WorktreeSettings::get_global(cx);
+    let worktree_settings = WorktreeSettings::get(Some(project_path.into()), cx);
+    global_settings.is_path_excluded(&project_path.path)
+        || global_settings.is_path_private(&project_path.path)
+        || worktree_settings.is_path_excluded(&project_path.path)
+        || worktree_settings.is_path_private(&project_path.path)
+}
+
+/// A location returned by a language server, described for the model.
+pub(crate) struct DescribedLocation {
+    pub path: PathBuf,
+    pub start: Point,
+    pub line_text: String,
+}
+
+/// Describes a language server location, or returns `None` when it points
+/// at a file the agent isn't allowed to see.
+pub(crate) fn describe_location(location: &Location, cx: &App) -> Option<DescribedLocation> {
+    let buffer = location.buffer.read(cx);
+    let file = buffer.file()?;
+    let project_path = ProjectPath {
+        worktree_id: file.worktree_id(cx),
+        path: file.path().clone(),
+    };
+    if is_hidden_path(&project_path, cx) {
+        return None;
+    }
+
+    let snapshot = buffer.snapshot();
+    let start = location.range.start.to_point(&snapshot);
+    Some(DescribedLocation {
+        path: file.full_path(cx),
+        start,
+        line_text: line_text(&snapshot, start.row).trim().to_string(),
+    })
+}
+
+/// Writes up to `max_lines` lines of source starting at the location's first line.
+pub(crate) fn write_excerpt(
+    output: &mut String,
+    location: &Location,
+    max_lines: u32,
+    cx: &App,
+) -> Option<()> {
+    let buffer = location.buffer.read(cx);
+    let snapshot = buffer.snapshot();
+    let start = location.range.start.to_point(&snapshot);
+    let last_row = (start.row + max_lines.saturating_sub(1)).min(snapshot.max_point().row);
+    let language = buffer
+        .language()
+        .map(|language| language.code_fence_block_name().to_string())
+        .unwrap_or_default();
+
+    writeln!(output, "```{language}").ok()?;
+    for row in start.row..=last_row {
+        writeln!(output, "{}", line_text(&snapshot, row)).ok()?;
+    }
+    writeln!(output, "```").ok()
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+    use agent_settings::AgentSettings;
+    use futures::StreamExt as _;
+    use gpui::{TestAppContext, UpdateGlobal as _};
+    use language::{LanguageConfig, LanguageMatcher};
+    use lsp::FakeLanguageServer;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::sync::Arc;
+    use util::path;
+
+    pub(crate) fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+        cx.update(|cx| {
+            let mut settings = AgentSettings::get_global(cx).clone();
+            settings.tool_permissions.default = settings::ToolPermissionMode::Allow;
+            AgentSettings::override_global(settings, cx);
+        });
+    }
+
+    /// Makes `generated.rs` files private, so the agent can't see them.
+    pub(crate) fn hide_generated_files(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings.project.worktree.private_files =
+                        Some(vec!["**/generated.rs".to_string()].into());
+                });
+            });
+        });
+    }
+
+    /// Opens `/root/src/main.rs` with a fake Rust language server that has the given
+    /// capabilities. `/root/notes.txt` has no language server.
+    pub(crate) async fn setup(
+        capabilities: lsp::ServerCapabilities,
+        cx: &mut TestAppContext,
+    ) -> (Entity<Project>, FakeLanguageServer, OpenLspBufferHandle) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "notes.txt": "Remember to parse the input.\n",
+                "src": {
+                    "main.rs": "fn parse() {}\nfn main() {\n    parse();\n}\n",
+                    "lib.rs": "pub fn run() {\n    crate::parse();\n}\n",
+                    "generated.rs": "pub use crate::parse;\n",
+                }
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(Arc::new(language::Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        )));
+        let mut fake_language_servers = language_registry.register_fake_lsp(
+            "Rust",
+            language::FakeLspAdapter {
+                capabilities,
+                ..Default::default()
+            },
+        );
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/root/src/main.rs"), cx)
+            })
+            .await
+            .unwrap();
+        let lsp_handle = project.update(cx, |project, cx| {
+            project.register_buffer_with_language_servers(&buffer, cx)
+        });
+        let fake_language_server = fake_language_servers.next().await.unwrap();
+        cx.executor().run_until_parked();
+
+        (project, fake_language_server, lsp_handle)
+    }
+
+    /// A location spanning `parse` at the given position of the file.
+    pub(crate) fn parse_location(path: &str, line: u32, column: u32) -> lsp::Location {
+        lsp::Location::new(
+            lsp::Uri::from_file_path(path).unwrap(),
+            lsp::Range::new(
+                lsp::Position::new(line, column),
+                lsp::Position::new(line, column + "parse".len() as u32),
+            ),
+        )
+    }
+
+    #[test]
+    fn test_find_symbol_in_line() {
+        assert_eq!(
+            find_symbol_in_line("let foo = foo_bar(foo);", "foo"),
+            Some(4)
+        );
+        assert_eq!(find_symbol_in_line("let foo_bar = foo;", "foo"), Some(14));
+        assert_eq!(find_symbol_in_line("let foo_bar = 1;", "foo"), Some(4));
+        assert_eq!(find_symbol_in_line("let bar = 1;", "foo"), None);
+    }
+}
@@ -3,7 +3,7 @@ use agent_client_protocol::ToolKind;
 use anyhow::Result;
 use collections::{BTreeMap, HashMap};
 use context_server::{ContextServerId, client::NotificationSubscription};
-use futures::FutureExt as _;
+use futures::{FutureExt as _, StreamExt as _, channel::mpsc};
 use gpui::{App, AppContext, AsyncApp, Context, Entity, EventEmitter, SharedString, Task};
 use project::context_server_store::{ContextServerStatus, ContextServerStore};
 use std::sync::Arc;
@@ -21,9 +21,25 @@ pub struct ContextServerPrompt {
     pub prompt: context_server::types::Prompt,
 }
 
+pub struct ContextServerResource {
+    pub server_id: ContextServerId,
+    pub resource: context_server::types::Resource,
+}
+
+pub struct ContextServerResourceTemplate {
+    pub server_id: ContextServerId,
+    pub template: context_server::types::ResourceTemplate,
+}
+
 pub enum ContextServerRegistryEvent {
     ToolsChanged,
     PromptsChanged,
+    ResourcesChanged,
+    /// A resource the client subscribed to has changed on the server.
+    ResourceUpdated {
+        server_id: ContextServerId,
+        uri: String,
+    },
 }
 
 impl EventEmitter<ContextServerRegistryEvent> for ContextServerRegistry {}
@@ -31,27 +47,44 @@ impl EventEmitter<ContextServerRegistryEvent> for ContextServerRegistry {}
 pub struct ContextServerRegistry {
     server_store: Entity<ContextServerStore>,
     registered_servers: HashMap<ContextServerId, RegisteredContextServer>,
+    /// How many [`ResourceWatch`]es are alive for each subscribed resource.
+    resource_watchers: HashMap<(ContextServerId, String), usize>,
+    dropped_resource_watches_tx: mpsc::UnboundedSender<(ContextServerId, String)>,
     _subscription: gpui::Subscription,
+    _unwatch_dropped_resources: Task<Result<()>>,
 }
 
 struct RegisteredContextServer {
     tools: BTreeMap<SharedString, Arc<dyn AnyAgentTool>>,
     prompts: BTreeMap<SharedString, ContextServerPrompt>,
+    resources: BTreeMap<SharedString, ContextServerResource>,
+    resource_templates: Vec<ContextServerResourceTemplate>,
     load_tools: Task<Result<()>>,
     load_prompts: Task<Result<()>>,
-    _tools_updated_subscription: Option<NotificationSubscription>,
+    load_resources: Task<Result<()>>,
+    _notification_subscriptions: Vec<NotificationSubscription>,
 }
 
 impl ContextServerRegistry {
     pub fn new(server_store: Entity<ContextServerStore>, cx: &mut Context<Self>) -> Self {
+        let (dropped_resource_watches_tx, mut dropped_resource_watches_rx) = mpsc::unbounded();
         let mut this = Self {
             server_store: server_store.clone(),
             registered_servers: HashMap::default(),
+            resource_watchers: HashMap::default(),
+            dropped_resource_watches_tx,
             _subscription: cx.subscribe(&server_store, Self::handle_context_server_store_event),
+            _unwatch_dropped_resources: cx.spawn(async move |this, cx| {
+                while let Some((server_id, uri)) = dropped_resource_watches_rx.next().await {
+                    this.update(cx, |this, cx| this.unwatch_resource(server_id, uri, cx))?;
+                }
+                Ok(())
+            }),
         };
         for server in server_store.read(cx).running_servers() {
             this.reload_tools_for_server(server.id(), cx);
             this.reload_prompts_for_server(server.id(), cx);
+            this.reload_resources_for_server(server.id(), cx);
         }
         this
     }
@@ -86,6 +119,18 @@ impl ContextServerRegistry {
             .flat_map(|server| server.prompts.values())
     }
 
+    pub fn resources(&self) -> impl Iterator<Item = &ContextServerResource> {
+        self.registered_servers
+            .values()
+            .flat_map(|server| server.resources.values())
+    }
+
+    pub fn resource_templates(&self) -> impl Iterator<Item = &ContextServerResourceTemplate> {
+        self.registered_servers
+            .values()
+            .flat_map(|server| server.resource_templates.iter())
+    }
+
     pub fn find_prompt(
         &self,
         server_id: Option<&ContextServerId>,
@@ -106,6 +151,60 @@ impl ContextServerRegistry {
         &self.server_store
     }
 
+    /// Subscribes to updates of a resource, which are reported as
+    /// [`ContextServerRegistryEvent::ResourceUpdated`]. The subscription lasts
+    /// until the last watch on the resource is dropped.
+    pub fn watch_resource(
+        &mut self,
+        server_id: ContextServerId,
+        uri: String,
+        cx: &mut Context<Self>,
+    ) -> ResourceWatch {
+        let watchers = self
+            .resource_watchers
+            .entry((server_id.clone(), uri.clone()))
+            .or_default();
+        *watchers += 1;
+        if *watchers == 1 {
+            let server_store = self.server_store.clone();
+            let server_id = server_id.clone();
+            let uri = uri.clone();
+            cx.spawn(async move |_, cx| {
+                subscribe_to_resource(&server_store, &server_id, &uri, cx).await
+            })
+            .detach_and_log_err(cx);
+        }
+        ResourceWatch {
+            server_id,
+            uri,
+            dropped_tx: self.dropped_resource_watches_tx.clone(),
+        }
+    }
+
+    fn unwatch_resource(
+        &mut self,
+        server_id: ContextServerId,
+        uri: String,
+        cx: &mut Context<Self>,
+    ) {
+        let key = (server_id, uri);
+        let Some(watchers) = self.resource_watchers.get_mut(&key) else {
+            return;
+        };
+        *watchers -= 1;
+        if *watchers > 0 {
+            return;
+        }
+
+        self.resource_watchers.remove(&key);
+        let (server_id, uri) = key;
+        let server_store = self.server_store.clone();
+        cx.spawn(async move |_, cx| {
+            unsubscribe_from_resource(&server_store, &server_id, &uri, cx).await
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn get_or_register_server(
         &mut self,
         server_id: &ContextServerId,
@@ -121,20 +220,18 @@ impl ContextServerRegistry {
         server_store: &Entity<ContextServerStore>,
         cx: &mut Context<Self>,
     ) -> RegisteredContextServer {
-        let tools_updated_subscription = server_store
+        let mut notification_subscriptions = Vec::new();
+        if let Some(client) = server_store
             .read(cx)
             .get_running_server(server_id)
-            .and_then(|server| {
-                let client = server.client()?;
-
-                if !client.capable(context_server::protocol::ServerCapability::Tools) {
-                    return None;
-                }
-
-                let server_id = server.id();
-                let this = cx.entity().downgrade();
-
-                Some(client.on_notification(
+            .and_then(|server| server.client())
+        {
+            let this = cx.entity().downgrade();
+
+            if client.capable(context_server::protocol::ServerCapability::Tools) {
+                let server_id = server_id.clone();
+                let this = this.clone();
+                notification_subscriptions.push(client.on_notification(
                     "notifications/tools/list_changed",
                     Box::new(move |_params, cx: AsyncApp| {
                         let server_id = server_id.clone();
@@ -150,15 +247,65 @@ impl ContextServerRegistry {
                         })
                         .detach();
                     }),
-                ))
-            });
+                ));
+            }
+
+            if client.capable(context_server::protocol::ServerCapability::Resources) {
+                let list_changed_server_id = server_id.clone();
+                let list_changed_this = this.clone();
+                notification_subscriptions.push(client.on_notification(
+                    "notifications/resources/list_changed",
+                    Box::new(move |_params, cx: AsyncApp| {
+                        let server_id = list_changed_server_id.clone();
+                        let this = list_changed_this.clone();
+                        cx.spawn(async move |cx| {
+                            this.update(cx, |this, cx| {
+                                log::info!(
+                                    "Received resources/list_changed notification for server {}",
+                                    server_id
+                                );
+                                this.reload_resources_for_server(server_id, cx);
+                            })
+                        })
+                        .detach();
+                    }),
+                ));
+
+                let server_id = server_id.clone();
+                notification_subscriptions.push(client.on_notification(
+                    "notifications/resources/updated",
+                    Box::new(move |params, cx: AsyncApp| {
+                        let Some(params) = serde_json::from_value::<
+                            context_server::types::ResourcesUpdatedParams,
+                        >(params)
+                        .log_err() else {
+                            return;
+                        };
+                        let server_id = server_id.clone();
+                        let this = this.clone();
+                        cx.spawn(async move |cx| {
+                            this.update(cx, |_, cx| {
+                                cx.emit(ContextServerRegistryEvent::ResourceUpdated {
+                                    server_id,
+                                    uri: params.uri,
+                                });
+                            })
+                        })
+                        .detach();
+                    }),
+                ));
+            }
+        }
 
         RegisteredContextServer {
             tools: BTreeMap::default(),
             prompts: BTreeMap::default(),
+            resources: BTreeMap::default(),
+            resource_templates: Vec::new(),
             load_tools: Task::ready(Ok(())),
             load_prompts: Task::ready(Ok(())),
-            _tools_updated_subscription: tools_updated_subscription,
+            load_resources: Task::ready(Ok(())),
+            _notification_subscriptions: notification_subscriptions,
         }
     }
 
@@ -244,6 +391,64 @@ impl ContextServerRegistry {
         });
     }
 
+    fn reload_resources_for_server(&mut self, server_id: ContextServerId, cx: &mut Context<Self>) {
+        let Some(server) = self.server_store.read(cx).get_running_server(&server_id) else {
+            return;
+        };
+        let Some(client) = server.client() else {
+            return;
+        };
+        if !client.capable(context_server::protocol::ServerCapability::Resources) {
+            return;
+        }
+
+        let registered_server = self.get_or_register_server(&server_id, cx);
+
+        registered_server.load_resources = cx.spawn(async move |this, cx| {
+            let (resources, templates) = futures::join!(
+                client.request::<context_server::types::requests::ResourcesList>(()),
+                client.request::<context_server::types::requests::ListResourceTemplates>(()),
+            );
+
+            this.update(cx, |this, cx| {
+                let Some(registered_server) = this.registered_servers.get_mut(&server_id) else {
+                    return;
+                };
+
+                registered_server.resources.clear();
+                if let Some(response) = resources.log_err() {
+                    for resource in response.resources {
+                        registered_server.resources.insert(
+                            resource.uri.to_string().into(),
+                            ContextServerResource {
+                                server_id: server_id.clone(),
+                                resource,
+                            },
+                        );
+                    }
+                }
+
+                // Templates are optional, so servers that don't implement the
+                // request just don't contribute any.
+                registered_server.resource_templates = templates
+                    .map(|response| {
+                        response
+                            .resource_templates
+                            .into_iter()
+                            .map(|template| ContextServerResourceTemplate {
+                                server_id: server_id.clone(),
+                                template,
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+
+                cx.emit(ContextServerRegistryEvent::ResourcesChanged);
+                cx.notify();
+            })
+        });
+    }
+
     fn handle_context_server_store_event(
         &mut self,
         _: Entity<ContextServerStore>,
@@ -257,6 +462,7 @@ impl ContextServerRegistry {
             ContextServerStatus::Running => {
                 self.reload_tools_for_server(server_id.clone(), cx);
                 self.reload_prompts_for_server(server_id.clone(), cx);
+                self.reload_resources_for_server(server_id.clone(), cx);
             }
             ContextServerStatus::Stopped
             | ContextServerStatus::Error(_)
@@ -268,6 +474,11 @@ impl ContextServerRegistry {
                     if !registered_server.prompts.is_empty() {
                         cx.emit(ContextServerRegistryEvent::PromptsChanged);
                     }
+                    if !registered_server.resources.is_empty()
+                        || !registered_server.resource_templates.is_empty()
+                    {
+                        cx.emit(ContextServerRegistryEvent::ResourcesChanged);
+                    }
                 }
                 cx.notify();
             }
@@ -457,6 +668,122 @@ pub fn get_prompt(
     })
 }
 
+pub fn read_resource(
+    server_store: &Entity<ContextServerStore>,
+    server_id: &ContextServerId,
+    uri: &str,
+    cx: &mut AsyncApp,
+) -> Task<Result<context_server::types::ResourcesReadResponse>> {
+    let server = cx.update(|cx| server_store.read(cx).get_running_server(server_id));
+    let Some(server) = server else {
+        return Task::ready(Err(anyhow::anyhow!("Context server not found")));
+    };
+
+    let Some(protocol) = server.client() else {
+        return Task::ready(Err(anyhow::anyhow!("Context server not initialized")));
+    };
+
+    let uri = match url::Url::parse(uri) {
+        Ok(uri) => uri,
+        Err(error) => return Task::ready(Err(error.into())),
+    };
+
+    cx.background_spawn(async move {
+        let response = protocol
+            .request::<context_server::types::requests::ResourcesRead>(
+                context_server::types::ResourcesReadParams { uri, meta: None },
+            )
+            .await?;
+
+        Ok(response)
+    })
+}
+
+/// Keeps a resource subscribed to while it's alive. See
+/// [`ContextServerRegistry::watch_resource`].
+pub struct ResourceWatch {
+    server_id: ContextServerId,
+    uri: String,
+    dropped_tx: mpsc::UnboundedSender<(ContextServerId, String)>,
+}
+
+impl Drop for ResourceWatch {
+    fn drop(&mut self) {
+        self.dropped_tx
+            .unbounded_send((self.server_id.clone(), std::mem::take(&mut self.uri)))
+            .ok();
+    }
+}
+
+/// Returns the server's client if it's running and supports resource subscriptions.
+fn subscribable_server_client(
+    server_store: &Entity<ContextServerStore>,
+    server_id: &ContextServerId,
+    cx: &mut AsyncApp,
+) -> Option<Arc<context_server::protocol::InitializedContextServerProtocol>> {
+    let server = cx.update(|cx| server_store.read(cx).get_running_server(server_id));
+    let protocol = server.and_then(|server| server.client())?;
+    protocol
+        .initialize
+        .capabilities
+        .resources
+        .as_ref()
+        .and_then(|resources| resources.subscribe)
+        .unwrap_or(false)
+        .then_some(protocol)
+}
+
+/// Asks the server to send `notifications/resources/updated` when the resource
+/// changes. Does nothing for servers that don't support subscriptions.
+fn subscribe_to_resource(
+    server_store: &Entity<ContextServerStore>,
+    server_id: &ContextServerId,
+    uri: &str,
+    cx: &mut AsyncApp,
+) -> Task<Result<()>> {
+    let Some(protocol) = subscribable_server_client(server_store, server_id, cx) else {
+        return Task::ready(Ok(()));
+    };
+    let uri = match url::Url::parse(uri) {
+        Ok(uri) => uri,
+        Err(error) => return Task::ready(Err(error.into())),
+    };
+
+    cx.background_spawn(async move {
+        protocol
+            .request::<context_server::types::requests::ResourcesSubscribe>(
+                context_server::types::ResourcesSubscribeParams { uri, meta: None },
+            )
+            .await?;
+        Ok(())
+    })
+}
+
+/// Asks the server to stop sending updates for the resource.
+fn unsubscribe_from_resource(
+    server_store: &Entity<ContextServerStore>,
+    server_id: &ContextServerId,
+    uri: &str,
+    cx: &mut AsyncApp,
+) -> Task<Result<()>> {
+    let Some(protocol) = subscribable_server_client(server_store, server_id, cx) else {
+        return Task::ready(Ok(()));
+    };
+    let uri = match url::Url::parse(uri) {
+        Ok(uri) => uri,
+        Err(error) => return Task::ready(Err(error.into())),
+    };
+
+    cx.background_spawn(async move {
+        protocol
+            .request::<context_server::types::requests::ResourcesUnsubscribe>(
+                context_server::types::ResourcesUnsubscribeParams { uri, meta: None },
+            )
+            .await?;
+        Ok(())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,285 @@
+use super::code_navigation::{describe_location, resolve_symbol};
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+const RESULTS_PER_PAGE: usize = 50;
+
+/// Finds all references to a symbol across the project, using the language server for the file.
+///
+/// Prefer this tool over `grep` when looking for usages of a function, type or variable, since it only returns real references to that exact symbol rather than every textual match.
+///
+/// The symbol is identified by the file it appears in, the line it's on and its name. Results are grouped by file and paginated with 50 references per page. Use the optional `offset` parameter to request subsequent pages.
+///
+/// <example>
+/// To find every use of the `Config` struct declared on line 10 of `src/config.rs`:
+/// {
+///     "path": "project/src/config.rs",
+///     "line": 10,
+///     "symbol": "Config"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct FindReferencesToolInput {
+    /// The path of the file containing the symbol.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based line number the symbol appears on.
+    pub line: u32,
+    /// The name of the symbol, exactly as it appears on that line.
+    pub symbol: String,
+    /// Optional starting position for paginated results (0-based).
+    /// When not provided, starts from the beginning.
+    #[serde(default)]
+    pub offset: usize,
+}
+
+pub struct FindReferencesTool {
+    project: Entity<Project>,
+}
+
+impl FindReferencesTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for FindReferencesTool {
+    type Input = FindReferencesToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "find_references";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Find references to {}", MarkdownInlineCode(&input.symbol)).into(),
+            Err(_) => "Find references".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+
+            let resolved = resolve_symbol(
+                &project,
+                &input.path,
+                input.line,
+                &input.symbol,
+                &event_stream,
+                cx,
+            )
+            .await?;
+
+            let references_task = project.update(cx, |project, cx| {
+                project.references(&resolved.buffer, resolved.position, cx)
+            });
+            let references = futures::select! {
+                result = references_task.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Find references cancelled by user".to_string());
+                }
+            }
+            .unwrap_or_default();
+
+            let mut references = cx.update(|cx| {
+                references
+                    .iter()
+                    .filter_map(|location| describe_location(location, cx))
+                    .collect::<Vec<_>>()
+            });
+            references.sort_by(|a, b| a.path.cmp(&b.path).then(a.start.cmp(&b.start)));
+            references.dedup_by(|a, b| a.path == b.path && a.start == b.start);
+
+            if references.is_empty() {
+                return Ok(format!(
+                    "No references found for `{}`. The language server may not support this file or may still be starting.",
+                    input.symbol
+                ));
+            }
+
+            let total = references.len();
+            let page = references
+                .iter()
+                .skip(input.offset)
+                .take(RESULTS_PER_PAGE)
+                .collect::<Vec<_>>();
+            if page.is_empty() {
+                return Ok(format!(
+                    "No references past offset {}, there are {total} in total.",
+                    input.offset
+                ));
+            }
+
+            let mut output = format!("Found {total} references to `{}`.\n", input.symbol);
+            let mut current_path = None;
+            for reference in &page {
+                if current_path != Some(&reference.path) {
+                    writeln!(output, "\n## {}", reference.path.display()).ok();
+                    current_path = Some(&reference.path);
+                }
+                writeln!(
+                    output,
+                    "- L{}: {}",
+                    reference.start.row + 1,
+                    reference.line_text
+                )
+                .ok();
+            }
+
+            let shown = input.offset + page.len();
+            if shown < total {
+                writeln!(
+                    output,
+                    "\nShowing references {}-{shown} of {total}. Use offset: {shown} to see more.",
+                    input.offset + 1,
+                )
+                .ok();
+            }
+
+            Ok(output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::code_navigation::tests::{
+        hide_generated_files, init_test, parse_location, setup,
+    };
+    use gpui::TestAppContext;
+    use util::path;
+
+    fn capabilities() -> lsp::ServerCapabilities {
+        lsp::ServerCapabilities {
+            references_provider: Some(lsp::OneOf::Left(true)),
+            ..Default::default()
+        }
+    }
+
+    fn input(path: &str, line: u32, offset: usize) -> FindReferencesToolInput {
+        FindReferencesToolInput {
+            path: path.into(),
+            line,
+            symbol: "parse".into(),
+            offset,
+        }
+    }
+
+    async fn run(
+        project: Entity<Project>,
+        input: FindReferencesToolInput,
+        cx: &mut TestAppContext,
+    ) -> Result<String, String> {
+        let tool = Arc::new(FindReferencesTool::new(project));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        cx.update(|cx| tool.run(ToolInput::resolved(input), event_stream, cx))
+            .await
+    }
+
+    #[gpui::test]
+    async fn test_find_references(cx: &mut TestAppContext) {
+        init_test(cx);
+        hide_generated_files(cx);
+        let (project, fake_language_server, _lsp_handle) = setup(capabilities(), cx).await;
+        fake_language_server.set_request_handler::<lsp::request::References, _, _>(
+            |_, _| async move {
+                Ok(Some(vec![
+                    parse_location(path!("/root/src/main.rs"), 2, 4),
+                    parse_location(path!("/root/src/generated.rs"), 0, 15),
+                    parse_location(path!("/root/src/main.rs"), 0, 3),
+                    parse_location(path!("/root/src/lib.rs"), 1, 11),
+                    parse_location(path!("/root/src/main.rs"), 2, 4),
+                ]))
+            },
+        );
+
+        // References are grouped by file and deduplicated, and those in private files are left
+        // out.
+        let output = run(project.clone(), input("root/src/main.rs", 1, 0), cx)
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "Found 3 references to `parse`.\n\n## {}\n- L2: crate::parse();\n\n## {}\n- L1: fn parse() {{}}\n- L3: parse();\n",
+                path!("root/src/lib.rs"),
+                path!("root/src/main.rs"),
+            )
+        );
+
+        let output = run(project.clone(), input("root/src/main.rs", 1, 2), cx)
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "Found 3 references to `parse`.\n\n## {}\n- L3: parse();\n",
+                path!("root/src/main.rs"),
+            )
+        );
+
+        let output = run(project, input("root/src/main.rs", 1, 5), cx)
+            .await
+            .unwrap();
+        assert_eq!(output, "No references past offset 5, there are 3 in total.");
+    }
+
+    #[gpui::test]
+    async fn test_find_references_without_results(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (project, fake_language_server, _lsp_handle) = setup(capabilities(), cx).await;
+        fake_language_server
+            .set_request_handler::<lsp::request::References, _, _>(|_, _| async move { Ok(None) });
+
+        let output = run(project.clone(), input("root/src/main.rs", 1, 0), cx)
+            .await
+            .unwrap();
+        assert!(output.starts_with("No references found"), "{output}");
+
+        // No language server handles text files.
+        let output = run(project, input("root/notes.txt", 1, 0), cx)
+            .await
+            .unwrap();
+        assert!(output.starts_with("No references found"), "{output}");
+    }
+
+    #[gpui::test]
+    async fn test_find_references_rejects_private_files(cx: &mut TestAppContext) {
+        init_test(cx);
+        hide_generated_files(cx);
+        let (project, _fake_language_server, _lsp_handle) = setup(capabilities(), cx).await;
+
+        let error = run(project, input("root/src/generated.rs", 1, 0), cx)
+            .await
+            .unwrap_err();
+        assert!(error.contains("private_files"), "{error}");
+    }
+}
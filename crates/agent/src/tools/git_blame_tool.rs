@@ -0,0 +1,305 @@
+use super::git_repository::{format_commit_date, short_sha};
+use super::project_tasks::open_buffer;
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use language::Point;
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+const MAX_LINES: u32 = 400;
+
+/// Shows which commit last changed each line in a range of a file, along with the commit's author, date and subject.
+///
+/// Use this tool to find out why or when code was written, then use `git_log` or `git_diff` to dig further. Use it instead of running `git blame` in the terminal.
+///
+/// <example>
+/// To blame lines 10 through 25 of `project/src/main.rs`:
+/// {
+///     "path": "project/src/main.rs",
+///     "start_line": 10,
+///     "end_line": 25
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GitBlameToolInput {
+    /// The path of the file to blame.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based line to start at. Defaults to the start of the file.
+    #[serde(default)]
+    pub start_line: Option<u32>,
+    /// The 1-based line to end at, inclusive. Defaults to the end of the file.
+    #[serde(default)]
+    pub end_line: Option<u32>,
+}
+
+pub struct GitBlameTool {
+    project: Entity<Project>,
+}
+
+impl GitBlameTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for GitBlameTool {
+    type Input = GitBlameToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "git_blame";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => match (input.start_line, input.end_line) {
+                (Some(start), Some(end)) => format!(
+                    "Git blame {} (lines {start}-{end})",
+                    MarkdownInlineCode(&input.path)
+                ),
+                _ => format!("Git blame {}", MarkdownInlineCode(&input.path)),
+            }
+            .into(),
+            Err(_) => "Git blame".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+            let path = input.path;
+
+            let buffer = open_buffer(&project, &path, cx).await?;
+            let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+            let line_count = snapshot.max_point().row + 1;
+            let start_line = input.start_line.unwrap_or(1).max(1);
+            let requested_end_line = input.end_line.unwrap_or(line_count).min(line_count);
+            if start_line > requested_end_line {
+                return Err(format!(
+                    "Invalid line range {start_line}-{requested_end_line}, {path} has {line_count} lines"
+                ));
+            }
+            let end_line = requested_end_line.min(start_line + MAX_LINES - 1);
+            let rows = start_line - 1..end_line;
+
+            let blame = project.update(cx, |project, cx| {
+                project.git_store().update(cx, |git_store, cx| {
+                    git_store.blame_buffer(&buffer, None, cx)
+                })
+            });
+            let blame = futures::select! {
+                result = blame.fuse() => result.map_err(|error| format!("{error:#}"))?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Git blame cancelled by user".to_string());
+                }
+            };
+            let blame = blame.ok_or_else(|| format!("{path} isn't in a git repository"))?;
+
+            let mut output = format!("# Blame of {path}, lines {start_line}-{end_line}\n");
+            let mut next_row = rows.start;
+            let write_lines = |output: &mut String, lines: std::ops::Range<u32>| {
+                for row in lines {
+                    let text = snapshot
+                        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(row)))
+                        .collect::<String>();
+                    writeln!(output, "{:>5} | {text}", row + 1).ok();
+                }
+            };
+            for entry in &blame.entries {
+                let lines = entry.range.start.max(rows.start)..entry.range.end.min(rows.end);
+                if lines.is_empty() {
+                    continue;
+                }
+                if next_row < lines.start {
+                    output.push_str("\nNot blamed:\n");
+                    write_lines(&mut output, next_row..lines.start);
+                }
+                let sha = entry.sha.to_string();
+                if sha.bytes().all(|byte| byte == b'0') {
+                    output.push_str("\nNot committed yet:\n");
+                } else {
+                    let summary = entry
+                        .summary
+                        .as_deref()
+                        .or_else(|| {
+                            blame
+                                .messages
+                                .get(&entry.sha)
+                                .and_then(|message| message.lines().next())
+                        })
+                        .unwrap_or_default();
+                    writeln!(
+                        output,
+                        "\n{} {} {}: {summary}",
+                        MarkdownInlineCode(short_sha(&sha)),
+                        entry
+                            .author_time
+                            .map(format_commit_date)
+                            .unwrap_or_default(),
+                        entry.author.as_deref().unwrap_or("Unknown author"),
+                    )
+                    .ok();
+                }
+                write_lines(&mut output, lines.clone());
+                next_row = lines.end;
+            }
+            if next_row < rows.end {
+                output.push_str("\nNot blamed:\n");
+                write_lines(&mut output, next_row..rows.end);
+            }
+            if end_line < requested_end_line {
+                write!(
+                    output,
+                    "\nOnly {MAX_LINES} lines can be blamed at once. Pass a `start_line` of {} to continue.",
+                    end_line + 1
+                )
+                .ok();
+            }
+            Ok(output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::{
+        blame::{Blame, BlameEntry},
+        repository::repo_path,
+    };
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use util::path;
+
+    async fn setup(cx: &mut TestAppContext) -> Entity<Project> {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                ".git": {},
+                ".env": "SECRET=1",
+                "file.txt": "one\ntwo\nthree",
+            }),
+        )
+        .await;
+        fs.set_blame_for_repo(
+            Path::new(path!("/root/.git")),
+            vec![(
+                repo_path("file.txt"),
+                Blame {
+                    entries: vec![
+                        BlameEntry {
+                            sha: "1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b1b".parse().unwrap(),
+                            range: 0..1,
+                            author: Some("Alice".into()),
+                            author_time: Some(1709251200),
+                            summary: Some("Add one".into()),
+                            ..Default::default()
+                        },
+                        BlameEntry {
+                            sha: "0000000000000000000000000000000000000000".parse().unwrap(),
+                            range: 1..2,
+                            ..Default::default()
+                        },
+                    ],
+                    ..Default::default()
+                },
+            )],
+        );
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+        cx.executor().run_until_parked();
+        project
+    }
+
+    async fn run(
+        project: Entity<Project>,
+        path: &str,
+        start_line: Option<u32>,
+        end_line: Option<u32>,
+        cx: &mut TestAppContext,
+    ) -> Result<String, String> {
+        let tool = Arc::new(GitBlameTool::new(project));
+        let input = GitBlameToolInput {
+            path: path.into(),
+            start_line,
+            end_line,
+        };
+        cx.update(|cx| {
+            tool.run(
+                ToolInput::resolved(input),
+                ToolCallEventStream::test().0,
+                cx,
+            )
+        })
+        .await
+    }
+
+    #[gpui::test]
+    async fn test_git_blame(cx: &mut TestAppContext) {
+        let project = setup(cx).await;
+
+        let output = run(project.clone(), "root/file.txt", None, None, cx)
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "# Blame of root/file.txt, lines 1-3\n\
+             \n`1b1b1b1` 2024-03-01 Alice: Add one\n    1 | one\n\
+             \nNot committed yet:\n    2 | two\n\
+             \nNot blamed:\n    3 | three\n"
+        );
+
+        let output = run(project.clone(), "root/file.txt", Some(2), Some(2), cx)
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "# Blame of root/file.txt, lines 2-2\n\nNot committed yet:\n    2 | two\n"
+        );
+
+        let error = run(project, "root/file.txt", Some(5), None, cx)
+            .await
+            .unwrap_err();
+        assert_eq!(error, "Invalid line range 5-3, root/file.txt has 3 lines");
+    }
+
+    #[gpui::test]
+    async fn test_git_blame_rejects_private_files(cx: &mut TestAppContext) {
+        let project = setup(cx).await;
+
+        let error = run(project, "root/.env", None, None, cx).await.unwrap_err();
+        assert!(error.contains("private_files"), "{error}");
+    }
+}
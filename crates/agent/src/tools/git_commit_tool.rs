@@ -0,0 +1,394 @@
+use super::code_navigation::is_hidden_path;
+use super::git_repository::resolve_repository;
+use crate::{
+    AgentTool, ToolCallEventStream, ToolInput, ToolPermissionDecision,
+    decide_permission_from_settings,
+};
+use agent_client_protocol as acp;
+use agent_settings::AgentSettings;
+use anyhow::Result;
+use git::repository::{AskPassDelegate, CommitOptions, RepoPath};
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::sync::Arc;
+use util::markdown::MarkdownInlineCode;
+
+/// Commits changes to a git repository in the project.
+///
+/// Only use this tool when the user asks you to commit. By default, only the changes that are already staged are committed. Pass `paths` to stage those files first. Use `git_status` and `git_diff` to check what will be committed.
+///
+/// Write a commit message with a short subject line, followed by a blank line and a description of the changes if needed.
+///
+/// <example>
+/// To commit the changes to two files:
+/// {
+///     "message": "Fix off-by-one error in pagination",
+///     "paths": ["project/src/pagination.rs", "project/tests/pagination.rs"]
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GitCommitToolInput {
+    /// The commit message.
+    pub message: String,
+    /// Files or directories to stage before committing. Leave this empty to commit the changes that are already staged.
+    ///
+    /// These paths should never be absolute, and the first component
+    /// of each path should always be a root directory in a project.
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// A path in the repository to commit to. This can be left out when `paths` is given or the project has a single repository.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+pub struct GitCommitTool {
+    project: Entity<Project>,
+}
+
+impl GitCommitTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for GitCommitTool {
+    type Input = GitCommitToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "git_commit";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Other
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!(
+                "Commit {}",
+                MarkdownInlineCode(commit_subject(&input.message))
+            )
+            .into(),
+            Err(_) => "Commit".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+            let message = input.message.trim().to_string();
+            if message.is_empty() {
+                return Err("The commit message must not be empty".to_string());
+            }
+
+            let (repository, paths) =
+                project.read_with(cx, |project, cx| -> Result<_, String> {
+                    let repository_path = input
+                        .path
+                        .as_deref()
+                        .or(input.paths.first().map(String::as_str));
+                    let (repository, _) = resolve_repository(project, repository_path, cx)?;
+                    let repository_snapshot = repository.read(cx);
+                    let is_hidden_repo_path = |repo_path: &RepoPath| {
+                        repository_snapshot
+                            .repo_path_to_project_path(repo_path, cx)
+                            .is_some_and(|project_path| is_hidden_path(&project_path, cx))
+                    };
+                    let paths = input
+                        .paths
+                        .iter()
+                        .map(|path| {
+                            let project_path = project
+                                .find_project_path(path, cx)
+                                .ok_or_else(|| format!("Could not find path {path} in project"))?;
+                            if is_hidden_path(&project_path, cx) {
+                                return Err(format!(
+                                    "Cannot stage {path} because it matches the `file_scan_exclusions` or `private_files` setting"
+                                ));
+                            }
+                            let repo_path = repository_snapshot
+                                .project_path_to_repo_path(&project_path, cx)
+                                .ok_or_else(|| {
+                                    format!(
+                                        "{path} isn't in the {} repository",
+                                        repository_snapshot.display_name()
+                                    )
+                                })?;
+                            // Staging a directory stages the changed files in it.
+                            if repository_snapshot.cached_status().any(|entry| {
+                                entry.repo_path.starts_with(&repo_path)
+                                    && is_hidden_repo_path(&entry.repo_path)
+                            }) {
+                                return Err(format!(
+                                    "Cannot stage {path} because it contains files that match the `file_scan_exclusions` or `private_files` setting"
+                                ));
+                            }
+                            Ok(repo_path)
+                        })
+                        .collect::<Result<Vec<_>, _>>()?;
+
+                    // The whole index is committed, so changes to hidden files that were staged
+                    // before would be committed along with the given paths.
+                    let stages_hidden_path = repository_snapshot.cached_status().any(|entry| {
+                        entry.status.staging().has_staged() && is_hidden_repo_path(&entry.repo_path)
+                    });
+                    if stages_hidden_path {
+                        return Err(
+                            "Cannot commit because changes to files that match the `file_scan_exclusions` or `private_files` setting are staged. Ask the user to unstage them, or to make the commit themselves.".to_string()
+                        );
+                    }
+                    Ok((repository.clone(), paths))
+                })?;
+
+            let authorize = cx.update(|cx| {
+                let decision = decide_permission_from_settings(
+                    Self::NAME,
+                    std::slice::from_ref(&message),
+                    AgentSettings::get_global(cx),
+                );
+                match decision {
+                    ToolPermissionDecision::Allow => Ok(None),
+                    ToolPermissionDecision::Deny(reason) => Err(reason),
+                    ToolPermissionDecision::Confirm => {
+                        let context =
+                            crate::ToolPermissionContext::new(Self::NAME, vec![message.clone()]);
+                        Ok(Some(event_stream.authorize(
+                            format!(
+                                "Commit {} to {}",
+                                MarkdownInlineCode(commit_subject(&message)),
+                                repository.read(cx).display_name()
+                            ),
+                            context,
+                            cx,
+                        )))
+                    }
+                }
+            })?;
+            if let Some(authorize) = authorize {
+                authorize.await.map_err(|e| e.to_string())?;
+            }
+
+            if !paths.is_empty() {
+                repository
+                    .update(cx, |repository, cx| repository.stage_entries(paths, cx))
+                    .await
+                    .map_err(|error| format!("Failed to stage the paths: {error:#}"))?;
+            }
+
+            // The agent can't answer prompts for credentials, such as a
+            // passphrase for a signing key, so they fail the commit.
+            let askpass = AskPassDelegate::new(cx, |_, _, _| {});
+            repository
+                .update(cx, |repository, cx| {
+                    repository.commit(
+                        message.clone().into(),
+                        None,
+                        CommitOptions::default(),
+                        askpass,
+                        cx,
+                    )
+                })
+                .await
+                .map_err(|_| "The repository was closed".to_string())?
+                .map_err(|error| format!("Failed to commit: {error:#}"))?;
+
+            let repository_name =
+                repository.read_with(cx, |repository, _| repository.display_name());
+            Ok(format!(
+                "Committed {} to {repository_name}",
+                MarkdownInlineCode(commit_subject(&message))
+            ))
+        })
+    }
+}
+
+fn commit_subject(message: &str) -> &str {
+    message.trim().lines().next().unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fs::FakeFs;
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use util::path;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+        cx.update(|cx| {
+            let mut settings = AgentSettings::get_global(cx).clone();
+            settings.tool_permissions.default = settings::ToolPermissionMode::Allow;
+            AgentSettings::override_global(settings, cx);
+        });
+    }
+
+    /// A repository at `/root` where `a.txt` and `.env` were changed since they were committed,
+    /// and nothing is staged.
+    async fn setup(cx: &mut TestAppContext) -> (Arc<FakeFs>, Entity<Project>) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                ".git": {},
+                ".env": "SECRET=1",
+                "a.txt": "a2",
+                "b.txt": "b",
+            }),
+        )
+        .await;
+        fs.set_head_and_index_for_repo(
+            Path::new(path!("/root/.git")),
+            &[
+                (".env", "SECRET=0".into()),
+                ("a.txt", "a".into()),
+                ("b.txt", "b".into()),
+            ],
+        );
+        let project = Project::test(fs.clone(), [path!("/root").as_ref()], cx).await;
+        cx.executor().run_until_parked();
+        (fs, project)
+    }
+
+    async fn run(
+        project: Entity<Project>,
+        message: &str,
+        paths: &[&str],
+        cx: &mut TestAppContext,
+    ) -> Result<String, String> {
+        let tool = Arc::new(GitCommitTool::new(project));
+        let input = GitCommitToolInput {
+            message: message.into(),
+            paths: paths.iter().map(|path| path.to_string()).collect(),
+            path: None,
+        };
+        let result = cx
+            .update(|cx| {
+                tool.run(
+                    ToolInput::resolved(input),
+                    ToolCallEventStream::test().0,
+                    cx,
+                )
+            })
+            .await;
+        cx.executor().run_until_parked();
+        result
+    }
+
+    fn index_contents(fs: &FakeFs, path: &str) -> Option<String> {
+        fs.with_git_state(Path::new(path!("/root/.git")), false, |state| {
+            state
+                .index_contents
+                .get(&git::repository::repo_path(path))
+                .cloned()
+        })
+        .unwrap()
+    }
+
+    #[gpui::test]
+    async fn test_git_commit_stages_paths(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (fs, project) = setup(cx).await;
+
+        let output = run(
+            project,
+            "Update a\n\nWith more details.",
+            &["root/a.txt"],
+            cx,
+        )
+        .await
+        .unwrap();
+        assert_eq!(output, "Committed `Update a` to root");
+        assert_eq!(index_contents(&fs, "a.txt").as_deref(), Some("a2"));
+        assert_eq!(index_contents(&fs, ".env").as_deref(), Some("SECRET=0"));
+    }
+
+    #[gpui::test]
+    async fn test_git_commit_rejects_hidden_paths(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (fs, project) = setup(cx).await;
+
+        // `.env` files are private by default.
+        let error = run(project.clone(), "Update secret", &["root/.env"], cx)
+            .await
+            .unwrap_err();
+        assert!(error.contains("private_files"), "{error}");
+
+        // Staging the whole worktree would stage `.env` too.
+        let error = run(project.clone(), "Update everything", &["root"], cx)
+            .await
+            .unwrap_err();
+        assert!(error.contains("contains files that match"), "{error}");
+        assert_eq!(index_contents(&fs, "a.txt").as_deref(), Some("a"));
+        assert_eq!(index_contents(&fs, ".env").as_deref(), Some("SECRET=0"));
+
+        // Private files that were already staged would be committed along with the given paths.
+        fs.set_index_for_repo(
+            Path::new(path!("/root/.git")),
+            &[
+                (".env", "SECRET=1".into()),
+                ("a.txt", "a".into()),
+                ("b.txt", "b".into()),
+            ],
+        );
+        cx.executor().run_until_parked();
+        let error = run(project.clone(), "Update a", &["root/a.txt"], cx)
+            .await
+            .unwrap_err();
+        assert!(error.contains("are staged"), "{error}");
+        let error = run(project, "Commit the index", &[], cx).await.unwrap_err();
+        assert!(error.contains("are staged"), "{error}");
+        assert_eq!(index_contents(&fs, "a.txt").as_deref(), Some("a"));
+    }
+
+    #[gpui::test]
+    async fn test_git_commit_honors_deny_policy(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            let mut settings = AgentSettings::get_global(cx).clone();
+            settings.tool_permissions.tools.insert(
+                "git_commit".into(),
+                agent_settings::ToolRules {
+                    default: Some(settings::ToolPermissionMode::Deny),
+                    ..Default::default()
+                },
+            );
+            AgentSettings::override_global(settings, cx);
+        });
+        let (fs, project) = setup(cx).await;
+
+        let result = run(project, "Update a", &["root/a.txt"], cx).await;
+        assert!(result.is_err(), "{result:?}");
+        assert_eq!(index_contents(&fs, "a.txt").as_deref(), Some("a"));
+    }
+
+    #[gpui::test]
+    async fn test_git_commit_requires_message(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (fs, project) = setup(cx).await;
+
+        let error = run(project, " \n ", &["root/a.txt"], cx).await.unwrap_err();
+        assert_eq!(error, "The commit message must not be empty");
+        assert_eq!(index_contents(&fs, "a.txt").as_deref(), Some("a"));
+    }
+}
@@ -0,0 +1,284 @@
+use super::git_repository::{describe_repo_path, resolve_repository};
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use git::repository::{DiffType, RepoPath};
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+const MAX_DIFF_BYTES: usize = 32 * 1024;
+
+/// Shows the changes in a git repository in the project as a unified diff.
+///
+/// By default, shows the unstaged changes. Set `changes` to `staged` to see what will be committed, or to `branch` to see all changes since this branch diverged from `base_branch`, including uncommitted ones.
+///
+/// Use this tool instead of running `git diff` in the terminal.
+///
+/// <example>
+/// To see the staged changes in `project/src`:
+/// {
+///     "path": "project/src",
+///     "changes": "staged"
+/// }
+///
+/// To review everything that changed relative to `main`:
+/// {
+///     "changes": "branch",
+///     "base_branch": "main"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GitDiffToolInput {
+    /// A path in the repository to show the changes of. Only changes to files under it are shown.
+    ///
+    /// This can be left out when the project has a single repository. Otherwise, pass the root directory of the repository to use.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// Which changes to show.
+    #[serde(default)]
+    pub changes: GitDiffChanges,
+    /// The branch (or any other revision) to compare against when `changes` is `branch`.
+    #[serde(default)]
+    pub base_branch: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GitDiffChanges {
+    /// Changes in the working tree that haven't been staged.
+    #[default]
+    Unstaged,
+    /// Changes that have been staged for the next commit.
+    Staged,
+    /// Changes since the current branch diverged from `base_branch`.
+    Branch,
+}
+
+pub struct GitDiffTool {
+    project: Entity<Project>,
+}
+
+impl GitDiffTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for GitDiffTool {
+    type Input = GitDiffToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "git_diff";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        let Ok(input) = input else {
+            return "Git diff".into();
+        };
+        let mut title = match (input.changes, &input.base_branch) {
+            (GitDiffChanges::Unstaged, _) => "Diff unstaged changes".to_string(),
+            (GitDiffChanges::Staged, _) => "Diff staged changes".to_string(),
+            (GitDiffChanges::Branch, Some(base_branch)) => {
+                format!("Diff against {}", MarkdownInlineCode(base_branch))
+            }
+            (GitDiffChanges::Branch, None) => "Diff against branch".to_string(),
+        };
+        if let Some(path) = input.path.filter(|path| !path.is_empty()) {
+            write!(title, " in {}", MarkdownInlineCode(&path)).ok();
+        }
+        title.into()
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+
+            let diff_type = match input.changes {
+                GitDiffChanges::Unstaged => DiffType::HeadToWorktree,
+                GitDiffChanges::Staged => DiffType::HeadToIndex,
+                GitDiffChanges::Branch => {
+                    let base_ref = input
+                        .base_branch
+                        .filter(|base_branch| !base_branch.is_empty())
+                        .ok_or("A `base_branch` is required to diff against a branch")?;
+                    DiffType::MergeBase {
+                        base_ref: base_ref.into(),
+                    }
+                }
+            };
+
+            let (repository, path_prefix) = project.read_with(cx, |project, cx| {
+                resolve_repository(project, input.path.as_deref(), cx)
+            })?;
+            let diff = repository.update(cx, |repository, cx| repository.diff(diff_type, cx));
+            let diff = futures::select! {
+                result = diff.fuse() => result
+                    .map_err(|_| "The repository was closed".to_string())?
+                    .map_err(|error| format!("{error:#}"))?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Diff cancelled by user".to_string());
+                }
+            };
+
+            let (diff, hidden_files) = project.read_with(cx, |project, cx| {
+                let repository = repository.read(cx);
+                filter_diff(&diff, |repo_path| {
+                    if path_prefix
+                        .as_ref()
+                        .is_some_and(|prefix| !repo_path.starts_with(prefix))
+                    {
+                        return Some(false);
+                    }
+                    Some(describe_repo_path(project, repository, repo_path, cx).is_some())
+                })
+            });
+
+            let mut output = if diff.is_empty() {
+                "There are no changes.".to_string()
+            } else if diff.len() > MAX_DIFF_BYTES {
+                let mut output =
+                    util::truncate_lines_to_byte_limit(&diff, MAX_DIFF_BYTES).to_string();
+                write!(
+                    output,
+                    "\n\nThe diff was truncated because it's {} bytes long. Pass a `path` to see the changes to fewer files.",
+                    diff.len()
+                )
+                .ok();
+                output
+            } else {
+                diff
+            };
+            if hidden_files > 0 {
+                write!(
+                    output,
+                    "\n\nChanges to {hidden_files} file(s) were left out because they match the `file_scan_exclusions` or `private_files` setting."
+                )
+                .ok();
+            }
+            Ok(output)
+        })
+    }
+}
+
+/// Keeps the sections of a unified diff for the files that `include` accepts.
+///
+/// `include` returns `Some(false)` for files that are filtered out and
+/// `None` for files the agent isn't allowed to see, which are counted.
+fn filter_diff(diff: &str, mut include: impl FnMut(&RepoPath) -> Option<bool>) -> (String, usize) {
+    let mut output = String::new();
+    let mut hidden_files = 0;
+    let mut including = true;
+    for line in diff.split_inclusive('\n') {
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            including = match diff_header_path(header).and_then(|path| RepoPath::new(&path).ok()) {
+                Some(repo_path) => match include(&repo_path) {
+                    Some(include) => include,
+                    None => {
+                        hidden_files += 1;
+                        false
+                    }
+                },
+                None => true,
+            };
+        }
+        if including {
+            output.push_str(line);
+        }
+    }
+    (output, hidden_files)
+}
+
+/// Extracts the path of the changed file from the rest of a `diff --git`
+/// header line, such as `a/src/main.rs b/src/main.rs`.
+fn diff_header_path(header: &str) -> Option<String> {
+    let header = header.trim_end();
+    if let Some(quoted) = header.strip_suffix('"') {
+        let start = quoted.rfind("\"b/")?;
+        return Some(
+            quoted[start + 3..]
+                .replace("\\\"", "\"")
+                .replace("\\\\", "\\"),
+        );
+    }
+    let start = header.rfind(" b/")?;
+    Some(header[start + 3..].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_filter_diff() {
+        let diff = indoc! {r#"
+            diff --git a/src/lib.rs b/src/lib.rs
+            index 1111111..2222222 100644
+            --- a/src/lib.rs
+            +++ b/src/lib.rs
+            @@ -1 +1 @@
+            -fn a() {}
+            +fn b() {}
+            diff --git a/.env b/.env
+            index 3333333..4444444 100644
+            --- a/.env
+            +++ b/.env
+            @@ -1 +1 @@
+            -SECRET=1
+            +SECRET=2
+            diff --git "a/docs/a \"b\".md" "b/docs/a \"b\".md"
+            new file mode 100644
+            --- /dev/null
+            +++ "b/docs/a \"b\".md"
+            @@ -0,0 +1 @@
+            +Hello
+        "#};
+
+        let mut seen = Vec::new();
+        let (filtered, hidden_files) = filter_diff(diff, |path| {
+            seen.push(path.as_unix_str().to_string());
+            match path.as_unix_str() {
+                ".env" => None,
+                path => Some(path.starts_with("src/")),
+            }
+        });
+        assert_eq!(seen, ["src/lib.rs", ".env", "docs/a \"b\".md"]);
+        assert_eq!(hidden_files, 1);
+        assert_eq!(
+            filtered,
+            indoc! {"
+                diff --git a/src/lib.rs b/src/lib.rs
+                index 1111111..2222222 100644
+                --- a/src/lib.rs
+                +++ b/src/lib.rs
+                @@ -1 +1 @@
+                -fn a() {}
+                +fn b() {}
+            "}
+        );
+    }
+}
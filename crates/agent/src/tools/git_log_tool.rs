@@ -0,0 +1,278 @@
+use super::git_repository::{
+    describe_repo_path, format_commit_date, resolve_repository, short_sha,
+};
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use git::repository::RepoPath;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::{markdown::MarkdownInlineCode, rel_path::RelPath};
+
+const DEFAULT_LIMIT: usize = 20;
+const MAX_LIMIT: usize = 100;
+
+/// Lists the commits in the history of a git repository in the project, most recent first, with their sha, date, author and subject.
+///
+/// Pass a `path` to only list the commits that changed it, following it across renames.
+///
+/// Use this tool instead of running `git log` in the terminal.
+///
+/// <example>
+/// To list the last 10 commits that changed `project/src/main.rs`:
+/// {
+///     "path": "project/src/main.rs",
+///     "limit": 10
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GitLogToolInput {
+    /// A file or directory to list the commits of.
+    ///
+    /// This can be left out when the project has a single repository. Otherwise, pass the root directory of the repository to use.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// The number of most recent commits to skip, to page through the history.
+    #[serde(default)]
+    pub skip: usize,
+    /// The maximum number of commits to list. Defaults to 20, and can't be more than 100.
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+pub struct GitLogTool {
+    project: Entity<Project>,
+}
+
+impl GitLogTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for GitLogTool {
+    type Input = GitLogToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "git_log";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input.ok().and_then(|input| input.path) {
+            Some(path) if !path.is_empty() => {
+                format!("Git log of {}", MarkdownInlineCode(&path)).into()
+            }
+            _ => "Git log".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+            let limit = input.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+
+            let (repository, repo_path) = project.read_with(cx, |project, cx| {
+                resolve_repository(project, input.path.as_deref(), cx)
+            })?;
+            let repo_path = repo_path.unwrap_or_else(|| RepoPath::from_rel_path(RelPath::empty()));
+            let history = repository.update(cx, |repository, _| {
+                repository.file_history_paginated(repo_path, input.skip, Some(limit))
+            });
+            let history = futures::select! {
+                result = history.fuse() => result
+                    .map_err(|_| "The repository was closed".to_string())?
+                    .map_err(|error| format!("{error:#}"))?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Git log cancelled by user".to_string());
+                }
+            };
+
+            let title = project.read_with(cx, |project, cx| {
+                let repository = repository.read(cx);
+                if history.path.is_empty() {
+                    format!("Commits in {}", repository.display_name())
+                } else {
+                    let path = describe_repo_path(project, repository, &history.path, cx)
+                        .unwrap_or_else(|| history.path.as_unix_str().to_string());
+                    format!("Commits that changed {path}")
+                }
+            });
+            if history.entries.is_empty() {
+                return Ok(if input.skip > 0 {
+                    format!("{title}: there are no more commits.")
+                } else {
+                    format!("{title}: there are no commits.")
+                });
+            }
+
+            let mut output = format!("# {title}\n\n");
+            for entry in &history.entries {
+                writeln!(
+                    output,
+                    "- {} {} {} <{}>: {}",
+                    MarkdownInlineCode(short_sha(&entry.sha)),
+                    format_commit_date(entry.commit_timestamp),
+                    entry.author_name,
+                    entry.author_email,
+                    entry.subject
+                )
+                .ok();
+            }
+            if history.entries.len() == limit {
+                write!(
+                    output,
+                    "\nThere may be more commits. Pass a `skip` of {} to list them.",
+                    input.skip + limit
+                )
+                .ok();
+            }
+            Ok(output)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::repository::{FileHistoryEntry, repo_path};
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use util::path;
+
+    fn commit(sha: &str, subject: &str) -> FileHistoryEntry {
+        FileHistoryEntry {
+            sha: sha.repeat(40).into(),
+            subject: subject.to_string().into(),
+            message: subject.to_string().into(),
+            commit_timestamp: 1709251200,
+            author_name: "Alice".into(),
+            author_email: "alice@example.com".into(),
+        }
+    }
+
+    async fn setup(cx: &mut TestAppContext) -> Entity<Project> {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                ".git": {},
+                ".env": "SECRET=1",
+                "a.txt": "a",
+            }),
+        )
+        .await;
+        fs.set_file_history_for_repo(
+            Path::new(path!("/root/.git")),
+            vec![
+                (
+                    repo_path(""),
+                    vec![
+                        commit("3", "Third"),
+                        commit("2", "Second"),
+                        commit("1", "First"),
+                    ],
+                ),
+                (repo_path("a.txt"), vec![commit("1", "First")]),
+            ],
+        );
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+        cx.executor().run_until_parked();
+        project
+    }
+
+    async fn run(
+        project: Entity<Project>,
+        path: Option<&str>,
+        skip: usize,
+        limit: Option<usize>,
+        cx: &mut TestAppContext,
+    ) -> Result<String, String> {
+        let tool = Arc::new(GitLogTool::new(project));
+        let input = GitLogToolInput {
+            path: path.map(Into::into),
+            skip,
+            limit,
+        };
+        cx.update(|cx| {
+            tool.run(
+                ToolInput::resolved(input),
+                ToolCallEventStream::test().0,
+                cx,
+            )
+        })
+        .await
+    }
+
+    #[gpui::test]
+    async fn test_git_log(cx: &mut TestAppContext) {
+        let project = setup(cx).await;
+
+        let output = run(project.clone(), None, 0, Some(2), cx).await.unwrap();
+        assert_eq!(
+            output,
+            "# Commits in root\n\n\
+             - `3333333` 2024-03-01 Alice <alice@example.com>: Third\n\
+             - `2222222` 2024-03-01 Alice <alice@example.com>: Second\n\
+             \nThere may be more commits. Pass a `skip` of 2 to list them."
+        );
+
+        let output = run(project.clone(), None, 2, Some(2), cx).await.unwrap();
+        assert_eq!(
+            output,
+            "# Commits in root\n\n- `1111111` 2024-03-01 Alice <alice@example.com>: First\n"
+        );
+
+        let output = run(project.clone(), None, 3, Some(2), cx).await.unwrap();
+        assert_eq!(output, "Commits in root: there are no more commits.");
+
+        let output = run(project, Some("root/a.txt"), 0, None, cx).await.unwrap();
+        assert_eq!(
+            output,
+            format!(
+                "# Commits that changed {}\n\n- `1111111` 2024-03-01 Alice <alice@example.com>: First\n",
+                path!("root/a.txt")
+            )
+        );
+    }
+
+    #[gpui::test]
+    async fn test_git_log_rejects_private_files(cx: &mut TestAppContext) {
+        let project = setup(cx).await;
+
+        let error = run(project, Some("root/.env"), 0, None, cx)
+            .await
+            .unwrap_err();
+        assert!(error.contains("private_files"), "{error}");
+    }
+}
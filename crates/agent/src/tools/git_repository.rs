@@ -0,0 +1,96 @@
+//! Helpers shared by the tools that work with the project's git repositories.
+//!
+//! The tools go through the project's `GitStore` rather than running git
+//! themselves, so they work the same way in local, remote and shared projects.
+
+use super::code_navigation::is_hidden_path;
+use chrono::{DateTime, Utc};
+use git::repository::RepoPath;
+use gpui::{App, Entity};
+use project::{Project, git_store::Repository};
+
+/// Finds the repository containing `path`, or the project's only repository
+/// when no path is given.
+///
+/// Returns the repository along with `path` relative to its root, which is
+/// `None` for the root itself. The error is suitable for returning to the model.
+pub(crate) fn resolve_repository(
+    project: &Project,
+    path: Option<&str>,
+    cx: &App,
+) -> Result<(Entity<Repository>, Option<RepoPath>), String> {
+    let git_store = project.git_store().read(cx);
+    if let Some(path) = path.filter(|path| !path.is_empty()) {
+        let project_path = project
+            .find_project_path(path, cx)
+            .ok_or_else(|| format!("Could not find path {path} in project"))?;
+        if is_hidden_path(&project_path, cx) {
+            return Err(format!(
+                "Cannot access {path} because it matches the `file_scan_exclusions` or `private_files` setting"
+            ));
+        }
+        let (repository, repo_path) = git_store
+            .repository_and_path_for_project_path(&project_path, cx)
+            .ok_or_else(|| format!("{path} isn't in a git repository"))?;
+        return Ok((repository, (!repo_path.is_empty()).then_some(repo_path)));
+    }
+
+    let mut repositories = git_store.repositories().values();
+    match (repositories.next(), repositories.next()) {
+        (Some(repository), None) => Ok((repository.clone(), None)),
+        (None, _) => Err("The project doesn't have a git repository".to_string()),
+        (Some(_), Some(_)) => {
+            let mut names = git_store
+                .repositories()
+                .values()
+                .map(|repository| repository.read(cx).display_name().to_string())
+                .collect::<Vec<_>>();
+            names.sort();
+            Err(format!(
+                "The project has several git repositories ({}). Pass a `path` inside the one to use.",
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+/// Describes a path in `repository` the way the model refers to project
+/// paths, or returns `None` when the agent isn't allowed to see it.
+///
+/// Paths in the repository that are outside of the project's worktrees are
+/// described relative to the repository root.
+pub(crate) fn describe_repo_path(
+    project: &Project,
+    repository: &Repository,
+    repo_path: &RepoPath,
+    cx: &App,
+) -> Option<String> {
+    let path_style = project.path_style(cx);
+    let Some(project_path) = repository.repo_path_to_project_path(repo_path, cx) else {
+        return Some(repo_path.display(path_style).into_owned());
+    };
+    if is_hidden_path(&project_path, cx) {
+        return None;
+    }
+    let worktree = project.worktree_for_id(project_path.worktree_id, cx)?;
+    Some(
+        worktree
+            .read(cx)
+            .root_name()
+            .join(&project_path.path)
+            .display(path_style)
+            .to_string(),
+    )
+}
+
+/// Formats a commit timestamp as a date, such as `2024-03-01`.
+pub(crate) fn format_commit_date(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp(timestamp, 0)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+/// Shortens a commit sha for display.
+pub(crate) fn short_sha(sha: &str) -> &str {
+    sha.get(..git::SHORT_SHA_LENGTH).unwrap_or(sha)
+}
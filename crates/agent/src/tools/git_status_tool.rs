@@ -0,0 +1,299 @@
+use super::git_repository::{describe_repo_path, resolve_repository, short_sha};
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use git::{
+    repository::UpstreamTracking,
+    status::{FileStatus, StatusCode},
+};
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+const MAX_ENTRIES: usize = 200;
+
+/// Shows the current branch of a git repository in the project, and which files have staged, unstaged, untracked or conflicting changes.
+///
+/// Use this tool instead of running `git status` in the terminal.
+///
+/// <example>
+/// To see the status of the project's repository:
+/// {}
+///
+/// To see the status of the files in `project/src`:
+/// {
+///     "path": "project/src"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GitStatusToolInput {
+    /// A path in the repository to show the status of. Only files under it are listed.
+    ///
+    /// This can be left out when the project has a single repository. Otherwise, pass the root directory of the repository to use.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+pub struct GitStatusTool {
+    project: Entity<Project>,
+}
+
+impl GitStatusTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for GitStatusTool {
+    type Input = GitStatusToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "git_status";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input.ok().and_then(|input| input.path) {
+            Some(path) if !path.is_empty() => {
+                format!("Git status of {}", MarkdownInlineCode(&path)).into()
+            }
+            _ => "Git status".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+
+            project.read_with(cx, |project, cx| {
+                let (repository, path_prefix) =
+                    resolve_repository(project, input.path.as_deref(), cx)?;
+                let repository = repository.read(cx);
+
+                let mut staged = Vec::new();
+                let mut unstaged = Vec::new();
+                let mut untracked = Vec::new();
+                let mut conflicted = Vec::new();
+                for entry in repository.cached_status() {
+                    if path_prefix
+                        .as_ref()
+                        .is_some_and(|prefix| !entry.repo_path.starts_with(prefix))
+                    {
+                        continue;
+                    }
+                    let Some(path) = describe_repo_path(project, repository, &entry.repo_path, cx)
+                    else {
+                        continue;
+                    };
+                    match entry.status {
+                        FileStatus::Ignored => {}
+                        FileStatus::Untracked => untracked.push(path),
+                        FileStatus::Unmerged(_) => conflicted.push(path),
+                        FileStatus::Tracked(tracked) => {
+                            if let Some(change) = describe_change(tracked.index_status) {
+                                staged.push(format!("{change}: {path}"));
+                            }
+                            if let Some(change) = describe_change(tracked.worktree_status) {
+                                unstaged.push(format!("{change}: {path}"));
+                            }
+                        }
+                    }
+                }
+
+                let mut output = format!("Repository {}", repository.display_name());
+                if let Some(branch) = &repository.branch {
+                    write!(
+                        output,
+                        " is on branch {}",
+                        MarkdownInlineCode(branch.name())
+                    )
+                    .ok();
+                    if let Some(upstream) = &branch.upstream {
+                        write!(
+                            output,
+                            ", tracking {}",
+                            MarkdownInlineCode(&upstream.ref_name)
+                        )
+                        .ok();
+                        match upstream.tracking {
+                            UpstreamTracking::Tracked(status) => {
+                                write!(
+                                    output,
+                                    " ({} ahead, {} behind)",
+                                    status.ahead, status.behind
+                                )
+                                .ok();
+                            }
+                            UpstreamTracking::Gone => {
+                                output.push_str(" (the upstream branch is gone)");
+                            }
+                        }
+                    }
+                } else {
+                    output.push_str(" has a detached HEAD");
+                }
+                if let Some(head_commit) = &repository.head_commit {
+                    let subject = head_commit.message.lines().next().unwrap_or_default();
+                    write!(
+                        output,
+                        " at {} {subject}",
+                        MarkdownInlineCode(short_sha(&head_commit.sha))
+                    )
+                    .ok();
+                }
+                output.push('\n');
+
+                let mut remaining = MAX_ENTRIES;
+                for (title, paths) in [
+                    ("Conflicted", conflicted),
+                    ("Staged", staged),
+                    ("Unstaged", unstaged),
+                    ("Untracked", untracked),
+                ] {
+                    if paths.is_empty() {
+                        continue;
+                    }
+                    writeln!(output, "\n## {title} ({})", paths.len()).ok();
+                    for path in paths.iter().take(remaining) {
+                        writeln!(output, "- {path}").ok();
+                    }
+                    if paths.len() > remaining {
+                        writeln!(output, "- …and {} more", paths.len() - remaining).ok();
+                    }
+                    remaining = remaining.saturating_sub(paths.len());
+                }
+                if remaining == MAX_ENTRIES {
+                    output.push_str("\nThere are no changes.");
+                }
+
+                Ok(output)
+            })
+        })
+    }
+}
+
+fn describe_change(status: StatusCode) -> Option<&'static str> {
+    match status {
+        StatusCode::Modified => Some("modified"),
+        StatusCode::TypeChanged => Some("type changed"),
+        StatusCode::Added => Some("added"),
+        StatusCode::Deleted => Some("deleted"),
+        StatusCode::Renamed => Some("renamed"),
+        StatusCode::Copied => Some("copied"),
+        StatusCode::Unmodified => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use settings::SettingsStore;
+    use std::path::Path;
+    use util::path;
+
+    #[gpui::test]
+    async fn test_git_status(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                ".git": {},
+                ".env": "SECRET=1",
+                "a.txt": "a",
+                "b.txt": "b",
+                "c.txt": "c",
+                "src": {
+                    "d.txt": "d",
+                },
+            }),
+        )
+        .await;
+        let dot_git = Path::new(path!("/root/.git"));
+        fs.set_branch_name(dot_git, Some("main"));
+        fs.set_status_for_repo(
+            dot_git,
+            &[
+                (".env", FileStatus::Untracked),
+                ("a.txt", StatusCode::Modified.worktree()),
+                ("b.txt", StatusCode::Added.index()),
+                ("src/d.txt", FileStatus::Untracked),
+            ],
+        );
+
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+        cx.executor().run_until_parked();
+        let tool = Arc::new(GitStatusTool::new(project));
+
+        let output = cx
+            .update(|cx| {
+                tool.clone().run(
+                    ToolInput::resolved(GitStatusToolInput { path: None }),
+                    ToolCallEventStream::test().0,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert!(output.contains("is on branch `main`"), "{output}");
+        assert!(
+            output.contains("## Staged (1)\n- added: root/b.txt\n"),
+            "{output}"
+        );
+        assert!(
+            output.contains("## Unstaged (1)\n- modified: root/a.txt\n"),
+            "{output}"
+        );
+        assert!(
+            output.contains("## Untracked (1)\n- root/src/d.txt\n"),
+            "{output}"
+        );
+        assert!(!output.contains(".env"), "{output}");
+
+        let output = cx
+            .update(|cx| {
+                tool.clone().run(
+                    ToolInput::resolved(GitStatusToolInput {
+                        path: Some("root/src".into()),
+                    }),
+                    ToolCallEventStream::test().0,
+                    cx,
+                )
+            })
+            .await
+            .unwrap();
+        assert!(!output.contains("Staged"), "{output}");
+        assert!(
+            output.contains("## Untracked (1)\n- root/src/d.txt\n"),
+            "{output}"
+        );
+    }
+}
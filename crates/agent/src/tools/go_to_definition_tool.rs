@@ -0,0 +1,296 @@
+use super::code_navigation::{describe_location, resolve_symbol, write_excerpt};
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+/// How many lines of each definition to include in the output.
+const DEFINITION_EXCERPT_LINES: u32 = 20;
+
+/// Finds where a symbol is defined, using the language server for the file.
+///
+/// Prefer this tool over `grep` when you know where a symbol is used and want to jump to its definition, since the language server resolves imports, overloads and shadowing precisely.
+///
+/// The symbol is identified by the file it appears in, the line it's on and its name. The output lists each definition's location followed by the first lines of its source.
+///
+/// <example>
+/// To find the definition of `parse_config` called on line 42 of `src/main.rs`:
+/// {
+///     "path": "project/src/main.rs",
+///     "line": 42,
+///     "symbol": "parse_config"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GoToDefinitionToolInput {
+    /// The path of the file containing the symbol.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based line number the symbol appears on.
+    pub line: u32,
+    /// The name of the symbol, exactly as it appears on that line.
+    pub symbol: String,
+    /// Whether to find the definition of the symbol's type instead of the symbol itself.
+    #[serde(default)]
+    pub type_definition: bool,
+}
+
+pub struct GoToDefinitionTool {
+    project: Entity<Project>,
+}
+
+impl GoToDefinitionTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for GoToDefinitionTool {
+    type Input = GoToDefinitionToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "go_to_definition";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) if input.type_definition => format!(
+                "Go to type definition of {}",
+                MarkdownInlineCode(&input.symbol)
+            )
+            .into(),
+            Ok(input) => {
+                format!("Go to definition of {}", MarkdownInlineCode(&input.symbol)).into()
+            }
+            Err(_) => "Go to definition".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+
+            let resolved = resolve_symbol(
+                &project,
+                &input.path,
+                input.line,
+                &input.symbol,
+                &event_stream,
+                cx,
+            )
+            .await?;
+
+            let definitions_task = project.update(cx, |project, cx| {
+                if input.type_definition {
+                    project.type_definitions(&resolved.buffer, resolved.position, cx)
+                } else {
+                    project.definitions(&resolved.buffer, resolved.position, cx)
+                }
+            });
+            let definitions = futures::select! {
+                result = definitions_task.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Go to definition cancelled by user".to_string());
+                }
+            }
+            .unwrap_or_default();
+
+            let output = cx.update(|cx| {
+                let mut output = String::new();
+                for definition in &definitions {
+                    let Some(location) = describe_location(&definition.target, cx) else {
+                        continue;
+                    };
+                    writeln!(
+                        output,
+                        "## {}:{}\n",
+                        location.path.display(),
+                        location.start.row + 1
+                    )
+                    .ok();
+                    write_excerpt(
+                        &mut output,
+                        &definition.target,
+                        DEFINITION_EXCERPT_LINES,
+                        cx,
+                    );
+                }
+                output
+            });
+
+            if output.is_empty() {
+                Ok(format!(
+                    "No definition found for `{}`. The language server may not support this file or may still be starting.",
+                    input.symbol
+                ))
+            } else {
+                Ok(output)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::code_navigation::tests::{
+        hide_generated_files, init_test, parse_location, setup,
+    };
+    use gpui::TestAppContext;
+    use util::path;
+
+    fn capabilities() -> lsp::ServerCapabilities {
+        lsp::ServerCapabilities {
+            definition_provider: Some(lsp::OneOf::Left(true)),
+            type_definition_provider: Some(lsp::TypeDefinitionProviderCapability::Simple(true)),
+            ..Default::default()
+        }
+    }
+
+    fn input(path: &str, line: u32, type_definition: bool) -> GoToDefinitionToolInput {
+        GoToDefinitionToolInput {
+            path: path.into(),
+            line,
+            symbol: "parse".into(),
+            type_definition,
+        }
+    }
+
+    async fn run(
+        project: Entity<Project>,
+        input: GoToDefinitionToolInput,
+        cx: &mut TestAppContext,
+    ) -> Result<String, String> {
+        let tool = Arc::new(GoToDefinitionTool::new(project));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        cx.update(|cx| tool.run(ToolInput::resolved(input), event_stream, cx))
+            .await
+    }
+
+    #[gpui::test]
+    async fn test_go_to_definition(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (project, fake_language_server, _lsp_handle) = setup(capabilities(), cx).await;
+        fake_language_server.set_request_handler::<lsp::request::GotoDefinition, _, _>(
+            |params, _| async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(2, 4)
+                );
+                Ok(Some(lsp::GotoDefinitionResponse::Scalar(parse_location(
+                    path!("/root/src/main.rs"),
+                    0,
+                    3,
+                ))))
+            },
+        );
+        fake_language_server.set_request_handler::<lsp::request::GotoTypeDefinition, _, _>(
+            |_, _| async move {
+                Ok(Some(lsp::GotoDefinitionResponse::Scalar(parse_location(
+                    path!("/root/src/lib.rs"),
+                    1,
+                    11,
+                ))))
+            },
+        );
+
+        let output = run(project.clone(), input("root/src/main.rs", 3, false), cx)
+            .await
+            .unwrap();
+        assert!(
+            output.contains(&format!("## {}:1", path!("root/src/main.rs"))),
+            "{output}"
+        );
+        assert!(output.contains("fn parse() {}"), "{output}");
+
+        let output = run(project, input("root/src/main.rs", 3, true), cx)
+            .await
+            .unwrap();
+        assert!(
+            output.contains(&format!("## {}:2", path!("root/src/lib.rs"))),
+            "{output}"
+        );
+        assert!(output.contains("crate::parse();"), "{output}");
+    }
+
+    #[gpui::test]
+    async fn test_go_to_definition_without_results(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (project, fake_language_server, _lsp_handle) = setup(capabilities(), cx).await;
+        fake_language_server.set_request_handler::<lsp::request::GotoDefinition, _, _>(
+            |_, _| async move { Ok(None) },
+        );
+
+        let output = run(project.clone(), input("root/src/main.rs", 3, false), cx)
+            .await
+            .unwrap();
+        assert!(output.starts_with("No definition found"), "{output}");
+
+        // No language server handles text files.
+        let output = run(project.clone(), input("root/notes.txt", 1, false), cx)
+            .await
+            .unwrap();
+        assert!(output.starts_with("No definition found"), "{output}");
+
+        let error = run(project, input("root/src/main.rs", 2, false), cx)
+            .await
+            .unwrap_err();
+        assert!(
+            error.contains("Could not find `parse` on line 2"),
+            "{error}"
+        );
+    }
+
+    #[gpui::test]
+    async fn test_go_to_definition_hides_private_files(cx: &mut TestAppContext) {
+        init_test(cx);
+        hide_generated_files(cx);
+        let (project, fake_language_server, _lsp_handle) = setup(capabilities(), cx).await;
+        fake_language_server.set_request_handler::<lsp::request::GotoDefinition, _, _>(
+            |_, _| async move {
+                Ok(Some(lsp::GotoDefinitionResponse::Scalar(parse_location(
+                    path!("/root/src/generated.rs"),
+                    0,
+                    15,
+                ))))
+            },
+        );
+
+        // Definitions in private files are left out.
+        let output = run(project.clone(), input("root/src/main.rs", 3, false), cx)
+            .await
+            .unwrap();
+        assert!(output.starts_with("No definition found"), "{output}");
+        assert!(!output.contains("pub use"), "{output}");
+
+        // Private files can't be queried.
+        let error = run(project, input("root/src/generated.rs", 1, false), cx)
+            .await
+            .unwrap_err();
+        assert!(error.contains("private_files"), "{error}");
+    }
+}
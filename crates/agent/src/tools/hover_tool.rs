@@ -0,0 +1,221 @@
+use super::code_navigation::resolve_symbol;
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::{HoverBlockKind, Project};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use util::markdown::MarkdownInlineCode;
+
+/// Shows the type, signature and documentation of a symbol, as the language server reports it when hovering over the symbol in the editor.
+///
+/// Use this tool to learn the inferred type of a variable, the signature of a function or the documentation of an item without reading its source.
+///
+/// The symbol is identified by the file it appears in, the line it's on and its name.
+///
+/// <example>
+/// To see the type of the `settings` variable on line 18 of `src/app.rs`:
+/// {
+///     "path": "project/src/app.rs",
+///     "line": 18,
+///     "symbol": "settings"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct HoverToolInput {
+    /// The path of the file containing the symbol.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based line number the symbol appears on.
+    pub line: u32,
+    /// The name of the symbol, exactly as it appears on that line.
+    pub symbol: String,
+}
+
+pub struct HoverTool {
+    project: Entity<Project>,
+}
+
+impl HoverTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for HoverTool {
+    type Input = HoverToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "hover";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Inspect {}", MarkdownInlineCode(&input.symbol)).into(),
+            Err(_) => "Inspect symbol".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+
+            let resolved = resolve_symbol(
+                &project,
+                &input.path,
+                input.line,
+                &input.symbol,
+                &event_stream,
+                cx,
+            )
+            .await?;
+
+            let hover_task = project.update(cx, |project, cx| {
+                project.hover(&resolved.buffer, resolved.position, cx)
+            });
+            let hovers = futures::select! {
+                hovers = hover_task.fuse() => hovers.unwrap_or_default(),
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Hover cancelled by user".to_string());
+                }
+            };
+
+            let output = hovers
+                .iter()
+                .flat_map(|hover| &hover.contents)
+                .filter(|block| !block.text.trim().is_empty())
+                .map(|block| match &block.kind {
+                    HoverBlockKind::Code { language } => {
+                        format!("```{language}\n{}\n```", block.text.trim_end())
+                    }
+                    HoverBlockKind::Markdown | HoverBlockKind::PlainText => {
+                        block.text.trim().to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n");
+
+            if output.is_empty() {
+                Ok(format!(
+                    "No information available for `{}`. The language server may not support this file or may still be starting.",
+                    input.symbol
+                ))
+            } else {
+                Ok(output)
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::code_navigation::tests::{hide_generated_files, init_test, setup};
+    use gpui::TestAppContext;
+
+    fn capabilities() -> lsp::ServerCapabilities {
+        lsp::ServerCapabilities {
+            hover_provider: Some(lsp::HoverProviderCapability::Simple(true)),
+            ..Default::default()
+        }
+    }
+
+    fn input(path: &str, line: u32) -> HoverToolInput {
+        HoverToolInput {
+            path: path.into(),
+            line,
+            symbol: "parse".into(),
+        }
+    }
+
+    async fn run(
+        project: Entity<Project>,
+        input: HoverToolInput,
+        cx: &mut TestAppContext,
+    ) -> Result<String, String> {
+        let tool = Arc::new(HoverTool::new(project));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        cx.update(|cx| tool.run(ToolInput::resolved(input), event_stream, cx))
+            .await
+    }
+
+    #[gpui::test]
+    async fn test_hover(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (project, fake_language_server, _lsp_handle) = setup(capabilities(), cx).await;
+        fake_language_server.set_request_handler::<lsp::request::HoverRequest, _, _>(
+            |params, _| async move {
+                assert_eq!(
+                    params.text_document_position_params.position,
+                    lsp::Position::new(2, 4)
+                );
+                Ok(Some(lsp::Hover {
+                    contents: lsp::HoverContents::Array(vec![
+                        lsp::MarkedString::LanguageString(lsp::LanguageString {
+                            language: "rust".into(),
+                            value: "fn parse()".into(),
+                        }),
+                        lsp::MarkedString::String("Parses the input.".into()),
+                    ]),
+                    range: None,
+                }))
+            },
+        );
+
+        let output = run(project, input("root/src/main.rs", 3), cx)
+            .await
+            .unwrap();
+        assert_eq!(output, "```rust\nfn parse()\n```\n\nParses the input.");
+    }
+
+    #[gpui::test]
+    async fn test_hover_without_results(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (project, fake_language_server, _lsp_handle) = setup(capabilities(), cx).await;
+        fake_language_server.set_request_handler::<lsp::request::HoverRequest, _, _>(
+            |_, _| async move { Ok(None) },
+        );
+
+        let output = run(project.clone(), input("root/src/main.rs", 3), cx)
+            .await
+            .unwrap();
+        assert!(output.starts_with("No information available"), "{output}");
+
+        // No language server handles text files.
+        let output = run(project, input("root/notes.txt", 1), cx).await.unwrap();
+        assert!(output.starts_with("No information available"), "{output}");
+    }
+
+    #[gpui::test]
+    async fn test_hover_rejects_private_files(cx: &mut TestAppContext) {
+        init_test(cx);
+        hide_generated_files(cx);
+        let (project, _fake_language_server, _lsp_handle) = setup(capabilities(), cx).await;
+
+        let error = run(project, input("root/src/generated.rs", 1), cx)
+            .await
+            .unwrap_err();
+        assert!(error.contains("private_files"), "{error}");
+    }
+}
@@ -0,0 +1,216 @@
+use super::project_tasks::{file_runnables, named_tasks, open_buffer};
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use gpui::{App, Entity, SharedString, Task};
+use project::{Project, TaskSourceKind};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use task::TaskTemplate;
+use util::markdown::MarkdownInlineCode;
+
+/// Lists the tasks defined for the project, and the runnables (such as tests) detected in a file.
+///
+/// Tasks come from the project's `.zed/tasks.json` files, the user's global tasks and the languages of the project's files. Run any of them with the `run_task` tool.
+///
+/// When a `path` is given, also lists the runnables in that file, such as test functions and `main` functions, with the line each one starts on and the tasks that can run it. Use this to find how to run a specific test.
+///
+/// <example>
+/// To see how to run the tests in `src/parser.rs`:
+/// {
+///     "path": "project/src/parser.rs"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct ListTasksToolInput {
+    /// The path of a file to list language tasks and runnables for.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+pub struct ListTasksTool {
+    project: Entity<Project>,
+}
+
+impl ListTasksTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for ListTasksTool {
+    type Input = ListTasksToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "list_tasks";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Read
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(ListTasksToolInput { path: Some(path) }) => {
+                format!("List tasks for {}", MarkdownInlineCode(&path)).into()
+            }
+            _ => "List tasks".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        _event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+
+            let buffer = match &input.path {
+                Some(path) => Some(open_buffer(&project, path, cx).await?),
+                None => None,
+            };
+
+            let mut output = String::new();
+            let tasks = named_tasks(&project, buffer.as_ref(), cx).await?;
+            if tasks.is_empty() {
+                output.push_str("No tasks are defined for this project.\n");
+            } else {
+                output.push_str("## Tasks\n\n");
+                for (kind, template) in &tasks {
+                    writeln!(
+                        output,
+                        "- {} ({}): {}",
+                        MarkdownInlineCode(&template.label),
+                        describe_source(kind),
+                        MarkdownInlineCode(&command_line(template))
+                    )
+                    .ok();
+                }
+            }
+
+            if let (Some(path), Some(buffer)) = (&input.path, &buffer) {
+                let runnables = file_runnables(&project, buffer, cx).await?;
+                if runnables.is_empty() {
+                    writeln!(output, "\nNo runnables were found in {path}.").ok();
+                } else {
+                    writeln!(output, "\n## Runnables in {path}\n").ok();
+                    for runnable in &runnables {
+                        let labels = runnable
+                            .templates
+                            .iter()
+                            .map(|(_, template)| MarkdownInlineCode(&template.label).to_string())
+                            .collect::<Vec<_>>()
+                            .join(", ");
+                        writeln!(
+                            output,
+                            "- L{}: {} can be run with {labels}",
+                            runnable.row + 1,
+                            MarkdownInlineCode(&runnable.line_text),
+                        )
+                        .ok();
+                    }
+                    output.push_str(
+                        "\nTo run a runnable, call `run_task` with its label, this path and its line.\n",
+                    );
+                }
+            }
+
+            Ok(output)
+        })
+    }
+}
+
+fn describe_source(kind: &TaskSourceKind) -> String {
+    match kind {
+        TaskSourceKind::UserInput => "one-off".to_string(),
+        TaskSourceKind::Worktree { .. } => "project task".to_string(),
+        TaskSourceKind::AbsPath { .. } => "global task".to_string(),
+        TaskSourceKind::Language { name } => format!("{name} task"),
+        TaskSourceKind::Lsp { language_name, .. } => {
+            format!("{language_name} language server task")
+        }
+    }
+}
+
+fn command_line(template: &TaskTemplate) -> String {
+    let mut command_line = template.command.clone();
+    for arg in &template.args {
+        command_line.push(' ');
+        command_line.push_str(arg);
+    }
+    command_line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::code_navigation::tests::init_test;
+    use gpui::TestAppContext;
+    use project::FakeFs;
+    use serde_json::json;
+    use util::path;
+
+    async fn run(
+        project: Entity<Project>,
+        path: Option<&str>,
+        cx: &mut TestAppContext,
+    ) -> Result<String, String> {
+        let tool = Arc::new(ListTasksTool::new(project));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let input = ListTasksToolInput {
+            path: path.map(str::to_string),
+        };
+        cx.update(|cx| tool.run(ToolInput::resolved(input), event_stream, cx))
+            .await
+    }
+
+    #[gpui::test]
+    async fn test_list_tasks(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                ".zed": {
+                    "tasks.json": r#"[{ "label": "build", "command": "cargo", "args": ["build"] }]"#,
+                },
+                ".env": "SECRET=1\n",
+                "notes.txt": "Nothing to run here.\n",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+        cx.run_until_parked();
+
+        let output = run(project.clone(), None, cx).await.unwrap();
+        assert_eq!(
+            output,
+            "## Tasks\n\n- `build` (project task): `cargo build`\n"
+        );
+
+        let output = run(project.clone(), Some("root/notes.txt"), cx)
+            .await
+            .unwrap();
+        assert_eq!(
+            output,
+            "## Tasks\n\n- `build` (project task): `cargo build`\n\n\
+            No runnables were found in root/notes.txt.\n"
+        );
+
+        let error = run(project, Some("root/.env"), cx).await.unwrap_err();
+        assert!(error.contains("private_files"), "{error}");
+    }
+}
@@ -0,0 +1,380 @@
+//! Helpers shared by the tools that list and run the project's tasks.
+
+use super::code_navigation::is_hidden_path;
+use collections::HashMap;
+use gpui::{AsyncApp, Entity};
+use language::{Bias, Buffer, LanguageName, Location, Point};
+use project::{Inventory, Project, TaskSourceKind, WorktreeId};
+use regex::Regex;
+use std::{ops::Range, path::Path, sync::LazyLock};
+use task::{TaskContext, TaskTemplate, TaskVariables, VariableName};
+
+/// The most failures to report from a single task run.
+const MAX_FAILURES: usize = 50;
+
+/// Something runnable detected in a file, such as a test or a `main` function.
+pub(crate) struct FileRunnable {
+    /// The 0-based row the runnable starts on.
+    pub row: u32,
+    pub column: u32,
+    /// The rows spanned by the runnable, such as the whole body of a test function.
+    pub rows: Range<u32>,
+    /// The trimmed text of the runnable's first line.
+    pub line_text: String,
+    /// Values captured by the language's runnable query, exposed to tasks as custom variables.
+    pub extra_variables: HashMap<String, String>,
+    pub templates: Vec<(TaskSourceKind, TaskTemplate)>,
+}
+
+pub(crate) fn task_inventory(
+    project: &Entity<Project>,
+    cx: &mut AsyncApp,
+) -> Result<Entity<Inventory>, String> {
+    project
+        .read_with(cx, |project, cx| {
+            project.task_store().read(cx).task_inventory().cloned()
+        })
+        .ok_or_else(|| "Tasks aren't available in this project".to_string())
+}
+
+/// Opens the file at `path`, refusing files the agent isn't allowed to see.
+pub(crate) async fn open_buffer(
+    project: &Entity<Project>,
+    path: &str,
+    cx: &mut AsyncApp,
+) -> Result<Entity<Buffer>, String> {
+    let open_buffer_task = project.update(cx, |project, cx| {
+        let project_path = project
+            .find_project_path(path, cx)
+            .ok_or_else(|| format!("Could not find path {path} in project"))?;
+        if is_hidden_path(&project_path, cx) {
+            return Err(format!(
+                "Cannot access {path} because it matches the `file_scan_exclusions` or `private_files` setting"
+            ));
+        }
+        Ok(project.open_buffer(project_path, cx))
+    })?;
+    open_buffer_task.await.map_err(|error| error.to_string())
+}
+
+/// Lists the tasks that can be run by name, worktree tasks first, language
+/// tasks for `buffer` second and global tasks last.
+///
+/// Tasks with tags are left out, as they only run through a runnable.
+pub(crate) async fn named_tasks(
+    project: &Entity<Project>,
+    buffer: Option<&Entity<Buffer>>,
+    cx: &mut AsyncApp,
+) -> Result<Vec<(TaskSourceKind, TaskTemplate)>, String> {
+    let inventory = task_inventory(project, cx)?;
+    let list_tasks = project.read_with(cx, |project, cx| {
+        let buffer_worktree_id = buffer
+            .and_then(|buffer| buffer.read(cx).file())
+            .map(|file| file.worktree_id(cx));
+        let mut tasks = project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).id())
+            .filter(|worktree_id| Some(*worktree_id) != buffer_worktree_id)
+            .map(|worktree_id| {
+                inventory
+                    .read(cx)
+                    .list_tasks(None, None, Some(worktree_id), cx)
+            })
+            .collect::<Vec<_>>();
+        let language = buffer.and_then(|buffer| buffer.read(cx).language().cloned());
+        tasks.push(inventory.read(cx).list_tasks(
+            buffer.cloned(),
+            language,
+            buffer_worktree_id,
+            cx,
+        ));
+        tasks
+    });
+
+    let mut tasks = Vec::new();
+    for list in list_tasks {
+        for (kind, template) in list.await {
+            if template.tags.is_empty()
+                && !tasks
+                    .iter()
+                    .any(|(existing_kind, existing): &(_, TaskTemplate)| {
+                        existing_kind == &kind && existing.label == template.label
+                    })
+            {
+                tasks.push((kind, template));
+            }
+        }
+    }
+    tasks.sort_by_key(|(kind, _)| match kind {
+        TaskSourceKind::Worktree { .. } => 0,
+        TaskSourceKind::Language { .. } | TaskSourceKind::Lsp { .. } => 1,
+        TaskSourceKind::UserInput | TaskSourceKind::AbsPath { .. } => 2,
+    });
+    Ok(tasks)
+}
+
+/// Finds the runnables in `buffer` and the tagged tasks that apply to each of
+/// them, the same way the editor does for its gutter run indicators.
+pub(crate) async fn file_runnables(
+    project: &Entity<Project>,
+    buffer: &Entity<Buffer>,
+    cx: &mut AsyncApp,
+) -> Result<Vec<FileRunnable>, String> {
+    let inventory = task_inventory(project, cx)?;
+    let (snapshot, worktree_id) = buffer.read_with(cx, |buffer, cx| {
+        (
+            buffer.snapshot(),
+            buffer.file().map(|file| file.worktree_id(cx)),
+        )
+    });
+    let runnable_ranges = cx
+        .background_spawn({
+            let snapshot = snapshot.clone();
+            async move {
+                snapshot
+                    .runnable_ranges(0..snapshot.len())
+                    .collect::<Vec<_>>()
+            }
+        })
+        .await;
+
+    let mut language_tasks: HashMap<LanguageName, Vec<(TaskSourceKind, TaskTemplate)>> =
+        HashMap::default();
+    let mut runnables = Vec::new();
+    for runnable_range in runnable_ranges {
+        let language = runnable_range.runnable.language.clone();
+        if !language_tasks.contains_key(&language.name()) {
+            let tasks = inventory.update(cx, |inventory, cx| {
+                inventory.list_tasks(
+                    Some(buffer.clone()),
+                    Some(language.clone()),
+                    worktree_id,
+                    cx,
+                )
+            });
+            language_tasks.insert(language.name(), tasks.await);
+        }
+        let tasks = &language_tasks[&language.name()];
+
+        let mut templates = tasks
+            .iter()
+            .filter(|(_, template)| {
+                runnable_range
+                    .runnable
+                    .tags
+                    .iter()
+                    .any(|tag| template.tags.iter().any(|source_tag| *source_tag == *tag.0))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        templates.sort_by_key(|(kind, _)| kind.to_owned());
+        // Strongest source wins, so that worktree tasks override global and
+        // language tasks bound to the same tag.
+        if let Some((leading_source, _)) = templates.first() {
+            let first_mismatch = templates
+                .iter()
+                .position(|(source, _)| source != leading_source);
+            if let Some(index) = first_mismatch {
+                templates.truncate(index);
+            }
+        }
+        if templates.is_empty() {
+            continue;
+        }
+
+        let start = snapshot.offset_to_point(runnable_range.run_range.start);
+        let full_range = snapshot.offset_to_point(runnable_range.full_range.start)
+            ..snapshot.offset_to_point(runnable_range.full_range.end);
+        let line_text = snapshot
+            .text_for_range(
+                Point::new(start.row, 0)..Point::new(start.row, snapshot.line_len(start.row)),
+            )
+            .collect::<String>()
+            .trim()
+            .to_string();
+        runnables.push(FileRunnable {
+            row: start.row,
+            column: start.column,
+            rows: full_range.start.row..full_range.end.row + 1,
+            line_text,
+            extra_variables: runnable_range.extra_captures,
+            templates,
+        });
+    }
+    runnables.sort_by_key(|runnable| (runnable.row, runnable.column));
+    runnables.dedup_by_key(|runnable| runnable.row);
+    Ok(runnables)
+}
+
+/// Builds the context for running a task from `buffer` at the given position,
+/// exposing the runnable's captured values as custom variables.
+pub(crate) async fn buffer_task_context(
+    project: &Entity<Project>,
+    buffer: &Entity<Buffer>,
+    position: Point,
+    extra_variables: &HashMap<String, String>,
+    cx: &mut AsyncApp,
+) -> Option<TaskContext> {
+    let mut captured_variables = TaskVariables::default();
+    for (name, value) in extra_variables {
+        captured_variables.insert(VariableName::Custom(name.clone().into()), value.clone());
+    }
+    let task = project.update(cx, |project, cx| {
+        let anchor = buffer.read(cx).anchor_at(position, Bias::Right);
+        let location = Location {
+            buffer: buffer.clone(),
+            range: anchor..anchor,
+        };
+        project.task_store().update(cx, |task_store, cx| {
+            task_store.task_context_for_location(captured_variables, location, cx)
+        })
+    });
+    task.await
+}
+
+/// Builds the context for running a task that isn't tied to a file, rooted at
+/// the task's own worktree or, failing that, the first visible worktree.
+pub(crate) fn worktree_task_context(
+    project: &Entity<Project>,
+    worktree_id: Option<WorktreeId>,
+    cx: &mut AsyncApp,
+) -> TaskContext {
+    let abs_path = project.read_with(cx, |project, cx| {
+        worktree_id
+            .and_then(|worktree_id| project.worktree_for_id(worktree_id, cx))
+            .or_else(|| project.visible_worktrees(cx).next())
+            .map(|worktree| worktree.read(cx).abs_path())
+    });
+    let mut task_variables = TaskVariables::default();
+    if let Some(abs_path) = &abs_path {
+        task_variables.insert(
+            VariableName::WorktreeRoot,
+            abs_path.to_string_lossy().into_owned(),
+        );
+    }
+    TaskContext {
+        cwd: abs_path.as_deref().map(Path::to_path_buf),
+        task_variables,
+        project_env: HashMap::default(),
+    }
+}
+
+static RUST_TEST_FAILURE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^test (\S+) \.\.\. FAILED$").unwrap());
+static RUST_PANIC: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^thread '([^']+)' panicked at ([^\s:]+:\d+:\d+):?$").unwrap());
+static RUST_COMPILER_ERROR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(error(?:\[E\d+\])?): (.+)$").unwrap());
+static RUST_ERROR_LOCATION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*--> ([^\s:]+:\d+:\d+)$").unwrap());
+static LOCATED_ERROR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^([^\s:]+:\d+(?::\d+)?):\s*(?:fatal )?error:?\s+(.+)$").unwrap());
+static PYTEST_FAILURE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^FAILED (\S+)(?: - (.+))?$").unwrap());
+static GO_TEST_FAILURE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*--- FAIL: (\S+)").unwrap());
+static JEST_FAILURE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*● (.+)$").unwrap());
+
+/// Extracts failing tests and compiler errors from a task's output, for the
+/// test runners and compilers whose output format we recognize.
+pub(crate) fn parse_failures(output: &str) -> Vec<String> {
+    let mut failures = Vec::new();
+    let mut lines = output.lines().peekable();
+    while let Some(line) = lines.next() {
+        let line = line.trim_end();
+        let failure = if let Some(captures) = RUST_TEST_FAILURE.captures(line) {
+            format!("test `{}` failed", &captures[1])
+        } else if let Some(captures) = RUST_PANIC.captures(line) {
+            format!("`{}` panicked at {}", &captures[1], &captures[2])
+        } else if let Some(captures) = RUST_COMPILER_ERROR.captures(line) {
+            match lines
+                .peek()
+                .and_then(|next| RUST_ERROR_LOCATION.captures(next))
+            {
+                Some(location) => {
+                    format!("{}: {}: {}", &location[1], &captures[1], &captures[2])
+                }
+                None => format!("{}: {}", &captures[1], &captures[2]),
+            }
+        } else if let Some(captures) = LOCATED_ERROR.captures(line) {
+            format!("{}: error: {}", &captures[1], &captures[2])
+        } else if let Some(captures) = PYTEST_FAILURE.captures(line) {
+            match captures.get(2) {
+                Some(reason) => format!("test `{}` failed: {}", &captures[1], reason.as_str()),
+                None => format!("test `{}` failed", &captures[1]),
+            }
+        } else if let Some(captures) = GO_TEST_FAILURE.captures(line) {
+            format!("test `{}` failed", &captures[1])
+        } else if let Some(captures) = JEST_FAILURE.captures(line) {
+            format!("test `{}` failed", &captures[1])
+        } else {
+            continue;
+        };
+        if !failures.contains(&failure) {
+            failures.push(failure);
+            if failures.len() == MAX_FAILURES {
+                break;
+            }
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    #[test]
+    fn test_parse_failures() {
+        let cargo_output = indoc! {"
+            running 3 tests
+            test config::tests::test_defaults ... ok
+            test config::tests::test_parse ... FAILED
+            test config::tests::test_merge ... ok
+
+            failures:
+
+            ---- config::tests::test_parse stdout ----
+
+            thread 'config::tests::test_parse' panicked at src/config.rs:42:9:
+            assertion `left == right` failed
+        "};
+        assert_eq!(
+            parse_failures(cargo_output),
+            vec![
+                "test `config::tests::test_parse` failed",
+                "`config::tests::test_parse` panicked at src/config.rs:42:9",
+            ]
+        );
+
+        let rustc_output = indoc! {"
+            error[E0308]: mismatched types
+              --> src/main.rs:4:18
+               |
+            4  |     let x: u32 = \"hello\";
+        "};
+        assert_eq!(
+            parse_failures(rustc_output),
+            vec!["src/main.rs:4:18: error[E0308]: mismatched types"]
+        );
+
+        let mixed_output = indoc! {"
+            main.c:10:5: error: use of undeclared identifier 'y'
+            FAILED tests/test_app.py::test_login - AssertionError: expected 200
+            --- FAIL: TestHandler (0.00s)
+              ● Parser › handles empty input
+        "};
+        assert_eq!(
+            parse_failures(mixed_output),
+            vec![
+                "main.c:10:5: error: use of undeclared identifier 'y'",
+                "test `tests/test_app.py::test_login` failed: AssertionError: expected 200",
+                "test `TestHandler` failed",
+                "test `Parser › handles empty input` failed",
+            ]
+        );
+
+        assert!(parse_failures("Compiling app v0.1.0\nFinished in 1.2s").is_empty());
+    }
+}
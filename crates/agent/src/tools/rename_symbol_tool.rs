@@ -0,0 +1,507 @@
+use super::code_navigation::{is_hidden_path, resolve_symbol};
+use super::tool_permissions::{SensitiveSettingsKind, sensitive_settings_kind};
+use crate::{
+    AgentTool, ToolCallEventStream, ToolInput, ToolPermissionDecision, decide_permission_for_paths,
+};
+use action_log::ActionLog;
+use agent_client_protocol as acp;
+use agent_settings::AgentSettings;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::Project;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::{collections::BTreeSet, path::Path, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+/// Renames a symbol and updates every reference to it across the project, using the language server for the file.
+///
+/// Prefer this tool over editing each occurrence by hand when renaming a function, type, field or variable, since the language server only touches real references to that exact symbol.
+///
+/// The symbol is identified by the file it appears in, the line it's on and its current name. Edited files are left unsaved, like edits made with the `edit_file` tool.
+///
+/// <example>
+/// To rename the `parse` function declared on line 12 of `src/config.rs` to `parse_config`:
+/// {
+///     "path": "project/src/config.rs",
+///     "line": 12,
+///     "symbol": "parse",
+///     "new_name": "parse_config"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RenameSymbolToolInput {
+    /// The path of the file containing the symbol.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    pub path: String,
+    /// The 1-based line number the symbol appears on.
+    pub line: u32,
+    /// The current name of the symbol, exactly as it appears on that line.
+    pub symbol: String,
+    /// The new name for the symbol.
+    pub new_name: String,
+}
+
+pub struct RenameSymbolTool {
+    project: Entity<Project>,
+    action_log: Entity<ActionLog>,
+}
+
+impl RenameSymbolTool {
+    pub fn new(project: Entity<Project>, action_log: Entity<ActionLog>) -> Self {
+        Self {
+            project,
+            action_log,
+        }
+    }
+}
+
+impl AgentTool for RenameSymbolTool {
+    type Input = RenameSymbolToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "rename_symbol";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Edit
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!(
+                "Rename {} to {}",
+                MarkdownInlineCode(&input.symbol),
+                MarkdownInlineCode(&input.new_name)
+            )
+            .into(),
+            Err(_) => "Rename symbol".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        let action_log = self.action_log.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+            if input.new_name.trim().is_empty() {
+                return Err("The new name must not be empty".to_string());
+            }
+
+            let resolved = resolve_symbol(
+                &project,
+                &input.path,
+                input.line,
+                &input.symbol,
+                &event_stream,
+                cx,
+            )
+            .await?;
+
+            // Resolve the edit before applying it, since the language server may
+            // touch more files than the symbol's references.
+            let rename_task = project.update(cx, |project, cx| {
+                if !project.is_local() {
+                    return Err(
+                        "Renaming symbols is only supported in local projects".to_string()
+                    );
+                }
+                Ok(project.resolve_rename(
+                    &resolved.buffer,
+                    resolved.position,
+                    input.new_name.clone(),
+                    cx,
+                ))
+            })?;
+            let rename = futures::select! {
+                result = rename_task.fuse() => result.map_err(|e| format!("Renaming `{}`: {e}", input.symbol))?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Rename cancelled by user".to_string());
+                }
+            };
+            let Some(rename) = rename else {
+                return Err(no_edits_error(&input.symbol));
+            };
+            if rename.has_file_operations() {
+                return Err(format!(
+                    "Cannot rename `{}` because the language server would also create, rename or delete files",
+                    input.symbol
+                ));
+            }
+            let abs_paths = rename.edited_paths().map_err(|e| e.to_string())?;
+            if abs_paths.is_empty() {
+                return Err(no_edits_error(&input.symbol));
+            }
+
+            let project_paths = project.read_with(cx, |project, cx| {
+                abs_paths
+                    .iter()
+                    .map(|abs_path| {
+                        let project_path =
+                            project.find_project_path(abs_path, cx).ok_or_else(|| {
+                                format!(
+                                    "Cannot rename `{}` because the language server would also edit {}, which is outside the project",
+                                    input.symbol,
+                                    abs_path.display()
+                                )
+                            })?;
+                        if is_hidden_path(&project_path, cx) {
+                            return Err(format!(
+                                "Cannot rename `{}` because some of its references are in files excluded by the `file_scan_exclusions` or `private_files` settings",
+                                input.symbol
+                            ));
+                        }
+                        Ok(project_path)
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })?;
+
+            let mut buffers = Vec::with_capacity(project_paths.len());
+            for project_path in project_paths {
+                let open_buffer =
+                    project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+                buffers.push(open_buffer.await.map_err(|e| e.to_string())?);
+            }
+            let paths = cx.update(|cx| {
+                buffers
+                    .iter()
+                    .filter_map(|buffer| {
+                        let file = buffer.read(cx).file()?;
+                        Some(file.full_path(cx).to_string_lossy().into_owned())
+                    })
+                    .collect::<Vec<_>>()
+            });
+
+            let decision = cx.update(|cx| {
+                decide_permission_for_paths(Self::NAME, &paths, AgentSettings::get_global(cx))
+            });
+            if let ToolPermissionDecision::Deny(reason) = decision {
+                return Err(reason);
+            }
+
+            let fs = project.read_with(cx, |project, _cx| project.fs().clone());
+            let mut settings_kind = None;
+            for path in &paths {
+                if let Some(kind) = sensitive_settings_kind(Path::new(path), fs.as_ref()).await {
+                    settings_kind = Some(kind);
+                    break;
+                }
+            }
+
+            let needs_confirmation = matches!(decision, ToolPermissionDecision::Confirm)
+                || (matches!(decision, ToolPermissionDecision::Allow) && settings_kind.is_some());
+            if needs_confirmation {
+                let authorize = cx.update(|cx| {
+                    let context = crate::ToolPermissionContext::new(Self::NAME, paths.clone());
+                    let title = format!(
+                        "Rename {} to {} in {} file(s)",
+                        MarkdownInlineCode(&input.symbol),
+                        MarkdownInlineCode(&input.new_name),
+                        paths.len()
+                    );
+                    let title = match settings_kind {
+                        Some(SensitiveSettingsKind::Local) => format!("{title} (local settings)"),
+                        Some(SensitiveSettingsKind::Global) => format!("{title} (settings)"),
+                        None => title,
+                    };
+                    event_stream.authorize(title, context, cx)
+                });
+                authorize.await.map_err(|e| e.to_string())?;
+            }
+
+            // Track the buffers before they change, so the rename shows up as
+            // reviewable agent edits.
+            action_log.update(cx, |action_log, cx| {
+                for buffer in &buffers {
+                    action_log.buffer_read(buffer.clone(), cx);
+                }
+            });
+
+            let transaction = project
+                .update(cx, |project, cx| project.apply_rename(rename, cx))
+                .await
+                .map_err(|e| format!("Renaming `{}`: {e}", input.symbol))?;
+            if transaction.0.is_empty() {
+                return Err(no_edits_error(&input.symbol));
+            }
+
+            let edited_paths = action_log.update(cx, |action_log, cx| {
+                let mut edited_paths = BTreeSet::default();
+                for buffer in transaction.0.keys() {
+                    action_log.buffer_edited(buffer.clone(), cx);
+                    if let Some(file) = buffer.read(cx).file() {
+                        edited_paths.insert(file.full_path(cx).display().to_string());
+                    }
+                }
+                edited_paths
+            });
+
+            let mut output = format!(
+                "Renamed `{}` to `{}` in {} file(s):\n",
+                input.symbol,
+                input.new_name,
+                edited_paths.len()
+            );
+            for path in edited_paths {
+                output.push_str(&format!("- {path}\n"));
+            }
+            Ok(output)
+        })
+    }
+}
+
+fn no_edits_error(symbol: &str) -> String {
+    format!(
+        "The language server didn't return any edits for renaming `{symbol}`. It may not support renaming this symbol or may still be starting."
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt as _;
+    use gpui::TestAppContext;
+    use language::{Buffer, LanguageConfig, LanguageMatcher};
+    use project::{FakeFs, lsp_store::OpenLspBufferHandle};
+    use serde_json::json;
+    use settings::SettingsStore;
+    use util::path;
+
+    use crate::ToolCallEventStream;
+
+    fn init_test(cx: &mut TestAppContext) {
+        cx.update(|cx| {
+            let settings_store = SettingsStore::test(cx);
+            cx.set_global(settings_store);
+        });
+        cx.update(|cx| {
+            let mut settings = AgentSettings::get_global(cx).clone();
+            settings.tool_permissions.default = settings::ToolPermissionMode::Allow;
+            AgentSettings::override_global(settings, cx);
+        });
+    }
+
+    /// Opens `/root/src/main.rs` with a fake Rust language server whose renames
+    /// edit `parse` at the given positions of each file.
+    async fn setup(
+        edits: &'static [(&'static str, &'static [(u32, u32)])],
+        cx: &mut TestAppContext,
+    ) -> (Entity<Project>, Entity<Buffer>, OpenLspBufferHandle) {
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            path!("/root"),
+            json!({
+                "src": {
+                    "main.rs": "fn parse() {}\nfn main() {\n    parse();\n}\n",
+                    "lib.rs": "pub fn run() {\n    crate::parse();\n}\n",
+                    "generated.rs": "pub use crate::parse;\n",
+                }
+            }),
+        )
+        .await;
+        let project = Project::test(fs, [path!("/root").as_ref()], cx).await;
+
+        let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+        language_registry.add(Arc::new(language::Language::new(
+            LanguageConfig {
+                name: "Rust".into(),
+                matcher: LanguageMatcher {
+                    path_suffixes: vec!["rs".to_string()],
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            None,
+        )));
+        let mut fake_language_servers = language_registry.register_fake_lsp(
+            "Rust",
+            language::FakeLspAdapter {
+                capabilities: lsp::ServerCapabilities {
+                    rename_provider: Some(lsp::OneOf::Left(true)),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/root/src/main.rs"), cx)
+            })
+            .await
+            .unwrap();
+        let lsp_handle = project.update(cx, |project, cx| {
+            project.register_buffer_with_language_servers(&buffer, cx)
+        });
+
+        let fake_language_server = fake_language_servers.next().await.unwrap();
+        fake_language_server.set_request_handler::<lsp::request::Rename, _, _>(
+            move |params, _| async move {
+                let changes = edits
+                    .iter()
+                    .map(|(path, positions)| {
+                        let edits = positions
+                            .iter()
+                            .map(|&(line, column)| {
+                                lsp::TextEdit::new(
+                                    lsp::Range::new(
+                                        lsp::Position::new(line, column),
+                                        lsp::Position::new(line, column + "parse".len() as u32),
+                                    ),
+                                    params.new_name.clone(),
+                                )
+                            })
+                            .collect();
+                        (lsp::Uri::from_file_path(path).unwrap(), edits)
+                    })
+                    .collect();
+                Ok(Some(lsp::WorkspaceEdit {
+                    changes: Some(changes),
+                    ..Default::default()
+                }))
+            },
+        );
+        cx.executor().run_until_parked();
+
+        (project, buffer, lsp_handle)
+    }
+
+    fn rename_input() -> RenameSymbolToolInput {
+        RenameSymbolToolInput {
+            path: "root/src/main.rs".into(),
+            line: 1,
+            symbol: "parse".into(),
+            new_name: "parse_config".into(),
+        }
+    }
+
+    #[gpui::test]
+    async fn test_rename_symbol_applies_edits_across_files(cx: &mut TestAppContext) {
+        init_test(cx);
+        let (project, buffer, _lsp_handle) = setup(
+            &[
+                (path!("/root/src/main.rs"), &[(0, 3), (2, 4)]),
+                (path!("/root/src/lib.rs"), &[(1, 11)]),
+            ],
+            cx,
+        )
+        .await;
+
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let tool = Arc::new(RenameSymbolTool::new(project, action_log.clone()));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let output = cx
+            .update(|cx| tool.run(ToolInput::resolved(rename_input()), event_stream, cx))
+            .await
+            .unwrap();
+
+        assert!(output.contains("in 2 file(s)"), "{output}");
+        assert!(
+            output.contains(&format!("- {}", path!("root/src/lib.rs"))),
+            "{output}"
+        );
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "fn parse_config() {}\nfn main() {\n    parse_config();\n}\n"
+        );
+        let changed_buffers =
+            action_log.read_with(cx, |action_log, cx| action_log.changed_buffers(cx).len());
+        assert_eq!(changed_buffers, 2);
+    }
+
+    #[gpui::test]
+    async fn test_rename_symbol_honors_deny_policy(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            let mut settings = AgentSettings::get_global(cx).clone();
+            settings.tool_permissions.tools.insert(
+                "rename_symbol".into(),
+                agent_settings::ToolRules {
+                    default: Some(settings::ToolPermissionMode::Deny),
+                    ..Default::default()
+                },
+            );
+            AgentSettings::override_global(settings, cx);
+        });
+        let (project, buffer, _lsp_handle) = setup(
+            &[
+                (path!("/root/src/main.rs"), &[(0, 3), (2, 4)]),
+                (path!("/root/src/lib.rs"), &[(1, 11)]),
+            ],
+            cx,
+        )
+        .await;
+
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let tool = Arc::new(RenameSymbolTool::new(project, action_log.clone()));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let result = cx
+            .update(|cx| tool.run(ToolInput::resolved(rename_input()), event_stream, cx))
+            .await;
+
+        assert!(result.is_err(), "{result:?}");
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "fn parse() {}\nfn main() {\n    parse();\n}\n"
+        );
+        let changed_buffers =
+            action_log.read_with(cx, |action_log, cx| action_log.changed_buffers(cx).len());
+        assert_eq!(changed_buffers, 0);
+    }
+
+    #[gpui::test]
+    async fn test_rename_symbol_rejects_edits_to_hidden_files(cx: &mut TestAppContext) {
+        init_test(cx);
+        cx.update(|cx| {
+            use gpui::UpdateGlobal;
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings(cx, |settings| {
+                    settings.project.worktree.private_files =
+                        Some(vec!["**/generated.rs".to_string()].into());
+                });
+            });
+        });
+        // The symbol's references are all visible, but the server also edits a
+        // private file.
+        let (project, buffer, _lsp_handle) = setup(
+            &[
+                (path!("/root/src/main.rs"), &[(0, 3), (2, 4)]),
+                (path!("/root/src/generated.rs"), &[(0, 15)]),
+            ],
+            cx,
+        )
+        .await;
+
+        let action_log = cx.new(|_| ActionLog::new(project.clone()));
+        let tool = Arc::new(RenameSymbolTool::new(project, action_log.clone()));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let result = cx
+            .update(|cx| tool.run(ToolInput::resolved(rename_input()), event_stream, cx))
+            .await;
+
+        let error = result.unwrap_err();
+        assert!(error.contains("private_files"), "{error}");
+        assert_eq!(
+            buffer.read_with(cx, |buffer, _| buffer.text()),
+            "fn parse() {}\nfn main() {\n    parse();\n}\n"
+        );
+        let changed_buffers =
+            action_log.read_with(cx, |action_log, cx| action_log.changed_buffers(cx).len());
+        assert_eq!(changed_buffers, 0);
+    }
+}
@@ -0,0 +1,379 @@
+use super::project_tasks::{
+    buffer_task_context, file_runnables, named_tasks, open_buffer, parse_failures,
+    worktree_task_context,
+};
+use crate::{
+    AgentTool, TerminalTool, ThreadEnvironment, ToolCallEventStream, ToolInput,
+    ToolPermissionDecision, decide_permission_from_settings,
+};
+use agent_client_protocol as acp;
+use agent_settings::AgentSettings;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use language::Point;
+use project::{Project, TaskSourceKind};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::{fmt::Write, rc::Rc, sync::Arc, time::Duration};
+use util::markdown::MarkdownInlineCode;
+
+const TASK_OUTPUT_LIMIT: u64 = 16 * 1024;
+
+/// Runs one of the project's tasks, or a runnable such as a test, and returns its exit code, its output and any failures found in the output.
+///
+/// Use the `list_tasks` tool first to find the label of the task to run. To run a runnable such as a single test, pass the `path` of its file and the `line` it's on, along with the label of one of its tasks.
+///
+/// Prefer this tool over `terminal` for building and testing, since tasks use the commands, arguments and environment the project is configured with. Tasks run in a new shell process each time, and are subject to the same permissions as `terminal` commands.
+///
+/// Do not use this tool for tasks that run indefinitely, such as servers or file watchers. For potentially long-running tasks, prefer specifying `timeout_ms`.
+///
+/// <example>
+/// To run the test starting on line 87 of `src/parser.rs`, listed by `list_tasks` with the label `cargo test $ZED_SYMBOL`:
+/// {
+///     "label": "cargo test $ZED_SYMBOL",
+///     "path": "project/src/parser.rs",
+///     "line": 87
+/// }
+/// </example>
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct RunTaskToolInput {
+    /// The label of the task to run, exactly as listed by the `list_tasks` tool.
+    pub label: String,
+    /// The path of a file to run the task for. Tasks use it to fill in variables such as the current file or symbol.
+    ///
+    /// This path should never be absolute, and the first component
+    /// of the path should always be a root directory in a project.
+    #[serde(default)]
+    pub path: Option<String>,
+    /// The 1-based line of the runnable to run in `path`, as listed by the `list_tasks` tool. Any line within the runnable (such as a line in the body of a test) works too.
+    #[serde(default)]
+    pub line: Option<u32>,
+    /// Optional maximum runtime (in milliseconds). If exceeded, the task is killed.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+pub struct RunTaskTool {
+    project: Entity<Project>,
+    environment: Rc<dyn ThreadEnvironment>,
+}
+
+impl RunTaskTool {
+    pub fn new(project: Entity<Project>, environment: Rc<dyn ThreadEnvironment>) -> Self {
+        Self {
+            project,
+            environment,
+        }
+    }
+}
+
+impl AgentTool for RunTaskTool {
+    type Input = RunTaskToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "run_task";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Execute
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Run task {}", MarkdownInlineCode(&input.label)).into(),
+            Err(_) => "Run task".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+
+            let buffer = match &input.path {
+                Some(path) => Some(open_buffer(&project, path, cx).await?),
+                None => None,
+            };
+
+            let (kind, template, context) = match (&buffer, input.line) {
+                (Some(buffer), Some(line)) => {
+                    let path = input.path.as_deref().unwrap_or_default();
+                    let row = line.checked_sub(1).ok_or("Line numbers start at 1")?;
+                    let runnables = file_runnables(&project, buffer, cx).await?;
+                    // The innermost runnable wins, e.g. a test inside a test module.
+                    let runnable = runnables
+                        .iter()
+                        .filter(|runnable| runnable.rows.contains(&row))
+                        .min_by_key(|runnable| runnable.rows.len())
+                        .ok_or_else(|| {
+                            let lines = runnables
+                                .iter()
+                                .map(|runnable| format!("L{}", runnable.row + 1))
+                                .collect::<Vec<_>>();
+                            if lines.is_empty() {
+                                format!("There are no runnables in {path}")
+                            } else {
+                                format!(
+                                    "There is no runnable on line {line} of {path}. Runnables start on lines {}",
+                                    lines.join(", ")
+                                )
+                            }
+                        })?;
+                    let (kind, template) = runnable
+                        .templates
+                        .iter()
+                        .find(|(_, template)| template.label == input.label)
+                        .cloned()
+                        .ok_or_else(|| {
+                            format!(
+                                "The runnable on line {} of {path} has no task labeled {}. Its tasks are: {}",
+                                runnable.row + 1,
+                                MarkdownInlineCode(&input.label),
+                                labels(&runnable.templates)
+                            )
+                        })?;
+                    let context = buffer_task_context(
+                        &project,
+                        buffer,
+                        Point::new(runnable.row, runnable.column),
+                        &runnable.extra_variables,
+                        cx,
+                    )
+                    .await;
+                    (kind, template, context)
+                }
+                (None, Some(_)) => {
+                    return Err(
+                        "A `path` is required when running the runnable on a `line`".to_string()
+                    );
+                }
+                (buffer, None) => {
+                    let tasks = named_tasks(&project, buffer.as_ref(), cx).await?;
+                    let (kind, template) = tasks
+                        .iter()
+                        .find(|(_, template)| template.label == input.label)
+                        .cloned()
+                        .ok_or_else(|| {
+                            format!(
+                                "There is no task labeled {}. Use the `list_tasks` tool to see the available tasks.",
+                                MarkdownInlineCode(&input.label)
+                            )
+                        })?;
+                    let context = match buffer {
+                        Some(buffer) => {
+                            buffer_task_context(
+                                &project,
+                                buffer,
+                                Point::zero(),
+                                &Default::default(),
+                                cx,
+                            )
+                            .await
+                        }
+                        None => {
+                            let worktree_id = match &kind {
+                                TaskSourceKind::Worktree { id, .. } => Some(*id),
+                                _ => None,
+                            };
+                            Some(worktree_task_context(&project, worktree_id, cx))
+                        }
+                    };
+                    (kind, template, context)
+                }
+            };
+
+            let context = context.ok_or_else(|| {
+                format!(
+                    "Could not determine the context to run {} in",
+                    MarkdownInlineCode(&input.label)
+                )
+            })?;
+            let resolved = template
+                .resolve_task(&kind.to_id_base(), &context)
+                .ok_or_else(|| {
+                    format!(
+                        "Task {} uses variables that aren't available here. Pass the `path` (and `line`) of the file it should run for.",
+                        MarkdownInlineCode(&input.label)
+                    )
+                })?;
+            let command_label = resolved.resolved.command_label.clone();
+
+            // Tasks run shell commands, so they're held to the user's terminal
+            // permission rules rather than rules of their own.
+            let authorize = cx.update(|cx| {
+                let decision = decide_permission_from_settings(
+                    TerminalTool::NAME,
+                    std::slice::from_ref(&command_label),
+                    AgentSettings::get_global(cx),
+                );
+                match decision {
+                    ToolPermissionDecision::Allow => Ok(None),
+                    ToolPermissionDecision::Deny(reason) => Err(reason),
+                    ToolPermissionDecision::Confirm => {
+                        let context = crate::ToolPermissionContext::new(
+                            TerminalTool::NAME,
+                            vec![command_label.clone()],
+                        );
+                        Ok(Some(event_stream.authorize(
+                            format!(
+                                "Run task {}: {}",
+                                MarkdownInlineCode(&resolved.resolved_label),
+                                MarkdownInlineCode(&command_label)
+                            ),
+                            context,
+                            cx,
+                        )))
+                    }
+                }
+            })?;
+            if let Some(authorize) = authorize {
+                authorize.await.map_err(|e| e.to_string())?;
+            }
+
+            let terminal = self
+                .environment
+                .create_task_terminal(resolved.resolved.clone(), Some(TASK_OUTPUT_LIMIT), cx)
+                .await
+                .map_err(|e| e.to_string())?;
+            let terminal_id = terminal.id(cx).map_err(|e| e.to_string())?;
+            event_stream.update_fields(acp::ToolCallUpdateFields::new().content(vec![
+                acp::ToolCallContent::Terminal(acp::Terminal::new(terminal_id)),
+            ]));
+
+            let executor = cx.background_executor().clone();
+            let timeout_ms = input.timeout_ms;
+            let timeout = async move {
+                match timeout_ms {
+                    Some(timeout_ms) => executor.timer(Duration::from_millis(timeout_ms)).await,
+                    None => futures::future::pending().await,
+                }
+            };
+            let wait_for_exit = terminal.wait_for_exit(cx).map_err(|e| e.to_string())?;
+            let mut timed_out = false;
+            futures::select! {
+                _ = wait_for_exit.clone().fuse() => {},
+                _ = timeout.fuse() => {
+                    timed_out = true;
+                    terminal.kill(cx).map_err(|e| e.to_string())?;
+                    wait_for_exit.await;
+                }
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    terminal.kill(cx).map_err(|e| e.to_string())?;
+                    wait_for_exit.await;
+                }
+            }
+            let user_stopped = event_stream.was_cancelled_by_user()
+                || terminal.was_stopped_by_user(cx).unwrap_or(false);
+
+            let output = terminal.current_output(cx).map_err(|e| e.to_string())?;
+            Ok(task_report(
+                &resolved.resolved_label,
+                &command_label,
+                output,
+                timed_out,
+                user_stopped,
+            ))
+        })
+    }
+}
+
+fn labels(templates: &[(TaskSourceKind, task::TaskTemplate)]) -> String {
+    templates
+        .iter()
+        .map(|(_, template)| MarkdownInlineCode(&template.label).to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn task_report(
+    label: &str,
+    command: &str,
+    output: acp::TerminalOutputResponse,
+    timed_out: bool,
+    user_stopped: bool,
+) -> String {
+    let task = format!(
+        "Task {} ({})",
+        MarkdownInlineCode(label),
+        MarkdownInlineCode(command)
+    );
+    let mut report = if user_stopped {
+        format!(
+            "{task} was stopped by the user. Ask them what they would like to do next rather than rerunning it."
+        )
+    } else if timed_out {
+        format!("{task} timed out.")
+    } else {
+        match output
+            .exit_status
+            .as_ref()
+            .and_then(|status| status.exit_code)
+        {
+            Some(0) => format!("{task} succeeded with exit code 0."),
+            Some(exit_code) => format!("{task} failed with exit code {exit_code}."),
+            None => format!("{task} terminated unexpectedly."),
+        }
+    };
+
+    let content = output.output.trim();
+    let failures = parse_failures(content);
+    if !failures.is_empty() {
+        report.push_str("\n\nFailures:\n");
+        for failure in &failures {
+            writeln!(report, "- {failure}").ok();
+        }
+    }
+
+    if content.is_empty() {
+        report.push_str("\n\nNo output was captured.");
+    } else {
+        let truncated = if output.truncated { " (truncated)" } else { "" };
+        write!(report, "\n\nOutput{truncated}:\n\n```\n{content}\n```").ok();
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_report() {
+        let output = acp::TerminalOutputResponse::new(
+            "running 1 test\ntest tests::test_add ... FAILED\n".to_string(),
+            false,
+        )
+        .exit_status(acp::TerminalExitStatus::new().exit_code(101));
+        assert_eq!(
+            task_report("cargo test", "cargo test -p math", output, false, false),
+            "Task `cargo test` (`cargo test -p math`) failed with exit code 101.\n\n\
+            Failures:\n\
+            - test `tests::test_add` failed\n\n\
+            Output:\n\n\
+            ```\n\
+            running 1 test\n\
+            test tests::test_add ... FAILED\n\
+            ```"
+        );
+
+        let output = acp::TerminalOutputResponse::new("".to_string(), false)
+            .exit_status(acp::TerminalExitStatus::new().exit_code(0));
+        assert_eq!(
+            task_report("build", "make", output, false, false),
+            "Task `build` (`make`) succeeded with exit code 0.\n\nNo output was captured."
+        );
+    }
+}
@@ -67,9 +67,13 @@ impl AgentTool for WebSearchTool {
         "Searching the Web".into()
     }
 
-    /// We currently only support Zed Cloud as a provider.
-    fn supports_provider(provider: &LanguageModelProviderId) -> bool {
+    /// Zed's hosted web search is only available with Zed's models, while
+    /// self-hosted search providers work with models from any provider.
+    fn supports_provider(provider: &LanguageModelProviderId, cx: &App) -> bool {
         provider == &ZED_CLOUD_PROVIDER_ID
+            || WebSearchRegistry::try_read_global(cx)
+                .and_then(|registry| registry.active_provider())
+                .is_some_and(|provider| provider.supports_any_model_provider())
     }
 
     fn run(
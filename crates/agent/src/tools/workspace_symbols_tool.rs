@@ -0,0 +1,211 @@
+use super::code_navigation::is_hidden_path;
+use crate::{AgentTool, ToolCallEventStream, ToolInput};
+use agent_client_protocol as acp;
+use anyhow::Result;
+use futures::FutureExt as _;
+use gpui::{App, Entity, SharedString, Task};
+use project::{Project, lsp_store::SymbolLocation};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::{fmt::Write, sync::Arc};
+use util::markdown::MarkdownInlineCode;
+
+const MAX_RESULTS: usize = 50;
+
+/// Searches for symbols (functions, types, constants, modules, etc.) by name across the whole project, using the project's language servers.
+///
+/// Prefer this tool over `grep` or `find_path` when you know (part of) the name of a symbol but not where it's declared. Matching is fuzzy, so a partial name is usually enough.
+///
+/// Returns up to 50 symbols, each with its kind, its container (if any) and the path and line it's declared on.
+///
+/// <example>
+/// To find where the `ThreadStore` type is declared:
+/// {
+///     "query": "ThreadStore"
+/// }
+/// </example>
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+pub struct WorkspaceSymbolsToolInput {
+    /// The name, or part of the name, of the symbol to search for.
+    pub query: String,
+}
+
+pub struct WorkspaceSymbolsTool {
+    project: Entity<Project>,
+}
+
+impl WorkspaceSymbolsTool {
+    pub fn new(project: Entity<Project>) -> Self {
+        Self { project }
+    }
+}
+
+impl AgentTool for WorkspaceSymbolsTool {
+    type Input = WorkspaceSymbolsToolInput;
+    type Output = String;
+
+    const NAME: &'static str = "workspace_symbols";
+
+    fn kind() -> acp::ToolKind {
+        acp::ToolKind::Search
+    }
+
+    fn initial_title(
+        &self,
+        input: Result<Self::Input, serde_json::Value>,
+        _cx: &mut App,
+    ) -> SharedString {
+        match input {
+            Ok(input) => format!("Search symbols for {}", MarkdownInlineCode(&input.query)).into(),
+            Err(_) => "Search symbols".into(),
+        }
+    }
+
+    fn run(
+        self: Arc<Self>,
+        input: ToolInput<Self::Input>,
+        event_stream: ToolCallEventStream,
+        cx: &mut App,
+    ) -> Task<Result<Self::Output, Self::Output>> {
+        let project = self.project.clone();
+        cx.spawn(async move |cx| {
+            let input = input
+                .recv()
+                .await
+                .map_err(|e| format!("Failed to receive tool input: {e}"))?;
+            if input.query.trim().is_empty() {
+                return Err("The query must not be empty".to_string());
+            }
+
+            let symbols_task = project.update(cx, |project, cx| project.symbols(&input.query, cx));
+            let symbols = futures::select! {
+                result = symbols_task.fuse() => result.map_err(|e| e.to_string())?,
+                _ = event_stream.cancelled_by_user().fuse() => {
+                    return Err("Symbol search cancelled by user".to_string());
+                }
+            };
+
+            let lines = project.read_with(cx, |project, cx| {
+                let path_style = project.path_style(cx);
+                symbols
+                    .iter()
+                    .filter_map(|symbol| {
+                        let SymbolLocation::InProject(project_path) = &symbol.path else {
+                            return None;
+                        };
+                        if is_hidden_path(project_path, cx) {
+                            return None;
+                        }
+                        let worktree = project.worktree_for_id(project_path.worktree_id, cx)?;
+                        let path = worktree
+                            .read(cx)
+                            .root_name()
+                            .join(&project_path.path)
+                            .display(path_style)
+                            .to_string();
+
+                        let mut line = format!("- `{}` ({:?})", symbol.label.text, symbol.kind);
+                        if let Some(container_name) = &symbol.container_name {
+                            write!(line, " in `{container_name}`").ok();
+                        }
+                        write!(line, " at {path}:{}", symbol.range.start.0.row + 1).ok();
+                        Some(line)
+                    })
+                    .take(MAX_RESULTS)
+                    .collect::<Vec<_>>()
+            });
+
+            if lines.is_empty() {
+                Ok(format!(
+                    "No symbols found matching `{}` in the project. The language servers may not support workspace symbols or may still be starting.",
+                    input.query
+                ))
+            } else {
+                Ok(lines.join("\n"))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::code_navigation::tests::{
+        hide_generated_files, init_test, parse_location, setup,
+    };
+    use gpui::TestAppContext;
+    use util::path;
+
+    fn capabilities() -> lsp::ServerCapabilities {
+        lsp::ServerCapabilities {
+            workspace_symbol_provider: Some(lsp::OneOf::Left(true)),
+            ..Default::default()
+        }
+    }
+
+    fn symbol(name: &str, location: lsp::Location) -> lsp::SymbolInformation {
+        #[allow(deprecated)]
+        lsp::SymbolInformation {
+            name: name.to_string(),
+            kind: lsp::SymbolKind::FUNCTION,
+            tags: None,
+            deprecated: None,
+            container_name: None,
+            location,
+        }
+    }
+
+    async fn run(
+        project: Entity<Project>,
+        query: &str,
+        cx: &mut TestAppContext,
+    ) -> Result<String, String> {
+        let tool = Arc::new(WorkspaceSymbolsTool::new(project));
+        let (event_stream, _event_rx) = ToolCallEventStream::test();
+        let input = WorkspaceSymbolsToolInput {
+            query: query.into(),
+        };
+        cx.update(|cx| tool.run(ToolInput::resolved(input), event_stream, cx))
+            .await
+    }
+
+    #[gpui::test]
+    async fn test_workspace_symbols(cx: &mut TestAppContext) {
+        init_test(cx);
+        hide_generated_files(cx);
+        let (project, fake_language_server, _lsp_handle) = setup(capabilities(), cx).await;
+        fake_language_server.set_request_handler::<lsp::WorkspaceSymbolRequest, _, _>(
+            |params, _| async move {
+                if params.query != "parse" {
+                    return Ok(None);
+                }
+                Ok(Some(lsp::WorkspaceSymbolResponse::Flat(vec![
+                    symbol("parse", parse_location(path!("/root/src/main.rs"), 0, 3)),
+                    symbol(
+                        "parse_generated",
+                        parse_location(path!("/root/src/generated.rs"), 0, 15),
+                    ),
+                    symbol(
+                        "parse_external",
+                        parse_location(path!("/external.rs"), 0, 0),
+                    ),
+                ])))
+            },
+        );
+
+        // Symbols in private files and outside the project are left out.
+        let output = run(project.clone(), "parse", cx).await.unwrap();
+        assert_eq!(output.lines().count(), 1, "{output}");
+        assert!(output.starts_with("- `parse` ("), "{output}");
+        assert!(
+            output.ends_with(&format!(" at {}:1", path!("root/src/main.rs"))),
+            "{output}"
+        );
+
+        let output = run(project.clone(), "missing", cx).await.unwrap();
+        assert!(output.starts_with("No symbols found"), "{output}");
+
+        let error = run(project, " ", cx).await.unwrap_err();
+        assert_eq!(error, "The query must not be empty");
+    }
+}
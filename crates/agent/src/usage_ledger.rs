@@ -0,0 +1,820 @@
+use crate::{DbUsageFilter, DbUsageRecord, DbUsageTotal, ThreadsDatabase};
+use agent_client_protocol as acp;
+use agent_settings::{AgentSettings, AgentUsageSettings, UsageBudget};
+use anyhow::{Result, anyhow};
+use chrono::{DateTime, Datelike as _, Local, NaiveDate, TimeZone, Utc};
+use collections::{HashMap, HashSet};
+use gpui::{App, Context, Entity, EventEmitter, Global, SharedString, Task, prelude::*};
+use language_model::TokenUsage;
+use settings::{Settings as _, UsageBudgetPeriod, UsageBudgetScope, UsageBudgetUnit};
+use util::path_list::PathList;
+
+struct GlobalUsageLedger(Entity<UsageLedger>);
+
+impl Global for GlobalUsageLedger {}
+
+/// Records the tokens used by the agent's completion requests, and enforces
+/// the budgets from the `agent.usage` settings.
+pub struct UsageLedger {
+    /// The soft limits that have been warned about, so that each budget warns
+    /// once per period.
+    warned: HashSet<WarningKey>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct WarningKey {
+    budget_ix: usize,
+    scope: Option<String>,
+    period_start: Option<DateTime<Utc>>,
+    unpriced: bool,
+}
+
+pub enum UsageLedgerEvent {
+    Recorded,
+    BudgetWarning {
+        folder_paths: PathList,
+        message: SharedString,
+    },
+}
+
+impl EventEmitter<UsageLedgerEvent> for UsageLedger {}
+
+/// What a completion request is about to be charged to.
+#[derive(Clone, Debug)]
+pub struct BudgetContext {
+    pub session_id: acp::SessionId,
+    pub folder_paths: PathList,
+    pub provider: SharedString,
+    pub model: SharedString,
+}
+
+impl UsageLedger {
+    pub fn init_global(cx: &mut App) {
+        let ledger = cx.new(|_| Self {
+            warned: HashSet::default(),
+        });
+        cx.set_global(GlobalUsageLedger(ledger));
+    }
+
+    pub fn global(cx: &App) -> Entity<Self> {
+        cx.global::<GlobalUsageLedger>().0.clone()
+    }
+
+    pub fn try_global(cx: &App) -> Option<Entity<Self>> {
+        cx.try_global::<GlobalUsageLedger>().map(|g| g.0.clone())
+    }
+
+    pub fn record(&mut self, record: DbUsageRecord, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.spawn(async move |this, cx| {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.record_usage(record).await?;
+            this.update(cx, |_, cx| cx.emit(UsageLedgerEvent::Recorded))
+        })
+    }
+
+    /// Returns the usage recorded since the given time, oldest first.
+    pub fn usage(
+        &self,
+        since: Option<DateTime<Utc>>,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Vec<DbUsageRecord>>> {
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.background_spawn(async move {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            database.list_usage(since).await
+        })
+    }
+
+    /// Fails when a hard limit of a budget that applies to the request has
+    /// been reached, and warns about soft limits that have been reached.
+    pub fn check_budgets(
+        &mut self,
+        context: BudgetContext,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        let settings = AgentSettings::get_global(cx).usage.clone();
+        let now = Local::now();
+        let filters = settings
+            .budgets
+            .iter()
+            .enumerate()
+            .filter_map(|(budget_ix, budget)| {
+                Some((budget_ix, usage_filter(budget, &context, now)?))
+            })
+            .collect::<Vec<_>>();
+        if filters.is_empty() {
+            return Task::ready(Ok(()));
+        }
+
+        let database_future = ThreadsDatabase::connect(cx);
+        cx.spawn(async move |this, cx| {
+            let database = database_future.await.map_err(|err| anyhow!(err))?;
+            let mut outcomes = Vec::new();
+            for (budget_ix, filter) in filters {
+                let budget = &settings.budgets[budget_ix];
+                let totals = database.usage_totals(filter).await?;
+                if let Some(outcome) = evaluate_budget(budget, &totals, &context, &settings) {
+                    outcomes.push((budget_ix, budget, outcome));
+                }
+            }
+
+            this.update(cx, |this, cx| {
+                for (budget_ix, budget, outcome) in outcomes {
+                    match outcome {
+                        BudgetOutcome::HardLimitReached { spent, limit } => {
+                            return Err(anyhow!(
+                                "{} Raise the limit in the `agent.usage.budgets` setting to continue.",
+                                describe_spending(budget, spent, limit, "hard")
+                            ));
+                        }
+                        BudgetOutcome::UnpricedModels {
+                            models,
+                            limit,
+                            hard: true,
+                        } => {
+                            return Err(anyhow!(
+                                "{} Add prices for them to the `agent.usage.pricing` setting to continue.",
+                                describe_unpriced_models(&models, limit, "hard")
+                            ));
+                        }
+                        BudgetOutcome::SoftLimitReached { spent, limit } => {
+                            this.warn(
+                                budget_ix,
+                                budget,
+                                &context,
+                                now,
+                                false,
+                                describe_spending(budget, spent, limit, "soft"),
+                                cx,
+                            );
+                        }
+                        BudgetOutcome::UnpricedModels {
+                            models,
+                            limit,
+                            hard: false,
+                        } => {
+                            this.warn(
+                                budget_ix,
+                                budget,
+                                &context,
+                                now,
+                                true,
+                                describe_unpriced_models(&models, limit, "soft"),
+                                cx,
+                            );
+                        }
+                    }
+                }
+                Ok(())
+            })?
+        })
+    }
+
+    /// Emits a warning about a budget, once per period of the budget.
+    fn warn(
+        &mut self,
+        budget_ix: usize,
+        budget: &UsageBudget,
+        context: &BudgetContext,
+        now: DateTime<Local>,
+        unpriced: bool,
+        message: String,
+        cx: &mut Context<Self>,
+    ) {
+        let key = WarningKey {
+            budget_ix,
+            scope: scope_key(budget.scope, context),
+            period_start: period_start(budget.period, now),
+            unpriced,
+        };
+        if self.warned.insert(key) {
+            cx.emit(UsageLedgerEvent::BudgetWarning {
+                folder_paths: context.folder_paths.clone(),
+                message: message.into(),
+            });
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum BudgetOutcome {
+    SoftLimitReached {
+        spent: f64,
+        limit: f64,
+    },
+    HardLimitReached {
+        spent: f64,
+        limit: f64,
+    },
+    /// Usage of these models counts towards a dollar budget, but they have no
+    /// price, so the `hard` or soft limit of the budget can't be enforced.
+    UnpricedModels {
+        models: Vec<String>,
+        limit: f64,
+        hard: bool,
+    },
+}
+
+/// Returns which recorded usage counts towards the budget in its current
+/// period, or `None` if the budget doesn't apply to the request.
+fn usage_filter(
+    budget: &UsageBudget,
+    context: &BudgetContext,
+    now: DateTime<Local>,
+) -> Option<DbUsageFilter> {
+    if budget
+        .provider
+        .as_ref()
+        .is_some_and(|provider| provider.as_str() != context.provider.as_ref())
+    {
+        return None;
+    }
+
+    Some(DbUsageFilter {
+        since: period_start(budget.period, now),
+        session_id: (budget.scope == UsageBudgetScope::Thread).then(|| context.session_id.clone()),
+        folder_paths: (budget.scope == UsageBudgetScope::Project)
+            .then(|| context.folder_paths.clone()),
+        provider: budget.provider.clone().map(SharedString::from),
+    })
+}
+
+/// Returns the limit of the budget that the usage counting towards it has
+/// reached. Dollar budgets fail closed: a hard limit can't be kept when the
+/// recorded usage or the request is for a model without a price.
+fn evaluate_budget(
+    budget: &UsageBudget,
+    totals: &[DbUsageTotal],
+    context: &BudgetContext,
+    settings: &AgentUsageSettings,
+) -> Option<BudgetOutcome> {
+    let mut spent = 0.;
+    let mut unpriced_models = Vec::new();
+    for total in totals {
+        spent += match budget.unit {
+            UsageBudgetUnit::Dollars => settings
+                .cost(&total.provider, &total.model, &total.usage)
+                .unwrap_or_else(|| {
+                    if total.usage.total_tokens() > 0 {
+                        unpriced_models.push(format!("{}/{}", total.provider, total.model));
+                    }
+                    0.
+                }),
+            UsageBudgetUnit::Tokens => total.usage.total_tokens() as f64,
+        };
+    }
+    if budget.unit == UsageBudgetUnit::Dollars
+        && settings
+            .pricing_for(&context.provider, &context.model)
+            .is_none()
+    {
+        unpriced_models.push(format!("{}/{}", context.provider, context.model));
+    }
+    unpriced_models.sort();
+    unpriced_models.dedup();
+
+    if let Some(limit) = budget.hard_limit
+        && spent >= limit
+    {
+        Some(BudgetOutcome::HardLimitReached { spent, limit })
+    } else if let Some(limit) = budget.hard_limit
+        && !unpriced_models.is_empty()
+    {
+        Some(BudgetOutcome::UnpricedModels {
+            models: unpriced_models,
+            limit,
+            hard: true,
+        })
+    } else if let Some(limit) = budget.soft_limit
+        && spent >= limit
+    {
+        Some(BudgetOutcome::SoftLimitReached { spent, limit })
+    } else if let Some(limit) = budget.soft_limit
+        && !unpriced_models.is_empty()
+    {
+        Some(BudgetOutcome::UnpricedModels {
+            models: unpriced_models,
+            limit,
+            hard: false,
+        })
+    } else {
+        None
+    }
+}
+
+/// Returns when the current period of a budget started, in local time, or
+/// `None` for budgets that never start over.
+fn period_start<Tz: TimeZone>(
+    period: UsageBudgetPeriod,
+    now: DateTime<Tz>,
+) -> Option<DateTime<Utc>> {
+    let date = now.date_naive();
+    let start = match period {
+        UsageBudgetPeriod::Day => date,
+        UsageBudgetPeriod::Month => date.with_day(1)?,
+        UsageBudgetPeriod::AllTime => return None,
+    };
+    now.timezone()
+        .from_local_datetime(&start.and_hms_opt(0, 0, 0)?)
+        .earliest()
+        .map(|start| start.with_timezone(&Utc))
+}
+
+fn scope_key(scope: UsageBudgetScope, context: &BudgetContext) -> Option<String> {
+    match scope {
+        UsageBudgetScope::Thread => Some(context.session_id.to_string()),
+        UsageBudgetScope::Project => Some(context.folder_paths.serialize().paths),
+        UsageBudgetScope::All => None,
+    }
+}
+
+fn describe_spending(budget: &UsageBudget, spent: f64, limit: f64, kind: &str) -> String {
+    let subject = match budget.scope {
+        UsageBudgetScope::Thread => "This thread",
+        UsageBudgetScope::Project => "This project",
+        UsageBudgetScope::All => "The agent",
+    };
+    let provider = budget
+        .provider
+        .as_ref()
+        .map(|provider| format!(" with {provider}"))
+        .unwrap_or_default();
+    let period = match budget.period {
+        UsageBudgetPeriod::Day => " today",
+        UsageBudgetPeriod::Month => " this month",
+        UsageBudgetPeriod::AllTime => "",
+    };
+    let amount = |amount: f64| match budget.unit {
+        UsageBudgetUnit::Dollars => format!("${amount:.2}"),
+        UsageBudgetUnit::Tokens => format!("{} tokens", amount as u64),
+    };
+    format!(
+        "{subject} has used {}{provider}{period}, reaching its {kind} limit of {}.",
+        amount(spent),
+        amount(limit)
+    )
+}
+
+fn describe_unpriced_models(models: &[String], limit: f64, kind: &str) -> String {
+    format!(
+        "The cost of {} isn't known, so the {kind} limit of ${limit:.2} can't be enforced.",
+        models.join(", ")
+    )
+}
+
+/// Usage added up by one of the dashboard's breakdowns.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UsageTotals {
+    pub label: SharedString,
+    pub requests: usize,
+    pub usage: TokenUsage,
+    /// The estimated cost in dollars of the requests whose model has a price,
+    /// or `None` when none of them do.
+    pub cost: Option<f64>,
+    /// The number of requests whose model has no price, which aren't
+    /// included in `cost`.
+    pub unpriced_requests: usize,
+}
+
+impl UsageTotals {
+    fn add(&mut self, record: &DbUsageRecord, cost: Option<f64>) {
+        self.requests += 1;
+        self.usage = self.usage + record.usage;
+        match cost {
+            Some(cost) => *self.cost.get_or_insert(0.) += cost,
+            None => self.unpriced_requests += 1,
+        }
+    }
+}
+
+/// The recorded usage, broken down for the usage dashboard.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UsageSummary {
+    pub total: UsageTotals,
+    /// Newest day first.
+    pub by_day: Vec<UsageTotals>,
+    /// Most expensive first, here and below.
+    pub by_project: Vec<UsageTotals>,
+    pub by_thread: Vec<UsageTotals>,
+    pub by_model: Vec<UsageTotals>,
+    /// The models that were used without a price, as `provider/model`.
+    pub unpriced_models: Vec<SharedString>,
+}
+
+impl UsageSummary {
+    pub fn new(records: &[DbUsageRecord], settings: &AgentUsageSettings) -> Self {
+        let mut total = UsageTotals {
+            label: "Total".into(),
+            ..Default::default()
+        };
+        let mut by_day = HashMap::<NaiveDate, UsageTotals>::default();
+        let mut by_project = HashMap::<PathList, UsageTotals>::default();
+        let mut by_thread = HashMap::<acp::SessionId, UsageTotals>::default();
+        let mut by_model = HashMap::<(SharedString, SharedString), UsageTotals>::default();
+
+        for record in records {
+            let cost = settings.cost(&record.provider, &record.model, &record.usage);
+            total.add(record, cost);
+
+            let day = record.recorded_at.with_timezone(&Local).date_naive();
+            by_day
+                .entry(day)
+                .or_insert_with(|| totals(day.format("%Y-%m-%d").to_string()))
+                .add(record, cost);
+            by_project
+                .entry(record.folder_paths.clone())
+                .or_insert_with(|| totals(project_label(&record.folder_paths)))
+                .add(record, cost);
+            let thread = by_thread
+                .entry(record.session_id.clone())
+                .or_insert_with(|| totals(String::new()));
+            // Records are oldest first, so this keeps the latest title.
+            thread.label = record
+                .thread_title
+                .clone()
+                .unwrap_or_else(|| "Deleted thread".into());
+            thread.add(record, cost);
+            by_model
+                .entry((record.provider.clone(), record.model.clone()))
+                .or_insert_with(|| totals(format!("{}/{}", record.provider, record.model)))
+                .add(record, cost);
+        }
+
+        let mut by_day = by_day.into_iter().collect::<Vec<_>>();
+        by_day.sort_by(|(a, _), (b, _)| b.cmp(a));
+
+        let by_model = most_expensive_first(by_model.into_values());
+        let mut unpriced_models = by_model
+            .iter()
+            .filter(|totals| totals.cost.is_none())
+            .map(|totals| totals.label.clone())
+            .collect::<Vec<_>>();
+        unpriced_models.sort();
+
+        Self {
+            total,
+            by_day: by_day.into_iter().map(|(_, totals)| totals).collect(),
+            by_project: most_expensive_first(by_project.into_values()),
+            by_thread: most_expensive_first(by_thread.into_values()),
+            by_model,
+            unpriced_models,
+        }
+    }
+}
+
+fn totals(label: String) -> UsageTotals {
+    UsageTotals {
+        label: label.into(),
+        ..Default::default()
+    }
+}
+
+fn project_label(folder_paths: &PathList) -> String {
+    if folder_paths.is_empty() {
+        return "No project".into();
+    }
+    folder_paths
+        .ordered_paths()
+        .map(|path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.to_string_lossy().into_owned())
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn most_expensive_first(totals: impl IntoIterator<Item = UsageTotals>) -> Vec<UsageTotals> {
+    let mut totals = totals.into_iter().collect::<Vec<_>>();
+    totals.sort_by(|a, b| {
+        b.cost
+            .unwrap_or(0.)
+            .total_cmp(&a.cost.unwrap_or(0.))
+            .then_with(|| b.usage.total_tokens().cmp(&a.usage.total_tokens()))
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    totals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use agent_settings::ModelPricing;
+    use std::path::PathBuf;
+
+    fn record(session: &str, project: &str, provider: &str, hour: u32) -> DbUsageRecord {
+        DbUsageRecord {
+            session_id: acp::SessionId::new(session),
+            thread_title: Some(format!("Thread {session}").into()),
+            folder_paths: PathList::new(&[PathBuf::from(project)]),
+            provider: provider.into(),
+            model: "model".into(),
+            usage: TokenUsage {
+                input_tokens: 1_000_000,
+                output_tokens: 100_000,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: 0,
+            },
+            recorded_at: Utc.with_ymd_and_hms(2025, 3, 14, hour, 0, 0).unwrap(),
+        }
+    }
+
+    fn settings() -> AgentUsageSettings {
+        AgentUsageSettings {
+            pricing: [(
+                "anthropic/model".to_string(),
+                ModelPricing {
+                    input: 3.,
+                    output: 15.,
+                    cache_write: 3.,
+                    cache_read: 3.,
+                },
+            )]
+            .into_iter()
+            .collect(),
+            budgets: Vec::new(),
+        }
+    }
+
+    fn budget(scope: UsageBudgetScope) -> UsageBudget {
+        UsageBudget {
+            scope,
+            provider: None,
+            period: UsageBudgetPeriod::AllTime,
+            unit: UsageBudgetUnit::Dollars,
+            soft_limit: Some(4.),
+            hard_limit: Some(9.),
+        }
+    }
+
+    #[test]
+    fn test_period_start() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 14, 15, 9, 26).unwrap();
+        assert_eq!(
+            period_start(UsageBudgetPeriod::Day, now),
+            Some(Utc.with_ymd_and_hms(2025, 3, 14, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            period_start(UsageBudgetPeriod::Month, now),
+            Some(Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(period_start(UsageBudgetPeriod::AllTime, now), None);
+    }
+
+    #[test]
+    fn test_usage_filter() {
+        let context = BudgetContext {
+            session_id: acp::SessionId::new("a"),
+            folder_paths: PathList::new(&[PathBuf::from("/zed")]),
+            provider: "anthropic".into(),
+            model: "model".into(),
+        };
+        let now = Local.with_ymd_and_hms(2025, 4, 1, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            usage_filter(&budget(UsageBudgetScope::Thread), &context, now),
+            Some(DbUsageFilter {
+                session_id: Some(context.session_id.clone()),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            usage_filter(
+                &UsageBudget {
+                    period: UsageBudgetPeriod::Day,
+                    ..budget(UsageBudgetScope::Project)
+                },
+                &context,
+                now
+            ),
+            Some(DbUsageFilter {
+                since: period_start(UsageBudgetPeriod::Day, now),
+                folder_paths: Some(context.folder_paths.clone()),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            usage_filter(
+                &UsageBudget {
+                    provider: Some("anthropic".into()),
+                    ..budget(UsageBudgetScope::All)
+                },
+                &context,
+                now
+            ),
+            Some(DbUsageFilter {
+                provider: Some("anthropic".into()),
+                ..Default::default()
+            })
+        );
+
+        // Budgets for other providers don't apply to the request.
+        assert_eq!(
+            usage_filter(
+                &UsageBudget {
+                    provider: Some("openai".into()),
+                    ..budget(UsageBudgetScope::All)
+                },
+                &context,
+                now
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_evaluate_budget() {
+        let total = |provider: &str, requests: u64| DbUsageTotal {
+            provider: provider.to_string().into(),
+            model: "model".into(),
+            usage: TokenUsage {
+                input_tokens: 1_000_000 * requests,
+                output_tokens: 100_000 * requests,
+                cache_creation_input_tokens: 0,
+                cache_read_input_tokens: 0,
+            },
+        };
+        let settings = settings();
+        let context = |provider: &str| BudgetContext {
+            session_id: acp::SessionId::new("a"),
+            folder_paths: PathList::new(&[PathBuf::from("/zed")]),
+            provider: provider.to_string().into(),
+            model: "model".into(),
+        };
+        let anthropic = context("anthropic");
+
+        // Each priced request costs $4.50.
+        assert_eq!(
+            evaluate_budget(
+                &budget(UsageBudgetScope::Thread),
+                &[total("anthropic", 2)],
+                &anthropic,
+                &settings
+            ),
+            Some(BudgetOutcome::HardLimitReached {
+                spent: 9.,
+                limit: 9.
+            })
+        );
+        // Unpriced usage fails hard limits closed, and warns for soft limits.
+        assert_eq!(
+            evaluate_budget(
+                &UsageBudget {
+                    hard_limit: Some(20.),
+                    ..budget(UsageBudgetScope::Project)
+                },
+                &[total("anthropic", 3), total("openai", 1)],
+                &anthropic,
+                &settings
+            ),
+            Some(BudgetOutcome::UnpricedModels {
+                models: vec!["openai/model".into()],
+                limit: 20.,
+                hard: true,
+            })
+        );
+        assert_eq!(
+            evaluate_budget(
+                &UsageBudget {
+                    hard_limit: None,
+                    ..budget(UsageBudgetScope::Project)
+                },
+                &[total("anthropic", 3), total("openai", 1)],
+                &anthropic,
+                &settings
+            ),
+            Some(BudgetOutcome::SoftLimitReached {
+                spent: 13.5,
+                limit: 4.
+            })
+        );
+        assert_eq!(
+            evaluate_budget(
+                &UsageBudget {
+                    hard_limit: None,
+                    ..budget(UsageBudgetScope::Project)
+                },
+                &[],
+                &context("openai"),
+                &settings
+            ),
+            Some(BudgetOutcome::UnpricedModels {
+                models: vec!["openai/model".into()],
+                limit: 4.,
+                hard: false,
+            })
+        );
+        // Hard limits that priced usage already reached are reported as such.
+        assert_eq!(
+            evaluate_budget(
+                &budget(UsageBudgetScope::Project),
+                &[total("anthropic", 2), total("openai", 1)],
+                &anthropic,
+                &settings
+            ),
+            Some(BudgetOutcome::HardLimitReached {
+                spent: 9.,
+                limit: 9.
+            })
+        );
+        assert_eq!(
+            evaluate_budget(
+                &UsageBudget {
+                    unit: UsageBudgetUnit::Tokens,
+                    soft_limit: Some(5_000_000.),
+                    hard_limit: None,
+                    ..budget(UsageBudgetScope::All)
+                },
+                &[total("anthropic", 4), total("openai", 1)],
+                &context("openai"),
+                &settings
+            ),
+            Some(BudgetOutcome::SoftLimitReached {
+                spent: 5_500_000.,
+                limit: 5_000_000.
+            })
+        );
+        assert_eq!(
+            evaluate_budget(&budget(UsageBudgetScope::All), &[], &anthropic, &settings),
+            None
+        );
+    }
+
+    #[test]
+    fn test_describe_spending() {
+        let budget = UsageBudget {
+            provider: Some("anthropic".into()),
+            period: UsageBudgetPeriod::Day,
+            ..budget(UsageBudgetScope::Project)
+        };
+        assert_eq!(
+            describe_spending(&budget, 4.5, 4., "soft"),
+            "This project has used $4.50 with anthropic today, reaching its soft limit of $4.00."
+        );
+    }
+
+    #[test]
+    fn test_describe_unpriced_models() {
+        assert_eq!(
+            describe_unpriced_models(&["openai/model".into()], 9., "hard"),
+            "The cost of openai/model isn't known, so the hard limit of $9.00 can't be enforced."
+        );
+    }
+
+    #[test]
+    fn test_usage_summary() {
+        let mut deleted = record("c", "/other", "openai", 13);
+        deleted.thread_title = None;
+        let records = [
+            record("a", "/zed", "anthropic", 9),
+            record("a", "/zed", "anthropic", 10),
+            record("b", "/zed", "anthropic", 11),
+            deleted,
+        ];
+        let summary = UsageSummary::new(&records, &settings());
+
+        assert_eq!(summary.total.requests, 4);
+        assert_eq!(summary.total.cost, Some(13.5));
+        assert_eq!(summary.total.unpriced_requests, 1);
+        assert_eq!(
+            summary.unpriced_models,
+            vec![SharedString::from("openai/model")]
+        );
+        assert_eq!(summary.total.usage.input_tokens, 4_000_000);
+
+        let rows = |totals: &[UsageTotals]| {
+            totals
+                .iter()
+                .map(|totals| (totals.label.to_string(), totals.requests, totals.cost))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            rows(&summary.by_project),
+            [("zed".into(), 3, Some(13.5)), ("other".into(), 1, None)]
+        );
+        assert_eq!(
+            rows(&summary.by_thread),
+            [
+                ("Thread a".into(), 2, Some(9.)),
+                ("Thread b".into(), 1, Some(4.5)),
+                ("Deleted thread".into(), 1, None),
+            ]
+        );
+        assert_eq!(
+            rows(&summary.by_model),
+            [
+                ("anthropic/model".into(), 3, Some(13.5)),
+                ("openai/model".into(), 1, None),
+            ]
+        );
+        assert_eq!(
+            summary
+                .by_day
+                .iter()
+                .map(|totals| totals.requests)
+                .sum::<usize>(),
+            4
+        );
+    }
+}
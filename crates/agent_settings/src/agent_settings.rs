@@ -2,12 +2,13 @@ mod agent_profile;
 
 use std::path::{Component, Path};
 use std::sync::{Arc, LazyLock};
+use std::time::Duration;
 
 use agent_client_protocol::ModelId;
 use collections::{HashSet, IndexMap};
 use fs::Fs;
 use gpui::{App, Pixels, px};
-use language_model::LanguageModel;
+use language_model::{LanguageModel, TokenUsage};
 use project::DisableAiSettings;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -15,7 +16,7 @@ use settings::{
     DockPosition, DockSide, LanguageModelParameters, LanguageModelSelection, NewThreadLocation,
     NotifyWhenAgentWaiting, PlaySoundWhenAgentDone, RegisterSetting, Settings, SettingsContent,
     SettingsStore, SidebarDockPosition, SidebarSide, ThinkingBlockDisplay, ToolPermissionMode,
-    update_settings_file,
+    UsageBudgetPeriod, UsageBudgetScope, UsageBudgetUnit, update_settings_file,
 };
 
 pub use crate::agent_profile::*;
@@ -23,6 +24,7 @@ pub use crate::agent_profile::*;
 pub const SUMMARIZE_THREAD_PROMPT: &str = include_str!("prompts/summarize_thread_prompt.txt");
 pub const SUMMARIZE_THREAD_DETAILED_PROMPT: &str =
     include_str!("prompts/summarize_thread_detailed_prompt.txt");
+pub const COMPACT_THREAD_PROMPT: &str = include_str!("prompts/compact_thread_prompt.txt");
 
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct PanelLayout {
@@ -176,6 +178,10 @@ pub struct AgentSettings {
     pub use_modifier_to_send: bool,
     pub message_editor_min_lines: usize,
     pub show_turn_stats: bool,
+    pub auto_compact_threads: bool,
+    pub auto_compact_threshold: f32,
+    pub hooks: AgentHooks,
+    pub usage: AgentUsageSettings,
     pub tool_permissions: ToolPermissions,
     pub new_thread_location: NewThreadLocation,
 }
@@ -299,6 +305,87 @@ impl Default for AgentProfileId {
     }
 }
 
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AgentHooks {
+    pub user_prompt_submit: Vec<AgentHook>,
+    pub pre_tool_call: Vec<AgentHook>,
+    pub post_tool_call: Vec<AgentHook>,
+    pub post_edit: Vec<AgentHook>,
+    pub stop: Vec<AgentHook>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AgentHook {
+    pub command: String,
+    /// The tools this hook applies to, or `None` when it applies to all of them.
+    pub tools: Option<Vec<String>>,
+    pub timeout: Duration,
+}
+
+impl AgentHook {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+    pub fn applies_to(&self, tool_name: &str) -> bool {
+        self.tools
+            .as_ref()
+            .is_none_or(|tools| tools.iter().any(|tool| tool == tool_name))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AgentUsageSettings {
+    /// Prices keyed by model id or by `provider/model`.
+    pub pricing: collections::HashMap<String, ModelPricing>,
+    pub budgets: Vec<UsageBudget>,
+}
+
+impl AgentUsageSettings {
+    /// Returns the price of a model, preferring a `provider/model` entry over a
+    /// plain model id.
+    pub fn pricing_for(&self, provider: &str, model: &str) -> Option<&ModelPricing> {
+        self.pricing
+            .get(&format!("{provider}/{model}"))
+            .or_else(|| self.pricing.get(model))
+    }
+
+    /// Estimates the cost of the usage in dollars, or `None` when the model
+    /// has no price configured.
+    pub fn cost(&self, provider: &str, model: &str, usage: &TokenUsage) -> Option<f64> {
+        self.pricing_for(provider, model)
+            .map(|pricing| pricing.cost(usage))
+    }
+}
+
+/// Prices in dollars per million tokens.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+impl ModelPricing {
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_creation_input_tokens as f64 * self.cache_write
+            + usage.cache_read_input_tokens as f64 * self.cache_read)
+            / 1_000_000.
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct UsageBudget {
+    pub scope: UsageBudgetScope,
+    /// The provider whose usage counts, or `None` when all of them count.
+    pub provider: Option<String>,
+    pub period: UsageBudgetPeriod,
+    pub unit: UsageBudgetUnit,
+    pub soft_limit: Option<f64>,
+    pub hard_limit: Option<f64>,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct ToolPermissions {
     /// Global default permission when no tool-specific rules or patterns match.
@@ -629,12 +716,84 @@ impl Settings for AgentSettings {
             use_modifier_to_send: agent.use_modifier_to_send.unwrap(),
             message_editor_min_lines: agent.message_editor_min_lines.unwrap(),
             show_turn_stats: agent.show_turn_stats.unwrap(),
+            auto_compact_threads: agent.auto_compact_threads.unwrap(),
+            auto_compact_threshold: agent.auto_compact_threshold.unwrap().clamp(0.1, 1.0),
+            hooks: resolve_hooks(agent.hooks),
+            usage: resolve_usage(agent.usage),
             tool_permissions: compile_tool_permissions(agent.tool_permissions),
             new_thread_location: agent.new_thread_location.unwrap_or_default(),
         }
     }
 }
 
+fn resolve_hooks(content: Option<settings::AgentHooksContent>) -> AgentHooks {
+    let Some(content) = content else {
+        return AgentHooks::default();
+    };
+
+    let resolve = |hooks: Option<Vec<settings::AgentHookContent>>| {
+        hooks
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|hook| !hook.command.trim().is_empty())
+            .map(|hook| AgentHook {
+                command: hook.command,
+                tools: hook.tools,
+                timeout: hook
+                    .timeout_ms
+                    .map_or(AgentHook::DEFAULT_TIMEOUT, Duration::from_millis),
+            })
+            .collect()
+    };
+
+    AgentHooks {
+        user_prompt_submit: resolve(content.user_prompt_submit),
+        pre_tool_call: resolve(content.pre_tool_call),
+        post_tool_call: resolve(content.post_tool_call),
+        post_edit: resolve(content.post_edit),
+        stop: resolve(content.stop),
+    }
+}
+
+fn resolve_usage(content: Option<settings::AgentUsageContent>) -> AgentUsageSettings {
+    let Some(content) = content else {
+        return AgentUsageSettings::default();
+    };
+
+    let pricing = content
+        .pricing
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(model, pricing)| {
+            let input = pricing.input.unwrap_or(0.);
+            let pricing = ModelPricing {
+                input,
+                output: pricing.output.unwrap_or(0.),
+                cache_write: pricing.cache_write.unwrap_or(input),
+                cache_read: pricing.cache_read.unwrap_or(input),
+            };
+            (model, pricing)
+        })
+        .collect();
+
+    let budgets = content
+        .budgets
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|budget| budget.soft_limit.is_some() || budget.hard_limit.is_some())
+        .map(|budget| UsageBudget {
+            scope: budget.scope.unwrap_or_default(),
+            provider: budget.provider.filter(|provider| !provider.is_empty()),
+            period: budget.period.unwrap_or_default(),
+            unit: budget.unit.unwrap_or_default(),
+            soft_limit: budget.soft_limit,
+            hard_limit: budget.hard_limit,
+        })
+        .collect();
+
+    AgentUsageSettings { pricing, budgets }
+}
+
 fn compile_tool_permissions(content: Option<settings::ToolPermissionsContent>) -> ToolPermissions {
     let Some(content) = content else {
         return ToolPermissions::default();
@@ -793,6 +952,56 @@ mod tests {
         assert!(terminal_rules.always_allow[0].is_match("git status"));
     }
 
+    #[test]
+    fn test_usage_settings_parsing() {
+        let json = json!({
+            "pricing": {
+                "claude-sonnet-4": { "input": 3, "output": 15, "cache_read": 0.3 },
+                "openai/gpt-5": { "input": 1.25, "output": 10 },
+            },
+            "budgets": [
+                { "scope": "project", "period": "day", "hard_limit": 20 },
+                { "provider": "anthropic" },
+            ],
+        });
+
+        let content: settings::AgentUsageContent = serde_json::from_value(json).unwrap();
+        let usage = resolve_usage(Some(content));
+
+        let sonnet = usage.pricing_for("anthropic", "claude-sonnet-4").unwrap();
+        assert_eq!(sonnet.cache_write, 3.);
+        assert_eq!(sonnet.cache_read, 0.3);
+        assert!(usage.pricing_for("openai", "gpt-5").is_some());
+        assert!(usage.pricing_for("openrouter", "gpt-5").is_none());
+
+        let cost = usage
+            .cost(
+                "anthropic",
+                "claude-sonnet-4",
+                &TokenUsage {
+                    input_tokens: 1_000_000,
+                    output_tokens: 100_000,
+                    cache_creation_input_tokens: 0,
+                    cache_read_input_tokens: 1_000_000,
+                },
+            )
+            .unwrap();
+        assert!((cost - 4.8).abs() < 1e-9);
+
+        // Budgets without limits are dropped.
+        assert_eq!(
+            usage.budgets,
+            [UsageBudget {
+                scope: UsageBudgetScope::Project,
+                provider: None,
+                period: UsageBudgetPeriod::Day,
+                unit: UsageBudgetUnit::Dollars,
+                soft_limit: None,
+                hard_limit: Some(20.),
+            }]
+        );
+    }
+
     #[test]
     fn test_tool_rules_default() {
         let json = json!({
@@ -3,6 +3,7 @@ pub mod configure_context_server_modal;
 mod configure_context_server_tools_modal;
 mod manage_profiles_modal;
 mod tool_picker;
+mod web_search_api_key;
 
 use std::{ops::Range, rc::Rc, sync::Arc};
 
@@ -51,6 +52,8 @@ use crate::{
     agent_connection_store::{AgentConnectionStatus, AgentConnectionStore},
 };
 
+use web_search_api_key::WebSearchApiKeyView;
+
 pub struct AgentConfiguration {
     fs: Arc<dyn Fs>,
     language_registry: Arc<LanguageRegistry>,
@@ -62,6 +65,7 @@ pub struct AgentConfiguration {
     context_server_store: Entity<ContextServerStore>,
     expanded_provider_configurations: HashMap<LanguageModelProviderId, bool>,
     context_server_registry: Entity<ContextServerRegistry>,
+    web_search_api_key_view: Entity<WebSearchApiKeyView>,
     _subscriptions: Vec<Subscription>,
     scroll_handle: ScrollHandle,
 }
@@ -79,6 +83,7 @@ impl AgentConfiguration {
         cx: &mut Context<Self>,
     ) -> Self {
         let focus_handle = cx.focus_handle();
+        let web_search_api_key_view = cx.new(|cx| WebSearchApiKeyView::new(window, cx));
 
         let subscriptions = vec![
             cx.subscribe_in(
@@ -100,6 +105,7 @@ impl AgentConfiguration {
             cx.subscribe(&agent_server_store, |_, _, _, cx| cx.notify()),
             cx.observe(&agent_connection_store, |_, _, cx| cx.notify()),
             cx.subscribe(&context_server_store, |_, _, _, cx| cx.notify()),
+            cx.observe(&web_search_api_key_view, |_, _, cx| cx.notify()),
         ];
 
         let mut this = Self {
@@ -113,6 +119,7 @@ impl AgentConfiguration {
             context_server_store,
             expanded_provider_configurations: HashMap::default(),
             context_server_registry,
+            web_search_api_key_view,
             _subscriptions: subscriptions,
             scroll_handle: ScrollHandle::new(),
         };
@@ -490,6 +497,31 @@ impl AgentConfiguration {
             )
     }
 
+    fn render_web_search_section(&mut self, cx: &mut Context<Self>) -> AnyElement {
+        if !self.web_search_api_key_view.read(cx).is_needed() {
+            return div().into_any_element();
+        }
+
+        v_flex()
+            .min_w_0()
+            .border_b_1()
+            .border_color(cx.theme().colors().border)
+            .child(self.render_section_title(
+                "Web Search",
+                "The API key of the self-hosted search provider set in the `web_search` setting.",
+                div().into_any_element(),
+            ))
+            .child(
+                v_flex()
+                    .pl_4()
+                    .pb_4()
+                    .pr_5()
+                    .w_full()
+                    .child(self.web_search_api_key_view.clone()),
+            )
+            .into_any_element()
+    }
+
     fn render_zed_plan_info(&self, plan: Option<Plan>, cx: &mut Context<Self>) -> impl IntoElement {
         if let Some(plan) = plan {
             let free_chip_bg = cx
@@ -1279,6 +1311,7 @@ impl Render for AgentConfiguration {
                             .overflow_y_scroll()
                             .child(self.render_agent_servers_section(cx))
                             .child(self.render_context_servers_section(cx))
+                            .child(self.render_web_search_section(cx))
                             .child(self.render_provider_configuration_section(cx)),
                     )
                     .vertical_scrollbar_for(&self.scroll_handle, window, cx),
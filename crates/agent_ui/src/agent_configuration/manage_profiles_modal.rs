@@ -360,7 +360,7 @@ impl ManageProfilesModal {
             .copied()
             .filter(|name| {
                 let supported_by_provider = provider.as_ref().map_or(true, |provider| {
-                    agent::tool_supports_provider(name, provider)
+                    agent::tool_supports_provider(name, provider, cx)
                 });
                 supported_by_provider
             })
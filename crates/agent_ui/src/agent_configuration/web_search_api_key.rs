@@ -0,0 +1,165 @@
+use std::sync::Arc;
+
+use agent::{WebSearchApiKeyStatus, WebSearchProvider, WebSearchRegistry};
+use gpui::{Entity, Subscription, Task};
+use ui::{ConfiguredApiCard, prelude::*};
+use ui_input::InputField;
+
+/// Saves the API key of the self-hosted web search provider configured in the
+/// `web_search` settings to the system keychain.
+pub(crate) struct WebSearchApiKeyView {
+    api_key_editor: Entity<InputField>,
+    provider: Option<Arc<dyn WebSearchProvider>>,
+    /// `None` while the API key is loading.
+    status: Option<WebSearchApiKeyStatus>,
+    load_status_task: Option<Task<()>>,
+    _registry_subscription: Subscription,
+}
+
+impl WebSearchApiKeyView {
+    pub fn new(window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let api_key_editor =
+            cx.new(|cx| InputField::new(window, cx, "Paste your API key and press enter"));
+        let registry_subscription = cx.observe(&WebSearchRegistry::global(cx), |this, _, cx| {
+            this.refresh(cx);
+        });
+
+        let mut this = Self {
+            api_key_editor,
+            provider: None,
+            status: None,
+            load_status_task: None,
+            _registry_subscription: registry_subscription,
+        };
+        this.refresh(cx);
+        this
+    }
+
+    /// Whether the active web search provider can authenticate with an API key.
+    pub fn is_needed(&self) -> bool {
+        self.provider.is_some()
+    }
+
+    fn refresh(&mut self, cx: &mut Context<Self>) {
+        let provider = WebSearchRegistry::read_global(cx)
+            .active_provider()
+            .filter(|provider| provider.api_key_env_var_name().is_some());
+        let unchanged = match (&self.provider, &provider) {
+            (Some(old), Some(new)) => Arc::ptr_eq(old, new),
+            (None, None) => true,
+            _ => false,
+        };
+        if unchanged {
+            return;
+        }
+
+        self.provider = provider.clone();
+        self.status = None;
+        self.load_status_task = provider.map(|provider| {
+            let status = provider.api_key_status(cx);
+            cx.spawn(async move |this, cx| {
+                let status = status.await;
+                this.update(cx, |this, cx| {
+                    this.status = Some(status);
+                    this.load_status_task = None;
+                    cx.notify();
+                })
+                .ok();
+            })
+        });
+        cx.notify();
+    }
+
+    fn save_api_key(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let api_key = self.api_key_editor.read(cx).text(cx).trim().to_string();
+        if api_key.is_empty() {
+            return;
+        }
+        self.store_api_key(Some(api_key), window, cx);
+    }
+
+    fn reset_api_key(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.store_api_key(None, window, cx);
+    }
+
+    fn store_api_key(
+        &mut self,
+        api_key: Option<String>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(provider) = self.provider.clone() else {
+            return;
+        };
+        self.api_key_editor
+            .update(cx, |editor, cx| editor.set_text("", window, cx));
+
+        let status = if api_key.is_some() {
+            WebSearchApiKeyStatus::SystemKeychain
+        } else {
+            WebSearchApiKeyStatus::NotSet
+        };
+        let store = provider.store_api_key(api_key, cx);
+        cx.spawn(async move |this, cx| {
+            store.await?;
+            this.update(cx, |this, cx| {
+                if this
+                    .provider
+                    .as_ref()
+                    .is_some_and(|current| Arc::ptr_eq(current, &provider))
+                {
+                    this.status = Some(status);
+                    cx.notify();
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+}
+
+impl Render for WebSearchApiKeyView {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let Some(provider) = &self.provider else {
+            return div().into_any_element();
+        };
+        let env_var_name = provider.api_key_env_var_name().unwrap_or_default();
+
+        match self.status {
+            None => Label::new("Loading API key…")
+                .color(Color::Muted)
+                .into_any_element(),
+            Some(WebSearchApiKeyStatus::NotSet) => v_flex()
+                .gap_1()
+                .on_action(cx.listener(Self::save_api_key))
+                .child(Label::new(
+                    "If your search instance requires an API key, paste it below and press enter to save it in the system keychain.",
+                ))
+                .child(self.api_key_editor.clone())
+                .child(
+                    Label::new(format!(
+                        "You can also set the {env_var_name} environment variable and restart Zed."
+                    ))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+                )
+                .into_any_element(),
+            Some(status) => {
+                let env_var_set = status == WebSearchApiKeyStatus::EnvVar;
+                let label = if env_var_set {
+                    format!("API key set in {env_var_name} environment variable")
+                } else {
+                    "API key configured".to_string()
+                };
+                ConfiguredApiCard::new(label)
+                    .disabled(env_var_set)
+                    .on_click(cx.listener(|this, _, window, cx| this.reset_api_key(window, cx)))
+                    .when(env_var_set, |this| {
+                        this.tooltip_label(format!(
+                            "To reset your API key, unset the {env_var_name} environment variable."
+                        ))
+                    })
+                    .into_any_element()
+            }
+        }
+    }
+}
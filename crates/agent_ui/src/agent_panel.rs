@@ -9,7 +9,9 @@ use std::{
 };
 
 use acp_thread::{AcpThread, MentionUri, ThreadStatus};
-use agent::{ContextServerRegistry, SharedThread, ThreadStore};
+use agent::{
+    ContextServerRegistry, SharedThread, ThreadExport, ThreadStore, UsageLedger, UsageLedgerEvent,
+};
 use agent_client_protocol as acp;
 use agent_servers::AgentServer;
 use collections::HashSet;
@@ -28,9 +30,10 @@ use zed_actions::agent::{
 use crate::thread_metadata_store::ThreadMetadataStore;
 use crate::{
     AddContextServer, AgentDiffPane, ConversationView, CopyThreadToClipboard, CycleStartThreadIn,
-    Follow, InlineAssistant, LoadThreadFromClipboard, NewThread, OpenActiveThreadAsMarkdown,
-    OpenAgentDiff, OpenHistory, ResetTrialEndUpsell, ResetTrialUpsell, StartThreadIn,
-    ToggleNavigationMenu, ToggleNewThreadMenu, ToggleOptionsMenu,
+    ExportThread, Follow, ImportThread, InlineAssistant, LoadThreadFromClipboard, NewThread,
+    OpenActiveThreadAsMarkdown, OpenAgentDiff, OpenHistory, ResetTrialEndUpsell, ResetTrialUpsell,
+    StartThreadIn, ThreadExportFormat, ToggleNavigationMenu, ToggleNewThreadMenu,
+    ToggleOptionsMenu,
     agent_configuration::{AgentConfiguration, AssistantConfigurationEvent},
     conversation_view::{AcpThreadViewEvent, ThreadView},
     ui::EndTrialUpsell,
@@ -312,6 +315,26 @@ pub fn init(cx: &mut App) {
                         });
                     }
                 })
+                .register_action(|workspace, action: &ExportThread, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| {
+                            panel.export_thread(
+                                action.format,
+                                action.include_git_state,
+                                window,
+                                cx,
+                            );
+                        });
+                    }
+                })
+                .register_action(|workspace, _: &ImportThread, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        workspace.focus_panel::<AgentPanel>(window, cx);
+                        panel.update(cx, |panel, cx| {
+                            panel.import_thread(window, cx);
+                        });
+                    }
+                })
                 .register_action(|workspace, action: &ReviewBranchDiff, window, cx| {
                     let Some(panel) = workspace.panel::<AgentPanel>(cx) else {
                         return;
@@ -668,6 +691,7 @@ pub struct AgentPanel {
     agent_navigation_menu: Option<Entity<ContextMenu>>,
     _extension_subscription: Option<Subscription>,
     _project_subscription: Subscription,
+    _usage_ledger_subscription: Option<Subscription>,
     zoomed: bool,
     pending_serialization: Option<Task<Result<()>>>,
     onboarding: Entity<AgentPanelOnboarding>,
@@ -960,6 +984,17 @@ impl AgentPanel {
                 }
                 _ => {}
             });
+        let _usage_ledger_subscription = UsageLedger::try_global(cx).map(|ledger| {
+            cx.subscribe(&ledger, |this, _ledger, event, cx| {
+                if let UsageLedgerEvent::BudgetWarning {
+                    folder_paths,
+                    message,
+                } = event
+                {
+                    this.show_budget_warning(folder_paths, message.clone(), cx);
+                }
+            })
+        });
 
         let mut panel = Self {
             workspace_id,
@@ -984,6 +1019,7 @@ impl AgentPanel {
             agent_navigation_menu: None,
             _extension_subscription: extension_subscription,
             _project_subscription,
+            _usage_ledger_subscription,
             zoomed: false,
             pending_serialization: None,
             onboarding,
@@ -1511,6 +1547,41 @@ impl AgentPanel {
         });
     }
 
+    fn show_budget_warning(
+        &self,
+        folder_paths: &PathList,
+        message: SharedString,
+        cx: &mut Context<Self>,
+    ) {
+        let project_paths = PathList::new(
+            &self
+                .project
+                .read(cx)
+                .visible_worktrees(cx)
+                .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+                .collect::<Vec<_>>(),
+        );
+        if &project_paths != folder_paths {
+            return;
+        }
+
+        self.workspace
+            .update(cx, |workspace, cx| {
+                struct BudgetWarningToast;
+                workspace.show_toast(
+                    workspace::Toast::new(
+                        workspace::notifications::NotificationId::unique::<BudgetWarningToast>(),
+                        message.to_string(),
+                    )
+                    .on_click("View Usage", |window, cx| {
+                        window.dispatch_action(Box::new(crate::OpenUsageDashboard), cx)
+                    }),
+                    cx,
+                );
+            })
+            .log_err();
+    }
+
     fn load_thread_from_clipboard(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let Some(clipboard) = cx.read_from_clipboard() else {
             Self::show_deferred_toast(&self.workspace, "No clipboard content available", cx);
@@ -1585,6 +1656,142 @@ impl AgentPanel {
         .detach_and_log_err(cx);
     }
 
+    fn export_thread(
+        &mut self,
+        format: ThreadExportFormat,
+        include_git_state: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(thread) = self.active_native_agent_thread(cx) else {
+            Self::show_deferred_toast(&self.workspace, "No active native thread to export", cx);
+            return;
+        };
+
+        let load_task = thread.read(cx).to_db(cx);
+        let directory = self
+            .project
+            .read(cx)
+            .visible_worktrees(cx)
+            .find_map(|worktree| Some(worktree.read(cx).as_local()?.abs_path().to_path_buf()))
+            .unwrap_or_else(|| paths::home_dir().clone());
+        let fs = self.fs.clone();
+        let workspace = self.workspace.clone();
+
+        cx.spawn_in(window, async move |_this, cx| {
+            let db_thread = load_task.await;
+            let export = ThreadExport::from_db_thread(&db_thread, include_git_state);
+            let contents = match format {
+                ThreadExportFormat::Json => export.to_json()?,
+                ThreadExportFormat::Markdown => export.to_markdown(),
+                ThreadExportFormat::Html => export.to_html(),
+            };
+
+            let file_name = export
+                .thread
+                .title
+                .chars()
+                .map(|c| {
+                    if c.is_alphanumeric() || c == '-' {
+                        c
+                    } else {
+                        '_'
+                    }
+                })
+                .collect::<String>();
+            let file_name = format!(
+                "{}.{}",
+                file_name.trim_matches('_').to_lowercase(),
+                format.extension()
+            );
+            let path = cx
+                .update(|_window, cx| cx.prompt_for_new_path(&directory, Some(&file_name)))?
+                .await??;
+            let Some(path) = path else {
+                return anyhow::Ok(());
+            };
+
+            let result = fs
+                .atomic_write(path.clone(), contents)
+                .await
+                .map_err(|error| {
+                    anyhow!(
+                        "Failed to export the thread to {}: {error:#}",
+                        path.display()
+                    )
+                });
+            cx.update(|_window, cx| {
+                let Some(workspace) = workspace.upgrade() else {
+                    return;
+                };
+                workspace.update(cx, |workspace, cx| match &result {
+                    Ok(()) => {
+                        struct ThreadExportedToast;
+                        workspace.show_toast(
+                            workspace::Toast::new(
+                                workspace::notifications::NotificationId::unique::<
+                                    ThreadExportedToast,
+                                >(),
+                                format!("Thread exported to {}", path.display()),
+                            )
+                            .autohide(),
+                            cx,
+                        );
+                    }
+                    Err(error) => workspace.show_error(error, cx),
+                });
+            })?;
+
+            result
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn import_thread(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let paths = cx.prompt_for_paths(gpui::PathPromptOptions {
+            files: true,
+            directories: false,
+            multiple: false,
+            prompt: Some("Import Thread".into()),
+        });
+        let fs = self.fs.clone();
+        let thread_store = self.thread_store.clone();
+        let workspace = self.workspace.clone();
+
+        cx.spawn_in(window, async move |this, cx| {
+            let Some(path) = paths.await??.and_then(|paths| paths.into_iter().next()) else {
+                return anyhow::Ok(());
+            };
+
+            let db_thread = match fs
+                .load(&path)
+                .await
+                .and_then(|json| ThreadExport::from_json(&json))
+            {
+                Ok(export) => export.into_db_thread(),
+                Err(error) => {
+                    let error = anyhow!("Failed to import {}: {error:#}", path.display());
+                    workspace.update(cx, |workspace, cx| workspace.show_error(&error, cx))?;
+                    return Err(error);
+                }
+            };
+
+            let title = db_thread.title.clone();
+            let session_id = thread_store
+                .update(&mut cx.clone(), |store, cx| {
+                    store.import_thread(db_thread, Default::default(), cx)
+                })
+                .await?;
+
+            this.update_in(cx, |this, window, cx| {
+                this.open_thread(session_id, None, Some(title), window, cx);
+            })?;
+
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
     fn handle_agent_configuration_event(
         &mut self,
         _entity: &Entity<AgentConfiguration>,
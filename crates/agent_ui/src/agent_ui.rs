@@ -10,8 +10,10 @@ mod completion_provider;
 mod config_options;
 mod context;
 mod context_server_configuration;
+mod context_server_delegate;
 pub(crate) mod conversation_view;
 mod diagnostics;
+mod elicitation_modal;
 mod entry_view_state;
 mod external_source_prompt;
 mod favorite_models;
@@ -34,6 +36,7 @@ mod thread_import;
 pub mod thread_metadata_store;
 pub mod threads_archive_view;
 mod ui;
+mod usage_dashboard;
 
 use std::rc::Rc;
 use std::sync::Arc;
@@ -64,6 +67,7 @@ use crate::agent_configuration::{ConfigureContextServerModal, ManageProfilesModa
 pub use crate::agent_panel::{AgentPanel, AgentPanelEvent, WorktreeCreationStatus};
 use crate::agent_registry_ui::AgentRegistryPage;
 pub use crate::inline_assistant::InlineAssistant;
+use crate::usage_dashboard::UsageDashboard;
 pub use agent_diff::{AgentDiffPane, AgentDiffToolbar};
 pub(crate) use conversation_view::ConversationView;
 pub use external_source_prompt::ExternalSourcePrompt;
@@ -127,6 +131,10 @@ actions!(
         CopyThreadToClipboard,
         /// Loads a thread from the clipboard JSON for debugging.
         LoadThreadFromClipboard,
+        /// Imports a thread from a file exported with `agent: export thread`.
+        ImportThread,
+        /// Opens the dashboard of the agent's token usage and estimated costs.
+        OpenUsageDashboard,
         /// Keeps the current suggestion or change.
         Keep,
         /// Rejects the current suggestion or change.
@@ -173,6 +181,8 @@ actions!(
         ToggleThinkingEffortMenu,
         /// Toggles fast mode for models that support it.
         ToggleFastMode,
+        /// Summarizes the older messages of the current thread to free up context.
+        CompactThread,
         /// Scroll the output by one page up.
         ScrollOutputPageUp,
         /// Scroll the output by one page down.
@@ -229,6 +239,42 @@ pub struct ToggleCommandPattern {
     pub pattern_index: usize,
 }
 
+/// Exports the active thread to a file.
+#[derive(Default, Clone, PartialEq, Deserialize, JsonSchema, Action)]
+#[action(namespace = agent)]
+#[serde(deny_unknown_fields)]
+pub struct ExportThread {
+    /// The format to export the thread in.
+    #[serde(default)]
+    pub format: ThreadExportFormat,
+    /// Whether to include the uncommitted changes and git remote URLs of the
+    /// project from when the thread started.
+    #[serde(default)]
+    pub include_git_state: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadExportFormat {
+    /// A JSON file that can be imported again with `agent: import thread`.
+    #[default]
+    Json,
+    /// A readable Markdown transcript.
+    Markdown,
+    /// A readable HTML page.
+    Html,
+}
+
+impl ThreadExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
 /// Creates a new conversation thread, optionally based on an existing thread.
 #[derive(Default, Clone, PartialEq, Deserialize, JsonSchema, Action)]
 #[action(namespace = agent)]
@@ -417,6 +463,7 @@ pub fn init(
     cx: &mut App,
 ) {
     agent::ThreadStore::init_global(cx);
+    agent::UsageLedger::init_global(cx);
     rules_library::init(cx);
     if !is_eval {
         // Initializing the language model from the user settings messes with the eval, so we only initialize them when
@@ -425,6 +472,7 @@ pub fn init(
     }
     agent_panel::init(cx);
     context_server_configuration::init(language_registry.clone(), fs.clone(), cx);
+    context_server_delegate::init(cx);
     thread_metadata_store::init(cx);
 
     inline_assistant::init(fs.clone(), prompt_builder.clone(), cx);
@@ -465,6 +513,28 @@ pub fn init(
         );
     })
     .detach();
+    cx.observe_new(|workspace: &mut Workspace, _window, _cx| {
+        workspace.register_action(
+            |workspace: &mut Workspace,
+             _: &OpenUsageDashboard,
+             window: &mut Window,
+             cx: &mut Context<Workspace>| {
+                let existing = workspace
+                    .active_pane()
+                    .read(cx)
+                    .items()
+                    .find_map(|item| item.downcast::<UsageDashboard>());
+
+                if let Some(existing) = existing {
+                    workspace.activate_item(&existing, true, true, window, cx);
+                } else {
+                    let dashboard = UsageDashboard::new(workspace, window, cx);
+                    workspace.add_item_to_active_pane(Box::new(dashboard), None, true, window, cx);
+                }
+            },
+        );
+    })
+    .detach();
     cx.observe_new(ManageProfilesModal::register).detach();
 
     // Update command palette filter based on AI settings
@@ -734,6 +804,10 @@ mod tests {
             message_editor_min_lines: 1,
             tool_permissions: Default::default(),
             show_turn_stats: false,
+            auto_compact_threads: false,
+            auto_compact_threshold: 0.85,
+            hooks: Default::default(),
+            usage: Default::default(),
             new_thread_location: Default::default(),
             sidebar_side: Default::default(),
             thinking_display: Default::default(),
@@ -7,8 +7,10 @@ use std::sync::atomic::AtomicBool;
 use crate::DEFAULT_THREAD_TITLE;
 use crate::ThreadHistory;
 use acp_thread::MentionUri;
+use agent::ContextServerRegistry;
 use agent_client_protocol as acp;
 use anyhow::Result;
+use context_server::ContextServerId;
 use editor::{CompletionProvider, Editor, code_context_menus::COMPLETION_MENU_MAX_WIDTH};
 use futures::FutureExt as _;
 use fuzzy::{PathMatch, StringMatch, StringMatchCandidate};
@@ -64,6 +66,7 @@ pub(crate) enum PromptContextType {
     Rules,
     Diagnostics,
     BranchDiff,
+    McpResource,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -103,6 +106,7 @@ impl TryFrom<&str> for PromptContextType {
             "rule" => Ok(Self::Rules),
             "diagnostics" => Ok(Self::Diagnostics),
             "diff" => Ok(Self::BranchDiff),
+            "resource" => Ok(Self::McpResource),
             _ => Err(format!("Invalid context picker mode: {}", value)),
         }
     }
@@ -118,6 +122,7 @@ impl PromptContextType {
             Self::Rules => "rule",
             Self::Diagnostics => "diagnostics",
             Self::BranchDiff => "branch diff",
+            Self::McpResource => "resource",
         }
     }
 
@@ -130,6 +135,7 @@ impl PromptContextType {
             Self::Rules => "Rules",
             Self::Diagnostics => "Diagnostics",
             Self::BranchDiff => "Branch Diff",
+            Self::McpResource => "MCP Resources",
         }
     }
 
@@ -142,6 +148,7 @@ impl PromptContextType {
             Self::Rules => IconName::Reader,
             Self::Diagnostics => IconName::Warning,
             Self::BranchDiff => IconName::GitBranch,
+            Self::McpResource => IconName::Server,
         }
     }
 }
@@ -155,6 +162,7 @@ pub(crate) enum Match {
     Rules(RulesContextEntry),
     Entry(EntryMatch),
     BranchDiff(BranchDiffMatch),
+    McpResource(McpResourceMatch),
 }
 
 #[derive(Debug, Clone)]
@@ -162,6 +170,16 @@ pub struct BranchDiffMatch {
     pub base_ref: SharedString,
 }
 
+#[derive(Debug, Clone)]
+pub struct McpResourceMatch {
+    pub server_id: ContextServerId,
+    pub uri: SharedString,
+    pub name: SharedString,
+    /// Whether `uri` is a resource template whose parameters still need to be
+    /// filled in.
+    pub is_template: bool,
+}
+
 impl Match {
     pub fn score(&self) -> f64 {
         match self {
@@ -173,6 +191,7 @@ impl Match {
             Match::Rules(_) => 1.,
             Match::Fetch(_) => 1.,
             Match::BranchDiff(_) => 1.,
+            Match::McpResource(_) => 1.,
         }
     }
 }
@@ -832,6 +851,82 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
         }
     }
 
+    fn completion_for_mcp_resource(
+        resource: McpResourceMatch,
+        source_range: Range<Anchor>,
+        source: Arc<T>,
+        editor: WeakEntity<Editor>,
+        mention_set: WeakEntity<MentionSet>,
+        workspace: Entity<Workspace>,
+        cx: &mut App,
+    ) -> Completion {
+        if resource.is_template {
+            // Insert the template so its parameters can be edited in place. Once
+            // they're filled in, the resulting URI is offered as a resource.
+            let variables = context_server::uri_template::template_variables(&resource.uri);
+            let documentation = (!variables.is_empty()).then(|| {
+                CompletionDocumentation::SingleLine(
+                    format!(
+                        "Replace {} to read this resource",
+                        variables
+                            .iter()
+                            .map(|variable| format!("{{{variable}}}"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )
+                    .into(),
+                )
+            });
+            return Completion {
+                replace_range: source_range,
+                new_text: format!(
+                    "@{} {}",
+                    PromptContextType::McpResource.keyword(),
+                    resource.uri
+                ),
+                label: CodeLabel::plain(format!("{} ({})", resource.name, resource.uri), None),
+                documentation,
+                source: project::CompletionSource::Custom,
+                icon_path: Some(IconName::Server.path().into()),
+                match_start: None,
+                snippet_deduplication_key: None,
+                insert_text_mode: None,
+                confirm: Some(Arc::new(|_, _, _| true)),
+            };
+        }
+
+        let uri = MentionUri::McpResource {
+            server_id: resource.server_id.0.to_string(),
+            uri: resource.uri.to_string(),
+            name: resource.name.to_string(),
+        };
+        let new_text = format!("{} ", uri.as_link());
+        let new_text_len = new_text.len();
+        let icon_path = uri.icon_path(cx);
+
+        Completion {
+            replace_range: source_range.clone(),
+            new_text,
+            label: CodeLabel::plain(resource.name.to_string(), None),
+            documentation: Some(CompletionDocumentation::SingleLine(resource.uri.clone())),
+            source: project::CompletionSource::Custom,
+            icon_path: Some(icon_path),
+            match_start: None,
+            snippet_deduplication_key: None,
+            insert_text_mode: None,
+            confirm: Some(confirm_completion_callback(
+                resource.name,
+                source_range.start,
+                new_text_len - 1,
+                uri,
+                source,
+                editor,
+                mention_set,
+                workspace,
+            )),
+        }
+    }
+
     fn search_slash_commands(&self, query: String, cx: &mut App) -> Task<Vec<AvailableCommand>> {
         let commands = self.source.available_commands(cx);
         if commands.is_empty() {
@@ -966,6 +1061,24 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
 
             Some(PromptContextType::BranchDiff) => Task::ready(Vec::new()),
 
+            Some(PromptContextType::McpResource) => {
+                let Some(registry) = workspace
+                    .read(cx)
+                    .panel::<AgentPanel>(cx)
+                    .map(|panel| panel.read(cx).context_server_registry().clone())
+                else {
+                    return Task::ready(Vec::new());
+                };
+                let search_task = search_mcp_resources(query, cancellation_flag, &registry, cx);
+                cx.background_spawn(async move {
+                    search_task
+                        .await
+                        .into_iter()
+                        .map(Match::McpResource)
+                        .collect()
+                })
+            }
+
             None if query.is_empty() => {
                 let recent_task = self.recent_context_picker_entries(&workspace, cx);
                 let entries = self
@@ -1226,6 +1339,19 @@ impl<T: PromptCompletionProviderDelegate> PromptCompletionProvider<T> {
             }
         }
 
+        if self
+            .source
+            .supports_context(PromptContextType::McpResource, cx)
+            && let Some(agent_panel) = workspace.read(cx).panel::<AgentPanel>(cx)
+        {
+            let registry = agent_panel.read(cx).context_server_registry().read(cx);
+            if registry.resources().next().is_some()
+                || registry.resource_templates().next().is_some()
+            {
+                entries.push(PromptContextEntry::Mode(PromptContextType::McpResource));
+            }
+        }
+
         entries
     }
 }
@@ -1493,6 +1619,17 @@ impl<T: PromptCompletionProviderDelegate> CompletionProvider for PromptCompletio
                                         cx,
                                     ))
                                 }
+                                Match::McpResource(resource) => {
+                                    Some(Self::completion_for_mcp_resource(
+                                        resource,
+                                        source_range.clone(),
+                                        source.clone(),
+                                        editor.clone(),
+                                        mention_set.clone(),
+                                        workspace.clone(),
+                                        cx,
+                                    ))
+                                }
                             })
                             .collect::<Vec<_>>()
                     });
@@ -2077,6 +2214,82 @@ async fn filter_sessions(
         .collect()
 }
 
+fn search_mcp_resources(
+    query: String,
+    cancellation_flag: Arc<AtomicBool>,
+    registry: &Entity<ContextServerRegistry>,
+    cx: &mut App,
+) -> Task<Vec<McpResourceMatch>> {
+    let registry = registry.read(cx);
+    let mut candidates = registry
+        .resources()
+        .map(|resource| McpResourceMatch {
+            server_id: resource.server_id.clone(),
+            uri: resource.resource.uri.to_string().into(),
+            name: resource.resource.name.clone().into(),
+            is_template: false,
+        })
+        .collect::<Vec<_>>();
+
+    // A fully expanded template is offered as a resource of its own.
+    let query_has_placeholders = query.contains(['{', '}']);
+    for template in registry.resource_templates() {
+        let uri_template = &template.template.uri_template;
+        if !query_has_placeholders
+            && !query.is_empty()
+            && context_server::uri_template::match_uri_template(uri_template, &query).is_some()
+        {
+            return Task::ready(vec![McpResourceMatch {
+                server_id: template.server_id.clone(),
+                uri: query.clone().into(),
+                name: query
+                    .rsplit('/')
+                    .find(|segment| !segment.is_empty())
+                    .unwrap_or(&query)
+                    .to_string()
+                    .into(),
+                is_template: false,
+            }]);
+        }
+        candidates.push(McpResourceMatch {
+            server_id: template.server_id.clone(),
+            uri: uri_template.clone().into(),
+            name: template.template.name.clone().into(),
+            is_template: true,
+        });
+    }
+
+    if query.is_empty() {
+        return Task::ready(candidates);
+    }
+
+    let executor = cx.background_executor().clone();
+    cx.background_spawn(async move {
+        let string_candidates = candidates
+            .iter()
+            .enumerate()
+            .map(|(id, candidate)| {
+                StringMatchCandidate::new(id, &format!("{} {}", candidate.name, candidate.uri))
+            })
+            .collect::<Vec<_>>();
+        let matches = fuzzy::match_strings(
+            &string_candidates,
+            &query,
+            false,
+            true,
+            100,
+            &cancellation_flag,
+            executor,
+        )
+        .await;
+
+        matches
+            .into_iter()
+            .map(|mat| candidates[mat.candidate_id].clone())
+            .collect()
+    })
+}
+
 pub(crate) fn search_rules(
     query: String,
     cancellation_flag: Arc<AtomicBool>,
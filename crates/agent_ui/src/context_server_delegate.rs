@@ -0,0 +1,325 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result, anyhow};
+use context_server::{
+    ContextServerClientDelegate, ContextServerId,
+    types::{
+        CreateMessageRequest, CreateMessageResult, ElicitAction, ElicitRequestParams, ElicitResult,
+        MessageContent as McpMessageContent, ModelPreferences, Role as McpRole,
+    },
+};
+use futures::{StreamExt as _, channel::oneshot};
+use gpui::{App, AsyncApp, PromptLevel, Task, WindowHandle};
+use language_model::{
+    LanguageModel, LanguageModelCompletionEvent, LanguageModelImage, LanguageModelRegistry,
+    LanguageModelRequest, LanguageModelRequestMessage, MessageContent, Role, StopReason,
+};
+use workspace::MultiWorkspace;
+
+use crate::elicitation_modal::ElicitationModal;
+
+/// Answers sampling and elicitation requests from context servers by prompting
+/// the user in the active workspace window.
+struct AgentContextServerDelegate;
+
+pub(crate) fn init(cx: &mut App) {
+    <dyn ContextServerClientDelegate>::set_global(Arc::new(AgentContextServerDelegate), cx);
+}
+
+impl ContextServerClientDelegate for AgentContextServerDelegate {
+    fn create_message(
+        &self,
+        server_id: ContextServerId,
+        params: CreateMessageRequest,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<CreateMessageResult>> {
+        cx.spawn(async move |cx| {
+            let model = cx
+                .update(|cx| model_for_preferences(params.model_preferences.as_ref(), cx))
+                .context("No language model is configured")?;
+
+            let window = active_workspace_window(cx)
+                .context("No workspace window is available to approve the request")?;
+            let detail = sampling_prompt_detail(&params, model.as_ref());
+            let answer = window.update(cx, |_, window, cx| {
+                window.prompt(
+                    PromptLevel::Info,
+                    &format!("The MCP server \"{server_id}\" wants to use your language model"),
+                    Some(&detail),
+                    &["Allow", "Deny"],
+                    cx,
+                )
+            })?;
+            if answer.await? != 0 {
+                return Err(anyhow!("The user rejected the sampling request"));
+            }
+
+            let max_tokens = params.max_tokens;
+            let request = sampling_request(params);
+            let mut events = model.stream_completion(request, cx).await?;
+            let mut text = String::new();
+            let mut stop_reason = None;
+            let mut output_tokens = 0;
+            while let Some(event) = events.next().await {
+                match event? {
+                    LanguageModelCompletionEvent::Text(chunk) => text.push_str(&chunk),
+                    LanguageModelCompletionEvent::UsageUpdate(usage) => {
+                        output_tokens = usage.output_tokens;
+                    }
+                    LanguageModelCompletionEvent::Stop(reason) => {
+                        stop_reason = Some(match reason {
+                            StopReason::EndTurn => "endTurn",
+                            StopReason::MaxTokens => "maxTokens",
+                            StopReason::ToolUse => "toolUse",
+                            StopReason::Refusal => "refusal",
+                        });
+                    }
+                    _ => {}
+                }
+                // Requests can't carry an output limit, so dropping the stream
+                // is what stops the model once the server's limit is reached.
+                if truncate_to_max_tokens(&mut text, output_tokens, max_tokens) {
+                    stop_reason = Some("maxTokens");
+                    break;
+                }
+            }
+
+            Ok(CreateMessageResult {
+                role: McpRole::Assistant,
+                content: McpMessageContent::Text {
+                    text,
+                    annotations: None,
+                },
+                model: model.id().0.to_string(),
+                stop_reason: stop_reason.map(ToString::to_string),
+            })
+        })
+    }
+
+    fn elicit(
+        &self,
+        server_id: ContextServerId,
+        params: ElicitRequestParams,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<ElicitResult>> {
+        cx.spawn(async move |cx| {
+            let window = active_workspace_window(cx)
+                .context("No workspace window is available to show the form")?;
+            let (tx, rx) = oneshot::channel();
+            window.update(cx, |multi_workspace, window, cx| {
+                multi_workspace.workspace().update(cx, |workspace, cx| {
+                    anyhow::ensure!(
+                        workspace.active_modal::<ElicitationModal>(cx).is_none(),
+                        "Another MCP server request is awaiting user input"
+                    );
+                    workspace.toggle_modal(window, cx, |window, cx| {
+                        ElicitationModal::new(server_id, params, tx, window, cx)
+                    });
+                    anyhow::Ok(())
+                })
+            })??;
+
+            // The sender is dropped when the modal is dismissed without an answer.
+            Ok(rx.await.unwrap_or(ElicitResult {
+                action: ElicitAction::Cancel,
+                content: None,
+            }))
+        })
+    }
+}
+
+fn active_workspace_window(cx: &AsyncApp) -> Option<WindowHandle<MultiWorkspace>> {
+    cx.update(|cx| {
+        cx.active_window()
+            .and_then(|window| window.downcast::<MultiWorkspace>())
+            .or_else(|| {
+                cx.windows()
+                    .into_iter()
+                    .find_map(|window| window.downcast::<MultiWorkspace>())
+            })
+    })
+}
+
+/// Picks the first available model matching one of the server's hints, falling
+/// back to the user's default model.
+fn model_for_preferences(
+    preferences: Option<&ModelPreferences>,
+    cx: &App,
+) -> Option<Arc<dyn LanguageModel>> {
+    let registry = LanguageModelRegistry::read_global(cx);
+    let hints = preferences
+        .and_then(|preferences| preferences.hints.as_ref())
+        .into_iter()
+        .flatten()
+        .filter_map(|hint| hint.name.as_deref());
+    for hint in hints {
+        let hint = hint.to_lowercase();
+        if let Some(model) = registry.available_models(cx).find(|model| {
+            model.id().0.to_lowercase().contains(&hint)
+                || model.name().0.to_lowercase().contains(&hint)
+        }) {
+            return Some(model);
+        }
+    }
+    registry.default_model().map(|configured| configured.model)
+}
+
+fn sampling_prompt_detail(params: &CreateMessageRequest, model: &dyn LanguageModel) -> String {
+    let mut detail = format!(
+        "Model: {}\nMaximum tokens: {}\n",
+        model.name().0,
+        params.max_tokens
+    );
+    if let Some(system_prompt) = &params.system_prompt {
+        detail.push_str(&format!("\nSystem prompt:\n{system_prompt}\n"));
+    }
+    for message in &params.messages {
+        let role = match message.role {
+            McpRole::User => "User",
+            McpRole::Assistant => "Assistant",
+        };
+        let content = match &message.content {
+            McpMessageContent::Text { text, .. } => text.as_str(),
+            McpMessageContent::Image { .. } => "[image]",
+            McpMessageContent::Audio { .. } => "[audio]",
+            McpMessageContent::Resource { .. } => "[resource]",
+        };
+        detail.push_str(&format!("\n{role}:\n{content}\n"));
+    }
+    detail
+}
+
+/// Cuts `text` down to `max_tokens`, returning whether the limit was reached.
+///
+/// Uses the model's reported output tokens when it's ahead of a rough guess
+/// from the length of the text, as not every provider reports usage while
+/// streaming.
+fn truncate_to_max_tokens(text: &mut String, reported_tokens: u64, max_tokens: u32) -> bool {
+    let max_tokens = max_tokens as u64;
+    let estimated_tokens = text.len().div_ceil(4) as u64;
+    if reported_tokens.max(estimated_tokens) < max_tokens {
+        return false;
+    }
+    if estimated_tokens > max_tokens {
+        let end = text.floor_char_boundary(max_tokens as usize * 4);
+        text.truncate(end);
+    }
+    true
+}
+
+fn sampling_request(params: CreateMessageRequest) -> LanguageModelRequest {
+    let mut messages = Vec::with_capacity(params.messages.len() + 1);
+    if let Some(system_prompt) = params.system_prompt {
+        messages.push(LanguageModelRequestMessage {
+            role: Role::System,
+            content: vec![system_prompt.into()],
+            cache: false,
+            reasoning_details: None,
+        });
+    }
+    for message in params.messages {
+        let content = match message.content {
+            McpMessageContent::Text { text, .. } => MessageContent::Text(text),
+            McpMessageContent::Image { data, .. } => MessageContent::Image(LanguageModelImage {
+                source: data.into(),
+                size: None,
+            }),
+            McpMessageContent::Audio { .. } | McpMessageContent::Resource { .. } => {
+                log::warn!("Ignoring unsupported content in MCP sampling request");
+                continue;
+            }
+        };
+        messages.push(LanguageModelRequestMessage {
+            role: match message.role {
+                McpRole::User => Role::User,
+                McpRole::Assistant => Role::Assistant,
+            },
+            content: vec![content],
+            cache: false,
+            reasoning_details: None,
+        });
+    }
+
+    LanguageModelRequest {
+        messages,
+        stop: params.stop_sequences.unwrap_or_default(),
+        temperature: params.temperature.map(|temperature| temperature as f32),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use context_server::types::SamplingMessage;
+
+    #[test]
+    fn test_truncate_to_max_tokens() {
+        let mut text = "a".repeat(10);
+        assert!(!truncate_to_max_tokens(&mut text, 0, 5));
+        assert_eq!(text, "a".repeat(10));
+
+        let mut text = "a".repeat(30);
+        assert!(truncate_to_max_tokens(&mut text, 0, 5));
+        assert_eq!(text, "a".repeat(20));
+
+        // The model's own count wins when it's ahead of the estimate.
+        let mut text = "a".repeat(10);
+        assert!(truncate_to_max_tokens(&mut text, 5, 5));
+        assert_eq!(text, "a".repeat(10));
+
+        let mut text = "é".repeat(15);
+        assert!(truncate_to_max_tokens(&mut text, 0, 5));
+        assert_eq!(text, "é".repeat(10));
+    }
+
+    #[test]
+    fn test_sampling_request() {
+        let request = sampling_request(CreateMessageRequest {
+            messages: vec![
+                SamplingMessage {
+                    role: McpRole::User,
+                    content: McpMessageContent::Text {
+                        text: "What is the capital of France?".into(),
+                        annotations: None,
+                    },
+                },
+                SamplingMessage {
+                    role: McpRole::Assistant,
+                    content: McpMessageContent::Audio {
+                        data: String::new(),
+                        mime_type: "audio/wav".into(),
+                        annotations: None,
+                    },
+                },
+            ],
+            model_preferences: None,
+            system_prompt: Some("Be brief.".into()),
+            include_context: None,
+            temperature: Some(0.5),
+            max_tokens: 100,
+            stop_sequences: Some(vec!["\n".into()]),
+            metadata: None,
+        });
+
+        assert_eq!(
+            request.messages,
+            vec![
+                LanguageModelRequestMessage {
+                    role: Role::System,
+                    content: vec!["Be brief.".into()],
+                    cache: false,
+                    reasoning_details: None,
+                },
+                LanguageModelRequestMessage {
+                    role: Role::User,
+                    content: vec!["What is the capital of France?".into()],
+                    cache: false,
+                    reasoning_details: None,
+                },
+            ]
+        );
+        assert_eq!(request.stop, vec!["\n".to_string()]);
+        assert_eq!(request.temperature, Some(0.5));
+    }
+}
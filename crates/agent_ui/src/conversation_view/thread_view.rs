@@ -2025,6 +2025,51 @@ impl ThreadView {
             .detach_and_log_err(cx);
     }
 
+    /// Opens a new thread that shares this thread's history up to the given
+    /// message, optionally restoring the project to that message's checkpoint.
+    fn fork_from_message(
+        &mut self,
+        message_id: &UserMessageId,
+        restore_checkpoint: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(connection) = self.as_native_connection(cx) else {
+            return;
+        };
+        let session_id = self.thread.read(cx).session_id().clone();
+        let fork = connection.fork_thread(&session_id, message_id, cx);
+        let message_id = message_id.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn_in(window, async move |this, cx| {
+            let result = async {
+                let fork_id = fork.await?;
+                if restore_checkpoint {
+                    this.update(cx, |this, cx| {
+                        this.thread.update(cx, |thread, cx| {
+                            thread.restore_git_checkpoint(&message_id, cx)
+                        })
+                    })?
+                    .await?;
+                }
+                anyhow::Ok(fork_id)
+            }
+            .await;
+
+            match result {
+                Ok(fork_id) => workspace.update_in(cx, |workspace, window, cx| {
+                    if let Some(panel) = workspace.panel::<AgentPanel>(cx) {
+                        panel.update(cx, |panel, cx| {
+                            panel.open_thread(fork_id, None, None, window, cx)
+                        });
+                    }
+                }),
+                Err(error) => this.update(cx, |this, cx| this.handle_thread_error(error, cx)),
+            }
+        })
+        .detach_and_log_err(cx);
+    }
+
     pub fn clear_thread_error(&mut self, cx: &mut Context<Self>) {
         self.thread_error = None;
         self.thread_error_markdown = None;
@@ -4345,6 +4390,7 @@ impl ThreadView {
 
                 let is_subagent = self.is_subagent();
                 let is_editable = message.id.is_some() && !is_subagent;
+                let is_native = self.as_native_connection(cx).is_some();
                 let agent_name = if is_subagent {
                     "subagents".into()
                 } else {
@@ -4376,10 +4422,25 @@ impl ThreadView {
                                         .label_size(LabelSize::XSmall)
                                         .color(Color::Muted)
                                         .tooltip(Tooltip::text("Restores all files in the project to the content they had at this point in the conversation."))
-                                        .on_click(cx.listener(move |this, _, _window, cx| {
-                                            this.restore_checkpoint(&message_id, cx);
+                                        .on_click(cx.listener({
+                                            let message_id = message_id.clone();
+                                            move |this, _, _window, cx| {
+                                                this.restore_checkpoint(&message_id, cx);
+                                            }
                                         }))
                                 )
+                                .when(is_native, |this| {
+                                    this.child(
+                                        Button::new("fork-from-checkpoint", "Fork From Checkpoint")
+                                            .start_icon(Icon::new(IconName::GitBranch).size(IconSize::XSmall).color(Color::Muted))
+                                            .label_size(LabelSize::XSmall)
+                                            .color(Color::Muted)
+                                            .tooltip(Tooltip::text("Opens a new thread from this point and restores all files in the project to the content they had here. This thread is kept as is."))
+                                            .on_click(cx.listener(move |this, _, window, cx| {
+                                                this.fork_from_message(&message_id, true, window, cx);
+                                            }))
+                                    )
+                                })
                                 .child(Divider::horizontal())
                         }))
                     })
@@ -4440,6 +4501,23 @@ impl ThreadView {
                                                     .icon_size(IconSize::XSmall)
                                                     .on_click(cx.listener(Self::cancel_editing))
                                             )
+                                            .when_some(
+                                                message.id.clone().filter(|_| is_native),
+                                                |this, message_id| {
+                                                    this.child(
+                                                        IconButton::new("fork", IconName::GitBranch)
+                                                            .disabled(is_loading_contents)
+                                                            .icon_color(Color::Muted)
+                                                            .icon_size(IconSize::XSmall)
+                                                            .tooltip(Tooltip::text(
+                                                                "Fork a new thread from this point, keeping this one as is."
+                                                            ))
+                                                            .on_click(cx.listener(move |this, _, window, cx| {
+                                                                this.fork_from_message(&message_id, false, window, cx);
+                                                            }))
+                                                    )
+                                                },
+                                            )
                                             .child(
                                                 if is_loading_contents {
                                                     div()
@@ -4621,6 +4699,22 @@ impl ThreadView {
             primary
         };
 
+        let is_first_kept_message = matches!(
+            entry,
+            AgentThreadEntry::UserMessage(message)
+                if message.id.is_some() && message.id == self.compaction_boundary_id(cx)
+        );
+
+        let primary = if is_first_kept_message {
+            v_flex()
+                .w_full()
+                .child(self.render_compaction_divider(cx))
+                .child(primary)
+                .into_any_element()
+        } else {
+            primary
+        };
+
         let thread = self.thread.clone();
 
         let primary = if is_indented {
@@ -4694,6 +4788,109 @@ impl ThreadView {
         }
     }
 
+    fn compaction_boundary_id(&self, cx: &App) -> Option<acp_thread::UserMessageId> {
+        let thread = self.as_native_thread(cx)?;
+        let compaction = thread.read(cx).compaction()?;
+        Some(compaction.first_kept_message_id.clone())
+    }
+
+    fn render_compaction_divider(&self, cx: &Context<Self>) -> AnyElement {
+        h_flex()
+            .id("compaction-divider")
+            .px_5()
+            .py_1()
+            .gap_2()
+            .child(Divider::horizontal())
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::ThreadFromSummary)
+                            .color(Color::Muted)
+                            .size(IconSize::Small),
+                    )
+                    .child(
+                        Label::new("Earlier Messages Compacted")
+                            .size(LabelSize::Custom(self.tool_name_font_size()))
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(Divider::horizontal())
+            .tooltip(Tooltip::text(
+                "Messages above this line were summarized to save context. \
+                They are kept here for reference, but only the summary is sent to the model. \
+                Click to view the summary.",
+            ))
+            .on_click(cx.listener(|this, _, window, cx| {
+                if let Some(workspace) = this.workspace.upgrade() {
+                    this.open_compaction_summary(workspace, window, cx)
+                        .detach_and_log_err(cx);
+                }
+            }))
+            .into_any_element()
+    }
+
+    fn open_compaction_summary(
+        &self,
+        workspace: Entity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<()>> {
+        let Some(summary) = self
+            .as_native_thread(cx)
+            .and_then(|thread| Some(thread.read(cx).compaction()?.summary.clone()))
+        else {
+            return Task::ready(Ok(()));
+        };
+        let markdown_language_task = workspace
+            .read(cx)
+            .app_state()
+            .languages
+            .language_for_name("Markdown");
+        let title = format!(
+            "Summary of {}",
+            self.thread
+                .read(cx)
+                .title()
+                .unwrap_or_else(|| DEFAULT_THREAD_TITLE.into())
+        );
+
+        let project = workspace.read(cx).project().clone();
+        window.spawn(cx, async move |cx| {
+            let markdown_language = markdown_language_task.await?;
+
+            let buffer = project
+                .update(cx, |project, cx| {
+                    project.create_buffer(Some(markdown_language), false, cx)
+                })
+                .await?;
+
+            buffer.update(cx, |buffer, cx| {
+                buffer.set_text(summary.to_string(), cx);
+                buffer.set_capability(language::Capability::ReadOnly, cx);
+            });
+
+            workspace.update_in(cx, |workspace, window, cx| {
+                let buffer =
+                    cx.new(|cx| MultiBuffer::singleton(buffer, cx).with_title(title.clone()));
+
+                workspace.add_item_to_active_pane(
+                    Box::new(cx.new(|cx| {
+                        let mut editor =
+                            Editor::for_multibuffer(buffer, Some(project.clone()), window, cx);
+                        editor.set_breadcrumb_header(title);
+                        editor
+                    })),
+                    None,
+                    true,
+                    window,
+                    cx,
+                );
+            })?;
+            anyhow::Ok(())
+        })
+    }
+
     fn render_feedback_feedback_editor(editor: Entity<Editor>, cx: &Context<Self>) -> Div {
         h_flex()
             .key_context("AgentFeedbackMessageEditor")
@@ -8444,6 +8641,27 @@ impl ThreadView {
         )
     }
 
+    fn render_compaction_error_callout(&self, cx: &mut Context<Self>) -> Option<Callout> {
+        let thread = self.as_native_thread(cx)?;
+        let error = thread.read(cx).compaction_error()?.clone();
+
+        Some(
+            Callout::new()
+                .severity(Severity::Error)
+                .icon(IconName::XCircle)
+                .title("Compaction Failed")
+                .description(error)
+                .dismiss_action(
+                    IconButton::new("dismiss-compaction-error", IconName::Close)
+                        .icon_size(IconSize::Small)
+                        .tooltip(Tooltip::text("Dismiss"))
+                        .on_click(move |_, _, cx| {
+                            thread.update(cx, |thread, cx| thread.dismiss_compaction_error(cx));
+                        }),
+                ),
+        )
+    }
+
     fn render_token_limit_callout(&self, cx: &mut Context<Self>) -> Option<Callout> {
         if self.token_limit_callout_dismissed {
             return None;
@@ -8466,7 +8684,19 @@ impl ThreadView {
             ),
         };
 
-        let description = "To continue, start a new thread from a summary.";
+        let native_thread = self.as_native_thread(cx);
+        let can_compact = native_thread
+            .as_ref()
+            .is_some_and(|thread| thread.read(cx).can_compact());
+        let is_compacting = native_thread
+            .as_ref()
+            .is_some_and(|thread| thread.read(cx).is_compacting());
+
+        let description = if can_compact {
+            "To continue, compact this thread or start a new thread from a summary."
+        } else {
+            "To continue, start a new thread from a summary."
+        };
 
         Some(
             Callout::new()
@@ -8475,20 +8705,41 @@ impl ThreadView {
                 .title(title)
                 .description(description)
                 .actions_slot(
-                    h_flex().gap_0p5().child(
-                        Button::new("start-new-thread", "Start New Thread")
-                            .label_size(LabelSize::Small)
-                            .on_click(cx.listener(|this, _, window, cx| {
-                                let session_id = this.thread.read(cx).session_id().clone();
-                                window.dispatch_action(
-                                    crate::NewNativeAgentThreadFromSummary {
-                                        from_session_id: session_id,
-                                    }
-                                    .boxed_clone(),
-                                    cx,
-                                );
-                            })),
-                    ),
+                    h_flex()
+                        .gap_0p5()
+                        .when(can_compact, |this| {
+                            this.child(
+                                Button::new(
+                                    "compact-thread",
+                                    if is_compacting {
+                                        "Compacting…"
+                                    } else {
+                                        "Compact Thread"
+                                    },
+                                )
+                                .label_size(LabelSize::Small)
+                                .disabled(is_compacting)
+                                .on_click(cx.listener(
+                                    |this, _, _window, cx| {
+                                        this.compact_thread(cx);
+                                    },
+                                )),
+                            )
+                        })
+                        .child(
+                            Button::new("start-new-thread", "Start New Thread")
+                                .label_size(LabelSize::Small)
+                                .on_click(cx.listener(|this, _, window, cx| {
+                                    let session_id = this.thread.read(cx).session_id().clone();
+                                    window.dispatch_action(
+                                        crate::NewNativeAgentThreadFromSummary {
+                                            from_session_id: session_id,
+                                        }
+                                        .boxed_clone(),
+                                        cx,
+                                    );
+                                })),
+                        ),
                 )
                 .dismiss_action(self.dismiss_error_button(cx)),
         )
@@ -8518,6 +8769,27 @@ impl ThreadView {
         });
     }
 
+    fn compact_thread(&mut self, cx: &mut Context<Self>) {
+        let Some(thread) = self.as_native_thread(cx) else {
+            return;
+        };
+        let task = thread.update(cx, |thread, cx| thread.compact(cx));
+        cx.spawn(async move |this, cx| {
+            let result = task.await;
+            this.update(cx, |this, cx| {
+                match result {
+                    Ok(()) => this.token_limit_callout_dismissed = true,
+                    // Failed summaries are shown by `render_compaction_error_callout`.
+                    Err(_) if thread.read(cx).compaction_error().is_some() => {}
+                    Err(error) => this.handle_thread_error(error, cx),
+                }
+                cx.notify();
+            })
+            .ok();
+        })
+        .detach();
+    }
+
     fn toggle_fast_mode(&mut self, cx: &mut Context<Self>) {
         if !self.fast_mode_available(cx) {
             return;
@@ -8657,6 +8929,9 @@ impl Render for ThreadView {
             .on_action(cx.listener(|this, _: &ToggleFastMode, _window, cx| {
                 this.toggle_fast_mode(cx);
             }))
+            .on_action(cx.listener(|this, _: &CompactThread, _window, cx| {
+                this.compact_thread(cx);
+            }))
             .on_action(cx.listener(|this, _: &ToggleThinkingMode, _window, cx| {
                 if this.thread.read(cx).status() != ThreadStatus::Idle {
                     return;
@@ -8811,6 +9086,7 @@ impl Render for ThreadView {
                 },
                 |this, version| this.child(self.render_new_version_callout(&version, cx)),
             )
+            .children(self.render_compaction_error_callout(cx))
             .children(self.render_token_limit_callout(cx))
             .child(self.render_message_editor(window, cx))
     }
@@ -8919,6 +9195,7 @@ pub(crate) fn open_link(
             MentionUri::TerminalSelection { .. } => {}
             MentionUri::GitDiff { .. } => {}
             MentionUri::MergeConflict { .. } => {}
+            MentionUri::McpResource { .. } => {}
         })
     } else {
         cx.open_url(&url);
@@ -0,0 +1,449 @@
+use anyhow::{Result, anyhow};
+use context_server::{
+    ContextServerId,
+    types::{ElicitAction, ElicitRequestParams, ElicitResult, PrimitiveSchemaDefinition},
+};
+use futures::channel::oneshot;
+use gpui::{DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, ScrollHandle};
+use serde_json::Value;
+use ui::{
+    Banner, Checkbox, KeyBinding, Modal, ModalFooter, ModalHeader, Section, ToggleState,
+    WithScrollbar, prelude::*,
+};
+use ui_input::InputField;
+use workspace::ModalView;
+
+enum FieldInput {
+    Text(Entity<InputField>),
+    Boolean(ToggleState),
+}
+
+struct ElicitationField {
+    name: String,
+    schema: PrimitiveSchemaDefinition,
+    required: bool,
+    input: FieldInput,
+}
+
+/// A form generated from the schema of an MCP `elicitation/create` request.
+pub struct ElicitationModal {
+    server_id: ContextServerId,
+    message: SharedString,
+    fields: Vec<ElicitationField>,
+    response_tx: Option<oneshot::Sender<ElicitResult>>,
+    last_error: Option<SharedString>,
+    focus_handle: FocusHandle,
+    scroll_handle: ScrollHandle,
+}
+
+impl ElicitationModal {
+    pub fn new(
+        server_id: ContextServerId,
+        params: ElicitRequestParams,
+        response_tx: oneshot::Sender<ElicitResult>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let required = params.requested_schema.required;
+        let fields = params
+            .requested_schema
+            .properties
+            .into_iter()
+            .enumerate()
+            .map(|(ix, (name, schema))| {
+                let label = schema.title().unwrap_or(&name).to_string();
+                let input = match &schema {
+                    PrimitiveSchemaDefinition::Boolean(boolean) => {
+                        FieldInput::Boolean(boolean.default.unwrap_or(false).into())
+                    }
+                    PrimitiveSchemaDefinition::String(string) => {
+                        let placeholder = string
+                            .enum_names
+                            .as_ref()
+                            .or(string.options.as_ref())
+                            .map(|options| options.join(", "))
+                            .unwrap_or_default();
+                        FieldInput::Text(text_input(label, &placeholder, ix, window, cx))
+                    }
+                    PrimitiveSchemaDefinition::Number(_)
+                    | PrimitiveSchemaDefinition::Integer(_) => {
+                        FieldInput::Text(text_input(label, "", ix, window, cx))
+                    }
+                };
+                ElicitationField {
+                    required: required.contains(&name),
+                    name,
+                    schema,
+                    input,
+                }
+            })
+            .collect();
+
+        Self {
+            server_id,
+            message: params.message.into(),
+            fields,
+            response_tx: Some(response_tx),
+            last_error: None,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: ScrollHandle::new(),
+        }
+    }
+
+    fn respond(&mut self, result: ElicitResult, cx: &mut Context<Self>) {
+        if let Some(response_tx) = self.response_tx.take() {
+            response_tx.send(result).ok();
+        }
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, _: &mut Window, cx: &mut Context<Self>) {
+        let mut content = serde_json::Map::default();
+        for field in &self.fields {
+            let value = match &field.input {
+                FieldInput::Boolean(state) => Ok(Some(Value::Bool(state.selected()))),
+                FieldInput::Text(input) => {
+                    parse_field_value(&field.schema, field.required, &input.read(cx).text(cx))
+                }
+            };
+            match value {
+                Ok(Some(value)) => {
+                    content.insert(field.name.clone(), value);
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    let label = field.schema.title().unwrap_or(&field.name);
+                    self.last_error = Some(format!("{label}: {error}").into());
+                    cx.notify();
+                    return;
+                }
+            }
+        }
+
+        self.respond(
+            ElicitResult {
+                action: ElicitAction::Accept,
+                content: Some(content),
+            },
+            cx,
+        );
+    }
+
+    fn decline(&mut self, cx: &mut Context<Self>) {
+        self.respond(
+            ElicitResult {
+                action: ElicitAction::Decline,
+                content: None,
+            },
+            cx,
+        );
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, _: &mut Window, cx: &mut Context<Self>) {
+        self.respond(
+            ElicitResult {
+                action: ElicitAction::Cancel,
+                content: None,
+            },
+            cx,
+        );
+    }
+
+    fn on_tab(&mut self, _: &menu::SelectNext, window: &mut Window, cx: &mut Context<Self>) {
+        window.focus_next(cx);
+    }
+
+    fn on_tab_prev(
+        &mut self,
+        _: &menu::SelectPrevious,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.focus_prev(cx);
+    }
+
+    fn render_field(
+        &self,
+        ix: usize,
+        field: &ElicitationField,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let label = field.schema.title().unwrap_or(&field.name).to_string();
+        let input = match &field.input {
+            FieldInput::Text(input) => input.clone().into_any_element(),
+            FieldInput::Boolean(state) => Checkbox::new(("elicitation-field", ix), *state)
+                .label(label)
+                .on_click(cx.listener(move |this, checked, _window, cx| {
+                    if let Some(FieldInput::Boolean(state)) =
+                        this.fields.get_mut(ix).map(|field| &mut field.input)
+                    {
+                        *state = *checked;
+                    }
+                    cx.notify();
+                }))
+                .into_any_element(),
+        };
+
+        v_flex()
+            .gap_0p5()
+            .child(input)
+            .when_some(field.schema.description(), |this, description| {
+                this.child(
+                    Label::new(description.to_string())
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+            })
+            .into_any_element()
+    }
+}
+
+fn text_input(
+    label: String,
+    placeholder: &str,
+    tab_index: usize,
+    window: &mut Window,
+    cx: &mut App,
+) -> Entity<InputField> {
+    cx.new(|cx| {
+        InputField::new(window, cx, placeholder)
+            .label(label)
+            .tab_index(tab_index as isize)
+            .tab_stop(true)
+    })
+}
+
+/// Converts the text entered for a field into the JSON value expected by its schema.
+/// Returns `None` when an optional field was left empty.
+fn parse_field_value(
+    schema: &PrimitiveSchemaDefinition,
+    required: bool,
+    text: &str,
+) -> Result<Option<Value>> {
+    let text = text.trim();
+    if text.is_empty() {
+        return if required {
+            Err(anyhow!("a value is required"))
+        } else {
+            Ok(None)
+        };
+    }
+
+    let value = match schema {
+        PrimitiveSchemaDefinition::String(string) => {
+            if let Some(options) = &string.options {
+                let position = options
+                    .iter()
+                    .position(|option| option == text)
+                    .or_else(|| {
+                        string
+                            .enum_names
+                            .as_ref()?
+                            .iter()
+                            .position(|name| name == text)
+                    });
+                match position {
+                    Some(position) => Value::String(options[position].clone()),
+                    None => return Err(anyhow!("must be one of {}", options.join(", "))),
+                }
+            } else {
+                let length = text.chars().count();
+                if let Some(min_length) = string.min_length
+                    && length < min_length
+                {
+                    return Err(anyhow!("must be at least {min_length} characters"));
+                }
+                if let Some(max_length) = string.max_length
+                    && length > max_length
+                {
+                    return Err(anyhow!("must be at most {max_length} characters"));
+                }
+                Value::String(text.to_string())
+            }
+        }
+        PrimitiveSchemaDefinition::Number(number) | PrimitiveSchemaDefinition::Integer(number) => {
+            let is_integer = matches!(schema, PrimitiveSchemaDefinition::Integer(_));
+            let parsed = if is_integer {
+                text.parse::<i64>()
+                    .map(|value| value as f64)
+                    .map_err(|_| anyhow!("must be an integer"))?
+            } else {
+                text.parse::<f64>()
+                    .map_err(|_| anyhow!("must be a number"))?
+            };
+            if let Some(minimum) = number.minimum
+                && parsed < minimum
+            {
+                return Err(anyhow!("must be at least {minimum}"));
+            }
+            if let Some(maximum) = number.maximum
+                && parsed > maximum
+            {
+                return Err(anyhow!("must be at most {maximum}"));
+            }
+            if is_integer {
+                Value::from(parsed as i64)
+            } else {
+                Value::from(parsed)
+            }
+        }
+        PrimitiveSchemaDefinition::Boolean(_) => match text {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => return Err(anyhow!("must be true or false")),
+        },
+    };
+    Ok(Some(value))
+}
+
+impl ModalView for ElicitationModal {}
+
+impl Focusable for ElicitationModal {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<DismissEvent> for ElicitationModal {}
+
+impl Render for ElicitationModal {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let focus_handle = self.focus_handle(cx);
+        let fields = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(ix, field)| self.render_field(ix, field, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .id("elicitation-modal")
+            .key_context("ElicitationModal")
+            .w(rems(34.))
+            .elevation_3(cx)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::on_tab))
+            .on_action(cx.listener(Self::on_tab_prev))
+            .capture_any_mouse_down(cx.listener(|this, _, window, cx| {
+                this.focus_handle(cx).focus(window, cx);
+            }))
+            .child(
+                Modal::new("elicitation", None)
+                    .header(
+                        ModalHeader::new()
+                            .headline(format!("Input requested by {}", self.server_id.0))
+                            .description(self.message.clone()),
+                    )
+                    .when_some(self.last_error.clone(), |this, error| {
+                        this.section(
+                            Section::new().child(
+                                Banner::new()
+                                    .severity(Severity::Warning)
+                                    .child(div().text_xs().child(error)),
+                            ),
+                        )
+                    })
+                    .child(
+                        div()
+                            .size_full()
+                            .vertical_scrollbar_for(&self.scroll_handle, window, cx)
+                            .child(
+                                v_flex()
+                                    .id("modal_content")
+                                    .size_full()
+                                    .tab_group()
+                                    .max_h(rems_from_px(450.))
+                                    .pl_3()
+                                    .pr_4()
+                                    .pb_2()
+                                    .gap_2()
+                                    .overflow_y_scroll()
+                                    .track_scroll(&self.scroll_handle)
+                                    .children(fields),
+                            ),
+                    )
+                    .footer(
+                        ModalFooter::new().end_slot(
+                            h_flex()
+                                .gap_1()
+                                .child(Button::new("decline", "Decline").on_click(
+                                    cx.listener(|this, _event, _window, cx| this.decline(cx)),
+                                ))
+                                .child(
+                                    Button::new("submit", "Submit")
+                                        .key_binding(
+                                            KeyBinding::for_action_in(
+                                                &menu::Confirm,
+                                                &focus_handle,
+                                                cx,
+                                            )
+                                            .map(|kb| kb.size(rems_from_px(12.))),
+                                        )
+                                        .on_click(cx.listener(|this, _event, window, cx| {
+                                            this.confirm(&menu::Confirm, window, cx)
+                                        })),
+                                ),
+                        ),
+                    ),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use context_server::types::{NumberSchema, StringSchema};
+    use serde_json::json;
+
+    fn string_schema(options: Option<Vec<&str>>, enum_names: Option<Vec<&str>>) -> StringSchema {
+        StringSchema {
+            title: None,
+            description: None,
+            min_length: None,
+            max_length: Some(5),
+            format: None,
+            options: options.map(|options| options.into_iter().map(Into::into).collect()),
+            enum_names: enum_names.map(|names| names.into_iter().map(Into::into).collect()),
+        }
+    }
+
+    #[test]
+    fn test_parse_field_value() {
+        let string = PrimitiveSchemaDefinition::String(string_schema(None, None));
+        assert_eq!(parse_field_value(&string, false, "  ").unwrap(), None);
+        assert!(parse_field_value(&string, true, "").is_err());
+        assert_eq!(
+            parse_field_value(&string, true, "abc").unwrap(),
+            Some(json!("abc"))
+        );
+        assert!(parse_field_value(&string, true, "abcdef").is_err());
+
+        let options = PrimitiveSchemaDefinition::String(string_schema(
+            Some(vec!["sm", "lg"]),
+            Some(vec!["Small", "Large"]),
+        ));
+        assert_eq!(
+            parse_field_value(&options, true, "Large").unwrap(),
+            Some(json!("lg"))
+        );
+        assert_eq!(
+            parse_field_value(&options, true, "sm").unwrap(),
+            Some(json!("sm"))
+        );
+        assert!(parse_field_value(&options, true, "medium").is_err());
+
+        let integer = PrimitiveSchemaDefinition::Integer(NumberSchema {
+            title: None,
+            description: None,
+            minimum: Some(1.),
+            maximum: Some(10.),
+        });
+        assert_eq!(
+            parse_field_value(&integer, true, "7").unwrap(),
+            Some(json!(7))
+        );
+        assert!(parse_field_value(&integer, true, "7.5").is_err());
+        assert!(parse_field_value(&integer, true, "11").is_err());
+    }
+}
@@ -1,10 +1,13 @@
 use crate::diagnostics::{DiagnosticsOptions, codeblock_fence_for_path, collect_diagnostics};
 use acp_thread::{MentionUri, selection_name};
-use agent::{ThreadStore, outline};
+use agent::{
+    ContextServerRegistry, ContextServerRegistryEvent, ResourceWatch, ThreadStore, outline,
+};
 use agent_client_protocol as acp;
 use agent_servers::{AgentServer, AgentServerDelegate};
 use anyhow::{Context as _, Result, anyhow};
 use collections::{HashMap, HashSet};
+use context_server::{ContextServerId, types::ResourceContentsType};
 use editor::{
     Anchor, Editor, EditorSnapshot, FoldPlaceholder, ToOffset,
     display_map::{Crease, CreaseId, CreaseMetadata, FoldId},
@@ -13,7 +16,7 @@ use editor::{
 use futures::{AsyncReadExt as _, FutureExt as _, future::Shared};
 use gpui::{
     AppContext, ClipboardEntry, Context, Empty, Entity, EntityId, Image, ImageFormat, Img,
-    SharedString, Task, WeakEntity,
+    SharedString, Subscription, Task, WeakEntity,
 };
 use http_client::{AsyncBody, HttpClientWithUrl};
 use itertools::Either;
@@ -38,6 +41,7 @@ use ui::{Disclosure, Toggleable, prelude::*};
 use util::{ResultExt, debug_panic, rel_path::RelPath};
 use workspace::{Workspace, notifications::NotifyResultExt as _};
 
+use crate::AgentPanel;
 use crate::ui::MentionCrease;
 
 pub type MentionTask = Shared<Task<Result<Mention, String>>>;
@@ -63,6 +67,8 @@ pub struct MentionSet {
     thread_store: Option<Entity<ThreadStore>>,
     prompt_store: Option<Entity<PromptStore>>,
     mentions: HashMap<CreaseId, (MentionUri, MentionTask)>,
+    mcp_resource_watches: HashMap<CreaseId, ResourceWatch>,
+    _context_server_registry_subscription: Option<Subscription>,
 }
 
 impl MentionSet {
@@ -76,6 +82,8 @@ impl MentionSet {
             thread_store,
             prompt_store,
             mentions: HashMap::default(),
+            mcp_resource_watches: HashMap::default(),
+            _context_server_registry_subscription: None,
         }
     }
 
@@ -112,6 +120,7 @@ impl MentionSet {
                 self.mentions.remove(&crease_id);
             }
         }
+        self.drop_stale_mcp_resource_watches();
     }
 
     pub fn insert_mention(&mut self, crease_id: CreaseId, uri: MentionUri, task: MentionTask) {
@@ -154,6 +163,9 @@ impl MentionSet {
             MentionUri::Selection { abs_path: None, .. } => Task::ready(Err(anyhow!(
                 "Untitled buffer selection mentions are not supported for paste"
             ))),
+            MentionUri::McpResource { server_id, uri, .. } => {
+                self.confirm_mention_for_mcp_resource(server_id, uri, cx)
+            }
             MentionUri::PastedImage
             | MentionUri::TerminalSelection { .. }
             | MentionUri::MergeConflict { .. } => {
@@ -164,6 +176,7 @@ impl MentionSet {
 
     pub fn remove_mention(&mut self, crease_id: &CreaseId) {
         self.mentions.remove(crease_id);
+        self.mcp_resource_watches.remove(crease_id);
     }
 
     pub fn creases(&self) -> HashSet<CreaseId> {
@@ -176,12 +189,22 @@ impl MentionSet {
 
     pub fn set_mentions(&mut self, mentions: HashMap<CreaseId, (MentionUri, MentionTask)>) {
         self.mentions = mentions;
+        self.drop_stale_mcp_resource_watches();
     }
 
     pub fn clear(&mut self) -> impl Iterator<Item = (CreaseId, (MentionUri, MentionTask))> {
+        self.mcp_resource_watches.clear();
         self.mentions.drain()
     }
 
+    /// Drops the watches of MCP resources that are no longer mentioned, so the
+    /// context server can stop sending their updates.
+    fn drop_stale_mcp_resource_watches(&mut self) {
+        let mentions = &self.mentions;
+        self.mcp_resource_watches
+            .retain(|crease_id, _| mentions.contains_key(crease_id));
+    }
+
     #[cfg(test)]
     pub fn has_thread_store(&self) -> bool {
         self.thread_store.is_some()
@@ -304,6 +327,10 @@ impl MentionSet {
                 debug_panic!("unexpected merge conflict URI");
                 Task::ready(Err(anyhow!("unexpected merge conflict URI")))
             }
+            MentionUri::McpResource { server_id, uri, .. } => {
+                self.watch_mcp_resource(crease_id, workspace, &server_id, &uri, cx);
+                self.confirm_mention_for_mcp_resource(server_id, uri, cx)
+            }
         };
         let task = cx
             .spawn(async move |_, _| task.await.map_err(|e| e.to_string()))
@@ -322,6 +349,7 @@ impl MentionSet {
                         editor.edit([(start_anchor..end_anchor, "")], cx);
                     });
                     this.mentions.remove(&crease_id);
+                    this.mcp_resource_watches.remove(&crease_id);
                 })
                 .ok();
             }
@@ -600,6 +628,112 @@ impl MentionSet {
         })
     }
 
+    fn confirm_mention_for_mcp_resource(
+        &self,
+        server_id: String,
+        uri: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Mention>> {
+        let Some(project) = self.project.upgrade() else {
+            return Task::ready(Err(anyhow!("project not found")));
+        };
+        let server_store = project.read(cx).context_server_store();
+
+        cx.spawn(async move |_, cx| {
+            let response =
+                agent::read_resource(&server_store, &ContextServerId(server_id.into()), &uri, cx)
+                    .await?;
+
+            let mut content = String::new();
+            for contents in response.contents {
+                if !content.is_empty() {
+                    content.push('\n');
+                }
+                match contents {
+                    ResourceContentsType::Text(text) => content.push_str(&text.text),
+                    ResourceContentsType::Blob(blob) => {
+                        write!(
+                            &mut content,
+                            "[binary content of type {}]",
+                            blob.mime_type.as_deref().unwrap_or("unknown")
+                        )
+                        .ok();
+                    }
+                }
+            }
+            Ok(Mention::Text {
+                content,
+                tracked_buffers: Vec::new(),
+            })
+        })
+    }
+
+    /// Subscribes to changes of a mentioned resource, so that the mention is
+    /// re-read whenever the context server reports an update. The subscription
+    /// ends once the mention is removed.
+    fn watch_mcp_resource(
+        &mut self,
+        crease_id: CreaseId,
+        workspace: &Entity<Workspace>,
+        server_id: &str,
+        uri: &str,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(registry) = workspace
+            .read(cx)
+            .panel::<AgentPanel>(cx)
+            .map(|panel| panel.read(cx).context_server_registry().clone())
+        else {
+            return;
+        };
+        if self._context_server_registry_subscription.is_none() {
+            self._context_server_registry_subscription =
+                Some(cx.subscribe(&registry, Self::handle_context_server_registry_event));
+        }
+
+        let watch = registry.update(cx, |registry, cx| {
+            registry.watch_resource(ContextServerId(server_id.into()), uri.to_string(), cx)
+        });
+        self.mcp_resource_watches.insert(crease_id, watch);
+    }
+
+    fn handle_context_server_registry_event(
+        &mut self,
+        _: Entity<ContextServerRegistry>,
+        event: &ContextServerRegistryEvent,
+        cx: &mut Context<Self>,
+    ) {
+        let ContextServerRegistryEvent::ResourceUpdated { server_id, uri } = event else {
+            return;
+        };
+
+        let updated_creases = self
+            .mentions
+            .iter()
+            .filter_map(|(crease_id, (mention_uri, _))| match mention_uri {
+                MentionUri::McpResource {
+                    server_id: mention_server_id,
+                    uri: mention_uri,
+                    ..
+                } if mention_server_id.as_str() == server_id.0.as_ref() && mention_uri == uri => {
+                    Some(*crease_id)
+                }
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for crease_id in updated_creases {
+            let task =
+                self.confirm_mention_for_mcp_resource(server_id.to_string(), uri.clone(), cx);
+            let task = cx
+                .spawn(async move |_, _| task.await.map_err(|e| e.to_string()))
+                .shared();
+            if let Some((_, mention_task)) = self.mentions.get_mut(&crease_id) {
+                *mention_task = task;
+            }
+        }
+    }
+
     pub fn confirm_mention_for_git_diff(
         &self,
         base_ref: SharedString,
@@ -80,6 +80,7 @@ impl SessionCapabilities {
                 PromptContextType::Fetch,
                 PromptContextType::Rules,
                 PromptContextType::BranchDiff,
+                PromptContextType::McpResource,
             ]);
         }
         supported
@@ -68,6 +68,7 @@ fn migrate_thread_metadata(cx: &mut App) {
                         folder_paths: entry.folder_paths,
                         main_worktree_paths: PathList::default(),
                         archived: true,
+                        forked_from: None,
                     })
                 })
                 .collect::<Vec<_>>()
@@ -129,6 +130,8 @@ pub struct ThreadMetadata {
     pub folder_paths: PathList,
     pub main_worktree_paths: PathList,
     pub archived: bool,
+    /// The thread this one was forked from, if any.
+    pub forked_from: Option<acp::SessionId>,
 }
 
 impl From<&ThreadMetadata> for acp_thread::AgentSessionInfo {
@@ -589,6 +592,21 @@ impl ThreadMetadataStore {
                     .map(|t| t.archived)
                     .unwrap_or(folder_paths.is_empty());
 
+                let forked_from =
+                    existing_thread
+                        .and_then(|t| t.forked_from.clone())
+                        .or_else(|| {
+                            let connection = thread_ref
+                                .connection()
+                                .clone()
+                                .downcast::<agent::NativeAgentConnection>()?;
+                            connection
+                                .thread(&session_id, cx)?
+                                .read(cx)
+                                .forked_from()
+                                .cloned()
+                        });
+
                 let metadata = ThreadMetadata {
                     session_id,
                     agent_id,
@@ -598,6 +616,7 @@ impl ThreadMetadataStore {
                     folder_paths,
                     main_worktree_paths,
                     archived,
+                    forked_from,
                 };
 
                 self.save(metadata, cx);
@@ -634,6 +653,7 @@ impl Domain for ThreadMetadataDb {
         sql!(ALTER TABLE sidebar_threads ADD COLUMN archived INTEGER DEFAULT 0),
         sql!(ALTER TABLE sidebar_threads ADD COLUMN main_worktree_paths TEXT),
         sql!(ALTER TABLE sidebar_threads ADD COLUMN main_worktree_paths_order TEXT),
+        sql!(ALTER TABLE sidebar_threads ADD COLUMN forked_from TEXT),
     ];
 }
 
@@ -650,7 +670,7 @@ impl ThreadMetadataDb {
     /// List all sidebar thread metadata, ordered by updated_at descending.
     pub fn list(&self) -> anyhow::Result<Vec<ThreadMetadata>> {
         self.select::<ThreadMetadata>(
-            "SELECT session_id, agent_id, title, updated_at, created_at, folder_paths, folder_paths_order, archived, main_worktree_paths, main_worktree_paths_order \
+            "SELECT session_id, agent_id, title, updated_at, created_at, folder_paths, folder_paths_order, archived, main_worktree_paths, main_worktree_paths_order, forked_from \
              FROM sidebar_threads \
              ORDER BY updated_at DESC"
         )?()
@@ -681,10 +701,11 @@ impl ThreadMetadataDb {
             (Some(main_serialized.paths), Some(main_serialized.order))
         };
         let archived = row.archived;
+        let forked_from = row.forked_from.map(|id| id.0);
 
         self.write(move |conn| {
-            let sql = "INSERT INTO sidebar_threads(session_id, agent_id, title, updated_at, created_at, folder_paths, folder_paths_order, archived, main_worktree_paths, main_worktree_paths_order) \
-                       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10) \
+            let sql = "INSERT INTO sidebar_threads(session_id, agent_id, title, updated_at, created_at, folder_paths, folder_paths_order, archived, main_worktree_paths, main_worktree_paths_order, forked_from) \
+                       VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11) \
                        ON CONFLICT(session_id) DO UPDATE SET \
                            agent_id = excluded.agent_id, \
                            title = excluded.title, \
@@ -694,7 +715,8 @@ impl ThreadMetadataDb {
                            folder_paths_order = excluded.folder_paths_order, \
                            archived = excluded.archived, \
                            main_worktree_paths = excluded.main_worktree_paths, \
-                           main_worktree_paths_order = excluded.main_worktree_paths_order";
+                           main_worktree_paths_order = excluded.main_worktree_paths_order, \
+                           forked_from = excluded.forked_from";
             let mut stmt = Statement::prepare(conn, sql)?;
             let mut i = stmt.bind(&id, 1)?;
             i = stmt.bind(&agent_id, i)?;
@@ -705,7 +727,8 @@ impl ThreadMetadataDb {
             i = stmt.bind(&folder_paths_order, i)?;
             i = stmt.bind(&archived, i)?;
             i = stmt.bind(&main_worktree_paths, i)?;
-            stmt.bind(&main_worktree_paths_order, i)?;
+            i = stmt.bind(&main_worktree_paths_order, i)?;
+            stmt.bind(&forked_from, i)?;
             stmt.exec()
         })
         .await
@@ -739,6 +762,7 @@ impl Column for ThreadMetadata {
             Column::column(statement, next)?;
         let (main_worktree_paths_order_str, next): (Option<String>, i32) =
             Column::column(statement, next)?;
+        let (forked_from, next): (Option<Arc<str>>, i32) = Column::column(statement, next)?;
 
         let agent_id = agent_id
             .map(|id| AgentId::new(id))
@@ -779,6 +803,7 @@ impl Column for ThreadMetadata {
                 folder_paths,
                 main_worktree_paths,
                 archived,
+                forked_from: forked_from.map(acp::SessionId::new),
             },
             next,
         ))
@@ -804,6 +829,7 @@ mod tests {
             title: title.to_string().into(),
             messages: Vec::new(),
             updated_at,
+            compaction: None,
             detailed_summary: None,
             initial_project_snapshot: None,
             cumulative_token_usage: Default::default(),
@@ -812,6 +838,7 @@ mod tests {
             profile: None,
             imported: false,
             subagent_context: None,
+            forked_from: None,
             speed: None,
             thinking_enabled: false,
             thinking_effort: None,
@@ -828,6 +855,7 @@ mod tests {
     ) -> ThreadMetadata {
         ThreadMetadata {
             archived: false,
+            forked_from: None,
             session_id: acp::SessionId::new(session_id),
             agent_id: agent::ZED_AGENT_ID.clone(),
             title: title.to_string().into(),
@@ -1052,6 +1080,7 @@ mod tests {
             folder_paths: project_a_paths.clone(),
             main_worktree_paths: PathList::default(),
             archived: false,
+            forked_from: None,
         };
 
         cx.update(|cx| {
@@ -1162,6 +1191,7 @@ mod tests {
             folder_paths: project_paths.clone(),
             main_worktree_paths: PathList::default(),
             archived: false,
+            forked_from: None,
         };
 
         cx.update(|cx| {
@@ -0,0 +1,233 @@
+use agent::{DbUsageRecord, UsageLedger, UsageLedgerEvent, UsageSummary, UsageTotals};
+use agent_settings::AgentSettings;
+use gpui::{Entity, EventEmitter, FocusHandle, Focusable, Task};
+use settings::{Settings as _, SettingsStore};
+use ui::{Table, prelude::*};
+use util::ResultExt as _;
+use workspace::{
+    Workspace,
+    item::{Item, ItemEvent},
+};
+
+use crate::humanize_token_count;
+
+/// Shows the tokens the agent has used and what they're estimated to have
+/// cost, broken down by day, project, thread and model.
+pub struct UsageDashboard {
+    focus_handle: FocusHandle,
+    records: Option<Vec<DbUsageRecord>>,
+    summary: UsageSummary,
+    _load_task: Task<()>,
+    _subscriptions: Vec<gpui::Subscription>,
+}
+
+impl UsageDashboard {
+    pub fn new(
+        _workspace: &Workspace,
+        _window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        cx.new(|cx| {
+            let ledger = UsageLedger::global(cx);
+            let subscriptions = vec![
+                cx.subscribe(&ledger, |this, _, event, cx| {
+                    if let UsageLedgerEvent::Recorded = event {
+                        this.reload(cx);
+                    }
+                }),
+                // Costs are estimated from the current prices.
+                cx.observe_global::<SettingsStore>(|this, cx| this.summarize(cx)),
+            ];
+
+            let mut this = Self {
+                focus_handle: cx.focus_handle(),
+                records: None,
+                summary: UsageSummary::default(),
+                _load_task: Task::ready(()),
+                _subscriptions: subscriptions,
+            };
+            this.reload(cx);
+            this
+        })
+    }
+
+    fn reload(&mut self, cx: &mut Context<Self>) {
+        let usage = UsageLedger::global(cx).update(cx, |ledger, cx| ledger.usage(None, cx));
+        self._load_task = cx.spawn(async move |this, cx| {
+            let Some(records) = usage.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, cx| {
+                this.records = Some(records);
+                this.summarize(cx);
+            })
+            .ok();
+        });
+    }
+
+    fn summarize(&mut self, cx: &mut Context<Self>) {
+        let Some(records) = &self.records else {
+            return;
+        };
+        let summary = UsageSummary::new(records, &AgentSettings::get_global(cx).usage);
+        if summary != self.summary {
+            self.summary = summary;
+            cx.notify();
+        }
+    }
+
+    fn render_section(
+        &self,
+        title: &'static str,
+        first_column: &'static str,
+        rows: &[UsageTotals],
+    ) -> impl IntoElement {
+        let table = rows.iter().fold(
+            Table::new(5).width(relative(1.)).striped().header(vec![
+                first_column,
+                "Requests",
+                "Input Tokens",
+                "Output Tokens",
+                "Cost",
+            ]),
+            |table, totals| table.row(usage_row(totals)),
+        );
+
+        v_flex()
+            .gap_2()
+            .child(Headline::new(title).size(HeadlineSize::Small))
+            .child(table)
+    }
+}
+
+fn usage_row(totals: &UsageTotals) -> Vec<SharedString> {
+    let input_tokens = totals.usage.input_tokens
+        + totals.usage.cache_creation_input_tokens
+        + totals.usage.cache_read_input_tokens;
+    vec![
+        totals.label.clone(),
+        totals.requests.to_string().into(),
+        humanize_token_count(input_tokens).into(),
+        humanize_token_count(totals.usage.output_tokens).into(),
+        format_cost(totals).into(),
+    ]
+}
+
+/// Formats the estimated cost, flagging requests to models without a price,
+/// as the estimate leaves them out.
+fn format_cost(totals: &UsageTotals) -> String {
+    match (totals.cost, totals.unpriced_requests) {
+        (Some(cost), 0) => format!("${cost:.2}"),
+        (Some(cost), unpriced) => format!("${cost:.2} + {unpriced} unpriced"),
+        (None, _) => "Unpriced".into(),
+    }
+}
+
+impl Render for UsageDashboard {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let total = &self.summary.total;
+        let content = if self.records.is_none() {
+            v_flex()
+                .child(Label::new("Loading usage…").color(Color::Muted))
+                .into_any_element()
+        } else if total.requests == 0 {
+            v_flex()
+                .child(Label::new("The agent hasn't used any tokens yet.").color(Color::Muted))
+                .into_any_element()
+        } else {
+            v_flex()
+                .gap_6()
+                .child(self.render_section("By Day", "Day", &self.summary.by_day))
+                .child(self.render_section("By Project", "Project", &self.summary.by_project))
+                .child(self.render_section("By Thread", "Thread", &self.summary.by_thread))
+                .child(self.render_section("By Model", "Model", &self.summary.by_model))
+                .into_any_element()
+        };
+
+        v_flex()
+            .id("agent-usage-dashboard")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .p_4()
+            .gap_4()
+            .overflow_y_scroll()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(Headline::new("Agent Usage").size(HeadlineSize::Large))
+                    .child(
+                        Label::new(format!(
+                            "{} requests, {} tokens, {} estimated",
+                            total.requests,
+                            humanize_token_count(total.usage.total_tokens()),
+                            format_cost(total),
+                        ))
+                        .color(Color::Muted),
+                    )
+                    .when(!self.summary.unpriced_models.is_empty(), |this| {
+                        this.child(
+                            h_flex()
+                                .gap_1()
+                                .child(
+                                    Icon::new(IconName::Warning)
+                                        .size(IconSize::Small)
+                                        .color(Color::Warning),
+                                )
+                                .child(
+                                    Label::new(format!(
+                                        "No price is set for {}. Their usage isn't included in \
+                                         the estimates, and dollar budgets with a hard limit \
+                                         stop requests to them.",
+                                        self.summary
+                                            .unpriced_models
+                                            .iter()
+                                            .map(|model| model.as_ref())
+                                            .collect::<Vec<_>>()
+                                            .join(", ")
+                                    ))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Warning),
+                                ),
+                        )
+                    })
+                    .child(
+                        Label::new(
+                            "Costs are estimated from the prices in the `agent.usage.pricing` \
+                             setting. Models without a price aren't included.",
+                        )
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                    ),
+            )
+            .child(content)
+    }
+}
+
+impl EventEmitter<ItemEvent> for UsageDashboard {}
+
+impl Focusable for UsageDashboard {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Item for UsageDashboard {
+    type Event = ItemEvent;
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        "Agent Usage".into()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Agent Usage Dashboard Opened")
+    }
+
+    fn show_toolbar(&self) -> bool {
+        false
+    }
+
+    fn to_item_events(event: &Self::Event, f: &mut dyn FnMut(workspace::item::ItemEvent)) {
+        f(*event)
+    }
+}
@@ -25,6 +25,7 @@ async fn test_share_and_retrieve_thread(
         updated_at: chrono::Utc::now(),
         model: None,
         version: SharedThread::VERSION.to_string(),
+        details: None,
     };
 
     let thread_data = original_thread
@@ -153,6 +154,7 @@ async fn test_sync_imported_thread(
         updated_at: chrono::Utc::now(),
         model: None,
         version: SharedThread::VERSION.to_string(),
+        details: None,
     };
 
     client_a
@@ -185,6 +187,7 @@ async fn test_sync_imported_thread(
         updated_at: chrono::Utc::now(),
         model: None,
         version: SharedThread::VERSION.to_string(),
+        details: None,
     };
 
     client_a
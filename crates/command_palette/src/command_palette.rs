@@ -140,6 +140,26 @@ impl CommandPalette {
         self.picker
             .update(cx, |picker, cx| picker.set_query(query, window, cx))
     }
+
+    pub fn query(&self, cx: &App) -> String {
+        self.picker.read(cx).query(cx)
+    }
+
+    /// Lists the queries that commands were run with, oldest first.
+    pub fn recent_queries(cx: &App) -> Vec<String> {
+        CommandPaletteDB::global(cx)
+            .list_recent_queries()
+            .log_err()
+            .unwrap_or_default()
+    }
+
+    /// Records a command run outside of the palette, so its query shows up in
+    /// the palette's history.
+    pub fn record_query(command_name: String, query: String, cx: &App) {
+        let db = CommandPaletteDB::global(cx);
+        cx.background_spawn(async move { db.write_command_invocation(command_name, query).await })
+            .detach_and_log_err(cx);
+    }
 }
 
 impl EventEmitter<DismissEvent> for CommandPalette {}
@@ -53,6 +53,7 @@ pub(crate) struct Client {
     name: Arc<str>,
     subscription_set: Arc<Mutex<NotificationSubscriptionSet>>,
     response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+    request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
     #[allow(clippy::type_complexity)]
     #[allow(dead_code)]
     io_tasks: Mutex<Option<(Task<Option<()>>, Task<Option<()>>)>>,
@@ -109,7 +110,6 @@ struct AnyResponse<'a> {
 }
 
 #[derive(Serialize, Deserialize)]
-#[allow(dead_code)]
 pub(crate) struct Response<T> {
     pub jsonrpc: &'static str,
     pub id: RequestId,
@@ -122,7 +122,6 @@ pub(crate) struct Response<T> {
 pub(crate) enum CspResult<T> {
     #[serde(rename = "result")]
     Ok(Option<T>),
-    #[allow(dead_code)]
     Error(Option<Error>),
 }
 
@@ -210,12 +209,14 @@ impl Client {
             let response_handlers = response_handlers.clone();
             let request_handlers = request_handlers.clone();
             let transport = transport.clone();
+            let outbound_tx = outbound_tx.clone();
             async move |cx| {
                 Self::handle_input(
                     transport,
                     subscription_set,
                     request_handlers,
                     response_handlers,
+                    outbound_tx,
                     cx,
                 )
                 .log_err()
@@ -249,6 +250,7 @@ impl Client {
             server_id,
             subscription_set,
             response_handlers,
+            request_handlers,
             name: server_name,
             next_id: Default::default(),
             outbound_tx,
@@ -264,14 +266,16 @@ impl Client {
     /// Handles input from the server's stdout.
     ///
     /// This function continuously reads lines from the provided stdout stream,
-    /// parses them as JSON-RPC responses or notifications, and dispatches them
-    /// to the appropriate handlers. It processes both responses (which are matched
-    /// to pending requests) and notifications (which trigger registered handlers).
+    /// parses them as JSON-RPC requests, responses or notifications, and dispatches
+    /// them to the appropriate handlers. Requests initiated by the server are routed
+    /// to handlers registered with [`Client::on_request`], and answered with a
+    /// "method not found" error when no handler exists.
     async fn handle_input(
         transport: Arc<dyn Transport>,
         subscription_set: Arc<Mutex<NotificationSubscriptionSet>>,
         request_handlers: Arc<Mutex<HashMap<&'static str, RequestHandler>>>,
         response_handlers: Arc<Mutex<Option<HashMap<RequestId, ResponseHandler>>>>,
+        outbound_tx: channel::Sender<String>,
         cx: &mut AsyncApp,
     ) -> anyhow::Result<()> {
         let mut receiver = transport.receive();
@@ -286,6 +290,15 @@ impl Client {
                         request.params.unwrap_or(RawValue::NULL),
                         cx.clone(),
                     );
+                } else {
+                    log::debug!("unhandled request from context server: {}", request.method);
+                    outbound_tx
+                        .try_send(error_response(
+                            request.id,
+                            METHOD_NOT_FOUND,
+                            format!("Method not found: {}", request.method),
+                        ))
+                        .log_err();
                 }
             } else if let Ok(response) = serde_json::from_str::<AnyResponse>(&message) {
                 if let Some(handlers) = response_handlers.lock().as_mut()
@@ -474,6 +487,46 @@ impl Client {
         Ok(())
     }
 
+    /// Registers a handler for requests initiated by the context server, such as
+    /// `sampling/createMessage`. The task returned by the handler is awaited and
+    /// its result is sent back to the server as the response.
+    pub fn on_request<P, R, F>(&self, method: &'static str, mut f: F)
+    where
+        P: DeserializeOwned,
+        R: Serialize + 'static,
+        F: 'static + Send + FnMut(P, AsyncApp) -> Task<Result<R>>,
+    {
+        let outbound_tx = self.outbound_tx.clone();
+        self.request_handlers.lock().insert(
+            method,
+            Box::new(move |id, params, cx| {
+                let task = match serde_json::from_str::<P>(params.get()) {
+                    Ok(params) => f(params, cx.clone()),
+                    Err(error) => {
+                        outbound_tx
+                            .try_send(error_response(id, INVALID_PARAMS, error.to_string()))
+                            .log_err();
+                        return;
+                    }
+                };
+                let outbound_tx = outbound_tx.clone();
+                cx.spawn(async move |_| {
+                    let response = match task.await {
+                        Ok(result) => serde_json::to_string(&Response {
+                            jsonrpc: JSON_RPC_VERSION,
+                            id,
+                            value: CspResult::Ok(Some(result)),
+                        })
+                        .unwrap(),
+                        Err(error) => error_response(id, INTERNAL_ERROR, format!("{error:#}")),
+                    };
+                    outbound_tx.try_send(response).log_err();
+                })
+                .detach();
+            }),
+        );
+    }
+
     #[must_use]
     pub fn on_notification(
         &self,
@@ -489,6 +542,15 @@ impl Client {
     }
 }
 
+fn error_response(id: RequestId, code: i32, message: String) -> String {
+    serde_json::to_string(&Response::<()> {
+        jsonrpc: JSON_RPC_VERSION,
+        id,
+        value: CspResult::Error(Some(Error { message, code })),
+    })
+    .unwrap()
+}
+
 #[derive(Debug)]
 pub struct RequestCanceled;
 
@@ -584,3 +646,86 @@ impl Drop for NotificationSubscription {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use futures::{Stream, channel::mpsc};
+    use gpui::TestAppContext;
+    use serde_json::json;
+    use std::pin::Pin;
+
+    struct ChannelTransport {
+        incoming: Mutex<Option<mpsc::UnboundedReceiver<String>>>,
+        outgoing: mpsc::UnboundedSender<String>,
+    }
+
+    #[async_trait]
+    impl Transport for ChannelTransport {
+        async fn send(&self, message: String) -> Result<()> {
+            self.outgoing.unbounded_send(message)?;
+            Ok(())
+        }
+
+        fn receive(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+            Box::pin(self.incoming.lock().take().expect("receive called twice"))
+        }
+
+        fn receive_err(&self) -> Pin<Box<dyn Stream<Item = String> + Send>> {
+            Box::pin(futures::stream::empty())
+        }
+    }
+
+    #[gpui::test]
+    async fn test_server_initiated_requests(cx: &mut TestAppContext) {
+        let (server_tx, client_rx) = mpsc::unbounded();
+        let (client_tx, mut server_rx) = mpsc::unbounded();
+        let transport = Arc::new(ChannelTransport {
+            incoming: Mutex::new(Some(client_rx)),
+            outgoing: client_tx,
+        });
+        let client = Client::new(
+            ContextServerId("test".into()),
+            "test".into(),
+            transport,
+            None,
+            cx.to_async(),
+        )
+        .unwrap();
+        client.on_request::<Value, Value, _>("echo", |params, _| Task::ready(Ok(params)));
+        client.on_request::<Value, Value, _>("fail", |_, _| {
+            Task::ready(Err(anyhow!("something went wrong")))
+        });
+
+        server_tx
+            .unbounded_send(
+                json!({"jsonrpc": "2.0", "id": 1, "method": "echo", "params": {"text": "hi"}})
+                    .to_string(),
+            )
+            .unwrap();
+        cx.run_until_parked();
+        let response: Value = serde_json::from_str(&server_rx.next().await.unwrap()).unwrap();
+        assert_eq!(
+            response,
+            json!({"jsonrpc": "2.0", "id": 1, "result": {"text": "hi"}})
+        );
+
+        server_tx
+            .unbounded_send(json!({"jsonrpc": "2.0", "id": 2, "method": "fail"}).to_string())
+            .unwrap();
+        cx.run_until_parked();
+        let response: Value = serde_json::from_str(&server_rx.next().await.unwrap()).unwrap();
+        assert_eq!(response["id"], 2);
+        assert_eq!(response["error"]["code"], INTERNAL_ERROR);
+        assert_eq!(response["error"]["message"], "something went wrong");
+
+        server_tx
+            .unbounded_send(json!({"jsonrpc": "2.0", "id": "abc", "method": "unknown"}).to_string())
+            .unwrap();
+        cx.run_until_parked();
+        let response: Value = serde_json::from_str(&server_rx.next().await.unwrap()).unwrap();
+        assert_eq!(response["id"], "abc");
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+    }
+}
@@ -6,6 +6,7 @@ pub mod protocol;
 pub mod test;
 pub mod transport;
 pub mod types;
+pub mod uri_template;
 
 use collections::HashMap;
 use http_client::HttpClient;
@@ -16,7 +17,7 @@ use std::{fmt::Display, path::PathBuf};
 
 use anyhow::Result;
 use client::Client;
-use gpui::AsyncApp;
+use gpui::{App, AsyncApp, Global, Task};
 use parking_lot::RwLock;
 pub use settings::ContextServerCommand;
 use url::Url;
@@ -32,6 +33,45 @@ impl Display for ContextServerId {
     }
 }
 
+/// Handles requests that context servers send back to Zed.
+///
+/// When a delegate is registered, Zed advertises the `sampling` and
+/// `elicitation` client capabilities to every context server it starts.
+pub trait ContextServerClientDelegate: 'static + Send + Sync {
+    /// Requests a model completion on behalf of the server (`sampling/createMessage`).
+    fn create_message(
+        &self,
+        server_id: ContextServerId,
+        params: types::CreateMessageRequest,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<types::CreateMessageResult>>;
+
+    /// Asks the user to fill in a form described by the server (`elicitation/create`).
+    fn elicit(
+        &self,
+        server_id: ContextServerId,
+        params: types::ElicitRequestParams,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<types::ElicitResult>>;
+}
+
+struct GlobalContextServerClientDelegate(Arc<dyn ContextServerClientDelegate>);
+
+impl Global for GlobalContextServerClientDelegate {}
+
+impl dyn ContextServerClientDelegate {
+    /// Returns the global [`ContextServerClientDelegate`], if one has been registered.
+    pub fn try_global(cx: &App) -> Option<Arc<Self>> {
+        cx.try_global::<GlobalContextServerClientDelegate>()
+            .map(|delegate| delegate.0.clone())
+    }
+
+    /// Sets the global [`ContextServerClientDelegate`].
+    pub fn set_global(delegate: Arc<Self>, cx: &mut App) {
+        cx.set_global(GlobalContextServerClientDelegate(delegate));
+    }
+}
+
 enum ContextServerTransport {
     Stdio(ContextServerCommand, Option<PathBuf>),
     Custom(Arc<dyn crate::transport::Transport>),
@@ -107,7 +147,7 @@ impl ContextServer {
     }
 
     pub async fn start(&self, cx: &AsyncApp) -> Result<()> {
-        self.initialize(self.new_client(cx)?).await
+        self.initialize(self.new_client(cx)?, cx).await
     }
 
     fn new_client(&self, cx: &AsyncApp) -> Result<Client> {
@@ -133,14 +173,33 @@ impl ContextServer {
         })
     }
 
-    async fn initialize(&self, client: Client) -> Result<()> {
+    async fn initialize(&self, client: Client, cx: &AsyncApp) -> Result<()> {
         log::debug!("starting context server {}", self.id);
         let protocol = crate::protocol::ModelContextProtocol::new(client);
         let client_info = types::Implementation {
             name: "Zed".to_string(),
             version: env!("CARGO_PKG_VERSION").to_string(),
         };
-        let initialized_protocol = protocol.initialize(client_info).await?;
+        let mut capabilities = types::ClientCapabilities {
+            experimental: None,
+            sampling: None,
+            elicitation: None,
+            roots: None,
+        };
+        if let Some(delegate) = cx.update(|cx| <dyn ContextServerClientDelegate>::try_global(cx)) {
+            capabilities.sampling = Some(serde_json::json!({}));
+            capabilities.elicitation = Some(serde_json::json!({}));
+            protocol.on_request::<types::requests::CreateMessage>({
+                let delegate = delegate.clone();
+                let server_id = self.id.clone();
+                move |params, mut cx| delegate.create_message(server_id.clone(), params, &mut cx)
+            });
+            protocol.on_request::<types::requests::ElicitationCreate>({
+                let server_id = self.id.clone();
+                move |params, mut cx| delegate.elicit(server_id.clone(), params, &mut cx)
+            });
+        }
+        let initialized_protocol = protocol.initialize(client_info, capabilities).await?;
 
         log::debug!(
             "context server {} initialized: {:?}",
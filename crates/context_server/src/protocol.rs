@@ -9,7 +9,7 @@ use std::time::Duration;
 
 use anyhow::Result;
 use futures::channel::oneshot;
-use gpui::AsyncApp;
+use gpui::{AsyncApp, Task};
 use serde_json::Value;
 
 use crate::client::{Client, NotificationSubscription};
@@ -31,17 +31,25 @@ impl ModelContextProtocol {
         ]
     }
 
+    /// Registers a handler for a request the server may send to the client.
+    ///
+    /// Handlers should be registered before calling [`Self::initialize`], so that
+    /// requests sent by the server right after initialization are not dropped.
+    pub fn on_request<T: Request>(
+        &self,
+        f: impl 'static + Send + FnMut(T::Params, AsyncApp) -> Task<Result<T::Response>>,
+    ) {
+        self.inner.on_request(T::METHOD, f)
+    }
+
     pub async fn initialize(
         self,
         client_info: types::Implementation,
+        capabilities: types::ClientCapabilities,
     ) -> Result<InitializedContextServerProtocol> {
         let params = types::InitializeParams {
             protocol_version: types::ProtocolVersion(types::LATEST_PROTOCOL_VERSION.to_string()),
-            capabilities: types::ClientCapabilities {
-                experimental: None,
-                sampling: None,
-                roots: None,
-            },
+            capabilities,
             meta: None,
             client_info,
         };
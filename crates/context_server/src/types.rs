@@ -1,4 +1,5 @@
 use collections::HashMap;
+use indexmap::IndexMap;
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use url::Url;
@@ -34,13 +35,13 @@ pub mod requests {
         "resources/unsubscribe",
         ResourcesUnsubscribe,
         ResourcesUnsubscribeParams,
-        ()
+        EmptyResult
     );
     request!(
         "resources/subscribe",
         ResourcesSubscribe,
         ResourcesSubscribeParams,
-        ()
+        EmptyResult
     );
     request!(
         "resources/read",
@@ -77,6 +78,18 @@ pub mod requests {
         ListResourceTemplatesResponse
     );
     request!("roots/list", ListRoots, (), ListRootsResponse);
+    request!(
+        "sampling/createMessage",
+        CreateMessage,
+        CreateMessageRequest,
+        CreateMessageResult
+    );
+    request!(
+        "elicitation/create",
+        ElicitationCreate,
+        ElicitRequestParams,
+        ElicitResult
+    );
 }
 
 pub trait Request {
@@ -177,6 +190,13 @@ pub struct ResourcesSubscribeParams {
     pub meta: Option<HashMap<String, serde_json::Value>>,
 }
 
+/// The result of requests that return no data other than metadata.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EmptyResult {
+    #[serde(rename = "_meta", skip_serializing_if = "Option::is_none")]
+    pub meta: Option<HashMap<String, serde_json::Value>>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ResourcesReadParams {
@@ -313,7 +333,7 @@ pub struct CreateMessageRequest {
     pub metadata: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateMessageResult {
     pub role: Role,
@@ -323,6 +343,113 @@ pub struct CreateMessageResult {
     pub stop_reason: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitRequestParams {
+    pub message: String,
+    pub requested_schema: ElicitationSchema,
+}
+
+/// A restricted JSON schema describing the form a server wants the user to
+/// fill in. Only flat objects with primitive properties are allowed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitationSchema {
+    pub properties: IndexMap<String, PrimitiveSchemaDefinition>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum PrimitiveSchemaDefinition {
+    String(StringSchema),
+    Number(NumberSchema),
+    Integer(NumberSchema),
+    Boolean(BooleanSchema),
+}
+
+impl PrimitiveSchemaDefinition {
+    pub fn title(&self) -> Option<&str> {
+        match self {
+            PrimitiveSchemaDefinition::String(schema) => schema.title.as_deref(),
+            PrimitiveSchemaDefinition::Number(schema)
+            | PrimitiveSchemaDefinition::Integer(schema) => schema.title.as_deref(),
+            PrimitiveSchemaDefinition::Boolean(schema) => schema.title.as_deref(),
+        }
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        match self {
+            PrimitiveSchemaDefinition::String(schema) => schema.description.as_deref(),
+            PrimitiveSchemaDefinition::Number(schema)
+            | PrimitiveSchemaDefinition::Integer(schema) => schema.description.as_deref(),
+            PrimitiveSchemaDefinition::Boolean(schema) => schema.description.as_deref(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StringSchema {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// When present, the value must be one of these options.
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<String>>,
+    /// Display names for each entry in `options`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enum_names: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberSchema {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minimum: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BooleanSchema {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitResult {
+    pub action: ElicitAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<serde_json::Map<String, serde_json::Value>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PromptMessage {
@@ -445,6 +572,8 @@ pub struct ClientCapabilities {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sampling: Option<serde_json::Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub elicitation: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub roots: Option<RootsCapabilities>,
 }
 
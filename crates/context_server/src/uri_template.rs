@@ -0,0 +1,119 @@
+//! Minimal support for the RFC 6570 URI templates used by MCP resource templates.
+//!
+//! Only simple (`{var}`) and reserved (`{+var}`) expressions with a single
+//! variable are supported, which covers the templates context servers advertise
+//! in practice.
+
+use collections::HashMap;
+
+/// Returns the names of the variables referenced by `template`, in order of appearance.
+pub fn template_variables(template: &str) -> Vec<String> {
+    let mut variables = Vec::new();
+    for part in parse(template) {
+        if let Part::Variable { name, .. } = part
+            && !variables.iter().any(|variable| variable == name)
+        {
+            variables.push(name.to_string());
+        }
+    }
+    variables
+}
+
+/// Matches `uri` against `template`, returning the value of every variable if
+/// the URI could have been produced by expanding the template.
+///
+/// Simple expressions don't match across `/`, mirroring how they would have been
+/// percent-encoded during expansion.
+pub fn match_uri_template(template: &str, uri: &str) -> Option<HashMap<String, String>> {
+    let parts = parse(template);
+    let mut values = HashMap::default();
+    let mut rest = uri;
+    for (ix, part) in parts.iter().enumerate() {
+        match part {
+            Part::Literal(literal) => rest = rest.strip_prefix(literal)?,
+            Part::Variable { name, reserved } => {
+                let end = match parts.get(ix + 1) {
+                    Some(Part::Literal(next)) => rest.find(next)?,
+                    _ => rest.len(),
+                };
+                let value = &rest[..end];
+                if value.is_empty() || (!reserved && value.contains('/')) {
+                    return None;
+                }
+                if let Some(existing) = values.get(*name)
+                    && existing != value
+                {
+                    return None;
+                }
+                values.insert(name.to_string(), value.to_string());
+                rest = &rest[end..];
+            }
+        }
+    }
+    rest.is_empty().then_some(values)
+}
+
+enum Part<'a> {
+    Literal(&'a str),
+    Variable { name: &'a str, reserved: bool },
+}
+
+fn parse(template: &str) -> Vec<Part<'_>> {
+    let mut parts = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        if start > 0 {
+            parts.push(Part::Literal(&rest[..start]));
+        }
+        let expression = &rest[start + 1..end];
+        let (name, reserved) = match expression.strip_prefix('+') {
+            Some(name) => (name, true),
+            None => (expression, false),
+        };
+        parts.push(Part::Variable {
+            name: name.trim(),
+            reserved,
+        });
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Literal(rest));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_variables() {
+        assert_eq!(
+            template_variables("repo://{owner}/{repo}/blob/{+path}"),
+            vec!["owner", "repo", "path"]
+        );
+        assert_eq!(template_variables("db://{table}/{table}"), vec!["table"]);
+        assert!(template_variables("file:///etc/hosts").is_empty());
+    }
+
+    #[test]
+    fn test_match_uri_template() {
+        let values = match_uri_template(
+            "repo://{owner}/{repo}/blob/{+path}",
+            "repo://zed-industries/zed/blob/crates/gpui/src/app.rs",
+        )
+        .unwrap();
+        assert_eq!(values["owner"], "zed-industries");
+        assert_eq!(values["repo"], "zed");
+        assert_eq!(values["path"], "crates/gpui/src/app.rs");
+
+        assert!(match_uri_template("repo://{owner}/{repo}", "repo://zed-industries").is_none());
+        assert!(match_uri_template("repo://{owner}", "repo://zed-industries/zed").is_none());
+        assert!(match_uri_template("repo://{owner}", "file://zed-industries").is_none());
+        assert!(match_uri_template("db://{table}/{table}", "db://users/posts").is_none());
+        assert!(match_uri_template("db://{table}/{table}", "db://users/users").is_some());
+    }
+}
@@ -353,6 +353,30 @@ pub struct AddSelectionBelow {
     pub skip_soft_wrap: bool,
 }
 
+/// Moves to an older state of the buffer, in the order the states were made,
+/// across every branch of the undo tree.
+#[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = editor)]
+#[serde(deny_unknown_fields)]
+pub struct Earlier {
+    /// The number of states to move back by, one if unset.
+    pub steps: Option<usize>,
+    /// Move back by an amount of time instead, in seconds.
+    pub seconds: Option<u64>,
+}
+
+/// Moves to a newer state of the buffer, in the order the states were made,
+/// across every branch of the undo tree.
+#[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
+#[action(namespace = editor)]
+#[serde(deny_unknown_fields)]
+pub struct Later {
+    /// The number of states to move forward by, one if unset.
+    pub steps: Option<usize>,
+    /// Move forward by an amount of time instead, in seconds.
+    pub seconds: Option<u64>,
+}
+
 /// Inserts a snippet at the cursor.
 #[derive(PartialEq, Clone, Default, Debug, Deserialize, JsonSchema, Action)]
 #[action(namespace = editor)]
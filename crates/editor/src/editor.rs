@@ -154,7 +154,7 @@ use multi_buffer::{
     MultiBufferRow,
 };
 use parking_lot::Mutex;
-use persistence::EditorDb;
+use persistence::{EditorDb, SerializedUndoHistory, content_hash};
 use project::{
     BreakpointWithPosition, CodeAction, Completion, CompletionDisplayOptions, CompletionIntent,
     CompletionResponse, CompletionSource, DisableAiSettings, DocumentHighlight, InlayHint, InlayId,
@@ -247,6 +247,8 @@ const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
 const MAX_LINE_LEN: usize = 1024;
 const MIN_NAVIGATION_HISTORY_ROW_DELTA: i64 = 10;
 const MAX_SELECTION_HISTORY_LEN: usize = 1024;
+const MAX_PERSISTED_UNDO_TRANSACTIONS: usize = 1000;
+const MAX_PERSISTED_UNDO_HISTORY_LEN: usize = 1024 * 1024;
 pub(crate) const CURSORS_VISIBLE_FOR: Duration = Duration::from_millis(2000);
 #[doc(hidden)]
 pub const CODE_ACTIONS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(250);
@@ -1321,6 +1323,7 @@ pub struct Editor {
     _scroll_cursor_center_top_bottom_task: Task<()>,
     serialize_selections: Task<()>,
     serialize_folds: Task<()>,
+    serialize_undo_history: Task<()>,
     mouse_cursor_hidden: bool,
     minimap: Option<Entity<Self>>,
     hide_mouse_mode: HideMouseMode,
@@ -2577,6 +2580,7 @@ impl Editor {
             toggle_fold_multiple_buffers: Task::ready(()),
             serialize_selections: Task::ready(()),
             serialize_folds: Task::ready(()),
+            serialize_undo_history: Task::ready(()),
             text_style_refinement: None,
             load_diff_task: load_uncommitted_diff,
             temporary_diff_override: false,
@@ -3936,6 +3940,92 @@ impl Editor {
         });
     }
 
+    /// Persists the undo history of the file once it matches the file on disk,
+    /// so that it can be restored the next time the file is opened unchanged.
+    fn undo_history_did_change(&mut self, cx: &mut Context<Self>) {
+        if self.mode.is_minimap() || !ProjectSettings::get_global(cx).session.restore_undo_history {
+            return;
+        }
+        let Some(workspace_id) = self.workspace_serialization_id(cx) else {
+            return;
+        };
+        if self.buffer().read(cx).as_singleton().is_none() {
+            return;
+        }
+
+        let db = EditorDb::global(cx);
+        self.serialize_undo_history = cx.spawn(async move |editor, cx| {
+            cx.background_executor()
+                .timer(SERIALIZATION_THROTTLE_TIME)
+                .await;
+            let Some((file_path, mtime, history, snapshot)) = editor
+                .update(cx, |editor, cx| {
+                    let buffer = editor.buffer().read(cx).as_singleton()?;
+                    let buffer = buffer.read(cx);
+                    let file = project::File::from_dyn(buffer.file())?;
+                    let file_path = Arc::<Path>::from(file.abs_path(cx));
+                    if buffer.is_dirty() {
+                        return None;
+                    }
+                    // Files that are too large, or gone from disk, keep no history.
+                    let history = if buffer.len() > MAX_PERSISTED_UNDO_HISTORY_LEN
+                        || buffer
+                            .file()
+                            .is_some_and(|file| file.disk_state().is_deleted())
+                    {
+                        None
+                    } else {
+                        buffer.history_snapshot()
+                    };
+                    Some((
+                        file_path,
+                        buffer.saved_mtime(),
+                        history,
+                        buffer.text_snapshot(),
+                    ))
+                })
+                .ok()
+                .flatten()
+            else {
+                return;
+            };
+
+            cx.background_spawn(async move {
+                let history = history
+                    .map(|history| {
+                        history.serialize(
+                            MAX_PERSISTED_UNDO_TRANSACTIONS,
+                            MAX_PERSISTED_UNDO_HISTORY_LEN,
+                        )
+                    })
+                    .filter(|history| !history.is_empty());
+                if let Some(mtime) = mtime
+                    && let Some(history) = history
+                {
+                    let undo_history = SerializedUndoHistory {
+                        mtime,
+                        content_hash: content_hash(snapshot.as_rope()),
+                        history,
+                    };
+                    db.save_file_undo_history(workspace_id, file_path, undo_history)
+                        .await
+                        .with_context(|| {
+                            format!("persisting undo history for workspace {workspace_id:?}")
+                        })
+                        .log_err();
+                } else {
+                    db.delete_file_undo_history(workspace_id, file_path)
+                        .await
+                        .with_context(|| {
+                            format!("deleting undo history for workspace {workspace_id:?}")
+                        })
+                        .log_err();
+                }
+            })
+            .await;
+        });
+    }
+
     pub fn sync_selections(
         &mut self,
         other: Entity<Editor>,
@@ -14338,6 +14428,70 @@ impl Editor {
         }
     }
 
+    pub fn earlier(&mut self, action: &Earlier, window: &mut Window, cx: &mut Context<Self>) {
+        self.step_through_undo_tree(action.steps, action.seconds, false, window, cx);
+    }
+
+    pub fn later(&mut self, action: &Later, window: &mut Window, cx: &mut Context<Self>) {
+        self.step_through_undo_tree(action.steps, action.seconds, true, window, cx);
+    }
+
+    fn step_through_undo_tree(
+        &mut self,
+        steps: Option<usize>,
+        seconds: Option<u64>,
+        forward: bool,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+        let buffer = buffer.read(cx);
+        let target = if let Some(seconds) = seconds {
+            buffer.undo_tree_step_by_time(Duration::from_secs(seconds), forward)
+        } else {
+            let steps = steps.unwrap_or(1) as isize;
+            buffer.undo_tree_step(if forward { steps } else { -steps })
+        };
+        self.navigate_undo_tree(target, window, cx);
+    }
+
+    /// Moves a singleton buffer to the given node of its undo tree, or to its
+    /// original text if `target` is `None`.
+    pub fn navigate_undo_tree(
+        &mut self,
+        target: Option<TransactionId>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.read_only(cx) {
+            return;
+        }
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return;
+        };
+
+        self.hide_mouse_cursor(HideMouseCursorOrigin::TypingAction, cx);
+
+        if buffer.update(cx, |buffer, cx| buffer.navigate_undo_tree(target, cx)) {
+            if let Some(transaction_id) = target
+                && let Some((_, Some(selections))) =
+                    self.selection_history.transaction(transaction_id).cloned()
+            {
+                self.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                    s.select_anchors(selections.to_vec());
+                });
+            }
+            self.request_autoscroll(Autoscroll::fit(), cx);
+            self.unmark_text(window, cx);
+            self.refresh_edit_prediction(true, false, window, cx);
+            if let Some(transaction_id) = target {
+                cx.emit(EditorEvent::Edited { transaction_id });
+            }
+        }
+    }
+
     pub fn redo(&mut self, _: &Redo, window: &mut Window, cx: &mut Context<Self>) {
         if self.read_only(cx) {
             return;
@@ -24362,11 +24516,23 @@ impl Editor {
                 self.update_edit_prediction_settings(cx);
                 cx.notify();
             }
-            multi_buffer::Event::DirtyChanged => cx.emit(EditorEvent::DirtyChanged),
-            multi_buffer::Event::Saved => cx.emit(EditorEvent::Saved),
-            multi_buffer::Event::FileHandleChanged
-            | multi_buffer::Event::Reloaded
-            | multi_buffer::Event::BufferDiffChanged => cx.emit(EditorEvent::TitleChanged),
+            multi_buffer::Event::DirtyChanged => {
+                self.undo_history_did_change(cx);
+                cx.emit(EditorEvent::DirtyChanged)
+            }
+            multi_buffer::Event::Saved => {
+                self.undo_history_did_change(cx);
+                cx.emit(EditorEvent::Saved)
+            }
+            multi_buffer::Event::Reloaded => {
+                self.undo_history_did_change(cx);
+                cx.emit(EditorEvent::TitleChanged)
+            }
+            multi_buffer::Event::FileHandleChanged => {
+                self.undo_history_did_change(cx);
+                cx.emit(EditorEvent::TitleChanged)
+            }
+            multi_buffer::Event::BufferDiffChanged => cx.emit(EditorEvent::TitleChanged),
             multi_buffer::Event::DiagnosticsUpdated => {
                 self.update_diagnostics_state(window, cx);
             }
@@ -25616,6 +25782,55 @@ impl Editor {
         self.read_scroll_position_from_db(item_id, workspace_id, window, cx);
     }
 
+    /// Restores the undo history persisted for the file, as long as the file
+    /// wasn't changed on disk since, and the buffer has no history of its own.
+    fn load_undo_history_from_db(
+        &mut self,
+        workspace_id: WorkspaceId,
+        file_path: PathBuf,
+        cx: &mut Context<Editor>,
+    ) {
+        if self.mode.is_minimap() || !ProjectSettings::get_global(cx).session.restore_undo_history {
+            return;
+        }
+        let Some(buffer) = self.buffer().read(cx).as_singleton() else {
+            return;
+        };
+        let db = EditorDb::global(cx);
+        let Some(undo_history) = db
+            .get_file_undo_history(workspace_id, &file_path)
+            .log_err()
+            .flatten()
+        else {
+            return;
+        };
+
+        let restored = buffer.update(cx, |buffer, cx| {
+            // Leave the history in place for when the buffer matches the file again.
+            if buffer.is_dirty()
+                || buffer.peek_undo_stack().is_some()
+                || buffer.peek_redo_stack().is_some()
+            {
+                return true;
+            }
+            buffer.saved_mtime() == Some(undo_history.mtime)
+                && content_hash(buffer.as_rope()) == undo_history.content_hash
+                && buffer.restore_history(&undo_history.history, cx)
+        });
+        if !restored {
+            let file_path = Arc::from(file_path);
+            cx.background_spawn(async move {
+                db.delete_file_undo_history(workspace_id, file_path)
+                    .await
+                    .with_context(|| {
+                        format!("discarding stale undo history for workspace {workspace_id:?}")
+                    })
+                    .log_err();
+            })
+            .detach();
+        }
+    }
+
     /// Load folds from the file_folds database table by file path.
     /// Used when manually opening a file that was previously closed.
     fn load_folds_from_db(
@@ -320,6 +320,40 @@ fn test_undo_redo_with_selection_restoration(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_earlier_and_later(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state("abcˇ");
+    cx.simulate_input("1");
+    cx.simulate_input("2");
+    cx.update_editor(|editor, window, cx| editor.undo(&Undo, window, cx));
+    cx.simulate_input("3");
+    assert_eq!(cx.buffer_text(), "abc13");
+
+    // Earlier states include the ones undone before typing "3".
+    cx.update_editor(|editor, window, cx| editor.earlier(&Earlier::default(), window, cx));
+    assert_eq!(cx.buffer_text(), "abc12");
+    cx.update_editor(|editor, window, cx| editor.earlier(&Earlier::default(), window, cx));
+    assert_eq!(cx.buffer_text(), "abc1");
+    cx.update_editor(|editor, window, cx| {
+        editor.later(
+            &Later {
+                steps: Some(2),
+                seconds: None,
+            },
+            window,
+            cx,
+        )
+    });
+    assert_eq!(cx.buffer_text(), "abc13");
+
+    // Undoing still follows the branch the buffer is on.
+    cx.update_editor(|editor, window, cx| editor.undo(&Undo, window, cx));
+    assert_eq!(cx.buffer_text(), "abc1");
+}
+
 #[gpui::test]
 fn test_accessibility_keyboard_word_completion(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -305,6 +305,8 @@ impl EditorElement {
         register_action(editor, window, Editor::paste);
         register_action(editor, window, Editor::undo);
         register_action(editor, window, Editor::redo);
+        register_action(editor, window, Editor::earlier);
+        register_action(editor, window, Editor::later);
         register_action(editor, window, Editor::move_page_up);
         register_action(editor, window, Editor::move_page_down);
         register_action(editor, window, Editor::next_screen);
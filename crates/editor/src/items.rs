@@ -1,7 +1,7 @@
 use crate::{
     ActiveDebugLine, Anchor, Autoscroll, BufferSerialization, Capability, Editor, EditorEvent,
     EditorSettings, ExcerptRange, FormatTarget, MultiBuffer, MultiBufferSnapshot, NavigationData,
-    ReportEditorEvent, SelectionEffects, ToPoint as _,
+    ReportEditorEvent, SelectionEffects, ToOffset as _, ToPoint as _,
     display_map::HighlightKey,
     editor_settings::SeedQuerySetting,
     persistence::{EditorDb, SerializedEditor},
@@ -60,6 +60,7 @@ use zed_actions::preview::{
 };
 
 pub const MAX_TAB_TITLE_LEN: usize = 24;
+const MAX_PERSISTED_UNDO_HISTORIES: usize = 100;
 
 impl FollowableItem for Editor {
     fn remote_id(&self) -> Option<ViewId> {
@@ -1016,6 +1017,14 @@ impl Item for Editor {
             .detach();
         }
 
+        if let Some(workspace_id) = workspace.database_id()
+            && let Some(file_path) = self.buffer().read(cx).as_singleton().and_then(|buffer| {
+                project::File::from_dyn(buffer.read(cx).file()).map(|file| file.abs_path(cx))
+            })
+        {
+            self.load_undo_history_from_db(workspace_id, file_path, cx);
+        }
+
         // Load persisted folds if this editor doesn't already have folds.
         // This handles manually-opened files (not workspace restoration).
         let display_snapshot = self
@@ -1144,13 +1153,20 @@ impl SerializableItem for Editor {
         _window: &mut Window,
         cx: &mut App,
     ) -> Task<Result<()>> {
-        workspace::delete_unloaded_items(
-            alive_items,
-            workspace_id,
-            "editors",
-            &EditorDb::global(cx),
-            cx,
-        )
+        let db = EditorDb::global(cx);
+        // Without persisted undo history, drop whatever was stored while it was on.
+        let max_undo_histories = if ProjectSettings::get_global(cx).session.restore_undo_history {
+            MAX_PERSISTED_UNDO_HISTORIES
+        } else {
+            0
+        };
+        let delete_unloaded_items =
+            workspace::delete_unloaded_items(alive_items, workspace_id, "editors", &db, cx);
+        cx.background_spawn(async move {
+            delete_unloaded_items.await?;
+            db.prune_file_undo_history(workspace_id, max_undo_histories)
+                .await
+        })
     }
 
     fn deserialize(
@@ -1498,6 +1514,42 @@ fn clip_ranges<'a>(
         .collect()
 }
 
+/// Expands a structural query's replacement for each match, which requires the
+/// syntax tree of the buffer the match is in. Matches are grouped by buffer, so
+/// that each buffer is only searched once.
+fn structural_replacements<'a>(
+    snapshot: &MultiBufferSnapshot,
+    matches: impl IntoIterator<Item = &'a Range<Anchor>>,
+    query: &SearchQuery,
+) -> Vec<(Range<Anchor>, Arc<str>)> {
+    let mut matches_by_buffer = HashMap::<BufferId, (&language::BufferSnapshot, Vec<_>)>::default();
+    for range in matches {
+        let offsets = range.start.to_offset(snapshot)..range.end.to_offset(snapshot);
+        if let Some((buffer, buffer_range)) = snapshot.range_to_buffer_range(offsets) {
+            matches_by_buffer
+                .entry(buffer.remote_id())
+                .or_insert_with(|| (buffer, Vec::new()))
+                .1
+                .push((range.clone(), buffer_range));
+        }
+    }
+
+    let mut replacements = Vec::new();
+    for (buffer, matches) in matches_by_buffer.into_values() {
+        let buffer_ranges = matches
+            .iter()
+            .map(|(_, buffer_range)| buffer_range.clone())
+            .collect::<Vec<_>>();
+        replacements.extend(
+            matches
+                .into_iter()
+                .zip(query.replacements_for_ranges(buffer, &buffer_ranges))
+                .filter_map(|((range, _), replacement)| Some((range, Arc::from(replacement?)))),
+        );
+    }
+    replacements
+}
+
 impl EventEmitter<SearchEvent> for Editor {}
 
 impl Editor {
@@ -1714,6 +1766,15 @@ impl SearchableItem for Editor {
     ) {
         let text = self.buffer.read(cx);
         let text = text.snapshot(cx);
+        if query.is_structural() {
+            let edits = structural_replacements(&text, [identifier], query);
+            if !edits.is_empty() {
+                self.transact(window, cx, |this, _, cx| {
+                    this.edit(edits, cx);
+                });
+            }
+            return;
+        }
         let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
         let text: Cow<_> = if text.len() == 1 {
             text.first().cloned().unwrap().into()
@@ -1742,7 +1803,9 @@ impl SearchableItem for Editor {
 
         // A regex might have replacement variables so we cannot apply
         // the same replacement to all matches
-        if query.is_regex() {
+        if query.is_structural() {
+            edits = structural_replacements(&text, matches, query);
+        } else if query.is_regex() {
             edits = matches
                 .filter_map(|m| {
                     let text = text.text_for_range(m.clone()).collect::<Vec<_>>();
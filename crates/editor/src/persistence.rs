@@ -10,10 +10,15 @@ use db::{
 };
 use fs::MTime;
 use itertools::Itertools as _;
+use rope::Rope;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::{
+    ops::Range,
     path::{Path, PathBuf},
     sync::Arc,
 };
+use text::{SerializedHistory, SerializedTransaction};
 
 use workspace::{ItemId, WorkspaceDb, WorkspaceId};
 
@@ -90,6 +95,62 @@ impl Column for SerializedEditor {
     }
 }
 
+/// The undo history of a file, along with the state of the file it applies to.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct SerializedUndoHistory {
+    pub(crate) mtime: MTime,
+    pub(crate) content_hash: String,
+    pub(crate) history: SerializedHistory,
+}
+
+/// Hashes a file's text, to tell whether a persisted undo history still applies to it.
+pub(crate) fn content_hash(text: &Rope) -> String {
+    let mut hasher = Sha256::new();
+    for chunk in text.chunks() {
+        hasher.update(chunk.as_bytes());
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+#[derive(Serialize, Deserialize)]
+struct UndoHistoryJson {
+    base_text: String,
+    undo_stack: Vec<Vec<(Range<usize>, String)>>,
+    redo_stack: Vec<Vec<(Range<usize>, String)>>,
+}
+
+impl From<&SerializedHistory> for UndoHistoryJson {
+    fn from(history: &SerializedHistory) -> Self {
+        let transactions = |stack: &[SerializedTransaction]| {
+            stack
+                .iter()
+                .map(|transaction| transaction.edits.clone())
+                .collect()
+        };
+        Self {
+            base_text: history.base_text.clone(),
+            undo_stack: transactions(&history.undo_stack),
+            redo_stack: transactions(&history.redo_stack),
+        }
+    }
+}
+
+impl From<UndoHistoryJson> for SerializedHistory {
+    fn from(history: UndoHistoryJson) -> Self {
+        let transactions = |stack: Vec<Vec<(Range<usize>, String)>>| {
+            stack
+                .into_iter()
+                .map(|edits| SerializedTransaction { edits })
+                .collect()
+        };
+        Self {
+            base_text: history.base_text,
+            undo_stack: transactions(history.undo_stack),
+            redo_stack: transactions(history.redo_stack),
+        }
+    }
+}
+
 pub struct EditorDb(db::sqlez::thread_safe_connection::ThreadSafeConnection);
 
 impl Domain for EditorDb {
@@ -126,6 +187,15 @@ impl Domain for EditorDb {
     //   start_fingerprint: Option<String>,
     //   end_fingerprint: Option<String>,
     // )
+    //
+    // file_undo_history(
+    //   workspace_id: usize,
+    //   path: PathBuf,
+    //   mtime_seconds: i64,
+    //   mtime_nanos: i32,
+    //   content_hash: String,
+    //   history: String,
+    // )
 
     const MIGRATIONS: &[&str] = &[
         sql! (
@@ -223,6 +293,22 @@ impl Domain for EditorDb {
                 PRIMARY KEY(workspace_id, path, start)
             );
         ),
+        // Undo history, stored by file path like file_folds, along with the
+        // state of the file it was recorded against.
+        sql! (
+            CREATE TABLE file_undo_history (
+                workspace_id INTEGER NOT NULL,
+                path TEXT NOT NULL,
+                mtime_seconds INTEGER NOT NULL,
+                mtime_nanos INTEGER NOT NULL,
+                content_hash TEXT NOT NULL,
+                history TEXT NOT NULL,
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                    ON DELETE CASCADE
+                    ON UPDATE CASCADE,
+                PRIMARY KEY(workspace_id, path)
+            );
+        ),
     ];
 }
 
@@ -319,6 +405,102 @@ impl EditorDb {
         }
     }
 
+    query! {
+        fn get_file_undo_history_row(
+            workspace_id: WorkspaceId,
+            path: &Path
+        ) -> Result<Option<(i64, i32, String, String)>> {
+            SELECT mtime_seconds, mtime_nanos, content_hash, history
+            FROM file_undo_history
+            WHERE workspace_id = ?1 AND path = ?2
+        }
+    }
+
+    pub(crate) fn get_file_undo_history(
+        &self,
+        workspace_id: WorkspaceId,
+        path: &Path,
+    ) -> Result<Option<SerializedUndoHistory>> {
+        let Some((mtime_seconds, mtime_nanos, content_hash, history)) =
+            self.get_file_undo_history_row(workspace_id, path)?
+        else {
+            return Ok(None);
+        };
+        let history = serde_json::from_str::<UndoHistoryJson>(&history)?;
+        Ok(Some(SerializedUndoHistory {
+            mtime: MTime::from_seconds_and_nanos(mtime_seconds as u64, mtime_nanos as u32),
+            content_hash,
+            history: history.into(),
+        }))
+    }
+
+    pub(crate) async fn save_file_undo_history(
+        &self,
+        workspace_id: WorkspaceId,
+        path: Arc<Path>,
+        undo_history: SerializedUndoHistory,
+    ) -> Result<()> {
+        let Some((mtime_seconds, mtime_nanos)) =
+            undo_history.mtime.to_seconds_and_nanos_for_persistence()
+        else {
+            return self.delete_file_undo_history(workspace_id, path).await;
+        };
+        let history = serde_json::to_string(&UndoHistoryJson::from(&undo_history.history))?;
+        log::debug!("Saving undo history for file {path:?} in workspace {workspace_id:?}");
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                INSERT OR REPLACE INTO file_undo_history
+                    (workspace_id, path, mtime_seconds, mtime_nanos, content_hash, history)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6);
+            ))?((
+                workspace_id,
+                path.as_ref(),
+                mtime_seconds as i64,
+                mtime_nanos as i32,
+                undo_history.content_hash,
+                history,
+            ))
+        })
+        .await
+    }
+
+    pub(crate) async fn delete_file_undo_history(
+        &self,
+        workspace_id: WorkspaceId,
+        path: Arc<Path>,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            conn.exec_bound(sql!(
+                DELETE FROM file_undo_history WHERE workspace_id = ?1 AND path = ?2;
+            ))?((workspace_id, path.as_ref()))
+        })
+        .await
+    }
+
+    /// Keeps the undo history of only the `max_files` most recently saved files
+    /// in the workspace, dropping histories of files that were renamed, deleted
+    /// or simply not edited in a while.
+    pub(crate) async fn prune_file_undo_history(
+        &self,
+        workspace_id: WorkspaceId,
+        max_files: usize,
+    ) -> Result<()> {
+        self.write(move |conn| {
+            // `INSERT OR REPLACE` gives a replaced row a new rowid, so rowids
+            // follow the order in which the histories were last saved.
+            conn.exec_bound(sql!(
+                DELETE FROM file_undo_history
+                WHERE workspace_id = ?1 AND rowid NOT IN (
+                    SELECT rowid FROM file_undo_history
+                    WHERE workspace_id = ?1
+                    ORDER BY rowid DESC
+                    LIMIT ?2
+                );
+            ))?((workspace_id, max_files as i64))
+        })
+        .await
+    }
+
     pub async fn save_editor_selections(
         &self,
         editor_id: ItemId,
@@ -614,4 +796,101 @@ mod tests {
         assert_eq!(retrieved_a[0].0, 10); // file_a's fold
         assert_eq!(retrieved_b[0].0, 30); // file_b's fold
     }
+
+    #[gpui::test]
+    async fn test_save_and_get_file_undo_history(cx: &mut gpui::TestAppContext) {
+        let db = cx.update(|cx| workspace::WorkspaceDb::global(cx));
+        let workspace_id = db.next_id().await.unwrap();
+        let editor_db = cx.update(|cx| EditorDb::global(cx));
+
+        let file_path: Arc<Path> = Arc::from(Path::new("/tmp/test_file_undo_history.rs"));
+        assert_eq!(
+            editor_db
+                .get_file_undo_history(workspace_id, &file_path)
+                .unwrap(),
+            None
+        );
+
+        let undo_history = SerializedUndoHistory {
+            mtime: MTime::from_seconds_and_nanos(100, 42),
+            content_hash: "hash".to_string(),
+            history: SerializedHistory {
+                base_text: "one\n".to_string(),
+                undo_stack: vec![SerializedTransaction {
+                    edits: vec![(0..3, "ONE".to_string()), (4..4, "two\n".to_string())],
+                }],
+                redo_stack: vec![SerializedTransaction {
+                    edits: vec![(0..0, "zero\n".to_string())],
+                }],
+            },
+        };
+        editor_db
+            .save_file_undo_history(workspace_id, file_path.clone(), undo_history.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            editor_db
+                .get_file_undo_history(workspace_id, &file_path)
+                .unwrap(),
+            Some(undo_history.clone())
+        );
+
+        // Saving again replaces the previous history.
+        let new_undo_history = SerializedUndoHistory {
+            mtime: MTime::from_seconds_and_nanos(200, 0),
+            content_hash: "new hash".to_string(),
+            history: SerializedHistory {
+                redo_stack: Vec::new(),
+                ..undo_history.history
+            },
+        };
+        editor_db
+            .save_file_undo_history(workspace_id, file_path.clone(), new_undo_history.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            editor_db
+                .get_file_undo_history(workspace_id, &file_path)
+                .unwrap(),
+            Some(new_undo_history.clone())
+        );
+
+        // Pruning keeps the most recently saved histories.
+        let other_file_path: Arc<Path> = Arc::from(Path::new("/tmp/test_other_undo_history.rs"));
+        editor_db
+            .save_file_undo_history(
+                workspace_id,
+                other_file_path.clone(),
+                new_undo_history.clone(),
+            )
+            .await
+            .unwrap();
+        editor_db
+            .prune_file_undo_history(workspace_id, 1)
+            .await
+            .unwrap();
+        assert_eq!(
+            editor_db
+                .get_file_undo_history(workspace_id, &file_path)
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            editor_db
+                .get_file_undo_history(workspace_id, &other_file_path)
+                .unwrap(),
+            Some(new_undo_history)
+        );
+
+        editor_db
+            .delete_file_undo_history(workspace_id, other_file_path.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            editor_db
+                .get_file_undo_history(workspace_id, &file_path)
+                .unwrap(),
+            None
+        );
+    }
 }
@@ -45,6 +45,9 @@ pub struct FakeGitRepositoryState {
     pub merge_base_contents: HashMap<RepoPath, Oid>,
     pub oids: HashMap<Oid, String>,
     pub blames: HashMap<RepoPath, Blame>,
+    /// The commits that changed each path, most recent first. The empty path lists the
+    /// repository's commits.
+    pub file_histories: HashMap<RepoPath, Vec<git::repository::FileHistoryEntry>>,
     pub current_branch_name: Option<String>,
     pub branches: HashSet<String>,
     /// List of remotes, keys are names and values are URLs
@@ -63,6 +66,7 @@ impl FakeGitRepositoryState {
             index_contents: Default::default(),
             unmerged_paths: Default::default(),
             blames: Default::default(),
+            file_histories: Default::default(),
             current_branch_name: Default::default(),
             branches: Default::default(),
             simulated_index_write_error_message: Default::default(),
@@ -719,16 +723,21 @@ impl GitRepository for FakeGitRepository {
     fn file_history_paginated(
         &self,
         path: RepoPath,
-        _skip: usize,
-        _limit: Option<usize>,
+        skip: usize,
+        limit: Option<usize>,
     ) -> BoxFuture<'_, Result<git::repository::FileHistory>> {
-        async move {
-            Ok(git::repository::FileHistory {
-                entries: Vec::new(),
-                path,
-            })
-        }
-        .boxed()
+        self.with_state_async(false, move |state| {
+            let entries = state
+                .file_histories
+                .get(&path)
+                .into_iter()
+                .flatten()
+                .skip(skip)
+                .take(limit.unwrap_or(usize::MAX))
+                .cloned()
+                .collect();
+            Ok(git::repository::FileHistory { entries, path })
+        })
     }
 
     fn stage_paths(
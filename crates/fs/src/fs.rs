@@ -2161,6 +2161,18 @@ impl FakeFs {
         .unwrap();
     }
 
+    pub fn set_file_history_for_repo(
+        &self,
+        dot_git: &Path,
+        histories: Vec<(RepoPath, Vec<git::repository::FileHistoryEntry>)>,
+    ) {
+        self.with_git_state(dot_git, true, |state| {
+            state.file_histories.clear();
+            state.file_histories.extend(histories);
+        })
+        .unwrap();
+    }
+
     pub fn set_graph_commits(&self, dot_git: &Path, commits: Vec<Arc<InitialGraphCommitData>>) {
         self.with_git_state(dot_git, true, |state| {
             state.graph_commits = commits;
@@ -59,9 +59,10 @@ use text::operation_queue::OperationQueue;
 use text::*;
 pub use text::{
     Anchor, Bias, Buffer as TextBuffer, BufferId, BufferSnapshot as TextBufferSnapshot, Edit,
-    LineIndent, OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope, Selection,
-    SelectionGoal, Subscription, TextDimension, TextSummary, ToOffset, ToOffsetUtf16, ToPoint,
-    ToPointUtf16, Transaction, TransactionId, Unclipped,
+    HistorySnapshot, LineIndent, OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope,
+    Selection, SelectionGoal, SerializedHistory, SerializedTransaction, Subscription,
+    TextDimension, TextSummary, ToOffset, ToOffsetUtf16, ToPoint, ToPointUtf16, Transaction,
+    TransactionId, Unclipped, UndoTreeNode,
 };
 use theme::{ActiveTheme as _, SyntaxTheme};
 #[cfg(any(test, feature = "test-support"))]
@@ -3186,6 +3187,76 @@ impl Buffer {
         }
     }
 
+    /// Moves the buffer to the given node of its undo tree, or to its original
+    /// text if `target` is `None`.
+    pub fn navigate_undo_tree(
+        &mut self,
+        target: Option<TransactionId>,
+        cx: &mut Context<Self>,
+    ) -> bool {
+        if self.transaction_depth > 0 {
+            return false;
+        }
+        let was_dirty = self.is_dirty();
+        let old_version = self.version.clone();
+
+        if let Some(operation) = self.text.navigate_undo_tree(target) {
+            self.send_operation(Operation::Buffer(operation), true, cx);
+            self.did_edit(&old_version, was_dirty, true, cx);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Replays a [`SerializedHistory`] onto an unmodified buffer without any
+    /// history of its own, so that its transactions can be undone and redone.
+    ///
+    /// Returns `false` without touching the buffer if its text doesn't match
+    /// the text the history ends at.
+    pub fn restore_history(&mut self, history: &SerializedHistory, cx: &mut Context<Self>) -> bool {
+        if history.is_empty()
+            || self.is_dirty()
+            || self.peek_undo_stack().is_some()
+            || self.peek_redo_stack().is_some()
+            || history.text().is_none_or(|text| text != self.text())
+        {
+            return false;
+        }
+
+        self.start_transaction();
+        self.edit([(0..self.len(), history.base_text.as_str())], None, cx);
+        if let Some(transaction_id) = self.end_transaction(cx) {
+            self.forget_transaction(transaction_id);
+        }
+        for transaction in history.undo_stack.iter().chain(&history.redo_stack) {
+            self.start_transaction();
+            self.edit(
+                transaction
+                    .edits
+                    .iter()
+                    .map(|(range, new_text)| (range.clone(), new_text.as_str())),
+                None,
+                cx,
+            );
+            self.end_transaction(cx);
+            self.finalize_last_transaction();
+        }
+        for _ in &history.redo_stack {
+            self.undo(cx);
+        }
+
+        let was_dirty = self.is_dirty();
+        self.saved_version = self.version();
+        self.has_unsaved_edits
+            .set((self.saved_version.clone(), false));
+        if was_dirty {
+            cx.emit(BufferEvent::DirtyChanged);
+        }
+        cx.notify();
+        true
+    }
+
     fn restore_encoding_for_transaction(&mut self, transaction_id: TransactionId, was_dirty: bool) {
         if let Some((old_encoding, old_has_bom)) =
             self.reload_with_encoding_txns.get(&transaction_id)
@@ -3243,6 +3243,51 @@ fn test_undo_after_merge_into_base(cx: &mut TestAppContext) {
     branch.read_with(cx, |branch, _| assert_eq!(branch.text(), "ABCdefgHIjk"));
 }
 
+#[gpui::test]
+fn test_restore_history(cx: &mut TestAppContext) {
+    cx.update(|cx| init_settings(cx, |_| {}));
+
+    let original = cx.new(|cx| Buffer::local("one\ntwo\n", cx));
+    let history = original.update(cx, |buffer, cx| {
+        buffer.edit([(0..3, "ONE")], None, cx);
+        buffer.finalize_last_transaction();
+        buffer.edit([(8..8, "three\n")], None, cx);
+        buffer.finalize_last_transaction();
+        buffer.edit([(0..0, "zero\n")], None, cx);
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "ONE\ntwo\nthree\n");
+        buffer.serialize_history(usize::MAX, usize::MAX).unwrap()
+    });
+
+    // The history doesn't apply to a buffer with different text.
+    let buffer = cx.new(|cx| Buffer::local("ONE\ntwo\n", cx));
+    buffer.update(cx, |buffer, cx| {
+        assert!(!buffer.restore_history(&history, cx));
+        assert_eq!(buffer.text(), "ONE\ntwo\n");
+        assert!(buffer.peek_undo_stack().is_none());
+    });
+
+    let buffer = cx.new(|cx| Buffer::local("ONE\ntwo\nthree\n", cx));
+    buffer.update(cx, |buffer, cx| {
+        assert!(buffer.restore_history(&history, cx));
+        assert_eq!(buffer.text(), "ONE\ntwo\nthree\n");
+        assert!(!buffer.is_dirty());
+
+        buffer.redo(cx);
+        assert_eq!(buffer.text(), "zero\nONE\ntwo\nthree\n");
+        buffer.undo(cx);
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "ONE\ntwo\n");
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "one\ntwo\n");
+        assert!(buffer.undo(cx).is_none());
+        assert_eq!(buffer.text(), "one\ntwo\n");
+
+        // A buffer that already has history is left alone.
+        assert!(!buffer.restore_history(&history, cx));
+    });
+}
+
 #[gpui::test]
 async fn test_preview_edits(cx: &mut TestAppContext) {
     cx.update(|cx| {
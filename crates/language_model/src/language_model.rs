@@ -9,6 +9,8 @@ pub mod tool_schema;
 
 #[cfg(any(test, feature = "test-support"))]
 pub mod fake_provider;
+#[cfg(any(test, feature = "test-support"))]
+pub mod replay;
 
 use anyhow::{Result, anyhow};
 use client::Client;
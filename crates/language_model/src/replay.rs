@@ -0,0 +1,739 @@
+//! Records the requests sent to a language model and the events it streams
+//! back, so that they can be replayed later without the model.
+//!
+//! Wrap a real model in a [`RecordingLanguageModel`] to write a fixture, and
+//! serve the fixture with a [`ReplayLanguageModel`] to run the same exchange
+//! offline. The replay model fails any request that doesn't match a recorded
+//! one, so changes to prompts or tools show up as test failures. The parts of
+//! the system prompt that depend on the machine, like the operating system,
+//! the shell and absolute paths, are replaced with placeholders, so that a
+//! fixture recorded on one machine can be replayed on another.
+
+use crate::{
+    LanguageModel, LanguageModelCompletionError, LanguageModelCompletionEvent, LanguageModelId,
+    LanguageModelName, LanguageModelProviderId, LanguageModelProviderName, LanguageModelRequest,
+    LanguageModelToolChoice, LanguageModelToolSchemaFormat, MessageContent, Role,
+};
+use anyhow::{Context as _, Result, anyhow};
+use futures::{FutureExt as _, StreamExt as _, future::BoxFuture, stream::BoxStream};
+use gpui::{App, AsyncApp};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// A recorded exchange with a language model.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFixture {
+    pub model: RecordedModel,
+    /// The completions in the order they were requested.
+    pub completions: Vec<RecordedCompletion>,
+}
+
+/// The capabilities of the recorded model, which affect the requests that
+/// are built for it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedModel {
+    pub provider_id: String,
+    pub provider_name: String,
+    pub id: String,
+    pub name: String,
+    pub max_token_count: u64,
+    pub max_output_tokens: Option<u64>,
+    pub supports_images: bool,
+    pub supports_tools: bool,
+    pub supports_thinking: bool,
+    pub supports_streaming_tools: bool,
+    pub supported_tool_choices: Vec<LanguageModelToolChoice>,
+    pub tool_input_format: LanguageModelToolSchemaFormat,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCompletion {
+    /// The request, without the thread and prompt ids, which change on every run.
+    pub request: LanguageModelRequest,
+    /// The error the request failed with before any events were streamed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub events: Vec<RecordedEvent>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedEvent {
+    Event(LanguageModelCompletionEvent),
+    /// Errors are recorded as their message, and replayed as
+    /// [`LanguageModelCompletionError::Other`].
+    Error(String),
+}
+
+impl ReplayFixture {
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("reading fixture {}", path.display()))?;
+        serde_json::from_str(&json).with_context(|| format!("parsing fixture {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json + "\n")
+            .with_context(|| format!("writing fixture {}", path.display()))
+    }
+}
+
+fn normalize_request(request: &LanguageModelRequest) -> LanguageModelRequest {
+    let mut request = LanguageModelRequest {
+        thread_id: None,
+        prompt_id: None,
+        ..request.clone()
+    };
+    for message in &mut request.messages {
+        if message.role != Role::System {
+            continue;
+        }
+        for content in &mut message.content {
+            if let MessageContent::Text(text) = content {
+                *text = normalize_system_prompt(text);
+            }
+        }
+    }
+    request
+}
+
+const MACHINE_SPECIFIC_LINES: [&str; 2] = ["Operating System: ", "Default Shell: "];
+
+/// Replaces the operating system, the shell and absolute paths in a system
+/// prompt with placeholders. Paths keep their last component, so that
+/// different project roots can still be told apart.
+fn normalize_system_prompt(prompt: &str) -> String {
+    let mut normalized = String::with_capacity(prompt.len());
+    for line in prompt.split_inclusive('\n') {
+        if let Some(prefix) = MACHINE_SPECIFIC_LINES
+            .iter()
+            .find(|prefix| line.starts_with(**prefix))
+        {
+            normalized.push_str(prefix);
+            normalized.push_str("<redacted>");
+            if line.ends_with('\n') {
+                normalized.push('\n');
+            }
+            continue;
+        }
+
+        for (ix, segment) in line.split('`').enumerate() {
+            if ix > 0 {
+                normalized.push('`');
+            }
+            // Odd segments are the ones between backticks.
+            if ix % 2 == 1 && is_absolute_path(segment) {
+                let name = segment
+                    .rsplit(['/', '\\'])
+                    .find(|component| !component.is_empty())
+                    .unwrap_or_default();
+                normalized.push_str("<absolute path>/");
+                normalized.push_str(name);
+            } else {
+                normalized.push_str(segment);
+            }
+        }
+    }
+    normalized
+}
+
+/// Whether the text is an absolute path on any platform, regardless of the
+/// one the tests run on.
+fn is_absolute_path(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    text.starts_with('/')
+        || text.starts_with("\\\\")
+        || (bytes.len() >= 3
+            && bytes[0].is_ascii_alphabetic()
+            && bytes[1] == b':'
+            && matches!(bytes[2], b'\\' | b'/'))
+}
+
+/// Wraps a language model, recording its requests and completion events.
+pub struct RecordingLanguageModel {
+    model: Arc<dyn LanguageModel>,
+    recording: Arc<Recording>,
+}
+
+struct Recording {
+    fixture: Mutex<ReplayFixture>,
+    path: Option<PathBuf>,
+}
+
+impl Recording {
+    fn update(&self, update: impl FnOnce(&mut ReplayFixture)) {
+        update(&mut self.fixture.lock());
+    }
+
+    fn save(&self) {
+        if let Some(path) = &self.path
+            && let Err(error) = self.fixture.lock().save(path)
+        {
+            log::error!("{error:#}");
+        }
+    }
+}
+
+/// Saves the recording once a completion's stream ends or is dropped, rather
+/// than on every event.
+struct SaveOnDrop(Arc<Recording>);
+
+impl Drop for SaveOnDrop {
+    fn drop(&mut self) {
+        self.0.save();
+    }
+}
+
+impl RecordingLanguageModel {
+    pub fn new(model: Arc<dyn LanguageModel>) -> Self {
+        let recorded_model = RecordedModel {
+            provider_id: model.provider_id().0.to_string(),
+            provider_name: model.provider_name().0.to_string(),
+            id: model.id().0.to_string(),
+            name: model.name().0.to_string(),
+            max_token_count: model.max_token_count(),
+            max_output_tokens: model.max_output_tokens(),
+            supports_images: model.supports_images(),
+            supports_tools: model.supports_tools(),
+            supports_thinking: model.supports_thinking(),
+            supports_streaming_tools: model.supports_streaming_tools(),
+            supported_tool_choices: [
+                LanguageModelToolChoice::Auto,
+                LanguageModelToolChoice::Any,
+                LanguageModelToolChoice::None,
+            ]
+            .into_iter()
+            .filter(|choice| model.supports_tool_choice(choice.clone()))
+            .collect(),
+            tool_input_format: model.tool_input_format(),
+        };
+        Self {
+            model,
+            recording: Arc::new(Recording {
+                fixture: Mutex::new(ReplayFixture {
+                    model: recorded_model,
+                    completions: Vec::new(),
+                }),
+                path: None,
+            }),
+        }
+    }
+
+    /// Writes the fixture to the path whenever a completion finishes.
+    pub fn save_to(model: Arc<dyn LanguageModel>, path: impl Into<PathBuf>) -> Self {
+        let mut this = Self::new(model);
+        let recording = Arc::get_mut(&mut this.recording).expect("recording is not shared yet");
+        recording.path = Some(path.into());
+        this
+    }
+
+    pub fn fixture(&self) -> ReplayFixture {
+        self.recording.fixture.lock().clone()
+    }
+}
+
+impl LanguageModel for RecordingLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        self.model.id()
+    }
+
+    fn name(&self) -> LanguageModelName {
+        self.model.name()
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        self.model.provider_id()
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        self.model.provider_name()
+    }
+
+    fn telemetry_id(&self) -> String {
+        self.model.telemetry_id()
+    }
+
+    fn supports_thinking(&self) -> bool {
+        self.model.supports_thinking()
+    }
+
+    fn supports_images(&self) -> bool {
+        self.model.supports_images()
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.model.supports_tools()
+    }
+
+    fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
+        self.model.supports_tool_choice(choice)
+    }
+
+    fn supports_streaming_tools(&self) -> bool {
+        self.model.supports_streaming_tools()
+    }
+
+    fn tool_input_format(&self) -> LanguageModelToolSchemaFormat {
+        self.model.tool_input_format()
+    }
+
+    fn max_token_count(&self) -> u64 {
+        self.model.max_token_count()
+    }
+
+    fn max_output_tokens(&self) -> Option<u64> {
+        self.model.max_output_tokens()
+    }
+
+    fn count_tokens(
+        &self,
+        request: LanguageModelRequest,
+        cx: &App,
+    ) -> BoxFuture<'static, Result<u64>> {
+        self.model.count_tokens(request, cx)
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let recording = self.recording.clone();
+        let recorded_request = normalize_request(&request);
+        let completion = self.model.stream_completion(request, cx);
+        async move {
+            let events = match completion.await {
+                Ok(events) => events,
+                Err(error) => {
+                    recording.update(|fixture| {
+                        fixture.completions.push(RecordedCompletion {
+                            request: recorded_request,
+                            error: Some(error.to_string()),
+                            events: Vec::new(),
+                        })
+                    });
+                    recording.save();
+                    return Err(error);
+                }
+            };
+
+            // Reserve the completion's place now, so that completions streamed
+            // concurrently are recorded in the order they were requested.
+            let mut ix = 0;
+            recording.update(|fixture| {
+                ix = fixture.completions.len();
+                fixture.completions.push(RecordedCompletion {
+                    request: recorded_request,
+                    error: None,
+                    events: Vec::new(),
+                });
+            });
+            let recording = SaveOnDrop(recording);
+            Ok(events
+                .map(move |event| {
+                    let recorded_event = match &event {
+                        Ok(event) => RecordedEvent::Event(event.clone()),
+                        Err(error) => RecordedEvent::Error(error.to_string()),
+                    };
+                    recording
+                        .0
+                        .update(|fixture| fixture.completions[ix].events.push(recorded_event));
+                    event
+                })
+                .boxed())
+        }
+        .boxed()
+    }
+}
+
+/// Serves the completions of a [`ReplayFixture`].
+///
+/// Each request is answered with the first unused recorded completion whose
+/// request is equal to it, so requests that are made concurrently can arrive
+/// in a different order than they were recorded in.
+pub struct ReplayLanguageModel {
+    fixture: ReplayFixture,
+    used: Mutex<Vec<bool>>,
+}
+
+impl ReplayLanguageModel {
+    pub fn new(fixture: ReplayFixture) -> Self {
+        let used = vec![false; fixture.completions.len()];
+        Self {
+            fixture,
+            used: Mutex::new(used),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        Ok(Self::new(ReplayFixture::load(path)?))
+    }
+
+    /// The number of recorded completions that haven't been requested.
+    pub fn remaining_completions(&self) -> usize {
+        self.used.lock().iter().filter(|used| !**used).count()
+    }
+
+    fn take_completion(&self, request: &LanguageModelRequest) -> Result<&RecordedCompletion> {
+        let request = normalize_request(request);
+        let mut used = self.used.lock();
+        let mut next_unused = None;
+        for (ix, completion) in self.fixture.completions.iter().enumerate() {
+            if used[ix] {
+                continue;
+            }
+            if completion.request == request {
+                used[ix] = true;
+                return Ok(completion);
+            }
+            next_unused.get_or_insert(completion);
+        }
+
+        let Some(next_unused) = next_unused else {
+            return Err(anyhow!(
+                "all {} recorded completions have been replayed",
+                self.fixture.completions.len()
+            ));
+        };
+        let difference = serde_json::to_value(&next_unused.request)
+            .ok()
+            .zip(serde_json::to_value(&request).ok())
+            .and_then(|(expected, actual)| first_difference(&expected, &actual, String::new()))
+            .unwrap_or_default();
+        Err(anyhow!(
+            "the request doesn't match any recorded completion.{difference}"
+        ))
+    }
+}
+
+/// Describes where two JSON values first differ, such as `messages.1.content`.
+fn first_difference(expected: &Value, actual: &Value, path: String) -> Option<String> {
+    let child_path = |key: &dyn std::fmt::Display| {
+        if path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{path}.{key}")
+        }
+    };
+    match (expected, actual) {
+        _ if expected == actual => None,
+        (Value::Object(expected), Value::Object(actual)) => expected
+            .keys()
+            .chain(actual.keys().filter(|key| !expected.contains_key(*key)))
+            .find_map(|key| {
+                first_difference(
+                    expected.get(key).unwrap_or(&Value::Null),
+                    actual.get(key).unwrap_or(&Value::Null),
+                    child_path(key),
+                )
+            }),
+        (Value::Array(expected_items), Value::Array(actual_items))
+            if expected_items.len() == actual_items.len() =>
+        {
+            expected_items
+                .iter()
+                .zip(actual_items)
+                .enumerate()
+                .find_map(|(ix, (expected, actual))| {
+                    first_difference(expected, actual, child_path(&ix))
+                })
+        }
+        _ => {
+            let location = if path.is_empty() {
+                String::new()
+            } else {
+                format!(" at `{path}`")
+            };
+            Some(format!(
+                " The next recorded request differs{location}.\nRecorded: {}\nActual: {}",
+                truncate(expected),
+                truncate(actual)
+            ))
+        }
+    }
+}
+
+fn truncate(value: &Value) -> String {
+    const MAX_LEN: usize = 500;
+    let mut text = value.to_string();
+    if text.len() > MAX_LEN {
+        let mut end = MAX_LEN;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+        text.push('…');
+    }
+    text
+}
+
+impl LanguageModel for ReplayLanguageModel {
+    fn id(&self) -> LanguageModelId {
+        LanguageModelId::from(self.fixture.model.id.clone())
+    }
+
+    fn name(&self) -> LanguageModelName {
+        LanguageModelName::from(self.fixture.model.name.clone())
+    }
+
+    fn provider_id(&self) -> LanguageModelProviderId {
+        LanguageModelProviderId::from(self.fixture.model.provider_id.clone())
+    }
+
+    fn provider_name(&self) -> LanguageModelProviderName {
+        LanguageModelProviderName::from(self.fixture.model.provider_name.clone())
+    }
+
+    fn telemetry_id(&self) -> String {
+        format!("replay/{}", self.fixture.model.id)
+    }
+
+    fn supports_thinking(&self) -> bool {
+        self.fixture.model.supports_thinking
+    }
+
+    fn supports_images(&self) -> bool {
+        self.fixture.model.supports_images
+    }
+
+    fn supports_tools(&self) -> bool {
+        self.fixture.model.supports_tools
+    }
+
+    fn supports_tool_choice(&self, choice: LanguageModelToolChoice) -> bool {
+        self.fixture.model.supported_tool_choices.contains(&choice)
+    }
+
+    fn supports_streaming_tools(&self) -> bool {
+        self.fixture.model.supports_streaming_tools
+    }
+
+    fn tool_input_format(&self) -> LanguageModelToolSchemaFormat {
+        self.fixture.model.tool_input_format
+    }
+
+    fn max_token_count(&self) -> u64 {
+        self.fixture.model.max_token_count
+    }
+
+    fn max_output_tokens(&self) -> Option<u64> {
+        self.fixture.model.max_output_tokens
+    }
+
+    fn count_tokens(&self, _: LanguageModelRequest, _: &App) -> BoxFuture<'static, Result<u64>> {
+        futures::future::ready(Ok(0)).boxed()
+    }
+
+    fn stream_completion(
+        &self,
+        request: LanguageModelRequest,
+        _: &AsyncApp,
+    ) -> BoxFuture<
+        'static,
+        Result<
+            BoxStream<'static, Result<LanguageModelCompletionEvent, LanguageModelCompletionError>>,
+            LanguageModelCompletionError,
+        >,
+    > {
+        let result = self.take_completion(&request).map(|completion| {
+            if let Some(error) = &completion.error {
+                return Err(LanguageModelCompletionError::Other(anyhow!(error.clone())));
+            }
+            let events = completion
+                .events
+                .iter()
+                .map(|event| match event {
+                    RecordedEvent::Event(event) => Ok(event.clone()),
+                    RecordedEvent::Error(error) => {
+                        Err(LanguageModelCompletionError::Other(anyhow!(error.clone())))
+                    }
+                })
+                .collect::<Vec<_>>();
+            Ok(futures::stream::iter(events).boxed())
+        });
+        let result = match result {
+            Ok(result) => result,
+            Err(error) => Err(LanguageModelCompletionError::Other(error)),
+        };
+        futures::future::ready(result).boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fake_provider::FakeLanguageModel;
+    use crate::{LanguageModelRequestMessage, StopReason};
+    use gpui::TestAppContext;
+
+    fn request(thread_id: &str, text: &str) -> LanguageModelRequest {
+        LanguageModelRequest {
+            thread_id: Some(thread_id.into()),
+            messages: vec![LanguageModelRequestMessage {
+                role: Role::User,
+                content: vec![text.into()],
+                cache: false,
+                reasoning_details: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    async fn collect(
+        model: &dyn LanguageModel,
+        request: LanguageModelRequest,
+        cx: &AsyncApp,
+    ) -> Result<Vec<Result<LanguageModelCompletionEvent, String>>, String> {
+        let events = model
+            .stream_completion(request, cx)
+            .await
+            .map_err(|error| error.to_string())?;
+        Ok(events
+            .map(|event| event.map_err(|error| error.to_string()))
+            .collect()
+            .await)
+    }
+
+    #[gpui::test]
+    async fn test_record_and_replay(cx: &mut TestAppContext) {
+        let fake = Arc::new(FakeLanguageModel::default());
+        let recording = RecordingLanguageModel::new(fake.clone());
+        let cx = cx.to_async();
+
+        let first = recording.stream_completion(request("recorded", "Hello"), &cx);
+        let second = recording.stream_completion(request("recorded", "Bye"), &cx);
+        let (first, second) = futures::join!(first, second);
+        let (first, second) = (first.unwrap(), second.unwrap());
+
+        let pending = fake.pending_completions();
+        let (hello_request, bye_request) = (&pending[0], &pending[1]);
+        fake.send_completion_stream_text_chunk(bye_request, "Goodbye");
+        fake.send_completion_stream_event(
+            bye_request,
+            LanguageModelCompletionEvent::Stop(StopReason::EndTurn),
+        );
+        fake.end_completion_stream(bye_request);
+        fake.send_completion_stream_text_chunk(hello_request, "Hi");
+        fake.send_completion_stream_error(hello_request, anyhow!("overloaded"));
+        fake.end_completion_stream(hello_request);
+        let recorded_first = first.collect::<Vec<_>>().await;
+        let recorded_second = second.collect::<Vec<_>>().await;
+        assert_eq!(recorded_first.len(), 2);
+        assert_eq!(recorded_second.len(), 2);
+
+        let fixture = recording.fixture();
+        assert_eq!(fixture.model.id, "fake");
+        assert_eq!(fixture.completions.len(), 2);
+        assert_eq!(fixture.completions[0].request.thread_id, None);
+        let fixture =
+            serde_json::from_str::<ReplayFixture>(&serde_json::to_string(&fixture).unwrap())
+                .unwrap();
+
+        // Requests are matched regardless of their order and thread id.
+        let replay = ReplayLanguageModel::new(fixture);
+        assert_eq!(replay.remaining_completions(), 2);
+        assert_eq!(
+            collect(&replay, request("replayed", "Bye"), &cx).await,
+            Ok(vec![
+                Ok(LanguageModelCompletionEvent::Text("Goodbye".into())),
+                Ok(LanguageModelCompletionEvent::Stop(StopReason::EndTurn)),
+            ])
+        );
+        assert_eq!(
+            collect(&replay, request("replayed", "Hello"), &cx).await,
+            Ok(vec![
+                Ok(LanguageModelCompletionEvent::Text("Hi".into())),
+                Err("overloaded".into()),
+            ])
+        );
+        assert_eq!(replay.remaining_completions(), 0);
+        assert_eq!(
+            collect(&replay, request("replayed", "Hello"), &cx).await,
+            Err("all 2 recorded completions have been replayed".into())
+        );
+    }
+
+    #[gpui::test]
+    async fn test_replay_mismatch(cx: &mut TestAppContext) {
+        let fake = Arc::new(FakeLanguageModel::default());
+        let recording = RecordingLanguageModel::new(fake.clone());
+        let cx = cx.to_async();
+
+        let events = recording
+            .stream_completion(request("recorded", "Hello"), &cx)
+            .await
+            .unwrap();
+        fake.end_last_completion_stream();
+        events.collect::<Vec<_>>().await;
+
+        let replay = ReplayLanguageModel::new(recording.fixture());
+        let error = collect(&replay, request("replayed", "Hello, world"), &cx)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error,
+            "the request doesn't match any recorded completion. The next recorded request \
+             differs at `messages.0.content.0.Text`.\nRecorded: \"Hello\"\nActual: \"Hello, world\""
+        );
+        assert_eq!(replay.remaining_completions(), 1);
+    }
+
+    #[gpui::test]
+    async fn test_recording_is_saved_when_completions_finish(cx: &mut TestAppContext) {
+        let fake = Arc::new(FakeLanguageModel::default());
+        let path = std::env::temp_dir().join(format!(
+            "zed-replay-fixture-{}-{}.json",
+            std::process::id(),
+            line!()
+        ));
+        let recording = RecordingLanguageModel::save_to(fake.clone(), &path);
+        let cx = cx.to_async();
+
+        let events = recording
+            .stream_completion(request("recorded", "Hello"), &cx)
+            .await
+            .unwrap();
+        fake.send_last_completion_stream_text_chunk("Hi");
+        fake.end_last_completion_stream();
+        assert!(!path.exists());
+
+        events.collect::<Vec<_>>().await;
+        let saved = ReplayFixture::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(saved, recording.fixture());
+        assert_eq!(saved.completions[0].events.len(), 1);
+    }
+
+    #[test]
+    fn test_normalize_system_prompt() {
+        let prompt = "The project contains the following root directories:\n\n\
+            - `/Users/someone/src/zed`\n\
+            - `C:\\Users\\someone\\notes\\`\n\n\
+            Start paths with a root directory, like `zed/README.md`.\n\n\
+            Operating System: macos\n\
+            Default Shell: zsh\n";
+        assert_eq!(
+            normalize_system_prompt(prompt),
+            "The project contains the following root directories:\n\n\
+            - `<absolute path>/zed`\n\
+            - `<absolute path>/notes`\n\n\
+            Start paths with a root directory, like `zed/README.md`.\n\n\
+            Operating System: <redacted>\n\
+            Default Shell: <redacted>\n"
+        );
+    }
+}
@@ -0,0 +1,648 @@
+//! A view of a file opened in large file mode, which renders only the lines in view, without
+//! syntax highlighting, and supports search, go-to-line and editing one line at a time.
+
+use std::ops::Range;
+
+use anyhow::Result;
+use editor::{Editor, actions::SelectAll};
+use file_icons::FileIcons;
+use gpui::{
+    AnyElement, App, AppContext as _, ClickEvent, Context, Entity, EventEmitter, FocusHandle,
+    Focusable, HighlightStyle, IntoElement, Render, ScrollStrategy, StyledText, Subscription, Task,
+    UniformListScrollHandle, Window, actions, uniform_list,
+};
+use project::{
+    LargeFile, LargeFileEvent, Project,
+    large_file::{LargeFileText, MAX_LINE_LEN},
+};
+use settings::Settings as _;
+use ui::{Tooltip, WithScrollbar, prelude::*};
+use util::size::format_file_size;
+use workspace::{
+    ItemSettings, Pane,
+    item::{Item, ItemEvent, ProjectItem, SaveOptions, TabContentParams},
+};
+
+actions!(
+    large_file_viewer,
+    [
+        /// Focuses the search input.
+        Find,
+        /// Selects the next match of the search query.
+        FindNext,
+        /// Selects the previous match of the search query.
+        FindPrevious,
+        /// Focuses the go-to-line input.
+        GoToLine,
+        /// Edits the selected line.
+        EditLine,
+        /// Deletes the selected line.
+        DeleteLine,
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    workspace::register_project_item::<LargeFileView>(cx);
+}
+
+pub struct LargeFileView {
+    large_file: Entity<LargeFile>,
+    focus_handle: FocusHandle,
+    scroll_handle: UniformListScrollHandle,
+    selected_row: Option<usize>,
+    /// The row of the current search match, and its range within that row.
+    active_match: Option<(usize, Range<usize>)>,
+    query_editor: Entity<Editor>,
+    go_to_line_editor: Entity<Editor>,
+    /// The row being edited, and the editor for its new contents.
+    line_editor: Option<(usize, Entity<Editor>)>,
+    status: Option<SharedString>,
+    pending_search: Task<()>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl LargeFileView {
+    fn new(large_file: Entity<LargeFile>, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let query_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Find…", window, cx);
+            editor
+        });
+        let go_to_line_editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_placeholder_text("Go to line…", window, cx);
+            editor
+        });
+        let subscriptions = vec![cx.subscribe(&large_file, |this, _, event, cx| {
+            match event {
+                LargeFileEvent::Loaded => {
+                    let line_count = this.line_count(cx);
+                    this.selected_row = this.selected_row.map(|row| row.min(line_count - 1));
+                    this.active_match = None;
+                }
+                LargeFileEvent::Edited => this.active_match = None,
+                LargeFileEvent::Saved => {}
+            }
+            cx.emit(ItemEvent::UpdateTab);
+            cx.notify();
+        })];
+
+        Self {
+            large_file,
+            focus_handle: cx.focus_handle(),
+            scroll_handle: UniformListScrollHandle::new(),
+            selected_row: None,
+            active_match: None,
+            query_editor,
+            go_to_line_editor,
+            line_editor: None,
+            status: None,
+            pending_search: Task::ready(()),
+            _subscriptions: subscriptions,
+        }
+    }
+
+    fn text<'a>(&self, cx: &'a App) -> Option<&'a LargeFileText> {
+        self.large_file.read(cx).text()
+    }
+
+    fn line_count(&self, cx: &App) -> usize {
+        self.text(cx).map_or(0, |text| text.line_count())
+    }
+
+    fn select_row(&mut self, row: usize, cx: &mut Context<Self>) {
+        self.selected_row = Some(row);
+        self.scroll_handle
+            .scroll_to_item(row, ScrollStrategy::Center);
+        cx.notify();
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let line_count = self.line_count(cx);
+        if line_count > 0 {
+            let row = self
+                .selected_row
+                .map_or(0, |row| (row + 1).min(line_count - 1));
+            self.select_row(row, cx);
+        }
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        if self.line_count(cx) > 0 {
+            let row = self.selected_row.map_or(0, |row| row.saturating_sub(1));
+            self.select_row(row, cx);
+        }
+    }
+
+    fn select_first(&mut self, _: &menu::SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        if self.line_count(cx) > 0 {
+            self.select_row(0, cx);
+        }
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(row) = self.line_count(cx).checked_sub(1) {
+            self.select_row(row, cx);
+        }
+    }
+
+    fn find(&mut self, _: &Find, window: &mut Window, cx: &mut Context<Self>) {
+        self.query_editor.update(cx, |editor, cx| {
+            editor.select_all(&SelectAll, window, cx);
+        });
+        window.focus(&self.query_editor.focus_handle(cx), cx);
+    }
+
+    fn find_next(&mut self, _: &FindNext, _: &mut Window, cx: &mut Context<Self>) {
+        self.search(false, cx);
+    }
+
+    fn find_previous(&mut self, _: &FindPrevious, _: &mut Window, cx: &mut Context<Self>) {
+        self.search(true, cx);
+    }
+
+    /// Searches for the query from the current match or selected line, wrapping around the end
+    /// of the file.
+    fn search(&mut self, backwards: bool, cx: &mut Context<Self>) {
+        let query = self.query_editor.read(cx).text(cx);
+        let Some(text) = self.text(cx).cloned() else {
+            return;
+        };
+        if query.is_empty() {
+            return;
+        }
+
+        let offset = match (&self.active_match, self.selected_row) {
+            (Some((row, range)), _) => text.line_start(*row).map(|start| {
+                if backwards {
+                    start + range.start
+                } else {
+                    start + range.end
+                }
+            }),
+            (None, Some(row)) => text.line_start(row),
+            (None, None) => None,
+        }
+        .unwrap_or(0);
+
+        self.status = Some("Searching…".into());
+        cx.notify();
+        self.pending_search = cx.spawn(async move |this, cx| {
+            let found = cx
+                .background_spawn(async move {
+                    let found = text.find(&query, offset, backwards).or_else(|| {
+                        let wrapped_offset = if backwards { text.len() } else { 0 };
+                        text.find(&query, wrapped_offset, backwards)
+                    })?;
+                    let row = text.row_for_offset(found.start);
+                    let line_start = text.line_start(row)?;
+                    Some((row, found.start - line_start..found.end - line_start))
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                match found {
+                    Some((row, range)) => {
+                        this.status = None;
+                        this.active_match = Some((row, range));
+                        this.select_row(row, cx);
+                    }
+                    None => {
+                        this.status = Some("No matches".into());
+                        this.active_match = None;
+                    }
+                }
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn go_to_line(&mut self, _: &GoToLine, window: &mut Window, cx: &mut Context<Self>) {
+        self.go_to_line_editor.update(cx, |editor, cx| {
+            editor.select_all(&SelectAll, window, cx);
+        });
+        window.focus(&self.go_to_line_editor.focus_handle(cx), cx);
+    }
+
+    fn confirm_go_to_line(
+        &mut self,
+        _: &menu::Confirm,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let query = self.go_to_line_editor.read(cx).text(cx);
+        let row = query
+            .trim()
+            .split(':')
+            .next()
+            .and_then(|row| row.trim().parse::<usize>().ok());
+        let line_count = self.line_count(cx);
+        match row {
+            Some(row) if line_count > 0 => {
+                self.status = None;
+                self.select_row(row.saturating_sub(1).min(line_count - 1), cx);
+                window.focus(&self.focus_handle, cx);
+            }
+            _ => {
+                self.status = Some("Enter a line number".into());
+                cx.notify();
+            }
+        }
+    }
+
+    fn edit_line(&mut self, _: &EditLine, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(row) = self.selected_row {
+            self.start_editing(row, window, cx);
+        }
+    }
+
+    fn start_editing(&mut self, row: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(text) = self.text(cx) else {
+            return;
+        };
+        let Some(range) = text.line_range(row) else {
+            return;
+        };
+        if range.len() > MAX_LINE_LEN {
+            self.status = Some("This line is too long to edit".into());
+            cx.notify();
+            return;
+        }
+        let Some(line) = text.line(row) else {
+            return;
+        };
+
+        let editor = cx.new(|cx| {
+            let mut editor = Editor::single_line(window, cx);
+            editor.set_text(line, window, cx);
+            editor
+        });
+        window.focus(&editor.focus_handle(cx), cx);
+        self.line_editor = Some((row, editor));
+        self.selected_row = Some(row);
+        cx.notify();
+    }
+
+    fn confirm_edit(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((row, editor)) = self.line_editor.take() else {
+            return;
+        };
+        let new_text = editor.read(cx).text(cx);
+        let result = self.large_file.update(cx, |large_file, cx| {
+            large_file.replace_lines(row..row + 1, &new_text, cx)
+        });
+        self.report(result, cx);
+        window.focus(&self.focus_handle, cx);
+        cx.notify();
+    }
+
+    fn delete_line(&mut self, _: &DeleteLine, _: &mut Window, cx: &mut Context<Self>) {
+        let Some(row) = self.selected_row else {
+            return;
+        };
+        let result = self.large_file.update(cx, |large_file, cx| {
+            large_file.delete_lines(row..row + 1, cx)
+        });
+        self.report(result, cx);
+        let line_count = self.line_count(cx);
+        if line_count > 0 {
+            self.select_row(row.min(line_count - 1), cx);
+        }
+    }
+
+    fn report(&mut self, result: Result<()>, cx: &mut Context<Self>) {
+        self.status = result.err().map(|error| error.to_string().into());
+        cx.notify();
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, window: &mut Window, cx: &mut Context<Self>) {
+        self.line_editor = None;
+        self.status = None;
+        window.focus(&self.focus_handle, cx);
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, window: &mut Window, cx: &mut Context<Self>) {
+        self.edit_line(&EditLine, window, cx);
+    }
+
+    fn render_line(
+        &self,
+        row: usize,
+        line: String,
+        line_number_width: Rems,
+        cx: &mut Context<Self>,
+    ) -> AnyElement {
+        let is_selected = self.selected_row == Some(row);
+        let colors = cx.theme().colors();
+        let content = match &self.line_editor {
+            Some((editing_row, editor)) if *editing_row == row => div()
+                .flex_1()
+                .on_action(cx.listener(Self::confirm_edit))
+                .child(editor.clone())
+                .into_any_element(),
+            _ => {
+                let highlights = self
+                    .active_match
+                    .as_ref()
+                    .filter(|(match_row, range)| {
+                        *match_row == row
+                            && line.is_char_boundary(range.start.min(line.len()))
+                            && line.is_char_boundary(range.end.min(line.len()))
+                    })
+                    .map(|(_, range)| {
+                        (
+                            range.start.min(line.len())..range.end.min(line.len()),
+                            HighlightStyle {
+                                background_color: Some(colors.search_match_background),
+                                ..Default::default()
+                            },
+                        )
+                    });
+                div()
+                    .flex_1()
+                    .whitespace_nowrap()
+                    .child(StyledText::new(line).with_highlights(highlights))
+                    .into_any_element()
+            }
+        };
+
+        h_flex()
+            .id(("line", row))
+            .w_full()
+            .px_2()
+            .gap_3()
+            .when(is_selected, |this| {
+                this.bg(colors.editor_active_line_background)
+            })
+            .child(
+                div()
+                    .w(line_number_width)
+                    .flex_none()
+                    .text_right()
+                    .text_color(if is_selected {
+                        colors.editor_active_line_number
+                    } else {
+                        colors.editor_line_number
+                    })
+                    .child((row + 1).to_string()),
+            )
+            .child(content)
+            .on_click(cx.listener(move |this, event: &ClickEvent, window, cx| {
+                this.select_row(row, cx);
+                if event.click_count() > 1 {
+                    this.start_editing(row, window, cx);
+                } else {
+                    window.focus(&this.focus_handle, cx);
+                }
+            }))
+            .into_any_element()
+    }
+
+    fn render_toolbar(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let large_file = self.large_file.read(cx);
+        let summary = match large_file.text() {
+            Some(text) => format!(
+                "{} lines, {}",
+                text.line_count(),
+                format_file_size(text.len() as u64, false)
+            ),
+            None => "Indexing…".to_string(),
+        };
+        let is_edited = large_file.text().is_some_and(|text| text.is_edited());
+
+        h_flex()
+            .h(rems_from_px(41.))
+            .px_2()
+            .gap_2()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(
+                h_flex()
+                    .w(rems_from_px(280.))
+                    .gap_1()
+                    .child(
+                        Icon::new(IconName::MagnifyingGlass)
+                            .size(IconSize::Small)
+                            .color(Color::Muted),
+                    )
+                    .child(
+                        div()
+                            .flex_1()
+                            .on_action(
+                                cx.listener(|this, _: &menu::Confirm, _, cx| {
+                                    this.search(false, cx)
+                                }),
+                            )
+                            .child(self.query_editor.clone()),
+                    )
+                    .child(
+                        IconButton::new("find-previous", IconName::ChevronLeft)
+                            .icon_size(IconSize::Small)
+                            .tooltip(Tooltip::text("Find Previous"))
+                            .on_click(cx.listener(|this, _, _, cx| this.search(true, cx))),
+                    )
+                    .child(
+                        IconButton::new("find-next", IconName::ChevronRight)
+                            .icon_size(IconSize::Small)
+                            .tooltip(Tooltip::text("Find Next"))
+                            .on_click(cx.listener(|this, _, _, cx| this.search(false, cx))),
+                    ),
+            )
+            .child(
+                div()
+                    .w(rems_from_px(120.))
+                    .on_action(cx.listener(Self::confirm_go_to_line))
+                    .child(self.go_to_line_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .flex_1()
+                    .justify_end()
+                    .gap_2()
+                    .when_some(self.status.clone(), |this, status| {
+                        this.child(Label::new(status).size(LabelSize::Small))
+                    })
+                    .when(is_edited, |this| {
+                        this.child(
+                            Label::new("Unsaved edits")
+                                .size(LabelSize::Small)
+                                .color(Color::Modified),
+                        )
+                    })
+                    .child(
+                        Label::new(summary)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+    }
+}
+
+impl EventEmitter<ItemEvent> for LargeFileView {}
+
+impl Focusable for LargeFileView {
+    fn focus_handle(&self, _: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for LargeFileView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let line_count = self.line_count(cx);
+        let line_number_width = rems(0.6 * line_count.max(1).to_string().len() as f32 + 0.5);
+
+        v_flex()
+            .key_context("LargeFileView")
+            .on_action(cx.listener(Self::find))
+            .on_action(cx.listener(Self::find_next))
+            .on_action(cx.listener(Self::find_previous))
+            .on_action(cx.listener(Self::go_to_line))
+            .on_action(cx.listener(Self::cancel))
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(self.render_toolbar(cx))
+            .child(
+                v_flex()
+                    .track_focus(&self.focus_handle)
+                    .on_action(cx.listener(Self::select_next))
+                    .on_action(cx.listener(Self::select_previous))
+                    .on_action(cx.listener(Self::select_first))
+                    .on_action(cx.listener(Self::select_last))
+                    .on_action(cx.listener(Self::confirm))
+                    .on_action(cx.listener(Self::edit_line))
+                    .on_action(cx.listener(Self::delete_line))
+                    .flex_1()
+                    .size_full()
+                    .font_buffer(cx)
+                    .text_buffer(cx)
+                    .child({
+                        let view = cx.weak_entity();
+                        uniform_list("large-file-lines", line_count, move |range, _, cx| {
+                            let Some(view) = view.upgrade() else {
+                                return Vec::new();
+                            };
+                            view.update(cx, |this, cx| {
+                                let lines = this.text(cx).map_or(Vec::new(), |text| {
+                                    range
+                                        .filter_map(|row| Some((row, text.line(row)?)))
+                                        .collect()
+                                });
+                                lines
+                                    .into_iter()
+                                    .map(|(row, line)| {
+                                        this.render_line(row, line, line_number_width, cx)
+                                    })
+                                    .collect()
+                            })
+                        })
+                        .flex_1()
+                        .size_full()
+                        .track_scroll(&self.scroll_handle)
+                    })
+                    .vertical_scrollbar_for(&self.scroll_handle, window, cx),
+            )
+    }
+}
+
+impl Item for LargeFileView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, f: &mut dyn FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &App,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::ProjectItem),
+    ) {
+        f(self.large_file.entity_id(), self.large_file.read(cx))
+    }
+
+    fn tab_content(&self, params: TabContentParams, _window: &Window, cx: &App) -> AnyElement {
+        Label::new(self.tab_content_text(params.detail.unwrap_or_default(), cx))
+            .single_line()
+            .color(params.text_color())
+            .when(params.preview, |this| this.italic())
+            .into_any_element()
+    }
+
+    fn tab_content_text(&self, _: usize, cx: &App) -> SharedString {
+        self.large_file
+            .read(cx)
+            .abs_path()
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+            .into()
+    }
+
+    fn tab_tooltip_text(&self, cx: &App) -> Option<SharedString> {
+        Some(
+            format!(
+                "{} (opened in large file mode)",
+                self.large_file.read(cx).abs_path().display()
+            )
+            .into(),
+        )
+    }
+
+    fn tab_icon(&self, _: &Window, cx: &App) -> Option<Icon> {
+        ItemSettings::get_global(cx)
+            .file_icons
+            .then(|| FileIcons::get_icon(self.large_file.read(cx).abs_path(), cx))
+            .flatten()
+            .map(Icon::from_path)
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Large File Opened")
+    }
+
+    fn is_dirty(&self, cx: &App) -> bool {
+        project::ProjectItem::is_dirty(self.large_file.read(cx))
+    }
+
+    fn has_conflict(&self, cx: &App) -> bool {
+        self.large_file.read(cx).has_conflict(cx)
+    }
+
+    fn can_save(&self, _: &App) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        _: SaveOptions,
+        _: Entity<Project>,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
+        self.large_file
+            .update(cx, |large_file, cx| large_file.save(cx))
+    }
+
+    fn buffer_kind(&self, _: &App) -> workspace::item::ItemBufferKind {
+        workspace::item::ItemBufferKind::Singleton
+    }
+}
+
+impl ProjectItem for LargeFileView {
+    type Item = LargeFile;
+
+    fn for_project_item(
+        _: Entity<Project>,
+        _: Option<&Pane>,
+        large_file: Entity<LargeFile>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        Self::new(large_file, window, cx)
+    }
+}
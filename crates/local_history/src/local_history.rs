@@ -0,0 +1,737 @@
+//! A timeline of the snapshots that [`LocalHistoryStore`] took of a file, each of which can be
+//! diffed against the file's current contents and restored.
+
+use anyhow::Result;
+use buffer_diff::BufferDiff;
+use editor::{Editor, MultiBuffer};
+use futures::{FutureExt as _, select_biased};
+use gpui::{
+    AnyElement, AnyEntity, App, AppContext as _, Context, Entity, EventEmitter, FocusHandle,
+    Focusable, IntoElement, Render, ScrollStrategy, Subscription, Task, UniformListScrollHandle,
+    WeakEntity, Window, actions, uniform_list,
+};
+use language::{Buffer, BufferEvent};
+use project::{
+    LocalHistoryEntry, LocalHistoryReason, LocalHistoryStore, Project,
+    local_history::LocalHistoryEvent,
+};
+use std::{any::TypeId, path::PathBuf, pin::pin, sync::Arc, time::Duration};
+use time::OffsetDateTime;
+use ui::{ListItem, Tooltip, WithScrollbar, prelude::*};
+use util::ResultExt as _;
+use workspace::{
+    Item, Workspace,
+    item::ItemEvent,
+    notifications::{
+        NotificationId, NotifyTaskExt as _, simple_message_notification::MessageNotification,
+    },
+};
+
+actions!(
+    local_history,
+    [
+        /// Shows the local history of the active file, with a diff of each snapshot against the
+        /// file's current contents.
+        Show,
+        /// Restores the file's contents from the selected snapshot.
+        Restore
+    ]
+);
+
+const RECALCULATE_DIFF_DEBOUNCE: Duration = Duration::from_millis(250);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(LocalHistoryView::register).detach();
+}
+
+pub struct LocalHistoryView {
+    workspace: WeakEntity<Workspace>,
+    project: Entity<Project>,
+    store: Entity<LocalHistoryStore>,
+    buffer: Entity<Buffer>,
+    abs_path: PathBuf,
+    /// The file's snapshots, newest first.
+    entries: Vec<LocalHistoryEntry>,
+    selected_entry: Option<usize>,
+    /// The contents of the selected snapshot, which the diff is computed against.
+    base_text: Option<Arc<str>>,
+    editor: Entity<Editor>,
+    scroll_handle: UniformListScrollHandle,
+    focus_handle: FocusHandle,
+    diff_changes_tx: watch::Sender<()>,
+    _load_entries: Task<()>,
+    _load_base_text: Task<()>,
+    _recalculate_diff: Task<Result<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl LocalHistoryView {
+    fn register(editor: &mut Editor, _window: Option<&mut Window>, cx: &mut Context<Editor>) {
+        let editor_handle = cx.weak_entity();
+        editor
+            .register_action(move |_: &Show, window, cx| {
+                Self::show(&editor_handle, window, cx);
+            })
+            .detach();
+    }
+
+    fn show(editor: &WeakEntity<Editor>, window: &mut Window, cx: &mut App) {
+        let Some((workspace, buffer)) = editor
+            .update(cx, |editor, cx| {
+                Some((
+                    editor.workspace()?,
+                    editor.buffer().read(cx).as_singleton()?,
+                ))
+            })
+            .ok()
+            .flatten()
+        else {
+            return;
+        };
+        let Some(abs_path) = buffer
+            .read(cx)
+            .file()
+            .and_then(|file| file.as_local())
+            .map(|file| file.abs_path(cx))
+        else {
+            workspace.update(cx, |workspace, cx| {
+                workspace.show_notification(NotificationId::unique::<Self>(), cx, |cx| {
+                    cx.new(|cx| {
+                        MessageNotification::new("Local history is only kept for local files.", cx)
+                    })
+                });
+            });
+            return;
+        };
+        let project = workspace.read(cx).project().clone();
+        let Some(store) = project.read(cx).local_history_store() else {
+            return;
+        };
+
+        workspace.update(cx, |workspace, cx| {
+            let existing = workspace
+                .active_pane()
+                .read(cx)
+                .items_of_type::<Self>()
+                .find(|view| view.read(cx).buffer == buffer);
+            if let Some(existing) = existing {
+                workspace.activate_item(&existing, true, true, window, cx);
+                return;
+            }
+
+            let view = cx.new(|cx| {
+                Self::new(
+                    workspace.weak_handle(),
+                    project,
+                    store,
+                    buffer,
+                    abs_path,
+                    window,
+                    cx,
+                )
+            });
+            workspace.add_item_to_active_pane(Box::new(view), None, true, window, cx);
+        });
+    }
+
+    fn new(
+        workspace: WeakEntity<Workspace>,
+        project: Entity<Project>,
+        store: Entity<LocalHistoryStore>,
+        buffer: Entity<Buffer>,
+        abs_path: PathBuf,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let snapshot = buffer.read(cx).snapshot();
+        let languages = project.read(cx).languages().clone();
+        let diff = cx.new(|cx| {
+            let mut diff = BufferDiff::new(&snapshot.text, cx);
+            diff.language_changed(snapshot.language().cloned(), Some(languages), cx);
+            diff
+        });
+        let multibuffer = cx.new(|cx| {
+            let mut multibuffer = MultiBuffer::singleton(buffer.clone(), cx);
+            multibuffer.add_diff(diff.clone(), cx);
+            multibuffer
+        });
+        let editor = cx.new(|cx| {
+            let mut editor =
+                Editor::for_multibuffer(multibuffer, Some(project.clone()), window, cx);
+            editor.start_temporary_diff_override();
+            editor.disable_diagnostics(cx);
+            editor.set_expand_all_diff_hunks(cx);
+            editor.set_render_diff_hunk_controls(
+                Arc::new(|_, _, _, _, _, _, _, _| gpui::Empty.into_any_element()),
+                cx,
+            );
+            editor
+        });
+
+        let (diff_changes_tx, mut diff_changes_rx) = watch::channel(());
+        let subscriptions = vec![
+            cx.subscribe(&buffer, |this, _, event, _| match event {
+                BufferEvent::Edited { .. }
+                | BufferEvent::LanguageChanged(_)
+                | BufferEvent::Reparsed => {
+                    this.diff_changes_tx.send(()).ok();
+                }
+                _ => {}
+            }),
+            cx.subscribe(&store, |this, _, event, cx| match event {
+                LocalHistoryEvent::EntriesChanged(abs_path) => {
+                    if *abs_path == this.abs_path {
+                        this.load_entries(cx);
+                    }
+                }
+            }),
+        ];
+
+        let recalculate_diff = cx.spawn(async move |this, cx| {
+            while diff_changes_rx.recv().await.is_ok() {
+                loop {
+                    let mut timer = cx
+                        .background_executor()
+                        .timer(RECALCULATE_DIFF_DEBOUNCE)
+                        .fuse();
+                    let mut recv = pin!(diff_changes_rx.recv().fuse());
+                    select_biased! {
+                        _ = timer => break,
+                        _ = recv => continue,
+                    }
+                }
+
+                let (base_text, snapshot) = this.update(cx, |this, cx| {
+                    (this.base_text.clone(), this.buffer.read(cx).snapshot())
+                })?;
+                let Some(base_text) = base_text else {
+                    continue;
+                };
+                diff.update(cx, |diff, cx| {
+                    diff.set_base_text(
+                        Some(base_text),
+                        snapshot.language().cloned(),
+                        snapshot.text.clone(),
+                        cx,
+                    )
+                })
+                .await
+                .ok();
+            }
+            Ok(())
+        });
+
+        let mut this = Self {
+            workspace,
+            project,
+            store,
+            buffer,
+            abs_path,
+            entries: Vec::new(),
+            selected_entry: None,
+            base_text: None,
+            editor,
+            scroll_handle: UniformListScrollHandle::new(),
+            focus_handle: cx.focus_handle(),
+            diff_changes_tx,
+            _load_entries: Task::ready(()),
+            _load_base_text: Task::ready(()),
+            _recalculate_diff: recalculate_diff,
+            _subscriptions: subscriptions,
+        };
+        this.load_entries(cx);
+        this
+    }
+
+    fn load_entries(&mut self, cx: &mut Context<Self>) {
+        let entries = self.store.read(cx).entries(&self.abs_path, cx);
+        self._load_entries = cx.spawn(async move |this, cx| {
+            let Some(mut entries) = entries.await.log_err() else {
+                return;
+            };
+            entries.reverse();
+            this.update(cx, |this, cx| {
+                let selected_id = this.selected_entry().map(|entry| entry.id);
+                this.entries = entries;
+                let ix = selected_id
+                    .and_then(|id| this.entries.iter().position(|entry| entry.id == id))
+                    .or((!this.entries.is_empty()).then_some(0));
+                this.select_ix(ix, cx);
+            })
+            .ok();
+        });
+    }
+
+    fn selected_entry(&self) -> Option<&LocalHistoryEntry> {
+        self.entries.get(self.selected_entry?)
+    }
+
+    fn select_ix(&mut self, ix: Option<usize>, cx: &mut Context<Self>) {
+        let previous_id = self.selected_entry().map(|entry| entry.id);
+        self.selected_entry = ix;
+        if let Some(ix) = ix {
+            self.scroll_handle.scroll_to_item(ix, ScrollStrategy::Top);
+        }
+        cx.notify();
+
+        let Some(entry) = self.selected_entry().cloned() else {
+            return;
+        };
+        if previous_id == Some(entry.id) && self.base_text.is_some() {
+            return;
+        }
+        let text = self.store.read(cx).load_entry(&self.abs_path, &entry, cx);
+        self._load_base_text = cx.spawn(async move |this, cx| {
+            let Some(text) = text.await.log_err() else {
+                return;
+            };
+            this.update(cx, |this, _| {
+                this.base_text = Some(text.into());
+                this.diff_changes_tx.send(()).ok();
+            })
+            .ok();
+        });
+    }
+
+    fn select_next(&mut self, _: &menu::SelectNext, _: &mut Window, cx: &mut Context<Self>) {
+        let entry_count = self.entries.len();
+        let ix = match self.selected_entry {
+            _ if entry_count == 0 => None,
+            None => Some(0),
+            Some(ix) => Some((ix + 1) % entry_count),
+        };
+        self.select_ix(ix, cx);
+    }
+
+    fn select_previous(
+        &mut self,
+        _: &menu::SelectPrevious,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let entry_count = self.entries.len();
+        let ix = match self.selected_entry {
+            _ if entry_count == 0 => None,
+            None | Some(0) => Some(entry_count - 1),
+            Some(ix) => Some(ix - 1),
+        };
+        self.select_ix(ix, cx);
+    }
+
+    fn select_first(&mut self, _: &menu::SelectFirst, _: &mut Window, cx: &mut Context<Self>) {
+        let ix = (!self.entries.is_empty()).then_some(0);
+        self.select_ix(ix, cx);
+    }
+
+    fn select_last(&mut self, _: &menu::SelectLast, _: &mut Window, cx: &mut Context<Self>) {
+        let ix = self.entries.len().checked_sub(1);
+        self.select_ix(ix, cx);
+    }
+
+    fn restore_selected(&mut self, _: &Restore, window: &mut Window, cx: &mut Context<Self>) {
+        if let Some(ix) = self.selected_entry {
+            self.restore(ix, window, cx);
+        }
+    }
+
+    /// Replaces the buffer's contents with the given snapshot, as a single undoable edit. The
+    /// buffer's current contents are snapshotted first, so the restore can itself be reverted.
+    fn restore(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(entry) = self.entries.get(ix) else {
+            return;
+        };
+        let text = self.store.read(cx).load_entry(&self.abs_path, entry, cx);
+        let project = self.project.clone();
+        let buffer = self.buffer.clone();
+        let task = cx.spawn(async move |_, cx| {
+            let text = text.await?;
+            project.update(cx, |project, cx| {
+                project.record_local_history(&buffer, LocalHistoryReason::BeforeRestore, cx)
+            });
+            let diff = buffer
+                .read_with(cx, |buffer, cx| buffer.diff(text, cx))
+                .await;
+            buffer.update(cx, |buffer, cx| {
+                buffer.finalize_last_transaction();
+                buffer.apply_diff(diff, cx);
+                buffer.finalize_last_transaction();
+            });
+            anyhow::Ok(())
+        });
+        task.detach_and_notify_err(self.workspace.clone(), window, cx);
+    }
+
+    fn render_entry(&self, ix: usize, entry: &LocalHistoryEntry, cx: &Context<Self>) -> AnyElement {
+        let timestamp = time_format::format_localized_timestamp(
+            OffsetDateTime::from(entry.timestamp()),
+            OffsetDateTime::now_utc(),
+            time::UtcOffset::current_local_offset().unwrap_or(time::UtcOffset::UTC),
+            time_format::TimestampFormat::Relative,
+        );
+        let is_selected = Some(ix) == self.selected_entry;
+
+        ListItem::new(("local-history-entry", ix))
+            .toggle_state(is_selected)
+            .child(
+                h_flex()
+                    .h_8()
+                    .w_full()
+                    .pl_1()
+                    .pr_1()
+                    .gap_2()
+                    .justify_between()
+                    .child(
+                        v_flex()
+                            .min_w_0()
+                            .child(
+                                Label::new(entry.reason.label())
+                                    .size(LabelSize::Small)
+                                    .truncate(),
+                            )
+                            .child(
+                                Label::new(timestamp)
+                                    .size(LabelSize::XSmall)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .child(
+                        IconButton::new(("restore-entry", ix), IconName::HistoryRerun)
+                            .icon_size(IconSize::Small)
+                            .tooltip(Tooltip::text("Restore This Snapshot"))
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                this.restore(ix, window, cx);
+                            })),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, _, cx| {
+                this.select_ix(Some(ix), cx);
+            }))
+            .into_any_element()
+    }
+
+    fn file_name(&self) -> String {
+        self.abs_path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "File".to_string())
+    }
+}
+
+impl EventEmitter<ItemEvent> for LocalHistoryView {}
+
+impl Focusable for LocalHistoryView {
+    fn focus_handle(&self, _cx: &App) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for LocalHistoryView {
+    fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        let entry_count = self.entries.len();
+
+        h_flex()
+            .id("local_history_view")
+            .key_context("LocalHistoryView")
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                v_flex()
+                    .track_focus(&self.focus_handle)
+                    .on_action(cx.listener(Self::select_next))
+                    .on_action(cx.listener(Self::select_previous))
+                    .on_action(cx.listener(Self::select_first))
+                    .on_action(cx.listener(Self::select_last))
+                    .on_action(cx.listener(Self::restore_selected))
+                    .w(rems_from_px(260.))
+                    .h_full()
+                    .flex_none()
+                    .border_r_1()
+                    .border_color(cx.theme().colors().border_variant)
+                    .child(
+                        h_flex()
+                            .h(rems_from_px(41.))
+                            .px_3()
+                            .justify_between()
+                            .border_b_1()
+                            .border_color(cx.theme().colors().border_variant)
+                            .child(Label::new(self.file_name()).truncate())
+                            .child(
+                                Label::new(format!("{entry_count} snapshots"))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            ),
+                    )
+                    .map(|this| {
+                        if entry_count == 0 {
+                            this.child(
+                                v_flex().flex_1().items_center().justify_center().child(
+                                    Label::new("No snapshots yet")
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                ),
+                            )
+                        } else {
+                            this.child(
+                                v_flex()
+                                    .flex_1()
+                                    .size_full()
+                                    .child({
+                                        let view = cx.weak_entity();
+                                        uniform_list(
+                                            "local-history-list",
+                                            entry_count,
+                                            move |range, _, cx| {
+                                                let Some(view) = view.upgrade() else {
+                                                    return Vec::new();
+                                                };
+                                                view.update(cx, |this, cx| {
+                                                    range
+                                                        .filter_map(|ix| {
+                                                            let entry = this.entries.get(ix)?;
+                                                            Some(this.render_entry(ix, entry, cx))
+                                                        })
+                                                        .collect()
+                                                })
+                                            },
+                                        )
+                                        .flex_1()
+                                        .size_full()
+                                        .track_scroll(&self.scroll_handle)
+                                    })
+                                    .vertical_scrollbar_for(&self.scroll_handle, window, cx),
+                            )
+                        }
+                    }),
+            )
+            .child(div().flex_1().h_full().child(self.editor.clone()))
+    }
+}
+
+impl Item for LocalHistoryView {
+    type Event = ItemEvent;
+
+    fn to_item_events(event: &Self::Event, f: &mut dyn FnMut(ItemEvent)) {
+        f(*event)
+    }
+
+    fn tab_content_text(&self, _detail: usize, _cx: &App) -> SharedString {
+        format!("Local History: {}", self.file_name()).into()
+    }
+
+    fn tab_tooltip_text(&self, _cx: &App) -> Option<SharedString> {
+        Some(format!("Local history of {}", self.abs_path.display()).into())
+    }
+
+    fn tab_icon(&self, _window: &Window, _cx: &App) -> Option<Icon> {
+        Some(Icon::new(IconName::HistoryRerun))
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("Local History Opened")
+    }
+
+    fn deactivated(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.deactivated(window, cx));
+    }
+
+    fn added_to_workspace(
+        &mut self,
+        _workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        window.focus(&self.focus_handle, cx);
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a Entity<Self>,
+        _: &'a App,
+    ) -> Option<AnyEntity> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.clone().into())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.clone().into())
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use util::path;
+    use workspace::{AppState, MultiWorkspace};
+
+    async fn init_test(
+        cx: &mut TestAppContext,
+    ) -> (Entity<Workspace>, Entity<Project>, &mut VisualTestContext) {
+        cx.update(|cx| {
+            AppState::test(cx);
+            editor::init(cx);
+            init(cx);
+        });
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_file(path!("/dir/file.txt"), b"one".to_vec())
+            .await;
+        let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+        let window = cx.add_window({
+            let project = project.clone();
+            |window, cx| MultiWorkspace::test_new(project, window, cx)
+        });
+        let cx = VisualTestContext::from_window(*window, cx).into_mut();
+        let workspace = window
+            .read_with(cx, |multi_workspace, _| multi_workspace.workspace().clone())
+            .unwrap();
+        (workspace, project, cx)
+    }
+
+    fn open_editor(
+        workspace: &Entity<Workspace>,
+        project: &Entity<Project>,
+        buffer: Entity<Buffer>,
+        cx: &mut VisualTestContext,
+    ) {
+        workspace.update_in(cx, |workspace, window, cx| {
+            let editor = cx.new(|cx| Editor::for_buffer(buffer, Some(project.clone()), window, cx));
+            workspace.add_item_to_active_pane(Box::new(editor), None, true, window, cx);
+        });
+    }
+
+    fn active_view(
+        workspace: &Entity<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> Option<Entity<LocalHistoryView>> {
+        workspace.read_with(cx, |workspace, cx| workspace.active_item_as(cx))
+    }
+
+    #[gpui::test]
+    async fn test_local_history_view(cx: &mut TestAppContext) {
+        let (workspace, project, cx) = init_test(cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/dir/file.txt"), cx)
+            })
+            .await
+            .unwrap();
+        for text in ["two", "three"] {
+            buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+            project
+                .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+                .await
+                .unwrap();
+            cx.run_until_parked();
+        }
+
+        open_editor(&workspace, &project, buffer.clone(), cx);
+        cx.dispatch_action(Show);
+        cx.run_until_parked();
+        let view = active_view(&workspace, cx).expect("local history view is not open");
+
+        // Snapshots are listed newest first, and the newest is selected.
+        view.read_with(cx, |view, _| {
+            assert_eq!(
+                view.entries
+                    .iter()
+                    .map(|entry| entry.len)
+                    .collect::<Vec<_>>(),
+                [5, 3]
+            );
+            assert_eq!(view.selected_entry, Some(0));
+            assert_eq!(view.base_text.as_deref(), Some("three"));
+        });
+
+        view.update_in(cx, |view, window, cx| {
+            view.select_next(&menu::SelectNext, window, cx)
+        });
+        cx.run_until_parked();
+        view.read_with(cx, |view, _| {
+            assert_eq!(view.selected_entry, Some(1));
+            assert_eq!(view.base_text.as_deref(), Some("two"));
+        });
+
+        // Selection wraps around in both directions.
+        view.update_in(cx, |view, window, cx| {
+            view.select_next(&menu::SelectNext, window, cx)
+        });
+        view.read_with(cx, |view, _| assert_eq!(view.selected_entry, Some(0)));
+        view.update_in(cx, |view, window, cx| {
+            view.select_previous(&menu::SelectPrevious, window, cx)
+        });
+        view.read_with(cx, |view, _| assert_eq!(view.selected_entry, Some(1)));
+
+        // Showing the history of the same buffer again activates the existing view.
+        open_editor(&workspace, &project, buffer.clone(), cx);
+        cx.dispatch_action(Show);
+        cx.run_until_parked();
+        assert_eq!(active_view(&workspace, cx), Some(view.clone()));
+        workspace.read_with(cx, |workspace, cx| {
+            assert_eq!(
+                workspace
+                    .active_pane()
+                    .read(cx)
+                    .items_of_type::<LocalHistoryView>()
+                    .count(),
+                1
+            );
+        });
+    }
+
+    #[gpui::test]
+    async fn test_restore_local_history_entry(cx: &mut TestAppContext) {
+        let (workspace, project, cx) = init_test(cx).await;
+        let buffer = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer(path!("/dir/file.txt"), cx)
+            })
+            .await
+            .unwrap();
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+        buffer.update(cx, |buffer, cx| buffer.set_text("two", cx));
+        cx.run_until_parked();
+
+        open_editor(&workspace, &project, buffer.clone(), cx);
+        cx.dispatch_action(Show);
+        cx.run_until_parked();
+        let view = active_view(&workspace, cx).expect("local history view is not open");
+        view.update_in(cx, |view, window, cx| {
+            view.restore_selected(&Restore, window, cx)
+        });
+        cx.run_until_parked();
+        buffer.read_with(cx, |buffer, _| assert_eq!(buffer.text(), "one"));
+
+        // The unsaved contents were snapshotted before the restore, which can be undone.
+        view.read_with(cx, |view, _| {
+            assert_eq!(
+                view.entries
+                    .iter()
+                    .map(|entry| entry.reason)
+                    .collect::<Vec<_>>(),
+                [LocalHistoryReason::BeforeRestore, LocalHistoryReason::Save]
+            );
+        });
+        buffer.update(cx, |buffer, cx| buffer.undo(cx));
+        buffer.read_with(cx, |buffer, _| assert_eq!(buffer.text(), "two"));
+    }
+
+    #[gpui::test]
+    async fn test_local_history_of_untitled_buffer(cx: &mut TestAppContext) {
+        let (workspace, project, cx) = init_test(cx).await;
+        let buffer = project.update(cx, |project, cx| {
+            project.create_local_buffer("untitled", None, false, cx)
+        });
+        open_editor(&workspace, &project, buffer, cx);
+        cx.dispatch_action(Show);
+        cx.run_until_parked();
+        assert!(active_view(&workspace, cx).is_none());
+    }
+}
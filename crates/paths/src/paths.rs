@@ -373,6 +373,30 @@ pub fn languages_dir() -> &'static PathBuf {
     LANGUAGES_DIR.get_or_init(|| data_dir().join("languages"))
 }
 
+/// Returns the path to the search index directory.
+///
+/// This is where trigram indices used to speed up project search are stored.
+pub fn search_index_dir() -> &'static PathBuf {
+    static SEARCH_INDEX_DIR: OnceLock<PathBuf> = OnceLock::new();
+    SEARCH_INDEX_DIR.get_or_init(|| data_dir().join("search_index"))
+}
+
+/// Returns the path to the local history directory.
+///
+/// This is where snapshots of files taken on save are stored.
+pub fn local_history_dir() -> &'static PathBuf {
+    static LOCAL_HISTORY_DIR: OnceLock<PathBuf> = OnceLock::new();
+    LOCAL_HISTORY_DIR.get_or_init(|| data_dir().join("local_history"))
+}
+
+/// Returns the path to the large file edits directory.
+///
+/// This is where unsaved edits to files opened in large file mode are kept.
+pub fn large_file_edits_dir() -> &'static PathBuf {
+    static LARGE_FILE_EDITS_DIR: OnceLock<PathBuf> = OnceLock::new();
+    LARGE_FILE_EDITS_DIR.get_or_init(|| data_dir().join("large_file_edits"))
+}
+
 /// Returns the path to the debug adapters directory
 ///
 /// This is where debug adapters are downloaded to for DAPs that are built-in to Zed.
@@ -0,0 +1,863 @@
+//! Large file mode, for files too large to load into a [`Buffer`](language::Buffer).
+//!
+//! A large file is memory-mapped rather than loaded, and read a line at a time through a sparse
+//! index of line offsets. Reading the mapping of a file that another process truncated faults, so
+//! the file's length and modification time are checked before each read, and the mapping is
+//! replaced with zeroes once they change. Saving replaces the file rather than writing it in place,
+//! which leaves the mapped file intact. Edits are limited to replacing and deleting whole lines.
+//! They're kept in a piece table over the mapping, which is spilled to a sidecar file under
+//! [`paths::large_file_edits_dir`] until the file is saved, so that unsaved edits survive a
+//! restart.
+
+use std::{
+    borrow::Cow,
+    fs::File,
+    io::Read,
+    ops::Range,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    time::SystemTime,
+};
+
+use anyhow::{Context as _, Result};
+use fs::{Fs, MTime, RemoveOptions, RenameOptions};
+use futures::io::AllowStdIo;
+use gpui::{App, AppContext as _, AsyncApp, Context, Entity, EventEmitter, Task, WeakEntity};
+use memchr::memmem;
+use memmap2::{Mmap, MmapOptions};
+use serde::{Deserialize, Serialize};
+use settings::Settings as _;
+use sha2::{Digest as _, Sha256};
+use util::ResultExt as _;
+use worktree::WorktreeSettings;
+
+use crate::{Project, ProjectEntryId, ProjectItem, ProjectPath, image_store::is_image_file};
+
+/// The index records the offset of every this many lines.
+const LINES_PER_CHECKPOINT: usize = 1024;
+/// The most text that edits can add before the file has to be saved.
+const MAX_ADDED_LEN: usize = 16 * 1024 * 1024;
+/// Lines longer than this many bytes are truncated when read with [`LargeFileText::line`].
+pub const MAX_LINE_LEN: usize = 4096;
+
+pub type LargeFileBytes = Arc<dyn AsRef<[u8]> + Send + Sync>;
+
+/// The offsets at which every [`LINES_PER_CHECKPOINT`]th line of a text starts. Other lines are
+/// found by scanning forward from the closest preceding checkpoint.
+struct LineIndex {
+    checkpoints: Vec<usize>,
+    newlines: usize,
+}
+
+impl LineIndex {
+    fn new(text: &[u8]) -> Self {
+        let mut checkpoints = vec![0];
+        let mut newlines = 0;
+        for ix in memchr::memchr_iter(b'\n', text) {
+            newlines += 1;
+            if newlines % LINES_PER_CHECKPOINT == 0 {
+                checkpoints.push(ix + 1);
+            }
+        }
+        Self {
+            checkpoints,
+            newlines,
+        }
+    }
+
+    /// Returns the offset at which the given row starts.
+    fn line_start(&self, text: &[u8], row: usize) -> Option<usize> {
+        if row > self.newlines {
+            return None;
+        }
+        let start = self.checkpoints[row / LINES_PER_CHECKPOINT];
+        match row % LINES_PER_CHECKPOINT {
+            0 => Some(start),
+            skip => memchr::memchr_iter(b'\n', &text[start..])
+                .nth(skip - 1)
+                .map(|ix| start + ix + 1),
+        }
+    }
+
+    /// Returns the number of newlines before the given offset.
+    fn row_for_offset(&self, text: &[u8], offset: usize) -> usize {
+        let checkpoint = self.checkpoints.partition_point(|&start| start <= offset) - 1;
+        checkpoint * LINES_PER_CHECKPOINT
+            + memchr::memchr_iter(b'\n', &text[self.checkpoints[checkpoint]..offset]).count()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum PieceSource {
+    Original,
+    Added,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Piece {
+    source: PieceSource,
+    range: Range<usize>,
+    newlines: usize,
+}
+
+/// The text of a large file: its mapping, with any unsaved edits applied.
+///
+/// Cloning is cheap, so that a snapshot of the text can be searched in the background.
+#[derive(Clone)]
+pub struct LargeFileText {
+    original: LargeFileBytes,
+    index: Arc<LineIndex>,
+    added: Arc<String>,
+    pieces: Vec<Piece>,
+    len: usize,
+    line_ending: &'static str,
+}
+
+impl LargeFileText {
+    /// Indexes the given text. This reads all of it, so should be done in the background.
+    pub fn new(original: LargeFileBytes) -> Self {
+        let bytes = (*original).as_ref();
+        let index = LineIndex::new(bytes);
+        let len = bytes.len();
+        let line_ending = match memchr::memchr(b'\n', bytes) {
+            Some(ix) if ix > 0 && bytes[ix - 1] == b'\r' => "\r\n",
+            _ => "\n",
+        };
+        let pieces = Self::original_pieces(len, index.newlines);
+        Self {
+            original,
+            index: Arc::new(index),
+            added: Arc::default(),
+            pieces,
+            len,
+            line_ending,
+        }
+    }
+
+    fn original_pieces(len: usize, newlines: usize) -> Vec<Piece> {
+        if len == 0 {
+            Vec::new()
+        } else {
+            vec![Piece {
+                source: PieceSource::Original,
+                range: 0..len,
+                newlines,
+            }]
+        }
+    }
+
+    fn original(&self) -> &[u8] {
+        (*self.original).as_ref()
+    }
+
+    fn piece_bytes(&self, piece: &Piece) -> &[u8] {
+        match piece.source {
+            PieceSource::Original => &self.original()[piece.range.clone()],
+            PieceSource::Added => &self.added.as_bytes()[piece.range.clone()],
+        }
+    }
+
+    /// Returns the part of the piece in the given range, relative to the piece's start.
+    fn slice_piece(&self, piece: &Piece, range: Range<usize>) -> Piece {
+        let range = piece.range.start + range.start..piece.range.start + range.end;
+        if range == piece.range {
+            return piece.clone();
+        }
+        let newlines = match piece.source {
+            PieceSource::Original => {
+                self.index.row_for_offset(self.original(), range.end)
+                    - self.index.row_for_offset(self.original(), range.start)
+            }
+            PieceSource::Added => {
+                memchr::memchr_iter(b'\n', &self.added.as_bytes()[range.clone()]).count()
+            }
+        };
+        Piece {
+            source: piece.source,
+            range,
+            newlines,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.pieces
+            .iter()
+            .map(|piece| piece.newlines)
+            .sum::<usize>()
+            + 1
+    }
+
+    /// Whether the text differs from the file as it was mapped.
+    pub fn is_edited(&self) -> bool {
+        self.pieces != Self::original_pieces(self.original().len(), self.index.newlines)
+    }
+
+    /// Returns the contents of the given range, a piece at a time.
+    pub fn chunks(&self, range: Range<usize>) -> impl Iterator<Item = &[u8]> {
+        let mut piece_start = 0;
+        self.pieces.iter().filter_map(move |piece| {
+            let start = piece_start;
+            let end = start + piece.range.len();
+            piece_start = end;
+            let range = range.start.max(start)..range.end.min(end);
+            (range.start < range.end)
+                .then(|| &self.piece_bytes(piece)[range.start - start..range.end - start])
+        })
+    }
+
+    /// Returns the offset at which the given row starts.
+    pub fn line_start(&self, row: usize) -> Option<usize> {
+        if row == 0 {
+            return Some(0);
+        }
+        let mut piece_start = 0;
+        let mut piece_row = 0;
+        for piece in &self.pieces {
+            if row <= piece_row + piece.newlines {
+                // The row starts after this piece's `skip`th newline.
+                let skip = row - piece_row;
+                let start_in_piece = match piece.source {
+                    PieceSource::Original => {
+                        let first_row = self
+                            .index
+                            .row_for_offset(self.original(), piece.range.start);
+                        self.index.line_start(self.original(), first_row + skip)?
+                            - piece.range.start
+                    }
+                    PieceSource::Added => {
+                        memchr::memchr_iter(b'\n', self.piece_bytes(piece)).nth(skip - 1)? + 1
+                    }
+                };
+                return Some(piece_start + start_in_piece);
+            }
+            piece_start += piece.range.len();
+            piece_row += piece.newlines;
+        }
+        None
+    }
+
+    /// Returns the range of the given row, excluding its line ending.
+    pub fn line_range(&self, row: usize) -> Option<Range<usize>> {
+        let start = self.line_start(row)?;
+        let end = self
+            .line_start(row + 1)
+            .map_or(self.len, |next_start| next_start - 1);
+        Some(start..end)
+    }
+
+    /// Returns the contents of the given row, truncated to [`MAX_LINE_LEN`] bytes.
+    pub fn line(&self, row: usize) -> Option<String> {
+        let range = self.line_range(row)?;
+        let mut line = Vec::new();
+        for chunk in self.chunks(range.start..range.end.min(range.start + MAX_LINE_LEN)) {
+            line.extend_from_slice(chunk);
+        }
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        Some(String::from_utf8_lossy(&line).into_owned())
+    }
+
+    /// Returns the row that contains the given offset.
+    pub fn row_for_offset(&self, offset: usize) -> usize {
+        let mut piece_start = 0;
+        let mut row = 0;
+        for piece in &self.pieces {
+            let piece_end = piece_start + piece.range.len();
+            if offset < piece_end {
+                return row + self.slice_piece(piece, 0..offset - piece_start).newlines;
+            }
+            piece_start = piece_end;
+            row += piece.newlines;
+        }
+        row
+    }
+
+    /// Returns the range of the first occurrence of the query that starts at or after the given
+    /// offset or, when searching backwards, the last occurrence that ends before it.
+    ///
+    /// Queries can't contain newlines, so that matches never span pieces: edits always start
+    /// and end at line boundaries.
+    pub fn find(&self, query: &str, offset: usize, backwards: bool) -> Option<Range<usize>> {
+        if query.is_empty() || query.contains('\n') {
+            return None;
+        }
+        let mut piece_starts = Vec::with_capacity(self.pieces.len());
+        let mut piece_start = 0;
+        for piece in &self.pieces {
+            piece_starts.push(piece_start);
+            piece_start += piece.range.len();
+        }
+        let pieces = self.pieces.iter().zip(piece_starts);
+
+        let start = if backwards {
+            let finder = memmem::FinderRev::new(query.as_bytes());
+            pieces.rev().find_map(|(piece, piece_start)| {
+                let end = offset.checked_sub(piece_start)?.min(piece.range.len());
+                Some(piece_start + finder.rfind(&self.piece_bytes(piece)[..end])?)
+            })
+        } else {
+            let finder = memmem::Finder::new(query.as_bytes());
+            pieces
+                .filter(|(piece, piece_start)| piece_start + piece.range.len() > offset)
+                .find_map(|(piece, piece_start)| {
+                    let skip = offset.saturating_sub(piece_start);
+                    Some(piece_start + skip + finder.find(&self.piece_bytes(piece)[skip..])?)
+                })
+        }?;
+        Some(start..start + query.len())
+    }
+
+    /// Replaces the given range with the given text. Ranges are only ever line boundaries, which
+    /// [`Self::find`] relies on.
+    fn edit(&mut self, range: Range<usize>, new_text: &str) -> Result<()> {
+        anyhow::ensure!(
+            range.start <= range.end && range.end <= self.len,
+            "edit range {range:?} is out of bounds"
+        );
+        anyhow::ensure!(
+            self.added.len() + new_text.len() <= MAX_ADDED_LEN,
+            "too many unsaved edits, save the file before making more"
+        );
+
+        let mut before = Vec::new();
+        let mut after = Vec::new();
+        let mut piece_start = 0;
+        for piece in &self.pieces {
+            let piece_end = piece_start + piece.range.len();
+            if piece_start < range.start {
+                before.push(self.slice_piece(piece, 0..range.start.min(piece_end) - piece_start));
+            }
+            if piece_end > range.end {
+                after.push(self.slice_piece(
+                    piece,
+                    range.end.max(piece_start) - piece_start..piece.range.len(),
+                ));
+            }
+            piece_start = piece_end;
+        }
+
+        let added = Arc::make_mut(&mut self.added);
+        let inserted = (!new_text.is_empty()).then(|| {
+            let start = added.len();
+            added.push_str(new_text);
+            Piece {
+                source: PieceSource::Added,
+                range: start..added.len(),
+                newlines: new_text.matches('\n').count(),
+            }
+        });
+        self.pieces = before.into_iter().chain(inserted).chain(after).collect();
+        self.len = self.len - range.len() + new_text.len();
+        Ok(())
+    }
+
+    /// Replaces the given rows with the given text, which may span multiple lines.
+    pub fn replace_lines(&mut self, rows: Range<usize>, new_text: &str) -> Result<()> {
+        anyhow::ensure!(rows.start < rows.end, "no rows to replace");
+        let start = self.line_start(rows.start).context("row out of range")?;
+        let mut new_text = if self.line_ending == "\n" {
+            Cow::Borrowed(new_text)
+        } else {
+            Cow::Owned(new_text.replace('\n', self.line_ending))
+        };
+        let end = match self.line_start(rows.end) {
+            Some(end) => {
+                new_text.to_mut().push_str(self.line_ending);
+                end
+            }
+            None => self.len,
+        };
+        self.edit(start..end, &new_text)
+    }
+
+    /// Deletes the given rows, including their line endings.
+    pub fn delete_lines(&mut self, rows: Range<usize>) -> Result<()> {
+        anyhow::ensure!(rows.start < rows.end, "no rows to delete");
+        let start = self.line_start(rows.start).context("row out of range")?;
+        match self.line_start(rows.end) {
+            Some(end) => self.edit(start..end, ""),
+            // The last row has no line ending, so delete the one before the deleted rows.
+            None => {
+                let line_ending_start = start
+                    .checked_sub(self.line_ending.len())
+                    .filter(|_| rows.start > 0)
+                    .unwrap_or(0);
+                self.edit(line_ending_start..self.len, "")
+            }
+        }
+    }
+
+    fn edits(&self) -> (String, Vec<Piece>) {
+        (self.added.as_ref().clone(), self.pieces.clone())
+    }
+
+    fn restore_edits(&mut self, added: String, pieces: Vec<Piece>) -> Result<()> {
+        let original_len = self.original().len();
+        for piece in &pieces {
+            let source_len = match piece.source {
+                PieceSource::Original => original_len,
+                PieceSource::Added => added.len(),
+            };
+            anyhow::ensure!(
+                piece.range.start <= piece.range.end && piece.range.end <= source_len,
+                "invalid piece {piece:?}"
+            );
+        }
+        self.added = Arc::new(added);
+        self.len = pieces.iter().map(|piece| piece.range.len()).sum();
+        self.pieces = pieces
+            .into_iter()
+            .map(|mut piece| {
+                piece.newlines = memchr::memchr_iter(b'\n', self.piece_bytes(&piece)).count();
+                piece
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Writes the text, with its edits applied, to the given writer.
+    pub fn write_to(&self, writer: &mut impl std::io::Write) -> std::io::Result<()> {
+        for chunk in self.chunks(0..self.len) {
+            writer.write_all(chunk)?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the text, with its edits applied, from the start.
+struct LargeFileTextReader {
+    text: LargeFileText,
+    offset: usize,
+}
+
+impl Read for LargeFileTextReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let end = self.text.len.min(self.offset + buf.len());
+        let mut read = 0;
+        for chunk in self.text.chunks(self.offset..end) {
+            buf[read..read + chunk.len()].copy_from_slice(chunk);
+            read += chunk.len();
+        }
+        self.offset += read;
+        Ok(read)
+    }
+}
+
+/// Unsaved edits to a large file, as persisted in its sidecar file.
+#[derive(Serialize, Deserialize)]
+struct SidecarEdits {
+    path: PathBuf,
+    /// The length and modification time of the file when it was edited. The edits are discarded
+    /// if the file has changed since.
+    original_len: usize,
+    original_mtime: MTime,
+    added: String,
+    pieces: Vec<Piece>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LargeFileEvent {
+    /// The file's text has been indexed, and can be read and edited.
+    Loaded,
+    Edited,
+    Saved,
+}
+
+pub struct LargeFile {
+    project: WeakEntity<Project>,
+    fs: Arc<dyn Fs>,
+    project_path: ProjectPath,
+    abs_path: PathBuf,
+    entry_id: Option<ProjectEntryId>,
+    /// The modification time of the file when it was mapped.
+    mtime: MTime,
+    text: Option<LargeFileText>,
+    /// Whether the file is being (re)indexed. The text can't be edited until it's done.
+    loading: bool,
+    _load: Task<()>,
+    _write_sidecar: Task<()>,
+}
+
+impl EventEmitter<LargeFileEvent> for LargeFile {}
+
+impl LargeFile {
+    fn open(
+        project: &Entity<Project>,
+        project_path: ProjectPath,
+        abs_path: PathBuf,
+        entry_id: Option<ProjectEntryId>,
+        cx: &mut App,
+    ) -> Task<Result<Entity<Self>>> {
+        let fs = project.read(cx).fs().clone();
+        let project = project.downgrade();
+        cx.spawn(async move |cx| {
+            let (original, mtime) = map_file(&fs, &abs_path, cx).await?;
+            Ok(cx.new(|cx| {
+                let mut this = Self {
+                    project,
+                    fs,
+                    project_path,
+                    abs_path,
+                    entry_id,
+                    mtime,
+                    text: None,
+                    loading: true,
+                    _load: Task::ready(()),
+                    _write_sidecar: Task::ready(()),
+                };
+                this.load(original, cx);
+                this
+            }))
+        })
+    }
+
+    /// Indexes the mapping in the background, keeping the current text readable until done.
+    fn load(&mut self, original: LargeFileBytes, cx: &mut Context<Self>) {
+        self.loading = true;
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        let mtime = self.mtime;
+        self._load = cx.spawn(async move |this, cx| {
+            let text = cx
+                .background_spawn(async move {
+                    let mut text = LargeFileText::new(original);
+                    if let Some(edits) = read_sidecar(&fs, &abs_path).await.log_err().flatten()
+                        && edits.original_len == text.original().len()
+                        && edits.original_mtime == mtime
+                    {
+                        text.restore_edits(edits.added, edits.pieces).log_err();
+                    }
+                    text
+                })
+                .await;
+            this.update(cx, |this, cx| {
+                this.text = Some(text);
+                this.loading = false;
+                cx.emit(LargeFileEvent::Loaded);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    pub fn abs_path(&self) -> &Path {
+        &self.abs_path
+    }
+
+    /// Returns the file's text, once it's been indexed.
+    pub fn text(&self) -> Option<&LargeFileText> {
+        self.text.as_ref()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading
+    }
+
+    /// Whether the file has changed on disk since it was opened or last saved, while there are
+    /// unsaved edits that saving would overwrite those changes with.
+    pub fn has_conflict(&self, cx: &App) -> bool {
+        ProjectItem::is_dirty(self)
+            && self
+                .project
+                .upgrade()
+                .and_then(|project| project.read(cx).entry_for_path(&self.project_path, cx))
+                .and_then(|entry| entry.mtime)
+                .is_some_and(|mtime| mtime.bad_is_greater_than(self.mtime))
+    }
+
+    /// Replaces the given rows with the given text, which may span multiple lines.
+    pub fn replace_lines(
+        &mut self,
+        rows: Range<usize>,
+        new_text: &str,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        self.edit(|text| text.replace_lines(rows, new_text), cx)
+    }
+
+    /// Deletes the given rows.
+    pub fn delete_lines(&mut self, rows: Range<usize>, cx: &mut Context<Self>) -> Result<()> {
+        self.edit(|text| text.delete_lines(rows), cx)
+    }
+
+    fn edit(
+        &mut self,
+        edit: impl FnOnce(&mut LargeFileText) -> Result<()>,
+        cx: &mut Context<Self>,
+    ) -> Result<()> {
+        anyhow::ensure!(!self.loading, "the file is still being indexed");
+        let text = self
+            .text
+            .as_mut()
+            .context("the file is still being indexed")?;
+        edit(text)?;
+
+        let (added, pieces) = text.edits();
+        let edits = SidecarEdits {
+            path: self.abs_path.clone(),
+            original_len: text.original().len(),
+            original_mtime: self.mtime,
+            added,
+            pieces,
+        };
+        let fs = self.fs.clone();
+        self._write_sidecar = cx.background_spawn(async move {
+            write_sidecar(&fs, &edits).await.log_err();
+        });
+        cx.emit(LargeFileEvent::Edited);
+        cx.notify();
+        Ok(())
+    }
+
+    /// Writes the edited text to a temporary file that then replaces the file, then maps and
+    /// indexes the file again.
+    ///
+    /// The file can't be written in place, since the text is read from its mapping. Symlinks are
+    /// followed, so that the file they point to is replaced rather than the symlink. Callers should
+    /// check [`Self::has_conflict`] first.
+    pub fn save(&mut self, cx: &mut Context<Self>) -> Task<Result<()>> {
+        let Some(text) = self.text.clone().filter(|text| text.is_edited()) else {
+            return Task::ready(Ok(()));
+        };
+        if self.loading {
+            return Task::ready(Err(anyhow::anyhow!("the file is still being indexed")));
+        }
+        let fs = self.fs.clone();
+        let abs_path = self.abs_path.clone();
+        self._write_sidecar = Task::ready(());
+        cx.spawn(async move |this, cx| {
+            cx.background_spawn({
+                let fs = fs.clone();
+                let abs_path = abs_path.clone();
+                async move {
+                    let target = fs.canonicalize(&abs_path).await?;
+                    let file_name = target
+                        .file_name()
+                        .with_context(|| format!("{target:?} has no file name"))?;
+                    let temp_path = target.with_file_name(format!(
+                        ".{}.{}.tmp",
+                        file_name.to_string_lossy(),
+                        std::process::id()
+                    ));
+                    let mut reader = AllowStdIo::new(LargeFileTextReader { text, offset: 0 });
+                    let replaced = async {
+                        fs.create_file_with(&temp_path, Pin::new(&mut reader))
+                            .await?;
+                        fs.rename(
+                            &temp_path,
+                            &target,
+                            RenameOptions {
+                                overwrite: true,
+                                ..RenameOptions::default()
+                            },
+                        )
+                        .await
+                    }
+                    .await;
+                    if replaced.is_err() {
+                        fs.remove_file(
+                            &temp_path,
+                            RemoveOptions {
+                                ignore_if_not_exists: true,
+                                ..RemoveOptions::default()
+                            },
+                        )
+                        .await
+                        .log_err();
+                    }
+                    replaced.with_context(|| format!("saving {abs_path:?}"))?;
+                    remove_sidecar(&fs, &abs_path).await.log_err();
+                    anyhow::Ok(())
+                }
+            })
+            .await?;
+            let (original, mtime) = map_file(&fs, &abs_path, cx).await?;
+            this.update(cx, |this, cx| {
+                this.mtime = mtime;
+                this.load(original, cx);
+                cx.emit(LargeFileEvent::Saved);
+            })
+        })
+    }
+}
+
+impl ProjectItem for LargeFile {
+    fn try_open(
+        project: &Entity<Project>,
+        path: &ProjectPath,
+        cx: &mut App,
+    ) -> Option<Task<Result<Entity<Self>>>> {
+        if !is_large_file(project, path, cx) {
+            return None;
+        }
+        let abs_path = project.read(cx).absolute_path(path, cx)?;
+        let entry_id = project
+            .read(cx)
+            .entry_for_path(path, cx)
+            .map(|entry| entry.id);
+        Some(Self::open(project, path.clone(), abs_path, entry_id, cx))
+    }
+
+    fn entry_id(&self, _: &App) -> Option<ProjectEntryId> {
+        self.entry_id
+    }
+
+    fn project_path(&self, _: &App) -> Option<ProjectPath> {
+        Some(self.project_path.clone())
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.text.as_ref().is_some_and(|text| text.is_edited())
+    }
+}
+
+/// Whether the file at the given path should be opened in large file mode, rather than loaded
+/// into a buffer. Only files in local projects are.
+pub fn is_large_file(project: &Entity<Project>, path: &ProjectPath, cx: &App) -> bool {
+    let threshold = WorktreeSettings::get(Some(path.into()), cx).large_file_threshold;
+    threshold > 0
+        && project.read(cx).is_local()
+        && project
+            .read(cx)
+            .entry_for_path(path, cx)
+            .is_some_and(|entry| entry.is_file() && entry.size >= threshold)
+        && !is_image_file(project, path, cx)
+}
+
+/// A mapped file, which reads as zeroes once the file's length or modification time changes.
+struct MappedFile {
+    mmap: Mmap,
+    file: File,
+    len: u64,
+    modified: Option<SystemTime>,
+    /// Replaces the mapping once the file changed.
+    zeroes: OnceLock<Mmap>,
+}
+
+impl MappedFile {
+    fn is_unchanged(&self) -> bool {
+        self.file.metadata().is_ok_and(|metadata| {
+            metadata.len() == self.len && metadata.modified().ok() == self.modified
+        })
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    fn as_ref(&self) -> &[u8] {
+        if self.zeroes.get().is_none() && self.is_unchanged() {
+            return &self.mmap;
+        }
+        self.zeroes.get_or_init(|| {
+            log::warn!("large file changed while it was open");
+            MmapOptions::new()
+                .len(self.mmap.len())
+                .map_anon()
+                .and_then(|zeroes| zeroes.make_read_only())
+                .expect("failed to map zeroes in place of a changed file")
+        })
+    }
+}
+
+/// Maps the file, returning its modification time.
+async fn map_file(
+    fs: &Arc<dyn Fs>,
+    abs_path: &Path,
+    cx: &AsyncApp,
+) -> Result<(LargeFileBytes, MTime)> {
+    let mtime = file_mtime(fs, abs_path).await?;
+    let bytes = if fs.is_fake() {
+        Arc::new(fs.load_bytes(abs_path).await?) as LargeFileBytes
+    } else {
+        cx.background_spawn({
+            let abs_path = abs_path.to_path_buf();
+            async move { map_real_file(&abs_path) }
+        })
+        .await?
+    };
+    anyhow::ensure!(
+        file_mtime(fs, abs_path).await? == mtime,
+        "{abs_path:?} changed while it was being read"
+    );
+    Ok((bytes, mtime))
+}
+
+async fn file_mtime(fs: &Arc<dyn Fs>, abs_path: &Path) -> Result<MTime> {
+    let metadata = fs
+        .metadata(abs_path)
+        .await?
+        .with_context(|| format!("{abs_path:?} doesn't exist"))?;
+    Ok(metadata.mtime)
+}
+
+fn map_real_file(abs_path: &Path) -> Result<LargeFileBytes> {
+    let file = File::open(abs_path).with_context(|| format!("opening {abs_path:?}"))?;
+    let metadata = file.metadata()?;
+    if metadata.len() == 0 {
+        return Ok(Arc::new(Vec::new()));
+    }
+    // SAFETY: the mapping faults if another process truncates the file, which is why it's only
+    // read through `MappedFile`, which checks that the file is unchanged first.
+    let mmap = unsafe { Mmap::map(&file) }.with_context(|| format!("mapping {abs_path:?}"))?;
+    Ok(Arc::new(MappedFile {
+        mmap,
+        file,
+        len: metadata.len(),
+        modified: metadata.modified().ok(),
+        zeroes: OnceLock::new(),
+    }))
+}
+
+fn sidecar_path(abs_path: &Path) -> PathBuf {
+    let digest = Sha256::digest(abs_path.to_string_lossy().as_bytes());
+    paths::large_file_edits_dir().join(format!("{digest:x}.json"))
+}
+
+async fn read_sidecar(fs: &Arc<dyn Fs>, abs_path: &Path) -> Result<Option<SidecarEdits>> {
+    let path = sidecar_path(abs_path);
+    if !fs.is_file(&path).await {
+        return Ok(None);
+    }
+    let edits: SidecarEdits = serde_json::from_slice(&fs.load_bytes(&path).await?)?;
+    Ok((edits.path == abs_path).then_some(edits))
+}
+
+async fn write_sidecar(fs: &Arc<dyn Fs>, edits: &SidecarEdits) -> Result<()> {
+    fs.create_dir(paths::large_file_edits_dir()).await?;
+    fs.atomic_write(sidecar_path(&edits.path), serde_json::to_string(edits)?)
+        .await
+}
+
+async fn remove_sidecar(fs: &Arc<dyn Fs>, abs_path: &Path) -> Result<()> {
+    fs.remove_file(
+        &sidecar_path(abs_path),
+        RemoveOptions {
+            ignore_if_not_exists: true,
+            ..RemoveOptions::default()
+        },
+    )
+    .await
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl LargeFileText {
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Self {
+        Self::new(Arc::new(bytes.into()))
+    }
+
+    pub fn to_string_lossy(&self) -> String {
+        let mut bytes = Vec::new();
+        self.write_to(&mut bytes).unwrap();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+}
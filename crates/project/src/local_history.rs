@@ -0,0 +1,501 @@
+//! Snapshots of local files, kept independently of version control.
+//!
+//! A snapshot of a buffer is taken whenever it is saved, and before its contents are replaced by
+//! a reload from disk or by an agent's edits. Snapshots are stored under
+//! [`paths::local_history_dir`], in one directory per file: an index of the file's snapshots, and
+//! the snapshotted contents, named by their digest so that identical contents are stored once.
+//!
+//! A file's snapshots are pruned by age and count whenever a snapshot of it is added. All
+//! snapshots are also swept when the store is created and periodically after that, which prunes
+//! the files that aren't snapshotted anymore and caps the total size of the history.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Result;
+use collections::{HashMap, HashSet};
+use fs::{Fs, RemoveOptions};
+use futures::{StreamExt as _, channel::mpsc};
+use gpui::{App, AppContext as _, Context, Entity, EventEmitter, Subscription, Task};
+use language::{Buffer, BufferEvent, BufferId};
+use serde::{Deserialize, Serialize};
+use settings::Settings as _;
+use sha2::{Digest as _, Sha256};
+use text::Rope;
+
+use crate::{
+    buffer_store::{BufferStore, BufferStoreEvent},
+    project_settings::{LocalHistorySettings, ProjectSettings},
+};
+
+const INDEX_FILE_NAME: &str = "index.json";
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Why a snapshot was taken.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LocalHistoryReason {
+    Save,
+    BeforeReload,
+    BeforeAgentEdit,
+    BeforeRestore,
+}
+
+impl LocalHistoryReason {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Save => "Saved",
+            Self::BeforeReload => "Before reload",
+            Self::BeforeAgentEdit => "Before agent edit",
+            Self::BeforeRestore => "Before restore",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LocalHistoryEntry {
+    pub id: u64,
+    /// Milliseconds since the Unix epoch.
+    timestamp: u64,
+    pub reason: LocalHistoryReason,
+    /// The length of the snapshotted contents, in bytes.
+    pub len: usize,
+    digest: String,
+}
+
+impl LocalHistoryEntry {
+    pub fn timestamp(&self) -> SystemTime {
+        UNIX_EPOCH + Duration::from_millis(self.timestamp)
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct FileHistory {
+    path: PathBuf,
+    entries: Vec<LocalHistoryEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LocalHistoryEvent {
+    /// A snapshot of the file at the given path was added, or some of its snapshots were pruned.
+    EntriesChanged(PathBuf),
+}
+
+struct PendingSnapshot {
+    abs_path: PathBuf,
+    text: Rope,
+    reason: LocalHistoryReason,
+    timestamp: SystemTime,
+    settings: LocalHistorySettings,
+}
+
+enum HistoryJob {
+    Snapshot(PendingSnapshot),
+    Sweep {
+        timestamp: SystemTime,
+        settings: LocalHistorySettings,
+    },
+}
+
+pub struct LocalHistoryStore {
+    fs: Arc<dyn Fs>,
+    history_dir: PathBuf,
+    jobs_tx: mpsc::UnboundedSender<HistoryJob>,
+    buffer_subscriptions: HashMap<BufferId, Subscription>,
+    _run_jobs: Task<()>,
+    _schedule_sweeps: Task<()>,
+    _buffer_store_subscription: Subscription,
+}
+
+impl EventEmitter<LocalHistoryEvent> for LocalHistoryStore {}
+
+impl LocalHistoryStore {
+    pub fn new(
+        buffer_store: &Entity<BufferStore>,
+        fs: Arc<dyn Fs>,
+        history_dir: PathBuf,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let (jobs_tx, mut jobs_rx) = mpsc::unbounded();
+        // Jobs run one at a time, so that updates to a file's index don't race.
+        let run_jobs = cx.spawn({
+            let fs = fs.clone();
+            let history_dir = history_dir.clone();
+            async move |this, cx| {
+                while let Some(job) = jobs_rx.next().await {
+                    let changed_paths = match job {
+                        HistoryJob::Snapshot(snapshot) => {
+                            let abs_path = snapshot.abs_path.clone();
+                            let written = cx
+                                .background_spawn(write_snapshot(
+                                    fs.clone(),
+                                    history_dir.clone(),
+                                    snapshot,
+                                ))
+                                .await;
+                            match written {
+                                Ok(true) => vec![abs_path],
+                                Ok(false) => Vec::new(),
+                                Err(error) => {
+                                    log::error!(
+                                        "failed to write local history of {abs_path:?}: {error:#}"
+                                    );
+                                    Vec::new()
+                                }
+                            }
+                        }
+                        HistoryJob::Sweep {
+                            timestamp,
+                            settings,
+                        } => cx
+                            .background_spawn(sweep(
+                                fs.clone(),
+                                history_dir.clone(),
+                                timestamp,
+                                settings,
+                            ))
+                            .await
+                            .unwrap_or_else(|error| {
+                                log::error!("failed to prune local history: {error:#}");
+                                Vec::new()
+                            }),
+                    };
+                    if changed_paths.is_empty() {
+                        continue;
+                    }
+                    this.update(cx, |_, cx| {
+                        for abs_path in changed_paths {
+                            cx.emit(LocalHistoryEvent::EntriesChanged(abs_path));
+                        }
+                    })
+                    .ok();
+                }
+            }
+        });
+        let schedule_sweeps = cx.spawn({
+            let jobs_tx = jobs_tx.clone();
+            async move |_, cx| {
+                loop {
+                    let settings = cx.update(|cx| ProjectSettings::get_global(cx).local_history);
+                    let job = HistoryJob::Sweep {
+                        timestamp: SystemTime::now(),
+                        settings,
+                    };
+                    if jobs_tx.unbounded_send(job).is_err() {
+                        break;
+                    }
+                    cx.background_executor().timer(SWEEP_INTERVAL).await;
+                }
+            }
+        });
+
+        let mut this = Self {
+            fs,
+            history_dir,
+            jobs_tx,
+            buffer_subscriptions: HashMap::default(),
+            _run_jobs: run_jobs,
+            _schedule_sweeps: schedule_sweeps,
+            _buffer_store_subscription: cx.subscribe(buffer_store, Self::on_buffer_store_event),
+        };
+        for buffer in buffer_store.read(cx).buffers() {
+            this.subscribe_to_buffer(&buffer, cx);
+        }
+        this
+    }
+
+    fn on_buffer_store_event(
+        &mut self,
+        _: Entity<BufferStore>,
+        event: &BufferStoreEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            BufferStoreEvent::BufferAdded(buffer) => self.subscribe_to_buffer(buffer, cx),
+            BufferStoreEvent::BufferDropped(buffer_id) => {
+                self.buffer_subscriptions.remove(buffer_id);
+            }
+            _ => {}
+        }
+    }
+
+    fn subscribe_to_buffer(&mut self, buffer: &Entity<Buffer>, cx: &mut Context<Self>) {
+        let subscription = cx.subscribe(buffer, |this, buffer, event, cx| match event {
+            BufferEvent::Saved => this.record(&buffer, LocalHistoryReason::Save, cx),
+            // Emitted before a clean buffer is reloaded with its file's new contents.
+            BufferEvent::ReloadNeeded => this.record(&buffer, LocalHistoryReason::BeforeReload, cx),
+            _ => {}
+        });
+        self.buffer_subscriptions
+            .insert(buffer.read(cx).remote_id(), subscription);
+    }
+
+    /// Snapshots the buffer's current contents, if it belongs to a local file that exists on disk.
+    pub fn record(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        reason: LocalHistoryReason,
+        cx: &mut Context<Self>,
+    ) {
+        let settings = ProjectSettings::get_global(cx).local_history;
+        let buffer = buffer.read(cx);
+        let Some(file) = buffer.file().and_then(|file| file.as_local()) else {
+            return;
+        };
+        if !settings.enabled
+            || !file.disk_state().exists()
+            || buffer.len() as u64 > settings.max_file_size
+        {
+            return;
+        }
+        self.jobs_tx
+            .unbounded_send(HistoryJob::Snapshot(PendingSnapshot {
+                abs_path: file.abs_path(cx),
+                text: buffer.as_rope().clone(),
+                reason,
+                timestamp: SystemTime::now(),
+                settings,
+            }))
+            .ok();
+    }
+
+    /// Returns the snapshots of the file at the given path, oldest first.
+    pub fn entries(&self, abs_path: &Path, cx: &App) -> Task<Result<Vec<LocalHistoryEntry>>> {
+        let fs = self.fs.clone();
+        let dir = file_history_dir(&self.history_dir, abs_path);
+        cx.background_spawn(async move { Ok(load_file_history(&fs, &dir).await?.entries) })
+    }
+
+    /// Returns the contents of the file at the time of the given snapshot.
+    pub fn load_entry(
+        &self,
+        abs_path: &Path,
+        entry: &LocalHistoryEntry,
+        cx: &App,
+    ) -> Task<Result<String>> {
+        let fs = self.fs.clone();
+        let path = file_history_dir(&self.history_dir, abs_path).join(&entry.digest);
+        cx.background_spawn(async move { fs.load(&path).await })
+    }
+}
+
+fn file_history_dir(history_dir: &Path, abs_path: &Path) -> PathBuf {
+    let mut hasher = Sha256::new();
+    hasher.update(abs_path.to_string_lossy().as_bytes());
+    history_dir.join(format!("{:x}", hasher.finalize()))
+}
+
+async fn load_file_history(fs: &Arc<dyn Fs>, dir: &Path) -> Result<FileHistory> {
+    let index_path = dir.join(INDEX_FILE_NAME);
+    if !fs.is_file(&index_path).await {
+        return Ok(FileHistory::default());
+    }
+    Ok(serde_json::from_str(&fs.load(&index_path).await?)?)
+}
+
+/// Adds a snapshot to the file's history, unless it has the same contents as the latest one,
+/// and prunes the snapshots that are too old or too many. Returns whether a snapshot was added.
+async fn write_snapshot(
+    fs: Arc<dyn Fs>,
+    history_dir: PathBuf,
+    snapshot: PendingSnapshot,
+) -> Result<bool> {
+    let dir = file_history_dir(&history_dir, &snapshot.abs_path);
+    let mut history = load_file_history(&fs, &dir).await?;
+    let text = snapshot.text.to_string();
+    let digest = format!("{:x}", Sha256::digest(text.as_bytes()));
+    if history
+        .entries
+        .last()
+        .is_some_and(|entry| entry.digest == digest)
+    {
+        return Ok(false);
+    }
+
+    fs.create_dir(&dir).await?;
+    let contents_path = dir.join(&digest);
+    if !fs.is_file(&contents_path).await {
+        fs.atomic_write(contents_path, text.clone()).await?;
+    }
+    let timestamp = snapshot
+        .timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    history.path = snapshot.abs_path;
+    history.entries.push(LocalHistoryEntry {
+        id: history.entries.last().map_or(0, |entry| entry.id + 1),
+        timestamp,
+        reason: snapshot.reason,
+        len: text.len(),
+        digest,
+    });
+
+    let previous_digests = digests(&history);
+    prune_by_age_and_count(&mut history, timestamp, &snapshot.settings);
+    save_file_history(&fs, &dir, &history, previous_digests).await?;
+    Ok(true)
+}
+
+/// Prunes the snapshots of every file by age and count, removing the histories that are left
+/// empty, and then deletes the oldest snapshots across all files until their total size is within
+/// the limit. Returns the paths of the files whose snapshots were pruned.
+async fn sweep(
+    fs: Arc<dyn Fs>,
+    history_dir: PathBuf,
+    timestamp: SystemTime,
+    settings: LocalHistorySettings,
+) -> Result<Vec<PathBuf>> {
+    if !fs.is_dir(&history_dir).await {
+        return Ok(Vec::new());
+    }
+    let timestamp = timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    let mut histories = Vec::new();
+    let mut dirs = fs.read_dir(&history_dir).await?;
+    while let Some(dir) = dirs.next().await {
+        let dir = dir?;
+        if !fs.is_dir(&dir).await {
+            continue;
+        }
+        match load_file_history(&fs, &dir).await {
+            Ok(history) => {
+                let previous_digests = digests(&history);
+                histories.push((dir, history, previous_digests));
+            }
+            Err(error) => log::error!("failed to load local history in {dir:?}: {error:#}"),
+        }
+    }
+
+    let mut changed = vec![false; histories.len()];
+    for ((_, history, _), changed) in histories.iter_mut().zip(&mut changed) {
+        let len = history.entries.len();
+        prune_by_age_and_count(history, timestamp, &settings);
+        *changed = history.entries.is_empty() || history.entries.len() != len;
+    }
+
+    // Each file's distinct contents are stored once, so they're counted once.
+    let mut total_size = histories
+        .iter()
+        .map(|(_, history, _)| {
+            let mut seen = HashSet::default();
+            history
+                .entries
+                .iter()
+                .filter(|entry| seen.insert(&entry.digest))
+                .map(|entry| entry.len as u64)
+                .sum::<u64>()
+        })
+        .sum::<u64>();
+    if total_size > settings.max_total_size {
+        let mut oldest_entries = histories
+            .iter()
+            .enumerate()
+            .flat_map(|(ix, (_, history, _))| {
+                history
+                    .entries
+                    .iter()
+                    .map(move |entry| (entry.timestamp, ix, entry.id))
+            })
+            .collect::<Vec<_>>();
+        oldest_entries.sort_unstable();
+        for (_, ix, id) in oldest_entries {
+            if total_size <= settings.max_total_size {
+                break;
+            }
+            let history = &mut histories[ix].1;
+            let Some(position) = history.entries.iter().position(|entry| entry.id == id) else {
+                continue;
+            };
+            let entry = history.entries.remove(position);
+            if !history
+                .entries
+                .iter()
+                .any(|other| other.digest == entry.digest)
+            {
+                total_size = total_size.saturating_sub(entry.len as u64);
+            }
+            changed[ix] = true;
+        }
+    }
+
+    let mut changed_paths = Vec::new();
+    for ((dir, history, previous_digests), changed) in histories.into_iter().zip(changed) {
+        if !changed {
+            continue;
+        }
+        save_file_history(&fs, &dir, &history, previous_digests).await?;
+        // Directories without an index have no path.
+        if !history.path.as_os_str().is_empty() {
+            changed_paths.push(history.path);
+        }
+    }
+    Ok(changed_paths)
+}
+
+fn digests(history: &FileHistory) -> HashSet<String> {
+    history
+        .entries
+        .iter()
+        .map(|entry| entry.digest.clone())
+        .collect()
+}
+
+/// Removes the snapshots that are older than the maximum age at the given time, and then the
+/// oldest snapshots beyond the maximum count.
+fn prune_by_age_and_count(
+    history: &mut FileHistory,
+    timestamp: u64,
+    settings: &LocalHistorySettings,
+) {
+    let max_age = Duration::from_secs(settings.max_age_days * 24 * 60 * 60);
+    let oldest_timestamp = timestamp.saturating_sub(max_age.as_millis() as u64);
+    history
+        .entries
+        .retain(|entry| entry.timestamp >= oldest_timestamp);
+    let excess_entries = history
+        .entries
+        .len()
+        .saturating_sub(settings.max_entries_per_file.max(1));
+    history.entries.drain(..excess_entries);
+}
+
+/// Writes the file's index and deletes the contents that it doesn't reference anymore, or
+/// deletes its whole directory if it has no snapshots left.
+async fn save_file_history(
+    fs: &Arc<dyn Fs>,
+    dir: &Path,
+    history: &FileHistory,
+    previous_digests: HashSet<String>,
+) -> Result<()> {
+    if history.entries.is_empty() {
+        return fs
+            .remove_dir(
+                dir,
+                RemoveOptions {
+                    recursive: true,
+                    ignore_if_not_exists: true,
+                },
+            )
+            .await;
+    }
+    let digests = digests(history);
+    for digest in previous_digests.difference(&digests) {
+        fs.remove_file(
+            &dir.join(digest),
+            RemoveOptions {
+                ignore_if_not_exists: true,
+                ..Default::default()
+            },
+        )
+        .await?;
+    }
+    fs.atomic_write(dir.join(INDEX_FILE_NAME), serde_json::to_string(history)?)
+        .await
+}
@@ -3931,6 +3931,59 @@ pub struct FormattableBuffer {
     ranges: Option<Vec<Range<Anchor>>>,
 }
 
+/// A rename edit returned by a language server, which hasn't been applied yet.
+#[derive(Debug)]
+pub struct PendingRename {
+    server_id: LanguageServerId,
+    edit: lsp::WorkspaceEdit,
+}
+
+impl PendingRename {
+    /// The files whose contents the rename changes.
+    pub fn edited_paths(&self) -> Result<Vec<PathBuf>> {
+        let uris = match &self.edit.document_changes {
+            Some(lsp::DocumentChanges::Edits(edits)) => edits
+                .iter()
+                .map(|edit| &edit.text_document.uri)
+                .collect::<Vec<_>>(),
+            Some(lsp::DocumentChanges::Operations(operations)) => operations
+                .iter()
+                .filter_map(|operation| match operation {
+                    lsp::DocumentChangeOperation::Edit(edit) => Some(&edit.text_document.uri),
+                    lsp::DocumentChangeOperation::Op(_) => None,
+                })
+                .collect(),
+            None => self
+                .edit
+                .changes
+                .iter()
+                .flat_map(|changes| changes.keys())
+                .collect(),
+        };
+        let mut paths = Vec::new();
+        for uri in uris {
+            let path = uri
+                .to_file_path()
+                .map_err(|()| anyhow!("can't convert URI to path"))?;
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Whether the rename also creates, renames or deletes files.
+    pub fn has_file_operations(&self) -> bool {
+        matches!(
+            &self.edit.document_changes,
+            Some(lsp::DocumentChanges::Operations(operations))
+                if operations
+                    .iter()
+                    .any(|operation| matches!(operation, lsp::DocumentChangeOperation::Op(_)))
+        )
+    }
+}
+
 pub struct RemoteLspStore {
     upstream_client: Option<AnyProtoClient>,
     upstream_project_id: u64,
@@ -5756,6 +5809,77 @@ impl LspStore {
         }
     }
 
+    /// Requests the edit renaming the symbol at `position` from the buffer's language
+    /// server without applying it, so that the files it touches can be checked first.
+    /// Only supported in local projects.
+    pub fn resolve_rename(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: PointUtf16,
+        new_name: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Option<PendingRename>>> {
+        let request = PerformRename {
+            position,
+            new_name,
+            push_to_history: true,
+        };
+        let Some(local) = self.as_local() else {
+            return Task::ready(Err(anyhow!(
+                "renames can only be resolved in local projects"
+            )));
+        };
+        let Some(language_server) = buffer.update(cx, |buffer, cx| {
+            local
+                .language_servers_for_buffer(buffer, cx)
+                .find(|(_, server)| {
+                    request.check_capabilities(server.adapter_server_capabilities())
+                })
+                .map(|(_, server)| server.clone())
+        }) else {
+            return Task::ready(Ok(None));
+        };
+        let Some(file) = File::from_dyn(buffer.read(cx).file()).and_then(File::as_local) else {
+            return Task::ready(Ok(None));
+        };
+        let params = match request.to_lsp(&file.abs_path(cx), buffer.read(cx), &language_server, cx)
+        {
+            Ok(params) => params,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let request_timeout = ProjectSettings::get_global(cx)
+            .global_lsp_settings
+            .get_request_timeout();
+
+        cx.background_spawn(async move {
+            let edit = language_server
+                .request::<lsp::request::Rename>(params, request_timeout)
+                .await
+                .into_response()
+                .with_context(|| format!("Rename via {} failed", language_server.name()))?;
+            Ok(edit.map(|edit| PendingRename {
+                server_id: language_server.server_id(),
+                edit,
+            }))
+        })
+    }
+
+    /// Applies a rename returned by [`Self::resolve_rename`].
+    pub fn apply_rename(
+        &mut self,
+        rename: PendingRename,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        let Some(language_server) = self.language_server_for_id(rename.server_id) else {
+            return Task::ready(Err(anyhow!("the language server is no longer running")));
+        };
+        cx.spawn(async move |this, cx| {
+            let this = this.upgrade().context("lsp store was dropped")?;
+            LocalLspStore::deserialize_workspace_edit(this, rename.edit, true, language_server, cx)
+                .await
+        })
+    }
+
     pub fn on_type_format<T: ToPointUtf16>(
         &mut self,
         buffer: Entity<Buffer>,
@@ -8,6 +8,8 @@ pub mod debounced_delay;
 pub mod debugger;
 pub mod git_store;
 pub mod image_store;
+pub mod large_file;
+pub mod local_history;
 pub mod lsp_command;
 pub mod lsp_store;
 pub mod manifest_tree;
@@ -15,6 +17,7 @@ pub mod prettier_store;
 pub mod project_search;
 pub mod project_settings;
 pub mod search;
+pub mod search_index;
 pub mod task_inventory;
 pub mod task_store;
 pub mod telemetry_snapshot;
@@ -49,8 +52,11 @@ pub use git_store::{
     git_traversal::{ChildEntriesGitIter, GitEntry, GitEntryRef, GitTraversal},
     linked_worktree_short_name, worktrees_directory_for_repo,
 };
+pub use large_file::{LargeFile, LargeFileEvent, LargeFileText};
+pub use local_history::{LocalHistoryEntry, LocalHistoryReason, LocalHistoryStore};
 pub use manifest_tree::ManifestTree;
 pub use project_search::{Search, SearchResults};
+pub use search_index::SearchIndexStore;
 
 use anyhow::{Context as _, Result, anyhow};
 use buffer_store::{BufferStore, BufferStoreEvent};
@@ -161,7 +167,7 @@ pub use buffer_store::ProjectTransaction;
 pub use lsp_store::{
     DiagnosticSummary, InvalidationStrategy, LanguageServerLogType, LanguageServerProgress,
     LanguageServerPromptRequest, LanguageServerStatus, LanguageServerToQuery, LspStore,
-    LspStoreEvent, ProgressToken, SERVER_PROGRESS_THROTTLE_TIMEOUT,
+    LspStoreEvent, PendingRename, ProgressToken, SERVER_PROGRESS_THROTTLE_TIMEOUT,
 };
 pub use toolchain_store::{ToolchainStore, Toolchains};
 const MAX_PROJECT_SEARCH_HISTORY_SIZE: usize = 500;
@@ -226,6 +232,8 @@ pub struct Project {
     collaborators: HashMap<proto::PeerId, Collaborator>,
     client_subscriptions: Vec<client::Subscription>,
     worktree_store: Entity<WorktreeStore>,
+    search_index_store: Option<Entity<SearchIndexStore>>,
+    local_history_store: Option<Entity<LocalHistoryStore>>,
     buffer_store: Entity<BufferStore>,
     context_server_store: Entity<ContextServerStore>,
     image_store: Entity<ImageStore>,
@@ -1169,6 +1177,14 @@ impl Project {
             }
             cx.subscribe(&worktree_store, Self::on_worktree_store_event)
                 .detach();
+            let search_index_store = cx.new(|cx| {
+                SearchIndexStore::new(
+                    &worktree_store,
+                    fs.clone(),
+                    paths::search_index_dir().clone(),
+                    cx,
+                )
+            });
 
             let weak_self = cx.weak_entity();
             let context_server_store = cx.new(|cx| {
@@ -1197,6 +1213,14 @@ impl Project {
             let buffer_store = cx.new(|cx| BufferStore::local(worktree_store.clone(), cx));
             cx.subscribe(&buffer_store, Self::on_buffer_store_event)
                 .detach();
+            let local_history_store = cx.new(|cx| {
+                LocalHistoryStore::new(
+                    &buffer_store,
+                    fs.clone(),
+                    paths::local_history_dir().clone(),
+                    cx,
+                )
+            });
 
             let breakpoint_store =
                 cx.new(|_| BreakpointStore::local(worktree_store.clone(), buffer_store.clone()));
@@ -1298,6 +1322,8 @@ impl Project {
                 buffer_ordered_messages_tx: tx,
                 collaborators: Default::default(),
                 worktree_store,
+                search_index_store: Some(search_index_store),
+                local_history_store: Some(local_history_store),
                 buffer_store,
                 image_store,
                 lsp_store,
@@ -1519,6 +1545,8 @@ impl Project {
                 buffer_ordered_messages_tx: tx,
                 collaborators: Default::default(),
                 worktree_store,
+                search_index_store: None,
+                local_history_store: None,
                 buffer_store,
                 image_store,
                 lsp_store,
@@ -1816,6 +1844,8 @@ impl Project {
                 buffer_store: buffer_store.clone(),
                 image_store,
                 worktree_store: worktree_store.clone(),
+                search_index_store: None,
+                local_history_store: None,
                 lsp_store: lsp_store.clone(),
                 context_server_store,
                 active_entry: None,
@@ -2094,6 +2124,26 @@ impl Project {
         self.worktree_store.clone()
     }
 
+    pub fn search_index_store(&self) -> Option<Entity<SearchIndexStore>> {
+        self.search_index_store.clone()
+    }
+
+    pub fn local_history_store(&self) -> Option<Entity<LocalHistoryStore>> {
+        self.local_history_store.clone()
+    }
+
+    /// Snapshots the buffer's contents into the local history, if the buffer belongs to a local file.
+    pub fn record_local_history(
+        &self,
+        buffer: &Entity<Buffer>,
+        reason: LocalHistoryReason,
+        cx: &mut Context<Self>,
+    ) {
+        if let Some(local_history_store) = &self.local_history_store {
+            local_history_store.update(cx, |store, cx| store.record(buffer, reason, cx));
+        }
+    }
+
     /// Returns a future that resolves when all visible worktrees have completed
     /// their initial scan.
     pub fn wait_for_initial_scan(&self, cx: &App) -> impl Future<Output = ()> + use<> {
@@ -2125,6 +2175,37 @@ impl Project {
         self.remote_client.clone()
     }
 
+    /// Builds the command that runs `command` in the shell of this remote project's host, with
+    /// its stdio piped rather than attached to a terminal. Returns `None` for projects that
+    /// aren't remote.
+    pub fn build_remote_shell_command(
+        &self,
+        command: String,
+        working_directory: Option<String>,
+        cx: &App,
+    ) -> Option<Result<remote::CommandTemplate>> {
+        let remote_client = self.remote_client.as_ref()?.read(cx);
+        Some(maybe!({
+            let shell = remote_client
+                .shell()
+                .context("not connected to the remote host")?;
+            let (program, args) = util::shell_builder::ShellBuilder::new(
+                &util::shell::Shell::Program(shell),
+                remote_client.path_style().is_windows(),
+            )
+            .non_interactive()
+            .build(Some(command), &[]);
+            remote_client.build_command_with_options(
+                Some(program),
+                &args,
+                &HashMap::default(),
+                working_directory,
+                None,
+                remote::Interactive::No,
+            )
+        }))
+    }
+
     #[inline]
     pub fn user_store(&self) -> Entity<UserStore> {
         self.user_store.clone()
@@ -4015,6 +4096,9 @@ impl Project {
         push_to_history: bool,
         cx: &mut Context<Self>,
     ) -> Task<Result<ProjectTransaction>> {
+        for buffer in &buffers {
+            self.record_local_history(buffer, LocalHistoryReason::BeforeReload, cx);
+        }
         self.buffer_store.update(cx, |buffer_store, cx| {
             buffer_store.reload_buffers(buffers, push_to_history, cx)
         })
@@ -4365,6 +4449,31 @@ impl Project {
         )
     }
 
+    /// Like [`Self::perform_rename`], but returns the edit without applying it, so
+    /// that the files it touches can be checked first. Only supported in local projects.
+    pub fn resolve_rename<T: ToPointUtf16>(
+        &mut self,
+        buffer: &Entity<Buffer>,
+        position: T,
+        new_name: String,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<Option<PendingRename>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.lsp_store.update(cx, |lsp_store, cx| {
+            lsp_store.resolve_rename(buffer, position, new_name, cx)
+        })
+    }
+
+    /// Applies a rename returned by [`Self::resolve_rename`].
+    pub fn apply_rename(
+        &mut self,
+        rename: PendingRename,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        self.lsp_store
+            .update(cx, |lsp_store, cx| lsp_store.apply_rename(rename, cx))
+    }
+
     pub fn on_type_format<T: ToPointUtf16>(
         &mut self,
         buffer: Entity<Buffer>,
@@ -4442,6 +4551,7 @@ impl Project {
                     self.fs.clone(),
                     self.buffer_store.clone(),
                     self.worktree_store.clone(),
+                    self.search_index_store.as_ref(),
                     project_search::Search::MAX_SEARCH_RESULT_FILES + 1,
                     cx,
                 ),
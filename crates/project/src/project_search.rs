@@ -10,7 +10,7 @@ use std::{
 };
 
 use anyhow::Context;
-use collections::HashSet;
+use collections::{HashMap, HashSet};
 use fs::Fs;
 use futures::FutureExt as _;
 use futures::{SinkExt, StreamExt, select_biased, stream::FuturesOrdered};
@@ -22,12 +22,13 @@ use rpc::{AnyProtoClient, proto};
 use smol::channel::{Receiver, Sender, bounded, unbounded};
 
 use util::{ResultExt, maybe, paths::compare_rel_paths, rel_path::RelPath};
-use worktree::{Entry, ProjectEntryId, Snapshot, Worktree, WorktreeSettings};
+use worktree::{Entry, ProjectEntryId, Snapshot, Worktree, WorktreeId, WorktreeSettings};
 
 use crate::{
     Project, ProjectItem, ProjectPath, RemotelyCreatedModels,
     buffer_store::BufferStore,
     search::{SearchQuery, SearchResult},
+    search_index::{SearchIndex, SearchIndexStore},
     worktree_store::WorktreeStore,
 };
 
@@ -44,6 +45,7 @@ enum SearchKind {
     Local {
         fs: Arc<dyn Fs>,
         worktrees: Vec<Entity<Worktree>>,
+        search_indices: HashMap<WorktreeId, SearchIndex>,
     },
     /// Query remote host for candidates. As of writing, the host runs a local search in "buffers with matches only" mode.
     Remote {
@@ -109,12 +111,20 @@ impl Search {
         fs: Arc<dyn Fs>,
         buffer_store: Entity<BufferStore>,
         worktree_store: Entity<WorktreeStore>,
+        search_index_store: Option<&Entity<SearchIndexStore>>,
         limit: usize,
         cx: &mut App,
     ) -> Self {
         let worktrees = worktree_store.read(cx).visible_worktrees(cx).collect();
+        let search_indices = search_index_store
+            .map(|store| store.read(cx).indices())
+            .unwrap_or_default();
         Self {
-            kind: SearchKind::Local { fs, worktrees },
+            kind: SearchKind::Local {
+                fs,
+                worktrees,
+                search_indices,
+            },
             buffer_store,
             worktree_store,
             limit,
@@ -207,6 +217,7 @@ impl Search {
                     SearchKind::Local {
                         fs,
                         ref mut worktrees,
+                        ref mut search_indices,
                     } => {
                         let (get_buffer_for_full_scan_tx, get_buffer_for_full_scan_rx) =
                             unbounded();
@@ -218,6 +229,8 @@ impl Search {
                         let tasks = vec![
                             cx.spawn(Self::provide_search_paths(
                                 std::mem::take(worktrees),
+                                std::mem::take(search_indices),
+                                open_buffers.clone(),
                                 query.clone(),
                                 input_paths_tx,
                                 sorted_search_results_tx,
@@ -370,6 +383,7 @@ impl Search {
                             grab_buffer_snapshot_rx,
                             find_all_matches_tx,
                             sorted_matches_tx,
+                            query.is_structural(),
                             cx.clone(),
                         )
                         .boxed_local(),
@@ -409,6 +423,8 @@ impl Search {
 
     fn provide_search_paths(
         worktrees: Vec<Entity<Worktree>>,
+        mut search_indices: HashMap<WorktreeId, SearchIndex>,
+        open_buffers: Arc<HashSet<ProjectEntryId>>,
         query: Arc<SearchQuery>,
         tx: Sender<InputPath>,
         results: Sender<oneshot::Receiver<ProjectPath>>,
@@ -451,10 +467,22 @@ impl Search {
                     }
                     let tx = tx.clone();
                     let results = results.clone();
+                    let search_index = search_indices.remove(&snapshot.id());
+                    let open_buffers = open_buffers.clone();
+                    let query = query.clone();
 
                     cx.background_executor()
                         .spawn(async move {
+                            let index_filter =
+                                search_index.and_then(|search_index| search_index.filter(&query));
                             for entry in snapshot.files(include_ignored, 0) {
+                                // Open buffers may have unsaved changes the index doesn't know about.
+                                if let Some(index_filter) = &index_filter
+                                    && !open_buffers.contains(&entry.id)
+                                    && index_filter.excludes(entry)
+                                {
+                                    continue;
+                                }
                                 let (should_scan_tx, should_scan_rx) = oneshot::channel();
 
                                 let Ok(_) = tx
@@ -538,10 +566,17 @@ impl Search {
             oneshot::Sender<(Entity<Buffer>, Vec<Range<language::Anchor>>)>,
         )>,
         results: Sender<oneshot::Receiver<(Entity<Buffer>, Vec<Range<language::Anchor>>)>>,
+        wait_for_parsing: bool,
         mut cx: AsyncApp,
     ) {
         _ = maybe!(async move {
             while let Ok(buffer) = rx.recv().await {
+                // Structural queries match against syntax trees, which buffers
+                // that were just opened for the search haven't built yet.
+                if wait_for_parsing {
+                    let parsing_idle = buffer.read_with(&mut cx, |this, _| this.parsing_idle());
+                    parsing_idle.await;
+                }
                 let snapshot = buffer.read_with(&mut cx, |this, _| this.snapshot());
                 let (tx, rx) = oneshot::channel();
                 find_all_matches_tx.send((buffer, snapshot, tx)).await?;
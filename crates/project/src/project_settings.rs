@@ -79,6 +79,9 @@ pub struct ProjectSettings {
 
     /// Configuration for session-related features
     pub session: SessionSettings,
+
+    /// Configuration for the local history of files
+    pub local_history: LocalHistorySettings,
 }
 
 #[derive(Copy, Clone, Debug)]
@@ -90,6 +93,11 @@ pub struct SessionSettings {
     ///
     /// Default: true
     pub restore_unsaved_buffers: bool,
+    /// Whether or not to persist the undo history of saved files, so that
+    /// edits can be undone after reopening a file that hasn't changed since.
+    ///
+    /// Default: false
+    pub restore_undo_history: bool,
     /// Whether or not to skip worktree trust checks.
     /// When trusted, project settings are synchronized automatically,
     /// language and MCP servers are downloaded and started automatically.
@@ -98,6 +106,31 @@ pub struct SessionSettings {
     pub trust_all_worktrees: bool,
 }
 
+#[derive(Copy, Clone, Debug)]
+pub struct LocalHistorySettings {
+    /// Whether to keep snapshots of local files when they are saved, and before they are
+    /// reloaded from disk or edited by an agent.
+    ///
+    /// Default: true
+    pub enabled: bool,
+    /// The maximum number of snapshots to keep for each file.
+    ///
+    /// Default: 50
+    pub max_entries_per_file: usize,
+    /// Snapshots older than this many days are deleted.
+    ///
+    /// Default: 30
+    pub max_age_days: u64,
+    /// Files larger than this many bytes are not snapshotted.
+    ///
+    /// Default: 1048576
+    pub max_file_size: u64,
+    /// The maximum total size of all snapshots, in bytes.
+    ///
+    /// Default: 104857600
+    pub max_total_size: u64,
+}
+
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct NodeBinarySettings {
     /// The path to the Node binary.
@@ -728,8 +761,19 @@ impl Settings for ProjectSettings {
             load_direnv: project.load_direnv.clone().unwrap(),
             session: SessionSettings {
                 restore_unsaved_buffers: content.session.unwrap().restore_unsaved_buffers.unwrap(),
+                restore_undo_history: content.session.unwrap().restore_undo_history.unwrap(),
                 trust_all_worktrees: content.session.unwrap().trust_all_worktrees.unwrap(),
             },
+            local_history: {
+                let local_history = content.local_history.unwrap();
+                LocalHistorySettings {
+                    enabled: local_history.enabled.unwrap(),
+                    max_entries_per_file: local_history.max_entries_per_file.unwrap(),
+                    max_age_days: local_history.max_age_days.unwrap(),
+                    max_file_size: local_history.max_file_size.unwrap(),
+                    max_total_size: local_history.max_total_size.unwrap(),
+                }
+            },
         }
     }
 }
@@ -1,3 +1,5 @@
+mod structural;
+
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::Result;
 use client::proto;
@@ -9,7 +11,9 @@ use smol::future::yield_now;
 use std::{
     borrow::Cow,
     io::{BufRead, BufReader, Read},
+    iter::Peekable,
     ops::Range,
+    str::Chars,
     sync::{Arc, LazyLock},
 };
 use text::Anchor;
@@ -18,6 +22,8 @@ use util::{
     rel_path::RelPath,
 };
 
+pub use structural::{StructuralMatch, StructuralPattern};
+
 #[derive(Debug)]
 pub enum SearchResult {
     Buffer {
@@ -77,6 +83,12 @@ pub enum SearchQuery {
         one_match_per_line: bool,
         inner: SearchInputs,
     },
+    Structural {
+        pattern: Arc<StructuralPattern>,
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 static WORD_MATCH_TEST: LazyLock<Regex> = LazyLock::new(|| {
@@ -206,6 +218,33 @@ impl SearchQuery {
         })
     }
 
+    /// Create a structural query, matching a code pattern or tree-sitter query
+    /// against the syntax tree of each buffer. See [`StructuralPattern`].
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: PathMatcher,
+        files_to_exclude: PathMatcher,
+        match_full_paths: bool,
+        buffers: Option<Vec<Entity<Buffer>>>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let pattern = StructuralPattern::new(&query)?;
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+            match_full_paths,
+            buffers,
+        };
+        Ok(Self::Structural {
+            pattern: Arc::new(pattern),
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     /// Extracts case sensitivity settings from pattern items in the provided
     /// query and returns the same query, with the pattern items removed.
     ///
@@ -270,7 +309,16 @@ impl SearchQuery {
             message.files_to_exclude
         };
 
-        if message.regex {
+        if message.structural {
+            Self::structural(
+                message.structural_pattern,
+                message.include_ignored,
+                PathMatcher::new(files_to_include, path_style)?,
+                PathMatcher::new(files_to_exclude, path_style)?,
+                message.match_full_paths,
+                None, // search opened only don't need search remote
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -305,6 +353,10 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
@@ -315,11 +367,28 @@ impl SearchQuery {
     pub fn to_proto(&self) -> proto::SearchQuery {
         let mut files_to_include = self.files_to_include().sources();
         let mut files_to_exclude = self.files_to_exclude().sources();
+        // Servers that don't know about structural queries only see a query
+        // that finds every file a structural match could be in, which is
+        // enough for them to report search candidates.
+        let (query, regex, case_sensitive, structural_pattern) = match self {
+            Self::Structural { pattern, .. } => match pattern.required_text() {
+                Some(text) => (text.to_string(), false, true, self.as_str().to_string()),
+                None => (r"[\s\S]".to_string(), true, true, self.as_str().to_string()),
+            },
+            _ => (
+                self.as_str().to_string(),
+                self.is_regex(),
+                self.case_sensitive(),
+                String::new(),
+            ),
+        };
         proto::SearchQuery {
-            query: self.as_str().to_string(),
-            regex: self.is_regex(),
+            query,
+            regex,
+            structural: self.is_structural(),
+            structural_pattern,
             whole_word: self.whole_word(),
-            case_sensitive: self.case_sensitive(),
+            case_sensitive,
             include_ignored: self.include_ignored(),
             files_to_include: files_to_include.clone().map(ToOwned::to_owned).collect(),
             files_to_exclude: files_to_exclude.clone().map(ToOwned::to_owned).collect(),
@@ -387,14 +456,24 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            // Only files containing the pattern's literal text can match, but
+            // whether they do depends on their syntax trees.
+            Self::Structural { pattern, .. } => {
+                let Some(required_text) = pattern.required_text() else {
+                    return Ok(true);
+                };
+                let mut text = String::new();
+                reader.read_to_string(&mut text)?;
+                Ok(text.contains(required_text))
+            }
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
@@ -421,6 +500,45 @@ impl SearchQuery {
                     None
                 }
             }
+            // Structural replacements refer to the syntax nodes that were
+            // matched, so they can't be derived from the text alone.
+            SearchQuery::Structural { .. } => None,
+        }
+    }
+
+    /// Returns the replacement for the match at `range` in the given buffer.
+    /// Unlike [`Self::replacement_for`], this supports structural queries.
+    pub fn replacement_for_range(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        self.replacements_for_ranges(buffer, &[range])
+            .pop()
+            .flatten()
+    }
+
+    /// Returns the replacements for the matches at `ranges` in the given
+    /// buffer. Structural queries search the buffer once for all of them.
+    pub fn replacements_for_ranges(
+        &self,
+        buffer: &BufferSnapshot,
+        ranges: &[Range<usize>],
+    ) -> Vec<Option<String>> {
+        match self {
+            SearchQuery::Structural {
+                pattern,
+                replacement: Some(replacement),
+                ..
+            } => pattern.replacements(replacement, buffer, ranges),
+            SearchQuery::Structural { .. } => vec![None; ranges.len()],
+            _ => ranges
+                .iter()
+                .map(|range| {
+                    let text = buffer.text_for_range(range.clone()).collect::<String>();
+                    self.replacement_for(&text).map(Cow::into_owned)
+                })
+                .collect(),
         }
     }
 
@@ -519,6 +637,16 @@ impl SearchQuery {
                     }
                 }
             }
+
+            Self::Structural { pattern, .. } => {
+                let range = range_offset..range_offset + rope.len();
+                matches.extend(
+                    pattern
+                        .find_matches(buffer, range)
+                        .into_iter()
+                        .map(|mat| mat.range.start - range_offset..mat.range.end - range_offset),
+                );
+            }
         }
 
         matches
@@ -536,6 +664,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -543,6 +672,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
         }
     }
 
@@ -554,6 +684,9 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
         }
     }
 
@@ -561,6 +694,10 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
     pub fn files_to_include(&self) -> &PathMatcher {
         self.as_inner().files_to_include()
     }
@@ -604,20 +741,137 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
         }
     }
 
     /// Whether this search should replace only one match per line, instead of
     /// all matches.
-    /// Returns `None` for text searches, as only regex searches support this
-    /// option.
+    /// Returns `None` for text and structural searches, as only regex searches
+    /// support this option.
     pub fn one_match_per_line(&self) -> Option<bool> {
         match self {
             Self::Regex {
                 one_match_per_line, ..
             } => Some(*one_match_per_line),
-            Self::Text { .. } => None,
+            Self::Text { .. } | Self::Structural { .. } => None,
+        }
+    }
+
+    /// Returns literal strings that every match of this query contains, which allows ruling out
+    /// files without reading them. Returns `None` if no such strings could be determined.
+    pub fn required_literals(&self) -> Option<Vec<String>> {
+        match self {
+            Self::Text { inner, .. } => Some(vec![inner.as_str().to_string()]),
+            Self::Regex { regex, .. } => regex_required_literals(regex.as_str()),
+            Self::Structural { pattern, .. } => Some(vec![pattern.required_text()?.to_string()]),
+        }
+    }
+
+    /// Whether this query may match text case-insensitively beyond ASCII, in which case `k` and
+    /// `s` also match the Kelvin sign and the long s.
+    pub fn folds_unicode_case(&self) -> bool {
+        match self {
+            Self::Regex {
+                regex,
+                case_sensitive,
+                ..
+            } => !*case_sensitive || regex.as_str().contains("(?"),
+            Self::Text { .. } | Self::Structural { .. } => false,
+        }
+    }
+}
+
+/// Returns literal strings that every match of a regex contains, erring on the side of returning
+/// fewer or shorter ones. Groups and character classes are treated as opaque, and patterns with a
+/// top-level alternation or with escapes that aren't understood yield `None`.
+fn regex_required_literals(pattern: &str) -> Option<Vec<String>> {
+    let mut literals = Vec::new();
+    let mut literal = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                c if c.is_ascii_punctuation() || c == ' ' => literal.push(c),
+                // Escapes taking an argument, like `\x41` or `\p{L}`, and backreferences.
+                'x' | 'u' | 'U' | 'p' | 'P' | '0'..='9' => return None,
+                // Classes and assertions, like `\d` or `\b`.
+                _ => literals.extend(take_literal(&mut literal)),
+            },
+            '(' => {
+                if chars.peek() == Some(&'?')
+                    && chars
+                        .clone()
+                        .take_while(|c| !matches!(c, ':' | ')'))
+                        .any(|c| c == 'x')
+                {
+                    // Whitespace and comments are insignificant in verbose mode.
+                    return None;
+                }
+                skip_regex_group(&mut chars)?;
+                literals.extend(take_literal(&mut literal));
+            }
+            '[' => {
+                skip_regex_class(&mut chars)?;
+                literals.extend(take_literal(&mut literal));
+            }
+            '*' | '?' => {
+                literal.pop();
+                literals.extend(take_literal(&mut literal));
+            }
+            '{' => {
+                let repetition = chars.by_ref().take_while(|c| *c != '}').collect::<String>();
+                let min = repetition.split(',').next().unwrap_or_default();
+                if min.trim().parse::<usize>().ok().is_none_or(|min| min == 0) {
+                    literal.pop();
+                }
+                literals.extend(take_literal(&mut literal));
+            }
+            '+' | '.' | '^' | '$' => literals.extend(take_literal(&mut literal)),
+            '|' | ')' => return None,
+            c => literal.push(c),
+        }
+    }
+    literals.extend(take_literal(&mut literal));
+    Some(literals)
+}
+
+fn take_literal(literal: &mut String) -> Option<String> {
+    (!literal.is_empty()).then(|| std::mem::take(literal))
+}
+
+/// Skips past the end of a group whose opening parenthesis has been consumed.
+fn skip_regex_group(chars: &mut Peekable<Chars>) -> Option<()> {
+    let mut depth = 1;
+    while depth > 0 {
+        match chars.next()? {
+            '\\' => {
+                chars.next()?;
+            }
+            '[' => skip_regex_class(chars)?,
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    Some(())
+}
+
+/// Skips past the end of a character class whose opening bracket has been consumed.
+fn skip_regex_class(chars: &mut Peekable<Chars>) -> Option<()> {
+    // A `]` right after the opening bracket is part of the class.
+    chars.next_if_eq(&'^');
+    chars.next_if_eq(&']');
+    loop {
+        match chars.next()? {
+            '\\' => {
+                chars.next()?;
+            }
+            '[' => skip_regex_class(chars)?,
+            ']' => return Some(()),
+            _ => {}
         }
     }
 }
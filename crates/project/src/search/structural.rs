@@ -0,0 +1,497 @@
+use anyhow::{Result, anyhow};
+use collections::HashMap;
+use language::{BufferSnapshot, Language, LanguageName, Node, Tree, with_parser};
+use parking_lot::Mutex;
+use std::{fmt, ops::Range, sync::Arc};
+use tree_sitter::{Query, QueryCursor, StreamingIterator as _};
+
+/// Metavariables are swapped for identifiers starting with this prefix before
+/// the pattern is parsed, so that the pattern parses like ordinary code.
+const METAVARIABLE_PLACEHOLDER: &str = "zed_metavariable_";
+/// The capture that determines the matched range of a tree-sitter query.
+const MATCH_CAPTURE: &str = "match";
+
+/// A syntax-aware search pattern, matched against each buffer's syntax tree
+/// rather than its text.
+///
+/// A pattern is either code in the buffer's language, where `$NAME` matches
+/// any single syntax node and `$$$NAME` matches any sequence of sibling nodes,
+/// or a raw tree-sitter query. Patterns that parse as a tree-sitter query for
+/// the buffer's language are run as one, and their captures can be referenced
+/// like metavariables. A query reports the range of its `@match` capture, or
+/// the range spanning all of its captures if there's none.
+///
+/// A metavariable used more than once only matches if each occurrence matches
+/// the same text. `$_` and `$$$` match without capturing anything.
+pub struct StructuralPattern {
+    source: Arc<str>,
+    metavariables: Vec<Metavariable>,
+    placeholder_source: String,
+    compiled: Mutex<HashMap<LanguageName, Option<Arc<CompiledPattern>>>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Metavariable {
+    name: Option<String>,
+    multiple: bool,
+}
+
+enum CompiledPattern {
+    Code { tree: Tree },
+    Query(Query),
+}
+
+/// A match of a [`StructuralPattern`], with the text of each of its captures.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructuralMatch {
+    pub range: Range<usize>,
+    pub captures: Vec<(String, Range<usize>)>,
+}
+
+impl fmt::Debug for StructuralPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StructuralPattern")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StructuralPattern {
+    pub fn new(source: &str) -> Result<Self> {
+        let source = source.trim();
+        let mut metavariables = Vec::new();
+        let mut placeholder_source = String::new();
+        let mut rest = source;
+        while let Some(ix) = rest.find('$') {
+            placeholder_source.push_str(&rest[..ix]);
+            rest = &rest[ix..];
+            let (metavariable, len) = parse_metavariable(rest, false);
+            match metavariable {
+                Some(metavariable) => {
+                    // Occurrences of the same metavariable share a binding.
+                    let index = metavariables
+                        .iter()
+                        .position(|existing| existing.name.is_some() && *existing == metavariable)
+                        .unwrap_or_else(|| {
+                            metavariables.push(metavariable);
+                            metavariables.len() - 1
+                        });
+                    placeholder_source.push_str(&format!("{METAVARIABLE_PLACEHOLDER}{index}"));
+                }
+                None => placeholder_source.push_str(&rest[..len]),
+            }
+            rest = &rest[len..];
+        }
+        placeholder_source.push_str(rest);
+
+        if placeholder_source
+            .strip_prefix(METAVARIABLE_PLACEHOLDER)
+            .is_some_and(|index| index.chars().all(|c| c.is_ascii_digit()))
+        {
+            return Err(anyhow!(
+                "A structural pattern can't consist of a single metavariable"
+            ));
+        }
+
+        Ok(Self {
+            source: source.into(),
+            metavariables,
+            placeholder_source,
+            compiled: Mutex::default(),
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns a piece of text that every match of this pattern contains, for
+    /// skipping files without reading their syntax trees.
+    pub fn required_text(&self) -> Option<&str> {
+        if self.looks_like_query() {
+            return None;
+        }
+        self.placeholder_source
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .filter(|word| !word.starts_with(METAVARIABLE_PLACEHOLDER))
+            .max_by_key(|word| word.len())
+            .filter(|word| !word.is_empty())
+    }
+
+    /// Finds the non-overlapping matches that lie within `range` of the buffer.
+    pub fn find_matches(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Vec<StructuralMatch> {
+        let mut matches = Vec::new();
+        let mut text = None;
+        for layer in buffer.syntax_layers_for_range(range.clone(), true) {
+            let Some(compiled) = self.compiled_for(layer.language) else {
+                continue;
+            };
+            let text = text.get_or_insert_with(|| buffer.text());
+            match compiled.as_ref() {
+                CompiledPattern::Code { tree } => {
+                    self.find_code_matches(tree, layer.node(), text, &range, &mut matches)
+                }
+                CompiledPattern::Query(query) => {
+                    find_query_matches(query, layer.node(), text, &range, &mut matches)
+                }
+            }
+        }
+
+        matches.sort_by_key(|mat| (mat.range.start, std::cmp::Reverse(mat.range.end)));
+        let mut end = 0;
+        matches.retain(|mat| {
+            let keep = mat.range.start >= end;
+            if keep {
+                end = mat.range.end;
+            }
+            keep
+        });
+        matches
+    }
+
+    /// Expands `template` for each of the matches spanning exactly `ranges`,
+    /// replacing each `$NAME` or `$$$NAME` in it with the text that was
+    /// captured. The buffer is searched once for all of the ranges, and ranges
+    /// that aren't a match get no replacement.
+    pub fn replacements(
+        &self,
+        template: &str,
+        buffer: &BufferSnapshot,
+        ranges: &[Range<usize>],
+    ) -> Vec<Option<String>> {
+        let (Some(start), Some(end)) = (
+            ranges.iter().map(|range| range.start).min(),
+            ranges.iter().map(|range| range.end).max(),
+        ) else {
+            return Vec::new();
+        };
+        let captures_by_range = self
+            .find_matches(buffer, start..end)
+            .into_iter()
+            .map(|mat| (mat.range, mat.captures))
+            .collect::<HashMap<_, _>>();
+        ranges
+            .iter()
+            .map(|range| {
+                let captures = captures_by_range.get(range)?;
+                Some(expand_template(template, buffer, captures))
+            })
+            .collect()
+    }
+
+    fn looks_like_query(&self) -> bool {
+        self.source.starts_with(['(', '['])
+    }
+
+    fn compiled_for(&self, language: &Arc<Language>) -> Option<Arc<CompiledPattern>> {
+        self.compiled
+            .lock()
+            .entry(language.name())
+            .or_insert_with(|| self.compile(language).map(Arc::new))
+            .clone()
+    }
+
+    fn compile(&self, language: &Language) -> Option<CompiledPattern> {
+        let grammar = language.grammar()?;
+        if self.looks_like_query()
+            && let Ok(query) = Query::new(&grammar.ts_language, &self.source)
+        {
+            return Some(CompiledPattern::Query(query));
+        }
+
+        let tree = with_parser(|parser| {
+            parser.set_language(&grammar.ts_language).ok()?;
+            parser.parse(&self.placeholder_source, None)
+        })?;
+        if contains_error(tree.root_node()) {
+            log::debug!(
+                "structural pattern {:?} isn't valid {}",
+                self.source,
+                language.name()
+            );
+            return None;
+        }
+        Some(CompiledPattern::Code { tree })
+    }
+
+    fn find_code_matches(
+        &self,
+        pattern: &Tree,
+        root: Node,
+        text: &str,
+        range: &Range<usize>,
+        matches: &mut Vec<StructuralMatch>,
+    ) {
+        let pattern_root = pattern_root(pattern);
+        let mut cursor = root.walk();
+        'walk: loop {
+            let node = cursor.node();
+            let mut matched = false;
+            if node.end_byte() > range.start && node.start_byte() < range.end {
+                if node.kind_id() == pattern_root.kind_id()
+                    && range.start <= node.start_byte()
+                    && node.end_byte() <= range.end
+                {
+                    let mut bindings = vec![None; self.metavariables.len()];
+                    if self.match_node(pattern_root, node, text, &mut bindings) {
+                        matches.push(StructuralMatch {
+                            range: node.byte_range(),
+                            captures: self.captures(bindings),
+                        });
+                        matched = true;
+                    }
+                }
+                if !matched && cursor.goto_first_child() {
+                    continue;
+                }
+            }
+            while !cursor.goto_next_sibling() {
+                if !cursor.goto_parent() {
+                    break 'walk;
+                }
+            }
+        }
+    }
+
+    fn captures(&self, bindings: Vec<Option<Range<usize>>>) -> Vec<(String, Range<usize>)> {
+        self.metavariables
+            .iter()
+            .zip(bindings)
+            .filter_map(|(metavariable, range)| Some((metavariable.name.clone()?, range?)))
+            .collect()
+    }
+
+    fn metavariable_at(&self, node: Node) -> Option<(usize, &Metavariable)> {
+        let index = self.placeholder_source[node.byte_range()]
+            .strip_prefix(METAVARIABLE_PLACEHOLDER)?
+            .parse::<usize>()
+            .ok()?;
+        Some((index, self.metavariables.get(index)?))
+    }
+
+    fn match_node(
+        &self,
+        pattern: Node,
+        candidate: Node,
+        text: &str,
+        bindings: &mut [Option<Range<usize>>],
+    ) -> bool {
+        if let Some((index, metavariable)) = self.metavariable_at(pattern) {
+            return bind(metavariable, index, candidate.byte_range(), text, bindings);
+        }
+        if pattern.kind_id() != candidate.kind_id() {
+            return false;
+        }
+
+        let pattern_children = significant_children(pattern);
+        if pattern_children.is_empty() {
+            return self.placeholder_source[pattern.byte_range()] == text[candidate.byte_range()];
+        }
+        let candidate_children = significant_children(candidate);
+        self.match_children(
+            &pattern_children,
+            &candidate_children,
+            candidate.end_byte(),
+            text,
+            bindings,
+        )
+    }
+
+    fn match_children(
+        &self,
+        patterns: &[Node],
+        candidates: &[Node],
+        end: usize,
+        text: &str,
+        bindings: &mut [Option<Range<usize>>],
+    ) -> bool {
+        let Some((pattern, patterns)) = patterns.split_first() else {
+            return candidates.is_empty();
+        };
+
+        if let Some((index, metavariable)) = self.metavariable_at(*pattern)
+            && metavariable.multiple
+        {
+            let start = candidates.first().map_or(end, |node| node.start_byte());
+            for count in 0..=candidates.len() {
+                let range = match count {
+                    0 => start..start,
+                    _ => start..candidates[count - 1].end_byte(),
+                };
+                let mut attempt = bindings.to_vec();
+                if bind(metavariable, index, range, text, &mut attempt)
+                    && self.match_children(patterns, &candidates[count..], end, text, &mut attempt)
+                {
+                    bindings.clone_from_slice(&attempt);
+                    return true;
+                }
+            }
+            return false;
+        }
+
+        let Some((candidate, candidates)) = candidates.split_first() else {
+            return false;
+        };
+        self.match_node(*pattern, *candidate, text, bindings)
+            && self.match_children(patterns, candidates, end, text, bindings)
+    }
+}
+
+/// Parses a metavariable at the start of `text`, which begins with `$`,
+/// returning it along with the length of text it spans.
+///
+/// Metavariable names in patterns are uppercase, so that variables like PHP's
+/// `$this` can still be matched literally. Replacement templates also accept
+/// lowercase names, to refer to tree-sitter query captures.
+fn parse_metavariable(text: &str, allow_lowercase: bool) -> (Option<Metavariable>, usize) {
+    let multiple = text.starts_with("$$$");
+    let sigil_len = if multiple { 3 } else { 1 };
+    let name_len = text[sigil_len..]
+        .find(|c: char| {
+            !(c.is_ascii_uppercase()
+                || c.is_ascii_digit()
+                || c == '_'
+                || (allow_lowercase && c.is_ascii_lowercase()))
+        })
+        .unwrap_or(text.len() - sigil_len);
+    let name = &text[sigil_len..sigil_len + name_len];
+
+    if name.is_empty() || name == "_" {
+        if multiple || name == "_" {
+            let metavariable = Metavariable {
+                name: None,
+                multiple,
+            };
+            return (Some(metavariable), sigil_len + name.len());
+        }
+        return (None, 1);
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        return (None, sigil_len + name_len);
+    }
+    let metavariable = Metavariable {
+        name: Some(name.to_string()),
+        multiple,
+    };
+    (Some(metavariable), sigil_len + name_len)
+}
+
+/// Expands `template` with the text of the given captures.
+fn expand_template(
+    template: &str,
+    buffer: &BufferSnapshot,
+    captures: &[(String, Range<usize>)],
+) -> String {
+    let mut replacement = String::new();
+    let mut rest = template;
+    while let Some(ix) = rest.find('$') {
+        replacement.push_str(&rest[..ix]);
+        rest = &rest[ix..];
+        let (metavariable, len) = parse_metavariable(rest, true);
+        let captured = metavariable
+            .and_then(|metavariable| metavariable.name)
+            .and_then(|name| captures.iter().find(|(capture, _)| *capture == name));
+        match captured {
+            Some((_, range)) => replacement.extend(buffer.text_for_range(range.clone())),
+            None => replacement.push_str(&rest[..len]),
+        }
+        rest = &rest[len..];
+    }
+    replacement.push_str(rest);
+    replacement
+}
+
+fn bind(
+    metavariable: &Metavariable,
+    index: usize,
+    range: Range<usize>,
+    text: &str,
+    bindings: &mut [Option<Range<usize>>],
+) -> bool {
+    if metavariable.name.is_none() {
+        return true;
+    }
+    if let Some(bound) = &bindings[index] {
+        return text[bound.clone()] == text[range];
+    }
+    bindings[index] = Some(range);
+    true
+}
+
+fn find_query_matches(
+    query: &Query,
+    root: Node,
+    text: &str,
+    range: &Range<usize>,
+    matches: &mut Vec<StructuralMatch>,
+) {
+    let capture_names = query.capture_names();
+    let mut cursor = QueryCursor::new();
+    cursor.set_byte_range(range.clone());
+    let mut query_matches = cursor.matches(query, root, text.as_bytes());
+    while let Some(query_match) = query_matches.next() {
+        let mut captures: Vec<(String, Range<usize>)> = Vec::new();
+        for capture in query_match.captures {
+            let name = capture_names[capture.index as usize];
+            let node_range = capture.node.byte_range();
+            if let Some((_, range)) = captures.iter_mut().find(|(capture, _)| capture == name) {
+                range.start = range.start.min(node_range.start);
+                range.end = range.end.max(node_range.end);
+            } else {
+                captures.push((name.to_string(), node_range));
+            }
+        }
+
+        let match_range = captures
+            .iter()
+            .find(|(name, _)| name == MATCH_CAPTURE)
+            .map(|(_, range)| range.clone())
+            .or_else(|| {
+                let start = captures.iter().map(|(_, range)| range.start).min()?;
+                let end = captures.iter().map(|(_, range)| range.end).max()?;
+                Some(start..end)
+            });
+        if let Some(match_range) = match_range
+            && range.start <= match_range.start
+            && match_range.end <= range.end
+        {
+            matches.push(StructuralMatch {
+                range: match_range,
+                captures,
+            });
+        }
+    }
+}
+
+/// Descends through the nodes that wrap the pattern without adding anything
+/// to it, such as the source file and expression statement around a call, so
+/// that the pattern matches wherever the wrapped node appears.
+fn pattern_root(tree: &Tree) -> Node<'_> {
+    let mut node = tree.root_node();
+    loop {
+        match significant_children(node).as_slice() {
+            [child] => node = *child,
+            _ => return node,
+        }
+    }
+}
+
+/// Returns the children that take part in matching, skipping comments and the
+/// zero-width nodes that the parser inserts to recover from errors.
+fn significant_children(node: Node<'_>) -> Vec<Node<'_>> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|child| !child.is_extra() && !child.is_missing() && !child.byte_range().is_empty())
+        .collect()
+}
+
+fn contains_error(node: Node) -> bool {
+    node.is_error() || {
+        let mut cursor = node.walk();
+        node.children(&mut cursor).any(contains_error)
+    }
+}
@@ -120,4 +120,9 @@ impl SearchHistory {
     pub fn len(&self) -> usize {
         self.history.len()
     }
+
+    /// Iterates over the searches, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &str> {
+        self.history.iter().map(|search| search.as_str())
+    }
 }
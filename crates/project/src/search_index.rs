@@ -0,0 +1,690 @@
+//! An optional, persistent trigram index of the files in local worktrees.
+//!
+//! Project search consults the index to rule out files that cannot contain a match, so that
+//! on very large repositories only a small fraction of the files has to be read from disk.
+//! The index is only ever used to skip files: any file it doesn't know about, or whose
+//! modification time differs from the indexed one, is searched as usual.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use collections::{HashMap, HashSet};
+use fs::{Fs, MTime, RenameOptions};
+use futures::{FutureExt as _, StreamExt as _, channel::mpsc, select_biased, stream};
+use gpui::{
+    AppContext as _, AsyncApp, BackgroundExecutor, Context, Entity, Subscription, Task, WeakEntity,
+};
+use parking_lot::RwLock;
+use settings::{Settings as _, SettingsStore};
+use sha2::{Digest as _, Sha256};
+use util::{ResultExt as _, rel_path::RelPath};
+use worktree::{Entry, Snapshot, Worktree, WorktreeId, WorktreeSettings};
+
+use crate::{
+    search::SearchQuery,
+    worktree_store::{WorktreeStore, WorktreeStoreEvent},
+};
+
+const INDEX_FILE_MAGIC: &[u8; 4] = b"TGIX";
+const INDEX_FILE_VERSION: u32 = 1;
+/// Files larger than this are not indexed, and are always searched.
+const MAX_INDEXED_FILE_SIZE: u64 = 4 * 1024 * 1024;
+const MAX_CONCURRENT_FILE_LOADS: usize = 64;
+const SAVE_DEBOUNCE: Duration = Duration::from_secs(10);
+
+/// Three bytes of text, ASCII-lowercased and packed into the low bits of a `u32`.
+type Trigram = u32;
+type FileId = u32;
+
+/// Maintains a [`SearchIndex`] for every local worktree that has `search_index` enabled.
+pub struct SearchIndexStore {
+    fs: Arc<dyn Fs>,
+    index_dir: PathBuf,
+    worktree_store: WeakEntity<WorktreeStore>,
+    indices: HashMap<WorktreeId, WorktreeSearchIndex>,
+    _subscriptions: Vec<Subscription>,
+}
+
+struct WorktreeSearchIndex {
+    index: SearchIndex,
+    changed_paths_tx: mpsc::UnboundedSender<Arc<RelPath>>,
+    _maintain_index: Task<()>,
+}
+
+impl SearchIndexStore {
+    pub fn new(
+        worktree_store: &Entity<WorktreeStore>,
+        fs: Arc<dyn Fs>,
+        index_dir: PathBuf,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let mut this = Self {
+            fs,
+            index_dir,
+            worktree_store: worktree_store.downgrade(),
+            indices: HashMap::default(),
+            _subscriptions: vec![
+                cx.subscribe(worktree_store, Self::on_worktree_store_event),
+                cx.observe_global::<SettingsStore>(|this, cx| this.refresh_indices(cx)),
+            ],
+        };
+        this.refresh_indices(cx);
+        this
+    }
+
+    /// Returns the indices of all worktrees that currently have one.
+    pub(crate) fn indices(&self) -> HashMap<WorktreeId, SearchIndex> {
+        self.indices
+            .iter()
+            .map(|(worktree_id, index)| (*worktree_id, index.index.clone()))
+            .collect()
+    }
+
+    /// Returns the indexed files of the given worktree that may contain a match for the query,
+    /// or `None` if the index can't narrow down the search.
+    pub fn candidate_paths(
+        &self,
+        worktree_id: WorktreeId,
+        query: &SearchQuery,
+    ) -> Option<Vec<Arc<RelPath>>> {
+        let index = self.indices.get(&worktree_id)?.index.0.read();
+        let candidates =
+            index.candidates(&query.required_literals()?, query.folds_unicode_case())?;
+        Some(
+            candidates
+                .into_iter()
+                .filter_map(|id| Some(index.files[id as usize].as_ref()?.path.clone()))
+                .collect(),
+        )
+    }
+
+    fn on_worktree_store_event(
+        &mut self,
+        _: Entity<WorktreeStore>,
+        event: &WorktreeStoreEvent,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            WorktreeStoreEvent::WorktreeAdded(_) | WorktreeStoreEvent::WorktreeRemoved(..) => {
+                self.refresh_indices(cx);
+            }
+            WorktreeStoreEvent::WorktreeUpdatedEntries(worktree_id, changes) => {
+                if let Some(index) = self.indices.get(worktree_id) {
+                    for (path, _, _) in changes.iter() {
+                        index.changed_paths_tx.unbounded_send(path.clone()).ok();
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Starts indexing worktrees that have the setting enabled, and drops the indices of the
+    /// ones that were removed or had it disabled.
+    fn refresh_indices(&mut self, cx: &mut Context<Self>) {
+        let Some(worktree_store) = self.worktree_store.upgrade() else {
+            return;
+        };
+        let worktrees = worktree_store
+            .read(cx)
+            .visible_worktrees(cx)
+            .filter(|worktree| {
+                let worktree = worktree.read(cx);
+                worktree.is_local()
+                    && WorktreeSettings::get(Some(worktree.settings_location(cx)), cx).search_index
+            })
+            .collect::<Vec<_>>();
+
+        let enabled = worktrees
+            .iter()
+            .map(|worktree| worktree.read(cx).id())
+            .collect::<HashSet<_>>();
+        self.indices
+            .retain(|worktree_id, _| enabled.contains(worktree_id));
+
+        for worktree in worktrees {
+            let worktree_id = worktree.read(cx).id();
+            if !self.indices.contains_key(&worktree_id) {
+                let index = self.start_indexing(worktree, cx);
+                self.indices.insert(worktree_id, index);
+            }
+        }
+    }
+
+    fn start_indexing(
+        &self,
+        worktree: Entity<Worktree>,
+        cx: &mut Context<Self>,
+    ) -> WorktreeSearchIndex {
+        let index = SearchIndex::default();
+        let (changed_paths_tx, changed_paths_rx) = mpsc::unbounded();
+        let index_path = self
+            .index_dir
+            .join(index_file_name(&worktree.read(cx).abs_path()));
+        let maintain_index = cx.spawn({
+            let fs = self.fs.clone();
+            let index = index.clone();
+            let worktree = worktree.downgrade();
+            async move |_, cx| {
+                maintain_index(worktree, fs, index, index_path, changed_paths_rx, cx)
+                    .await
+                    .log_err();
+            }
+        });
+        WorktreeSearchIndex {
+            index,
+            changed_paths_tx,
+            _maintain_index: maintain_index,
+        }
+    }
+}
+
+/// Loads the index from disk, brings it up to date with the worktree, and then keeps it up to
+/// date as files change, periodically saving it back to disk.
+async fn maintain_index(
+    worktree: WeakEntity<Worktree>,
+    fs: Arc<dyn Fs>,
+    index: SearchIndex,
+    index_path: PathBuf,
+    changed_paths_rx: mpsc::UnboundedReceiver<Arc<RelPath>>,
+    cx: &mut AsyncApp,
+) -> Result<()> {
+    if fs.is_file(&index_path).await {
+        let bytes = fs.load_bytes(&index_path).await?;
+        match cx
+            .background_spawn(async move { TrigramIndex::deserialize(&bytes) })
+            .await
+        {
+            Ok(mut loaded) => {
+                let mut index = index.0.write();
+                loaded.generation = index.generation + 1;
+                *index = loaded;
+            }
+            Err(error) => log::warn!("discarding search index at {index_path:?}: {error:#}"),
+        }
+    }
+
+    let scan_complete = worktree
+        .read_with(cx, |worktree, _| {
+            worktree.as_local().map(|worktree| worktree.scan_complete())
+        })?
+        .context("search indices are only maintained for local worktrees")?;
+    scan_complete.await;
+
+    let snapshot = worktree.read_with(cx, |worktree, _| worktree.snapshot())?;
+    let stale_paths = cx
+        .background_spawn({
+            let index = index.clone();
+            let snapshot = snapshot.clone();
+            async move { index.0.write().reconcile(&snapshot) }
+        })
+        .await;
+    let mut needs_save = !stale_paths.is_empty();
+    let executor = cx.background_executor().clone();
+    cx.background_spawn(index_files(
+        fs.clone(),
+        executor.clone(),
+        index.clone(),
+        snapshot,
+        stale_paths,
+    ))
+    .await;
+
+    let mut changed_paths_rx = changed_paths_rx.ready_chunks(1024);
+    loop {
+        let save_timer = if needs_save {
+            executor.timer(SAVE_DEBOUNCE).boxed_local()
+        } else {
+            futures::future::pending().boxed_local()
+        };
+        select_biased! {
+            changed_paths = changed_paths_rx.next() => {
+                let Some(changed_paths) = changed_paths else {
+                    break;
+                };
+                let snapshot = worktree.read_with(cx, |worktree, _| worktree.snapshot())?;
+                cx.background_spawn(index_files(
+                    fs.clone(),
+                    executor.clone(),
+                    index.clone(),
+                    snapshot,
+                    changed_paths,
+                ))
+                .await;
+                needs_save = true;
+            }
+            _ = save_timer.fuse() => {
+                save_index(&fs, &index, &index_path, cx).await.log_err();
+                needs_save = false;
+            }
+        }
+    }
+
+    if needs_save {
+        save_index(&fs, &index, &index_path, cx).await?;
+    }
+    Ok(())
+}
+
+/// Re-indexes the given paths, removing the ones that no longer exist or shouldn't be indexed.
+async fn index_files(
+    fs: Arc<dyn Fs>,
+    executor: BackgroundExecutor,
+    index: SearchIndex,
+    snapshot: Snapshot,
+    paths: Vec<Arc<RelPath>>,
+) {
+    let mut files_to_load = Vec::new();
+    let mut seen = HashSet::default();
+    for path in paths {
+        if !seen.insert(path.clone()) {
+            continue;
+        }
+        match snapshot.entry_for_path(&path) {
+            Some(entry) if should_index(entry) => {
+                if let Some(mtime) = entry.mtime
+                    && index.0.read().indexed_mtime(&path) != Some(mtime)
+                {
+                    files_to_load.push((path, mtime));
+                }
+            }
+            _ => index.0.write().remove(&path),
+        }
+    }
+
+    let mut loaded_files = stream::iter(files_to_load)
+        .map(|(path, mtime)| {
+            let fs = fs.clone();
+            let abs_path = snapshot.absolutize(&path);
+            executor.spawn(async move {
+                let trigrams = fs.load_bytes(&abs_path).await.map(|bytes| trigrams(&bytes));
+                (path, mtime, trigrams)
+            })
+        })
+        .buffer_unordered(MAX_CONCURRENT_FILE_LOADS);
+    while let Some((path, mtime, trigrams)) = loaded_files.next().await {
+        let mut index = index.0.write();
+        match trigrams {
+            Ok(trigrams) => index.insert(path, mtime, &trigrams),
+            Err(_) => index.remove(&path),
+        }
+    }
+}
+
+async fn save_index(
+    fs: &Arc<dyn Fs>,
+    index: &SearchIndex,
+    index_path: &Path,
+    cx: &mut AsyncApp,
+) -> Result<()> {
+    let bytes = cx
+        .background_spawn({
+            let index = index.clone();
+            async move { index.0.read().serialize() }
+        })
+        .await;
+    if let Some(index_dir) = index_path.parent() {
+        fs.create_dir(index_dir).await?;
+    }
+    let temp_path = index_path.with_extension("tmp");
+    fs.write(&temp_path, &bytes).await?;
+    fs.rename(
+        &temp_path,
+        index_path,
+        RenameOptions {
+            overwrite: true,
+            ..Default::default()
+        },
+    )
+    .await
+}
+
+fn should_index(entry: &Entry) -> bool {
+    entry.is_file()
+        && !entry.is_ignored
+        && !entry.is_external
+        && !entry.is_fifo
+        && entry.size <= MAX_INDEXED_FILE_SIZE
+}
+
+fn index_file_name(worktree_abs_path: &Path) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(worktree_abs_path.to_string_lossy().as_bytes());
+    format!("{:x}.idx", hasher.finalize())
+}
+
+fn trigram(bytes: &[u8]) -> Trigram {
+    (bytes[0].to_ascii_lowercase() as u32) << 16
+        | (bytes[1].to_ascii_lowercase() as u32) << 8
+        | bytes[2].to_ascii_lowercase() as u32
+}
+
+fn is_line_break(byte: &u8) -> bool {
+    *byte == b'\n' || *byte == b'\r'
+}
+
+/// Returns the sorted, deduplicated trigrams of a file's contents. Trigrams spanning a line break
+/// are left out, as queries are split into lines before looking them up.
+fn trigrams(text: &[u8]) -> Vec<Trigram> {
+    let mut trigrams = text
+        .windows(3)
+        .filter(|window| !window.iter().any(is_line_break))
+        .map(trigram)
+        .collect::<Vec<_>>();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+/// Returns the trigrams that every occurrence of `literal` contains.
+///
+/// Non-ASCII bytes may be matched case-insensitively by other byte sequences, so windows
+/// containing them are skipped. When unicode case folding applies, so are windows containing
+/// `k` and `s`, which also match the Kelvin sign and the long s.
+fn literal_trigrams(literal: &str, folds_unicode_case: bool) -> impl Iterator<Item = Trigram> {
+    literal
+        .as_bytes()
+        .split(is_line_break)
+        .flat_map(|line| line.windows(3))
+        .filter(move |window| {
+            window.iter().all(|byte| {
+                byte.is_ascii()
+                    && !(folds_unicode_case && matches!(byte.to_ascii_lowercase(), b'k' | b's'))
+            })
+        })
+        .map(trigram)
+}
+
+/// A handle to the trigram index of a single worktree, shared with its indexing task.
+#[derive(Clone, Default)]
+pub(crate) struct SearchIndex(Arc<RwLock<TrigramIndex>>);
+
+impl SearchIndex {
+    /// Looks up the files that may match the query, for use while the query is running.
+    pub(crate) fn filter(&self, query: &SearchQuery) -> Option<SearchIndexFilter> {
+        let literals = query.required_literals()?;
+        let index = self.0.read();
+        let candidates = index.candidates(&literals, query.folds_unicode_case())?;
+        Some(SearchIndexFilter {
+            index: self.clone(),
+            generation: index.generation,
+            file_id_limit: index.files.len() as FileId,
+            candidates,
+        })
+    }
+}
+
+/// The result of looking up a query in a [`SearchIndex`].
+pub(crate) struct SearchIndexFilter {
+    index: SearchIndex,
+    generation: u64,
+    /// Files indexed after the lookup aren't covered by `candidates`.
+    file_id_limit: FileId,
+    candidates: Vec<FileId>,
+}
+
+impl SearchIndexFilter {
+    /// Whether the index shows that the given file doesn't contain a match.
+    pub(crate) fn excludes(&self, entry: &Entry) -> bool {
+        let index = self.index.0.read();
+        if index.generation != self.generation {
+            return false;
+        }
+        let Some(&id) = index.file_ids.get(&entry.path) else {
+            return false;
+        };
+        let Some(file) = index.files[id as usize].as_ref() else {
+            return false;
+        };
+        id < self.file_id_limit
+            && entry.mtime == Some(file.mtime)
+            && self.candidates.binary_search(&id).is_err()
+    }
+}
+
+struct IndexedFile {
+    path: Arc<RelPath>,
+    mtime: MTime,
+}
+
+/// Maps every trigram to the ids of the files containing it.
+///
+/// Re-indexing a file assigns it a new id and leaves a tombstone in place of the old one, so
+/// posting lists only ever get appended to and stay sorted. Tombstones are dropped when there
+/// are too many of them, which renumbers the files and bumps the generation.
+#[derive(Default)]
+struct TrigramIndex {
+    files: Vec<Option<IndexedFile>>,
+    file_ids: HashMap<Arc<RelPath>, FileId>,
+    postings: HashMap<Trigram, Vec<FileId>>,
+    removed_files: usize,
+    generation: u64,
+}
+
+impl TrigramIndex {
+    fn indexed_mtime(&self, path: &RelPath) -> Option<MTime> {
+        let id = *self.file_ids.get(path)?;
+        Some(self.files[id as usize].as_ref()?.mtime)
+    }
+
+    fn insert(&mut self, path: Arc<RelPath>, mtime: MTime, trigrams: &[Trigram]) {
+        self.remove(&path);
+        let id = self.files.len() as FileId;
+        self.files.push(Some(IndexedFile {
+            path: path.clone(),
+            mtime,
+        }));
+        self.file_ids.insert(path, id);
+        for trigram in trigrams {
+            self.postings.entry(*trigram).or_default().push(id);
+        }
+    }
+
+    fn remove(&mut self, path: &RelPath) {
+        if let Some(id) = self.file_ids.remove(path) {
+            self.files[id as usize] = None;
+            self.removed_files += 1;
+            if self.removed_files > 1024 && self.removed_files > self.files.len() / 2 {
+                self.compact();
+            }
+        }
+    }
+
+    /// Removes the files that are no longer in the worktree, and returns the paths of the
+    /// files that need to be (re)indexed.
+    fn reconcile(&mut self, snapshot: &Snapshot) -> Vec<Arc<RelPath>> {
+        let removed_paths = self
+            .file_ids
+            .keys()
+            .filter(|path| {
+                !snapshot
+                    .entry_for_path(path)
+                    .is_some_and(|entry| should_index(entry))
+            })
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in removed_paths {
+            self.remove(&path);
+        }
+
+        snapshot
+            .files(false, 0)
+            .filter(|entry| {
+                should_index(entry)
+                    && entry.mtime.is_some()
+                    && self.indexed_mtime(&entry.path) != entry.mtime
+            })
+            .map(|entry| entry.path.clone())
+            .collect()
+    }
+
+    /// Returns the sorted ids of the files that may contain all of the given literals, or `None`
+    /// if the literals are too short to narrow down the search.
+    fn candidates(&self, literals: &[String], folds_unicode_case: bool) -> Option<Vec<FileId>> {
+        let mut trigrams = literals
+            .iter()
+            .flat_map(|literal| literal_trigrams(literal, folds_unicode_case))
+            .collect::<Vec<_>>();
+        trigrams.sort_unstable();
+        trigrams.dedup();
+
+        // Intersect the shortest posting lists first, to keep the candidate list small.
+        let mut postings = trigrams
+            .iter()
+            .map(|trigram| self.postings.get(trigram).map_or(&[][..], Vec::as_slice))
+            .collect::<Vec<_>>();
+        postings.sort_by_key(|ids| ids.len());
+        let (first, rest) = postings.split_first()?;
+        let mut candidates = first.to_vec();
+        for ids in rest {
+            if candidates.is_empty() {
+                break;
+            }
+            candidates.retain(|id| ids.binary_search(id).is_ok());
+        }
+        Some(candidates)
+    }
+
+    fn compact(&mut self) {
+        let mut new_ids = Vec::with_capacity(self.files.len());
+        let mut files = Vec::with_capacity(self.files.len() - self.removed_files);
+        for file in self.files.drain(..) {
+            if let Some(file) = file {
+                new_ids.push(Some(files.len() as FileId));
+                files.push(Some(file));
+            } else {
+                new_ids.push(None);
+            }
+        }
+        for ids in self.postings.values_mut() {
+            ids.retain_mut(|id| {
+                if let Some(new_id) = new_ids[*id as usize] {
+                    *id = new_id;
+                    true
+                } else {
+                    false
+                }
+            });
+        }
+        self.postings.retain(|_, ids| !ids.is_empty());
+        self.file_ids = files
+            .iter()
+            .enumerate()
+            .filter_map(|(id, file)| Some((file.as_ref()?.path.clone(), id as FileId)))
+            .collect();
+        self.files = files;
+        self.removed_files = 0;
+        self.generation += 1;
+    }
+
+    /// Serializes the index, leaving out tombstones.
+    fn serialize(&self) -> Vec<u8> {
+        let mut new_ids = Vec::with_capacity(self.files.len());
+        let mut live_files = Vec::new();
+        for file in &self.files {
+            if let Some(file) = file {
+                new_ids.push(live_files.len() as FileId);
+                live_files.push(file);
+            } else {
+                new_ids.push(FileId::MAX);
+            }
+        }
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(INDEX_FILE_MAGIC);
+        bytes.extend_from_slice(&INDEX_FILE_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(live_files.len() as u32).to_le_bytes());
+        for file in live_files {
+            let path = file.path.as_unix_str().as_bytes();
+            let (seconds, nanos) = file
+                .mtime
+                .to_seconds_and_nanos_for_persistence()
+                .unwrap_or_default();
+            bytes.extend_from_slice(&(path.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(path);
+            bytes.extend_from_slice(&seconds.to_le_bytes());
+            bytes.extend_from_slice(&nanos.to_le_bytes());
+        }
+        bytes.extend_from_slice(&(self.postings.len() as u32).to_le_bytes());
+        for (trigram, ids) in &self.postings {
+            let ids = ids
+                .iter()
+                .map(|id| new_ids[*id as usize])
+                .filter(|id| *id != FileId::MAX)
+                .collect::<Vec<_>>();
+            bytes.extend_from_slice(&trigram.to_le_bytes());
+            bytes.extend_from_slice(&(ids.len() as u32).to_le_bytes());
+            for id in ids {
+                bytes.extend_from_slice(&id.to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader(bytes);
+        if reader.take(INDEX_FILE_MAGIC.len())? != INDEX_FILE_MAGIC {
+            return Err(anyhow!("not a search index"));
+        }
+        let version = reader.u32()?;
+        if version != INDEX_FILE_VERSION {
+            return Err(anyhow!("unsupported search index version {version}"));
+        }
+
+        let mut index = Self::default();
+        let file_count = reader.u32()?;
+        for id in 0..file_count {
+            let path_len = reader.u32()? as usize;
+            let path = std::str::from_utf8(reader.take(path_len)?)?;
+            let path = RelPath::unix(path)?.into_arc();
+            let seconds = reader.u64()?;
+            let nanos = reader.u32()?;
+            index.file_ids.insert(path.clone(), id);
+            index.files.push(Some(IndexedFile {
+                path,
+                mtime: MTime::from_seconds_and_nanos(seconds, nanos),
+            }));
+        }
+
+        let trigram_count = reader.u32()?;
+        for _ in 0..trigram_count {
+            let trigram = reader.u32()?;
+            let id_count = reader.u32()? as usize;
+            let mut ids = Vec::with_capacity(id_count.min(file_count as usize));
+            for _ in 0..id_count {
+                let id = reader.u32()?;
+                if id >= file_count || ids.last().is_some_and(|last| *last >= id) {
+                    return Err(anyhow!("invalid file id {id} for trigram {trigram:#x}"));
+                }
+                ids.push(id);
+            }
+            index.postings.insert(trigram, ids);
+        }
+        Ok(index)
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.0.len() < len {
+            return Err(anyhow!("unexpected end of search index"));
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into()?))
+    }
+}
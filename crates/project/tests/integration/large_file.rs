@@ -0,0 +1,181 @@
+use crate::init_test;
+use fs::FakeFs;
+use gpui::TestAppContext;
+use project::{
+    LargeFile, LargeFileText, Project, ProjectItem as _, ProjectPath, large_file::is_large_file,
+};
+use serde_json::json;
+use settings::SettingsStore;
+use util::{path, rel_path::rel_path};
+
+#[test]
+fn test_large_file_text_lines() {
+    let contents = (0..3000)
+        .map(|row| format!("line {row}\n"))
+        .collect::<String>();
+    let text = LargeFileText::from_bytes(contents.clone());
+    assert_eq!(text.line_count(), 3001);
+    for row in [0, 1, 1023, 1024, 1025, 2048, 2999] {
+        assert_eq!(text.line(row).unwrap(), format!("line {row}"));
+        let start = text.line_start(row).unwrap();
+        assert_eq!(text.row_for_offset(start), row);
+    }
+    assert_eq!(text.line(3000).unwrap(), "");
+    assert_eq!(text.line(3001), None);
+    assert!(!text.is_edited());
+    assert_eq!(text.to_string_lossy(), contents);
+}
+
+#[test]
+fn test_large_file_text_edits() {
+    let mut text = LargeFileText::from_bytes("one\ntwo\nthree\nfour");
+
+    text.replace_lines(1..2, "TWO\nTWO AND A HALF").unwrap();
+    assert_eq!(
+        text.to_string_lossy(),
+        "one\nTWO\nTWO AND A HALF\nthree\nfour"
+    );
+    assert_eq!(text.line_count(), 5);
+    assert_eq!(text.line(2).unwrap(), "TWO AND A HALF");
+    assert_eq!(text.line(3).unwrap(), "three");
+    assert!(text.is_edited());
+
+    text.replace_lines(4..5, "FOUR").unwrap();
+    assert_eq!(
+        text.to_string_lossy(),
+        "one\nTWO\nTWO AND A HALF\nthree\nFOUR"
+    );
+
+    text.delete_lines(0..1).unwrap();
+    assert_eq!(text.to_string_lossy(), "TWO\nTWO AND A HALF\nthree\nFOUR");
+    text.delete_lines(2..4).unwrap();
+    assert_eq!(text.to_string_lossy(), "TWO\nTWO AND A HALF");
+    assert_eq!(text.line_count(), 2);
+    assert_eq!(text.row_for_offset(5), 1);
+
+    // Line endings of the file are preserved.
+    let mut text = LargeFileText::from_bytes("a\r\nb\r\nc\r\n");
+    text.replace_lines(1..2, "B\nB2").unwrap();
+    assert_eq!(text.to_string_lossy(), "a\r\nB\r\nB2\r\nc\r\n");
+    assert_eq!(text.line(1).unwrap(), "B");
+}
+
+#[test]
+fn test_large_file_text_find() {
+    let mut text = LargeFileText::from_bytes("alpha\nbeta\ngamma\nbeta\n");
+    text.replace_lines(2..3, "beta gamma").unwrap();
+
+    let first = text.find("beta", 0, false).unwrap();
+    assert_eq!(text.row_for_offset(first.start), 1);
+    let second = text.find("beta", first.end, false).unwrap();
+    assert_eq!(text.row_for_offset(second.start), 2);
+    let third = text.find("beta", second.end, false).unwrap();
+    assert_eq!(text.row_for_offset(third.start), 3);
+    assert_eq!(text.find("beta", third.end, false), None);
+
+    assert_eq!(text.find("beta", third.start, true), Some(second.clone()));
+    assert_eq!(text.find("beta", second.start, true), Some(first.clone()));
+    assert_eq!(text.find("beta", first.start, true), None);
+    assert_eq!(text.find("delta", 0, false), None);
+}
+
+#[gpui::test]
+async fn test_is_large_file(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings(cx, |settings| {
+                settings.project.worktree.large_file_threshold = Some(16);
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "small.log": "short",
+            "large.log": "much longer than the threshold",
+            "large.png": "much longer than the threshold",
+        }),
+    )
+    .await;
+    let project = Project::test(fs, [path!("/dir").as_ref()], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+
+    cx.update(|cx| {
+        let is_large_file = |path: &str| {
+            let path = ProjectPath {
+                worktree_id,
+                path: rel_path(path).into(),
+            };
+            is_large_file(&project, &path, cx)
+        };
+        assert!(!is_large_file("small.log"));
+        assert!(is_large_file("large.log"));
+        // Images are opened in the image viewer, however large.
+        assert!(!is_large_file("large.png"));
+    });
+}
+
+#[gpui::test]
+async fn test_save_large_file(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings(cx, |settings| {
+                settings.project.worktree.large_file_threshold = Some(4);
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({ "large.log": "one\ntwo\nthree\n" }))
+        .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    let path = ProjectPath {
+        worktree_id,
+        path: rel_path("large.log").into(),
+    };
+
+    let large_file = cx
+        .update(|cx| LargeFile::try_open(&project, &path, cx).unwrap())
+        .await
+        .unwrap();
+    cx.run_until_parked();
+    large_file
+        .update(cx, |large_file, cx| {
+            large_file.replace_lines(1..2, "TWO", cx)
+        })
+        .unwrap();
+    large_file
+        .update(cx, |large_file, cx| large_file.save(cx))
+        .await
+        .unwrap();
+    cx.run_until_parked();
+
+    assert_eq!(
+        fs.load(path!("/dir/large.log").as_ref()).await.unwrap(),
+        "one\nTWO\nthree\n"
+    );
+    // The file is replaced by a temporary file, which doesn't outlive the save.
+    assert_eq!(
+        fs.files()
+            .into_iter()
+            .filter(|path| path.starts_with(path!("/dir")))
+            .collect::<Vec<_>>(),
+        [path!("/dir/large.log")].map(std::path::PathBuf::from)
+    );
+    large_file.read_with(cx, |large_file, _| {
+        assert!(!large_file.is_loading());
+        assert_eq!(
+            large_file.text().unwrap().to_string_lossy(),
+            "one\nTWO\nthree\n"
+        );
+    });
+}
@@ -0,0 +1,206 @@
+use crate::init_test;
+use collections::HashMap;
+use fs::{FakeFs, Fs as _};
+use futures::StreamExt as _;
+use gpui::TestAppContext;
+use project::{LocalHistoryEntry, LocalHistoryReason, Project};
+use serde_json::json;
+use settings::SettingsStore;
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use util::path;
+
+#[gpui::test]
+async fn test_local_history(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings(cx, |settings| {
+                settings
+                    .local_history
+                    .get_or_insert_default()
+                    .max_entries_per_file = Some(2);
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(path!("/dir"), json!({ "file.txt": "one" }))
+        .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer(path!("/dir/file.txt"), cx)
+        })
+        .await
+        .unwrap();
+    let store = project.read_with(cx, |project, _| project.local_history_store().unwrap());
+    let abs_path = Path::new(path!("/dir/file.txt"));
+
+    let entries = async |cx: &mut TestAppContext| -> Vec<LocalHistoryEntry> {
+        cx.run_until_parked();
+        store
+            .read_with(cx, |store, cx| store.entries(abs_path, cx))
+            .await
+            .unwrap()
+    };
+    let load_entry = async |entry: &LocalHistoryEntry, cx: &mut TestAppContext| -> String {
+        store
+            .read_with(cx, |store, cx| store.load_entry(abs_path, entry, cx))
+            .await
+            .unwrap()
+    };
+    let edit_and_save = async |text: &str, cx: &mut TestAppContext| {
+        buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+    };
+
+    assert!(entries(cx).await.is_empty());
+
+    edit_and_save("two", cx).await;
+    let history = entries(cx).await;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].reason, LocalHistoryReason::Save);
+    assert_eq!(load_entry(&history[0], cx).await, "two");
+
+    // Saving unchanged contents doesn't add a snapshot.
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+    assert_eq!(entries(cx).await.len(), 1);
+
+    // Snapshots beyond the limit are pruned, oldest first. A snapshot with the same contents as
+    // the latest one is skipped, whatever its reason.
+    edit_and_save("three", cx).await;
+    project.update(cx, |project, cx| {
+        project.record_local_history(&buffer, LocalHistoryReason::BeforeAgentEdit, cx)
+    });
+    edit_and_save("four", cx).await;
+    let history = entries(cx).await;
+    assert_eq!(
+        history.iter().map(|entry| entry.reason).collect::<Vec<_>>(),
+        [LocalHistoryReason::Save, LocalHistoryReason::Save]
+    );
+    assert_eq!(load_entry(&history[0], cx).await, "three");
+    assert_eq!(load_entry(&history[1], cx).await, "four");
+}
+
+#[gpui::test]
+async fn test_local_history_sweep(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings(cx, |settings| {
+                settings
+                    .local_history
+                    .get_or_insert_default()
+                    .max_total_size = Some(8);
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({ "a.txt": "", "b.txt": "", "c.txt": "" }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    let store = project.read_with(cx, |project, _| project.local_history_store().unwrap());
+    for (path, texts) in [
+        (path!("/dir/a.txt"), &["a1a1", "a2a2"][..]),
+        (path!("/dir/b.txt"), &["b1b1"]),
+        (path!("/dir/c.txt"), &["c1c1"]),
+    ] {
+        let buffer = project
+            .update(cx, |project, cx| project.open_local_buffer(path, cx))
+            .await
+            .unwrap();
+        for text in texts {
+            buffer.update(cx, |buffer, cx| buffer.set_text(*text, cx));
+            project
+                .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+                .await
+                .unwrap();
+            cx.run_until_parked();
+        }
+    }
+
+    // Snapshots taken in quick succession can share a timestamp, so give them distinct ones. The
+    // snapshot of c.txt is older than the maximum age.
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    let timestamps = HashMap::from_iter([
+        (
+            PathBuf::from(path!("/dir/a.txt")),
+            vec![now - 3000, now - 1000],
+        ),
+        (PathBuf::from(path!("/dir/b.txt")), vec![now - 2000]),
+        (PathBuf::from(path!("/dir/c.txt")), vec![0]),
+    ]);
+    let mut history_dirs = HashMap::default();
+    let mut dirs = fs.read_dir(paths::local_history_dir()).await.unwrap();
+    while let Some(dir) = dirs.next().await {
+        let index_path = dir.unwrap().join("index.json");
+        let mut index: serde_json::Value =
+            serde_json::from_str(&fs.load(&index_path).await.unwrap()).unwrap();
+        let path = PathBuf::from(index["path"].as_str().unwrap());
+        for (entry, timestamp) in index["entries"]
+            .as_array_mut()
+            .unwrap()
+            .iter_mut()
+            .zip(&timestamps[&path])
+        {
+            entry["timestamp"] = json!(timestamp);
+        }
+        fs.atomic_write(index_path.clone(), index.to_string())
+            .await
+            .unwrap();
+        history_dirs.insert(path, index_path.parent().unwrap().to_path_buf());
+    }
+    assert_eq!(history_dirs.len(), 3);
+
+    // The periodic sweep prunes c.txt by age, and then the oldest snapshot across all files to fit
+    // in the total size.
+    cx.executor().advance_clock(Duration::from_secs(60 * 60));
+    cx.run_until_parked();
+    let entries = async |path: &str, cx: &mut TestAppContext| -> Vec<String> {
+        let entries = store
+            .read_with(cx, |store, cx| store.entries(Path::new(path), cx))
+            .await
+            .unwrap();
+        let mut texts = Vec::new();
+        for entry in &entries {
+            texts.push(
+                store
+                    .read_with(cx, |store, cx| store.load_entry(Path::new(path), entry, cx))
+                    .await
+                    .unwrap(),
+            );
+        }
+        texts
+    };
+    assert_eq!(entries(path!("/dir/a.txt"), cx).await, ["a2a2"]);
+    assert_eq!(entries(path!("/dir/b.txt"), cx).await, ["b1b1"]);
+    assert!(entries(path!("/dir/c.txt"), cx).await.is_empty());
+    assert!(
+        !fs.is_dir(&history_dirs[Path::new(path!("/dir/c.txt"))])
+            .await
+    );
+    // Only the index and the remaining snapshot's contents are left.
+    let a_files = fs
+        .read_dir(&history_dirs[Path::new(path!("/dir/a.txt"))])
+        .await
+        .unwrap()
+        .collect::<Vec<_>>()
+        .await;
+    assert_eq!(a_files.len(), 2);
+}
@@ -7,12 +7,15 @@ mod ext_agent_tests;
 mod extension_agent_tests;
 mod git_store;
 mod image_store;
+mod large_file;
+mod local_history;
 mod lsp_command;
 mod lsp_store;
 mod manifest_tree;
 mod project_search;
 mod search;
 mod search_history;
+mod search_index;
 mod signature_help;
 mod task_inventory;
 mod trusted_worktrees;
@@ -130,6 +130,50 @@ fn test_case_sensitive_pattern_items() {
     );
 }
 
+#[test]
+fn test_regex_required_literals() {
+    let required_literals = |pattern: &str| {
+        SearchQuery::regex(
+            pattern,
+            false,
+            true,
+            false,
+            false,
+            Default::default(),
+            Default::default(),
+            false,
+            None,
+        )
+        .expect("Should be able to create a regex SearchQuery")
+        .required_literals()
+    };
+
+    assert_eq!(
+        required_literals("foo.*bar"),
+        Some(vec!["foo".into(), "bar".into()])
+    );
+    assert_eq!(
+        required_literals("ab*c"),
+        Some(vec!["a".into(), "c".into()])
+    );
+    assert_eq!(
+        required_literals("colou?r"),
+        Some(vec!["colo".into(), "r".into()])
+    );
+    assert_eq!(
+        required_literals("fn \\w+\\("),
+        Some(vec!["fn ".into(), "(".into()])
+    );
+    assert_eq!(required_literals("x{2}y{0,3}"), Some(vec!["x".into()]));
+    assert_eq!(
+        required_literals("(foo|bar)_baz"),
+        Some(vec!["_baz".into()])
+    );
+    assert_eq!(required_literals("[a-z\\]]+_id"), Some(vec!["_id".into()]));
+    assert_eq!(required_literals("foo|bar"), None);
+    assert_eq!(required_literals("\\x41bc"), None);
+}
+
 #[gpui::test]
 async fn test_multiline_regex(cx: &mut gpui::TestAppContext) {
     let search_query = SearchQuery::regex(
@@ -154,3 +198,156 @@ async fn test_multiline_regex(cx: &mut gpui::TestAppContext) {
     let results = search_query.search(&snapshot, None).await;
     assert_eq!(results, vec![0..6, 12..18]);
 }
+
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    use language::{Buffer, rust_lang};
+    let text = Rope::from(
+        "fn main() {\n    foo(1, 2);\n    foo(bar(3), 4);\n    self.foo(5, 6);\n    foo(7);\n    let x = a + a;\n    let y = a + b;\n}\n",
+    );
+    let snapshot = cx
+        .update(|app| Buffer::build_snapshot(text, Some(rust_lang()), None, None, app))
+        .await;
+    let matches = |query: &str| {
+        let search_query = SearchQuery::structural(
+            query,
+            false,
+            Default::default(),
+            Default::default(),
+            false,
+            None,
+        )
+        .expect("Should be able to create a structural SearchQuery");
+        let snapshot = snapshot.clone();
+        async move {
+            search_query
+                .search(&snapshot, None)
+                .await
+                .into_iter()
+                .map(|range| snapshot.text_for_range(range).collect::<String>())
+                .collect::<Vec<_>>()
+        }
+    };
+
+    assert_eq!(
+        matches("foo($A, $B)").await,
+        vec!["foo(1, 2)", "foo(bar(3), 4)"]
+    );
+    assert_eq!(
+        matches("foo($$$ARGS)").await,
+        vec!["foo(1, 2)", "foo(bar(3), 4)", "foo(7)"]
+    );
+    assert_eq!(matches("$A + $A").await, vec!["a + a"]);
+    assert_eq!(
+        matches(r#"(call_expression function: (identifier) @name (#eq? @name "foo")) @match"#)
+            .await,
+        vec!["foo(1, 2)", "foo(bar(3), 4)", "foo(7)"]
+    );
+    assert!(
+        SearchQuery::structural(
+            "$A",
+            false,
+            Default::default(),
+            Default::default(),
+            false,
+            None
+        )
+        .is_err()
+    );
+}
+
+#[gpui::test]
+async fn test_structural_replacement(cx: &mut gpui::TestAppContext) {
+    use language::{Buffer, rust_lang};
+    let text = Rope::from("fn main() {\n    foo(1, bar(2));\n}\n");
+    let snapshot = cx
+        .update(|app| Buffer::build_snapshot(text, Some(rust_lang()), None, None, app))
+        .await;
+
+    let search_query = SearchQuery::structural(
+        "foo($A, $B)",
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap()
+    .with_replacement("foo($B, $A, $C, $$$)".to_string());
+    let results = search_query.search(&snapshot, None).await;
+    assert_eq!(results.len(), 1);
+    assert_eq!(
+        search_query
+            .replacement_for_range(&snapshot, results[0].clone())
+            .as_deref(),
+        Some("foo(bar(2), 1, $C, $$$)")
+    );
+}
+
+#[gpui::test]
+async fn test_structural_replacements_for_ranges(cx: &mut gpui::TestAppContext) {
+    use language::{Buffer, rust_lang};
+    let text = Rope::from("fn main() {\n    foo(1, 2);\n    bar(3);\n    foo(4, 5);\n}\n");
+    let snapshot = cx
+        .update(|app| Buffer::build_snapshot(text, Some(rust_lang()), None, None, app))
+        .await;
+
+    let search_query = SearchQuery::structural(
+        "foo($A, $B)",
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap()
+    .with_replacement("foo($B, $A)".to_string());
+    let mut ranges = search_query.search(&snapshot, None).await;
+    assert_eq!(ranges.len(), 2);
+    // A range that isn't a match gets no replacement.
+    ranges.push(0..2);
+    assert_eq!(
+        search_query.replacements_for_ranges(&snapshot, &ranges),
+        vec![
+            Some("foo(2, 1)".to_string()),
+            Some("foo(5, 4)".to_string()),
+            None
+        ]
+    );
+}
+
+#[test]
+fn test_structural_query_to_proto() {
+    let query = SearchQuery::structural(
+        "foo($A, $B)",
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap();
+    let message = query.to_proto();
+    // Servers without structural search see a text query for the candidates.
+    assert_eq!(message.query, "foo");
+    assert!(!message.regex);
+    assert!(message.structural);
+    assert_eq!(message.structural_pattern, "foo($A, $B)");
+
+    let query = SearchQuery::from_proto(message, PathStyle::local()).unwrap();
+    assert!(query.is_structural());
+    assert_eq!(query.as_str(), "foo($A, $B)");
+
+    let query = SearchQuery::structural(
+        "(call_expression) @match",
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap();
+    let message = query.to_proto();
+    assert!(message.regex);
+    assert_eq!(message.structural_pattern, "(call_expression) @match");
+}
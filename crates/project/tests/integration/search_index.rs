@@ -0,0 +1,114 @@
+use std::{sync::Arc, time::Duration};
+
+use crate::{init_test, search};
+use fs::FakeFs;
+use gpui::TestAppContext;
+use project::{Project, search::SearchQuery};
+use serde_json::json;
+use settings::SettingsStore;
+use util::{
+    path,
+    rel_path::{RelPath, rel_path},
+};
+
+fn text_query(query: &str) -> SearchQuery {
+    SearchQuery::text(
+        query,
+        false,
+        false,
+        false,
+        Default::default(),
+        Default::default(),
+        false,
+        None,
+    )
+    .unwrap()
+}
+
+#[gpui::test]
+async fn test_search_index(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings(cx, |settings| {
+                settings.project.worktree.search_index = Some(true);
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        path!("/dir"),
+        json!({
+            "one.rs": "const GREETING: &str = \"Hello, world\";",
+            "two.rs": "fn goodbye() {}",
+            "three.rs": "// hello again",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+    cx.run_until_parked();
+
+    let worktree_id = project.update(cx, |project, cx| {
+        project.worktrees(cx).next().unwrap().read(cx).id()
+    });
+    let candidate_paths = |query: &str, cx: &mut TestAppContext| {
+        project.update(cx, |project, cx| {
+            let mut paths = project
+                .search_index_store()
+                .unwrap()
+                .read(cx)
+                .candidate_paths(worktree_id, &text_query(query))?;
+            paths.sort();
+            Some(paths)
+        })
+    };
+    let rel_paths = |paths: &[&str]| -> Vec<Arc<RelPath>> {
+        paths.iter().map(|path| rel_path(path).into_arc()).collect()
+    };
+
+    assert_eq!(
+        candidate_paths("hello", cx),
+        Some(rel_paths(&["one.rs", "three.rs"]))
+    );
+    assert_eq!(candidate_paths("goodbye", cx), Some(rel_paths(&["two.rs"])));
+    assert_eq!(candidate_paths("nowhere", cx), Some(Vec::new()));
+    // Queries without trigrams can't be narrowed down.
+    assert_eq!(candidate_paths("fn", cx), None);
+
+    // Changed files are re-indexed.
+    fs.save(
+        path!("/dir/two.rs").as_ref(),
+        &"fn goodbye() { hello() }".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.run_until_parked();
+    assert_eq!(
+        candidate_paths("hello", cx),
+        Some(rel_paths(&["one.rs", "three.rs", "two.rs"]))
+    );
+
+    // Searching with the index finds the same matches as without it.
+    let results = search(&project, text_query("hello"), cx).await.unwrap();
+    let mut matched_paths = results.into_keys().collect::<Vec<_>>();
+    matched_paths.sort();
+    assert_eq!(
+        matched_paths,
+        [
+            path!("dir/one.rs"),
+            path!("dir/three.rs"),
+            path!("dir/two.rs")
+        ]
+    );
+
+    // The index is written to disk after a while.
+    cx.executor().advance_clock(Duration::from_secs(60));
+    cx.run_until_parked();
+    assert!(
+        fs.files()
+            .iter()
+            .any(|path| path.starts_with(paths::search_index_dir()))
+    );
+}
@@ -16,7 +16,9 @@ use remote::{
     SshConnectionOptions,
 };
 pub use settings::SshConnection;
-use settings::{DevContainerConnection, ExtendingVec, RegisterSetting, Settings, WslConnection};
+use settings::{
+    DevContainerConnection, ExecConnection, ExtendingVec, RegisterSetting, Settings, WslConnection,
+};
 use util::paths::PathWithPosition;
 use workspace::{
     AppState, MultiWorkspace, OpenOptions, SerializedWorkspaceLocation, Workspace,
@@ -32,6 +34,7 @@ pub use remote_connection::{
 pub struct RemoteSettings {
     pub ssh_connections: ExtendingVec<SshConnection>,
     pub wsl_connections: ExtendingVec<WslConnection>,
+    pub exec_connections: ExtendingVec<ExecConnection>,
     /// Whether to read ~/.ssh/config for ssh connection sources.
     pub read_ssh_config: bool,
 }
@@ -45,6 +48,10 @@ impl RemoteSettings {
         self.wsl_connections.clone().0.into_iter()
     }
 
+    pub fn exec_connections(&self) -> impl Iterator<Item = ExecConnection> + use<> {
+        self.exec_connections.clone().0.into_iter()
+    }
+
     pub fn fill_connection_options_from_settings(&self, options: &mut SshConnectionOptions) {
         for conn in self.ssh_connections() {
             if conn.host == options.host.to_string()
@@ -82,6 +89,7 @@ pub enum Connection {
     Ssh(SshConnection),
     Wsl(WslConnection),
     DevContainer(DevContainerConnection),
+    Exec(ExecConnection),
 }
 
 impl From<Connection> for RemoteConnectionOptions {
@@ -99,6 +107,7 @@ impl From<Connection> for RemoteConnectionOptions {
                     remote_env: conn.remote_env,
                 })
             }
+            Connection::Exec(conn) => RemoteConnectionOptions::Exec(conn.into()),
         }
     }
 }
@@ -115,12 +124,19 @@ impl From<WslConnection> for Connection {
     }
 }
 
+impl From<ExecConnection> for Connection {
+    fn from(val: ExecConnection) -> Self {
+        Connection::Exec(val)
+    }
+}
+
 impl Settings for RemoteSettings {
     fn from_settings(content: &settings::SettingsContent) -> Self {
         let remote = &content.remote;
         Self {
             ssh_connections: remote.ssh_connections.clone().unwrap_or_default().into(),
             wsl_connections: remote.wsl_connections.clone().unwrap_or_default().into(),
+            exec_connections: remote.exec_connections.clone().unwrap_or_default().into(),
             read_ssh_config: remote.read_ssh_config.unwrap(),
         }
     }
@@ -320,6 +336,9 @@ pub async fn open_remote_project(
                                 RemoteConnectionOptions::Docker(_) => {
                                     "Failed to connect to Dev Container"
                                 }
+                                RemoteConnectionOptions::Exec(_) => {
+                                    "Failed to connect through custom command"
+                                }
                                 #[cfg(any(test, feature = "test-support"))]
                                 RemoteConnectionOptions::Mock(_) => {
                                     "Failed to connect to mock server"
@@ -381,6 +400,9 @@ pub async fn open_remote_project(
                                 RemoteConnectionOptions::Docker(_) => {
                                     "Failed to connect to Dev Container"
                                 }
+                                RemoteConnectionOptions::Exec(_) => {
+                                    "Failed to connect through custom command"
+                                }
                                 #[cfg(any(test, feature = "test-support"))]
                                 RemoteConnectionOptions::Mock(_) => {
                                     "Failed to connect to mock server"
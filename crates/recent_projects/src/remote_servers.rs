@@ -24,8 +24,8 @@ use paths::{global_ssh_config_file, user_ssh_config_file};
 use picker::{Picker, PickerDelegate};
 use project::{Fs, Project};
 use remote::{
-    RemoteClient, RemoteConnectionOptions, SshConnectionOptions, WslConnectionOptions,
-    remote_client::ConnectionIdentifier,
+    ExecConnectionOptions, RemoteClient, RemoteConnectionOptions, SshConnectionOptions,
+    WslConnectionOptions, remote_client::ConnectionIdentifier,
 };
 use settings::{
     RemoteProject, RemoteSettingsContent, Settings as _, SettingsStore, update_settings_file,
@@ -414,6 +414,10 @@ impl ProjectPicker {
                 connection_string: "".into(),
                 nickname: None,
             },
+            RemoteConnectionOptions::Exec(connection) => ProjectPickerData::Ssh {
+                connection_string: connection.name.clone().into(),
+                nickname: None,
+            },
             #[cfg(any(test, feature = "test-support"))]
             RemoteConnectionOptions::Mock(options) => ProjectPickerData::Ssh {
                 connection_string: format!("mock-{}", options.id).into(),
@@ -482,6 +486,16 @@ impl ProjectPicker {
                                         server.projects.insert(RemoteProject { paths });
                                     };
                                 }
+                                ServerIndex::Exec(index) => {
+                                    if let Some(server) = settings
+                                        .remote
+                                        .exec_connections
+                                        .as_mut()
+                                        .and_then(|connections| connections.get_mut(index.0))
+                                    {
+                                        server.projects.insert(RemoteProject { paths });
+                                    };
+                                }
                             }
                         });
                     })
@@ -603,10 +617,20 @@ impl std::fmt::Display for WslServerIndex {
     }
 }
 
+#[repr(transparent)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+struct ExecServerIndex(usize);
+impl std::fmt::Display for ExecServerIndex {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 enum ServerIndex {
     Ssh(SshServerIndex),
     Wsl(WslServerIndex),
+    Exec(ExecServerIndex),
 }
 impl From<SshServerIndex> for ServerIndex {
     fn from(index: SshServerIndex) -> Self {
@@ -618,6 +642,11 @@ impl From<WslServerIndex> for ServerIndex {
         Self::Wsl(index)
     }
 }
+impl From<ExecServerIndex> for ServerIndex {
+    fn from(index: ExecServerIndex) -> Self {
+        Self::Exec(index)
+    }
+}
 
 #[derive(Clone)]
 enum RemoteEntry {
@@ -712,7 +741,31 @@ impl DefaultState {
                 }
             });
 
-        let mut servers = ssh_servers.chain(wsl_servers).collect::<Vec<RemoteEntry>>();
+        let exec_servers =
+            ssh_settings
+                .exec_connections()
+                .enumerate()
+                .map(|(index, connection)| {
+                    let open_folder = NavigableEntry::new(&handle, cx);
+                    let configure = NavigableEntry::new(&handle, cx);
+                    let projects = connection
+                        .projects
+                        .iter()
+                        .map(|project| (NavigableEntry::new(&handle, cx), project.clone()))
+                        .collect();
+                    RemoteEntry::Project {
+                        open_folder,
+                        configure,
+                        projects,
+                        index: ServerIndex::Exec(ExecServerIndex(index)),
+                        connection: connection.into(),
+                    }
+                });
+
+        let mut servers = ssh_servers
+            .chain(wsl_servers)
+            .chain(exec_servers)
+            .collect::<Vec<RemoteEntry>>();
 
         if read_ssh_config {
             let mut extra_servers_from_config = ssh_config_servers.clone();
@@ -755,6 +808,11 @@ enum ViewServerOptionsState {
         server_index: WslServerIndex,
         entries: [NavigableEntry; 2],
     },
+    Exec {
+        connection: ExecConnectionOptions,
+        server_index: ExecServerIndex,
+        entries: [NavigableEntry; 2],
+    },
 }
 
 impl ViewServerOptionsState {
@@ -762,6 +820,7 @@ impl ViewServerOptionsState {
         match self {
             Self::Ssh { entries, .. } => entries,
             Self::Wsl { entries, .. } => entries,
+            Self::Exec { entries, .. } => entries,
         }
     }
 }
@@ -1129,6 +1188,13 @@ impl RemoteServerProjects {
                     entries: std::array::from_fn(|_| NavigableEntry::focusable(cx)),
                 }
             }
+            (ServerIndex::Exec(server_index), RemoteConnectionOptions::Exec(connection)) => {
+                ViewServerOptionsState::Exec {
+                    connection,
+                    server_index,
+                    entries: std::array::from_fn(|_| NavigableEntry::focusable(cx)),
+                }
+            }
             _ => {
                 log::error!("server index and connection options mismatch");
                 self.mode = Mode::default_mode(&BTreeSet::default(), cx);
@@ -1340,6 +1406,11 @@ impl RemoteServerProjects {
             Connection::DevContainer(dev_container_options) => {
                 (dev_container_options.name.clone(), None, false)
             }
+            Connection::Exec(exec_connection) => (
+                exec_connection.name.clone(),
+                Some(SharedString::from(format!("({})", exec_connection.command))),
+                false,
+            ),
         };
         v_flex()
             .w_full()
@@ -1533,6 +1604,7 @@ impl RemoteServerProjects {
             match server_ix {
                 ServerIndex::Ssh(index) => format!("ssh-{index}"),
                 ServerIndex::Wsl(index) => format!("wsl-{index}"),
+                ServerIndex::Exec(index) => format!("exec-{index}"),
             }
         ));
         let container_element_id_base =
@@ -1683,6 +1755,9 @@ impl RemoteServerProjects {
             ServerIndex::Wsl(server) => {
                 self.delete_wsl_project(server, project, cx);
             }
+            ServerIndex::Exec(server) => {
+                self.delete_exec_project(server, project, cx);
+            }
         }
     }
 
@@ -1730,6 +1805,34 @@ impl RemoteServerProjects {
         });
     }
 
+    fn delete_exec_project(
+        &mut self,
+        server: ExecServerIndex,
+        project: &RemoteProject,
+        cx: &mut Context<Self>,
+    ) {
+        let project = project.clone();
+        self.update_settings_file(cx, move |setting, _| {
+            if let Some(server) = setting
+                .exec_connections
+                .as_mut()
+                .and_then(|connections| connections.get_mut(server.0))
+            {
+                server.projects.remove(&project);
+            }
+        });
+    }
+
+    fn delete_exec_connection(&mut self, server: ExecServerIndex, cx: &mut Context<Self>) {
+        self.update_settings_file(cx, move |setting, _| {
+            if let Some(connections) = setting.exec_connections.as_mut()
+                && connections.get(server.0).is_some()
+            {
+                connections.remove(server.0);
+            }
+        });
+    }
+
     fn add_ssh_server(
         &mut self,
         connection_options: remote::SshConnectionOptions,
@@ -2209,6 +2312,15 @@ impl RemoteServerProjects {
                     }
                     .render(window, cx)
                     .into_any_element(),
+                    ViewServerOptionsState::Exec { connection, .. } => SshConnectionHeader {
+                        connection_string: connection.command.clone().into(),
+                        paths: Default::default(),
+                        nickname: Some(connection.name.clone().into()),
+                        is_wsl: false,
+                        is_devcontainer: false,
+                    }
+                    .render(window, cx)
+                    .into_any_element(),
                 })
                 .child(
                     v_flex()
@@ -2237,6 +2349,17 @@ impl RemoteServerProjects {
                                 window,
                                 cx,
                             )),
+                            ViewServerOptionsState::Exec {
+                                connection,
+                                entries,
+                                server_index,
+                            } => this.child(self.render_edit_exec(
+                                connection,
+                                *server_index,
+                                entries,
+                                window,
+                                cx,
+                            )),
                         })
                         .child(ListSeparator)
                         .child({
@@ -2345,6 +2468,73 @@ impl RemoteServerProjects {
         })
     }
 
+    fn render_edit_exec(
+        &self,
+        connection: &ExecConnectionOptions,
+        index: ExecServerIndex,
+        entries: &[NavigableEntry],
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> impl IntoElement {
+        let name = SharedString::new(connection.name.clone());
+
+        v_flex().child({
+            fn remove_exec_connection(
+                remote_servers: Entity<RemoteServerProjects>,
+                index: ExecServerIndex,
+                name: SharedString,
+                window: &mut Window,
+                cx: &mut App,
+            ) {
+                let prompt_message = format!("Remove connection `{}`?", name);
+
+                let confirmation = window.prompt(
+                    PromptLevel::Warning,
+                    &prompt_message,
+                    None,
+                    &["Yes, remove it", "No, keep it"],
+                    cx,
+                );
+
+                cx.spawn(async move |cx| {
+                    if confirmation.await.ok() == Some(0) {
+                        remote_servers.update(cx, |this, cx| {
+                            this.delete_exec_connection(index, cx);
+                        });
+                        remote_servers.update(cx, |this, cx| {
+                            this.mode = Mode::default_mode(&this.ssh_config_servers, cx);
+                            cx.notify();
+                        });
+                    }
+                    anyhow::Ok(())
+                })
+                .detach_and_log_err(cx);
+            }
+            div()
+                .id("exec-options-remove-connection")
+                .track_focus(&entries[0].focus_handle)
+                .on_action(cx.listener({
+                    let name = name.clone();
+                    move |_, _: &menu::Confirm, window, cx| {
+                        remove_exec_connection(cx.entity(), index, name.clone(), window, cx);
+                        cx.focus_self(window);
+                    }
+                }))
+                .child(
+                    ListItem::new("remove-connection")
+                        .toggle_state(entries[0].focus_handle.contains_focused(window, cx))
+                        .inset(true)
+                        .spacing(ui::ListItemSpacing::Sparse)
+                        .start_slot(Icon::new(IconName::Trash).color(Color::Error))
+                        .child(Label::new("Remove Connection").color(Color::Error))
+                        .on_click(cx.listener(move |_, _, window, cx| {
+                            remove_exec_connection(cx.entity(), index, name.clone(), window, cx);
+                            cx.focus_self(window);
+                        })),
+                )
+        })
+    }
+
     fn render_edit_ssh(
         &self,
         connection: &SshConnectionOptions,
@@ -2579,7 +2769,18 @@ impl RemoteServerProjects {
                 _ => None,
             }));
 
-        if ssh_connections_changed || wsl_connections_changed {
+        let exec_connections_changed = ssh_settings.exec_connections.0.iter().ne(state
+            .servers
+            .iter()
+            .filter_map(|server| match server {
+                RemoteEntry::Project {
+                    connection: Connection::Exec(connection),
+                    ..
+                } => Some(connection),
+                _ => None,
+            }));
+
+        if ssh_connections_changed || wsl_connections_changed || exec_connections_changed {
             should_rebuild = true;
         };
 
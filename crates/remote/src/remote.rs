@@ -12,6 +12,7 @@ pub use remote_client::{
     RemotePlatform, connect,
 };
 pub use transport::docker::DockerConnectionOptions;
+pub use transport::exec::ExecConnectionOptions;
 pub use transport::ssh::{SshConnectionOptions, SshPortForwardOption};
 pub use transport::wsl::WslConnectionOptions;
 #[cfg(target_os = "windows")]
@@ -6,6 +6,7 @@ use crate::{
     proxy::ProxyLaunchError,
     transport::{
         docker::{DockerConnectionOptions, DockerExecConnection},
+        exec::{ExecConnectionOptions, ExecRemoteConnection},
         ssh::SshRemoteConnection,
         wsl::{WslConnectionOptions, WslRemoteConnection},
     },
@@ -1231,6 +1232,11 @@ impl ConnectionPool {
                                 .await
                                 .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>)
                         }
+                        RemoteConnectionOptions::Exec(opts) => {
+                            ExecRemoteConnection::new(opts, delegate, cx)
+                                .await
+                                .map(|connection| Arc::new(connection) as Arc<dyn RemoteConnection>)
+                        }
                         #[cfg(any(test, feature = "test-support"))]
                         RemoteConnectionOptions::Mock(opts) => match cx.update(|cx| {
                             cx.default_global::<crate::transport::mock::MockConnectionRegistry>()
@@ -1278,6 +1284,7 @@ pub enum RemoteConnectionOptions {
     Ssh(SshConnectionOptions),
     Wsl(WslConnectionOptions),
     Docker(DockerConnectionOptions),
+    Exec(ExecConnectionOptions),
     #[cfg(any(test, feature = "test-support"))]
     Mock(crate::transport::mock::MockConnectionOptions),
 }
@@ -1294,6 +1301,7 @@ impl RemoteConnectionOptions {
                     opts.name.clone()
                 }
             }
+            RemoteConnectionOptions::Exec(opts) => opts.name.clone(),
             #[cfg(any(test, feature = "test-support"))]
             RemoteConnectionOptions::Mock(opts) => format!("mock-{}", opts.id),
         }
@@ -1312,6 +1320,12 @@ impl From<WslConnectionOptions> for RemoteConnectionOptions {
     }
 }
 
+impl From<ExecConnectionOptions> for RemoteConnectionOptions {
+    fn from(opts: ExecConnectionOptions) -> Self {
+        RemoteConnectionOptions::Exec(opts)
+    }
+}
+
 #[cfg(any(test, feature = "test-support"))]
 impl From<crate::transport::mock::MockConnectionOptions> for RemoteConnectionOptions {
     fn from(opts: crate::transport::mock::MockConnectionOptions) -> Self {
@@ -0,0 +1,766 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write as _,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Instant,
+};
+
+use anyhow::{Context as _, Result, anyhow};
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::{
+    AsyncRead, AsyncWriteExt as _,
+    channel::mpsc::{Sender, UnboundedReceiver, UnboundedSender},
+};
+use gpui::{App, AppContext as _, AsyncApp, Task};
+use parking_lot::Mutex;
+use release_channel::{AppCommitSha, AppVersion, ReleaseChannel};
+use rpc::proto::Envelope;
+use semver::Version;
+use util::{
+    command::Stdio,
+    paths::{PathStyle, RemotePathBuf},
+    rel_path::RelPath,
+    shell::ShellKind,
+};
+
+use crate::{
+    RemoteArch, RemoteClientDelegate, RemoteOs, RemotePlatform,
+    remote_client::{CommandTemplate, Interactive, RemoteConnection, RemoteConnectionOptions},
+    transport::{parse_platform, parse_shell},
+};
+
+/// The placeholder in a command template that stands for the program to run on the remote.
+const COMMAND_PLACEHOLDER: &str = "cmd";
+
+/// A remote reached by running programs through a user-provided command, such as
+/// `kubectl exec -i my-pod -- {cmd}` or `nix develop ./env --command {cmd}`.
+///
+/// Everything, including the upload of the server binary, goes through that command,
+/// so the only requirement on the remote is a POSIX shell.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    PartialEq,
+    Eq,
+    Hash,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+pub struct ExecConnectionOptions {
+    /// Name to use for this connection in UI.
+    pub name: String,
+    /// The command template. A `{cmd}` argument is replaced with the program and its
+    /// arguments, and `{cmd}` within a larger argument with the shell-quoted command line.
+    /// The program is appended when there's no `{cmd}` placeholder.
+    pub command: String,
+    /// The command template for interactive programs such as terminals, which usually
+    /// need a TTY (e.g. `kubectl exec -it my-pod -- {cmd}`). Defaults to `command`.
+    pub interactive_command: Option<String>,
+    /// Values for the other `{placeholder}`s in the command templates.
+    pub variables: BTreeMap<String, String>,
+    /// Whether to download the server binary locally and upload it through the command,
+    /// rather than downloading it on the remote.
+    pub upload_binary_over_exec: bool,
+}
+
+impl From<settings::ExecConnection> for ExecConnectionOptions {
+    fn from(val: settings::ExecConnection) -> Self {
+        ExecConnectionOptions {
+            name: val.name,
+            command: val.command,
+            interactive_command: val.interactive_command,
+            variables: val.variables,
+            upload_binary_over_exec: val.upload_binary_over_exec.unwrap_or_default(),
+        }
+    }
+}
+
+impl ExecConnectionOptions {
+    /// Returns the local program and arguments which run `program` on the remote.
+    pub fn expand(
+        &self,
+        program: &[String],
+        interactive: Interactive,
+    ) -> Result<(String, Vec<String>)> {
+        let template = match interactive {
+            Interactive::Yes => self.interactive_command.as_deref().unwrap_or(&self.command),
+            Interactive::No => &self.command,
+        };
+        let words = ShellKind::Posix
+            .split(template)
+            .with_context(|| format!("invalid command template {template:?}"))?;
+
+        let mut args = Vec::new();
+        let mut has_command = false;
+        for word in words {
+            if word == format!("{{{COMMAND_PLACEHOLDER}}}") {
+                args.extend(program.iter().cloned());
+                has_command = true;
+            } else {
+                let (word, contains_command) = self.substitute(&word, program)?;
+                has_command |= contains_command;
+                args.push(word);
+            }
+        }
+        if !has_command {
+            args.extend(program.iter().cloned());
+        }
+
+        let mut args = args.into_iter();
+        let program = args.next().context("empty command template")?;
+        Ok((program, args.collect()))
+    }
+
+    /// Fills in the placeholders within a single word of the template, leaving shell
+    /// parameter expansions such as `${HOME}` alone.
+    fn substitute(&self, word: &str, program: &[String]) -> Result<(String, bool)> {
+        let mut result = String::new();
+        let mut contains_command = false;
+        let mut rest = word;
+        while let Some(start) = rest.find('{')
+            && let Some(len) = rest[start..].find('}')
+        {
+            let (prefix, placeholder) = (&rest[..start], &rest[start..=start + len]);
+            let name = &placeholder[1..placeholder.len() - 1];
+            result.push_str(prefix);
+            if prefix.ends_with('$') || !is_placeholder_name(name) {
+                result.push_str(placeholder);
+            } else if name == COMMAND_PLACEHOLDER {
+                let quoted = program
+                    .iter()
+                    .map(|arg| ShellKind::Posix.try_quote(arg).context("shell quoting"))
+                    .collect::<Result<Vec<_>>>()?;
+                result.push_str(&quoted.join(" "));
+                contains_command = true;
+            } else {
+                let value = self.variables.get(name).with_context(|| {
+                    format!("no value for {placeholder} in connection {:?}", self.name)
+                })?;
+                result.push_str(value);
+            }
+            rest = &rest[start + len + 1..];
+        }
+        result.push_str(rest);
+        Ok((result, contains_command))
+    }
+}
+
+fn is_placeholder_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+pub(crate) struct ExecRemoteConnection {
+    proxy_process: Mutex<Option<u32>>,
+    remote_binary_path: Option<Arc<RelPath>>,
+    platform: RemotePlatform,
+    shell: String,
+    connection_options: ExecConnectionOptions,
+}
+
+impl ExecRemoteConnection {
+    pub(crate) async fn new(
+        connection_options: ExecConnectionOptions,
+        delegate: Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<Self> {
+        log::info!(
+            "Connecting to {} through {:?}",
+            connection_options.name,
+            connection_options.command
+        );
+        let (release_channel, version, commit) = cx.update(|cx| {
+            (
+                ReleaseChannel::global(cx),
+                AppVersion::global(cx),
+                AppCommitSha::try_global(cx),
+            )
+        });
+
+        let mut this = Self {
+            proxy_process: Mutex::new(None),
+            remote_binary_path: None,
+            platform: RemotePlatform {
+                os: RemoteOs::Linux,
+                arch: RemoteArch::X86_64,
+            },
+            shell: "sh".to_owned(),
+            connection_options,
+        };
+
+        delegate.set_status(Some("Detecting remote environment"), cx);
+        this.platform =
+            parse_platform(&this.run("uname -sm").await?).context("failed detecting platform")?;
+        log::info!("Remote platform discovered: {:?}", this.platform);
+
+        this.shell = match this.run("echo $SHELL").await {
+            Ok(output) => parse_shell(&output, "sh"),
+            Err(e) => {
+                log::error!("Failed to detect remote shell: {e}");
+                "sh".to_owned()
+            }
+        };
+        log::info!("Remote shell discovered: {}", this.shell);
+
+        this.remote_binary_path = Some(
+            this.ensure_server_binary(&delegate, release_channel, version, commit, cx)
+                .await
+                .context("failed ensuring server binary")?,
+        );
+
+        Ok(this)
+    }
+
+    /// Builds a command running `script` with `sh` in the remote home directory.
+    fn command(&self, script: &str, interactive: Interactive) -> Result<util::command::Command> {
+        let script = format!("cd && {script}");
+        let (program, args) = self
+            .connection_options
+            .expand(&["sh".to_owned(), "-c".to_owned(), script], interactive)?;
+        let mut command = util::command::new_command(program);
+        command
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        log::debug!("exec {:?}", command);
+        Ok(command)
+    }
+
+    async fn run(&self, script: &str) -> Result<String> {
+        let mut command = self.command(script, Interactive::No)?;
+        let output = command
+            .output()
+            .await
+            .with_context(|| format!("failed to run command {command:?}"))?;
+        anyhow::ensure!(
+            output.status.success(),
+            "command {command:?} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    async fn run_with_input(&self, script: &str, mut input: impl AsyncRead + Unpin) -> Result<()> {
+        run_with_input_impl(self.command(script, Interactive::No)?, &mut input).await
+    }
+
+    async fn ensure_server_binary(
+        &self,
+        delegate: &Arc<dyn RemoteClientDelegate>,
+        release_channel: ReleaseChannel,
+        version: Version,
+        commit: Option<AppCommitSha>,
+        cx: &mut AsyncApp,
+    ) -> Result<Arc<RelPath>> {
+        let version_str = match release_channel {
+            ReleaseChannel::Nightly => {
+                let commit = commit.map(|s| s.full()).unwrap_or_default();
+                format!("{}-{}", version, commit)
+            }
+            ReleaseChannel::Dev => "build".to_string(),
+            _ => version.to_string(),
+        };
+        let binary_name = format!(
+            "zed-remote-server-{}-{}",
+            release_channel.dev_name(),
+            version_str
+        );
+        let dst_path =
+            paths::remote_server_dir_relative().join(RelPath::unix(&binary_name).unwrap());
+
+        let binary_exists_on_server = self
+            .run(&format!(
+                "{} version",
+                quote(&dst_path.display(PathStyle::Posix))?
+            ))
+            .await
+            .is_ok();
+
+        #[cfg(any(debug_assertions, feature = "build-remote-server-binary"))]
+        if let Some(remote_server_path) = super::build_remote_server_from_source(
+            &self.platform,
+            delegate.as_ref(),
+            binary_exists_on_server,
+            cx,
+        )
+        .await?
+        {
+            let tmp_path = paths::remote_server_dir_relative().join(
+                RelPath::unix(&format!(
+                    "download-{}-{}",
+                    std::process::id(),
+                    remote_server_path.file_name().unwrap().to_string_lossy()
+                ))
+                .unwrap(),
+            );
+            self.upload_file(&remote_server_path, &tmp_path, delegate, cx)
+                .await?;
+            self.extract_server_binary(&dst_path, &tmp_path, delegate, cx)
+                .await?;
+            return Ok(dst_path);
+        }
+
+        if binary_exists_on_server {
+            return Ok(dst_path);
+        }
+
+        let wanted_version = cx.update(|cx| match release_channel {
+            ReleaseChannel::Nightly => Ok(None),
+            ReleaseChannel::Dev => {
+                anyhow::bail!(
+                    "ZED_BUILD_REMOTE_SERVER is not set and no remote server exists at ({:?})",
+                    dst_path
+                )
+            }
+            _ => Ok(Some(AppVersion::global(cx))),
+        })?;
+
+        let tmp_path_gz = paths::remote_server_dir_relative().join(
+            RelPath::unix(&format!(
+                "{}-download-{}.gz",
+                binary_name,
+                std::process::id()
+            ))
+            .unwrap(),
+        );
+        if !self.connection_options.upload_binary_over_exec
+            && let Some(url) = delegate
+                .get_download_url(self.platform, release_channel, wanted_version.clone(), cx)
+                .await?
+        {
+            match self
+                .download_binary_on_server(&url, &tmp_path_gz, delegate, cx)
+                .await
+            {
+                Ok(_) => {
+                    self.extract_server_binary(&dst_path, &tmp_path_gz, delegate, cx)
+                        .await
+                        .context("extracting server binary")?;
+                    return Ok(dst_path);
+                }
+                Err(e) => {
+                    log::error!(
+                        "Failed to download binary on server, attempting to download locally and then upload it the server: {e:#}",
+                    )
+                }
+            }
+        }
+
+        let src_path = delegate
+            .download_server_binary_locally(self.platform, release_channel, wanted_version, cx)
+            .await
+            .context("downloading server binary locally")?;
+        self.upload_file(&src_path, &tmp_path_gz, delegate, cx)
+            .await
+            .context("uploading server binary")?;
+        self.extract_server_binary(&dst_path, &tmp_path_gz, delegate, cx)
+            .await
+            .context("extracting server binary")?;
+        Ok(dst_path)
+    }
+
+    async fn download_binary_on_server(
+        &self,
+        url: &str,
+        tmp_path_gz: &RelPath,
+        delegate: &Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        delegate.set_status(Some("Downloading remote development server on host"), cx);
+
+        let url = quote(url)?;
+        let tmp_path = quote(&tmp_path_gz.display(PathStyle::Posix))?;
+        let mut script = String::new();
+        if let Some(parent) = tmp_path_gz.parent() {
+            write!(
+                script,
+                "mkdir -p {} && ",
+                quote(&parent.display(PathStyle::Posix))?
+            )?;
+        }
+        write!(
+            script,
+            "if command -v curl >/dev/null 2>&1; then curl -f -L {url} -o {tmp_path}; \
+             elif command -v wget >/dev/null 2>&1; then wget {url} -O {tmp_path}; \
+             else echo 'Neither curl nor wget is available' >&2; exit 1; fi"
+        )?;
+        self.run(&script).await.map(|_| ())
+    }
+
+    async fn upload_file(
+        &self,
+        src_path: &Path,
+        dst_path: &RelPath,
+        delegate: &Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        delegate.set_status(Some("Uploading remote development server"), cx);
+
+        let t0 = Instant::now();
+        let file = smol::fs::File::open(src_path)
+            .await
+            .with_context(|| format!("source path does not exist: {}", src_path.display()))?;
+        let size = file.metadata().await?.len();
+        log::info!(
+            "uploading remote development server to {:?} ({}kb)",
+            dst_path,
+            size / 1024
+        );
+
+        let mut script = String::new();
+        if let Some(parent) = dst_path.parent() {
+            write!(
+                script,
+                "mkdir -p {} && ",
+                quote(&parent.display(PathStyle::Posix))?
+            )?;
+        }
+        write!(
+            script,
+            "cat > {}",
+            quote(&dst_path.display(PathStyle::Posix))?
+        )?;
+        self.run_with_input(&script, file).await.with_context(|| {
+            format!(
+                "failed to upload {} to {}",
+                src_path.display(),
+                dst_path.display(PathStyle::Posix)
+            )
+        })?;
+
+        log::info!("uploaded remote development server in {:?}", t0.elapsed());
+        Ok(())
+    }
+
+    async fn extract_server_binary(
+        &self,
+        dst_path: &RelPath,
+        tmp_path: &RelPath,
+        delegate: &Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Result<()> {
+        delegate.set_status(Some("Extracting remote development server"), cx);
+
+        let orig_tmp_path = tmp_path.display(PathStyle::Posix);
+        let dst_path = quote(&dst_path.display(PathStyle::Posix))?;
+        let script = if let Some(tmp_path) = orig_tmp_path.strip_suffix(".gz") {
+            let orig_tmp_path = quote(&orig_tmp_path)?;
+            let tmp_path = quote(tmp_path)?;
+            format!(
+                "gunzip -f {orig_tmp_path} && chmod 755 {tmp_path} && mv -f {tmp_path} {dst_path}"
+            )
+        } else {
+            let tmp_path = quote(&orig_tmp_path)?;
+            format!("chmod 755 {tmp_path} && mv -f {tmp_path} {dst_path}")
+        };
+        self.run(&script).await.map(|_| ())
+    }
+
+    fn kill_inner(&self) -> Result<()> {
+        if let Some(pid) = self.proxy_process.lock().take() {
+            util::command::new_command("kill")
+                .arg(pid.to_string())
+                .spawn()
+                .context("failed to kill process")?;
+        }
+        Ok(())
+    }
+}
+
+fn quote(arg: &str) -> Result<String> {
+    Ok(ShellKind::Posix
+        .try_quote(arg)
+        .context("shell quoting")?
+        .into_owned())
+}
+
+async fn run_with_input_impl(
+    mut command: util::command::Command,
+    input: &mut (impl AsyncRead + Unpin),
+) -> Result<()> {
+    let mut child = command
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to run command {command:?}"))?;
+    let mut stdin = child.stdin.take().context("failed to open stdin")?;
+    futures::io::copy(input, &mut stdin).await?;
+    stdin.close().await?;
+    drop(stdin);
+
+    let output = child.output().await?;
+    anyhow::ensure!(
+        output.status.success(),
+        "command {command:?} failed: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    Ok(())
+}
+
+#[async_trait(?Send)]
+impl RemoteConnection for ExecRemoteConnection {
+    fn start_proxy(
+        &self,
+        unique_identifier: String,
+        reconnect: bool,
+        incoming_tx: UnboundedSender<Envelope>,
+        outgoing_rx: UnboundedReceiver<Envelope>,
+        connection_activity_tx: Sender<()>,
+        delegate: Arc<dyn RemoteClientDelegate>,
+        cx: &mut AsyncApp,
+    ) -> Task<Result<i32>> {
+        if !self.has_been_killed()
+            && let Err(e) = self.kill_inner()
+        {
+            return Task::ready(Err(e));
+        }
+
+        delegate.set_status(Some("Starting proxy"), cx);
+
+        let Some(remote_binary_path) = &self.remote_binary_path else {
+            return Task::ready(Err(anyhow!("Remote binary path not set")));
+        };
+
+        let command = util::maybe!({
+            let mut script = String::from("exec env");
+            for env_var in ["RUST_LOG", "RUST_BACKTRACE", "ZED_GENERATE_MINIDUMPS"] {
+                if let Some(value) = std::env::var(env_var).ok() {
+                    write!(script, " {}", quote(&format!("{env_var}={value}"))?)?;
+                }
+            }
+            write!(
+                script,
+                " {} proxy --identifier {}",
+                quote(&remote_binary_path.display(PathStyle::Posix))?,
+                quote(&unique_identifier)?
+            )?;
+            if reconnect {
+                script.push_str(" --reconnect");
+            }
+            self.command(&script, Interactive::No)
+        });
+        let mut command = match command {
+            Ok(command) => command,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        let child = match command.kill_on_drop(true).spawn() {
+            Ok(child) => child,
+            Err(error) => {
+                return Task::ready(Err(anyhow!("failed to spawn remote server: {}", error)));
+            }
+        };
+        *self.proxy_process.lock() = Some(child.id());
+
+        cx.spawn(async move |cx| {
+            super::handle_rpc_messages_over_child_process_stdio(
+                child,
+                incoming_tx,
+                outgoing_rx,
+                connection_activity_tx,
+                cx,
+            )
+            .await
+            .and_then(|status| {
+                if status != 0 {
+                    anyhow::bail!("Remote server exited with status {status}");
+                }
+                Ok(0)
+            })
+        })
+    }
+
+    fn upload_directory(
+        &self,
+        src_path: PathBuf,
+        dest_path: RemotePathBuf,
+        cx: &App,
+    ) -> Task<Result<()>> {
+        let command = quote(&dest_path.to_string()).and_then(|dest| {
+            self.command(
+                &format!("mkdir -p {dest} && tar -C {dest} -xf -"),
+                Interactive::No,
+            )
+        });
+        cx.background_spawn(async move {
+            let mut tar = util::command::new_command("tar");
+            tar.arg("-C")
+                .arg(&src_path)
+                .args(["-cf", "-", "."])
+                .stdout(Stdio::piped())
+                .kill_on_drop(true);
+            let mut tar = tar.spawn().context("failed to run tar")?;
+            let mut archive = tar.stdout.take().context("failed to read tar output")?;
+            run_with_input_impl(command?, &mut archive)
+                .await
+                .with_context(|| {
+                    format!(
+                        "failed to upload directory {} -> {}",
+                        src_path.display(),
+                        dest_path
+                    )
+                })?;
+            let status = tar.status().await?;
+            anyhow::ensure!(status.success(), "tar exited with {status}");
+            Ok(())
+        })
+    }
+
+    async fn kill(&self) -> Result<()> {
+        self.kill_inner()
+    }
+
+    fn has_been_killed(&self) -> bool {
+        self.proxy_process.lock().is_none()
+    }
+
+    fn build_command(
+        &self,
+        program: Option<String>,
+        args: &[String],
+        env: &HashMap<String, String>,
+        working_dir: Option<String>,
+        _port_forward: Option<(u16, String, u16)>,
+        interactive: Interactive,
+    ) -> Result<CommandTemplate> {
+        let mut script = String::new();
+        if let Some(working_dir) = working_dir {
+            let working_dir = RemotePathBuf::new(working_dir, PathStyle::Posix).to_string();
+            if working_dir == "~" {
+                script.push_str("cd && ");
+            } else if let Some(relative) = working_dir.strip_prefix("~/") {
+                write!(script, "cd \"$HOME\"/{} && ", quote(relative)?)?;
+            } else {
+                write!(script, "cd {} && ", quote(&working_dir)?)?;
+            }
+        }
+
+        script.push_str("exec env");
+        for (key, value) in env.iter() {
+            write!(script, " {}", quote(&format!("{key}={value}"))?)?;
+        }
+        if let Some(program) = program {
+            write!(
+                script,
+                " {}",
+                ShellKind::Posix
+                    .try_quote_prefix_aware(&program)
+                    .context("shell quoting")?
+            )?;
+            for arg in args {
+                write!(script, " {}", quote(arg)?)?;
+            }
+        } else {
+            write!(script, " {} -l", self.shell)?;
+        }
+
+        let (program, args) = self
+            .connection_options
+            .expand(&["sh".to_owned(), "-c".to_owned(), script], interactive)?;
+        Ok(CommandTemplate {
+            program,
+            args,
+            env: Default::default(),
+        })
+    }
+
+    fn build_forward_ports_command(
+        &self,
+        _forwards: Vec<(u16, String, u16)>,
+    ) -> Result<CommandTemplate> {
+        Err(anyhow!(
+            "Port forwarding is not supported for exec connections"
+        ))
+    }
+
+    fn connection_options(&self) -> RemoteConnectionOptions {
+        RemoteConnectionOptions::Exec(self.connection_options.clone())
+    }
+
+    fn path_style(&self) -> PathStyle {
+        PathStyle::Posix
+    }
+
+    fn shell(&self) -> String {
+        self.shell.clone()
+    }
+
+    fn default_system_shell(&self) -> String {
+        String::from("/bin/sh")
+    }
+
+    fn has_wsl_interop(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(command: &str) -> ExecConnectionOptions {
+        ExecConnectionOptions {
+            name: "test".to_owned(),
+            command: command.to_owned(),
+            interactive_command: Some(format!("{command} --tty")),
+            variables: BTreeMap::from_iter([("pod".to_owned(), "web-0".to_owned())]),
+            upload_binary_over_exec: false,
+        }
+    }
+
+    fn program(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_expand_command_template() {
+        let (program_name, args) = options("kubectl exec -i {pod} -- {cmd}")
+            .expand(&program(&["sh", "-c", "echo $HOME"]), Interactive::No)
+            .unwrap();
+        assert_eq!(program_name, "kubectl");
+        assert_eq!(
+            args,
+            ["exec", "-i", "web-0", "--", "sh", "-c", "echo $HOME"]
+        );
+
+        let (program_name, args) =
+            options("nix develop ./env --command bash -c 'cd /src && {cmd}'")
+                .expand(&program(&["sh", "-c", "echo $HOME"]), Interactive::No)
+                .unwrap();
+        assert_eq!(program_name, "nix");
+        assert_eq!(
+            args,
+            [
+                "develop",
+                "./env",
+                "--command",
+                "bash",
+                "-c",
+                "cd /src && sh -c 'echo $HOME'"
+            ]
+        );
+
+        let (program_name, args) = options("podman exec -i ${CONTAINER:-web}")
+            .expand(&program(&["uname", "-sm"]), Interactive::Yes)
+            .unwrap();
+        assert_eq!(program_name, "podman");
+        assert_eq!(
+            args,
+            ["exec", "-i", "${CONTAINER:-web}", "--tty", "uname", "-sm"]
+        );
+
+        assert!(
+            options("kubectl exec -i {container} -- {cmd}")
+                .expand(&program(&["true"]), Interactive::No)
+                .is_err()
+        );
+        assert!(options("").expand(&program(&[]), Interactive::No).is_err());
+    }
+}
@@ -24,6 +24,7 @@ use project::{
     lsp_store::log_store::{self, GlobalLogStore, LanguageServerKind, LogKind},
     project_settings::SettingsObserver,
     search::SearchQuery,
+    search_index::SearchIndexStore,
     task_store::TaskStore,
     trusted_worktrees::{PathTrust, RemoteHostLocation, TrustedWorktrees},
     worktree_store::{WorktreeIdCounter, WorktreeStore},
@@ -52,6 +53,7 @@ pub struct HeadlessProject {
     pub fs: Arc<dyn Fs>,
     pub session: AnyProtoClient,
     pub worktree_store: Entity<WorktreeStore>,
+    pub search_index_store: Entity<SearchIndexStore>,
     pub buffer_store: Entity<BufferStore>,
     pub lsp_store: Entity<LspStore>,
     pub task_store: Entity<TaskStore>,
@@ -109,6 +111,14 @@ impl HeadlessProject {
             store.shared(REMOTE_SERVER_PROJECT_ID, session.clone(), cx);
             store
         });
+        let search_index_store = cx.new(|cx| {
+            SearchIndexStore::new(
+                &worktree_store,
+                fs.clone(),
+                paths::search_index_dir().clone(),
+                cx,
+            )
+        });
 
         if init_worktree_trust {
             project::trusted_worktrees::track_worktree_trust(
@@ -344,6 +354,7 @@ impl HeadlessProject {
             settings_observer,
             fs,
             worktree_store,
+            search_index_store,
             buffer_store,
             lsp_store,
             task_store,
@@ -1052,6 +1063,7 @@ impl HeadlessProject {
                     this.fs.clone(),
                     this.buffer_store.clone(),
                     this.worktree_store.clone(),
+                    Some(&this.search_index_store),
                     message.limit as _,
                     cx,
                 )
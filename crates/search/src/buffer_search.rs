@@ -1138,6 +1138,10 @@ impl BufferSearchBar {
         self.query_editor.read(cx).text(cx)
     }
 
+    pub fn search_history(&self) -> &SearchHistory {
+        &self.search_history
+    }
+
     pub fn replacement(&self, cx: &mut App) -> String {
         self.replacement_editor.read(cx).text(cx)
     }
@@ -1,15 +1,17 @@
 use crate::{
     BufferSearchBar, FocusSearch, HighlightKey, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll,
     ReplaceNext, SearchOption, SearchOptions, SearchSource, SelectNextMatch, SelectPreviousMatch,
-    ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleWholeWord,
+    ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex, ToggleReplace, ToggleStructural,
+    ToggleWholeWord,
     buffer_search::Deploy,
+    saved_searches::SavedSearch,
     search_bar::{
         ActionButtonState, HistoryNavigationDirection, alignment_element, input_base_styles,
         render_action_button, render_text_input, should_navigate_history,
     },
 };
 use anyhow::Context as _;
-use collections::HashMap;
+use collections::{HashMap, HashSet};
 use editor::{
     Anchor, Editor, EditorEvent, EditorSettings, MAX_TAB_TITLE_LEN, MultiBuffer, PathKey,
     SelectionEffects,
@@ -25,9 +27,9 @@ use gpui::{
     SharedString, Styled, Subscription, Task, UpdateGlobal, WeakEntity, Window, actions, div,
 };
 use itertools::Itertools;
-use language::{Buffer, Language};
+use language::{Buffer, BufferId, Language, OffsetRangeExt as _};
 use menu::Confirm;
-use multi_buffer;
+use multi_buffer::{self, MultiBufferSnapshot};
 use project::{
     Project, ProjectPath, SearchResults,
     search::{SearchInputKind, SearchQuery},
@@ -65,7 +67,20 @@ actions!(
         /// Toggles the search filters panel.
         ToggleFilters,
         /// Toggles collapse/expand state of all search result excerpts.
-        ToggleAllSearchResults
+        ToggleAllSearchResults,
+        /// Re-runs the search query in the files of the current results only.
+        SearchInResults,
+        /// Removes the match under the cursor from the search results.
+        ExcludeMatch,
+        /// Removes the file under the cursor from the search results.
+        ExcludeFile,
+        /// Pins the match under the cursor, keeping it in the results when the
+        /// search is re-run, or unpins it if it is pinned already.
+        PinMatch,
+        /// Pins all matches in the file under the cursor, keeping them in the
+        /// results when the search is re-run, or unpins them if they are all
+        /// pinned already.
+        PinFile,
     ]
 );
 
@@ -130,6 +145,12 @@ pub fn init(cx: &mut App) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, window, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, window, cx);
         });
+        register_workspace_action(
+            workspace,
+            move |search_bar, _: &ToggleStructural, window, cx| {
+                search_bar.toggle_search_option(SearchOptions::STRUCTURAL, window, cx);
+            },
+        );
         register_workspace_action(
             workspace,
             move |search_bar, action: &ToggleReplace, window, cx| {
@@ -168,6 +189,12 @@ pub fn init(cx: &mut App) {
             },
         );
 
+        register_search_view_action(workspace, ProjectSearchView::search_in_results);
+        register_search_view_action(workspace, ProjectSearchView::exclude_match);
+        register_search_view_action(workspace, ProjectSearchView::exclude_file);
+        register_search_view_action(workspace, ProjectSearchView::pin_match);
+        register_search_view_action(workspace, ProjectSearchView::pin_file);
+
         register_workspace_action_for_present_search(
             workspace,
             |workspace, _: &menu::Cancel, window, cx| {
@@ -227,11 +254,23 @@ fn contains_uppercase(str: &str) -> bool {
     str.chars().any(|c| c.is_uppercase())
 }
 
+fn buffer_id_for_range(range: &Range<Anchor>) -> Option<BufferId> {
+    range
+        .start
+        .raw_text_anchor()
+        .map(|text_anchor| text_anchor.buffer_id)
+}
+
+fn same_range(a: &Range<Anchor>, b: &Range<Anchor>, snapshot: &MultiBufferSnapshot) -> bool {
+    a.start.cmp(&b.start, snapshot).is_eq() && a.end.cmp(&b.end, snapshot).is_eq()
+}
+
 pub struct ProjectSearch {
     project: Entity<Project>,
     excerpts: Entity<MultiBuffer>,
     pending_search: Option<Task<Option<()>>>,
     match_ranges: Vec<Range<Anchor>>,
+    pinned_ranges: Vec<Range<Anchor>>,
     active_query: Option<SearchQuery>,
     last_search_query_text: Option<String>,
     search_id: usize,
@@ -294,6 +333,7 @@ impl ProjectSearch {
             excerpts,
             pending_search: Default::default(),
             match_ranges: Default::default(),
+            pinned_ranges: Default::default(),
             active_query: None,
             last_search_query_text: None,
             search_id: 0,
@@ -318,6 +358,7 @@ impl ProjectSearch {
                 excerpts,
                 pending_search: Default::default(),
                 match_ranges: self.match_ranges.clone(),
+                pinned_ranges: self.pinned_ranges.clone(),
                 active_query: self.active_query.clone(),
                 last_search_query_text: self.last_search_query_text.clone(),
                 search_id: self.search_id,
@@ -368,10 +409,119 @@ impl ProjectSearch {
 
         self.match_ranges
             .retain(|range| snapshot.anchor_to_buffer_anchor(range.start).is_some());
+        self.pinned_ranges
+            .retain(|range| snapshot.anchor_to_buffer_anchor(range.start).is_some());
 
         cx.notify();
     }
 
+    /// Removes matches from the results, along with the excerpts of files that
+    /// have no matches left.
+    fn exclude(&mut self, ranges: &[Range<Anchor>], cx: &mut Context<Self>) {
+        let snapshot = self.excerpts.read(cx).snapshot(cx);
+        let is_excluded = |range: &Range<Anchor>| {
+            ranges
+                .iter()
+                .any(|excluded| same_range(range, excluded, &snapshot))
+        };
+        self.match_ranges.retain(|range| !is_excluded(range));
+        self.pinned_ranges.retain(|range| !is_excluded(range));
+
+        let buffers_with_matches = self
+            .match_ranges
+            .iter()
+            .filter_map(buffer_id_for_range)
+            .collect::<HashSet<_>>();
+        let emptied_buffers = ranges
+            .iter()
+            .filter_map(buffer_id_for_range)
+            .filter(|buffer_id| !buffers_with_matches.contains(buffer_id))
+            .collect::<HashSet<_>>();
+        self.excerpts.update(cx, |excerpts, cx| {
+            for buffer_id in emptied_buffers {
+                excerpts.remove_excerpts_for_buffer(buffer_id, cx);
+            }
+        });
+        if self.match_ranges.is_empty() {
+            self.no_results = Some(true);
+        }
+        cx.notify();
+    }
+
+    /// Pins the given matches so they're kept when the search is re-run, or
+    /// unpins them if they're all pinned already.
+    fn toggle_pinned(&mut self, ranges: &[Range<Anchor>], cx: &mut Context<Self>) {
+        let snapshot = self.excerpts.read(cx).snapshot(cx);
+        let is_pinned = |range: &Range<Anchor>, pinned_ranges: &[Range<Anchor>]| {
+            pinned_ranges
+                .iter()
+                .any(|pinned| same_range(range, pinned, &snapshot))
+        };
+        if ranges
+            .iter()
+            .all(|range| is_pinned(range, &self.pinned_ranges))
+        {
+            self.pinned_ranges
+                .retain(|pinned| !is_pinned(pinned, ranges));
+        } else {
+            for range in ranges {
+                if !is_pinned(range, &self.pinned_ranges) {
+                    self.pinned_ranges.push(range.clone());
+                }
+            }
+        }
+        cx.notify();
+    }
+
+    fn matches_in_buffer(&self, buffer_id: BufferId) -> Vec<Range<Anchor>> {
+        self.match_ranges
+            .iter()
+            .filter(|range| buffer_id_for_range(range) == Some(buffer_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Clears the results ahead of a new search, except for the pinned
+    /// matches and the excerpts of the files they're in.
+    fn retain_pinned_results(&mut self, cx: &mut Context<Self>) {
+        self.match_ranges.clone_from(&self.pinned_ranges);
+        let pinned_buffers = self
+            .pinned_ranges
+            .iter()
+            .filter_map(buffer_id_for_range)
+            .collect::<HashSet<_>>();
+        self.excerpts.update(cx, |excerpts, cx| {
+            if pinned_buffers.is_empty() {
+                excerpts.clear(cx);
+                return;
+            }
+            let unpinned_buffers = excerpts
+                .all_buffers_iter()
+                .map(|buffer| buffer.read(cx).remote_id())
+                .filter(|buffer_id| !pinned_buffers.contains(buffer_id))
+                .collect::<Vec<_>>();
+            for buffer_id in unpinned_buffers {
+                excerpts.remove_excerpts_for_buffer(buffer_id, cx);
+            }
+        });
+    }
+
+    fn pinned_text_ranges(&self, cx: &App) -> HashMap<BufferId, Vec<Range<language::Anchor>>> {
+        let snapshot = self.excerpts.read(cx).snapshot(cx);
+        let mut pinned_text_ranges = HashMap::<BufferId, Vec<_>>::default();
+        for range in &self.pinned_ranges {
+            if let Some((start, _)) = snapshot.anchor_to_buffer_anchor(range.start)
+                && let Some((end, _)) = snapshot.anchor_to_buffer_anchor(range.end)
+            {
+                pinned_text_ranges
+                    .entry(start.buffer_id)
+                    .or_default()
+                    .push(start..end);
+            }
+        }
+        pinned_text_ranges
+    }
+
     fn cursor(&self, kind: SearchInputKind) -> &SearchHistoryCursor {
         match kind {
             SearchInputKind::Query => &self.search_history_cursor,
@@ -409,17 +559,15 @@ impl ProjectSearch {
         self.last_search_query_text = Some(query.as_str().to_string());
         self.search_id += 1;
         self.active_query = Some(query);
-        self.match_ranges.clear();
+        self.match_ranges.clone_from(&self.pinned_ranges);
+        let pinned_text_ranges = self.pinned_text_ranges(cx);
         self.pending_search = Some(cx.spawn(async move |project_search, cx| {
             let SearchResults { rx, _task_handle } = search;
 
             let mut matches = pin!(rx.ready_chunks(1024));
             project_search
                 .update(cx, |project_search, cx| {
-                    project_search.match_ranges.clear();
-                    project_search
-                        .excerpts
-                        .update(cx, |excerpts, cx| excerpts.clear(cx));
+                    project_search.retain_pinned_results(cx);
                     project_search.no_results = Some(true);
                     project_search.limit_reached = false;
                 })
@@ -451,7 +599,20 @@ impl ProjectSearch {
                         project_search.excerpts.update(cx, |excerpts, cx| {
                             buffers_with_ranges
                                 .into_iter()
-                                .map(|(buffer, ranges)| {
+                                .map(|(buffer, mut ranges)| {
+                                    // Pinned matches stay in the results, so
+                                    // merge them with the new matches in their
+                                    // file rather than replacing them.
+                                    if let Some(pinned) =
+                                        pinned_text_ranges.get(&buffer.read(cx).remote_id())
+                                    {
+                                        let snapshot = buffer.read(cx).snapshot();
+                                        ranges.extend(pinned.iter().cloned());
+                                        ranges.sort_by(|a, b| a.start.cmp(&b.start, &snapshot));
+                                        ranges.dedup_by(|a, b| {
+                                            a.to_offset(&snapshot) == b.to_offset(&snapshot)
+                                        });
+                                    }
                                     excerpts.set_anchored_excerpts_for_path(
                                         PathKey::for_buffer(&buffer, cx),
                                         buffer,
@@ -470,6 +631,14 @@ impl ProjectSearch {
                     smol::future::yield_now().await;
                     project_search
                         .update(cx, |project_search, cx| {
+                            if let Some(buffer_id) =
+                                new_ranges.first().and_then(buffer_id_for_range)
+                                && pinned_text_ranges.contains_key(&buffer_id)
+                            {
+                                project_search
+                                    .match_ranges
+                                    .retain(|range| buffer_id_for_range(range) != Some(buffer_id));
+                            }
                             project_search.match_ranges.extend(new_ranges);
                             cx.notify();
                         })
@@ -479,6 +648,12 @@ impl ProjectSearch {
 
             project_search
                 .update(cx, |project_search, cx| {
+                    if !pinned_text_ranges.is_empty() {
+                        let snapshot = project_search.excerpts.read(cx).snapshot(cx);
+                        project_search
+                            .match_ranges
+                            .sort_by(|a, b| a.start.cmp(&b.start, &snapshot));
+                    }
                     if !project_search.match_ranges.is_empty() {
                         project_search.no_results = Some(false);
                     }
@@ -771,6 +946,15 @@ impl ProjectSearchView {
 
     fn toggle_search_option(&mut self, option: SearchOptions, cx: &mut Context<Self>) {
         self.search_options.toggle(option);
+        // Regex and structural patterns are different query languages, so
+        // only one of them can be enabled at a time.
+        if self.search_options.contains(option) {
+            if option == SearchOptions::REGEX {
+                self.search_options.remove(SearchOptions::STRUCTURAL);
+            } else if option == SearchOptions::STRUCTURAL {
+                self.search_options.remove(SearchOptions::REGEX);
+            }
+        }
         ActiveSettings::update_global(cx, |settings, cx| {
             settings.0.insert(
                 self.entity.read(cx).project.downgrade(),
@@ -876,6 +1060,68 @@ impl ProjectSearchView {
         self.update_results_visibility(window, cx);
     }
 
+    fn search_in_results(&mut self, _: &SearchInResults, _: &mut Window, cx: &mut Context<Self>) {
+        let buffers = self
+            .entity
+            .read(cx)
+            .excerpts
+            .read(cx)
+            .all_buffers_iter()
+            .collect::<Vec<_>>();
+        if buffers.is_empty() {
+            return;
+        }
+        if let Some(query) = self.build_search_query(cx, Some(buffers)) {
+            self.entity.update(cx, |model, cx| model.search(query, cx));
+        }
+    }
+
+    fn match_under_cursor(&self, cx: &App) -> Option<Range<Anchor>> {
+        let index = self.active_match_index?;
+        self.entity.read(cx).match_ranges.get(index).cloned()
+    }
+
+    fn buffer_under_cursor(&self, cx: &App) -> Option<BufferId> {
+        let results_editor = self.results_editor.read(cx);
+        let head = results_editor.selections.newest_anchor().head();
+        let snapshot = results_editor.buffer().read(cx).snapshot(cx);
+        snapshot
+            .anchor_to_buffer_anchor(head)
+            .map(|(text_anchor, _)| text_anchor.buffer_id)
+    }
+
+    fn exclude_match(&mut self, _: &ExcludeMatch, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(range) = self.match_under_cursor(cx) {
+            self.entity
+                .update(cx, |model, cx| model.exclude(&[range], cx));
+        }
+    }
+
+    fn exclude_file(&mut self, _: &ExcludeFile, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(buffer_id) = self.buffer_under_cursor(cx) {
+            self.entity.update(cx, |model, cx| {
+                let ranges = model.matches_in_buffer(buffer_id);
+                model.exclude(&ranges, cx);
+            });
+        }
+    }
+
+    fn pin_match(&mut self, _: &PinMatch, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(range) = self.match_under_cursor(cx) {
+            self.entity
+                .update(cx, |model, cx| model.toggle_pinned(&[range], cx));
+        }
+    }
+
+    fn pin_file(&mut self, _: &PinFile, _: &mut Window, cx: &mut Context<Self>) {
+        if let Some(buffer_id) = self.buffer_under_cursor(cx) {
+            self.entity.update(cx, |model, cx| {
+                let ranges = model.matches_in_buffer(buffer_id);
+                model.toggle_pinned(&ranges, cx);
+            });
+        }
+    }
+
     fn update_results_visibility(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         let has_any_folded = self.results_editor.read(cx).has_any_buffer_folded(cx);
         self.results_editor.update(cx, |editor, cx| {
@@ -1177,28 +1423,7 @@ impl ProjectSearchView {
             workspace.activate_item(&existing, true, true, window, cx);
             existing
         } else {
-            let settings = cx
-                .global::<ActiveSettings>()
-                .0
-                .get(&workspace.project().downgrade());
-
-            let settings = settings.cloned();
-
-            let weak_workspace = cx.entity().downgrade();
-
-            let project_search = cx.new(|cx| ProjectSearch::new(workspace.project().clone(), cx));
-            let project_search_view = cx.new(|cx| {
-                ProjectSearchView::new(weak_workspace, project_search, window, cx, settings)
-            });
-
-            workspace.add_item_to_active_pane(
-                Box::new(project_search_view.clone()),
-                None,
-                true,
-                window,
-                cx,
-            );
-            project_search_view
+            Self::add_to_workspace(workspace, window, cx)
         };
 
         search.update(cx, |search, cx| {
@@ -1222,6 +1447,96 @@ impl ProjectSearchView {
         });
     }
 
+    fn add_to_workspace(
+        workspace: &mut Workspace,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) -> Entity<Self> {
+        let settings = cx
+            .global::<ActiveSettings>()
+            .0
+            .get(&workspace.project().downgrade());
+
+        let settings = settings.cloned();
+
+        let weak_workspace = cx.entity().downgrade();
+
+        let project_search = cx.new(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+        let project_search_view = cx
+            .new(|cx| ProjectSearchView::new(weak_workspace, project_search, window, cx, settings));
+
+        workspace.add_item_to_active_pane(
+            Box::new(project_search_view.clone()),
+            None,
+            true,
+            window,
+            cx,
+        );
+        project_search_view
+    }
+
+    /// Captures the query, options and filters of this search under the given name.
+    pub(crate) fn saved_search(&self, name: String, cx: &App) -> SavedSearch {
+        let (included_files, excluded_files) = if self.filters_enabled {
+            (
+                self.included_files_editor.read(cx).text(cx),
+                self.excluded_files_editor.read(cx).text(cx),
+            )
+        } else {
+            Default::default()
+        };
+        SavedSearch {
+            name,
+            query: self.search_query_text(cx),
+            options: self.search_options,
+            included_files,
+            excluded_files,
+        }
+    }
+
+    /// Runs a saved search in the project search of the active pane, opening
+    /// a new one if there isn't one.
+    pub(crate) fn run_saved_search(
+        workspace: &mut Workspace,
+        saved_search: &SavedSearch,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items()
+            .find_map(|item| item.downcast::<ProjectSearchView>());
+        let search = if let Some(existing) = existing {
+            workspace.activate_item(&existing, true, true, window, cx);
+            existing
+        } else {
+            Self::add_to_workspace(workspace, window, cx)
+        };
+
+        search.update(cx, |search, cx| {
+            search.set_search_editor(SearchInputKind::Query, &saved_search.query, window, cx);
+            search.set_search_editor(
+                SearchInputKind::Include,
+                &saved_search.included_files,
+                window,
+                cx,
+            );
+            search.set_search_editor(
+                SearchInputKind::Exclude,
+                &saved_search.excluded_files,
+                window,
+                cx,
+            );
+            search.filters_enabled =
+                !saved_search.included_files.is_empty() || !saved_search.excluded_files.is_empty();
+            search.search_options = saved_search.options;
+            search.adjust_query_regex_language(cx);
+            search.search(cx);
+            search.focus_results_editor(window, cx);
+        });
+    }
+
     fn prompt_to_save_if_dirty_then_search(
         &mut self,
         window: &mut Window,
@@ -1377,7 +1692,35 @@ impl ProjectSearchView {
             .count()
             > 1;
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
+        let query = if self.search_options.contains(SearchOptions::STRUCTURAL) {
+            match SearchQuery::structural(
+                text,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+                match_full_paths,
+                open_buffers,
+            ) {
+                Ok(query) => {
+                    let should_unmark_error = self.panels_with_errors.remove(&InputPanel::Query);
+                    if should_unmark_error.is_some() {
+                        cx.notify();
+                    }
+
+                    Some(query)
+                }
+                Err(e) => {
+                    let should_mark_error = self
+                        .panels_with_errors
+                        .insert(InputPanel::Query, e.to_string());
+                    if should_mark_error.is_none() {
+                        cx.notify();
+                    }
+
+                    None
+                }
+            }
+        } else if self.search_options.contains(SearchOptions::REGEX) {
             match SearchQuery::regex(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
@@ -1679,6 +2022,18 @@ impl ProjectSearchView {
                         window.dispatch_action(ToggleRegex.boxed_clone(), cx)
                     }),
             )
+            .child(
+                Button::new("structural", "Match syntax structure")
+                    .start_icon(Icon::new(IconName::ListTree).size(IconSize::Small))
+                    .key_binding(KeyBinding::for_action_in(
+                        &ToggleStructural,
+                        &focus_handle,
+                        cx,
+                    ))
+                    .on_click(|_event, window, cx| {
+                        window.dispatch_action(ToggleStructural.boxed_clone(), cx)
+                    }),
+            )
             .child(
                 Button::new("match-case", "Match case")
                     .start_icon(Icon::new(IconName::CaseSensitive).size(IconSize::Small))
@@ -2188,6 +2543,11 @@ impl Render for ProjectSearchBar {
                         search.search_options,
                         SearchSource::Project(cx),
                         focus_handle.clone(),
+                    ))
+                    .child(SearchOption::Structural.as_button(
+                        search.search_options,
+                        SearchSource::Project(cx),
+                        focus_handle.clone(),
                     )),
             );
 
@@ -2575,6 +2935,24 @@ fn register_workspace_action_for_present_search<A: Action>(
     });
 }
 
+fn register_search_view_action<A: Action>(
+    workspace: &mut Workspace,
+    callback: fn(&mut ProjectSearchView, &A, &mut Window, &mut Context<ProjectSearchView>),
+) {
+    workspace.register_action(move |workspace, action: &A, window, cx| {
+        let Some(search_view) = workspace
+            .active_item(cx)
+            .and_then(|item| item.downcast::<ProjectSearchView>())
+        else {
+            cx.propagate();
+            return;
+        };
+        search_view.update(cx, |search_view, cx| {
+            callback(search_view, action, window, cx)
+        });
+    });
+}
+
 #[cfg(any(test, feature = "test-support"))]
 pub fn perform_project_search(
     search_view: &Entity<ProjectSearchView>,
@@ -5136,6 +5514,113 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_refine_exclude_and_pin_results(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            path!("/dir"),
+            json!({
+                "a.txt": "one two",
+                "b.txt": "one three",
+                "c.txt": "one four",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), [path!("/dir").as_ref()], cx).await;
+        let window =
+            cx.add_window(|window, cx| MultiWorkspace::test_new(project.clone(), window, cx));
+        let workspace = window
+            .read_with(cx, |mw, _| mw.workspace().clone())
+            .unwrap();
+        let search = cx.new(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = cx.add_window(|window, cx| {
+            ProjectSearchView::new(workspace.downgrade(), search.clone(), window, cx, None)
+        });
+        let select_result = |ix: usize, cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, window, cx| {
+                    let range = search_view.entity.read(cx).match_ranges[ix].clone();
+                    search_view.results_editor.update(cx, |editor, cx| {
+                        let range = editor.range_for_match(&range);
+                        editor.change_selections(Default::default(), window, cx, |s| {
+                            s.select_ranges([range])
+                        });
+                    });
+                    search_view.update_match_index(cx);
+                })
+                .unwrap();
+        };
+        let results_text = |cx: &mut TestAppContext| {
+            search_view
+                .update(cx, |search_view, _, cx| {
+                    search_view
+                        .results_editor
+                        .update(cx, |editor, cx| editor.display_text(cx))
+                })
+                .unwrap()
+        };
+        let match_count = |cx: &mut TestAppContext| {
+            search_view
+                .read_with(cx, |search_view, cx| {
+                    search_view.entity.read(cx).match_ranges.len()
+                })
+                .unwrap()
+        };
+
+        perform_search(search_view, "one", cx);
+        assert_eq!(match_count(cx), 3);
+
+        select_result(1, cx);
+        search_view
+            .update(cx, |search_view, window, cx| {
+                search_view.exclude_file(&ExcludeFile, window, cx)
+            })
+            .unwrap();
+        cx.run_until_parked();
+        assert_eq!(match_count(cx), 2);
+        assert!(!results_text(cx).contains("three"));
+
+        // Pinned matches are kept when the search is run again.
+        select_result(1, cx);
+        search_view
+            .update(cx, |search_view, window, cx| {
+                search_view.pin_file(&PinFile, window, cx)
+            })
+            .unwrap();
+        perform_search(search_view, "two", cx);
+        assert_eq!(match_count(cx), 2);
+        let text = results_text(cx);
+        assert!(text.contains("one two") && text.contains("one four"));
+
+        // Refining searches only the files in the results, and doesn't
+        // duplicate pinned matches.
+        search_view
+            .update(cx, |search_view, window, cx| {
+                search_view.query_editor.update(cx, |query_editor, cx| {
+                    query_editor.set_text("one", window, cx)
+                });
+                search_view.search_in_results(&SearchInResults, window, cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        assert_eq!(match_count(cx), 2);
+        assert!(!results_text(cx).contains("three"));
+
+        // Unpinned, the pinned match goes away once it no longer matches.
+        select_result(1, cx);
+        search_view
+            .update(cx, |search_view, window, cx| {
+                search_view.pin_file(&PinFile, window, cx)
+            })
+            .unwrap();
+        perform_search(search_view, "two", cx);
+        assert_eq!(match_count(cx), 1);
+        assert!(!results_text(cx).contains("four"));
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings = SettingsStore::test(cx);
@@ -0,0 +1,465 @@
+//! Named project searches, stored per workspace so they can be re-run later.
+
+use std::sync::Arc;
+
+use fuzzy::{StringMatch, StringMatchCandidate, match_strings};
+use gpui::{
+    App, Context, DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, Task, WeakEntity,
+    Window, actions,
+};
+use picker::{Picker, PickerDelegate};
+use ui::{IconButtonShape, ListItem, ListItemSpacing, Tooltip, prelude::*};
+use util::ResultExt as _;
+use workspace::{ModalView, Workspace, WorkspaceId};
+
+use crate::{ProjectSearchView, SearchOptions};
+
+use persistence::SavedSearchesDb;
+
+actions!(
+    project_search,
+    [
+        /// Saves the query, options and filters of the active project search
+        /// under a name.
+        SaveSearch,
+        /// Opens a picker to run one of the saved project searches.
+        RunSavedSearch,
+    ]
+);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SavedSearch {
+    pub name: String,
+    pub query: String,
+    pub options: SearchOptions,
+    pub included_files: String,
+    pub excluded_files: String,
+}
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &SaveSearch, window, cx| {
+            let Some(search_view) = workspace
+                .active_item(cx)
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+            else {
+                return;
+            };
+            let draft = search_view.read(cx).saved_search(String::new(), cx);
+            if draft.query.is_empty() {
+                return;
+            }
+            SavedSearchesModal::toggle(workspace, Mode::Save(draft), window, cx);
+        });
+        workspace.register_action(|workspace, _: &RunSavedSearch, window, cx| {
+            SavedSearchesModal::toggle(workspace, Mode::Run, window, cx);
+        });
+    })
+    .detach();
+}
+
+enum Mode {
+    /// Saving the given search, named after the picker query.
+    Save(SavedSearch),
+    Run,
+}
+
+pub struct SavedSearchesModal {
+    picker: Entity<Picker<SavedSearchesDelegate>>,
+}
+
+impl SavedSearchesModal {
+    fn toggle(
+        workspace: &mut Workspace,
+        mode: Mode,
+        window: &mut Window,
+        cx: &mut Context<Workspace>,
+    ) {
+        let Some(workspace_id) = workspace.database_id() else {
+            return;
+        };
+        let saved_searches = SavedSearchesDb::global(cx)
+            .saved_searches(workspace_id)
+            .log_err()
+            .unwrap_or_default();
+        let weak_workspace = cx.entity().downgrade();
+        workspace.toggle_modal(window, cx, move |window, cx| {
+            let delegate = SavedSearchesDelegate::new(
+                cx.entity().downgrade(),
+                weak_workspace,
+                workspace_id,
+                mode,
+                saved_searches,
+            );
+            let picker = cx.new(|cx| Picker::uniform_list(delegate, window, cx));
+            Self { picker }
+        });
+    }
+}
+
+impl Render for SavedSearchesModal {
+    fn render(&mut self, _window: &mut Window, _cx: &mut Context<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl Focusable for SavedSearchesModal {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SavedSearchesModal {}
+impl ModalView for SavedSearchesModal {}
+
+struct SavedSearchesDelegate {
+    modal: WeakEntity<SavedSearchesModal>,
+    workspace: WeakEntity<Workspace>,
+    workspace_id: WorkspaceId,
+    mode: Mode,
+    saved_searches: Vec<SavedSearch>,
+    matches: Vec<StringMatch>,
+    /// In save mode, a typed name that no saved search has yet. It's listed
+    /// ahead of the matches, which are overwritten when picked instead.
+    new_name: Option<String>,
+    selected_index: usize,
+}
+
+impl SavedSearchesDelegate {
+    fn new(
+        modal: WeakEntity<SavedSearchesModal>,
+        workspace: WeakEntity<Workspace>,
+        workspace_id: WorkspaceId,
+        mode: Mode,
+        saved_searches: Vec<SavedSearch>,
+    ) -> Self {
+        Self {
+            modal,
+            workspace,
+            workspace_id,
+            mode,
+            saved_searches,
+            matches: Vec::new(),
+            new_name: None,
+            selected_index: 0,
+        }
+    }
+
+    fn saved_search_for_index(&self, ix: usize) -> Option<&SavedSearch> {
+        let ix = ix.checked_sub(self.new_name.is_some() as usize)?;
+        let candidate_id = self.matches.get(ix)?.candidate_id;
+        self.saved_searches.get(candidate_id)
+    }
+
+    fn delete(&mut self, ix: usize, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        let Some(name) = self
+            .saved_search_for_index(ix)
+            .map(|saved_search| saved_search.name.clone())
+        else {
+            return;
+        };
+        self.saved_searches
+            .retain(|saved_search| saved_search.name != name);
+        let db = SavedSearchesDb::global(cx);
+        let workspace_id = self.workspace_id;
+        cx.background_spawn(async move { db.delete_saved_search(workspace_id, name).await })
+            .detach_and_log_err(cx);
+        cx.defer_in(window, |picker, window, cx| picker.refresh(window, cx));
+    }
+}
+
+impl PickerDelegate for SavedSearchesDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        match self.mode {
+            Mode::Save(_) => "Name this search…".into(),
+            Mode::Run => "Run a saved search…".into(),
+        }
+    }
+
+    fn no_matches_text(&self, _window: &mut Window, _cx: &mut App) -> Option<SharedString> {
+        Some("No saved searches".into())
+    }
+
+    fn match_count(&self) -> usize {
+        self.new_name.is_some() as usize + self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(
+        &mut self,
+        query: String,
+        window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .saved_searches
+            .iter()
+            .enumerate()
+            .map(|(id, saved_search)| StringMatchCandidate::new(id, &saved_search.name))
+            .collect::<Vec<_>>();
+        let query = query.trim().to_string();
+        let new_name = match self.mode {
+            Mode::Save(_)
+                if !query.is_empty()
+                    && !self
+                        .saved_searches
+                        .iter()
+                        .any(|saved_search| saved_search.name == query) =>
+            {
+                Some(query.clone())
+            }
+            _ => None,
+        };
+        cx.spawn_in(window, async move |this, cx| {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, candidate)| StringMatch {
+                        candidate_id: index,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    true,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(cx, |this, cx| {
+                this.delegate.matches = matches;
+                this.delegate.new_name = new_name;
+                this.delegate.selected_index = 0;
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        match &self.mode {
+            Mode::Save(draft) => {
+                let name = if self.selected_index == 0
+                    && let Some(new_name) = &self.new_name
+                {
+                    new_name.clone()
+                } else if let Some(saved_search) = self.saved_search_for_index(self.selected_index)
+                {
+                    saved_search.name.clone()
+                } else {
+                    return;
+                };
+                let saved_search = SavedSearch {
+                    name,
+                    ..draft.clone()
+                };
+                let db = SavedSearchesDb::global(cx);
+                let workspace_id = self.workspace_id;
+                cx.background_spawn(async move {
+                    db.save_search(
+                        workspace_id,
+                        saved_search.name,
+                        saved_search.query,
+                        saved_search.options.bits() as u32,
+                        saved_search.included_files,
+                        saved_search.excluded_files,
+                    )
+                    .await
+                })
+                .detach_and_log_err(cx);
+            }
+            Mode::Run => {
+                let Some(saved_search) = self.saved_search_for_index(self.selected_index).cloned()
+                else {
+                    return;
+                };
+                self.workspace
+                    .update(cx, |workspace, cx| {
+                        ProjectSearchView::run_saved_search(workspace, &saved_search, window, cx)
+                    })
+                    .log_err();
+            }
+        }
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.modal
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        if ix == 0
+            && let Some(new_name) = &self.new_name
+        {
+            return Some(
+                ListItem::new(ix)
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .toggle_state(selected)
+                    .start_slot(Icon::new(IconName::Plus).color(Color::Muted))
+                    .child(Label::new(format!("Save as \"{new_name}\""))),
+            );
+        }
+
+        let mat = self.matches.get(ix - self.new_name.is_some() as usize)?;
+        let saved_search = self.saved_searches.get(mat.candidate_id)?;
+        let overwrite = matches!(self.mode, Mode::Save(_));
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .toggle_state(selected)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(HighlightedLabel::new(
+                            saved_search.name.clone(),
+                            mat.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(saved_search.query.clone())
+                                .color(Color::Muted)
+                                .size(LabelSize::Small)
+                                .truncate(),
+                        ),
+                )
+                .when(overwrite, |item| {
+                    item.end_slot(
+                        Label::new("Overwrite")
+                            .color(Color::Muted)
+                            .size(LabelSize::Small),
+                    )
+                })
+                .when(!overwrite, |item| {
+                    item.end_slot(
+                        IconButton::new(("delete-saved-search", ix), IconName::Trash)
+                            .shape(IconButtonShape::Square)
+                            .icon_size(IconSize::Small)
+                            .tooltip(Tooltip::text("Delete Saved Search"))
+                            .on_click(cx.listener(move |picker, _, window, cx| {
+                                cx.stop_propagation();
+                                picker.delegate.delete(ix, window, cx);
+                            })),
+                    )
+                }),
+        )
+    }
+}
+
+impl SavedSearchesDb {
+    fn saved_searches(&self, workspace_id: WorkspaceId) -> anyhow::Result<Vec<SavedSearch>> {
+        Ok(self
+            .saved_search_rows(workspace_id)?
+            .into_iter()
+            .map(
+                |(name, query, options, included_files, excluded_files)| SavedSearch {
+                    name,
+                    query,
+                    options: SearchOptions::from_bits_truncate(options as u8),
+                    included_files,
+                    excluded_files,
+                },
+            )
+            .collect())
+    }
+}
+
+mod persistence {
+    use db::{
+        query,
+        sqlez::{domain::Domain, thread_safe_connection::ThreadSafeConnection},
+        sqlez_macros::sql,
+    };
+    use workspace::{WorkspaceDb, WorkspaceId};
+
+    pub struct SavedSearchesDb(ThreadSafeConnection);
+
+    impl Domain for SavedSearchesDb {
+        const NAME: &str = stringify!(SavedSearchesDb);
+
+        const MIGRATIONS: &[&str] = &[sql!(
+                CREATE TABLE saved_searches (
+                    workspace_id INTEGER,
+                    name TEXT NOT NULL,
+                    query TEXT NOT NULL,
+                    options INTEGER NOT NULL,
+                    included_files TEXT NOT NULL,
+                    excluded_files TEXT NOT NULL,
+
+                    PRIMARY KEY(workspace_id, name),
+                    FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                    ON DELETE CASCADE
+                ) STRICT;
+        )];
+    }
+
+    db::static_connection!(SavedSearchesDb, [WorkspaceDb]);
+
+    impl SavedSearchesDb {
+        query! {
+            pub async fn save_search(
+                workspace_id: WorkspaceId,
+                name: String,
+                query: String,
+                options: u32,
+                included_files: String,
+                excluded_files: String
+            ) -> Result<()> {
+                INSERT OR REPLACE INTO saved_searches(
+                    workspace_id, name, query, options, included_files, excluded_files
+                )
+                VALUES (?, ?, ?, ?, ?, ?)
+            }
+        }
+
+        query! {
+            pub async fn delete_saved_search(workspace_id: WorkspaceId, name: String) -> Result<()> {
+                DELETE FROM saved_searches
+                WHERE workspace_id = ? AND name = ?
+            }
+        }
+
+        query! {
+            pub fn saved_search_rows(
+                workspace_id: WorkspaceId
+            ) -> Result<Vec<(String, String, u32, String, String)>> {
+                SELECT name, query, options, included_files, excluded_files
+                FROM saved_searches
+                WHERE workspace_id = ?
+                ORDER BY name
+            }
+        }
+    }
+}
@@ -17,6 +17,7 @@ use crate::project_search::ProjectSearchBar;
 
 pub mod buffer_search;
 pub mod project_search;
+pub mod saved_searches;
 pub(crate) mod search_bar;
 pub mod search_status_button;
 
@@ -24,6 +25,7 @@ pub fn init(cx: &mut App) {
     menu::init();
     buffer_search::init(cx);
     project_search::init(cx);
+    saved_searches::init(cx);
 }
 
 actions!(
@@ -37,6 +39,8 @@ actions!(
         ToggleCaseSensitive,
         /// Toggles regular expression mode.
         ToggleRegex,
+        /// Toggles structural mode, matching code patterns against syntax trees.
+        ToggleStructural,
         /// Toggles the replace interface.
         ToggleReplace,
         /// Toggles searching within selection only.
@@ -71,6 +75,7 @@ bitflags! {
         const ONE_MATCH_PER_LINE = 1 << SearchOption::OneMatchPerLine as u8;
         /// If set, reverse direction when finding the active match
         const BACKWARDS = 1 << SearchOption::Backwards as u8;
+        const STRUCTURAL = 1 << SearchOption::Structural as u8;
     }
 }
 
@@ -83,6 +88,7 @@ pub enum SearchOption {
     Regex,
     OneMatchPerLine,
     Backwards,
+    Structural,
 }
 
 pub enum SearchSource<'a, 'b> {
@@ -103,6 +109,7 @@ impl SearchOption {
             SearchOption::Regex => "Use Regular Expressions",
             SearchOption::OneMatchPerLine => "One Match Per Line",
             SearchOption::Backwards => "Search Backwards",
+            SearchOption::Structural => "Match Syntax Structure",
         }
     }
 
@@ -112,6 +119,7 @@ impl SearchOption {
             SearchOption::CaseSensitive => ui::IconName::CaseSensitive,
             SearchOption::IncludeIgnored => ui::IconName::Sliders,
             SearchOption::Regex => ui::IconName::Regex,
+            SearchOption::Structural => ui::IconName::ListTree,
             _ => panic!("{self:?} is not a named SearchOption"),
         }
     }
@@ -122,6 +130,7 @@ impl SearchOption {
             SearchOption::CaseSensitive => &ToggleCaseSensitive,
             SearchOption::IncludeIgnored => &ToggleIncludeIgnored,
             SearchOption::Regex => &ToggleRegex,
+            SearchOption::Structural => &ToggleStructural,
             _ => panic!("{self:?} is not a toggle action"),
         }
     }
@@ -173,6 +182,7 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::STRUCTURAL, query.is_structural());
         options
     }
 
@@ -195,6 +195,7 @@ impl VsCodeSettings {
             journal: None,
             language_models: None,
             line_indicator_format: None,
+            local_history: None,
             log: None,
             message_editor: None,
             node: self.node_binary_settings(),
@@ -217,6 +218,7 @@ impl VsCodeSettings {
             title_bar: None,
             vim: None,
             vim_mode: None,
+            web_search: None,
             workspace: self.workspace_settings_content(),
             which_key: None,
             modeline_lines: None,
@@ -1054,6 +1056,8 @@ impl VsCodeSettings {
                         .collect::<Vec<_>>()
                 })
                 .filter(|r| !r.is_empty()),
+            search_index: None,
+            large_file_threshold: None,
         }
     }
 }
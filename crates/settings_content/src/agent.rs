@@ -209,6 +209,30 @@ pub struct AgentSettingsContent {
     ///
     /// Default: false
     pub show_turn_stats: Option<bool>,
+    /// Whether to automatically compact a thread when it approaches the
+    /// model's context window. Compaction summarizes older turns with the
+    /// thread summary model while keeping the original messages viewable.
+    ///
+    /// Default: true
+    pub auto_compact_threads: Option<bool>,
+    /// The fraction of the model's context window that has to be in use
+    /// before a thread is automatically compacted.
+    ///
+    /// Default: 0.85
+    #[serde(serialize_with = "crate::serialize_optional_f32_with_two_decimal_places")]
+    pub auto_compact_threshold: Option<f32>,
+    /// Commands to run around agent activity: when a message is sent, before
+    /// and after tool calls, after files are edited and when a turn ends.
+    /// Hooks run in the root of the project's first worktree, and what they
+    /// print is shown to the model.
+    ///
+    /// Default: {}
+    pub hooks: Option<AgentHooksContent>,
+    /// The prices of models, used to estimate what the agent spends, and
+    /// budgets that limit its spending.
+    ///
+    /// Default: {}
+    pub usage: Option<AgentUsageContent>,
     /// Per-tool permission rules for granular control over which tool actions
     /// require confirmation.
     ///
@@ -595,6 +619,226 @@ pub enum CustomAgentServerSettings {
     },
 }
 
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct WebSearchSettingsContent {
+    /// A self-hosted SearXNG instance to search the web with. When set, it's
+    /// used instead of Zed's hosted web search, with models from any provider.
+    ///
+    /// Default: null
+    pub searxng: Option<SearxngWebSearchSettingsContent>,
+    /// An HTTP endpoint that returns search results as JSON. When set, it's
+    /// used instead of Zed's hosted web search, with models from any provider.
+    /// If SearXNG is also configured, SearXNG is used.
+    ///
+    /// Default: null
+    pub json: Option<JsonWebSearchSettingsContent>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct SearxngWebSearchSettingsContent {
+    /// The URL of the SearXNG instance, such as `http://localhost:8080`.
+    /// The instance must have the `json` output format enabled.
+    #[serde(default)]
+    pub api_url: String,
+    /// The maximum number of results to return for a search.
+    ///
+    /// Default: 10
+    pub max_results: Option<usize>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct JsonWebSearchSettingsContent {
+    /// The URL to search with. It's requested with a GET request, with the
+    /// query in the `query_parameter` query parameter.
+    #[serde(default)]
+    pub api_url: String,
+    /// The name of the query parameter the search query is passed in.
+    ///
+    /// Default: "q"
+    pub query_parameter: Option<String>,
+    /// The header to send the API key in. When unset, the key is sent as a
+    /// bearer token in the `Authorization` header.
+    ///
+    /// Default: null
+    pub api_key_header: Option<String>,
+    /// The path to the array of results in the response, as keys separated
+    /// by dots. Leave it empty when the response itself is the array.
+    ///
+    /// Default: "results"
+    pub results_path: Option<String>,
+    /// The path to the title of a result, relative to the result.
+    ///
+    /// Default: "title"
+    pub title_field: Option<String>,
+    /// The path to the URL of a result, relative to the result.
+    ///
+    /// Default: "url"
+    pub url_field: Option<String>,
+    /// The path to the snippet of a result, relative to the result.
+    ///
+    /// Default: "snippet"
+    pub text_field: Option<String>,
+    /// The maximum number of results to return for a search.
+    ///
+    /// Default: 10
+    pub max_results: Option<usize>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct AgentUsageContent {
+    /// The prices of models, keyed by model id, or by `provider/model` to
+    /// price a model differently depending on its provider.
+    ///
+    /// Default: {}
+    pub pricing: Option<HashMap<String, ModelPricingContent>>,
+    /// Limits on how much the agent can spend. When a soft limit is reached
+    /// you're warned, and when a hard limit is reached the agent stops until
+    /// the limit is raised or the budget's period ends.
+    ///
+    /// Default: []
+    pub budgets: Option<Vec<UsageBudgetContent>>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct ModelPricingContent {
+    /// The price of a million input tokens, in dollars.
+    ///
+    /// Default: 0
+    pub input: Option<f64>,
+    /// The price of a million output tokens, in dollars.
+    ///
+    /// Default: 0
+    pub output: Option<f64>,
+    /// The price of a million input tokens written to the prompt cache, in dollars.
+    ///
+    /// Default: the input price
+    pub cache_write: Option<f64>,
+    /// The price of a million input tokens read from the prompt cache, in dollars.
+    ///
+    /// Default: the input price
+    pub cache_read: Option<f64>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct UsageBudgetContent {
+    /// Which usage counts towards the budget.
+    ///
+    /// Default: thread
+    pub scope: Option<UsageBudgetScope>,
+    /// Only count usage of this language model provider, such as `anthropic`.
+    ///
+    /// Default: all providers
+    pub provider: Option<String>,
+    /// The period after which the budget starts over.
+    ///
+    /// Default: all_time
+    pub period: Option<UsageBudgetPeriod>,
+    /// Whether the limits are in dollars, estimated from `pricing`, or in tokens.
+    ///
+    /// Default: dollars
+    pub unit: Option<UsageBudgetUnit>,
+    /// The spending at which you're warned.
+    pub soft_limit: Option<f64>,
+    /// The spending at which the agent stops.
+    pub hard_limit: Option<f64>,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, MergeFrom,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageBudgetScope {
+    /// Usage of each thread, including its subagents.
+    #[default]
+    Thread,
+    /// Usage of all threads in each project.
+    Project,
+    /// All usage.
+    All,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, MergeFrom,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageBudgetPeriod {
+    /// The budget starts over every day.
+    Day,
+    /// The budget starts over every month.
+    Month,
+    /// The budget never starts over.
+    #[default]
+    AllTime,
+}
+
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema, MergeFrom,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageBudgetUnit {
+    #[default]
+    Dollars,
+    Tokens,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct AgentHooksContent {
+    /// Hooks that run when a message is sent, before the model sees it.
+    /// A hook that exits with a non-zero status blocks the message, and the
+    /// output of successful hooks is added to it as extra context.
+    ///
+    /// Default: []
+    pub user_prompt_submit: Option<Vec<AgentHookContent>>,
+    /// Hooks that run before a tool is called. A hook that exits with a
+    /// non-zero status vetoes the call, and its output is returned to the
+    /// model instead. A hook can change the tool's input by printing a JSON
+    /// object with a `tool_input` field.
+    ///
+    /// Default: []
+    pub pre_tool_call: Option<Vec<AgentHookContent>>,
+    /// Hooks that run after a tool call completes. Their output is added to
+    /// the tool's result.
+    ///
+    /// Default: []
+    pub post_tool_call: Option<Vec<AgentHookContent>>,
+    /// Hooks that run after a tool call edits files, such as formatters or
+    /// linters. Their output is added to the tool's result.
+    ///
+    /// Default: []
+    pub post_edit: Option<Vec<AgentHookContent>>,
+    /// Hooks that run when the agent finishes its turn. A hook that exits
+    /// with a non-zero status sends its output back to the model, which keeps
+    /// working to address it.
+    ///
+    /// Default: []
+    pub stop: Option<Vec<AgentHookContent>>,
+}
+
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
+pub struct AgentHookContent {
+    /// The command to run with the user's shell. It receives a JSON
+    /// description of the event on stdin.
+    #[serde(default)]
+    pub command: String,
+    /// The names of the tools this hook applies to. Only used by the
+    /// `pre_tool_call`, `post_tool_call` and `post_edit` hooks.
+    ///
+    /// Default: all tools
+    pub tools: Option<Vec<String>>,
+    /// How long the command can run before it's stopped, in milliseconds.
+    ///
+    /// Default: 60000
+    pub timeout_ms: Option<u64>,
+}
+
 #[with_fallible_options]
 #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema, MergeFrom)]
 pub struct ToolPermissionsContent {
@@ -139,6 +139,18 @@ pub struct WorktreeSettingsContent {
     /// external dependencies that should not be modified directly.
     /// Default: []
     pub read_only_files: Option<Vec<String>>,
+
+    /// Whether to maintain an on-disk trigram index of the files in local worktrees, so that
+    /// project search can skip files that cannot contain a match without reading them.
+    /// Default: false
+    pub search_index: Option<bool>,
+
+    /// Files in local worktrees at least this many bytes in size are opened in large file mode:
+    /// memory-mapped and displayed a region at a time, instead of being loaded into a buffer.
+    /// Set to 0 to always load files into a buffer.
+    ///
+    /// Default: 134217728
+    pub large_file_threshold: Option<u64>,
 }
 
 #[with_fallible_options]
@@ -358,6 +370,11 @@ pub struct SessionSettingsContent {
     ///
     /// Default: true
     pub restore_unsaved_buffers: Option<bool>,
+    /// Whether or not to persist the undo history of saved files, so that
+    /// edits can be undone after reopening a file that hasn't changed since.
+    ///
+    /// Default: false
+    pub restore_undo_history: Option<bool>,
     /// Whether or not to skip worktree trust checks.
     /// When trusted, project settings are synchronized automatically,
     /// language and MCP servers are downloaded and started automatically.
@@ -366,6 +383,35 @@ pub struct SessionSettingsContent {
     pub trust_all_worktrees: Option<bool>,
 }
 
+#[with_fallible_options]
+#[derive(
+    Default, Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize, JsonSchema, MergeFrom,
+)]
+pub struct LocalHistorySettingsContent {
+    /// Whether to keep snapshots of local files when they are saved, and before they are
+    /// reloaded from disk or edited by an agent.
+    ///
+    /// Default: true
+    pub enabled: Option<bool>,
+    /// The maximum number of snapshots to keep for each file. The oldest ones are deleted first.
+    ///
+    /// Default: 50
+    pub max_entries_per_file: Option<usize>,
+    /// Snapshots older than this many days are deleted.
+    ///
+    /// Default: 30
+    pub max_age_days: Option<u64>,
+    /// Files larger than this many bytes are not snapshotted.
+    ///
+    /// Default: 1048576
+    pub max_file_size: Option<u64>,
+    /// The maximum total size of all snapshots, in bytes. The oldest snapshots, across all files,
+    /// are deleted first.
+    ///
+    /// Default: 104857600
+    pub max_total_size: Option<u64>,
+}
+
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq, JsonSchema, MergeFrom, Debug)]
 #[serde(untagged, rename_all = "snake_case")]
 pub enum ContextServerSettingsContent {
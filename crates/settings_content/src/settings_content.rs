@@ -113,6 +113,9 @@ pub struct SettingsContent {
     pub agent: Option<AgentSettingsContent>,
     pub agent_servers: Option<AllAgentServersSettings>,
 
+    /// Self-hosted providers for the agent's web search tool.
+    pub web_search: Option<WebSearchSettingsContent>,
+
     /// Configuration of audio in Zed.
     pub audio: Option<AudioSettingsContent>,
 
@@ -162,6 +165,9 @@ pub struct SettingsContent {
 
     pub line_indicator_format: Option<LineIndicatorFormat>,
 
+    /// Configuration for the local history of files, kept independently of version control.
+    pub local_history: Option<LocalHistorySettingsContent>,
+
     pub language_models: Option<AllLanguageModelSettingsContent>,
 
     pub outline_panel: Option<OutlinePanelSettingsContent>,
@@ -769,6 +775,9 @@ pub struct VimSettingsContent {
     pub custom_digraphs: Option<HashMap<String, Arc<str>>>,
     pub highlight_on_yank_duration: Option<u64>,
     pub cursor_shape: Option<CursorShapeSettings>,
+    /// Path to a vimrc or init.vim to load mappings and options from.
+    /// The file is reloaded whenever it changes.
+    pub vimrc: Option<String>,
 }
 
 #[derive(
@@ -1040,6 +1049,7 @@ pub struct RemoteSettingsContent {
     pub ssh_connections: Option<Vec<SshConnection>>,
     pub wsl_connections: Option<Vec<WslConnection>>,
     pub dev_container_connections: Option<Vec<DevContainerConnection>>,
+    pub exec_connections: Option<Vec<ExecConnection>>,
     pub read_ssh_config: Option<bool>,
     pub use_podman: Option<bool>,
 }
@@ -1089,6 +1099,33 @@ pub struct WslConnection {
     pub projects: BTreeSet<RemoteProject>,
 }
 
+/// A remote reached by running programs through an arbitrary command, such as
+/// `kubectl exec` or a `nix develop` shell.
+#[with_fallible_options]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, JsonSchema, MergeFrom)]
+pub struct ExecConnection {
+    /// Name to use for this connection in UI.
+    pub name: String,
+    /// The command that runs a program on the remote, where `{cmd}` stands for the
+    /// program and its arguments, e.g. `kubectl exec -i {pod} -- {cmd}`.
+    /// Standard input and output are used to talk to the remote server, so the
+    /// command must forward them.
+    pub command: String,
+    /// The command used for interactive programs such as terminals, usually one
+    /// that allocates a TTY, e.g. `kubectl exec -it {pod} -- {cmd}`.
+    /// Defaults to `command`.
+    pub interactive_command: Option<String>,
+    /// Values for the other `{placeholder}`s in the commands.
+    #[serde(default)]
+    pub variables: BTreeMap<String, String>,
+    /// By default Zed will download the binary on the remote directly.
+    /// If this is set to true, Zed will download the binary to your local machine
+    /// and then upload it through the command.
+    pub upload_binary_over_exec: Option<bool>,
+    #[serde(default)]
+    pub projects: BTreeSet<RemoteProject>,
+}
+
 #[with_fallible_options]
 #[derive(
     Clone, Debug, Default, Serialize, PartialEq, Eq, PartialOrd, Ord, Deserialize, JsonSchema,
@@ -229,7 +229,7 @@ fn general_page() -> SettingsPage {
         ]
     }
 
-    fn workspace_restoration_section() -> [SettingsPageItem; 3] {
+    fn workspace_restoration_section() -> [SettingsPageItem; 4] {
         [
             SettingsPageItem::SectionHeader("Workspace Restoration"),
             SettingsPageItem::SettingItem(SettingItem {
@@ -253,6 +253,27 @@ fn general_page() -> SettingsPage {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Restore Undo History",
+                description: "Whether or not to persist the undo history of saved files.",
+                field: Box::new(SettingField {
+                    json_path: Some("session.restore_undo_history"),
+                    pick: |settings_content| {
+                        settings_content
+                            .session
+                            .as_ref()
+                            .and_then(|session| session.restore_undo_history.as_ref())
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .session
+                            .get_or_insert_default()
+                            .restore_undo_history = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
             SettingsPageItem::SettingItem(SettingItem {
                 title: "Restore On Startup",
                 description: "What to restore from the previous session when opening Zed.",
@@ -2447,7 +2468,7 @@ fn editor_page() -> SettingsPage {
         ]
     }
 
-    fn vim_settings_section() -> [SettingsPageItem; 12] {
+    fn vim_settings_section() -> [SettingsPageItem; 13] {
         [
             SettingsPageItem::SectionHeader("Vim"),
             SettingsPageItem::SettingItem(SettingItem {
@@ -2678,6 +2699,19 @@ fn editor_page() -> SettingsPage {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Vimrc",
+                description: "Path to a vimrc or init.vim to load mappings and options from.",
+                field: Box::new(SettingField {
+                    json_path: Some("vim.vimrc"),
+                    pick: |settings_content| settings_content.vim.as_ref()?.vimrc.as_ref(),
+                    write: |settings_content, value| {
+                        settings_content.vim.get_or_insert_default().vimrc = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
         ]
     }
 
@@ -7470,6 +7504,24 @@ fn ai_page(cx: &App) -> SettingsPage {
                 metadata: None,
                 files: USER,
             }),
+            SettingsPageItem::SettingItem(SettingItem {
+                title: "Auto Compact Threads",
+                description: "Whether to summarize older turns when a thread approaches the model's context window.",
+                field: Box::new(SettingField {
+                    json_path: Some("agent.auto_compact_threads"),
+                    pick: |settings_content| {
+                        settings_content.agent.as_ref()?.auto_compact_threads.as_ref()
+                    },
+                    write: |settings_content, value| {
+                        settings_content
+                            .agent
+                            .get_or_insert_default()
+                            .auto_compact_threads = value;
+                    },
+                }),
+                metadata: None,
+                files: USER,
+            }),
         ]);
 
         items.into_boxed_slice()
@@ -56,6 +56,12 @@ const TOOLS: &[ToolInfo] = &[
         description: "Directory creation",
         regex_explanation: "Patterns are matched against the directory path being created.",
     },
+    ToolInfo {
+        id: "rename_symbol",
+        name: "Rename Symbol",
+        description: "Language server powered renames",
+        regex_explanation: "Patterns are matched against each file path the rename is expected to edit.",
+    },
     ToolInfo {
         id: "save_file",
         name: "Save File",
@@ -74,6 +80,12 @@ const TOOLS: &[ToolInfo] = &[
         description: "Web search queries",
         regex_explanation: "Patterns are matched against the search query.",
     },
+    ToolInfo {
+        id: "git_commit",
+        name: "Git Commit",
+        description: "Commits to the project's git repositories",
+        regex_explanation: "Patterns are matched against the commit message.",
+    },
     ToolInfo {
         id: "restore_file_from_disk",
         name: "Restore File from Disk",
@@ -307,9 +319,11 @@ fn get_tool_render_fn(
         "copy_path" => render_copy_path_tool_config,
         "move_path" => render_move_path_tool_config,
         "create_directory" => render_create_directory_tool_config,
+        "rename_symbol" => render_rename_symbol_tool_config,
         "save_file" => render_save_file_tool_config,
         "fetch" => render_fetch_tool_config,
         "web_search" => render_web_search_tool_config,
+        "git_commit" => render_git_commit_tool_config,
         "restore_file_from_disk" => render_restore_file_from_disk_tool_config,
         _ => render_terminal_tool_config, // fallback
     }
@@ -1387,9 +1401,11 @@ tool_config_page_fn!(render_delete_path_tool_config, "delete_path");
 tool_config_page_fn!(render_copy_path_tool_config, "copy_path");
 tool_config_page_fn!(render_move_path_tool_config, "move_path");
 tool_config_page_fn!(render_create_directory_tool_config, "create_directory");
+tool_config_page_fn!(render_rename_symbol_tool_config, "rename_symbol");
 tool_config_page_fn!(render_save_file_tool_config, "save_file");
 tool_config_page_fn!(render_fetch_tool_config, "fetch");
 tool_config_page_fn!(render_web_search_tool_config, "web_search");
+tool_config_page_fn!(render_git_commit_tool_config, "git_commit");
 tool_config_page_fn!(
     render_restore_file_from_disk_tool_config,
     "restore_file_from_disk"
@@ -1409,18 +1425,30 @@ mod tests {
             // Read-only / low-risk tools that don't call decide_permission_from_settings
             "diagnostics",
             "find_path",
+            "find_references",
+            "git_blame",
+            "git_diff",
+            "git_log",
+            "git_status",
+            "go_to_definition",
             "grep",
+            "hover",
             "list_directory",
+            "list_tasks",
             "now",
             "open",
             "read_file",
             "thinking",
+            "workspace_symbols",
             // streaming_edit_file uses "edit_file" for permission lookups,
             // so its rules are configured under the edit_file entry.
             "streaming_edit_file",
             // Subagent permission checks happen at the level of individual
             // tool calls within the subagent, not at the spawning level.
             "spawn_agent",
+            // run_task runs shell commands, so it's checked against the
+            // terminal rules rather than rules of its own.
+            "run_task",
             // update_plan updates UI-visible planning state but does not use
             // tool permission rules.
             "update_plan",
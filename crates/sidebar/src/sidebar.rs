@@ -1300,6 +1300,7 @@ impl Sidebar {
         let remote_icon_per_type = match remote_connection_options {
             RemoteConnectionOptions::Wsl(_) => IconName::Linux,
             RemoteConnectionOptions::Docker(_) => IconName::Box,
+            RemoteConnectionOptions::Exec(_) => IconName::Terminal,
             _ => IconName::Server,
         };
 
@@ -2529,7 +2530,9 @@ impl Sidebar {
             .insert(session_id.clone(), Utc::now());
     }
 
-    fn mru_threads_for_switcher(&self, _cx: &App) -> Vec<ThreadSwitcherEntry> {
+    fn mru_threads_for_switcher(&self, cx: &App) -> Vec<ThreadSwitcherEntry> {
+        let thread_store = ThreadMetadataStore::global(cx);
+        let thread_store = thread_store.read(cx);
         let mut current_header_label: Option<SharedString> = None;
         let mut current_header_workspace: Option<Entity<Workspace>> = None;
         let mut entries: Vec<ThreadSwitcherEntry> = self
@@ -2580,6 +2583,12 @@ impl Sidebar {
                                 highlight_positions: Vec::new(),
                             })
                             .collect(),
+                        forked_from_title: thread
+                            .metadata
+                            .forked_from
+                            .as_ref()
+                            .and_then(|id| thread_store.entry(id))
+                            .map(|parent| parent.title.clone()),
                         diff_stats: thread.diff_stats,
                         is_title_generating: thread.is_title_generating,
                         notified,
@@ -151,6 +151,7 @@ fn save_thread_metadata(
         folder_paths: path_list,
         main_worktree_paths: PathList::default(),
         archived: false,
+        forked_from: None,
     };
     cx.update(|cx| {
         ThreadMetadataStore::global(cx).update(cx, |store, cx| store.save_manually(metadata, cx))
@@ -703,6 +704,7 @@ async fn test_visible_entries_as_strings(cx: &mut TestAppContext) {
                     updated_at: Utc::now(),
                     created_at: Some(Utc::now()),
                     archived: false,
+                    forked_from: None,
                 },
                 icon: IconName::ZedAgent,
                 icon_from_external_svg: None,
@@ -726,6 +728,7 @@ async fn test_visible_entries_as_strings(cx: &mut TestAppContext) {
                     updated_at: Utc::now(),
                     created_at: Some(Utc::now()),
                     archived: false,
+                    forked_from: None,
                 },
                 icon: IconName::ZedAgent,
                 icon_from_external_svg: None,
@@ -749,6 +752,7 @@ async fn test_visible_entries_as_strings(cx: &mut TestAppContext) {
                     updated_at: Utc::now(),
                     created_at: Some(Utc::now()),
                     archived: false,
+                    forked_from: None,
                 },
                 icon: IconName::ZedAgent,
                 icon_from_external_svg: None,
@@ -772,6 +776,7 @@ async fn test_visible_entries_as_strings(cx: &mut TestAppContext) {
                     updated_at: Utc::now(),
                     created_at: Some(Utc::now()),
                     archived: false,
+                    forked_from: None,
                 },
                 icon: IconName::ZedAgent,
                 icon_from_external_svg: None,
@@ -795,6 +800,7 @@ async fn test_visible_entries_as_strings(cx: &mut TestAppContext) {
                     updated_at: Utc::now(),
                     created_at: Some(Utc::now()),
                     archived: false,
+                    forked_from: None,
                 },
                 icon: IconName::ZedAgent,
                 icon_from_external_svg: None,
@@ -2060,6 +2066,7 @@ async fn test_focused_thread_tracks_user_intent(cx: &mut TestAppContext) {
                 folder_paths: PathList::default(),
                 main_worktree_paths: PathList::default(),
                 archived: false,
+                forked_from: None,
             },
             &workspace_a,
             window,
@@ -2116,6 +2123,7 @@ async fn test_focused_thread_tracks_user_intent(cx: &mut TestAppContext) {
                 folder_paths: PathList::default(),
                 main_worktree_paths: PathList::default(),
                 archived: false,
+                forked_from: None,
             },
             &workspace_b,
             window,
@@ -3581,6 +3589,7 @@ async fn test_activate_archived_thread_with_saved_paths_activates_matching_works
                 folder_paths: PathList::new(&[PathBuf::from("/project-b")]),
                 main_worktree_paths: PathList::default(),
                 archived: false,
+                forked_from: None,
             },
             window,
             cx,
@@ -3644,6 +3653,7 @@ async fn test_activate_archived_thread_cwd_fallback_with_matching_workspace(
                 folder_paths: PathList::new(&[std::path::PathBuf::from("/project-b")]),
                 main_worktree_paths: PathList::default(),
                 archived: false,
+                forked_from: None,
             },
             window,
             cx,
@@ -3707,6 +3717,7 @@ async fn test_activate_archived_thread_no_paths_no_cwd_uses_active_workspace(
                 folder_paths: PathList::default(),
                 main_worktree_paths: PathList::default(),
                 archived: false,
+                forked_from: None,
             },
             window,
             cx,
@@ -3762,6 +3773,7 @@ async fn test_activate_archived_thread_saved_paths_opens_new_workspace(cx: &mut
                 folder_paths: path_list_b,
                 main_worktree_paths: PathList::default(),
                 archived: false,
+                forked_from: None,
             },
             window,
             cx,
@@ -3812,6 +3824,7 @@ async fn test_activate_archived_thread_reuses_workspace_in_another_window(cx: &m
                 folder_paths: PathList::new(&[PathBuf::from("/project-b")]),
                 main_worktree_paths: PathList::default(),
                 archived: false,
+                forked_from: None,
             },
             window,
             cx,
@@ -3889,6 +3902,7 @@ async fn test_activate_archived_thread_reuses_workspace_in_another_window_with_t
                 folder_paths: PathList::new(&[PathBuf::from("/project-b")]),
                 main_worktree_paths: PathList::default(),
                 archived: false,
+                forked_from: None,
             },
             window,
             cx,
@@ -3965,6 +3979,7 @@ async fn test_activate_archived_thread_prefers_current_window_for_matching_paths
                 folder_paths: PathList::new(&[PathBuf::from("/project-a")]),
                 main_worktree_paths: PathList::default(),
                 archived: false,
+                forked_from: None,
             },
             window,
             cx,
@@ -4953,6 +4968,7 @@ mod property_test {
             folder_paths: path_list,
             main_worktree_paths,
             archived: false,
+            forked_from: None,
         };
         cx.update(|_, cx| {
             ThreadMetadataStore::global(cx)
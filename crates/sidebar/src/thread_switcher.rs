@@ -19,6 +19,8 @@ pub(crate) struct ThreadSwitcherEntry {
     pub workspace: Entity<Workspace>,
     pub project_name: Option<SharedString>,
     pub worktrees: Vec<ThreadItemWorktreeInfo>,
+    /// The title of the thread this one was forked from, if any.
+    pub forked_from_title: Option<SharedString>,
     pub diff_stats: DiffStats,
     pub is_title_generating: bool,
     pub notified: bool,
@@ -213,6 +215,9 @@ impl Render for ThreadSwitcher {
                         this.project_name(name)
                     })
                     .worktrees(entry.worktrees.clone())
+                    .when_some(entry.forked_from_title.clone(), |this, title| {
+                        this.forked_from(title)
+                    })
                     .timestamp(entry.timestamp.clone())
                     .title_generating(entry.is_title_generating)
                     .notified(entry.notified)
@@ -0,0 +1,300 @@
+//! The loop reading the PTY output into the terminal and writing the user's input
+//! to the PTY, on its own thread.
+//!
+//! This mirrors Alacritty's `EventLoop`, except that the output is parsed through
+//! the [`OutputInterceptor`], so that the escape sequences Alacritty doesn't handle
+//! are resolved against the terminal state at the point they were emitted.
+
+use std::{
+    borrow::Cow,
+    collections::VecDeque,
+    io::{self, ErrorKind, Read, Write},
+    num::NonZeroUsize,
+    sync::{
+        Arc,
+        mpsc::{self, Receiver, Sender, TryRecvError},
+    },
+    thread::JoinHandle,
+    time::Instant,
+};
+
+use alacritty_terminal::{
+    Term,
+    event::{Event, EventListener, Notify, OnResize, WindowSize},
+    event_loop::Msg,
+    sync::FairMutex,
+    tty::{ChildEvent, EventedPty, EventedReadWrite},
+    vte::ansi::{Processor, StdSyncHandler},
+};
+use polling::{Event as PollingEvent, Events, PollMode, Poller};
+
+use crate::output_interceptor::{InterceptedOutputQueue, OutputInterceptor};
+
+/// The number of bytes read before parsing them even if that means waiting for
+/// the terminal lock.
+const READ_BUFFER_SIZE: usize = 0x10_0000;
+/// The number of bytes parsed before releasing the terminal lock.
+const MAX_LOCKED_READ: usize = u16::MAX as usize;
+
+pub(crate) struct EventLoop<T, U: EventListener> {
+    poll: Arc<Poller>,
+    pty: T,
+    rx: Receiver<Msg>,
+    tx: Sender<Msg>,
+    terminal: Arc<FairMutex<Term<U>>>,
+    event_proxy: U,
+    drain_on_exit: bool,
+    interceptor: OutputInterceptor,
+    intercepted_output: InterceptedOutputQueue,
+}
+
+#[derive(Default)]
+struct State {
+    parser: Processor<StdSyncHandler>,
+    write_list: VecDeque<Cow<'static, [u8]>>,
+    /// How much of the front of `write_list` was written already.
+    written: usize,
+}
+
+impl<T, U> EventLoop<T, U>
+where
+    T: EventedReadWrite + EventedPty + OnResize + Send + 'static,
+    U: EventListener + Send + 'static,
+{
+    pub fn new(
+        terminal: Arc<FairMutex<Term<U>>>,
+        event_proxy: U,
+        pty: T,
+        drain_on_exit: bool,
+        interceptor: OutputInterceptor,
+        intercepted_output: InterceptedOutputQueue,
+    ) -> io::Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        Ok(Self {
+            poll: Poller::new()?.into(),
+            pty,
+            rx,
+            tx,
+            terminal,
+            event_proxy,
+            drain_on_exit,
+            interceptor,
+            intercepted_output,
+        })
+    }
+
+    pub fn channel(&self) -> EventLoopSender {
+        EventLoopSender {
+            sender: self.tx.clone(),
+            poller: self.poll.clone(),
+        }
+    }
+
+    pub fn spawn(mut self) -> JoinHandle<()> {
+        std::thread::Builder::new()
+            .name("PTY reader".into())
+            .spawn(move || self.run())
+            .expect("failed to spawn the PTY reader thread")
+    }
+
+    fn run(&mut self) {
+        let mut state = State::default();
+        let mut buf = vec![0; READ_BUFFER_SIZE];
+        let mut interest = PollingEvent::readable(0);
+        // SAFETY: the PTY is deregistered before the poller is dropped.
+        if let Err(error) = unsafe { self.pty.register(&self.poll, interest, PollMode::Level) } {
+            log::error!("failed to register the PTY: {error}");
+            return;
+        }
+
+        let mut events = Events::with_capacity(NonZeroUsize::new(1024).unwrap());
+        'event_loop: loop {
+            let timeout = state
+                .parser
+                .sync_timeout()
+                .sync_timeout()
+                .map(|timeout| timeout.saturating_duration_since(Instant::now()));
+            events.clear();
+            if let Err(error) = self.poll.wait(&mut events, timeout) {
+                if error.kind() == ErrorKind::Interrupted {
+                    continue;
+                }
+                log::error!("failed to poll the PTY: {error}");
+                break;
+            }
+
+            let Some(received) = self.drain_channel(&mut state) else {
+                break;
+            };
+            // Nothing happened before a synchronized update timed out.
+            if events.is_empty() && received == 0 {
+                state.parser.stop_sync(&mut *self.terminal.lock());
+                self.event_proxy.send_event(Event::Wakeup);
+                continue;
+            }
+
+            for event in events.iter() {
+                if event.readable
+                    && let Err(error) = self.pty_read(&mut state, &mut buf)
+                {
+                    // Linux reports EIO once the child exited, which is handled below.
+                    #[cfg(target_os = "linux")]
+                    if error.raw_os_error() == Some(libc::EIO) {
+                        continue;
+                    }
+                    log::error!("failed to read from the PTY: {error}");
+                    break 'event_loop;
+                }
+                if event.writable
+                    && let Err(error) = self.pty_write(&mut state)
+                {
+                    log::error!("failed to write to the PTY: {error}");
+                    break 'event_loop;
+                }
+            }
+
+            if let Some(ChildEvent::Exited(code)) = self.pty.next_child_event() {
+                if self.drain_on_exit {
+                    self.pty_read(&mut state, &mut buf).ok();
+                }
+                if let Some(code) = code {
+                    self.event_proxy.send_event(Event::ChildExit(code));
+                }
+                self.terminal.lock().exit();
+                self.event_proxy.send_event(Event::Wakeup);
+                break;
+            }
+
+            let needs_write = !state.write_list.is_empty();
+            if needs_write != interest.writable {
+                interest.writable = needs_write;
+                if let Err(error) = self.pty.reregister(&self.poll, interest, PollMode::Level) {
+                    log::error!("failed to reregister the PTY: {error}");
+                    break;
+                }
+            }
+        }
+
+        self.pty.deregister(&self.poll).ok();
+    }
+
+    /// Handles the pending messages, returning how many there were, or `None` when
+    /// the loop should shut down.
+    fn drain_channel(&mut self, state: &mut State) -> Option<usize> {
+        let mut received = 0;
+        loop {
+            match self.rx.try_recv() {
+                Ok(Msg::Input(input)) => state.write_list.push_back(input),
+                Ok(Msg::Resize(window_size)) => {
+                    self.interceptor.metrics = window_size.into();
+                    self.pty.on_resize(window_size);
+                }
+                Ok(Msg::Shutdown) | Err(TryRecvError::Disconnected) => return None,
+                Err(TryRecvError::Empty) => return Some(received),
+            }
+            received += 1;
+        }
+    }
+
+    fn pty_read(&mut self, state: &mut State, buf: &mut [u8]) -> io::Result<()> {
+        let mut unprocessed = 0;
+        let mut processed = 0;
+        let mut output = Vec::new();
+        let mut terminal = None;
+        loop {
+            match self.pty.reader().read(&mut buf[unprocessed..]) {
+                Ok(0) if unprocessed == 0 => break,
+                Ok(read) => unprocessed += read,
+                Err(error) => match error.kind() {
+                    ErrorKind::Interrupted | ErrorKind::WouldBlock if unprocessed == 0 => break,
+                    ErrorKind::Interrupted | ErrorKind::WouldBlock => {}
+                    _ => return Err(error),
+                },
+            }
+
+            // Keep reading rather than wait for the UI thread to release the terminal,
+            // unless the buffer is full.
+            let terminal = match &mut terminal {
+                Some(terminal) => terminal,
+                None => terminal.insert(match self.terminal.try_lock_unfair() {
+                    Some(terminal) => terminal,
+                    None if unprocessed < buf.len() => continue,
+                    None => self.terminal.lock_unfair(),
+                }),
+            };
+            self.interceptor.advance(
+                &mut state.parser,
+                &mut **terminal,
+                &buf[..unprocessed],
+                &mut output,
+            );
+            processed += unprocessed;
+            unprocessed = 0;
+            if processed >= MAX_LOCKED_READ {
+                break;
+            }
+        }
+        drop(terminal);
+
+        if !output.is_empty() {
+            self.intercepted_output.lock().extend(output);
+        }
+        // Redraw unless everything was part of a synchronized update.
+        if processed > 0 && state.parser.sync_bytes_count() < processed {
+            self.event_proxy.send_event(Event::Wakeup);
+        }
+        Ok(())
+    }
+
+    fn pty_write(&mut self, state: &mut State) -> io::Result<()> {
+        while let Some(bytes) = state.write_list.front() {
+            match self.pty.writer().write(&bytes[state.written..]) {
+                Ok(0) => break,
+                Ok(written) => {
+                    state.written += written;
+                    if state.written == bytes.len() {
+                        state.write_list.pop_front();
+                        state.written = 0;
+                    }
+                }
+                Err(error) => match error.kind() {
+                    ErrorKind::Interrupted | ErrorKind::WouldBlock => break,
+                    _ => return Err(error),
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Sends messages to an [`EventLoop`], waking it up.
+pub(crate) struct EventLoopSender {
+    sender: Sender<Msg>,
+    poller: Arc<Poller>,
+}
+
+impl EventLoopSender {
+    pub fn send(&self, msg: Msg) -> anyhow::Result<()> {
+        self.sender.send(msg)?;
+        self.poller.notify()?;
+        Ok(())
+    }
+}
+
+/// Writes the user's input to the PTY of an [`EventLoop`].
+pub(crate) struct Notifier(pub EventLoopSender);
+
+impl Notify for Notifier {
+    fn notify<B: Into<Cow<'static, [u8]>>>(&self, bytes: B) {
+        let bytes = bytes.into();
+        if !bytes.is_empty() {
+            self.0.send(Msg::Input(bytes)).ok();
+        }
+    }
+}
+
+impl OnResize for Notifier {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.0.send(Msg::Resize(window_size)).ok();
+    }
+}
@@ -0,0 +1,829 @@
+//! Inline images drawn by programs through the kitty graphics protocol
+//! (`APC G ... ST`) and iTerm2's `OSC 1337 ; File=...` sequence.
+//!
+//! Alacritty doesn't know about either protocol, so the sequences are picked out of
+//! the output before it's parsed. Each displayed image is anchored to the cell the
+//! cursor was at, and the cursor is moved past it the same way the reference
+//! terminals do, so that subsequent output doesn't overlap the image.
+//!
+//! Only the image's dimensions are read while the output is parsed, which is enough
+//! to move the cursor past it. The image itself is decoded in the background.
+
+use std::{io::Cursor, sync::Arc};
+
+use alacritty_terminal::index::Point as AlacPoint;
+use base64::{
+    Engine as _, alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+};
+use collections::HashMap;
+use gpui::RenderImage;
+use parking_lot::Mutex;
+
+/// Longest escape sequence collected, and the most data a chunked kitty transmission
+/// can add up to. Roughly a 48MB image once decoded.
+const MAX_IMAGE_SEQUENCE_LEN: usize = 64 * 1024 * 1024;
+/// How much of an encoded image's payload is decoded to read its dimensions from its
+/// header.
+const MAX_HEADER_PAYLOAD_LEN: usize = 256 * 1024;
+/// How many images transmitted through the kitty protocol are kept for later placement.
+const MAX_TRANSMITTED_IMAGES: usize = 64;
+const ITERM_FILE_PREFIX: &[u8] = b"1337;File=";
+
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// An image displayed in the terminal grid. Until it's decoded, the image is a
+/// [`PendingImage`].
+#[derive(Clone, Debug)]
+pub struct ImagePlacement<Image = Arc<RenderImage>> {
+    pub image: Image,
+    /// The top left cell of the image, with the line counted from the top of the scrollback.
+    pub point: AlacPoint<usize>,
+    pub columns: usize,
+    pub rows: usize,
+    /// Whether the image was drawn on the alternate screen.
+    pub alt_screen: bool,
+    pub(crate) kitty_id: Option<u32>,
+}
+
+impl<Image> ImagePlacement<Image> {
+    pub(crate) fn with_image<T>(self, image: T) -> ImagePlacement<T> {
+        ImagePlacement {
+            image,
+            point: self.point,
+            columns: self.columns,
+            rows: self.rows,
+            alt_screen: self.alt_screen,
+            kitty_id: self.kitty_id,
+        }
+    }
+}
+
+/// The dimensions needed to lay images out in the grid.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CellMetrics {
+    pub cell_width: f32,
+    pub cell_height: f32,
+    pub columns: usize,
+    pub screen_lines: usize,
+}
+
+impl Default for CellMetrics {
+    fn default() -> Self {
+        Self {
+            cell_width: 8.,
+            cell_height: 16.,
+            columns: 80,
+            screen_lines: 24,
+        }
+    }
+}
+
+/// An image sequence, as found in the output.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum ImageSequence {
+    Kitty {
+        control: Vec<u8>,
+        payload: Vec<u8>,
+    },
+    Iterm {
+        arguments: Vec<u8>,
+        payload: Vec<u8>,
+    },
+}
+
+/// Incrementally recognizes image sequences in a byte stream, which may be split at
+/// arbitrary positions.
+#[derive(Default)]
+pub(crate) struct ImageScanner {
+    state: ScanState,
+    buffer: Vec<u8>,
+}
+
+#[derive(Default, Clone, Copy)]
+enum ScanState {
+    #[default]
+    Ground,
+    Escape,
+    ApcStart,
+    Kitty,
+    KittyEscape,
+    OscPrefix(usize),
+    Iterm,
+    ItermEscape,
+}
+
+impl ImageScanner {
+    /// Feeds a single byte, returning the image sequence that this byte completed, if any.
+    pub fn advance(&mut self, byte: u8) -> Option<ImageSequence> {
+        self.state = match self.state {
+            ScanState::Ground | ScanState::KittyEscape | ScanState::ItermEscape if byte == 0x1b => {
+                ScanState::Escape
+            }
+            ScanState::Ground => ScanState::Ground,
+            ScanState::Escape => match byte {
+                b'_' => ScanState::ApcStart,
+                b']' => ScanState::OscPrefix(0),
+                0x1b => ScanState::Escape,
+                _ => ScanState::Ground,
+            },
+            ScanState::ApcStart if byte == b'G' => {
+                self.buffer.clear();
+                ScanState::Kitty
+            }
+            ScanState::ApcStart => ScanState::Ground,
+            ScanState::OscPrefix(matched) if byte == ITERM_FILE_PREFIX[matched] => {
+                if matched + 1 == ITERM_FILE_PREFIX.len() {
+                    self.buffer.clear();
+                    ScanState::Iterm
+                } else {
+                    ScanState::OscPrefix(matched + 1)
+                }
+            }
+            ScanState::OscPrefix(_) if byte == 0x1b => ScanState::Escape,
+            ScanState::OscPrefix(_) => ScanState::Ground,
+            ScanState::Kitty | ScanState::Iterm if self.buffer.len() >= MAX_IMAGE_SEQUENCE_LEN => {
+                self.buffer = Vec::new();
+                ScanState::Ground
+            }
+            ScanState::Kitty if byte == 0x1b => ScanState::KittyEscape,
+            ScanState::Iterm if byte == 0x1b => ScanState::ItermEscape,
+            ScanState::Iterm if byte == 0x07 => return self.finish(false),
+            state @ (ScanState::Kitty | ScanState::Iterm) => {
+                self.buffer.push(byte);
+                state
+            }
+            ScanState::KittyEscape if byte == b'\\' => return self.finish(true),
+            ScanState::ItermEscape if byte == b'\\' => return self.finish(false),
+            ScanState::KittyEscape | ScanState::ItermEscape => ScanState::Ground,
+        };
+        None
+    }
+
+    fn finish(&mut self, kitty: bool) -> Option<ImageSequence> {
+        self.state = ScanState::Ground;
+        let mut sequence = std::mem::take(&mut self.buffer);
+        if kitty {
+            let payload = match sequence.iter().position(|byte| *byte == b';') {
+                Some(ix) => sequence.split_off(ix + 1),
+                None => Vec::new(),
+            };
+            if sequence.last() == Some(&b';') {
+                sequence.pop();
+            }
+            Some(ImageSequence::Kitty {
+                control: sequence,
+                payload,
+            })
+        } else {
+            let ix = sequence.iter().position(|byte| *byte == b':')?;
+            let payload = sequence.split_off(ix + 1);
+            sequence.pop();
+            Some(ImageSequence::Iterm {
+                arguments: sequence,
+                payload,
+            })
+        }
+    }
+}
+
+/// Image data received from a program, which is decoded when it's first placed.
+pub(crate) struct PendingImage {
+    width: u32,
+    height: u32,
+    state: Mutex<PendingImageState>,
+}
+
+enum PendingImageState {
+    Encoded {
+        format: PayloadFormat,
+        payload: Vec<u8>,
+    },
+    Decoded(Option<Arc<RenderImage>>),
+}
+
+#[derive(Clone, Copy)]
+enum PayloadFormat {
+    /// An image file, such as a PNG.
+    File,
+    Rgb,
+    Rgba,
+}
+
+impl std::fmt::Debug for PendingImage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingImage")
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PendingImage {
+    fn new(format: PayloadFormat, payload: Vec<u8>, (width, height): (u32, u32)) -> Arc<Self> {
+        Arc::new(Self {
+            width,
+            height,
+            state: Mutex::new(PendingImageState::Encoded { format, payload }),
+        })
+    }
+
+    /// Decodes the image, which is slow, so should be done in the background. The
+    /// image is decoded once, however many times it's placed.
+    pub fn decode(&self) -> Option<Arc<RenderImage>> {
+        let mut state = self.state.lock();
+        if let PendingImageState::Encoded { format, payload } = &mut *state {
+            let image = decode_payload(*format, std::mem::take(payload), self.width, self.height)
+                .inspect_err(|error| log::debug!("failed to decode inline terminal image: {error}"))
+                .ok();
+            *state = PendingImageState::Decoded(image);
+        }
+        match &*state {
+            PendingImageState::Decoded(image) => image.clone(),
+            PendingImageState::Encoded { .. } => None,
+        }
+    }
+}
+
+/// What an image sequence asks the terminal to do.
+pub(crate) enum ImageAction {
+    Place {
+        image: Arc<PendingImage>,
+        columns: usize,
+        rows: usize,
+        move_cursor: bool,
+        kitty_id: Option<u32>,
+    },
+    Delete(ImageDeletion),
+    /// A response to send back to the program.
+    Reply(Vec<u8>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ImageDeletion {
+    All,
+    Kitty(u32),
+}
+
+impl ImageAction {
+    /// The bytes that move the cursor past a placed image, to the cell following
+    /// its bottom right corner.
+    pub fn cursor_movement(&self) -> Vec<u8> {
+        match self {
+            Self::Place {
+                columns,
+                rows,
+                move_cursor: true,
+                ..
+            } => {
+                let mut bytes = b"\n".repeat(rows.saturating_sub(1));
+                bytes.extend(format!("\x1b[{columns}C").into_bytes());
+                bytes
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// A kitty transmission split into chunks, which is being received.
+enum ChunkedTransmission {
+    Receiving(KittyControl, Vec<u8>),
+    /// The transmission grew larger than [`MAX_IMAGE_SEQUENCE_LEN`], so the rest of
+    /// its chunks are ignored.
+    TooLarge(KittyControl),
+}
+
+/// Turns image sequences into actions, keeping track of the images transmitted
+/// through the kitty protocol for later placement. The images are left for the
+/// [`crate::Terminal`] to decode in the background.
+#[derive(Default)]
+pub(crate) struct ImageDecoder {
+    transmitted: HashMap<u32, Arc<PendingImage>>,
+    chunked: Option<ChunkedTransmission>,
+}
+
+impl ImageDecoder {
+    pub fn decode(&mut self, sequence: ImageSequence, metrics: CellMetrics) -> Vec<ImageAction> {
+        match sequence {
+            ImageSequence::Kitty { control, payload } => {
+                let control = KittyControl::parse(&control);
+                match self.chunked.take() {
+                    Some(ChunkedTransmission::Receiving(first, mut data)) => {
+                        if data.len() + payload.len() > MAX_IMAGE_SEQUENCE_LEN {
+                            return self.too_large(first, control.more);
+                        }
+                        data.extend(payload);
+                        if control.more {
+                            self.chunked = Some(ChunkedTransmission::Receiving(first, data));
+                            Vec::new()
+                        } else {
+                            self.kitty(first, data, metrics)
+                        }
+                    }
+                    Some(ChunkedTransmission::TooLarge(first)) => {
+                        self.too_large(first, control.more)
+                    }
+                    None if control.more => {
+                        self.chunked = Some(ChunkedTransmission::Receiving(control, payload));
+                        Vec::new()
+                    }
+                    None => self.kitty(control, payload, metrics),
+                }
+            }
+            ImageSequence::Iterm { arguments, payload } => {
+                iterm(&arguments, payload, metrics).into_iter().collect()
+            }
+        }
+    }
+
+    /// Drops a chunked transmission that's too large, replying once its last chunk
+    /// is received.
+    fn too_large(&mut self, first: KittyControl, more: bool) -> Vec<ImageAction> {
+        if more {
+            self.chunked = Some(ChunkedTransmission::TooLarge(first));
+            Vec::new()
+        } else {
+            first
+                .reply("EFBIG:image data is too large", true)
+                .into_iter()
+                .collect()
+        }
+    }
+
+    fn kitty(
+        &mut self,
+        control: KittyControl,
+        payload: Vec<u8>,
+        metrics: CellMetrics,
+    ) -> Vec<ImageAction> {
+        let image = match control.action {
+            b'd' => {
+                return vec![ImageAction::Delete(match control.delete {
+                    b'i' | b'I' => {
+                        if control.delete == b'I' {
+                            self.transmitted.remove(&control.id);
+                        }
+                        ImageDeletion::Kitty(control.id)
+                    }
+                    _ => ImageDeletion::All,
+                })];
+            }
+            b'p' => self
+                .transmitted
+                .get(&control.id)
+                .cloned()
+                .ok_or_else(|| "ENOENT:no image with this id".to_string()),
+            b't' | b'T' | b'q' => kitty_image(&control, payload),
+            _ => return Vec::new(),
+        };
+
+        let mut actions = Vec::new();
+        match image {
+            Ok(image) => {
+                if control.action != b'q' {
+                    if control.id != 0 {
+                        if self.transmitted.len() >= MAX_TRANSMITTED_IMAGES {
+                            self.transmitted.clear();
+                        }
+                        self.transmitted.insert(control.id, image.clone());
+                    }
+                    if control.action != b't' {
+                        let (columns, rows) = fit(
+                            (image.width, image.height),
+                            control.columns.map(|columns| columns as f32),
+                            control.rows.map(|rows| rows as f32),
+                            false,
+                            metrics,
+                        );
+                        actions.push(ImageAction::Place {
+                            image,
+                            columns,
+                            rows,
+                            move_cursor: !control.keep_cursor,
+                            kitty_id: (control.id != 0).then_some(control.id),
+                        });
+                    }
+                }
+                actions.extend(control.reply("OK", false));
+            }
+            Err(error) => actions.extend(control.reply(&error, true)),
+        }
+        actions
+    }
+}
+
+/// The control data of a kitty graphics command, with the defaults of the protocol.
+struct KittyControl {
+    action: u8,
+    format: u32,
+    medium: u8,
+    width: u32,
+    height: u32,
+    id: u32,
+    columns: Option<usize>,
+    rows: Option<usize>,
+    more: bool,
+    quiet: u8,
+    compressed: bool,
+    keep_cursor: bool,
+    delete: u8,
+}
+
+impl KittyControl {
+    fn parse(control: &[u8]) -> Self {
+        let mut this = Self {
+            action: b't',
+            format: 32,
+            medium: b'd',
+            width: 0,
+            height: 0,
+            id: 0,
+            columns: None,
+            rows: None,
+            more: false,
+            quiet: 0,
+            compressed: false,
+            keep_cursor: false,
+            delete: b'a',
+        };
+        for pair in control.split(|byte| *byte == b',') {
+            let Some(ix) = pair.iter().position(|byte| *byte == b'=') else {
+                continue;
+            };
+            let (key, value) = (&pair[..ix], &pair[ix + 1..]);
+            let number = || std::str::from_utf8(value).ok()?.parse::<u32>().ok();
+            let first = value.first().copied();
+            match key {
+                b"a" => this.action = first.unwrap_or(this.action),
+                b"f" => this.format = number().unwrap_or(this.format),
+                b"t" => this.medium = first.unwrap_or(this.medium),
+                b"s" => this.width = number().unwrap_or(0),
+                b"v" => this.height = number().unwrap_or(0),
+                b"i" => this.id = number().unwrap_or(0),
+                b"c" => this.columns = number().filter(|c| *c > 0).map(|c| c as usize),
+                b"r" => this.rows = number().filter(|r| *r > 0).map(|r| r as usize),
+                b"m" => this.more = value == b"1",
+                b"q" => this.quiet = number().unwrap_or(0) as u8,
+                b"o" => this.compressed = true,
+                b"C" => this.keep_cursor = value == b"1",
+                b"d" => this.delete = first.unwrap_or(this.delete),
+                _ => {}
+            }
+        }
+        this
+    }
+
+    /// Kitty only answers commands that carry an image id, unless asked to be quiet.
+    fn reply(&self, message: &str, error: bool) -> Option<ImageAction> {
+        let quiet = if error {
+            self.quiet >= 2
+        } else {
+            self.quiet >= 1
+        };
+        (self.id != 0 && !quiet).then(|| {
+            ImageAction::Reply(format!("\x1b_Gi={};{message}\x1b\\", self.id).into_bytes())
+        })
+    }
+}
+
+/// Checks a kitty image transmission, without decoding it.
+fn kitty_image(control: &KittyControl, payload: Vec<u8>) -> Result<Arc<PendingImage>, String> {
+    if control.medium != b'd' {
+        return Err("ENOTSUPPORTED:only direct transmission is supported".into());
+    }
+    if control.compressed {
+        return Err("ENOTSUPPORTED:compression is not supported".into());
+    }
+    match control.format {
+        100 => {
+            let size = file_dimensions(&payload)
+                .ok_or_else(|| "EBADPNG:invalid image header".to_string())?;
+            Ok(PendingImage::new(PayloadFormat::File, payload, size))
+        }
+        24 | 32 => {
+            let (width, height) = (control.width, control.height);
+            if width == 0 || height == 0 {
+                return Err("EINVAL:invalid image dimensions".into());
+            }
+            let (format, channels) = if control.format == 24 {
+                (PayloadFormat::Rgb, 3)
+            } else {
+                (PayloadFormat::Rgba, 4)
+            };
+            if decoded_len(&payload) != width as usize * height as usize * channels {
+                return Err("ENODATA:insufficient image data".into());
+            }
+            Ok(PendingImage::new(format, payload, (width, height)))
+        }
+        format => Err(format!("EINVAL:unsupported format {format}")),
+    }
+}
+
+/// The length of the base64 payload once decoded.
+fn decoded_len(payload: &[u8]) -> usize {
+    let len = payload.len()
+        - payload
+            .iter()
+            .rev()
+            .take_while(|byte| **byte == b'=')
+            .count();
+    len / 4 * 3 + (len % 4).saturating_sub(1)
+}
+
+/// Reads the dimensions of the image file in the base64 payload from its header.
+fn file_dimensions(payload: &[u8]) -> Option<(u32, u32)> {
+    let header = if payload.len() > MAX_HEADER_PAYLOAD_LEN {
+        &payload[..MAX_HEADER_PAYLOAD_LEN]
+    } else {
+        payload
+    };
+    let header = BASE64.decode(header).ok()?;
+    image::ImageReader::new(Cursor::new(header))
+        .with_guessed_format()
+        .ok()?
+        .into_dimensions()
+        .ok()
+}
+
+fn decode_payload(
+    format: PayloadFormat,
+    payload: Vec<u8>,
+    width: u32,
+    height: u32,
+) -> Result<Arc<RenderImage>, String> {
+    let data = BASE64
+        .decode(payload)
+        .map_err(|_| "invalid base64 payload".to_string())?;
+    let image = match format {
+        PayloadFormat::File => image::load_from_memory(&data)
+            .map_err(|error| error.to_string())?
+            .into_rgba8(),
+        PayloadFormat::Rgb | PayloadFormat::Rgba => {
+            let pixels = if let PayloadFormat::Rgba = format {
+                data
+            } else {
+                data.chunks_exact(3)
+                    .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], u8::MAX])
+                    .collect()
+            };
+            image::RgbaImage::from_raw(width, height, pixels)
+                .ok_or_else(|| "invalid image dimensions".to_string())?
+        }
+    };
+    Ok(render_image(image))
+}
+
+fn iterm(arguments: &[u8], payload: Vec<u8>, metrics: CellMetrics) -> Option<ImageAction> {
+    let arguments = String::from_utf8_lossy(arguments);
+    let arguments = arguments
+        .split(';')
+        .filter_map(|argument| argument.split_once('='))
+        .collect::<HashMap<_, _>>();
+    // Anything else is a file download.
+    if arguments.get("inline").copied() != Some("1") {
+        return None;
+    }
+
+    let Some(size) = file_dimensions(&payload) else {
+        log::debug!("failed to read the header of an inline terminal image");
+        return None;
+    };
+    let dimension = |key: &str, cell_size: f32, total: usize| {
+        let value = arguments.get(key)?.trim();
+        if let Some(pixels) = value.strip_suffix("px") {
+            Some(pixels.parse::<f32>().ok()? / cell_size)
+        } else if let Some(percent) = value.strip_suffix('%') {
+            Some(percent.parse::<f32>().ok()? * total as f32 / 100.)
+        } else {
+            value.parse::<f32>().ok()
+        }
+    };
+    let (columns, rows) = fit(
+        size,
+        dimension("width", metrics.cell_width, metrics.columns),
+        dimension("height", metrics.cell_height, metrics.screen_lines),
+        arguments.get("preserveAspectRatio").copied() != Some("0"),
+        metrics,
+    );
+    Some(ImageAction::Place {
+        image: PendingImage::new(PayloadFormat::File, payload, size),
+        columns,
+        rows,
+        move_cursor: true,
+        kitty_id: None,
+    })
+}
+
+fn render_image(mut image: image::RgbaImage) -> Arc<RenderImage> {
+    // Convert from RGBA to BGRA.
+    for pixel in image.chunks_exact_mut(4) {
+        pixel.swap(0, 2);
+    }
+    Arc::new(RenderImage::new(vec![image::Frame::new(image)]))
+}
+
+/// Returns the number of cells an image of the given size in pixels covers, given
+/// the requested size in cells.
+fn fit(
+    (width, height): (u32, u32),
+    columns: Option<f32>,
+    rows: Option<f32>,
+    preserve_aspect_ratio: bool,
+    metrics: CellMetrics,
+) -> (usize, usize) {
+    let (image_width, image_height) = (width.max(1) as f32, height.max(1) as f32);
+    let max_width = columns.map(|columns| columns * metrics.cell_width);
+    let max_height = rows.map(|rows| rows * metrics.cell_height);
+    let (mut width, mut height) = match (max_width, max_height) {
+        (Some(max_width), Some(max_height)) if preserve_aspect_ratio => {
+            let scale = (max_width / image_width).min(max_height / image_height);
+            (image_width * scale, image_height * scale)
+        }
+        (Some(width), Some(height)) => (width, height),
+        (Some(width), None) => (width, image_height * width / image_width),
+        (None, Some(height)) => (image_width * height / image_height, height),
+        (None, None) => (image_width, image_height),
+    };
+    let grid_width = metrics.columns as f32 * metrics.cell_width;
+    if width > grid_width {
+        height *= grid_width / width;
+        width = grid_width;
+    }
+    (
+        ((width / metrics.cell_width).ceil() as usize).max(1),
+        ((height / metrics.cell_height).ceil() as usize).max(1),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(scanner: &mut ImageScanner, bytes: &[u8]) -> Vec<ImageSequence> {
+        bytes
+            .iter()
+            .filter_map(|byte| scanner.advance(*byte))
+            .collect()
+    }
+
+    fn rgba_payload(width: u32, height: u32) -> Vec<u8> {
+        BASE64
+            .encode(vec![u8::MAX; (width * height * 4) as usize])
+            .into_bytes()
+    }
+
+    #[test]
+    fn test_scan_image_sequences() {
+        let mut scanner = ImageScanner::default();
+        assert_eq!(
+            scan(
+                &mut scanner,
+                b"text\x1b_Ga=T,f=100;AAAA\x1b\\\x1b]1337;File=inline=1:BBBB\x07\x1b]0;title\x07"
+            ),
+            vec![
+                ImageSequence::Kitty {
+                    control: b"a=T,f=100".to_vec(),
+                    payload: b"AAAA".to_vec(),
+                },
+                ImageSequence::Iterm {
+                    arguments: b"inline=1".to_vec(),
+                    payload: b"BBBB".to_vec(),
+                },
+            ]
+        );
+
+        assert!(scan(&mut scanner, b"\x1b_Ga=d").is_empty());
+        assert_eq!(
+            scan(&mut scanner, b"\x1b\\"),
+            vec![ImageSequence::Kitty {
+                control: b"a=d".to_vec(),
+                payload: Vec::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_decode_kitty_images() {
+        let metrics = CellMetrics {
+            cell_width: 10.,
+            cell_height: 20.,
+            columns: 80,
+            screen_lines: 24,
+        };
+        let mut decoder = ImageDecoder::default();
+
+        // Chunked transmission, displayed right away.
+        let payload = rgba_payload(40, 30);
+        let (first, second) = payload.split_at(payload.len() / 8 * 4);
+        assert!(
+            decoder
+                .decode(
+                    ImageSequence::Kitty {
+                        control: b"a=T,f=32,s=40,v=30,i=7,q=1,m=1".to_vec(),
+                        payload: first.to_vec(),
+                    },
+                    metrics,
+                )
+                .is_empty()
+        );
+        let actions = decoder.decode(
+            ImageSequence::Kitty {
+                control: b"m=0".to_vec(),
+                payload: second.to_vec(),
+            },
+            metrics,
+        );
+        assert_eq!(actions.len(), 1);
+        let ImageAction::Place {
+            image,
+            columns,
+            rows,
+            ..
+        } = &actions[0]
+        else {
+            panic!("expected the image to be placed");
+        };
+        assert_eq!((*columns, *rows), (4, 2));
+        assert_eq!(actions[0].cursor_movement(), b"\n\x1b[4C");
+        // The image is only decoded when asked to.
+        let decoded = image.decode().expect("failed to decode the image");
+        assert_eq!(decoded.size(0).width.0, 40);
+
+        // Placing the transmitted image again, at an explicit size.
+        let actions = decoder.decode(
+            ImageSequence::Kitty {
+                control: b"a=p,i=7,c=8,r=8".to_vec(),
+                payload: Vec::new(),
+            },
+            metrics,
+        );
+        assert!(matches!(
+            actions.as_slice(),
+            [
+                ImageAction::Place {
+                    columns: 8,
+                    rows: 8,
+                    ..
+                },
+                ImageAction::Reply(_)
+            ]
+        ));
+
+        let actions = decoder.decode(
+            ImageSequence::Kitty {
+                control: b"a=T,i=8,t=f".to_vec(),
+                payload: Vec::new(),
+            },
+            metrics,
+        );
+        let [ImageAction::Reply(reply)] = actions.as_slice() else {
+            panic!("expected an error reply");
+        };
+        assert!(String::from_utf8_lossy(reply).starts_with("\x1b_Gi=8;ENOTSUPPORTED"));
+    }
+
+    #[test]
+    fn test_drop_too_large_kitty_transmissions() {
+        let metrics = CellMetrics::default();
+        let mut decoder = ImageDecoder::default();
+        let mut chunk = |control: &[u8], len: usize| {
+            decoder.decode(
+                ImageSequence::Kitty {
+                    control: control.to_vec(),
+                    payload: vec![b'A'; len],
+                },
+                metrics,
+            )
+        };
+
+        let half = MAX_IMAGE_SEQUENCE_LEN / 2 + 4;
+        assert!(chunk(b"a=T,f=100,i=3,m=1", half).is_empty());
+        assert!(chunk(b"m=1", half).is_empty());
+        // The rest of the transmission is ignored, and the program told once it ends.
+        assert!(chunk(b"m=1", 4).is_empty());
+        let actions = chunk(b"m=0", 4);
+        let [ImageAction::Reply(reply)] = actions.as_slice() else {
+            panic!("expected an error reply");
+        };
+        assert_eq!(reply, b"\x1b_Gi=3;EFBIG:image data is too large\x1b\\");
+        assert!(decoder.chunked.is_none());
+    }
+
+    #[test]
+    fn test_fit() {
+        let metrics = CellMetrics {
+            cell_width: 10.,
+            cell_height: 20.,
+            columns: 10,
+            screen_lines: 24,
+        };
+        let size = (400, 100);
+        // Scaled down to the width of the grid.
+        assert_eq!(fit(size, None, None, true, metrics), (10, 2));
+        assert_eq!(fit(size, Some(4.), None, true, metrics), (4, 1));
+        assert_eq!(fit(size, Some(4.), Some(4.), false, metrics), (4, 4));
+    }
+}
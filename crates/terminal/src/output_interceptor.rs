@@ -0,0 +1,232 @@
+//! Picks the escape sequences Alacritty doesn't handle out of the terminal output.
+//!
+//! Their effect depends on where the cursor was when the sequence was emitted, so
+//! the output is parsed in chunks ending at each such sequence, and its effect is
+//! recorded before parsing the rest. The lines scrolled off the screen are counted
+//! while parsing, for the effects to be positioned against them.
+
+use std::sync::Arc;
+
+use alacritty_terminal::{
+    Term,
+    event::{EventListener, WindowSize},
+    grid::{Dimensions, Scroll},
+    term::TermMode,
+    vte::ansi::{Processor, StdSyncHandler},
+};
+use parking_lot::Mutex;
+
+use crate::{
+    inline_images::{
+        CellMetrics, ImageAction, ImageDecoder, ImageDeletion, ImagePlacement, ImageScanner,
+        PendingImage,
+    },
+    shell_integration::{self, LinesScrolled, Osc133Scanner, ShellMark, ShellMarkKind},
+};
+
+/// The number of bytes parsed at once when counting the lines they scroll. Scrolling
+/// takes at least one byte per line, so the count is exact as long as the scrollback
+/// is longer than that.
+const MAX_COUNTED_PARSE_LEN: usize = 1024;
+
+/// A sequence whose effect depends on the terminal state at the point it was emitted.
+pub(crate) enum Intercepted {
+    Mark(ShellMarkKind),
+    Images(Vec<ImageAction>),
+}
+
+/// The effect of an intercepted sequence, to be applied by the [`crate::Terminal`].
+pub(crate) enum InterceptedOutput {
+    Mark(ShellMark),
+    /// An image to place once it's decoded.
+    Image(ImagePlacement<Arc<PendingImage>>),
+    DeleteImages(ImageDeletion),
+    Reply(Vec<u8>),
+}
+
+/// Output recorded on the PTY event loop thread, waiting to be applied to the [`crate::Terminal`].
+pub(crate) type InterceptedOutputQueue = Arc<Mutex<Vec<InterceptedOutput>>>;
+
+impl From<WindowSize> for CellMetrics {
+    fn from(window_size: WindowSize) -> Self {
+        Self {
+            cell_width: (window_size.cell_width as f32).max(1.),
+            cell_height: (window_size.cell_height as f32).max(1.),
+            columns: (window_size.num_cols as usize).max(1),
+            screen_lines: (window_size.num_lines as usize).max(1),
+        }
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct OutputInterceptor {
+    marks: Osc133Scanner,
+    images: ImageScanner,
+    image_decoder: ImageDecoder,
+    pub metrics: CellMetrics,
+    lines_scrolled: LinesScrolled,
+    /// The number of bytes parsed at once when counting the lines they scroll.
+    counted_parse_len: usize,
+    /// The display offset to restore on the primary screen once it's shown again,
+    /// if the alternate screen was entered while counting scrolled lines.
+    primary_display_offset: Option<usize>,
+}
+
+impl OutputInterceptor {
+    /// Creates an interceptor counting the lines scrolled into `lines_scrolled`, for
+    /// a terminal keeping `scrolling_history` lines of scrollback.
+    pub fn new(lines_scrolled: LinesScrolled, scrolling_history: usize) -> Self {
+        Self {
+            lines_scrolled,
+            // The viewport can't be scrolled up further than the scrollback goes.
+            counted_parse_len: scrolling_history
+                .saturating_sub(1)
+                .clamp(1, MAX_COUNTED_PARSE_LEN),
+            ..Self::default()
+        }
+    }
+
+    /// Parses `bytes` into `term`, collecting the effect of the intercepted sequences
+    /// into `output`.
+    pub fn advance<T: EventListener>(
+        &mut self,
+        processor: &mut Processor<StdSyncHandler>,
+        term: &mut Term<T>,
+        bytes: &[u8],
+        output: &mut Vec<InterceptedOutput>,
+    ) {
+        let mut rest = bytes;
+        while !rest.is_empty() {
+            let (len, intercepted) = self.scan(rest);
+            self.parse(processor, term, &rest[..len]);
+            if let Some(intercepted) = intercepted {
+                let injected = Self::resolve(intercepted, term, self.lines_scrolled.get(), output);
+                self.parse(processor, term, &injected);
+            }
+            rest = &rest[len..];
+        }
+    }
+
+    /// Parses `bytes` into `term`, counting the lines they scroll off the screen.
+    ///
+    /// Alacritty doesn't report scrolling, and the scrollback stops growing once it's
+    /// full. But while the viewport is scrolled up, Alacritty moves it up along with
+    /// the lines that scroll, so the viewport is scrolled up by a line while parsing
+    /// and restored afterwards.
+    fn parse<T: EventListener>(
+        &mut self,
+        processor: &mut Processor<StdSyncHandler>,
+        term: &mut Term<T>,
+        bytes: &[u8],
+    ) {
+        for bytes in bytes.chunks(self.counted_parse_len.max(1)) {
+            let alt_screen = term.mode().contains(TermMode::ALT_SCREEN);
+            let history_size = term.history_size();
+            let display_offset = term.grid().display_offset();
+            term.grid_mut()
+                .scroll_display(Scroll::Delta(1 - display_offset as i32));
+            let counting_offset = term.grid().display_offset();
+
+            processor.advance(term, bytes);
+
+            let history_growth = term.history_size().saturating_sub(history_size);
+            let restored_offset = |scrolled: usize| {
+                if display_offset == 0 {
+                    0
+                } else {
+                    display_offset + scrolled
+                }
+            };
+            let scrolled = if term.mode().contains(TermMode::ALT_SCREEN) == alt_screen {
+                let scrolled = term
+                    .grid()
+                    .display_offset()
+                    .saturating_sub(counting_offset)
+                    .max(history_growth);
+                let offset = restored_offset(scrolled).min(term.history_size());
+                Self::set_display_offset(term, offset);
+                scrolled
+            } else if alt_screen {
+                // The primary screen is shown again, and it didn't scroll in between.
+                let offset = self.primary_display_offset.take().unwrap_or(0);
+                Self::set_display_offset(term, offset.min(term.history_size()));
+                history_growth
+            } else {
+                // The primary screen can't be reached until it's shown again.
+                self.primary_display_offset = Some(restored_offset(history_growth));
+                history_growth
+            };
+            self.lines_scrolled.add(scrolled as isize);
+        }
+    }
+
+    fn set_display_offset<T>(term: &mut Term<T>, offset: usize) {
+        let delta = offset as i32 - term.grid().display_offset() as i32;
+        if delta != 0 {
+            term.grid_mut().scroll_display(Scroll::Delta(delta));
+        }
+    }
+
+    /// Returns the length of the prefix of `bytes` that ends with the first
+    /// intercepted sequence, along with that sequence.
+    fn scan(&mut self, bytes: &[u8]) -> (usize, Option<Intercepted>) {
+        for (ix, byte) in bytes.iter().enumerate() {
+            if let Some(kind) = self.marks.advance(*byte) {
+                return (ix + 1, Some(Intercepted::Mark(kind)));
+            }
+            if let Some(sequence) = self.images.advance(*byte) {
+                let actions = self.image_decoder.decode(sequence, self.metrics);
+                if !actions.is_empty() {
+                    return (ix + 1, Some(Intercepted::Images(actions)));
+                }
+            }
+        }
+        (bytes.len(), None)
+    }
+
+    /// Records the effect of an intercepted sequence once everything preceding it
+    /// was parsed, returning the bytes to parse in its place.
+    fn resolve<T>(
+        intercepted: Intercepted,
+        term: &Term<T>,
+        lines_scrolled: usize,
+        output: &mut Vec<InterceptedOutput>,
+    ) -> Vec<u8> {
+        match intercepted {
+            Intercepted::Mark(kind) => {
+                output.extend(
+                    ShellMark::at_cursor(kind, term, lines_scrolled).map(InterceptedOutput::Mark),
+                );
+                Vec::new()
+            }
+            Intercepted::Images(actions) => {
+                let mut injected = Vec::new();
+                for action in actions {
+                    injected.extend(action.cursor_movement());
+                    output.push(match action {
+                        ImageAction::Place {
+                            image,
+                            columns,
+                            rows,
+                            kitty_id,
+                            ..
+                        } => InterceptedOutput::Image(ImagePlacement {
+                            image,
+                            point: shell_integration::absolute_point(
+                                term.grid().cursor.point,
+                                lines_scrolled,
+                            ),
+                            columns,
+                            rows,
+                            alt_screen: term.mode().contains(TermMode::ALT_SCREEN),
+                            kitty_id,
+                        }),
+                        ImageAction::Delete(deletion) => InterceptedOutput::DeleteImages(deletion),
+                        ImageAction::Reply(reply) => InterceptedOutput::Reply(reply),
+                    });
+                }
+                injected
+            }
+        }
+    }
+}
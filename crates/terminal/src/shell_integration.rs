@@ -0,0 +1,481 @@
+//! Tracking of command boundaries reported by shells through semantic prompt
+//! marks (`OSC 133 ; A/B/C/D`).
+//!
+//! Alacritty ignores these sequences, so they are picked out of the output before
+//! it's parsed, and each mark is recorded at the cursor position it was emitted at.
+//!
+//! Mark positions are absolute line numbers: the number of lines scrolled off the
+//! top of the screen when the mark was emitted, plus the cursor's screen line. They
+//! stay valid after the scrollback is full, until the line itself is dropped from it.
+
+use std::{
+    collections::VecDeque,
+    ops::RangeInclusive,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use alacritty_terminal::{
+    Term,
+    grid::Dimensions,
+    index::{Column, Line, Point as AlacPoint},
+    term::TermMode,
+};
+
+/// The maximum number of commands remembered per terminal.
+const MAX_COMMAND_BLOCKS: usize = 1000;
+/// Longest parameter list accepted in a mark, anything longer is not a mark we understand.
+const MAX_MARK_PARAMS_LEN: usize = 256;
+const OSC_133_PREFIX: &[u8] = b"133;";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShellMarkKind {
+    /// `OSC 133 ; A`, the shell is about to draw the prompt.
+    PromptStart,
+    /// `OSC 133 ; B`, the prompt was drawn and the user is typing the command.
+    CommandStart,
+    /// `OSC 133 ; C`, the command was submitted and its output follows.
+    OutputStart,
+    /// `OSC 133 ; D [; exit_code]`, the command finished.
+    CommandFinished { exit_code: Option<i32> },
+}
+
+impl ShellMarkKind {
+    fn parse(params: &[u8]) -> Option<Self> {
+        let mut params = params.split(|byte| *byte == b';');
+        match params.next()? {
+            b"A" => Some(Self::PromptStart),
+            b"B" => Some(Self::CommandStart),
+            b"C" => Some(Self::OutputStart),
+            b"D" => Some(Self::CommandFinished {
+                exit_code: params
+                    .next()
+                    .and_then(|code| std::str::from_utf8(code).ok()?.trim().parse().ok()),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The number of lines scrolled off the top of the screen since the terminal started,
+/// which absolute line numbers are counted from. Unlike the size of the scrollback, it
+/// keeps growing once the scrollback is full and its oldest lines are dropped.
+///
+/// It's shared by the thread parsing the output and the [`crate::Terminal`], and only
+/// read or updated while the terminal is locked.
+#[derive(Clone, Default)]
+pub(crate) struct LinesScrolled(Arc<AtomicUsize>);
+
+impl LinesScrolled {
+    pub fn get(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Adds lines that scrolled off the screen, or removes lines that moved back onto
+    /// it when the terminal grew taller.
+    pub fn add(&self, lines: isize) {
+        let lines_scrolled = self.get().saturating_add_signed(lines);
+        self.0.store(lines_scrolled, Ordering::Relaxed);
+    }
+}
+
+/// A semantic prompt mark, positioned where the cursor was when the shell emitted it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShellMark {
+    pub kind: ShellMarkKind,
+    /// The line is counted from the top of the scrollback.
+    pub point: AlacPoint<usize>,
+    pub at: Instant,
+}
+
+impl ShellMark {
+    /// Creates a mark at the current cursor position, unless the alternate screen is
+    /// active, which doesn't have a scrollback and can't contain commands.
+    pub(crate) fn at_cursor<T>(
+        kind: ShellMarkKind,
+        term: &Term<T>,
+        lines_scrolled: usize,
+    ) -> Option<Self> {
+        if term.mode().contains(TermMode::ALT_SCREEN) {
+            return None;
+        }
+        Some(Self {
+            kind,
+            point: absolute_point(term.grid().cursor.point, lines_scrolled),
+            at: Instant::now(),
+        })
+    }
+}
+
+pub(crate) fn absolute_point(point: AlacPoint, lines_scrolled: usize) -> AlacPoint<usize> {
+    AlacPoint::new(
+        lines_scrolled.saturating_add_signed(point.line.0 as isize),
+        point.column,
+    )
+}
+
+/// Converts an absolute point into a grid point, returning `None` if the line has
+/// already been dropped from the scrollback.
+pub(crate) fn grid_point<T>(
+    point: AlacPoint<usize>,
+    term: &Term<T>,
+    lines_scrolled: usize,
+) -> Option<AlacPoint> {
+    let line = point.line as isize - lines_scrolled as isize;
+    let line = Line(i32::try_from(line).ok()?);
+    (line >= term.topmost_line() && line <= term.bottommost_line())
+        .then(|| AlacPoint::new(line, point.column.min(term.last_column())))
+}
+
+/// Converts the absolute range from `start` up to, but not including, `end` into
+/// an inclusive grid range.
+pub(crate) fn grid_range<T>(
+    start: AlacPoint<usize>,
+    end: AlacPoint<usize>,
+    term: &Term<T>,
+    lines_scrolled: usize,
+) -> Option<RangeInclusive<AlacPoint>> {
+    let start = grid_point(start, term, lines_scrolled)?;
+    let end = grid_point(end, term, lines_scrolled)?;
+    let end = if end.column.0 > 0 {
+        AlacPoint::new(end.line, Column(end.column.0 - 1))
+    } else {
+        AlacPoint::new(Line(end.line.0 - 1), term.last_column())
+    };
+    (end >= start).then_some(start..=end)
+}
+
+/// Returns the text from `start` up to, but not including, `end`.
+pub(crate) fn text_between<T>(
+    start: AlacPoint<usize>,
+    end: AlacPoint<usize>,
+    term: &Term<T>,
+    lines_scrolled: usize,
+) -> Option<String> {
+    let range = grid_range(start, end, term, lines_scrolled)?;
+    Some(
+        term.bounds_to_string(*range.start(), *range.end())
+            .trim_end()
+            .to_string(),
+    )
+}
+
+/// Incrementally recognizes `OSC 133` sequences in a byte stream, which may be split
+/// at arbitrary positions.
+#[derive(Default)]
+pub struct Osc133Scanner {
+    state: ScanState,
+    params: Vec<u8>,
+}
+
+#[derive(Default, Clone, Copy)]
+enum ScanState {
+    #[default]
+    Ground,
+    Escape,
+    OscPrefix(usize),
+    Params,
+    ParamsEscape,
+}
+
+impl Osc133Scanner {
+    /// Feeds a single byte, returning the mark that this byte completed, if any.
+    pub fn advance(&mut self, byte: u8) -> Option<ShellMarkKind> {
+        match self.state {
+            ScanState::Ground => {
+                if byte == 0x1b {
+                    self.state = ScanState::Escape;
+                }
+            }
+            ScanState::Escape => {
+                self.state = match byte {
+                    b']' => ScanState::OscPrefix(0),
+                    0x1b => ScanState::Escape,
+                    _ => ScanState::Ground,
+                };
+            }
+            ScanState::OscPrefix(matched) => {
+                self.state = if byte == OSC_133_PREFIX[matched] {
+                    if matched + 1 == OSC_133_PREFIX.len() {
+                        self.params.clear();
+                        ScanState::Params
+                    } else {
+                        ScanState::OscPrefix(matched + 1)
+                    }
+                } else if byte == 0x1b {
+                    ScanState::Escape
+                } else {
+                    ScanState::Ground
+                };
+            }
+            ScanState::Params => match byte {
+                0x07 => return self.finish(),
+                0x1b => self.state = ScanState::ParamsEscape,
+                _ if self.params.len() < MAX_MARK_PARAMS_LEN => self.params.push(byte),
+                _ => self.state = ScanState::Ground,
+            },
+            ScanState::ParamsEscape => {
+                if byte == b'\\' {
+                    return self.finish();
+                }
+                self.state = if byte == b']' {
+                    ScanState::OscPrefix(0)
+                } else {
+                    ScanState::Ground
+                };
+            }
+        }
+        None
+    }
+
+    fn finish(&mut self) -> Option<ShellMarkKind> {
+        self.state = ScanState::Ground;
+        ShellMarkKind::parse(&self.params)
+    }
+}
+
+/// A single command run at a shell prompt, delimited by semantic prompt marks.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommandBlock {
+    pub prompt_start: AlacPoint<usize>,
+    pub command_start: Option<AlacPoint<usize>>,
+    pub output_start: Option<AlacPoint<usize>>,
+    pub output_end: Option<AlacPoint<usize>>,
+    /// The command line as typed by the user, read from the screen when it was submitted.
+    pub command: Option<String>,
+    pub exit_code: Option<i32>,
+    pub started_at: Option<Instant>,
+    pub finished_at: Option<Instant>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandStatus {
+    /// The prompt is shown, but no command was submitted from it yet.
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    /// The command finished without reporting an exit code.
+    Finished,
+}
+
+impl CommandBlock {
+    fn new(prompt_start: AlacPoint<usize>) -> Self {
+        Self {
+            prompt_start,
+            command_start: None,
+            output_start: None,
+            output_end: None,
+            command: None,
+            exit_code: None,
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    pub fn status(&self) -> CommandStatus {
+        match (self.started_at, self.finished_at, self.exit_code) {
+            (None, _, _) => CommandStatus::Pending,
+            (Some(_), None, _) => CommandStatus::Running,
+            (Some(_), Some(_), Some(0)) => CommandStatus::Succeeded,
+            (Some(_), Some(_), Some(_)) => CommandStatus::Failed,
+            (Some(_), Some(_), None) => CommandStatus::Finished,
+        }
+    }
+
+    /// How long the command ran, or has been running so far.
+    pub fn duration(&self) -> Option<Duration> {
+        let started_at = self.started_at?;
+        Some(
+            self.finished_at
+                .unwrap_or_else(Instant::now)
+                .saturating_duration_since(started_at),
+        )
+    }
+}
+
+/// The commands run in a terminal, oldest first.
+#[derive(Default)]
+pub struct CommandBlocks {
+    blocks: VecDeque<CommandBlock>,
+}
+
+impl CommandBlocks {
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &CommandBlock> {
+        self.blocks.iter()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.blocks.clear();
+    }
+
+    /// Drops the commands whose prompt was dropped from the scrollback, for which
+    /// `is_in_scrollback` returns false.
+    pub(crate) fn remove_scrolled_out(
+        &mut self,
+        mut is_in_scrollback: impl FnMut(AlacPoint<usize>) -> bool,
+    ) {
+        while let Some(block) = self.blocks.front()
+            && !is_in_scrollback(block.prompt_start)
+        {
+            self.blocks.pop_front();
+        }
+    }
+
+    /// Where the command text of the latest block starts, if it is still being typed.
+    pub(crate) fn pending_command_start(&self) -> Option<AlacPoint<usize>> {
+        let block = self.blocks.back()?;
+        block.output_start.is_none().then_some(block.command_start?)
+    }
+
+    /// Updates the blocks with a new mark. `command` is the text between the
+    /// latest command start and an output start mark.
+    pub(crate) fn apply(&mut self, mark: ShellMark, command: Option<String>) {
+        match mark.kind {
+            ShellMarkKind::PromptStart => {
+                // Shells that don't report `D` only let us know a command finished by
+                // drawing the next prompt.
+                if let Some(block) = self.blocks.back_mut()
+                    && block.output_start.is_some()
+                    && block.output_end.is_none()
+                {
+                    block.output_end = Some(mark.point);
+                    block.finished_at = Some(mark.at);
+                }
+                if self.blocks.len() == MAX_COMMAND_BLOCKS {
+                    self.blocks.pop_front();
+                }
+                self.blocks.push_back(CommandBlock::new(mark.point));
+            }
+            ShellMarkKind::CommandStart => {
+                if let Some(block) = self.blocks.back_mut()
+                    && block.output_start.is_none()
+                {
+                    block.command_start = Some(mark.point);
+                }
+            }
+            ShellMarkKind::OutputStart => {
+                if let Some(block) = self.blocks.back_mut()
+                    && block.output_start.is_none()
+                {
+                    block.output_start = Some(mark.point);
+                    block.started_at = Some(mark.at);
+                    block.command = command.filter(|command| !command.is_empty());
+                }
+            }
+            ShellMarkKind::CommandFinished { exit_code } => {
+                if let Some(block) = self.blocks.back_mut()
+                    && block.output_start.is_some()
+                    && block.output_end.is_none()
+                {
+                    block.output_end = Some(mark.point);
+                    block.exit_code = exit_code;
+                    block.finished_at = Some(mark.at);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(scanner: &mut Osc133Scanner, bytes: &[u8]) -> Vec<ShellMarkKind> {
+        bytes
+            .iter()
+            .filter_map(|byte| scanner.advance(*byte))
+            .collect()
+    }
+
+    fn mark(kind: ShellMarkKind, line: usize) -> ShellMark {
+        ShellMark {
+            kind,
+            point: AlacPoint::new(line, Column(0)),
+            at: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn test_scan_marks() {
+        let mut scanner = Osc133Scanner::default();
+        assert_eq!(
+            scan(
+                &mut scanner,
+                b"\x1b]133;A\x07$ \x1b]133;B\x1b\\ls\r\n\x1b]133;C;cmdline=ls\x07out\x1b]133;D;2\x07"
+            ),
+            vec![
+                ShellMarkKind::PromptStart,
+                ShellMarkKind::CommandStart,
+                ShellMarkKind::OutputStart,
+                ShellMarkKind::CommandFinished { exit_code: Some(2) },
+            ]
+        );
+
+        // Sequences can be split across reads.
+        assert!(scan(&mut scanner, b"\x1b]13").is_empty());
+        assert!(scan(&mut scanner, b"3;D\x1b").is_empty());
+        assert_eq!(
+            scan(&mut scanner, b"\\"),
+            vec![ShellMarkKind::CommandFinished { exit_code: None }]
+        );
+
+        // Other OSC sequences and unknown marks are ignored.
+        assert!(
+            scan(
+                &mut scanner,
+                b"\x1b]0;title\x07\x1b]1337;A\x07\x1b]133;Z\x07"
+            )
+            .is_empty()
+        );
+        assert_eq!(
+            scan(&mut scanner, b"\x1b]0;\x1b]133;A\x07"),
+            vec![ShellMarkKind::PromptStart]
+        );
+    }
+
+    #[test]
+    fn test_command_blocks() {
+        let mut blocks = CommandBlocks::default();
+        blocks.apply(mark(ShellMarkKind::PromptStart, 0), None);
+        blocks.apply(mark(ShellMarkKind::CommandStart, 0), None);
+        assert_eq!(
+            blocks.pending_command_start(),
+            Some(AlacPoint::new(0, Column(0)))
+        );
+        blocks.apply(mark(ShellMarkKind::OutputStart, 1), Some("false".into()));
+        assert_eq!(blocks.pending_command_start(), None);
+        assert_eq!(
+            blocks.iter().last().unwrap().status(),
+            CommandStatus::Running
+        );
+        blocks.apply(
+            mark(ShellMarkKind::CommandFinished { exit_code: Some(1) }, 1),
+            None,
+        );
+
+        // A shell that doesn't report exit codes.
+        blocks.apply(mark(ShellMarkKind::PromptStart, 1), None);
+        blocks.apply(mark(ShellMarkKind::CommandStart, 1), None);
+        blocks.apply(mark(ShellMarkKind::OutputStart, 2), Some("ls".into()));
+        blocks.apply(mark(ShellMarkKind::PromptStart, 5), None);
+
+        let blocks = blocks.iter().collect::<Vec<_>>();
+        assert_eq!(blocks.len(), 3);
+        assert_eq!(blocks[0].command.as_deref(), Some("false"));
+        assert_eq!(blocks[0].status(), CommandStatus::Failed);
+        assert_eq!(blocks[0].output_end, Some(AlacPoint::new(1, Column(0))));
+        assert_eq!(blocks[1].command.as_deref(), Some("ls"));
+        assert_eq!(blocks[1].status(), CommandStatus::Finished);
+        assert_eq!(blocks[1].output_end, Some(AlacPoint::new(5, Column(0))));
+        assert_eq!(blocks[2].status(), CommandStatus::Pending);
+        assert!(blocks[2].duration().is_none());
+    }
+}
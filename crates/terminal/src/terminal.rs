@@ -2,14 +2,18 @@ pub mod mappings;
 
 pub use alacritty_terminal;
 
+mod event_loop;
+pub mod inline_images;
+mod output_interceptor;
 mod pty_info;
+pub mod shell_integration;
 mod terminal_hyperlinks;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
     Term,
     event::{Event as AlacTermEvent, EventListener, Notify, WindowSize},
-    event_loop::{EventLoop, Msg, Notifier},
+    event_loop::Msg,
     grid::{Dimensions, Grid, Row, Scroll as AlacScroll},
     index::{Boundary, Column, Direction as AlacDirection, Line, Point as AlacPoint},
     selection::{Selection, SelectionRange, SelectionType},
@@ -40,10 +44,16 @@ use mappings::mouse::{
 };
 
 use collections::{HashMap, VecDeque};
+use event_loop::{EventLoop, Notifier};
 use futures::StreamExt;
+use inline_images::{ImageDeletion, ImagePlacement};
+use output_interceptor::{InterceptedOutput, InterceptedOutputQueue, OutputInterceptor};
 use pty_info::{ProcessIdGetter, PtyProcessInfo};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
+use shell_integration::{
+    CommandBlock, CommandBlocks, CommandStatus, LinesScrolled, ShellMark, ShellMarkKind,
+};
 use smol::channel::{Receiver, Sender};
 use task::{HideStrategy, Shell, SpawnInTerminal};
 use terminal_hyperlinks::RegexSearches;
@@ -69,7 +79,7 @@ use thiserror::Error;
 use gpui::{
     App, AppContext as _, BackgroundExecutor, Bounds, ClipboardItem, Context, EventEmitter, Hsla,
     Keystroke, Modifiers, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, Pixels, Point,
-    Rgba, ScrollWheelEvent, Size, Task, TouchPhase, Window, actions, black, px,
+    RenderImage, Rgba, ScrollWheelEvent, Size, Task, TouchPhase, Window, actions, black, px,
 };
 
 use crate::mappings::{colors::to_alac_rgb, keys::to_esc_str};
@@ -107,9 +117,22 @@ actions!(
         ToggleViMode,
         /// Selects all text in the terminal.
         SelectAll,
+        /// Scrolls to the previous shell prompt.
+        ScrollToPreviousPrompt,
+        /// Scrolls to the next shell prompt.
+        ScrollToNextPrompt,
+        /// Selects the output of the current command.
+        SelectCommandOutput,
+        /// Copies the output of the current command to the clipboard.
+        CopyCommandOutput,
+        /// Runs the current command again.
+        RerunCommand,
     ]
 );
 
+/// The maximum number of inline images kept per terminal.
+const MAX_INLINE_IMAGES: usize = 256;
+
 const DEBUG_TERMINAL_WIDTH: Pixels = px(500.);
 const DEBUG_TERMINAL_HEIGHT: Pixels = px(30.);
 const DEBUG_CELL_WIDTH: Pixels = px(5.);
@@ -379,6 +402,7 @@ impl TerminalBuilder {
         }
 
         let term = Arc::new(FairMutex::new(term));
+        let lines_scrolled = LinesScrolled::default();
 
         let terminal = Terminal {
             task: None,
@@ -421,6 +445,14 @@ impl TerminalBuilder {
             event_loop_task: Task::ready(Ok(())),
             background_executor: background_executor.clone(),
             path_style,
+            intercepted_output: InterceptedOutputQueue::default(),
+            output_interceptor: OutputInterceptor::new(lines_scrolled.clone(), scrolling_history),
+            lines_scrolled,
+            command_blocks: CommandBlocks::default(),
+            inline_images: VecDeque::new(),
+            dropped_images: Vec::new(),
+            decoding_images: HashMap::default(),
+            next_decoding_image_id: 0,
             #[cfg(any(test, feature = "test-support"))]
             input_log: Vec::new(),
         };
@@ -594,13 +626,17 @@ impl TerminalBuilder {
 
             let pty_info = PtyProcessInfo::new(&pty);
 
+            let intercepted_output = InterceptedOutputQueue::default();
+            let lines_scrolled = LinesScrolled::default();
+
             //And connect them together
             let event_loop = EventLoop::new(
                 term.clone(),
                 ZedListener(events_tx),
                 pty,
                 pty_options.drain_on_exit,
-                false,
+                OutputInterceptor::new(lines_scrolled.clone(), scrolling_history),
+                intercepted_output.clone(),
             )
             .context("failed to create event loop")?;
 
@@ -655,6 +691,14 @@ impl TerminalBuilder {
                 event_loop_task: Task::ready(Ok(())),
                 background_executor,
                 path_style,
+                intercepted_output,
+                output_interceptor: OutputInterceptor::default(),
+                lines_scrolled,
+                command_blocks: CommandBlocks::default(),
+                inline_images: VecDeque::new(),
+                dropped_images: Vec::new(),
+                decoding_images: HashMap::default(),
+                next_decoding_image_id: 0,
                 #[cfg(any(test, feature = "test-support"))]
                 input_log: Vec::new(),
             };
@@ -882,6 +926,19 @@ pub struct Terminal {
     event_loop_task: Task<Result<(), anyhow::Error>>,
     background_executor: BackgroundExecutor,
     path_style: PathStyle,
+    intercepted_output: InterceptedOutputQueue,
+    /// Only used by display-only terminals, the PTY output is intercepted on the event loop thread.
+    output_interceptor: OutputInterceptor,
+    /// What the absolute positions of the shell marks and images are counted from.
+    lines_scrolled: LinesScrolled,
+    command_blocks: CommandBlocks,
+    inline_images: VecDeque<ImagePlacement>,
+    /// Images that are no longer displayed, to be removed from the sprite atlas.
+    dropped_images: Vec<Arc<RenderImage>>,
+    /// The kitty ids of the images being decoded in the background, by decoding id.
+    /// An image is only placed once it's decoded if it wasn't deleted in the meantime.
+    decoding_images: HashMap<usize, Option<u32>>,
+    next_decoding_image_id: usize,
     #[cfg(any(test, feature = "test-support"))]
     input_log: Vec<Vec<u8>>,
 }
@@ -987,6 +1044,7 @@ impl Terminal {
                 //NOOP, Handled in render
             }
             AlacTermEvent::Wakeup => {
+                self.apply_intercepted_output(cx);
                 cx.emit(Event::Wakeup);
 
                 if let TerminalType::Pty { info, .. } = &self.terminal_type {
@@ -1036,7 +1094,12 @@ impl Terminal {
                     pty_tx.0.send(Msg::Resize(new_bounds.into())).ok();
                 }
 
+                let history_size = term.history_size();
                 term.resize(new_bounds);
+                // Lines pushed into the scrollback by shrinking the terminal, or pulled
+                // back out of it by growing it, shift the screen like scrolling does.
+                self.lines_scrolled
+                    .add(term.history_size() as isize - history_size as isize);
                 // If there are matches we need to emit a wake up event to
                 // invalidate the matches and recalculate their locations
                 // in the new terminal layout
@@ -1046,6 +1109,9 @@ impl Terminal {
             }
             InternalEvent::Clear => {
                 trace!("Clearing");
+                self.command_blocks.clear();
+                self.retain_inline_images(|_| false);
+                self.decoding_images.clear();
                 // Clear back buffer
                 term.clear_screen(ClearMode::Saved);
 
@@ -1322,12 +1388,343 @@ impl Terminal {
             alacritty_terminal::vte::ansi::StdSyncHandler,
         >::new();
         {
-            let mut term = self.term.lock();
-            processor.advance(&mut *term, &converted);
+            let term = self.term.clone();
+            let mut term = term.lock();
+            self.output_interceptor.metrics =
+                WindowSize::from(self.last_content.terminal_bounds).into();
+            let mut output = Vec::new();
+            self.output_interceptor
+                .advance(&mut processor, &mut term, &converted, &mut output);
+            for output in output {
+                self.apply_output(output, &term, cx);
+            }
+            self.remove_scrolled_out_marks(&term);
         }
         cx.emit(Event::Wakeup);
     }
 
+    fn apply_intercepted_output(&mut self, cx: &mut Context<Self>) {
+        let output = std::mem::take(&mut *self.intercepted_output.lock());
+        if output.is_empty() && self.command_blocks.is_empty() && self.inline_images.is_empty() {
+            return;
+        }
+        let term = self.term.clone();
+        let term = term.lock();
+        for output in output {
+            self.apply_output(output, &term, cx);
+        }
+        self.remove_scrolled_out_marks(&term);
+    }
+
+    /// Drops the commands and images that scrolled out of the scrollback, which are
+    /// gone for good.
+    fn remove_scrolled_out_marks(&mut self, term: &Term<ZedListener>) {
+        let lines_scrolled = self.lines_scrolled.get();
+        let is_in_scrollback =
+            |point| shell_integration::grid_point(point, term, lines_scrolled).is_some();
+        self.command_blocks.remove_scrolled_out(is_in_scrollback);
+        if self
+            .inline_images
+            .iter()
+            .any(|placement| !is_in_scrollback(placement.point))
+        {
+            self.retain_inline_images(|placement| is_in_scrollback(placement.point));
+        }
+    }
+
+    fn apply_output(
+        &mut self,
+        output: InterceptedOutput,
+        term: &Term<ZedListener>,
+        cx: &mut Context<Self>,
+    ) {
+        match output {
+            InterceptedOutput::Mark(mark) => Self::apply_shell_mark(
+                &mut self.command_blocks,
+                mark,
+                term,
+                self.lines_scrolled.get(),
+            ),
+            InterceptedOutput::Image(placement) => {
+                let id = self.next_decoding_image_id;
+                self.next_decoding_image_id += 1;
+                self.decoding_images.insert(id, placement.kitty_id);
+                let image = placement.image.clone();
+                let decode = self
+                    .background_executor
+                    .spawn(async move { image.decode() });
+                cx.spawn(async move |this, cx| {
+                    let image = decode.await;
+                    this.update(cx, |this, cx| {
+                        if this.decoding_images.remove(&id).is_some()
+                            && let Some(image) = image
+                        {
+                            this.place_image(placement.with_image(image));
+                            cx.emit(Event::Wakeup);
+                        }
+                    })
+                })
+                .detach();
+            }
+            InterceptedOutput::DeleteImages(deletion) => {
+                let is_deleted = |kitty_id: Option<u32>| match deletion {
+                    ImageDeletion::All => true,
+                    ImageDeletion::Kitty(id) => kitty_id == Some(id),
+                };
+                self.decoding_images
+                    .retain(|_, kitty_id| !is_deleted(*kitty_id));
+                self.retain_inline_images(|placement| !is_deleted(placement.kitty_id));
+            }
+            InterceptedOutput::Reply(reply) => self.write_to_pty(reply),
+        }
+    }
+
+    fn place_image(&mut self, placement: ImagePlacement) {
+        if self.inline_images.len() == MAX_INLINE_IMAGES
+            && let Some(oldest) = self.inline_images.pop_front()
+        {
+            self.release_image(oldest.image);
+        }
+        self.inline_images.push_back(placement);
+    }
+
+    fn retain_inline_images(&mut self, mut keep: impl FnMut(&ImagePlacement) -> bool) {
+        let mut removed = Vec::new();
+        self.inline_images.retain(|placement| {
+            let kept = keep(placement);
+            if !kept {
+                removed.push(placement.image.clone());
+            }
+            kept
+        });
+        for image in removed {
+            self.release_image(image);
+        }
+    }
+
+    fn release_image(&mut self, image: Arc<RenderImage>) {
+        let still_used = self
+            .inline_images
+            .iter()
+            .map(|placement| &placement.image)
+            .chain(&self.dropped_images)
+            .any(|other| Arc::ptr_eq(other, &image));
+        if !still_used {
+            self.dropped_images.push(image);
+        }
+    }
+
+    /// The images drawn in the terminal grid, along with the viewport line their top
+    /// row is at, which is negative for images that start above the viewport.
+    pub fn visible_inline_images(&self) -> Vec<(i32, &ImagePlacement)> {
+        let term = self.term.lock_unfair();
+        let alt_screen = term.mode().contains(TermMode::ALT_SCREEN);
+        let lines_scrolled = self.lines_scrolled.get();
+        let display_offset = term.grid().display_offset() as i32;
+        let screen_lines = term.screen_lines() as i32;
+        self.inline_images
+            .iter()
+            .filter(|placement| placement.alt_screen == alt_screen)
+            .filter_map(|placement| {
+                let point = shell_integration::grid_point(placement.point, &term, lines_scrolled)?;
+                let line = point.line.0 + display_offset;
+                (line < screen_lines && line + placement.rows as i32 > 0)
+                    .then_some((line, placement))
+            })
+            .collect()
+    }
+
+    /// Takes the images that aren't displayed anymore, so that they can be freed.
+    pub fn take_dropped_images(&mut self) -> Vec<Arc<RenderImage>> {
+        std::mem::take(&mut self.dropped_images)
+    }
+
+    fn apply_shell_mark(
+        blocks: &mut CommandBlocks,
+        mark: ShellMark,
+        term: &Term<ZedListener>,
+        lines_scrolled: usize,
+    ) {
+        let command = match mark.kind {
+            ShellMarkKind::OutputStart => blocks.pending_command_start().and_then(|start| {
+                shell_integration::text_between(start, mark.point, term, lines_scrolled)
+            }),
+            _ => None,
+        };
+        blocks.apply(mark, command);
+    }
+
+    /// The commands run in this terminal, as reported by the shell's semantic prompt
+    /// marks. Empty if the shell doesn't emit them.
+    pub fn command_blocks(&self) -> &CommandBlocks {
+        &self.command_blocks
+    }
+
+    /// The commands whose prompt is visible in the viewport, along with the viewport
+    /// line the prompt starts at.
+    pub fn visible_command_blocks(&self) -> Vec<(usize, &CommandBlock)> {
+        let term = self.term.lock_unfair();
+        let lines_scrolled = self.lines_scrolled.get();
+        let display_offset = term.grid().display_offset() as i32;
+        let screen_lines = term.screen_lines() as i32;
+        self.command_blocks
+            .iter()
+            .filter_map(|block| {
+                let point =
+                    shell_integration::grid_point(block.prompt_start, &term, lines_scrolled)?;
+                let line = point.line.0 + display_offset;
+                (0..screen_lines)
+                    .contains(&line)
+                    .then_some((line as usize, block))
+            })
+            .collect()
+    }
+
+    /// The text printed by the command in `block`, so far if it's still running.
+    /// `None` if part of it was already dropped from the scrollback.
+    pub fn command_output(&self, block: &CommandBlock) -> Option<String> {
+        let term = self.term.lock_unfair();
+        let lines_scrolled = self.lines_scrolled.get();
+        let (start, end) = Self::command_output_bounds(block, &term, lines_scrolled)?;
+        shell_integration::text_between(start, end, &term, lines_scrolled)
+    }
+
+    /// The output of the most recently submitted command. `None` if part of it was
+    /// already dropped from the scrollback.
+    pub fn last_command_output(&self) -> Option<String> {
+        let block = self
+            .command_blocks
+            .iter()
+            .rev()
+            .find(|block| block.output_start.is_some())?;
+        self.command_output(block)
+    }
+
+    fn command_output_bounds(
+        block: &CommandBlock,
+        term: &Term<ZedListener>,
+        lines_scrolled: usize,
+    ) -> Option<(AlacPoint<usize>, AlacPoint<usize>)> {
+        let start = block.output_start?;
+        let end = block.output_end.unwrap_or_else(|| {
+            shell_integration::absolute_point(term.grid().cursor.point, lines_scrolled)
+        });
+        Some((start, end))
+    }
+
+    /// The command shown at the top of the viewport, or the latest one when scrolled
+    /// to the bottom.
+    fn current_command_block(&self, term: &Term<ZedListener>) -> Option<&CommandBlock> {
+        let mut blocks = self
+            .command_blocks
+            .iter()
+            .rev()
+            .filter(|block| block.output_start.is_some());
+        let display_offset = term.grid().display_offset();
+        if display_offset == 0 {
+            return blocks.next();
+        }
+        let viewport_top = Line(-(display_offset as i32));
+        let lines_scrolled = self.lines_scrolled.get();
+        blocks.find(|block| {
+            shell_integration::grid_point(block.prompt_start, term, lines_scrolled)
+                .is_some_and(|point| point.line <= viewport_top)
+        })
+    }
+
+    pub fn scroll_to_previous_prompt(&mut self) {
+        let term = self.term.lock();
+        let lines_scrolled = self.lines_scrolled.get();
+        let viewport_top = -(term.grid().display_offset() as i32);
+        let target = self
+            .command_blocks
+            .iter()
+            .rev()
+            .filter_map(|block| {
+                shell_integration::grid_point(block.prompt_start, &term, lines_scrolled)
+            })
+            .find(|point| point.line.0 < viewport_top);
+        drop(term);
+        if let Some(target) = target {
+            self.events
+                .push_back(InternalEvent::Scroll(AlacScroll::Delta(
+                    viewport_top - target.line.0,
+                )));
+        }
+    }
+
+    pub fn scroll_to_next_prompt(&mut self) {
+        let term = self.term.lock();
+        let lines_scrolled = self.lines_scrolled.get();
+        let viewport_top = -(term.grid().display_offset() as i32);
+        let target = self
+            .command_blocks
+            .iter()
+            .filter_map(|block| {
+                shell_integration::grid_point(block.prompt_start, &term, lines_scrolled)
+            })
+            .find(|point| point.line.0 > viewport_top);
+        drop(term);
+        match target {
+            Some(target) if target.line.0 < 0 => {
+                self.events
+                    .push_back(InternalEvent::Scroll(AlacScroll::Delta(
+                        viewport_top - target.line.0,
+                    )));
+            }
+            _ => self.scroll_to_bottom(),
+        }
+    }
+
+    pub fn select_command_output(&mut self) {
+        let term = self.term.lock();
+        let lines_scrolled = self.lines_scrolled.get();
+        let range = self
+            .current_command_block(&term)
+            .and_then(|block| Self::command_output_bounds(block, &term, lines_scrolled))
+            .and_then(|(start, end)| {
+                shell_integration::grid_range(start, end, &term, lines_scrolled)
+            });
+        drop(term);
+        if let Some(range) = range {
+            self.set_selection(Some((make_selection(&range), *range.end())));
+        }
+    }
+
+    pub fn copy_command_output(&mut self, cx: &mut Context<Self>) {
+        let term = self.term.lock();
+        let lines_scrolled = self.lines_scrolled.get();
+        let output = self.current_command_block(&term).and_then(|block| {
+            let (start, end) = Self::command_output_bounds(block, &term, lines_scrolled)?;
+            shell_integration::text_between(start, end, &term, lines_scrolled)
+        });
+        drop(term);
+        if let Some(output) = output {
+            cx.write_to_clipboard(ClipboardItem::new_string(output));
+        }
+    }
+
+    /// Types the current command at the prompt again and submits it. Does nothing
+    /// while a command is running.
+    pub fn rerun_command(&mut self) {
+        if self
+            .command_blocks
+            .iter()
+            .last()
+            .is_none_or(|block| block.status() == CommandStatus::Running)
+        {
+            return;
+        }
+        let term = self.term.lock();
+        let command = self
+            .current_command_block(&term)
+            .and_then(|block| block.command.clone());
+        drop(term);
+        if let Some(command) = command {
+            self.input(format!("{command}\r").into_bytes());
+        }
+    }
+
     pub fn total_lines(&self) -> usize {
         self.term.lock_unfair().total_lines()
     }
@@ -3164,6 +3561,146 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_write_output_tracks_command_blocks(cx: &mut TestAppContext) {
+        let terminal = cx.new(|cx| {
+            TerminalBuilder::new_display_only(
+                CursorShape::default(),
+                AlternateScroll::On,
+                None,
+                0,
+                cx.background_executor(),
+                PathStyle::local(),
+            )
+            .unwrap()
+            .subscribe(cx)
+        });
+
+        terminal.update(cx, |terminal, cx| {
+            terminal.write_output(b"\x1b]133;A\x07$ \x1b]133;B\x07echo hi\n\x1b]1", cx);
+            terminal.write_output(b"33;C\x07hi\n\x1b]133;D;0\x07", cx);
+            terminal.write_output(b"\x1b]133;A\x07$ \x1b]133;B\x07", cx);
+        });
+
+        terminal.update(cx, |terminal, _| {
+            let blocks = terminal.command_blocks().iter().collect::<Vec<_>>();
+            assert_eq!(blocks.len(), 2);
+            assert_eq!(blocks[0].command.as_deref(), Some("echo hi"));
+            assert_eq!(blocks[0].status(), CommandStatus::Succeeded);
+            assert_eq!(blocks[1].status(), CommandStatus::Pending);
+            assert_eq!(terminal.last_command_output().as_deref(), Some("hi"));
+        });
+    }
+
+    /// Marks stay on their line once the scrollback is full and its oldest lines are
+    /// dropped, and are dropped along with their line.
+    #[gpui::test]
+    async fn test_command_blocks_with_full_scrollback(cx: &mut TestAppContext) {
+        let terminal = cx.new(|cx| {
+            TerminalBuilder::new_display_only(
+                CursorShape::default(),
+                AlternateScroll::On,
+                Some(10),
+                0,
+                cx.background_executor(),
+                PathStyle::local(),
+            )
+            .unwrap()
+            .subscribe(cx)
+        });
+        let filler = |lines: usize| {
+            (0..lines)
+                .map(|ix| format!("filler {ix}\n"))
+                .collect::<String>()
+                .into_bytes()
+        };
+
+        terminal.update(cx, |terminal, cx| {
+            terminal.write_output(&filler(30), cx);
+            terminal.write_output(
+                b"\x1b]133;A\x07$ \x1b]133;B\x07echo hi\n\x1b]133;C\x07hi\n\x1b]133;D;0\x07",
+                cx,
+            );
+            terminal.write_output(&filler(5), cx);
+        });
+        terminal.update(cx, |terminal, _| {
+            assert_eq!(terminal.command_blocks().iter().count(), 1);
+            assert_eq!(terminal.last_command_output().as_deref(), Some("hi"));
+        });
+
+        terminal.update(cx, |terminal, cx| terminal.write_output(&filler(30), cx));
+        terminal.update(cx, |terminal, _| {
+            assert!(terminal.command_blocks().is_empty());
+            assert_eq!(terminal.last_command_output(), None);
+        });
+    }
+
+    /// Marks are read from the PTY on the event loop thread, and applied to the
+    /// terminal where the cursor was when they were emitted.
+    #[cfg(unix)]
+    #[gpui::test]
+    async fn test_pty_output_tracks_command_blocks(cx: &mut TestAppContext) {
+        cx.executor().allow_parking();
+
+        let (terminal, completion_rx) = build_test_terminal(
+            cx,
+            "printf",
+            &[r"'\033]133;A\007$ \033]133;B\007echo hi\n\033]133;C\007hi\n\033]133;D;0\007'"],
+        )
+        .await;
+        completion_rx.recv().await.unwrap();
+
+        terminal.update(cx, |terminal, cx| {
+            terminal.apply_intercepted_output(cx);
+            let blocks = terminal.command_blocks().iter().collect::<Vec<_>>();
+            assert_eq!(blocks.len(), 1);
+            assert_eq!(blocks[0].command.as_deref(), Some("echo hi"));
+            assert_eq!(blocks[0].status(), CommandStatus::Succeeded);
+            assert_eq!(terminal.last_command_output().as_deref(), Some("hi"));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_write_output_places_inline_images(cx: &mut TestAppContext) {
+        use base64::Engine as _;
+
+        let terminal = cx.new(|cx| {
+            TerminalBuilder::new_display_only(
+                CursorShape::default(),
+                AlternateScroll::On,
+                None,
+                0,
+                cx.background_executor(),
+                PathStyle::local(),
+            )
+            .unwrap()
+            .subscribe(cx)
+        });
+
+        let pixels = base64::engine::general_purpose::STANDARD.encode([0u8; 10 * 10 * 4]);
+        terminal.update(cx, |terminal, cx| {
+            terminal.write_output(
+                format!("ab\x1b_Ga=T,f=32,s=10,v=10,c=2,r=3;{pixels}\x1b\\cd").as_bytes(),
+                cx,
+            );
+        });
+        // Images are placed once they're decoded in the background.
+        cx.run_until_parked();
+
+        terminal.update(cx, |terminal, _| {
+            let images = terminal.visible_inline_images();
+            assert_eq!(images.len(), 1);
+            let (line, placement) = images[0];
+            assert_eq!(line, 0);
+            assert_eq!(placement.point.column, Column(2));
+            assert_eq!((placement.columns, placement.rows), (2, 3));
+
+            // The text following the image starts after its bottom right corner.
+            let term = terminal.term.lock();
+            assert_eq!(term.grid().cursor.point, AlacPoint::new(Line(2), Column(6)));
+        });
+    }
+
     #[gpui::test]
     async fn test_write_output_preserves_bare_cr(cx: &mut TestAppContext) {
         let terminal = cx.new(|cx| {
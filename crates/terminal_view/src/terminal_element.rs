@@ -1,12 +1,12 @@
 use editor::{CursorLayout, EditorSettings, HighlightedRange, HighlightedRangeLine};
 use gpui::{
-    AbsoluteLength, AnyElement, App, AvailableSpace, Bounds, ContentMask, Context, DispatchPhase,
-    Element, ElementId, Entity, FocusHandle, Font, FontFeatures, FontStyle, FontWeight,
-    GlobalElementId, HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement, Interactivity,
-    IntoElement, LayoutId, Length, ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels,
-    Point, StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun, TextStyle,
-    UTF16Selection, UnderlineStyle, WeakEntity, WhiteSpace, Window, div, fill, point, px, relative,
-    size,
+    AbsoluteLength, AnyElement, App, AvailableSpace, Bounds, ContentMask, Context, Corners,
+    DispatchPhase, Element, ElementId, Entity, FocusHandle, Font, FontFeatures, FontStyle,
+    FontWeight, GlobalElementId, HighlightStyle, Hitbox, Hsla, InputHandler, InteractiveElement,
+    Interactivity, IntoElement, LayoutId, Length, ModifiersChangedEvent, MouseButton,
+    MouseMoveEvent, Pixels, Point, RenderImage, StatefulInteractiveElement, StrikethroughStyle,
+    Styled, TextRun, TextStyle, UTF16Selection, UnderlineStyle, WeakEntity, WhiteSpace, Window,
+    div, fill, point, px, relative, size,
 };
 use itertools::Itertools;
 use language::CursorShape;
@@ -23,6 +23,7 @@ use terminal::{
             CursorShape as AlacCursorShape, NamedColor,
         },
     },
+    shell_integration::CommandStatus,
     terminal_settings::TerminalSettings,
 };
 use theme::{ActiveTheme, Theme};
@@ -33,7 +34,7 @@ use util::ResultExt;
 use workspace::Workspace;
 
 use std::mem;
-use std::{fmt::Debug, ops::RangeInclusive, rc::Rc};
+use std::{fmt::Debug, ops::RangeInclusive, rc::Rc, sync::Arc};
 
 use crate::{BlockContext, BlockProperties, ContentMode, TerminalMode, TerminalView};
 
@@ -51,11 +52,23 @@ pub struct LayoutState {
     display_offset: usize,
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
+    /// Viewport lines of the visible shell prompts, colored by the status of their command.
+    command_markers: Vec<(usize, Hsla)>,
+    inline_images: Vec<LayoutImage>,
     block_below_cursor_element: Option<AnyElement>,
     base_text_style: TextStyle,
     content_mode: ContentMode,
 }
 
+/// An inline image, positioned in viewport cells.
+struct LayoutImage {
+    line: i32,
+    column: usize,
+    columns: usize,
+    rows: usize,
+    image: Arc<RenderImage>,
+}
+
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
 #[derive(Copy, Clone)]
 struct DisplayCursor {
@@ -1177,6 +1190,44 @@ impl Element for TerminalElement {
                     })
                 };
 
+                let command_markers = self
+                    .terminal
+                    .read(cx)
+                    .visible_command_blocks()
+                    .into_iter()
+                    .map(|(line, block)| {
+                        let color = match block.status() {
+                            CommandStatus::Succeeded => theme.status().success,
+                            CommandStatus::Failed => theme.status().error,
+                            CommandStatus::Running => theme.status().info,
+                            CommandStatus::Pending | CommandStatus::Finished => {
+                                theme.colors().text_muted
+                            }
+                        };
+                        (line, color)
+                    })
+                    .collect();
+
+                let inline_images = self
+                    .terminal
+                    .read(cx)
+                    .visible_inline_images()
+                    .into_iter()
+                    .map(|(line, placement)| LayoutImage {
+                        line,
+                        column: placement.point.column.0,
+                        columns: placement.columns,
+                        rows: placement.rows,
+                        image: placement.image.clone(),
+                    })
+                    .collect();
+                for image in self
+                    .terminal
+                    .update(cx, |terminal, _| terminal.take_dropped_images())
+                {
+                    window.drop_image(image).log_err();
+                }
+
                 let block_below_cursor_element = if let Some(block) = &self.block_below_cursor {
                     let terminal = self.terminal.read(cx);
                     if terminal.last_content.display_offset == 0 {
@@ -1222,6 +1273,8 @@ impl Element for TerminalElement {
                     display_offset,
                     hyperlink_tooltip,
                     gutter,
+                    command_markers,
+                    inline_images,
                     block_below_cursor_element,
                     base_text_style: text_style,
                     content_mode,
@@ -1305,6 +1358,40 @@ impl Element for TerminalElement {
                         rect.paint(origin, &layout.dimensions, window);
                     }
 
+                    for (line, color) in &layout.command_markers {
+                        let marker_bounds = Bounds::new(
+                            point(
+                                bounds.origin.x + layout.gutter * 0.25,
+                                origin.y + layout.dimensions.line_height * *line as f32,
+                            ),
+                            size(layout.gutter * 0.25, layout.dimensions.line_height),
+                        );
+                        window.paint_quad(fill(marker_bounds, *color));
+                    }
+
+                    for image in &layout.inline_images {
+                        let image_bounds = Bounds::new(
+                            origin
+                                + point(
+                                    layout.dimensions.cell_width * image.column as f32,
+                                    layout.dimensions.line_height * image.line as f32,
+                                ),
+                            size(
+                                layout.dimensions.cell_width * image.columns as f32,
+                                layout.dimensions.line_height * image.rows as f32,
+                            ),
+                        );
+                        window
+                            .paint_image(
+                                image_bounds,
+                                Corners::default(),
+                                image.image.clone(),
+                                0,
+                                false,
+                            )
+                            .log_err();
+                    }
+
                     for (relative_highlighted_range, color) in &layout.relative_highlighted_ranges {
                         if let Some((start_y, highlighted_range_lines)) =
                             to_highlighted_range_lines(relative_highlighted_range, layout, origin)
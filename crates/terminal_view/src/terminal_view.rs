@@ -32,8 +32,9 @@ use std::{
 };
 use task::TaskId;
 use terminal::{
-    Clear, Copy, Event, HoveredWord, MaybeNavigationTarget, Paste, ScrollLineDown, ScrollLineUp,
-    ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToTop, ShowCharacterPalette, TaskState,
+    Clear, Copy, CopyCommandOutput, Event, HoveredWord, MaybeNavigationTarget, Paste, RerunCommand,
+    ScrollLineDown, ScrollLineUp, ScrollPageDown, ScrollPageUp, ScrollToBottom, ScrollToNextPrompt,
+    ScrollToPreviousPrompt, ScrollToTop, SelectCommandOutput, ShowCharacterPalette, TaskState,
     TaskStatus, Terminal, TerminalBounds, ToggleViMode,
     alacritty_terminal::{
         index::Point as AlacPoint,
@@ -504,6 +505,7 @@ impl TerminalView {
             .selection_text
             .as_ref()
             .is_some_and(|text| !text.is_empty());
+        let has_command_blocks = !self.terminal.read(cx).command_blocks().is_empty();
         let context_menu = ContextMenu::build(window, cx, |menu, _, _| {
             menu.context(self.focus_handle.clone())
                 .action("New Terminal", Box::new(NewTerminal::default()))
@@ -512,6 +514,12 @@ impl TerminalView {
                 .action("Paste", Box::new(Paste))
                 .action("Select All", Box::new(SelectAll))
                 .action("Clear", Box::new(Clear))
+                .when(has_command_blocks, |menu| {
+                    menu.separator()
+                        .action("Copy Command Output", Box::new(CopyCommandOutput))
+                        .action("Select Command Output", Box::new(SelectCommandOutput))
+                        .action("Rerun Command", Box::new(RerunCommand))
+                })
                 .when(assistant_enabled, |menu| {
                     menu.separator()
                         .action("Inline Assist", Box::new(InlineAssist::default()))
@@ -746,6 +754,56 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(
+        &mut self,
+        _: &ScrollToNextPrompt,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn select_command_output(
+        &mut self,
+        _: &SelectCommandOutput,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.select_command_output());
+        cx.notify();
+    }
+
+    fn copy_command_output(
+        &mut self,
+        _: &CopyCommandOutput,
+        _: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, cx| term.copy_command_output(cx));
+    }
+
+    fn rerun_command(&mut self, _: &RerunCommand, _: &mut Window, cx: &mut Context<Self>) {
+        self.clear_bell(cx);
+        self.blink_manager.update(cx, BlinkManager::pause_blinking);
+        self.terminal.update(cx, |term, _| term.rerun_command());
+        cx.notify();
+    }
+
     fn toggle_vi_mode(&mut self, _: &ToggleViMode, _: &mut Window, cx: &mut Context<Self>) {
         self.terminal.update(cx, |term, _| term.toggle_vi_mode());
         cx.notify();
@@ -1230,6 +1288,11 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::scroll_page_down))
             .on_action(cx.listener(TerminalView::scroll_to_top))
             .on_action(cx.listener(TerminalView::scroll_to_bottom))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::select_command_output))
+            .on_action(cx.listener(TerminalView::copy_command_output))
+            .on_action(cx.listener(TerminalView::rerun_command))
             .on_action(cx.listener(TerminalView::toggle_vi_mode))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
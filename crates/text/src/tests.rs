@@ -581,6 +581,208 @@ fn test_undo_redo() {
     assert_eq!(buffer.text(), "1234");
 }
 
+#[test]
+fn test_undo_tree() {
+    let mut buffer = Buffer::new(ReplicaId::LOCAL, BufferId::new(1).unwrap(), "abc");
+    buffer.set_group_interval(Duration::from_secs(0));
+
+    buffer.edit([(3..3, "1")]);
+    let transaction_1 = buffer.undo_tree_position().unwrap();
+    buffer.edit([(4..4, "2")]);
+    let transaction_2 = buffer.undo_tree_position().unwrap();
+    buffer.undo();
+    assert_eq!(buffer.text(), "abc1");
+
+    // Editing after undoing keeps the undone transaction as a separate branch.
+    buffer.edit([(0..0, "X")]);
+    let transaction_3 = buffer.undo_tree_position().unwrap();
+    assert_eq!(buffer.text(), "Xabc1");
+    assert_eq!(
+        buffer
+            .undo_tree()
+            .iter()
+            .map(|node| (node.transaction_id, node.parent))
+            .collect::<Vec<_>>(),
+        [
+            (transaction_1, None),
+            (transaction_2, Some(transaction_1)),
+            (transaction_3, Some(transaction_1)),
+        ]
+    );
+
+    assert_eq!(
+        buffer
+            .undo_tree_snapshot(Some(transaction_2))
+            .unwrap()
+            .text(),
+        "abc12"
+    );
+    assert_eq!(buffer.text(), "Xabc1");
+
+    buffer.navigate_undo_tree(Some(transaction_2)).unwrap();
+    assert_eq!(buffer.text(), "abc12");
+    assert_eq!(buffer.undo_tree_position(), Some(transaction_2));
+    buffer.undo();
+    assert_eq!(buffer.text(), "abc1");
+    buffer.redo();
+    assert_eq!(buffer.text(), "abc12");
+
+    // States are stepped through in the order they were made, across branches.
+    assert_eq!(buffer.undo_tree_step(-1), Some(transaction_1));
+    assert_eq!(buffer.undo_tree_step(1), Some(transaction_3));
+    assert_eq!(buffer.undo_tree_step(-10), None);
+    assert_eq!(buffer.undo_tree_step(10), Some(transaction_3));
+
+    // Redoing from the original text follows the most recent branch.
+    buffer.navigate_undo_tree(None).unwrap();
+    assert_eq!(buffer.text(), "abc");
+    buffer.redo();
+    buffer.redo();
+    assert_eq!(buffer.text(), "Xabc1");
+    assert!(buffer.redo().is_none());
+    assert!(buffer.navigate_undo_tree(Some(transaction_3)).is_none());
+}
+
+#[test]
+fn test_undo_tree_drops_oldest_branches() {
+    let mut buffer = Buffer::new(ReplicaId::LOCAL, BufferId::new(1).unwrap(), "");
+    buffer.set_group_interval(Duration::from_secs(0));
+
+    let mut branches = Vec::new();
+    let mut kept = Vec::new();
+    for _ in 0..MAX_DETACHED_TRANSACTIONS {
+        // Each branch has two transactions, which are dropped newest first.
+        buffer.edit([(buffer.len()..buffer.len(), "a")]);
+        let first = buffer.undo_tree_position().unwrap();
+        buffer.edit([(buffer.len()..buffer.len(), "b")]);
+        let second = buffer.undo_tree_position().unwrap();
+        branches.push([first, second]);
+        buffer.undo();
+        buffer.undo();
+        buffer.edit([(buffer.len()..buffer.len(), "c")]);
+        kept.push(buffer.undo_tree_position().unwrap());
+    }
+    assert_eq!(buffer.text(), "c".repeat(MAX_DETACHED_TRANSACTIONS));
+
+    let tree = buffer.undo_tree();
+    let ids = tree
+        .iter()
+        .map(|node| node.transaction_id)
+        .collect::<HashSet<_>>();
+    assert!(kept.iter().all(|id| ids.contains(id)));
+    let dropped = MAX_DETACHED_TRANSACTIONS / 2;
+    assert!(
+        branches[..dropped]
+            .iter()
+            .flatten()
+            .all(|id| !ids.contains(id))
+    );
+    assert!(
+        branches[dropped..]
+            .iter()
+            .flatten()
+            .all(|id| ids.contains(id))
+    );
+    assert!(
+        tree.iter()
+            .filter_map(|node| node.parent)
+            .all(|parent| ids.contains(&parent))
+    );
+
+    let [first, second] = branches[dropped];
+    buffer.navigate_undo_tree(Some(second)).unwrap();
+    assert_eq!(buffer.undo_tree_position(), Some(second));
+    buffer.undo();
+    assert_eq!(buffer.undo_tree_position(), Some(first));
+}
+
+#[test]
+fn test_undo_tree_step_by_time() {
+    let mut now = Instant::now();
+    let mut buffer = Buffer::new(ReplicaId::LOCAL, BufferId::new(1).unwrap(), "");
+    let mut transactions = Vec::new();
+    for text in ["a", "b", "c"] {
+        transactions.push(buffer.start_transaction_at(now).unwrap());
+        buffer.edit([(buffer.len()..buffer.len(), text)]);
+        buffer.end_transaction_at(now);
+        now += Duration::from_secs(60);
+    }
+    assert_eq!(buffer.text(), "abc");
+
+    assert_eq!(
+        buffer.undo_tree_step_by_time(Duration::from_secs(90), false),
+        Some(transactions[0])
+    );
+    assert_eq!(
+        buffer.undo_tree_step_by_time(Duration::from_secs(600), false),
+        None
+    );
+    assert_eq!(
+        buffer.undo_tree_step_by_time(Duration::from_secs(600), true),
+        Some(transactions[2])
+    );
+
+    buffer.navigate_undo_tree(Some(transactions[0]));
+    assert_eq!(buffer.text(), "a");
+    assert_eq!(
+        buffer.undo_tree_step_by_time(Duration::from_secs(60), true),
+        Some(transactions[1])
+    );
+    assert_eq!(
+        buffer.undo_tree_step_by_time(Duration::from_secs(30), true),
+        Some(transactions[0])
+    );
+}
+
+#[test]
+fn test_serialize_history() {
+    let mut buffer = Buffer::new(ReplicaId::LOCAL, BufferId::new(1).unwrap(), "1234");
+    buffer.set_group_interval(Duration::from_secs(0));
+
+    buffer.edit([(1..1, "abx")]);
+    buffer.edit([(3..4, "yzef")]);
+    buffer.edit([(0..1, ""), (5..7, "cd")]);
+    buffer.edit([(0..0, "X")]);
+    buffer.undo();
+    assert_eq!(buffer.text(), "abyzcd234");
+
+    let history = buffer.serialize_history(usize::MAX, usize::MAX).unwrap();
+    assert_eq!(history.base_text, "1234");
+    assert_eq!(history.undo_stack.len(), 3);
+    assert_eq!(
+        history.redo_stack,
+        [SerializedTransaction {
+            edits: vec![(0..0, "X".into())]
+        }]
+    );
+    assert_eq!(history.text().as_deref(), Some("abyzcd234"));
+
+    // Serializing doesn't affect the buffer's own history.
+    assert_eq!(buffer.text(), "abyzcd234");
+    buffer.redo();
+    assert_eq!(buffer.text(), "Xabyzcd234");
+    buffer.undo();
+
+    let history = buffer.serialize_history(2, usize::MAX).unwrap();
+    assert_eq!(history.base_text, "1abx234");
+    assert_eq!(history.undo_stack.len(), 2);
+    assert_eq!(history.text().as_deref(), Some("abyzcd234"));
+
+    // Older transactions are dropped once their edits exceed the length limit.
+    let history = buffer.serialize_history(usize::MAX, 3).unwrap();
+    assert_eq!(history.base_text, "1abyzef234");
+    assert_eq!(history.undo_stack.len(), 1);
+    assert_eq!(history.text().as_deref(), Some("abyzcd234"));
+    assert_eq!(history.redo_stack.len(), 1);
+
+    let mut invalid = history.clone();
+    invalid.undo_stack[0].edits[0].0 = 20..21;
+    assert_eq!(invalid.text(), None);
+
+    buffer.start_transaction();
+    assert_eq!(buffer.serialize_history(usize::MAX, usize::MAX), None);
+}
+
 #[test]
 fn test_history() {
     let mut now = Instant::now();
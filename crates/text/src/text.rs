@@ -29,7 +29,8 @@ use std::{
     cmp::{self, Ordering, Reverse},
     fmt::Display,
     future::Future,
-    iter::Iterator,
+    iter::{self, Iterator},
+    mem,
     num::NonZeroU64,
     ops::{self, Deref, Range, Sub},
     str,
@@ -53,6 +54,9 @@ static LINE_SEPARATORS_REGEX: LazyLock<Regex> =
 /// fragments. This allows us to use relative `u32` offsets instead of `usize`,
 /// reducing memory usage.
 const MAX_INSERTION_LEN: usize = if cfg!(test) { 16 } else { u32::MAX as usize };
+/// How many undone transactions that were branched off from are kept in the
+/// undo tree before the oldest branches are dropped.
+const MAX_DETACHED_TRANSACTIONS: usize = if cfg!(test) { 8 } else { 1000 };
 
 pub type TransactionId = clock::Lamport;
 
@@ -126,11 +130,158 @@ pub struct BufferSnapshot {
 #[derive(Clone, Debug)]
 pub struct HistoryEntry {
     transaction: Transaction,
+    parent: Option<TransactionId>,
     first_edit_at: Instant,
     last_edit_at: Instant,
     suppress_grouping: bool,
 }
 
+/// A transaction in a buffer's undo tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UndoTreeNode {
+    pub transaction_id: TransactionId,
+    /// The transaction this one was made on top of, or `None` if it was made
+    /// on top of the original text.
+    pub parent: Option<TransactionId>,
+    pub edited_at: Instant,
+}
+
+/// The undo and redo stacks of a buffer, expressed as text edits so that they
+/// can be stored and replayed onto a buffer with the same text.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SerializedHistory {
+    /// The text preceding the oldest transaction on the undo stack.
+    pub base_text: String,
+    /// The transactions that can be undone, oldest first.
+    pub undo_stack: Vec<SerializedTransaction>,
+    /// The transactions that can be redone, in the order they'd be redone.
+    pub redo_stack: Vec<SerializedTransaction>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SerializedTransaction {
+    /// Sorted, disjoint byte ranges in the text preceding the transaction,
+    /// along with their replacements.
+    pub edits: Vec<(Range<usize>, String)>,
+}
+
+impl SerializedHistory {
+    pub fn is_empty(&self) -> bool {
+        self.undo_stack.is_empty() && self.redo_stack.is_empty()
+    }
+
+    /// Replays the undo stack onto the base text and returns the resulting text,
+    /// or `None` if any transaction doesn't apply cleanly.
+    pub fn text(&self) -> Option<String> {
+        let mut text = self.base_text.clone();
+        for transaction in &self.undo_stack {
+            transaction.apply(&mut text)?;
+        }
+        let mut redone = text.clone();
+        for transaction in &self.redo_stack {
+            transaction.apply(&mut redone)?;
+        }
+        Some(text)
+    }
+}
+
+/// A copy of a buffer along with its history, taken by [`Buffer::history_snapshot`].
+pub struct HistorySnapshot(Buffer);
+
+impl HistorySnapshot {
+    /// Captures the most recent `max_transactions` entries of the undo and redo
+    /// stacks as plain text edits. Older transactions are dropped once the text
+    /// of the captured edits exceeds `max_len` bytes on either stack.
+    pub fn serialize(self, max_transactions: usize, max_len: usize) -> SerializedHistory {
+        let mut undo_stack = Vec::new();
+        let mut undo_len = 0;
+        let mut buffer = self.0.fork();
+        let mut base = buffer.snapshot.clone();
+        while undo_stack.len() < max_transactions {
+            let before = buffer.snapshot.clone();
+            if buffer.undo().is_none() {
+                break;
+            }
+            let edits = buffer
+                .edits_since::<usize>(&before.version)
+                .map(|edit| {
+                    (
+                        edit.new,
+                        before.text_for_range(edit.old).collect::<String>(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            undo_len += SerializedTransaction::edits_len(&edits);
+            if undo_len > max_len {
+                break;
+            }
+            base = buffer.snapshot.clone();
+            if !edits.is_empty() {
+                undo_stack.push(SerializedTransaction { edits });
+            }
+        }
+        undo_stack.reverse();
+        let base_text = base.text();
+
+        let mut redo_stack = Vec::new();
+        let mut redo_len = 0;
+        let mut buffer = self.0;
+        while redo_stack.len() < max_transactions {
+            let before = buffer.snapshot.clone();
+            if buffer.redo().is_none() {
+                break;
+            }
+            let edits = buffer
+                .edits_since::<usize>(&before.version)
+                .map(|edit| {
+                    (
+                        edit.old,
+                        buffer.text_for_range(edit.new).collect::<String>(),
+                    )
+                })
+                .collect::<Vec<_>>();
+            redo_len += SerializedTransaction::edits_len(&edits);
+            if redo_len > max_len {
+                break;
+            }
+            if !edits.is_empty() {
+                redo_stack.push(SerializedTransaction { edits });
+            }
+        }
+
+        SerializedHistory {
+            base_text,
+            undo_stack,
+            redo_stack,
+        }
+    }
+}
+
+impl SerializedTransaction {
+    fn edits_len(edits: &[(Range<usize>, String)]) -> usize {
+        edits.iter().map(|(_, new_text)| new_text.len()).sum()
+    }
+
+    fn apply(&self, text: &mut String) -> Option<()> {
+        let mut prev_end = 0;
+        for (range, _) in &self.edits {
+            if range.start < prev_end
+                || range.start > range.end
+                || range.end > text.len()
+                || !text.is_char_boundary(range.start)
+                || !text.is_char_boundary(range.end)
+            {
+                return None;
+            }
+            prev_end = range.end;
+        }
+        for (range, new_text) in self.edits.iter().rev() {
+            text.replace_range(range.clone(), new_text);
+        }
+        Some(())
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct Transaction {
     pub id: TransactionId,
@@ -150,11 +301,16 @@ impl HistoryEntry {
     }
 }
 
+#[derive(Clone)]
 struct History {
     base_text: Rope,
     operations: TreeMap<clock::Lamport, Operation>,
     undo_stack: Vec<HistoryEntry>,
     redo_stack: Vec<HistoryEntry>,
+    /// Undone transactions that were branched off from by a new transaction.
+    /// Along with the undo and redo stacks, they form a tree linked through
+    /// each entry's parent.
+    detached: Vec<HistoryEntry>,
     transaction_depth: usize,
     group_interval: Duration,
 }
@@ -221,6 +377,7 @@ impl History {
             operations: Default::default(),
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            detached: Vec::new(),
             transaction_depth: 0,
             // Don't group transactions in tests unless we opt in, because it's a footgun.
             group_interval: if cfg!(any(test, feature = "test-support")) {
@@ -250,6 +407,7 @@ impl History {
                     start,
                     edit_ids: Default::default(),
                 },
+                parent: self.undo_stack.last().map(|entry| entry.transaction.id),
                 first_edit_at: now,
                 last_edit_at: now,
                 suppress_grouping: false,
@@ -275,7 +433,7 @@ impl History {
                 self.undo_stack.pop();
                 None
             } else {
-                self.redo_stack.clear();
+                self.detach_redo_stack();
                 let entry = self.undo_stack.last_mut().unwrap();
                 entry.last_edit_at = now;
                 Some(entry)
@@ -285,6 +443,125 @@ impl History {
         }
     }
 
+    fn detach_redo_stack(&mut self) {
+        if self.redo_stack.is_empty() {
+            return;
+        }
+        // The history branches off after the transaction preceding the new
+        // one, so they must stay separate nodes of the tree.
+        if let Some(ix) = self.undo_stack.len().checked_sub(2) {
+            self.undo_stack[ix].suppress_grouping = true;
+        }
+        self.detached.append(&mut self.redo_stack);
+        self.prune_detached();
+    }
+
+    /// Drops the oldest detached transactions that nothing was made on top of,
+    /// until at most `MAX_DETACHED_TRANSACTIONS` are left.
+    fn prune_detached(&mut self) {
+        while self.detached.len() > MAX_DETACHED_TRANSACTIONS {
+            // Only detached transactions can be made on top of a detached one.
+            let parents = self
+                .detached
+                .iter()
+                .filter_map(|entry| entry.parent)
+                .collect::<HashSet<_>>();
+            let Some(oldest_leaf_ix) = self
+                .detached
+                .iter()
+                .enumerate()
+                .filter(|(_, entry)| !parents.contains(&entry.transaction.id))
+                .min_by_key(|(_, entry)| entry.transaction.id)
+                .map(|(ix, _)| ix)
+            else {
+                break;
+            };
+            self.detached.remove(oldest_leaf_ix);
+        }
+    }
+
+    fn entries(&self) -> impl Iterator<Item = &HistoryEntry> {
+        self.undo_stack
+            .iter()
+            .chain(&self.redo_stack)
+            .chain(&self.detached)
+    }
+
+    fn entries_mut(&mut self) -> impl Iterator<Item = &mut HistoryEntry> {
+        self.undo_stack
+            .iter_mut()
+            .chain(&mut self.redo_stack)
+            .chain(&mut self.detached)
+    }
+
+    fn reparent(&mut self, old_parents: &[TransactionId], new_parent: Option<TransactionId>) {
+        for entry in self.entries_mut() {
+            if entry
+                .parent
+                .is_some_and(|parent| old_parents.contains(&parent))
+            {
+                entry.parent = new_parent;
+            }
+        }
+    }
+
+    /// The transactions leading from the original text to the given node of
+    /// the undo tree, oldest first.
+    fn undo_tree_path(&self, target: Option<TransactionId>) -> Option<Vec<TransactionId>> {
+        let parents = self
+            .entries()
+            .map(|entry| (entry.transaction.id, entry.parent))
+            .collect::<HashMap<_, _>>();
+        let mut path = Vec::new();
+        let mut next = target;
+        while let Some(transaction_id) = next {
+            if path.len() == parents.len() {
+                return None;
+            }
+            next = *parents.get(&transaction_id)?;
+            path.push(transaction_id);
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Rearranges the undo and redo stacks after moving to the end of the
+    /// given path. Redoing follows the most recent branch from there.
+    fn move_in_undo_tree(&mut self, path: &[TransactionId]) {
+        let mut entries = mem::take(&mut self.undo_stack)
+            .into_iter()
+            .chain(mem::take(&mut self.redo_stack))
+            .chain(mem::take(&mut self.detached))
+            .map(|entry| (entry.transaction.id, entry))
+            .collect::<HashMap<_, _>>();
+        let mut latest_children = HashMap::<Option<TransactionId>, TransactionId>::default();
+        for (transaction_id, entry) in &entries {
+            let child = latest_children
+                .entry(entry.parent)
+                .or_insert(*transaction_id);
+            *child = (*child).max(*transaction_id);
+        }
+
+        self.undo_stack = path
+            .iter()
+            .filter_map(|transaction_id| entries.remove(transaction_id))
+            .collect();
+        if let Some(entry) = self.undo_stack.last_mut() {
+            entry.suppress_grouping = true;
+        }
+        let mut parent = path.last().copied();
+        while let Some(child) = latest_children.get(&parent)
+            && let Some(entry) = entries.remove(child)
+        {
+            parent = Some(*child);
+            self.redo_stack.push(entry);
+        }
+        self.redo_stack.reverse();
+        self.detached = entries.into_values().collect();
+        self.detached
+            .sort_unstable_by_key(|entry| entry.transaction.id);
+    }
+
     fn group(&mut self) -> Option<TransactionId> {
         let mut count = 0;
         let mut entries = self.undo_stack.iter();
@@ -319,6 +596,10 @@ impl History {
 
     fn group_trailing(&mut self, n: usize) -> Option<TransactionId> {
         let new_len = self.undo_stack.len() - n;
+        let merged_ids = self.undo_stack[new_len..]
+            .iter()
+            .map(|entry| entry.transaction.id)
+            .collect::<Vec<_>>();
         let (entries_to_keep, entries_to_merge) = self.undo_stack.split_at_mut(new_len);
         if let Some(last_entry) = entries_to_keep.last_mut() {
             for entry in &*entries_to_merge {
@@ -333,7 +614,11 @@ impl History {
         }
 
         self.undo_stack.truncate(new_len);
-        self.undo_stack.last().map(|e| e.transaction.id)
+        let last_id = self.undo_stack.last().map(|e| e.transaction.id);
+        if !merged_ids.is_empty() && last_id.is_some() {
+            self.reparent(&merged_ids, last_id);
+        }
+        last_id
     }
 
     fn finalize_last_transaction(&mut self) -> Option<&Transaction> {
@@ -348,6 +633,7 @@ impl History {
         assert_eq!(self.transaction_depth, 0);
         self.undo_stack.push(HistoryEntry {
             transaction,
+            parent: self.undo_stack.last().map(|entry| entry.transaction.id),
             first_edit_at: now,
             last_edit_at: now,
             suppress_grouping: false,
@@ -380,6 +666,7 @@ impl History {
         };
         self.undo_stack.push(HistoryEntry {
             transaction,
+            parent: self.undo_stack.last().map(|entry| entry.transaction.id),
             first_edit_at: now,
             last_edit_at: now,
             suppress_grouping: false,
@@ -434,21 +721,29 @@ impl History {
 
     fn forget(&mut self, transaction_id: TransactionId) -> Option<Transaction> {
         assert_eq!(self.transaction_depth, 0);
-        if let Some(entry_ix) = self
+        let entry = if let Some(entry_ix) = self
             .undo_stack
             .iter()
             .rposition(|entry| entry.transaction.id == transaction_id)
         {
-            Some(self.undo_stack.remove(entry_ix).transaction)
+            self.undo_stack.remove(entry_ix)
         } else if let Some(entry_ix) = self
             .redo_stack
             .iter()
             .rposition(|entry| entry.transaction.id == transaction_id)
         {
-            Some(self.redo_stack.remove(entry_ix).transaction)
+            self.redo_stack.remove(entry_ix)
+        } else if let Some(entry_ix) = self
+            .detached
+            .iter()
+            .position(|entry| entry.transaction.id == transaction_id)
+        {
+            self.detached.remove(entry_ix)
         } else {
-            None
-        }
+            return None;
+        };
+        self.reparent(&[transaction_id], entry.parent);
+        Some(entry.transaction)
     }
 
     fn transaction(&self, transaction_id: TransactionId) -> Option<&Transaction> {
@@ -460,6 +755,11 @@ impl History {
                 self.redo_stack
                     .iter()
                     .rfind(|entry| entry.transaction.id == transaction_id)
+            })
+            .or_else(|| {
+                self.detached
+                    .iter()
+                    .find(|entry| entry.transaction.id == transaction_id)
             })?;
         Some(&entry.transaction)
     }
@@ -473,6 +773,11 @@ impl History {
                 self.redo_stack
                     .iter_mut()
                     .rfind(|entry| entry.transaction.id == transaction_id)
+            })
+            .or_else(|| {
+                self.detached
+                    .iter_mut()
+                    .find(|entry| entry.transaction.id == transaction_id)
             })?;
         Some(&mut entry.transaction)
     }
@@ -851,6 +1156,21 @@ impl Buffer {
         }
     }
 
+    /// A copy of the buffer sharing its history, used to replay undo and redo
+    /// without affecting the buffer itself.
+    fn fork(&self) -> Self {
+        Self {
+            snapshot: self.snapshot.clone(),
+            history: self.history.clone(),
+            deferred_ops: OperationQueue::new(),
+            deferred_replicas: HashSet::default(),
+            lamport_clock: self.lamport_clock,
+            subscriptions: Default::default(),
+            edit_id_resolvers: Default::default(),
+            wait_for_version_txs: Default::default(),
+        }
+    }
+
     pub fn replica_id(&self) -> ReplicaId {
         self.lamport_clock.replica_id
     }
@@ -1613,6 +1933,140 @@ impl Buffer {
             .collect()
     }
 
+    /// Every transaction in the history, including the undone ones that were
+    /// branched off from, in the order they were made.
+    pub fn undo_tree(&self) -> Vec<UndoTreeNode> {
+        let mut nodes = self
+            .history
+            .entries()
+            .map(|entry| UndoTreeNode {
+                transaction_id: entry.transaction.id,
+                parent: entry.parent,
+                edited_at: entry.last_edit_at,
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_unstable_by_key(|node| node.transaction_id);
+        nodes
+    }
+
+    /// The node of the undo tree the buffer is at, or `None` for the original text.
+    pub fn undo_tree_position(&self) -> Option<TransactionId> {
+        self.history
+            .undo_stack
+            .last()
+            .map(|entry| entry.transaction.id)
+    }
+
+    /// The node of the undo tree that's `steps` states away from the current
+    /// one, in the order the states were made, regardless of their branch.
+    pub fn undo_tree_step(&self, steps: isize) -> Option<TransactionId> {
+        let states = iter::once(None)
+            .chain(
+                self.undo_tree()
+                    .into_iter()
+                    .map(|node| Some(node.transaction_id)),
+            )
+            .collect::<Vec<_>>();
+        let position = self.undo_tree_position();
+        let current_ix = states
+            .iter()
+            .position(|state| *state == position)
+            .unwrap_or(0);
+        let target_ix = current_ix
+            .saturating_add_signed(steps)
+            .min(states.len() - 1);
+        states[target_ix]
+    }
+
+    /// The most recent node of the undo tree made at most `offset` before, or
+    /// after if `forward` is set, the current one.
+    pub fn undo_tree_step_by_time(&self, offset: Duration, forward: bool) -> Option<TransactionId> {
+        let nodes = self.undo_tree();
+        let position = self.undo_tree_position();
+        let current =
+            position.and_then(|position| nodes.iter().find(|node| node.transaction_id == position));
+        if forward {
+            let Some(base) = current.or(nodes.first()).map(|node| node.edited_at) else {
+                return position;
+            };
+            let target = base + offset;
+            nodes
+                .iter()
+                .filter(|node| Some(node.transaction_id) > position && node.edited_at <= target)
+                .map(|node| node.transaction_id)
+                .max()
+                .or(position)
+        } else {
+            let target = current.and_then(|node| node.edited_at.checked_sub(offset))?;
+            nodes
+                .iter()
+                .filter(|node| Some(node.transaction_id) < position && node.edited_at <= target)
+                .map(|node| node.transaction_id)
+                .max()
+        }
+    }
+
+    /// Moves the buffer to the given node of the undo tree, undoing and redoing
+    /// transactions as needed. Returns `None` if there was nothing to change.
+    pub fn navigate_undo_tree(&mut self, target: Option<TransactionId>) -> Option<Operation> {
+        if self.history.transaction_depth > 0 {
+            return None;
+        }
+        let path = self.history.undo_tree_path(target)?;
+        let applied = path.iter().copied().collect::<HashSet<_>>();
+        let mut counts = HashMap::default();
+        for entry in self.history.entries() {
+            let should_apply = applied.contains(&entry.transaction.id);
+            for edit_id in &entry.transaction.edit_ids {
+                let undo_count = self.undo_map.undo_count(*edit_id);
+                if (undo_count % 2 == 0) != should_apply {
+                    counts.insert(*edit_id, undo_count + 1);
+                }
+            }
+        }
+        self.history.move_in_undo_tree(&path);
+        if counts.is_empty() {
+            return None;
+        }
+        let operation = self.undo_operations(counts);
+        self.history.push(operation.clone());
+        Some(operation)
+    }
+
+    /// The buffer's contents at the given node of the undo tree, leaving the
+    /// buffer itself untouched.
+    pub fn undo_tree_snapshot(&self, target: Option<TransactionId>) -> Option<BufferSnapshot> {
+        if self.history.transaction_depth > 0 {
+            return None;
+        }
+        self.history.undo_tree_path(target)?;
+        let mut buffer = self.fork();
+        buffer.navigate_undo_tree(target);
+        Some(buffer.snapshot)
+    }
+
+    /// A detached copy of the buffer's history, or `None` while a transaction
+    /// is in progress. Taking it is cheap, and the history can then be
+    /// serialized on a background thread.
+    pub fn history_snapshot(&self) -> Option<HistorySnapshot> {
+        if self.history.transaction_depth > 0 {
+            return None;
+        }
+        Some(HistorySnapshot(self.fork()))
+    }
+
+    /// See [`HistorySnapshot::serialize`].
+    pub fn serialize_history(
+        &self,
+        max_transactions: usize,
+        max_len: usize,
+    ) -> Option<SerializedHistory> {
+        Some(
+            self.history_snapshot()?
+                .serialize(max_transactions, max_len),
+        )
+    }
+
     fn undo_or_redo(&mut self, transaction: Transaction) -> Operation {
         let mut counts = HashMap::default();
         for edit_id in transaction.edit_ids {
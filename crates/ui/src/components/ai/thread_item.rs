@@ -49,6 +49,7 @@ pub struct ThreadItem {
     project_paths: Option<Arc<[PathBuf]>>,
     project_name: Option<SharedString>,
     worktrees: Vec<ThreadItemWorktreeInfo>,
+    forked_from: Option<SharedString>,
     on_click: Option<Box<dyn Fn(&ClickEvent, &mut Window, &mut App) + 'static>>,
     on_hover: Box<dyn Fn(&bool, &mut Window, &mut App) + 'static>,
     action_slot: Option<AnyElement>,
@@ -81,6 +82,7 @@ impl ThreadItem {
             project_paths: None,
             project_name: None,
             worktrees: Vec::new(),
+            forked_from: None,
             on_click: None,
             on_hover: Box::new(|_, _, _| {}),
             action_slot: None,
@@ -174,6 +176,12 @@ impl ThreadItem {
         self
     }
 
+    /// Sets the title of the thread this one was forked from.
+    pub fn forked_from(mut self, title: impl Into<SharedString>) -> Self {
+        self.forked_from = Some(title.into());
+        self
+    }
+
     pub fn hovered(mut self, hovered: bool) -> Self {
         self.hovered = hovered;
         self
@@ -363,6 +371,7 @@ impl RenderOnce for ThreadItem {
         let has_project_name = self.project_name.is_some();
         let has_project_paths = project_paths.is_some();
         let has_worktree = !self.worktrees.is_empty();
+        let has_forked_from = self.forked_from.is_some();
         let has_timestamp = !self.timestamp.is_empty();
         let timestamp = self.timestamp;
 
@@ -423,6 +432,7 @@ impl RenderOnce for ThreadItem {
                 has_project_name
                     || has_project_paths
                     || has_worktree
+                    || has_forked_from
                     || has_diff_stats
                     || has_timestamp,
                 |this| {
@@ -518,7 +528,35 @@ impl RenderOnce for ThreadItem {
                             })
                             .children(worktree_labels)
                             .when(
-                                (has_project_name || has_project_paths || has_worktree)
+                                has_forked_from
+                                    && (has_project_name || has_project_paths || has_worktree),
+                                |this| this.child(dot_separator()),
+                            )
+                            .when_some(self.forked_from, |this, title| {
+                                this.child(
+                                    h_flex()
+                                        .id(format!("{}-forked-from", self.id.clone()))
+                                        .min_w_0()
+                                        .gap_0p5()
+                                        .child(
+                                            Icon::new(IconName::GitBranch)
+                                                .size(IconSize::XSmall)
+                                                .color(Color::Muted),
+                                        )
+                                        .child(
+                                            Label::new(title.clone())
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted)
+                                                .truncate(),
+                                        )
+                                        .tooltip(Tooltip::text(format!("Forked from {title}"))),
+                                )
+                            })
+                            .when(
+                                (has_project_name
+                                    || has_project_paths
+                                    || has_worktree
+                                    || has_forked_from)
                                     && (has_diff_stats || has_timestamp),
                                 |this| this.child(dot_separator()),
                             )
@@ -636,6 +674,17 @@ impl Component for ThreadItem {
                     )
                     .into_any_element(),
             ),
+            single_example(
+                "Forked",
+                container()
+                    .child(
+                        ThreadItem::new("ti-4b", "Try a streaming parser instead")
+                            .icon(IconName::ZedAgent)
+                            .timestamp("3d")
+                            .forked_from("Speed up large file parsing"),
+                    )
+                    .into_any_element(),
+            ),
             single_example(
                 "With Changes (months)",
                 container()
@@ -0,0 +1,403 @@
+use collections::HashMap;
+use editor::Editor;
+use gpui::{
+    DismissEvent, Entity, EventEmitter, FocusHandle, Focusable, SharedString, Task, WeakEntity,
+    actions,
+};
+use language::{Buffer, TransactionId, UndoTreeNode};
+use picker::{Picker, PickerDelegate};
+use std::{sync::Arc, time::Instant};
+use time::OffsetDateTime;
+use time_format::TimestampFormat;
+use ui::{ListItem, ListItemSpacing, prelude::*};
+use util::ResultExt;
+use workspace::ModalView;
+
+actions!(
+    undo_tree,
+    [
+        /// Toggles the undo tree, listing every state of the buffer's history
+        /// including the undone branches.
+        Toggle
+    ]
+);
+
+pub fn init(cx: &mut App) {
+    cx.observe_new(UndoTree::register).detach();
+}
+
+pub struct UndoTree {
+    picker: Entity<Picker<UndoTreeDelegate>>,
+    _picker_subscription: gpui::Subscription,
+}
+
+impl UndoTree {
+    fn register(editor: &mut Editor, _window: Option<&mut Window>, cx: &mut Context<Editor>) {
+        let editor_handle = cx.weak_entity();
+        editor
+            .register_action(move |_: &Toggle, window, cx| {
+                Self::toggle(&editor_handle, window, cx);
+            })
+            .detach();
+    }
+
+    fn toggle(editor: &WeakEntity<Editor>, window: &mut Window, cx: &mut App) {
+        let Some((workspace, buffer)) = editor
+            .update(cx, |editor, cx| {
+                Some((
+                    editor.workspace()?,
+                    editor.buffer().read(cx).as_singleton()?,
+                ))
+            })
+            .ok()
+            .flatten()
+        else {
+            return;
+        };
+
+        let editor = editor.clone();
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(window, cx, move |window, cx| {
+                UndoTree::new(editor, buffer, window, cx)
+            });
+        })
+    }
+
+    fn new(
+        editor: WeakEntity<Editor>,
+        buffer: Entity<Buffer>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Self {
+        let delegate = UndoTreeDelegate::new(cx.entity().downgrade(), editor, buffer, cx);
+        let picker = cx.new(|cx| {
+            let mut picker = Picker::nonsearchable_uniform_list(delegate, window, cx).modal(false);
+            picker.delegate.update_preview(cx);
+            picker
+        });
+        let picker_subscription = cx.observe(&picker, |_, _, cx| cx.notify());
+        Self {
+            picker,
+            _picker_subscription: picker_subscription,
+        }
+    }
+
+    fn render_preview(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let delegate = &self.picker.read(cx).delegate;
+        let colors = cx.theme().colors();
+        let content = match &delegate.preview {
+            None => Label::new("Loading…")
+                .color(Color::Muted)
+                .into_any_element(),
+            Some(preview) if preview.is_empty() => Label::new("No changes from the current text")
+                .color(Color::Muted)
+                .into_any_element(),
+            Some(preview) => v_flex()
+                .children(preview.lines().map(|line| {
+                    let color = if line.starts_with("@@") {
+                        Color::Accent
+                    } else if line.starts_with('+') {
+                        Color::Created
+                    } else if line.starts_with('-') {
+                        Color::Deleted
+                    } else {
+                        Color::Default
+                    };
+                    Label::new(SharedString::from(line.to_string()))
+                        .buffer_font(cx)
+                        .size(LabelSize::Small)
+                        .color(color)
+                        .single_line()
+                }))
+                .into_any_element(),
+        };
+
+        v_flex()
+            .id("undo-tree-preview")
+            .flex_1()
+            .h(rems(28.))
+            .p_2()
+            .gap_1()
+            .border_l_1()
+            .border_color(colors.border_variant)
+            .bg(colors.editor_background)
+            .overflow_scroll()
+            .child(
+                Label::new("Changes to restore")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(content)
+    }
+}
+
+impl Render for UndoTree {
+    fn render(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        h_flex()
+            .elevation_3(cx)
+            .items_start()
+            .w(rems(60.))
+            .overflow_hidden()
+            .child(v_flex().w(rems(20.)).child(self.picker.clone()))
+            .child(self.render_preview(cx))
+    }
+}
+
+impl Focusable for UndoTree {
+    fn focus_handle(&self, cx: &App) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for UndoTree {}
+impl ModalView for UndoTree {}
+
+/// A state of the buffer, as listed in the undo tree.
+#[derive(Debug, PartialEq)]
+struct UndoTreeEntry {
+    /// The transaction leading to this state, or `None` for the original text.
+    transaction_id: Option<TransactionId>,
+    /// The position of the state in the order the states were made.
+    number: usize,
+    /// How far the state's branch is nested under the most recent one.
+    depth: usize,
+    edited_at: Option<Instant>,
+}
+
+/// Lists the states of the buffer from newest to oldest. Each state continues
+/// the branch of the state it was made on, unless a newer state was made on
+/// that one too, in which case it starts a nested branch.
+fn undo_tree_entries(nodes: &[UndoTreeNode]) -> Vec<UndoTreeEntry> {
+    let mut latest_children = HashMap::default();
+    for node in nodes {
+        latest_children.insert(node.parent, node.transaction_id);
+    }
+
+    let mut depths = HashMap::default();
+    let mut entries = vec![UndoTreeEntry {
+        transaction_id: None,
+        number: 0,
+        depth: 0,
+        edited_at: None,
+    }];
+    for (ix, node) in nodes.iter().enumerate() {
+        let parent_depth = node
+            .parent
+            .and_then(|parent| depths.get(&parent).copied())
+            .unwrap_or(0);
+        let depth = if latest_children.get(&node.parent) == Some(&node.transaction_id) {
+            parent_depth
+        } else {
+            parent_depth + 1
+        };
+        depths.insert(node.transaction_id, depth);
+        entries.push(UndoTreeEntry {
+            transaction_id: Some(node.transaction_id),
+            number: ix + 1,
+            depth,
+            edited_at: Some(node.edited_at),
+        });
+    }
+    entries.reverse();
+    entries
+}
+
+fn format_edited_at(edited_at: Instant) -> String {
+    let now = OffsetDateTime::now_utc();
+    time_format::format_local_timestamp(now - edited_at.elapsed(), now, TimestampFormat::Relative)
+}
+
+struct UndoTreeDelegate {
+    undo_tree: WeakEntity<UndoTree>,
+    editor: WeakEntity<Editor>,
+    buffer: Entity<Buffer>,
+    entries: Vec<UndoTreeEntry>,
+    position: Option<TransactionId>,
+    selected_index: usize,
+    preview: Option<Arc<str>>,
+    preview_task: Task<()>,
+}
+
+impl UndoTreeDelegate {
+    fn new(
+        undo_tree: WeakEntity<UndoTree>,
+        editor: WeakEntity<Editor>,
+        buffer: Entity<Buffer>,
+        cx: &App,
+    ) -> Self {
+        let entries = undo_tree_entries(&buffer.read(cx).undo_tree());
+        let position = buffer.read(cx).undo_tree_position();
+        let selected_index = entries
+            .iter()
+            .position(|entry| entry.transaction_id == position)
+            .unwrap_or(0);
+        Self {
+            undo_tree,
+            editor,
+            buffer,
+            entries,
+            position,
+            selected_index,
+            preview: None,
+            preview_task: Task::ready(()),
+        }
+    }
+
+    fn update_preview(&mut self, cx: &mut Context<Picker<Self>>) {
+        self.preview = None;
+        let Some(entry) = self.entries.get(self.selected_index) else {
+            return;
+        };
+        let buffer = self.buffer.read(cx);
+        let current = buffer.text_snapshot();
+        let Some(target) = buffer.undo_tree_snapshot(entry.transaction_id) else {
+            return;
+        };
+        self.preview_task = cx.spawn(async move |picker, cx| {
+            let preview = cx
+                .background_spawn(
+                    async move { language::unified_diff(&current.text(), &target.text()) },
+                )
+                .await;
+            picker
+                .update(cx, |picker, cx| {
+                    picker.delegate.preview = Some(preview.into());
+                    cx.notify();
+                })
+                .ok();
+        });
+    }
+}
+
+impl PickerDelegate for UndoTreeDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _window: &mut Window, _cx: &mut App) -> Arc<str> {
+        "Select a state to restore…".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn confirm(&mut self, _: bool, window: &mut Window, cx: &mut Context<Picker<Self>>) {
+        if let Some(entry) = self.entries.get(self.selected_index) {
+            let target = entry.transaction_id;
+            self.editor
+                .update(cx, |editor, cx| {
+                    editor.navigate_undo_tree(target, window, cx);
+                })
+                .log_err();
+        }
+        self.dismissed(window, cx);
+    }
+
+    fn dismissed(&mut self, _: &mut Window, cx: &mut Context<Picker<Self>>) {
+        self.undo_tree
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: usize,
+        _window: &mut Window,
+        cx: &mut Context<Picker<Self>>,
+    ) {
+        self.selected_index = ix;
+        self.update_preview(cx);
+    }
+
+    fn update_matches(
+        &mut self,
+        _query: String,
+        _window: &mut Window,
+        _cx: &mut Context<Picker<Self>>,
+    ) -> Task<()> {
+        Task::ready(())
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut Window,
+        _: &mut Context<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = self.entries.get(ix)?;
+        let label = if entry.transaction_id.is_some() {
+            format!("#{}", entry.number)
+        } else {
+            "Original".to_string()
+        };
+
+        let mut list_item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .toggle_state(selected)
+            .indent_level(entry.depth)
+            .indent_step_size(px(12.))
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(Label::new(label))
+                    .children(entry.edited_at.map(|edited_at| {
+                        Label::new(format_edited_at(edited_at))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                    })),
+            );
+
+        if entry.transaction_id == self.position {
+            list_item = list_item.end_slot(Icon::new(IconName::Check).color(Color::Muted));
+        }
+
+        Some(list_item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock::{Lamport, ReplicaId};
+
+    #[test]
+    fn test_undo_tree_entries() {
+        let now = Instant::now();
+        let id = |value| Lamport {
+            value,
+            replica_id: ReplicaId::LOCAL,
+        };
+        let node = |transaction_id, parent| UndoTreeNode {
+            transaction_id: id(transaction_id),
+            parent: parent.map(id),
+            edited_at: now,
+        };
+
+        // 1 ── 2 ── 4
+        //  └── 3
+        let entries = undo_tree_entries(&[
+            node(1, None),
+            node(2, Some(1)),
+            node(3, Some(1)),
+            node(4, Some(2)),
+        ]);
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.transaction_id, entry.number, entry.depth))
+                .collect::<Vec<_>>(),
+            [
+                (Some(id(4)), 4, 1),
+                (Some(id(3)), 3, 0),
+                (Some(id(2)), 2, 1),
+                (Some(id(1)), 1, 0),
+                (None, 0, 0),
+            ]
+        );
+    }
+}
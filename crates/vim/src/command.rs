@@ -2,21 +2,22 @@ use anyhow::{Result, anyhow};
 use collections::{HashMap, HashSet};
 use command_palette_hooks::{CommandInterceptItem, CommandInterceptResult};
 use editor::{
-    Bias, Editor, EditorSettings, SelectionEffects, ToPoint,
+    Anchor, Bias, Editor, EditorSettings, SelectionEffects, ToPoint,
     actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
     display_map::ToDisplayPoint,
 };
-use futures::AsyncWriteExt as _;
+use futures::{AsyncWriteExt as _, FutureExt as _};
 use gpui::{
-    Action, App, AppContext as _, Context, Global, Keystroke, Task, WeakEntity, Window, actions,
+    Action, App, AppContext as _, Context, Entity, Global, Keystroke, Task, WeakEntity, Window,
+    actions,
 };
 use itertools::Itertools;
-use language::Point;
+use language::{Buffer, Point};
 use multi_buffer::MultiBufferRow;
 use project::ProjectPath;
 use regex::Regex;
 use schemars::JsonSchema;
-use search::{BufferSearchBar, SearchOptions};
+use search::{BufferSearchBar, ProjectSearchView, SearchOptions};
 use serde::Deserialize;
 use settings::{Settings, SettingsStore};
 use std::{
@@ -35,12 +36,12 @@ use util::{
     paths::PathStyle,
     rel_path::{RelPath, RelPathBuf},
 };
-use workspace::{Item, SaveIntent, Workspace, notifications::NotifyResultExt};
+use workspace::{Item, ItemHandle, Pane, SaveIntent, Workspace, notifications::NotifyResultExt};
 use workspace::{SplitDirection, notifications::DetachAndPromptErr};
 use zed_actions::{OpenDocs, RevealTarget};
 
 use crate::{
-    ToggleMarksView, ToggleRegistersView, Vim, VimSettings,
+    ToggleMarksView, ToggleRegistersView, Vim, VimAddon, VimSettings,
     motion::{EndOfDocument, Motion, MotionKind, StartOfDocument},
     normal::{
         JoinLines,
@@ -776,83 +777,7 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
     });
 
     Vim::action(editor, cx, |vim, action: &VimNorm, window, cx| {
-        let keystrokes = action
-            .command
-            .chars()
-            .map(|c| Keystroke::parse(&c.to_string()).unwrap())
-            .collect();
-        vim.switch_mode(Mode::Normal, true, window, cx);
-        if let Some(override_rows) = &action.override_rows {
-            vim.update_editor(cx, |_, editor, cx| {
-                editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
-                    s.replace_cursors_with(|map| {
-                        override_rows
-                            .iter()
-                            .map(|row| Point::new(*row, 0).to_display_point(map))
-                            .collect()
-                    });
-                });
-            });
-        } else if let Some(range) = &action.range {
-            let result = vim.update_editor(cx, |vim, editor, cx| {
-                let range = range.buffer_range(vim, editor, window, cx)?;
-                editor.change_selections(
-                    SelectionEffects::no_scroll().nav_history(false),
-                    window,
-                    cx,
-                    |s| {
-                        s.select_ranges(
-                            (range.start.0..=range.end.0)
-                                .map(|line| Point::new(line, 0)..Point::new(line, 0)),
-                        );
-                    },
-                );
-                anyhow::Ok(())
-            });
-            if let Some(Err(err)) = result {
-                log::error!("Error selecting range: {}", err);
-                return;
-            }
-        };
-
-        let Some(workspace) = vim.workspace(window, cx) else {
-            return;
-        };
-        let task = workspace.update(cx, |workspace, cx| {
-            workspace.send_keystrokes_impl(keystrokes, window, cx)
-        });
-        let had_range = action.range.is_some();
-        let had_override = action.override_rows.is_some();
-
-        cx.spawn_in(window, async move |vim, cx| {
-            task.await;
-            vim.update_in(cx, |vim, window, cx| {
-                if matches!(vim.mode, Mode::Insert | Mode::Replace) {
-                    vim.normal_before(&Default::default(), window, cx);
-                } else {
-                    vim.switch_mode(Mode::Normal, true, window, cx);
-                }
-                if had_override || had_range {
-                    vim.update_editor(cx, |_, editor, cx| {
-                        editor.change_selections(SelectionEffects::default(), window, cx, |s| {
-                            s.select_anchor_ranges([s.newest_anchor().range()]);
-                        });
-                        if let Some(tx_id) = editor
-                            .buffer()
-                            .update(cx, |multi, cx| multi.last_transaction_id(cx))
-                        {
-                            let last_sel = editor.selections.disjoint_anchors_arc();
-                            editor.modify_transaction_selection_history(tx_id, |old| {
-                                old.0 = old.0.get(..1).unwrap_or(&[]).into();
-                                old.1 = Some(last_sel);
-                            });
-                        }
-                    });
-                }
-            })
-            .log_err();
-        })
-        .detach();
+        vim.run_norm(action, window, cx).detach();
     });
 
     Vim::action(editor, cx, |vim, _: &CountCommand, window, cx| {
@@ -980,6 +905,14 @@ pub fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
         action.run(vim, window, cx)
     });
 
+    Vim::action(editor, cx, |vim, action: &TransferLines, window, cx| {
+        action.run(vim, window, cx)
+    });
+
+    Vim::action(editor, cx, |vim, action: &BatchCommand, window, cx| {
+        action.run(vim, window, cx)
+    });
+
     Vim::action(editor, cx, |vim, action: &ShellExec, window, cx| {
         action.run(vim, window, cx)
     })
@@ -1734,6 +1667,14 @@ fn generate_commands(_: &App) -> Vec<VimCommand> {
         )
         .range(wrap_count),
         VimCommand::new(("j", "oin"), JoinLines).range(select_range),
+        VimCommand::new(("ea", "rlier"), editor::actions::Earlier::default()).args(|_, args| {
+            let (steps, seconds) = parse_undo_tree_offset(&args)?;
+            Some(editor::actions::Earlier { steps, seconds }.boxed_clone())
+        }),
+        VimCommand::new(("lat", "er"), editor::actions::Later::default()).args(|_, args| {
+            let (steps, seconds) = parse_undo_tree_offset(&args)?;
+            Some(editor::actions::Later { steps, seconds }.boxed_clone())
+        }),
         VimCommand::new(("reflow", ""), Rewrap { line_length: None })
             .range(select_range)
             .args(|_action, args| {
@@ -1811,6 +1752,26 @@ fn commands(cx: &App) -> &Vec<VimCommand> {
         .0
 }
 
+/// Parses the argument of `:earlier` and `:later`, either a number of text
+/// states or an amount of time such as `10s`, `5m`, `2h` or `1d`.
+fn parse_undo_tree_offset(args: &str) -> Option<(Option<usize>, Option<u64>)> {
+    let args = args.trim();
+    let unit_ix = args
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(args.len());
+    let (count, unit) = args.split_at(unit_ix);
+    let count = count.parse::<u64>().ok()?;
+    let seconds_per_unit = match unit {
+        "" => return Some((Some(count as usize), None)),
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+    Some((None, Some(count.saturating_mul(seconds_per_unit))))
+}
+
 fn act_on_range(action: Box<dyn Action>, range: &CommandRange) -> Option<Box<dyn Action>> {
     Some(
         WithRange {
@@ -1894,6 +1855,17 @@ pub fn command_interceptor(
             results: commands,
             exclusive: false,
         });
+    } else if let Some(action) = BatchCommand::parse(query, &workspace, cx) {
+        Some(action)
+    } else if let Some(action) = TransferLines::parse(
+        query,
+        if on_matching_lines.is_some() {
+            &None
+        } else {
+            &range
+        },
+    ) {
+        Some(action)
     } else if query.starts_with('s') {
         let mut substitute = "substitute".chars().peekable();
         let mut query = query.chars().peekable();
@@ -2292,6 +2264,460 @@ impl OnMatchingLines {
     }
 }
 
+/// Copies (`:t`, `:copy`) or moves (`:move`) lines below the given address.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct TransferLines {
+    range: Option<CommandRange>,
+    destination: Position,
+    is_move: bool,
+}
+
+impl TransferLines {
+    // unlike other commands, the address may follow the command name
+    // without a space, as in `:t.` or `:m0`.
+    pub(crate) fn parse(query: &str, range: &Option<CommandRange>) -> Option<Box<dyn Action>> {
+        let (rest, is_move) = if let Some(rest) = query.strip_prefix('t') {
+            (rest, false)
+        } else if let Some(rest) = query.strip_prefix("co") {
+            (strip_abbreviated(rest, "py"), false)
+        } else if let Some(rest) = query.strip_prefix('m') {
+            (strip_abbreviated(rest, "ove"), true)
+        } else {
+            return None;
+        };
+        let mut chars = rest.trim().chars().peekable();
+        let destination = VimCommand::parse_position(&mut chars)?;
+        if chars.next().is_some() {
+            return None;
+        }
+        Some(
+            TransferLines {
+                range: range.clone(),
+                destination,
+                is_move,
+            }
+            .boxed_clone(),
+        )
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let result = vim.update_editor(cx, |vim, editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            // Without a range, the line of each cursor is transferred in turn,
+            // so that `:g/pattern/m0` reverses the matching lines.
+            let ranges = if let Some(range) = &self.range {
+                let range = range.buffer_range(vim, editor, window, cx)?;
+                vec![
+                    snapshot.anchor_after(Point::new(range.start.0, 0))
+                        ..snapshot.anchor_after(Point::new(range.end.0, 0)),
+                ]
+            } else {
+                editor
+                    .selections
+                    .all::<Point>(&editor.display_snapshot(cx))
+                    .iter()
+                    .map(|selection| {
+                        let anchor = snapshot.anchor_after(Point::new(selection.head().row, 0));
+                        anchor..anchor
+                    })
+                    .collect()
+            };
+
+            editor.start_transaction_at(Instant::now(), window, cx);
+            let mut result = Ok(());
+            for range in ranges {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let start = range.start.to_point(&snapshot).row;
+                let end = range.end.to_point(&snapshot).row;
+                if self.range.is_none() {
+                    editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                        s.select_ranges([Point::new(start, 0)..Point::new(start, 0)]);
+                    });
+                }
+                result = self.transfer(vim, start..end, editor, window, cx);
+                if result.is_err() {
+                    break;
+                }
+            }
+            editor.end_transaction_at(Instant::now(), cx);
+            result
+        });
+
+        if let Some(e @ Err(_)) = result {
+            let Some(workspace) = vim.workspace(window, cx) else {
+                return;
+            };
+            workspace.update(cx, |workspace, cx| {
+                e.notify_err(workspace, cx);
+            });
+        }
+    }
+
+    fn transfer(
+        &self,
+        vim: &Vim,
+        rows: Range<u32>,
+        editor: &mut Editor,
+        window: &mut Window,
+        cx: &mut Context<Editor>,
+    ) -> Result<()> {
+        // `None` is the `0` address, above the first line.
+        let destination = if self.destination == (Position::Line { row: 0, offset: 0 }) {
+            None
+        } else {
+            Some(self.destination.buffer_row(vim, editor, window, cx)?.0)
+        };
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let max_row = snapshot.max_row().0;
+        let (start, end) = (rows.start, rows.end);
+        if self.is_move
+            && let Some(row) = destination
+            && (start..end).contains(&row)
+        {
+            anyhow::bail!("cannot move a range of lines into itself");
+        }
+
+        let line_count = end - start + 1;
+        let in_place =
+            self.is_move && destination.map_or(start == 0, |row| row == end || row + 1 == start);
+        // Lines that are moved to where they already are stay untouched, but
+        // the cursor still goes to the last of them.
+        let cursor_row = if in_place {
+            end
+        } else {
+            let text = snapshot
+                .text_for_range(
+                    Point::new(start, 0)..Point::new(end, snapshot.line_len(MultiBufferRow(end))),
+                )
+                .collect::<String>();
+            let (insert_at, new_text) = match destination {
+                None => (Point::zero(), format!("{text}\n")),
+                Some(row) if row < max_row => (Point::new(row + 1, 0), format!("{text}\n")),
+                Some(_) => (snapshot.max_point(), format!("\n{text}")),
+            };
+            let mut edits = vec![(insert_at..insert_at, new_text)];
+            if self.is_move {
+                let removed = if end < max_row {
+                    Point::new(start, 0)..Point::new(end + 1, 0)
+                } else {
+                    Point::new(start - 1, snapshot.line_len(MultiBufferRow(start - 1)))
+                        ..snapshot.max_point()
+                };
+                edits.push((removed, String::new()));
+                edits.sort_by_key(|(range, _)| range.start);
+            }
+            editor.edit(edits, cx);
+
+            match destination {
+                Some(row) if self.is_move && row > end => row,
+                Some(row) => row + line_count,
+                None => line_count - 1,
+            }
+        };
+
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let cursor = Point::new(
+            cursor_row,
+            snapshot
+                .indent_size_for_line(MultiBufferRow(cursor_row))
+                .len,
+        );
+        editor.change_selections(Default::default(), window, cx, |s| {
+            s.select_ranges([cursor..cursor]);
+        });
+        Ok(())
+    }
+}
+
+/// Strips any leading part of `suffix` from `rest`, so that `co`, `cop` and
+/// `copy` are all accepted.
+fn strip_abbreviated<'a>(rest: &'a str, suffix: &str) -> &'a str {
+    let len = rest
+        .chars()
+        .zip(suffix.chars())
+        .take_while(|(a, b)| a == b)
+        .count();
+    &rest[len..]
+}
+
+/// Runs a command for each buffer (`:bufdo`), pane (`:windo`), or quickfix
+/// entry (`:cdo`) or file (`:cfdo`) in turn.
+#[derive(Clone, Debug, PartialEq, Action)]
+#[action(namespace = vim, no_json, no_register)]
+pub(crate) struct BatchCommand {
+    targets: BatchTargets,
+    action: WrappedAction,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum BatchTargets {
+    /// Each editor in the active pane. Zed has no argument list, so `:argdo`
+    /// uses these too.
+    Buffers,
+    /// The active editor of each pane.
+    Panes,
+    /// Each project search match or, outside of a project search, each
+    /// primary diagnostic in the active editor.
+    Entries,
+    /// Each file containing one of the entries, opened at its first entry.
+    Files,
+}
+
+enum BatchTarget {
+    Item {
+        pane: Entity<Pane>,
+        item: Box<dyn ItemHandle>,
+    },
+    Buffer {
+        buffer: Entity<Buffer>,
+        position: Point,
+    },
+    Position {
+        editor: Entity<Editor>,
+        anchor: Anchor,
+    },
+}
+
+impl BatchCommand {
+    pub(crate) fn parse(
+        query: &str,
+        workspace: &WeakEntity<Workspace>,
+        cx: &mut App,
+    ) -> Option<Box<dyn Action>> {
+        let (name, command) = query.split_once(' ')?;
+        let targets = match name {
+            "bufd" | "bufdo" | "argdo" => BatchTargets::Buffers,
+            "windo" => BatchTargets::Panes,
+            "cdo" | "ld" | "ldo" => BatchTargets::Entries,
+            "cfdo" | "lfdo" => BatchTargets::Files,
+            _ => return None,
+        };
+        let action = command_interceptor(command.trim(), workspace.clone(), cx)
+            .now_or_never()?
+            .results
+            .into_iter()
+            .next()?
+            .action;
+        Some(
+            BatchCommand {
+                targets,
+                action: WrappedAction(action),
+            }
+            .boxed_clone(),
+        )
+    }
+
+    pub fn run(&self, vim: &mut Vim, window: &mut Window, cx: &mut Context<Vim>) {
+        let Some((workspace, editor)) = vim.workspace(window, cx).zip(vim.editor()) else {
+            return;
+        };
+        let targets = match self.targets {
+            BatchTargets::Buffers => {
+                let Some(pane) = vim.pane(window, cx) else {
+                    return;
+                };
+                pane.read(cx)
+                    .items()
+                    .filter(|item| item.act_as::<Editor>(cx).is_some())
+                    .map(|item| BatchTarget::Item {
+                        pane: pane.clone(),
+                        item: item.boxed_clone(),
+                    })
+                    .collect::<Vec<_>>()
+            }
+            BatchTargets::Panes => workspace
+                .read(cx)
+                .panes()
+                .iter()
+                .filter_map(|pane| {
+                    let item = pane.read(cx).active_item()?;
+                    item.act_as::<Editor>(cx)?;
+                    Some(BatchTarget::Item {
+                        pane: pane.clone(),
+                        item,
+                    })
+                })
+                .collect(),
+            BatchTargets::Entries => Self::quickfix_entries(&workspace, &editor, cx)
+                .into_iter()
+                .map(|anchor| BatchTarget::Position {
+                    editor: editor.clone(),
+                    anchor,
+                })
+                .collect(),
+            BatchTargets::Files => {
+                let multi_buffer = editor.read(cx).buffer().read(cx);
+                let snapshot = multi_buffer.snapshot(cx);
+                let mut targets = Vec::new();
+                let mut seen_buffers = HashSet::default();
+                for anchor in Self::quickfix_entries(&workspace, &editor, cx) {
+                    let Some((text_anchor, buffer_snapshot)) =
+                        snapshot.anchor_to_buffer_anchor(anchor)
+                    else {
+                        continue;
+                    };
+                    let buffer_id = buffer_snapshot.remote_id();
+                    if seen_buffers.insert(buffer_id)
+                        && let Some(buffer) = multi_buffer.buffer(buffer_id)
+                    {
+                        targets.push(BatchTarget::Buffer {
+                            buffer,
+                            position: buffer_snapshot.summary_for_anchor(&text_anchor),
+                        });
+                    }
+                }
+                targets
+            }
+        };
+
+        if targets.is_empty() {
+            workspace.update(cx, |workspace, cx| {
+                Err::<(), _>(anyhow!("no {} to run the command on", self.targets.noun()))
+                    .notify_err(workspace, cx);
+            });
+            return;
+        }
+
+        let action = self.action.clone();
+        cx.spawn_in(window, async move |_, cx| {
+            for target in targets {
+                let task = cx.update(|window, cx| {
+                    let editor = target.activate(&workspace, window, cx)?;
+                    // Draw so that the command is dispatched to the newly
+                    // focused editor.
+                    window.draw(cx).clear();
+                    Some(Self::run_in_editor(&editor, &*action, window, cx))
+                });
+                match task {
+                    Ok(Some(task)) => task.await.log_err(),
+                    Ok(None) => None,
+                    Err(_) => return,
+                };
+                // Yield to the executor, so that the effects of running the command are
+                // handled before the next target is activated.
+                cx.background_spawn(async {}).await;
+            }
+        })
+        .detach();
+    }
+
+    fn quickfix_entries(
+        workspace: &Entity<Workspace>,
+        editor: &Entity<Editor>,
+        cx: &App,
+    ) -> Vec<Anchor> {
+        if let Some(search_view) = workspace.read(cx).active_item_as::<ProjectSearchView>(cx)
+            && search_view.read(cx).results_editor() == editor
+        {
+            return search_view
+                .read(cx)
+                .get_matches(cx)
+                .into_iter()
+                .map(|range| range.start)
+                .collect();
+        }
+        let snapshot = editor.read(cx).buffer().read(cx).snapshot(cx);
+        snapshot
+            .diagnostics_in_range::<Point>(Point::zero()..snapshot.max_point())
+            .filter(|entry| entry.diagnostic.is_primary)
+            .map(|entry| snapshot.anchor_after(entry.range.start))
+            .collect()
+    }
+
+    /// Runs the command in the given editor, returning a task that completes
+    /// once substitutions and `:normal` keystrokes have been applied.
+    fn run_in_editor(
+        editor: &Entity<Editor>,
+        action: &dyn Action,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Task<Result<()>> {
+        let Some(vim) = editor
+            .read(cx)
+            .addon::<VimAddon>()
+            .map(|addon| addon.entity.clone())
+        else {
+            return Task::ready(Ok(()));
+        };
+        if let Some(replace) = action.as_any().downcast_ref::<ReplaceCommand>() {
+            vim.update(cx, |vim, cx| vim.run_replace_command(replace, window, cx))
+        } else if let Some(norm) = action.as_any().downcast_ref::<VimNorm>() {
+            let task = vim.update(cx, |vim, cx| vim.run_norm(norm, window, cx));
+            cx.spawn(async move |_| {
+                task.await;
+                Ok(())
+            })
+        } else {
+            editor.focus_handle(cx).dispatch_action(action, window, cx);
+            Task::ready(Ok(()))
+        }
+    }
+}
+
+impl BatchTargets {
+    fn noun(&self) -> &'static str {
+        match self {
+            BatchTargets::Buffers => "buffers",
+            BatchTargets::Panes => "windows",
+            BatchTargets::Entries => "quickfix entries",
+            BatchTargets::Files => "quickfix files",
+        }
+    }
+}
+
+impl BatchTarget {
+    /// Focuses the editor for the target, placing the cursor at it.
+    fn activate(
+        &self,
+        workspace: &Entity<Workspace>,
+        window: &mut Window,
+        cx: &mut App,
+    ) -> Option<Entity<Editor>> {
+        match self {
+            BatchTarget::Item { pane, item } => {
+                let editor = item.act_as::<Editor>(cx)?;
+                pane.update(cx, |pane, cx| {
+                    let ix = pane.index_for_item(item.as_ref())?;
+                    pane.activate_item(ix, true, true, window, cx);
+                    Some(())
+                })?;
+                Some(editor)
+            }
+            BatchTarget::Buffer { buffer, position } => {
+                let editor = workspace.update(cx, |workspace, cx| {
+                    let pane = workspace.active_pane().clone();
+                    workspace.open_project_item::<Editor>(
+                        pane,
+                        buffer.clone(),
+                        true,
+                        true,
+                        true,
+                        true,
+                        window,
+                        cx,
+                    )
+                });
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Default::default(), window, cx, |s| {
+                        s.select_ranges([*position..*position]);
+                    });
+                });
+                Some(editor)
+            }
+            BatchTarget::Position { editor, anchor } => {
+                editor.update(cx, |editor, cx| {
+                    editor.change_selections(Default::default(), window, cx, |s| {
+                        s.select_anchor_ranges([*anchor..*anchor]);
+                    });
+                });
+                window.focus(&editor.focus_handle(cx), cx);
+                Some(editor.clone())
+            }
+        }
+    }
+}
+
 /// Executes a shell command and returns the output.
 #[derive(Clone, Debug, PartialEq, Action)]
 #[action(namespace = vim, no_json, no_register)]
@@ -2302,6 +2728,92 @@ pub struct ShellExec {
 }
 
 impl Vim {
+    /// Runs `:normal`, returning a task that completes once its keystrokes
+    /// have been dispatched.
+    fn run_norm(
+        &mut self,
+        action: &VimNorm,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<()> {
+        let keystrokes = action
+            .command
+            .chars()
+            .map(|c| Keystroke::parse(&c.to_string()).unwrap())
+            .collect();
+        self.switch_mode(Mode::Normal, true, window, cx);
+        if let Some(override_rows) = &action.override_rows {
+            self.update_editor(cx, |_, editor, cx| {
+                editor.change_selections(SelectionEffects::no_scroll(), window, cx, |s| {
+                    s.replace_cursors_with(|map| {
+                        override_rows
+                            .iter()
+                            .map(|row| Point::new(*row, 0).to_display_point(map))
+                            .collect()
+                    });
+                });
+            });
+        } else if let Some(range) = &action.range {
+            let result = self.update_editor(cx, |vim, editor, cx| {
+                let range = range.buffer_range(vim, editor, window, cx)?;
+                editor.change_selections(
+                    SelectionEffects::no_scroll().nav_history(false),
+                    window,
+                    cx,
+                    |s| {
+                        s.select_ranges(
+                            (range.start.0..=range.end.0)
+                                .map(|line| Point::new(line, 0)..Point::new(line, 0)),
+                        );
+                    },
+                );
+                anyhow::Ok(())
+            });
+            if let Some(Err(err)) = result {
+                log::error!("Error selecting range: {}", err);
+                return Task::ready(());
+            }
+        };
+
+        let Some(workspace) = self.workspace(window, cx) else {
+            return Task::ready(());
+        };
+        let task = workspace.update(cx, |workspace, cx| {
+            workspace.send_keystrokes_impl(keystrokes, window, cx)
+        });
+        let had_range = action.range.is_some();
+        let had_override = action.override_rows.is_some();
+
+        cx.spawn_in(window, async move |vim, cx| {
+            task.await;
+            vim.update_in(cx, |vim, window, cx| {
+                if matches!(vim.mode, Mode::Insert | Mode::Replace) {
+                    vim.normal_before(&Default::default(), window, cx);
+                } else {
+                    vim.switch_mode(Mode::Normal, true, window, cx);
+                }
+                if had_override || had_range {
+                    vim.update_editor(cx, |_, editor, cx| {
+                        editor.change_selections(SelectionEffects::default(), window, cx, |s| {
+                            s.select_anchor_ranges([s.newest_anchor().range()]);
+                        });
+                        if let Some(tx_id) = editor
+                            .buffer()
+                            .update(cx, |multi, cx| multi.last_transaction_id(cx))
+                        {
+                            let last_sel = editor.selections.disjoint_anchors_arc();
+                            editor.modify_transaction_selection_history(tx_id, |old| {
+                                old.0 = old.0.get(..1).unwrap_or(&[]).into();
+                                old.1 = Some(last_sel);
+                            });
+                        }
+                    });
+                }
+            })
+            .log_err();
+        })
+    }
+
     pub fn cancel_running_command(&mut self, window: &mut Window, cx: &mut Context<Self>) {
         if self.running_command.take().is_some() {
             self.update_editor(cx, |_, editor, cx| {
@@ -2649,6 +3161,7 @@ impl ShellExec {
 mod test {
     use std::path::{Path, PathBuf};
 
+    use super::parse_undo_tree_offset;
     use crate::{
         VimAddon,
         state::Mode,
@@ -2657,6 +3170,7 @@ mod test {
     use editor::{Editor, EditorSettings};
     use gpui::{Context, TestAppContext};
     use indoc::indoc;
+    use search::ProjectSearchView;
     use settings::Settings;
     use util::path;
     use workspace::{OpenOptions, Workspace};
@@ -3294,6 +3808,163 @@ mod test {
         "});
     }
 
+    #[gpui::test]
+    async fn test_copy_and_move_lines(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                ˇa
+                b
+                c
+                d"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": t . enter");
+        cx.assert_state("a\nˇa\nb\nc\nd", Mode::Normal);
+
+        cx.simulate_keystrokes(": 1 , 2 m $ enter");
+        cx.assert_state("b\nc\nd\na\nˇa", Mode::Normal);
+
+        cx.simulate_keystrokes(": m 0 enter");
+        cx.assert_state("ˇa\nb\nc\nd\na", Mode::Normal);
+
+        cx.simulate_keystrokes(": 2 c o p y space $ enter");
+        cx.assert_state("a\nb\nc\nd\na\nˇb", Mode::Normal);
+
+        cx.simulate_keystrokes(": 2 , 3 m 3 enter");
+        cx.assert_state("a\nb\nˇc\nd\na\nb", Mode::Normal);
+
+        cx.set_state("ˇ1\n2\n3", Mode::Normal);
+        cx.simulate_keystrokes(": g / ^ / m 0 enter");
+        cx.run_until_parked();
+        cx.assert_state("ˇ3\n2\n1", Mode::Normal);
+
+        cx.simulate_keystrokes("u");
+        cx.assert_state("ˇ1\n2\n3", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_bufdo(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb", Mode::Normal);
+        cx.simulate_keystrokes(": n e w enter");
+        cx.simulate_keystrokes(": b u f d o space n o r m space I x enter");
+        cx.run_until_parked();
+
+        cx.workspace(|workspace, _, cx| {
+            let texts = workspace
+                .items_of_type::<Editor>(cx)
+                .map(|editor| editor.read(cx).text(cx))
+                .collect::<Vec<_>>();
+            assert_eq!(texts, ["xa\nb", "x"]);
+        });
+    }
+
+    #[gpui::test]
+    async fn test_windo(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇa\nb", Mode::Normal);
+        cx.simulate_keystrokes(": v n e w enter");
+        cx.simulate_keystrokes(": w i n d o space n o r m space I x enter");
+        cx.run_until_parked();
+
+        cx.workspace(|workspace, _, cx| {
+            assert_eq!(workspace.panes().len(), 2);
+            let texts = workspace
+                .panes()
+                .iter()
+                .map(|pane| {
+                    let editor = pane.read(cx).active_item().unwrap().act_as::<Editor>(cx);
+                    editor.unwrap().read(cx).text(cx)
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(texts, ["xa\nb", "x"]);
+        });
+    }
+
+    /// Searches the project for `foo`, in two files besides the test context's
+    /// own, and focuses the search results.
+    async fn search_project_for_foo(cx: &mut VimTestContext) {
+        let fs = cx.workspace(|workspace, _, cx| workspace.project().read(cx).fs().clone());
+        fs.as_fake()
+            .insert_file(path!("/root/dir/a.rs"), b"foo one\nfoo two".to_vec())
+            .await;
+        fs.as_fake()
+            .insert_file(path!("/root/dir/b.rs"), b"three foo".to_vec())
+            .await;
+        cx.run_until_parked();
+
+        let search_view = cx.workspace(|workspace, window, cx| {
+            ProjectSearchView::deploy_search(
+                workspace,
+                &workspace::DeploySearch::default(),
+                window,
+                cx,
+            );
+            workspace.active_item_as::<ProjectSearchView>(cx).unwrap()
+        });
+        search::project_search::perform_project_search(&search_view, "foo", &mut cx);
+        cx.update(|window, cx| {
+            let results_editor = search_view.read(cx).results_editor().clone();
+            window.focus(&results_editor.focus_handle(cx), cx);
+        });
+        cx.run_until_parked();
+    }
+
+    fn buffer_text(cx: &mut VimTestContext, path: &str) -> String {
+        cx.workspace(|workspace, _, cx| {
+            let project = workspace.project().read(cx);
+            let project_path = project.find_project_path(path, cx).unwrap();
+            project
+                .get_open_buffer(&project_path, cx)
+                .unwrap()
+                .read(cx)
+                .text()
+        })
+    }
+
+    #[gpui::test]
+    async fn test_cdo_over_project_search(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.set_state("ˇa", Mode::Normal);
+        search_project_for_foo(&mut cx).await;
+
+        // Each match is visited in turn, and files without matches are left alone.
+        cx.simulate_keystrokes(": c d o space n o r m space A ! enter");
+        cx.run_until_parked();
+
+        assert_eq!(
+            buffer_text(&mut cx, path!("/root/dir/a.rs")),
+            "foo one!\nfoo two!"
+        );
+        assert_eq!(buffer_text(&mut cx, path!("/root/dir/b.rs")), "three foo!");
+        assert_eq!(buffer_text(&mut cx, path!("/root/dir/file.rs")), "a");
+    }
+
+    #[gpui::test]
+    async fn test_cfdo_over_project_search(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.set_state("ˇa", Mode::Normal);
+        search_project_for_foo(&mut cx).await;
+
+        // Each file is opened once, at its first match.
+        cx.simulate_keystrokes(": c f d o space n o r m space A ! enter");
+        cx.run_until_parked();
+
+        assert_eq!(
+            buffer_text(&mut cx, path!("/root/dir/a.rs")),
+            "foo one!\nfoo two"
+        );
+        assert_eq!(buffer_text(&mut cx, path!("/root/dir/b.rs")), "three foo!");
+        cx.workspace(|workspace, _, cx| {
+            let active_editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            assert_eq!(active_editor.read(cx).text(cx), "three foo!");
+        });
+    }
+
     #[gpui::test]
     async fn test_command_tabnew(cx: &mut TestAppContext) {
         let mut cx = VimTestContext::new(cx, true).await;
@@ -3639,4 +4310,43 @@ mod test {
             Mode::VisualLine,
         );
     }
+
+    #[gpui::test]
+    async fn test_earlier_and_later(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇabc", Mode::Normal);
+        cx.simulate_keystrokes("A 1 escape");
+        cx.simulate_keystrokes("a 2 escape");
+        cx.simulate_keystrokes("u");
+        cx.simulate_keystrokes("a 3 escape");
+        assert_eq!(cx.buffer_text(), "abc13");
+
+        cx.simulate_keystrokes("g -");
+        assert_eq!(cx.buffer_text(), "abc12");
+        cx.simulate_keystrokes("2 g -");
+        assert_eq!(cx.buffer_text(), "abc");
+        cx.simulate_keystrokes("g +");
+        assert_eq!(cx.buffer_text(), "abc1");
+
+        cx.simulate_keystrokes(": l a t e r space 2");
+        cx.simulate_keystrokes("enter");
+        assert_eq!(cx.buffer_text(), "abc13");
+
+        // Going back further than the history reaches restores the original text.
+        cx.simulate_keystrokes(": e a r l i e r space 1 h");
+        cx.simulate_keystrokes("enter");
+        assert_eq!(cx.buffer_text(), "");
+    }
+
+    #[test]
+    fn test_parse_undo_tree_offset() {
+        assert_eq!(parse_undo_tree_offset("3"), Some((Some(3), None)));
+        assert_eq!(parse_undo_tree_offset("10s"), Some((None, Some(10))));
+        assert_eq!(parse_undo_tree_offset(" 5m "), Some((None, Some(300))));
+        assert_eq!(parse_undo_tree_offset("2h"), Some((None, Some(7200))));
+        assert_eq!(parse_undo_tree_offset("1d"), Some((None, Some(86400))));
+        assert_eq!(parse_undo_tree_offset("1f"), None);
+        assert_eq!(parse_undo_tree_offset("m"), None);
+    }
 }
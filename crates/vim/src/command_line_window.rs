@@ -0,0 +1,301 @@
+//! The command-line window, opened with `q:`, `q/` and `q?` or with `ctrl-f`
+//! from the `:` prompt. It lists the ex command or search history in an
+//! editor, and `enter` runs the line under the cursor in the editor the
+//! window was opened from.
+
+use anyhow::anyhow;
+use command_palette::CommandPalette;
+use editor::{Editor, SelectionEffects};
+use gpui::{
+    App, AppContext as _, Context, Entity, Focusable as _, Subscription, WeakEntity, Window,
+    actions,
+};
+use language::{Buffer, BufferEvent, Point};
+use multi_buffer::{MultiBuffer, MultiBufferRow};
+use search::BufferSearchBar;
+use workspace::{SplitDirection, Workspace, notifications::NotifyResultExt as _};
+
+use crate::{Vim, command::command_interceptor, normal::search::FindCommand};
+
+actions!(
+    vim,
+    [
+        /// Opens the command-line window with the ex command history,
+        /// continuing the command being typed in the command palette.
+        OpenCommandLineWindow,
+        /// Runs the line under the cursor in the command-line window.
+        ExecuteCommandLine,
+    ]
+);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum CommandLineKind {
+    Command,
+    Search { backwards: bool },
+}
+
+impl CommandLineKind {
+    pub(crate) fn for_register(register: char) -> Option<Self> {
+        match register {
+            ':' => Some(Self::Command),
+            '/' => Some(Self::Search { backwards: false }),
+            '?' => Some(Self::Search { backwards: true }),
+            _ => None,
+        }
+    }
+
+    fn title(&self) -> &'static str {
+        match self {
+            Self::Command => "[Command Line]",
+            Self::Search { .. } => "[Search History]",
+        }
+    }
+}
+
+/// Marks an editor as a command-line window, adding `VimCommandLineWindow` to
+/// its key context.
+struct CommandLineWindowAddon {
+    kind: CommandLineKind,
+    target: WeakEntity<Editor>,
+    _keep_clean: Subscription,
+}
+
+impl editor::Addon for CommandLineWindowAddon {
+    fn extend_key_context(&self, key_context: &mut gpui::KeyContext, _: &App) {
+        key_context.add("VimCommandLineWindow");
+    }
+
+    fn to_any(&self) -> &dyn std::any::Any {
+        self
+    }
+}
+
+pub(crate) fn init(cx: &mut App) {
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &OpenCommandLineWindow, window, cx| {
+            let query = workspace
+                .active_modal::<CommandPalette>(cx)
+                .map(|command_palette| command_palette.read(cx).query(cx))
+                .unwrap_or_default();
+            workspace.hide_modal(window, cx);
+            let Some(target) = workspace.active_item_as::<Editor>(cx) else {
+                return;
+            };
+            let history = CommandPalette::recent_queries(cx);
+            open_command_line_window(
+                workspace,
+                target.downgrade(),
+                CommandLineKind::Command,
+                history,
+                query,
+                window,
+                cx,
+            );
+        });
+    })
+    .detach();
+}
+
+pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
+    Vim::action(editor, cx, |vim, _: &ExecuteCommandLine, window, cx| {
+        vim.execute_command_line(window, cx)
+    });
+}
+
+impl Vim {
+    pub(crate) fn open_command_line_window(
+        &mut self,
+        kind: CommandLineKind,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let Some((workspace, editor)) = self.workspace(window, cx).zip(self.editor()) else {
+            return;
+        };
+        let history = match kind {
+            CommandLineKind::Command => CommandPalette::recent_queries(cx),
+            CommandLineKind::Search { .. } => self
+                .pane(window, cx)
+                .and_then(|pane| {
+                    pane.read(cx)
+                        .toolbar()
+                        .read(cx)
+                        .item_of_type::<BufferSearchBar>()
+                })
+                .map(|search_bar| {
+                    search_bar
+                        .read(cx)
+                        .search_history()
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+        workspace.update(cx, |workspace, cx| {
+            open_command_line_window(
+                workspace,
+                editor.downgrade(),
+                kind,
+                history,
+                String::new(),
+                window,
+                cx,
+            )
+        });
+    }
+
+    fn execute_command_line(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some((workspace, window_editor)) = self.workspace(window, cx).zip(self.editor()) else {
+            return;
+        };
+        let Some((kind, target, line)) = window_editor.update(cx, |editor, cx| {
+            let addon = editor.addon::<CommandLineWindowAddon>()?;
+            let (kind, target) = (addon.kind, addon.target.clone());
+            let row = editor
+                .selections
+                .newest::<Point>(&editor.display_snapshot(cx))
+                .head()
+                .row;
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let line = snapshot
+                .text_for_range(
+                    Point::new(row, 0)..Point::new(row, snapshot.line_len(MultiBufferRow(row))),
+                )
+                .collect::<String>();
+            Some((kind, target, line))
+        }) else {
+            return;
+        };
+
+        workspace.update(cx, |workspace, cx| {
+            if let Some(pane) = workspace.pane_for(&window_editor) {
+                pane.update(cx, |pane, cx| {
+                    pane.remove_item(window_editor.entity_id(), false, true, window, cx)
+                });
+            }
+            let Some(target) = target
+                .upgrade()
+                .or_else(|| workspace.active_item_as::<Editor>(cx))
+            else {
+                return;
+            };
+            let focus_handle = target.focus_handle(cx);
+            window.focus(&focus_handle, cx);
+
+            let line = line.trim().to_string();
+            if line.is_empty() {
+                return;
+            }
+            match kind {
+                CommandLineKind::Search { backwards } => {
+                    let action = FindCommand {
+                        query: line,
+                        backwards,
+                    };
+                    focus_handle.dispatch_action(&action, window, cx);
+                }
+                CommandLineKind::Command => {
+                    let intercepted = command_interceptor(&line, cx.weak_entity(), cx);
+                    cx.spawn_in(window, async move |workspace, cx| {
+                        let item = intercepted.await.results.into_iter().next();
+                        let Some(item) = item
+                            .ok_or_else(|| anyhow!("Not an editor command: {line}"))
+                            .notify_workspace_async_err(workspace, cx)
+                        else {
+                            return;
+                        };
+                        cx.update(|window, cx| {
+                            CommandPalette::record_query(item.string, line, cx);
+                            focus_handle.dispatch_action(&*item.action, window, cx);
+                        })
+                        .ok();
+                    })
+                    .detach();
+                }
+            }
+        });
+    }
+}
+
+fn open_command_line_window(
+    workspace: &mut Workspace,
+    target: WeakEntity<Editor>,
+    kind: CommandLineKind,
+    history: Vec<String>,
+    query: String,
+    window: &mut Window,
+    cx: &mut Context<Workspace>,
+) {
+    let mut text = history.join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text.push_str(&query);
+
+    let buffer = cx.new(|cx| Buffer::local(text, cx));
+    let multibuffer =
+        cx.new(|cx| MultiBuffer::singleton(buffer.clone(), cx).with_title(kind.title().into()));
+    let project = workspace.project().clone();
+    let editor: Entity<Editor> = cx.new(|cx| {
+        let mut editor = Editor::for_multibuffer(multibuffer, Some(project), window, cx);
+        // The window is a scratch buffer, so keep it from prompting to be
+        // saved when it's closed.
+        let keep_clean = cx.subscribe(&buffer, |_, buffer, event, cx| {
+            if let BufferEvent::Edited { .. } = event {
+                buffer.update(cx, |buffer, cx| buffer.did_save(buffer.version(), None, cx));
+            }
+        });
+        editor.register_addon(CommandLineWindowAddon {
+            kind,
+            target,
+            _keep_clean: keep_clean,
+        });
+        editor
+    });
+    workspace.split_item(SplitDirection::Down, Box::new(editor.clone()), window, cx);
+    editor.update(cx, |editor, cx| {
+        let end = editor.buffer().read(cx).snapshot(cx).max_point();
+        editor.change_selections(SelectionEffects::default(), window, cx, |s| {
+            s.select_ranges([end..end])
+        });
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use editor::Editor;
+    use gpui::TestAppContext;
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_search_command_line_window(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.set_state("ˇone two one three two", Mode::Normal);
+        cx.simulate_keystrokes("/ t w o enter");
+        cx.run_until_parked();
+        cx.simulate_keystrokes("/ t h r e e enter");
+        cx.run_until_parked();
+        cx.assert_state("one two one ˇthree two", Mode::Normal);
+
+        cx.simulate_keystrokes("q /");
+        cx.run_until_parked();
+        let history = cx.workspace(|workspace, _, cx| {
+            workspace
+                .active_item_as::<Editor>(cx)
+                .unwrap()
+                .read(cx)
+                .text(cx)
+        });
+        assert_eq!(history, "two\nthree\n");
+
+        cx.simulate_keystrokes("g g enter");
+        cx.run_until_parked();
+        cx.assert_state("one two one three ˇtwo", Mode::Normal);
+        assert_eq!(
+            cx.workspace(|workspace, _, cx| workspace.items(cx).count()),
+            1
+        );
+    }
+}
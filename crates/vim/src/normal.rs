@@ -96,6 +96,10 @@ actions!(
         Redo,
         /// Undoes all changes to the most recently changed line.
         UndoLastLine,
+        /// Goes to an older text state, across undo branches (with count support).
+        Earlier,
+        /// Goes to a newer text state, across undo branches (with count support).
+        Later,
         /// Go to tab page (with count support).
         GoToTab,
         /// Go to previous tab page (with count support).
@@ -254,6 +258,34 @@ pub(crate) fn register(editor: &mut Editor, cx: &mut Context<Vim>) {
             }
         });
     });
+    Vim::action(editor, cx, |vim, _: &Earlier, window, cx| {
+        let steps = Vim::take_count(cx);
+        Vim::take_forced_motion(cx);
+        vim.update_editor(cx, |_, editor, cx| {
+            editor.earlier(
+                &editor::actions::Earlier {
+                    steps,
+                    seconds: None,
+                },
+                window,
+                cx,
+            );
+        });
+    });
+    Vim::action(editor, cx, |vim, _: &Later, window, cx| {
+        let steps = Vim::take_count(cx);
+        Vim::take_forced_motion(cx);
+        vim.update_editor(cx, |_, editor, cx| {
+            editor.later(
+                &editor::actions::Later {
+                    steps,
+                    seconds: None,
+                },
+                window,
+                cx,
+            );
+        });
+    });
     Vim::action(editor, cx, |vim, _: &UndoLastLine, window, cx| {
         Vim::take_forced_motion(cx);
         vim.update_editor(cx, |vim, editor, cx| {
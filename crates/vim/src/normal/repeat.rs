@@ -2,6 +2,7 @@ use std::{cell::RefCell, rc::Rc};
 
 use crate::{
     Vim,
+    command_line_window::CommandLineKind,
     insert::NormalBefore,
     motion::Motion,
     normal::InsertBefore,
@@ -191,6 +192,11 @@ impl Vim {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        if let Some(kind) = CommandLineKind::for_register(register) {
+            self.clear_operator(window, cx);
+            self.open_command_line_window(kind, window, cx);
+            return;
+        }
         let globals = Vim::globals(cx);
         globals.recording_register = Some(register);
         globals.recordings.remove(&register);
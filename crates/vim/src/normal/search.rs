@@ -1,5 +1,6 @@
+use anyhow::Result;
 use editor::{Editor, EditorSettings};
-use gpui::{Action, Context, Window, actions};
+use gpui::{Action, Context, Task, Window, actions};
 use language::Point;
 use schemars::JsonSchema;
 use search::{BufferSearchBar, SearchOptions, buffer_search};
@@ -554,13 +555,25 @@ impl Vim {
         window: &mut Window,
         cx: &mut Context<Self>,
     ) {
+        self.run_replace_command(action, window, cx)
+            .detach_and_log_err(cx);
+    }
+
+    /// Runs a substitution, returning a task that completes once the matches
+    /// have been replaced.
+    pub(crate) fn run_replace_command(
+        &mut self,
+        action: &ReplaceCommand,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) -> Task<Result<()>> {
         let replacement = action.replacement.clone();
         let Some(((pane, workspace), editor)) = self
             .pane(window, cx)
             .zip(self.workspace(window, cx))
             .zip(self.editor())
         else {
-            return;
+            return Task::ready(Ok(()));
         };
         if let Some(result) = self.update_editor(cx, |vim, editor, cx| {
             let range = action.range.buffer_range(vim, editor, window, cx)?;
@@ -579,7 +592,7 @@ impl Vim {
         let Some(search_bar) = pane.update(cx, |pane, cx| {
             pane.toolbar().read(cx).item_of_type::<BufferSearchBar>()
         }) else {
-            return;
+            return Task::ready(Ok(()));
         };
         let mut options = SearchOptions::REGEX;
         let search = search_bar.update(cx, |search_bar, cx| {
@@ -630,9 +643,11 @@ impl Vim {
                 window,
                 cx,
             );
-            return;
+            return Task::ready(Ok(()));
         }
-        let Some(search) = search else { return };
+        let Some(search) = search else {
+            return Task::ready(Ok(()));
+        };
         let search_bar = search_bar.downgrade();
         cx.spawn_in(window, async move |vim, cx| {
             search.await?;
@@ -665,7 +680,6 @@ impl Vim {
                 search_bar.set_search_options(options, cx);
             })
         })
-        .detach_and_log_err(cx);
     }
 }
 
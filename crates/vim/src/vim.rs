@@ -5,6 +5,7 @@ mod test;
 
 mod change_list;
 mod command;
+mod command_line_window;
 mod digraph;
 mod helix;
 mod indent;
@@ -17,6 +18,7 @@ mod replace;
 mod rewrap;
 mod state;
 mod surrounds;
+mod vimrc;
 mod visual;
 
 use crate::normal::paste::Paste as VimPaste;
@@ -49,12 +51,13 @@ pub use settings::{
     ModeContent, Settings, SettingsStore, UseSystemClipboard, update_settings_file,
 };
 use state::{Mode, Operator, RecordedSelection, SearchState, VimGlobals};
-use std::{mem, ops::Range, sync::Arc};
+use std::{mem, ops::Range, path::PathBuf, sync::Arc};
 use surrounds::SurroundsType;
 use theme_settings::ThemeSettings;
 use ui::{IntoElement, SharedString, px};
 use vim_mode_setting::HelixModeSetting;
 use vim_mode_setting::VimModeSetting;
+pub use vimrc::{LoadedVimrc, vimrc_key_bindings};
 use workspace::{self, Pane, Workspace};
 
 use crate::{
@@ -278,6 +281,8 @@ actions!(
 /// Initializes the `vim` crate.
 pub fn init(cx: &mut App) {
     VimGlobals::register(cx);
+    vimrc::init(cx);
+    command_line_window::init(cx);
 
     cx.observe_new(Vim::register).detach();
 
@@ -969,6 +974,7 @@ impl Vim {
             helix::register(editor, cx);
             motion::register(editor, cx);
             command::register(editor, cx);
+            command_line_window::register(editor, cx);
             replace::register(editor, cx);
             indent::register(editor, cx);
             rewrap::register(editor, cx);
@@ -2145,6 +2151,7 @@ struct VimSettings {
     pub custom_digraphs: HashMap<String, Arc<str>>,
     pub highlight_on_yank_duration: u64,
     pub cursor_shape: CursorShapeSettings,
+    pub vimrc: Option<PathBuf>,
 }
 
 /// Cursor shape configuration for insert mode.
@@ -2231,6 +2238,7 @@ impl Settings for VimSettings {
             custom_digraphs: vim.custom_digraphs.unwrap(),
             highlight_on_yank_duration: vim.highlight_on_yank_duration.unwrap(),
             cursor_shape: vim.cursor_shape.unwrap().into(),
+            vimrc: vim.vimrc.as_deref().map(vimrc::expand_home),
         }
     }
 }
@@ -0,0 +1,1175 @@
+//! Loads mappings and options from a vimrc or init.vim.
+//!
+//! Only the parts of a vimrc that have a counterpart in Zed are translated:
+//! `map` and its mode-specific variants become key bindings that send the
+//! mapped keystrokes, `set` options are written to the user's settings file,
+//! and `let mapleader` picks the keys used for `<leader>`. Everything else is
+//! listed in the vimrc report.
+
+use anyhow::{Context as _, Result, anyhow, bail};
+use fs::Fs;
+use futures::StreamExt as _;
+use gpui::{
+    Action, App, AppContext as _, BorrowAppContext, Context, DismissEvent, Global, KeyBinding,
+    Keystroke, Task, Window, actions,
+};
+use serde_json::{Map, Value, json};
+use settings::{
+    CurrentLineHighlight, KeybindSource, KeymapFile, KeymapFileLoadResult, RelativeLineNumbers,
+    Settings, SettingsContent, SettingsStore, ShowWhitespaceSetting, SoftWrap, UseSystemClipboard,
+    update_settings_file,
+};
+use std::{collections::BTreeSet, num::NonZeroU32, path::PathBuf};
+use util::paths::{PathExt as _, home_dir};
+use vim_mode_setting::VimModeSetting;
+use workspace::{
+    Workspace,
+    notifications::{
+        NotificationId, NotifyResultExt as _, show_app_notification,
+        simple_message_notification::MessageNotification,
+    },
+};
+
+use crate::VimSettings;
+
+actions!(
+    vim,
+    [
+        /// Lists the lines of the loaded vimrc that could not be translated.
+        ShowVimrcReport,
+    ]
+);
+
+// in the workspace namespace so it's not filtered out when vim is disabled.
+actions!(
+    workspace,
+    [
+        /// Enables vim mode and loads mappings and options from your vimrc.
+        ImportVimrc,
+    ]
+);
+
+const NORMAL_VISUAL_OPERATOR_CONTEXT: &str =
+    "(vim_mode == normal || vim_mode == visual || vim_mode == operator) && !menu";
+const NORMAL_CONTEXT: &str = "vim_mode == normal && !menu";
+const VISUAL_CONTEXT: &str = "vim_mode == visual && !menu";
+const OPERATOR_CONTEXT: &str = "vim_mode == operator";
+const INSERT_CONTEXT: &str = "vim_mode == insert";
+
+/// The mapping commands, with the length of their shortest abbreviation and
+/// the context of the bindings they create.
+const MAP_COMMANDS: &[(&str, usize, Option<&str>)] = &[
+    ("map", 3, Some(NORMAL_VISUAL_OPERATOR_CONTEXT)),
+    ("noremap", 2, Some(NORMAL_VISUAL_OPERATOR_CONTEXT)),
+    ("nmap", 2, Some(NORMAL_CONTEXT)),
+    ("nnoremap", 2, Some(NORMAL_CONTEXT)),
+    ("vmap", 2, Some(VISUAL_CONTEXT)),
+    ("vnoremap", 2, Some(VISUAL_CONTEXT)),
+    ("xmap", 2, Some(VISUAL_CONTEXT)),
+    ("xnoremap", 2, Some(VISUAL_CONTEXT)),
+    ("omap", 2, Some(OPERATOR_CONTEXT)),
+    ("onoremap", 3, Some(OPERATOR_CONTEXT)),
+    ("imap", 2, Some(INSERT_CONTEXT)),
+    ("inoremap", 3, Some(INSERT_CONTEXT)),
+    ("smap", 4, None),
+    ("snoremap", 4, None),
+    ("cmap", 2, None),
+    ("cnoremap", 3, None),
+    ("tmap", 3, None),
+    ("tnoremap", 3, None),
+    ("lmap", 2, None),
+    ("lnoremap", 2, None),
+];
+
+/// `set` arguments that match Zed's behavior already.
+const IGNORED_OPTIONS: &[&str] = &[
+    "nocompatible",
+    "nocp",
+    "hidden",
+    "hid",
+    "encoding=utf-8",
+    "enc=utf-8",
+    "incsearch",
+    "is",
+    "autoindent",
+    "ai",
+    "ttyfast",
+    "tf",
+    "showcmd",
+    "sc",
+    "wildmenu",
+    "wmnu",
+    "backspace=indent,eol,start",
+    "bs=indent,eol,start",
+    "bs=2",
+];
+
+/// The supported options and their abbreviations.
+const OPTIONS: &[(&str, &str)] = &[
+    ("number", "nu"),
+    ("relativenumber", "rnu"),
+    ("wrap", "wrap"),
+    ("tabstop", "ts"),
+    ("shiftwidth", "sw"),
+    ("expandtab", "et"),
+    ("ignorecase", "ic"),
+    ("smartcase", "scs"),
+    ("gdefault", "gd"),
+    ("scrolloff", "so"),
+    ("clipboard", "cb"),
+    ("cursorline", "cul"),
+    ("list", "list"),
+    ("colorcolumn", "cc"),
+    ("textwidth", "tw"),
+];
+
+pub(crate) fn init(cx: &mut App) {
+    cx.set_global(LoadedVimrc::default());
+    watch_configured_vimrc(cx);
+    cx.observe_global::<SettingsStore>(watch_configured_vimrc)
+        .detach();
+
+    cx.observe_new(|workspace: &mut Workspace, _, _| {
+        workspace.register_action(|workspace, _: &ImportVimrc, window, cx| {
+            import_vimrc(workspace, window, cx);
+        });
+
+        workspace.register_action(|_, _: &ShowVimrcReport, _, cx| {
+            let text = cx.global::<LoadedVimrc>().report();
+            cx.emit(workspace::Event::OpenBundledFile {
+                text: text.into(),
+                title: "Vimrc Report",
+                language: "Markdown",
+            });
+        });
+    })
+    .detach();
+}
+
+/// Expands a leading `~` in the `vim.vimrc` setting.
+pub(crate) fn expand_home(path: &str) -> PathBuf {
+    if path == "~" {
+        home_dir().clone()
+    } else if let Some(relative_path) = path.strip_prefix("~/") {
+        home_dir().join(relative_path)
+    } else {
+        PathBuf::from(path)
+    }
+}
+
+/// Key bindings for the mappings in the loaded vimrc.
+pub fn vimrc_key_bindings(cx: &App) -> Vec<KeyBinding> {
+    let Some(vimrc) = cx
+        .try_global::<LoadedVimrc>()
+        .and_then(|loaded| loaded.vimrc.as_ref())
+    else {
+        return Vec::new();
+    };
+    if vimrc.mappings.is_empty() {
+        return Vec::new();
+    }
+
+    let mut key_bindings = match KeymapFile::load(&vimrc.keymap_json(), cx) {
+        KeymapFileLoadResult::Success { key_bindings } => key_bindings,
+        KeymapFileLoadResult::SomeFailedToLoad {
+            key_bindings,
+            error_message,
+        } => {
+            log::error!("failed to load some vimrc mappings: {error_message}");
+            key_bindings
+        }
+        KeymapFileLoadResult::JsonParseFailure { error } => {
+            log::error!("failed to load vimrc mappings: {error}");
+            Vec::new()
+        }
+    };
+    for key_binding in &mut key_bindings {
+        key_binding.set_meta(KeybindSource::Vim.meta());
+    }
+    key_bindings
+}
+
+/// The vimrc from the `vim.vimrc` setting, reloaded whenever the file changes.
+#[derive(Default)]
+pub struct LoadedVimrc {
+    path: Option<PathBuf>,
+    vimrc: Option<Vimrc>,
+    _watch: Option<Task<()>>,
+}
+
+impl Global for LoadedVimrc {}
+
+impl LoadedVimrc {
+    fn report(&self) -> String {
+        let Some(path) = &self.path else {
+            return "# Vimrc Report\n\nNo vimrc is loaded. Run `workspace: import vimrc` or set \
+                `vim.vimrc` in your settings to load one.\n"
+                .to_string();
+        };
+        let mut report = format!("# Vimrc Report\n\n`{}`", path.compact().display());
+        let Some(vimrc) = &self.vimrc else {
+            report.push_str(" is still loading.\n");
+            return report;
+        };
+
+        report.push_str(&format!(": {}.\n", vimrc.summary()));
+        if vimrc.unsupported.is_empty() {
+            report.push_str("\nEvery line was translated.\n");
+        } else {
+            report.push_str("\n## Unsupported lines\n\n");
+            for unsupported in &vimrc.unsupported {
+                report.push_str(&format!(
+                    "- Line {}: `{}`\n  {}\n",
+                    unsupported.row, unsupported.line, unsupported.reason
+                ));
+            }
+        }
+        report
+    }
+}
+
+fn watch_configured_vimrc(cx: &mut App) {
+    let path = if VimModeSetting::get_global(cx).0 {
+        VimSettings::get_global(cx).vimrc.clone()
+    } else {
+        None
+    };
+    if cx.global::<LoadedVimrc>().path == path {
+        return;
+    }
+
+    let Some(path) = path else {
+        cx.set_global(LoadedVimrc::default());
+        return;
+    };
+
+    let fs = <dyn Fs>::global(cx);
+    let (mut contents, watcher) =
+        settings::watch_config_file(cx.background_executor(), fs, path.clone());
+    let watch = cx.spawn({
+        let path = path.clone();
+        async move |cx| {
+            let _watcher = watcher;
+            let mut initial_load = true;
+            while let Some(content) = contents.next().await {
+                let vimrc = Vimrc::parse(&content);
+                cx.update(|cx| {
+                    if !initial_load && !vimrc.unsupported.is_empty() {
+                        show_vimrc_notification(
+                            format!(
+                                "Reloaded {}. {}.",
+                                path.compact().display(),
+                                vimrc.unsupported_summary()
+                            ),
+                            true,
+                            cx,
+                        );
+                    }
+                    let previous_options = cx
+                        .global::<LoadedVimrc>()
+                        .vimrc
+                        .as_ref()
+                        .map(|loaded| loaded.settings.clone());
+                    apply_vimrc_options(previous_options, vimrc.settings.clone(), cx);
+                    cx.update_global::<LoadedVimrc, _>(|loaded, _| loaded.vimrc = Some(vimrc));
+                });
+                initial_load = false;
+            }
+        }
+    });
+    cx.set_global(LoadedVimrc {
+        path: Some(path),
+        vimrc: None,
+        _watch: Some(watch),
+    });
+}
+
+/// Writes the options translated from a vimrc to the user's settings file,
+/// where they can be seen and changed like any other setting.
+///
+/// When the vimrc is first loaded, settings that are already set are kept.
+/// When it's reloaded, only the settings whose options changed since
+/// `previous_options` are overwritten, and the settings of removed options
+/// are removed, unless they were changed in the settings file since.
+fn apply_vimrc_options(
+    previous_options: Option<SettingsContent>,
+    options: SettingsContent,
+    cx: &mut App,
+) {
+    let current = cx
+        .global::<SettingsStore>()
+        .raw_user_settings()
+        .map(|user_settings| user_settings.content.as_ref().clone())
+        .unwrap_or_default();
+    match merge_vimrc_options(&current, previous_options.as_ref(), &options) {
+        Ok(merged) if merged == current => return,
+        Ok(_) => {}
+        Err(error) => {
+            log::error!("failed to apply vimrc options: {error:#}");
+            return;
+        }
+    }
+
+    let fs = <dyn Fs>::global(cx);
+    update_settings_file(fs, cx, move |settings, _| {
+        match merge_vimrc_options(settings, previous_options.as_ref(), &options) {
+            Ok(merged) => *settings = merged,
+            Err(error) => log::error!("failed to apply vimrc options: {error:#}"),
+        }
+    });
+}
+
+fn merge_vimrc_options(
+    settings: &SettingsContent,
+    previous_options: Option<&SettingsContent>,
+    options: &SettingsContent,
+) -> Result<SettingsContent> {
+    let mut merged = serde_json::to_value(settings)?;
+    let previous_options = previous_options.map(serde_json::to_value).transpose()?;
+    let options = serde_json::to_value(options)?;
+    merge_vimrc_option_values(&mut merged, previous_options.as_ref(), Some(&options));
+    Ok(serde_json::from_value(merged)?)
+}
+
+/// Merges the options of a vimrc into `settings` key by key. Without any
+/// `previous` options, only keys missing from `settings` are added.
+fn merge_vimrc_option_values(
+    settings: &mut Value,
+    previous: Option<&Value>,
+    options: Option<&Value>,
+) {
+    let previous_object = previous.and_then(Value::as_object);
+    let options_object = options.and_then(Value::as_object);
+    if previous_object.is_some() || options_object.is_some() {
+        if !settings.is_object() {
+            if options_object.is_none() {
+                return;
+            }
+            *settings = Value::Object(Map::new());
+        }
+        let Some(settings) = settings.as_object_mut() else {
+            return;
+        };
+        let keys = previous_object
+            .into_iter()
+            .chain(options_object)
+            .flat_map(|object| object.keys().cloned())
+            .collect::<BTreeSet<_>>();
+        for key in keys {
+            let previous = previous_object.and_then(|object| object.get(&key));
+            let option = options_object.and_then(|object| object.get(&key));
+            let value = settings.entry(key.clone()).or_insert(Value::Null);
+            merge_vimrc_option_values(value, previous, option);
+            if value.is_null() || value.as_object().is_some_and(Map::is_empty) {
+                settings.remove(&key);
+            }
+        }
+        return;
+    }
+
+    match (previous, options) {
+        (None, Some(option)) if settings.is_null() => *settings = option.clone(),
+        (Some(previous), Some(option)) if previous != option => *settings = option.clone(),
+        (Some(previous), None) if settings == previous => *settings = Value::Null,
+        _ => {}
+    }
+}
+
+fn import_vimrc(workspace: &mut Workspace, _: &mut Window, cx: &mut Context<Workspace>) {
+    let fs = workspace.app_state().fs.clone();
+    let configured_path = VimSettings::get_global(cx).vimrc.clone();
+    cx.spawn(async move |workspace, cx| {
+        let Some((path, content)) = find_vimrc(fs.as_ref(), configured_path)
+            .await
+            .notify_workspace_async_err(workspace, cx)
+        else {
+            return;
+        };
+        let vimrc = Vimrc::parse(&content);
+        let vimrc_setting = path.compact().to_string_lossy().into_owned();
+        cx.update(|cx| {
+            update_settings_file(fs, cx, move |settings, _| {
+                settings.vim_mode = Some(true);
+                if let Some(helix_mode) = &mut settings.helix_mode {
+                    *helix_mode = false;
+                }
+                settings.vim.get_or_insert_default().vimrc = Some(vimrc_setting);
+            });
+
+            let mut message = format!(
+                "Imported {} from {}.",
+                vimrc.summary(),
+                path.compact().display()
+            );
+            if !vimrc.unsupported.is_empty() {
+                message.push_str(&format!(" {}.", vimrc.unsupported_summary()));
+            }
+            show_vimrc_notification(message, !vimrc.unsupported.is_empty(), cx);
+        });
+    })
+    .detach();
+}
+
+async fn find_vimrc(fs: &dyn Fs, configured_path: Option<PathBuf>) -> Result<(PathBuf, String)> {
+    let home = home_dir();
+    let candidates = configured_path
+        .into_iter()
+        .chain([
+            home.join(".vimrc"),
+            home.join(".vim/vimrc"),
+            home.join(".config/nvim/init.vim"),
+            home.join("_vimrc"),
+        ])
+        .collect::<Vec<_>>();
+    for candidate in &candidates {
+        if fs.is_file(candidate).await {
+            let content = fs
+                .load(candidate)
+                .await
+                .with_context(|| format!("Error loading vimrc from {}", candidate.display()))?;
+            return Ok((candidate.clone(), content));
+        }
+    }
+    Err(anyhow!(
+        "No vimrc found, expected to find it in one of the following paths:\n{}",
+        candidates
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join("\n")
+    ))
+}
+
+fn show_vimrc_notification(message: String, show_report_button: bool, cx: &mut App) {
+    struct VimrcNotification;
+
+    show_app_notification(
+        NotificationId::unique::<VimrcNotification>(),
+        cx,
+        move |cx| {
+            let message = message.clone();
+            cx.new(|cx| {
+                let notification = MessageNotification::new(message, cx);
+                if show_report_button {
+                    notification
+                        .primary_message("Show Unsupported Lines")
+                        .primary_on_click(|window, cx| {
+                            window.dispatch_action(ShowVimrcReport.boxed_clone(), cx);
+                            cx.emit(DismissEvent);
+                        })
+                } else {
+                    notification
+                }
+            })
+        },
+    );
+}
+
+/// The translatable contents of a vimrc.
+#[derive(Debug, Default)]
+pub(crate) struct Vimrc {
+    mappings: Vec<Mapping>,
+    settings: SettingsContent,
+    option_count: usize,
+    unsupported: Vec<UnsupportedLine>,
+    leader: String,
+    local_leader: String,
+    tab_stop: Option<u32>,
+    shift_width: Option<u32>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Mapping {
+    context: &'static str,
+    keystrokes: String,
+    /// The keystrokes to send, or `None` for `<Nop>`.
+    sends: Option<String>,
+}
+
+#[derive(Debug, PartialEq)]
+struct UnsupportedLine {
+    row: usize,
+    line: String,
+    reason: String,
+}
+
+impl Vimrc {
+    pub(crate) fn parse(content: &str) -> Self {
+        let mut vimrc = Self {
+            leader: "\\".to_string(),
+            local_leader: "\\".to_string(),
+            ..Default::default()
+        };
+        for (row, line) in logical_lines(content) {
+            if let Err(error) = vimrc.parse_line(&line) {
+                vimrc.unsupported.push(UnsupportedLine {
+                    row,
+                    line: line.trim().to_string(),
+                    reason: error.to_string(),
+                });
+            }
+        }
+        vimrc
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "{} mapping{} and {} option{}",
+            self.mappings.len(),
+            if self.mappings.len() == 1 { "" } else { "s" },
+            self.option_count,
+            if self.option_count == 1 { "" } else { "s" },
+        )
+    }
+
+    fn unsupported_summary(&self) -> String {
+        match self.unsupported.len() {
+            1 => "1 line could not be translated".to_string(),
+            count => format!("{count} lines could not be translated"),
+        }
+    }
+
+    /// Serializes the mappings as a keymap file, with a section per context.
+    fn keymap_json(&self) -> String {
+        let mut sections: Vec<(&str, Map<String, Value>)> = Vec::new();
+        for mapping in &self.mappings {
+            let ix = match sections
+                .iter()
+                .position(|(context, _)| *context == mapping.context)
+            {
+                Some(ix) => ix,
+                None => {
+                    sections.push((mapping.context, Map::new()));
+                    sections.len() - 1
+                }
+            };
+            let action = match &mapping.sends {
+                Some(keystrokes) => json!(["workspace::SendKeystrokes", keystrokes]),
+                None => Value::Null,
+            };
+            sections[ix].1.insert(mapping.keystrokes.clone(), action);
+        }
+        Value::Array(
+            sections
+                .into_iter()
+                .map(|(context, bindings)| json!({ "context": context, "bindings": bindings }))
+                .collect(),
+        )
+        .to_string()
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<()> {
+        let line = line.trim().trim_start_matches(':').trim_start();
+        if line.is_empty() || line.starts_with('"') {
+            return Ok(());
+        }
+
+        let name_len = line
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(line.len());
+        let (name, rest) = line.split_at(name_len);
+        let (bang, args) = match rest.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        if name.is_empty() || !(args.is_empty() || args.starts_with(char::is_whitespace)) {
+            bail!("not a supported command");
+        }
+        let args = args.trim();
+
+        if let Some((command, context)) = map_command(name) {
+            let context = match (context, bang) {
+                (_, true) if command == "map" || command == "noremap" => INSERT_CONTEXT,
+                (Some(context), false) => context,
+                (None, false) => bail!("`{command}` mappings are not supported"),
+                (_, true) => bail!("`{command}!` is not supported"),
+            };
+            return self.parse_map(context, args);
+        }
+
+        match name {
+            "se" | "set" | "setg" | "setglobal" => self.parse_set(strip_comment(args)),
+            "let" => self.parse_let(args),
+            "sy" | "syn" | "syntax" if matches!(strip_comment(args), "on" | "enable") => Ok(()),
+            "filet" | "filetype" if strip_comment(args).ends_with("on") => Ok(()),
+            _ => bail!("`{name}` is not supported"),
+        }
+    }
+
+    fn parse_map(&mut self, context: &'static str, args: &str) -> Result<()> {
+        let mut args = args;
+        'arguments: loop {
+            for argument in ["<silent>", "<nowait>", "<unique>", "<special>"] {
+                if let Some(rest) = strip_prefix_ignore_case(args, argument) {
+                    args = rest.trim_start();
+                    continue 'arguments;
+                }
+            }
+            for argument in ["<buffer>", "<expr>", "<script>"] {
+                if strip_prefix_ignore_case(args, argument).is_some() {
+                    bail!("`{argument}` mappings are not supported");
+                }
+            }
+            break;
+        }
+
+        let (lhs, rhs) = args
+            .split_once(char::is_whitespace)
+            .map(|(lhs, rhs)| (lhs, rhs.trim()))
+            .unwrap_or((args, ""));
+        if lhs.is_empty() || rhs.is_empty() {
+            bail!("listing mappings is not supported");
+        }
+        if let Some(ix) = rhs.find('|')
+            && !rhs[..ix].ends_with('\\')
+        {
+            bail!("commands after `|` are not supported");
+        }
+
+        let keystrokes = self.translate_keys(lhs)?;
+        let sends = if rhs.eq_ignore_ascii_case("<nop>") {
+            None
+        } else {
+            let sends = self.translate_keys(rhs)?;
+            if sends.starts_with(&keystrokes) {
+                bail!("mappings that start with their own keys are not supported");
+            }
+            Some(sends.join(" "))
+        };
+
+        self.mappings.push(Mapping {
+            context,
+            keystrokes: keystrokes.join(" "),
+            sends,
+        });
+        Ok(())
+    }
+
+    fn parse_let(&mut self, args: &str) -> Result<()> {
+        let (name, value) = args
+            .split_once('=')
+            .context("expected `let {name} = {value}`")?;
+        let name = name.trim();
+        let name = name.strip_prefix("g:").unwrap_or(name);
+        let leader = match name {
+            "mapleader" => &mut self.leader,
+            "maplocalleader" => &mut self.local_leader,
+            _ => bail!("`let {name}` is not supported"),
+        };
+        *leader = parse_string(value.trim())?;
+        Ok(())
+    }
+
+    fn parse_set(&mut self, args: &str) -> Result<()> {
+        if args.is_empty() {
+            bail!("listing options is not supported");
+        }
+        let errors = args
+            .split_whitespace()
+            .filter_map(|argument| self.set_option(argument).err())
+            .map(|error| error.to_string())
+            .collect::<Vec<_>>();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(errors.join("; ")))
+        }
+    }
+
+    fn set_option(&mut self, argument: &str) -> Result<()> {
+        if IGNORED_OPTIONS.contains(&argument) {
+            return Ok(());
+        }
+
+        let (name, value) = match argument.split_once(['=', ':']) {
+            Some((name, value)) => (name, Some(value)),
+            None => (argument, None),
+        };
+        if name.ends_with(['+', '-', '^']) {
+            bail!("`{argument}` is not supported, use `=` instead");
+        }
+        let (option, enable) = if let Some(option) = option_name(name) {
+            (option, true)
+        } else if let Some(option) = name.strip_prefix("no").and_then(option_name) {
+            (option, false)
+        } else {
+            bail!("the `{name}` option is not supported");
+        };
+
+        let settings = &mut self.settings;
+        let language = &mut settings.project.all_languages.defaults;
+        match (option, value) {
+            ("number", None) => {
+                settings.editor.gutter.get_or_insert_default().line_numbers = Some(enable);
+            }
+            ("relativenumber", None) => {
+                settings.editor.relative_line_numbers = Some(if enable {
+                    RelativeLineNumbers::Enabled
+                } else {
+                    RelativeLineNumbers::Disabled
+                });
+            }
+            ("wrap", None) => {
+                language.soft_wrap = Some(if enable {
+                    SoftWrap::EditorWidth
+                } else {
+                    SoftWrap::None
+                });
+            }
+            ("expandtab", None) => language.hard_tabs = Some(!enable),
+            ("ignorecase", None) => {
+                settings
+                    .editor
+                    .search
+                    .get_or_insert_default()
+                    .case_sensitive = Some(!enable);
+            }
+            ("smartcase", None) => settings.editor.use_smartcase_search = Some(enable),
+            ("gdefault", None) => settings.vim.get_or_insert_default().gdefault = Some(enable),
+            ("cursorline", None) => {
+                settings.editor.current_line_highlight = Some(if enable {
+                    CurrentLineHighlight::All
+                } else {
+                    CurrentLineHighlight::None
+                });
+            }
+            ("list", None) => {
+                language.show_whitespaces = Some(if enable {
+                    ShowWhitespaceSetting::All
+                } else {
+                    ShowWhitespaceSetting::None
+                });
+            }
+            (_, Some(_)) if !enable => bail!("`{argument}` is not a valid option"),
+            ("tabstop", Some(value)) => {
+                let tab_stop = parse_number(option, value)?;
+                self.tab_stop = Some(tab_stop);
+                if self.shift_width.is_none_or(|shift_width| shift_width == 0) {
+                    language.tab_size = Some(tab_size(tab_stop)?);
+                }
+            }
+            ("shiftwidth", Some(value)) => {
+                let shift_width = parse_number(option, value)?;
+                self.shift_width = Some(shift_width);
+                let width = if shift_width > 0 {
+                    Some(shift_width)
+                } else {
+                    self.tab_stop
+                };
+                if let Some(width) = width {
+                    language.tab_size = Some(tab_size(width)?);
+                }
+            }
+            ("scrolloff", Some(value)) => {
+                settings.editor.vertical_scroll_margin = Some(parse_number(option, value)? as f32);
+            }
+            ("textwidth", Some(value)) => {
+                language.preferred_line_length = Some(parse_number(option, value)?);
+            }
+            ("clipboard", Some(value)) => {
+                let use_system_clipboard = if value.is_empty() {
+                    UseSystemClipboard::Never
+                } else if value
+                    .split(',')
+                    .any(|register| register == "unnamed" || register == "unnamedplus")
+                {
+                    UseSystemClipboard::Always
+                } else {
+                    bail!("`clipboard={value}` is not supported");
+                };
+                settings.vim.get_or_insert_default().use_system_clipboard =
+                    Some(use_system_clipboard);
+            }
+            ("colorcolumn", Some(value)) => {
+                if value.is_empty() {
+                    language.show_wrap_guides = Some(false);
+                } else {
+                    let columns = value
+                        .split(',')
+                        .map(|column| {
+                            if column.starts_with(['+', '-']) {
+                                bail!("columns relative to `textwidth` are not supported");
+                            }
+                            column
+                                .parse::<usize>()
+                                .with_context(|| format!("`{column}` is not a column"))
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    language.show_wrap_guides = Some(true);
+                    language.wrap_guides = Some(columns);
+                }
+            }
+            (_, None) => bail!("the `{option}` option needs a value"),
+            (_, Some(_)) => bail!("the `{option}` option doesn't take a value"),
+        }
+        self.option_count += 1;
+        Ok(())
+    }
+
+    /// Translates keys in vim's notation into keystrokes in Zed's.
+    fn translate_keys(&self, keys: &str) -> Result<Vec<String>> {
+        let mut keystrokes = Vec::new();
+        let mut rest = keys;
+        while let Some(c) = rest.chars().next() {
+            if c == '<'
+                && let Some(end) = rest.find('>')
+                && end > 2
+                && !rest[1..end].contains(['<', ' '])
+            {
+                let name = &rest[1..end];
+                rest = &rest[end + 1..];
+                match name.to_ascii_lowercase().as_str() {
+                    "leader" => keystrokes.extend(self.translate_keys(&self.leader)?),
+                    "localleader" => keystrokes.extend(self.translate_keys(&self.local_leader)?),
+                    _ => keystrokes.push(translate_key_notation(name)?),
+                }
+                continue;
+            }
+
+            rest = &rest[c.len_utf8()..];
+            let c = match c {
+                '\\' if rest.starts_with('|') => {
+                    rest = &rest[1..];
+                    '|'
+                }
+                c => c,
+            };
+            let keystroke = match c {
+                ' ' => "space".to_string(),
+                '\t' => "tab".to_string(),
+                c if c.is_ascii_uppercase() => format!("shift-{}", c.to_ascii_lowercase()),
+                c => c.to_string(),
+            };
+            Keystroke::parse(&keystroke).map_err(|_| anyhow!("`{c}` is not a supported key"))?;
+            keystrokes.push(keystroke);
+        }
+        Ok(keystrokes)
+    }
+}
+
+/// Translates a key in angle brackets, like `<C-w>` or `<CR>`.
+fn translate_key_notation(name: &str) -> Result<String> {
+    let mut modifiers = String::new();
+    let mut key = name;
+    while key.len() > 2 && key.as_bytes()[1] == b'-' {
+        modifiers.push_str(match key.as_bytes()[0].to_ascii_lowercase() {
+            b'c' => "ctrl-",
+            b's' => "shift-",
+            b'm' | b'a' => "alt-",
+            b'd' => "cmd-",
+            _ => bail!("`<{name}>` is not a supported key"),
+        });
+        key = &key[2..];
+    }
+
+    let lowercase_key = key.to_ascii_lowercase();
+    let key = match lowercase_key.as_str() {
+        "cr" | "enter" | "return" => "enter".to_string(),
+        "esc" => "escape".to_string(),
+        "bs" => "backspace".to_string(),
+        "del" => "delete".to_string(),
+        "lt" => "<".to_string(),
+        "bar" => "|".to_string(),
+        "bslash" => "\\".to_string(),
+        "space" | "tab" | "up" | "down" | "left" | "right" | "home" | "end" | "pageup"
+        | "pagedown" | "insert" => lowercase_key.clone(),
+        "plug" | "sid" | "snr" | "cmd" => bail!("`<{name}>` mappings are not supported"),
+        function_key
+            if function_key
+                .strip_prefix('f')
+                .is_some_and(|number| number.parse::<u8>().is_ok()) =>
+        {
+            lowercase_key.clone()
+        }
+        _ if key.chars().count() == 1 && !modifiers.is_empty() => {
+            let c = key.chars().next().unwrap_or_default();
+            if c.is_ascii_uppercase() && !modifiers.contains("ctrl-") {
+                modifiers.push_str("shift-");
+            }
+            c.to_ascii_lowercase().to_string()
+        }
+        _ => bail!("`<{name}>` is not a supported key"),
+    };
+
+    let keystroke = format!("{modifiers}{key}");
+    Keystroke::parse(&keystroke).map_err(|_| anyhow!("`<{name}>` is not a supported key"))?;
+    Ok(keystroke)
+}
+
+/// Joins lines continued with a leading backslash, keeping the row of the
+/// first one.
+fn logical_lines(content: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    for (ix, line) in content.lines().enumerate() {
+        if let Some(continuation) = line.trim_start().strip_prefix('\\')
+            && let Some((_, previous_line)) = lines.last_mut()
+        {
+            previous_line.push_str(continuation);
+        } else {
+            lines.push((ix + 1, line.to_string()));
+        }
+    }
+    lines
+}
+
+fn map_command(name: &str) -> Option<(&'static str, Option<&'static str>)> {
+    MAP_COMMANDS
+        .iter()
+        .find(|(command, min_len, _)| name.len() >= *min_len && command.starts_with(name))
+        .map(|(command, _, context)| (*command, *context))
+}
+
+fn option_name(name: &str) -> Option<&'static str> {
+    OPTIONS
+        .iter()
+        .find(|(option, abbreviation)| name == *option || name == *abbreviation)
+        .map(|(option, _)| *option)
+}
+
+fn strip_prefix_ignore_case<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    text.get(..prefix.len())
+        .filter(|start| start.eq_ignore_ascii_case(prefix))
+        .map(|_| &text[prefix.len()..])
+}
+
+fn strip_comment(args: &str) -> &str {
+    args.split_once('"').map_or(args, |(args, _)| args).trim()
+}
+
+fn parse_number(option: &str, value: &str) -> Result<u32> {
+    value
+        .parse()
+        .with_context(|| format!("the `{option}` option needs a number"))
+}
+
+fn tab_size(width: u32) -> Result<NonZeroU32> {
+    NonZeroU32::new(width).context("the tab size must be greater than zero")
+}
+
+/// Parses a vim string literal, like `","` or `"\<Space>"`.
+fn parse_string(value: &str) -> Result<String> {
+    if let Some(rest) = value.strip_prefix('\'') {
+        let end = rest.find('\'').context("unterminated string")?;
+        return Ok(rest[..end].to_string());
+    }
+
+    let rest = value
+        .strip_prefix('"')
+        .context("only string values are supported")?;
+    let mut string = String::new();
+    let mut chars = rest.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return Ok(string),
+            '\\' => {
+                let remaining = chars.as_str();
+                if let Some(after) = strip_prefix_ignore_case(remaining, "<space>") {
+                    string.push(' ');
+                    chars = after.chars();
+                } else if let Some(escaped) = chars.next() {
+                    string.push(escaped);
+                }
+            }
+            c => string.push(c),
+        }
+    }
+    bail!("unterminated string")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn unsupported_rows(vimrc: &Vimrc) -> Vec<usize> {
+        vimrc.unsupported.iter().map(|line| line.row).collect()
+    }
+
+    #[test]
+    fn test_mappings() {
+        let vimrc = Vimrc::parse(indoc! {r#"
+            " comment
+            let mapleader = "\<Space>"
+            nnoremap <leader>w :w<CR>
+            nnoremap Y y$
+            vnoremap <silent> <C-j> :m '>+1<CR>gv
+            inoremap jk <Esc>
+            map! <C-a> <Home>
+            nmap <leader>x <Nop>
+            let maplocalleader=','
+            nn <localleader>G gg
+        "#});
+
+        assert_eq!(unsupported_rows(&vimrc), Vec::<usize>::new());
+        assert_eq!(
+            vimrc.mappings,
+            [
+                Mapping {
+                    context: NORMAL_CONTEXT,
+                    keystrokes: "space w".into(),
+                    sends: Some(": w enter".into()),
+                },
+                Mapping {
+                    context: NORMAL_CONTEXT,
+                    keystrokes: "shift-y".into(),
+                    sends: Some("y $".into()),
+                },
+                Mapping {
+                    context: VISUAL_CONTEXT,
+                    keystrokes: "ctrl-j".into(),
+                    sends: Some(": m space ' > + 1 enter g v".into()),
+                },
+                Mapping {
+                    context: INSERT_CONTEXT,
+                    keystrokes: "j k".into(),
+                    sends: Some("escape".into()),
+                },
+                Mapping {
+                    context: INSERT_CONTEXT,
+                    keystrokes: "ctrl-a".into(),
+                    sends: Some("home".into()),
+                },
+                Mapping {
+                    context: NORMAL_CONTEXT,
+                    keystrokes: "space x".into(),
+                    sends: None,
+                },
+                Mapping {
+                    context: NORMAL_CONTEXT,
+                    keystrokes: ", shift-g".into(),
+                    sends: Some("g g".into()),
+                },
+            ]
+        );
+
+        let keymap: Value = serde_json::from_str(&vimrc.keymap_json()).unwrap();
+        assert_eq!(
+            keymap[0],
+            json!({
+                "context": NORMAL_CONTEXT,
+                "bindings": {
+                    "space w": ["workspace::SendKeystrokes", ": w enter"],
+                    "shift-y": ["workspace::SendKeystrokes", "y $"],
+                    "space x": null,
+                    ", shift-g": ["workspace::SendKeystrokes", "g g"],
+                }
+            })
+        );
+        assert_eq!(keymap.as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_options() {
+        let vimrc = Vimrc::parse(indoc! {r#"
+            set nocompatible
+            set number relativenumber
+            set tabstop=8 shiftwidth=4 expandtab
+            set ignorecase smartcase " search
+            se nowrap so=5 cc=80,100
+            set clipboard=unnamedplus
+        "#});
+
+        assert_eq!(unsupported_rows(&vimrc), Vec::<usize>::new());
+        assert_eq!(vimrc.option_count, 11);
+
+        let settings = &vimrc.settings;
+        let language = &settings.project.all_languages.defaults;
+        assert_eq!(
+            settings.editor.gutter.as_ref().unwrap().line_numbers,
+            Some(true)
+        );
+        assert_eq!(
+            settings.editor.relative_line_numbers,
+            Some(RelativeLineNumbers::Enabled)
+        );
+        assert_eq!(language.tab_size, NonZeroU32::new(4));
+        assert_eq!(language.hard_tabs, Some(false));
+        assert_eq!(
+            settings.editor.search.as_ref().unwrap().case_sensitive,
+            Some(false)
+        );
+        assert_eq!(settings.editor.use_smartcase_search, Some(true));
+        assert_eq!(language.soft_wrap, Some(SoftWrap::None));
+        assert_eq!(settings.editor.vertical_scroll_margin, Some(5.));
+        assert_eq!(language.wrap_guides, Some(vec![80, 100]));
+        assert_eq!(
+            settings.vim.as_ref().unwrap().use_system_clipboard,
+            Some(UseSystemClipboard::Always)
+        );
+    }
+
+    #[test]
+    fn test_merge_vimrc_options() {
+        let options = Vimrc::parse("set tabstop=8 number").settings;
+        let mut settings = SettingsContent::default();
+        settings.project.all_languages.defaults.tab_size = NonZeroU32::new(2);
+
+        // Settings that are already set are kept when the vimrc is first loaded.
+        let merged = merge_vimrc_options(&settings, None, &options).unwrap();
+        assert_eq!(
+            merged.project.all_languages.defaults.tab_size,
+            NonZeroU32::new(2)
+        );
+        assert_eq!(
+            merged.editor.gutter.as_ref().unwrap().line_numbers,
+            Some(true)
+        );
+
+        // Only the options that changed overwrite the settings on reload.
+        let mut settings = merged;
+        settings.editor.gutter.get_or_insert_default().line_numbers = Some(false);
+        let new_options = Vimrc::parse("set tabstop=4 number").settings;
+        let merged = merge_vimrc_options(&settings, Some(&options), &new_options).unwrap();
+        assert_eq!(
+            merged.project.all_languages.defaults.tab_size,
+            NonZeroU32::new(4)
+        );
+        assert_eq!(
+            merged.editor.gutter.as_ref().unwrap().line_numbers,
+            Some(false)
+        );
+
+        // Settings of options removed from the vimrc are removed, unless they
+        // were changed since.
+        let options = new_options;
+        let new_options = Vimrc::parse("set cursorline").settings;
+        let merged = merge_vimrc_options(&merged, Some(&options), &new_options).unwrap();
+        assert_eq!(merged.project.all_languages.defaults.tab_size, None);
+        assert_eq!(
+            merged.editor.gutter.as_ref().unwrap().line_numbers,
+            Some(false)
+        );
+        assert_eq!(
+            merged.editor.current_line_highlight,
+            new_options.editor.current_line_highlight
+        );
+        assert!(merged.editor.current_line_highlight.is_some());
+    }
+
+    #[test]
+    fn test_unsupported_lines() {
+        let vimrc = Vimrc::parse(indoc! {r#"
+            syntax on
+            colorscheme gruvbox
+            set foldmethod=marker number
+            nnoremap <buffer> x y
+            nnoremap <C-d> <C-d>zz
+            nnoremap <leader>q :q<CR>|echo
+            autocmd BufWritePre * :%s/\s\+$//e
+            call plug#begin()
+            smap x y
+            nmap <Plug>(foo) x
+        "#});
+
+        assert_eq!(unsupported_rows(&vimrc), [2, 3, 4, 5, 6, 7, 8, 9, 10]);
+        assert_eq!(
+            vimrc.unsupported[1].reason,
+            "the `foldmethod` option is not supported"
+        );
+        assert_eq!(
+            vimrc.settings.editor.gutter.as_ref().unwrap().line_numbers,
+            Some(true)
+        );
+        assert!(vimrc.mappings.is_empty());
+    }
+}
@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use anyhow::Result;
+use anyhow::{Result, anyhow};
 use cloud_llm_client::WebSearchResponse;
 use collections::HashMap;
 use gpui::{App, AppContext as _, Context, Entity, Global, SharedString, Task};
@@ -16,6 +16,41 @@ pub struct WebSearchProviderId(pub SharedString);
 pub trait WebSearchProvider {
     fn id(&self) -> WebSearchProviderId;
     fn search(&self, query: String, cx: &mut App) -> Task<Result<WebSearchResponse>>;
+
+    /// Whether searches can be made while using models from any language model
+    /// provider, rather than only the one this provider is billed through.
+    fn supports_any_model_provider(&self) -> bool {
+        true
+    }
+
+    /// The environment variable the provider reads its API key from, for
+    /// providers that can authenticate with an API key. Those keys can also be
+    /// saved in the system keychain with [`Self::store_api_key`].
+    fn api_key_env_var_name(&self) -> Option<SharedString> {
+        None
+    }
+
+    /// Loads the provider's API key and reports where it's set.
+    fn api_key_status(&self, _cx: &mut App) -> Task<WebSearchApiKeyStatus> {
+        Task::ready(WebSearchApiKeyStatus::NotSet)
+    }
+
+    /// Saves the provider's API key in the system keychain, or deletes it when
+    /// `api_key` is `None`.
+    fn store_api_key(&self, _api_key: Option<String>, _cx: &mut App) -> Task<Result<()>> {
+        Task::ready(Err(anyhow!(
+            "the {} web search provider doesn't use an API key",
+            self.id().0
+        )))
+    }
+}
+
+/// Where a web search provider's API key is set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WebSearchApiKeyStatus {
+    NotSet,
+    SystemKeychain,
+    EnvVar,
 }
 
 struct GlobalWebSearchRegistry(Entity<WebSearchRegistry>);
@@ -26,6 +61,9 @@ impl Global for GlobalWebSearchRegistry {}
 pub struct WebSearchRegistry {
     providers: HashMap<WebSearchProviderId, Arc<dyn WebSearchProvider>>,
     active_provider: Option<Arc<dyn WebSearchProvider>>,
+    /// The provider configured in the settings, which searches fall back to
+    /// when the active provider is unregistered.
+    default_provider: Option<WebSearchProviderId>,
 }
 
 impl WebSearchRegistry {
@@ -37,6 +75,11 @@ impl WebSearchRegistry {
         cx.global::<GlobalWebSearchRegistry>().0.read(cx)
     }
 
+    pub fn try_read_global(cx: &App) -> Option<&Self> {
+        cx.try_global::<GlobalWebSearchRegistry>()
+            .map(|registry| registry.0.read(cx))
+    }
+
     pub fn providers(&self) -> impl Iterator<Item = &Arc<dyn WebSearchProvider>> {
         self.providers.values()
     }
@@ -45,7 +88,10 @@ impl WebSearchRegistry {
         self.active_provider.clone()
     }
 
+    /// Makes `provider` the active provider, and the one to fall back to when
+    /// other providers are unregistered.
     pub fn set_active_provider(&mut self, provider: Arc<dyn WebSearchProvider>) {
+        self.default_provider = Some(provider.id());
         self.active_provider = Some(provider.clone());
         self.providers.insert(provider.id(), provider);
     }
@@ -66,7 +112,63 @@ impl WebSearchRegistry {
     pub fn unregister_provider(&mut self, id: WebSearchProviderId) {
         self.providers.remove(&id);
         if self.active_provider.as_ref().map(|provider| provider.id()) == Some(id) {
-            self.active_provider = None;
+            self.active_provider = self
+                .default_provider
+                .as_ref()
+                .and_then(|id| self.providers.get(id))
+                .or_else(|| {
+                    self.providers
+                        .iter()
+                        .min_by_key(|(id, _)| *id)
+                        .map(|(_, provider)| provider)
+                })
+                .cloned();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeProvider(&'static str);
+
+    impl WebSearchProvider for FakeProvider {
+        fn id(&self) -> WebSearchProviderId {
+            WebSearchProviderId(self.0.into())
+        }
+
+        fn search(&self, _query: String, _cx: &mut App) -> Task<Result<WebSearchResponse>> {
+            unimplemented!()
+        }
+    }
+
+    fn active_provider_id(registry: &WebSearchRegistry) -> Option<WebSearchProviderId> {
+        registry.active_provider().map(|provider| provider.id())
+    }
+
+    #[test]
+    fn test_unregister_falls_back_to_default_provider() {
+        let mut registry = WebSearchRegistry::default();
+        registry.set_active_provider(Arc::new(FakeProvider("searxng")));
+        registry
+            .providers
+            .insert(FakeProvider("a").id(), Arc::new(FakeProvider("a")));
+        registry.active_provider = Some(Arc::new(FakeProvider("zed")));
+        registry
+            .providers
+            .insert(FakeProvider("zed").id(), Arc::new(FakeProvider("zed")));
+
+        registry.unregister_provider(FakeProvider("zed").id());
+        assert_eq!(
+            active_provider_id(&registry),
+            Some(FakeProvider("searxng").id())
+        );
+
+        registry.unregister_provider(FakeProvider("searxng").id());
+        assert_eq!(active_provider_id(&registry), Some(FakeProvider("a").id()));
+
+        registry.unregister_provider(FakeProvider("a").id());
+        assert_eq!(active_provider_id(&registry), None);
+    }
+}
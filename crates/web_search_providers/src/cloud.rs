@@ -61,6 +61,10 @@ impl WebSearchProvider for CloudWebSearchProvider {
             perform_web_search(client, llm_api_token, organization_id, body).await
         })
     }
+
+    fn supports_any_model_provider(&self) -> bool {
+        false
+    }
 }
 
 async fn perform_web_search(
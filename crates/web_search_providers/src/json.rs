@@ -0,0 +1,220 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use cloud_llm_client::{WebSearchResponse, WebSearchResult};
+use gpui::{App, Entity, SharedString, Task};
+use http_client::{HttpClient, Url};
+use language_model::ApiKeyState;
+use serde_json::Value;
+use web_search::{WebSearchApiKeyStatus, WebSearchProvider, WebSearchProviderId};
+
+use crate::self_hosted::{api_key_state, api_key_status, get_json, load_api_key, store_api_key};
+use crate::web_search_settings::JsonWebSearchSettings;
+
+pub const JSON_WEB_SEARCH_PROVIDER_ID: &str = "json";
+const JSON_WEB_SEARCH_API_KEY_ENV_VAR_NAME: &str = "WEB_SEARCH_API_KEY";
+
+/// Searches with any HTTP endpoint that answers a `GET` request with JSON,
+/// using the field mapping from the settings to read the results.
+pub struct JsonWebSearchProvider {
+    http_client: Arc<dyn HttpClient>,
+    settings: JsonWebSearchSettings,
+    api_key_state: Entity<ApiKeyState>,
+}
+
+impl JsonWebSearchProvider {
+    pub fn new(
+        http_client: Arc<dyn HttpClient>,
+        settings: JsonWebSearchSettings,
+        cx: &mut App,
+    ) -> Self {
+        let api_key_state =
+            api_key_state(&settings.api_url, JSON_WEB_SEARCH_API_KEY_ENV_VAR_NAME, cx);
+        Self {
+            http_client,
+            settings,
+            api_key_state,
+        }
+    }
+}
+
+impl WebSearchProvider for JsonWebSearchProvider {
+    fn id(&self) -> WebSearchProviderId {
+        WebSearchProviderId(JSON_WEB_SEARCH_PROVIDER_ID.into())
+    }
+
+    fn search(&self, query: String, cx: &mut App) -> Task<Result<WebSearchResponse>> {
+        let http_client = self.http_client.clone();
+        let settings = self.settings.clone();
+        let api_key = load_api_key(&self.api_key_state, cx);
+        cx.spawn(async move |_cx| {
+            let api_key = api_key.await;
+            search_json(http_client, &settings, &query, api_key.as_deref()).await
+        })
+    }
+
+    fn api_key_env_var_name(&self) -> Option<SharedString> {
+        Some(JSON_WEB_SEARCH_API_KEY_ENV_VAR_NAME.into())
+    }
+
+    fn api_key_status(&self, cx: &mut App) -> Task<WebSearchApiKeyStatus> {
+        api_key_status(&self.api_key_state, cx)
+    }
+
+    fn store_api_key(&self, api_key: Option<String>, cx: &mut App) -> Task<Result<()>> {
+        store_api_key(&self.api_key_state, api_key, cx)
+    }
+}
+
+async fn search_json(
+    http_client: Arc<dyn HttpClient>,
+    settings: &JsonWebSearchSettings,
+    query: &str,
+    api_key: Option<&str>,
+) -> Result<WebSearchResponse> {
+    let url = Url::parse_with_params(
+        &settings.api_url,
+        &[(settings.query_parameter.as_str(), query)],
+    )
+    .with_context(|| format!("invalid web search URL {}", settings.api_url))?;
+
+    let auth_header = api_key.map(|api_key| match &settings.api_key_header {
+        Some(header) => (header.as_str(), api_key.to_string()),
+        None => ("Authorization", format!("Bearer {api_key}")),
+    });
+    let response = get_json(http_client, url, auth_header).await?;
+    parse_results(&response, settings)
+}
+
+fn parse_results(response: &Value, settings: &JsonWebSearchSettings) -> Result<WebSearchResponse> {
+    let results = lookup(response, &settings.results_path)
+        .and_then(Value::as_array)
+        .with_context(|| {
+            format!(
+                "the web search response has no array of results at `{}`",
+                settings.results_path
+            )
+        })?;
+
+    let text_field = |result: &Value, path: &str| {
+        lookup(result, path)
+            .and_then(Value::as_str)
+            .map(|text| text.trim().to_string())
+            .filter(|text| !text.is_empty())
+    };
+    Ok(WebSearchResponse {
+        results: results
+            .iter()
+            .filter_map(|result| {
+                let url = text_field(result, &settings.url_field)?;
+                Some(WebSearchResult {
+                    title: text_field(result, &settings.title_field).unwrap_or_else(|| url.clone()),
+                    text: text_field(result, &settings.text_field).unwrap_or_default(),
+                    url,
+                })
+            })
+            .take(settings.max_results)
+            .collect(),
+    })
+}
+
+/// Looks up a value by a dot-separated path of object keys and array indices,
+/// such as `data.items` or `items.0.link`. An empty path is the value itself.
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(value, |value, segment| match value {
+            Value::Object(object) => object.get(segment),
+            Value::Array(array) => array.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http_client::{FakeHttpClient, Response};
+    use serde_json::json;
+
+    fn settings() -> JsonWebSearchSettings {
+        JsonWebSearchSettings {
+            api_url: "http://search.internal/api?format=json".into(),
+            query_parameter: "query".into(),
+            api_key_header: Some("X-Api-Key".into()),
+            results_path: "data.hits".into(),
+            title_field: "name".into(),
+            url_field: "link.href".into(),
+            text_field: "summary".into(),
+            max_results: 2,
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let value = json!({ "data": { "items": [{ "link": "a" }, { "link": "b" }] } });
+        assert_eq!(lookup(&value, "data.items.1.link"), Some(&json!("b")));
+        assert_eq!(lookup(&value, ""), Some(&value));
+        assert_eq!(lookup(&value, "data.items.2"), None);
+        assert_eq!(lookup(&value, "data.items.link"), None);
+        assert_eq!(lookup(&value, "data.missing"), None);
+    }
+
+    #[test]
+    fn test_json_search() {
+        let http_client = FakeHttpClient::create(|request| async move {
+            assert_eq!(request.uri().path(), "/api");
+            assert_eq!(request.uri().query(), Some("format=json&query=zed+editor"));
+            assert_eq!(request.headers().get("X-Api-Key").unwrap(), "secret");
+            let body = json!({
+                "data": {
+                    "hits": [
+                        { "name": "No link" },
+                        {
+                            "name": "Zed",
+                            "link": { "href": "https://zed.dev" },
+                            "summary": "A code editor",
+                        },
+                        { "link": { "href": "https://zed.dev/docs" } },
+                        { "name": "Blog", "link": { "href": "https://zed.dev/blog" } },
+                    ]
+                }
+            });
+            Ok(Response::builder()
+                .status(200)
+                .body(body.to_string().into())
+                .unwrap())
+        });
+
+        let response = futures::executor::block_on(search_json(
+            http_client,
+            &settings(),
+            "zed editor",
+            Some("secret"),
+        ))
+        .unwrap();
+        let results = response
+            .results
+            .iter()
+            .map(|result| {
+                (
+                    result.title.as_str(),
+                    result.url.as_str(),
+                    result.text.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            [
+                ("Zed", "https://zed.dev", "A code editor"),
+                ("https://zed.dev/docs", "https://zed.dev/docs", ""),
+            ]
+        );
+
+        let error = parse_results(&json!({ "hits": [] }), &settings()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "the web search response has no array of results at `data.hits`"
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use cloud_llm_client::{WebSearchResponse, WebSearchResult};
+use gpui::{App, Entity, SharedString, Task};
+use http_client::{HttpClient, Url};
+use language_model::ApiKeyState;
+use serde::Deserialize;
+use web_search::{WebSearchApiKeyStatus, WebSearchProvider, WebSearchProviderId};
+
+use crate::self_hosted::{api_key_state, api_key_status, get_json, load_api_key, store_api_key};
+use crate::web_search_settings::SearxngSettings;
+
+pub const SEARXNG_WEB_SEARCH_PROVIDER_ID: &str = "searxng";
+const SEARXNG_API_KEY_ENV_VAR_NAME: &str = "SEARXNG_API_KEY";
+
+/// Searches with a self-hosted [SearXNG](https://docs.searxng.org) instance.
+pub struct SearxngWebSearchProvider {
+    http_client: Arc<dyn HttpClient>,
+    settings: SearxngSettings,
+    api_key_state: Entity<ApiKeyState>,
+}
+
+impl SearxngWebSearchProvider {
+    pub fn new(http_client: Arc<dyn HttpClient>, settings: SearxngSettings, cx: &mut App) -> Self {
+        let api_key_state = api_key_state(&settings.api_url, SEARXNG_API_KEY_ENV_VAR_NAME, cx);
+        Self {
+            http_client,
+            settings,
+            api_key_state,
+        }
+    }
+}
+
+impl WebSearchProvider for SearxngWebSearchProvider {
+    fn id(&self) -> WebSearchProviderId {
+        WebSearchProviderId(SEARXNG_WEB_SEARCH_PROVIDER_ID.into())
+    }
+
+    fn search(&self, query: String, cx: &mut App) -> Task<Result<WebSearchResponse>> {
+        let http_client = self.http_client.clone();
+        let settings = self.settings.clone();
+        let api_key = load_api_key(&self.api_key_state, cx);
+        cx.spawn(async move |_cx| {
+            let api_key = api_key.await;
+            search_searxng(http_client, &settings, &query, api_key.as_deref()).await
+        })
+    }
+
+    fn api_key_env_var_name(&self) -> Option<SharedString> {
+        Some(SEARXNG_API_KEY_ENV_VAR_NAME.into())
+    }
+
+    fn api_key_status(&self, cx: &mut App) -> Task<WebSearchApiKeyStatus> {
+        api_key_status(&self.api_key_state, cx)
+    }
+
+    fn store_api_key(&self, api_key: Option<String>, cx: &mut App) -> Task<Result<()>> {
+        store_api_key(&self.api_key_state, api_key, cx)
+    }
+}
+
+#[derive(Deserialize)]
+struct SearxngResponse {
+    #[serde(default)]
+    results: Vec<SearxngResult>,
+}
+
+#[derive(Deserialize)]
+struct SearxngResult {
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    content: String,
+}
+
+async fn search_searxng(
+    http_client: Arc<dyn HttpClient>,
+    settings: &SearxngSettings,
+    query: &str,
+    api_key: Option<&str>,
+) -> Result<WebSearchResponse> {
+    let api_url = settings.api_url.trim_end_matches('/');
+    let search_url = if api_url.ends_with("/search") {
+        api_url.to_string()
+    } else {
+        format!("{api_url}/search")
+    };
+    let url = Url::parse_with_params(&search_url, &[("q", query), ("format", "json")])
+        .with_context(|| format!("invalid SearXNG URL {api_url}"))?;
+
+    let auth_header = api_key.map(|api_key| ("Authorization", format!("Bearer {api_key}")));
+    let response = get_json(http_client, url, auth_header).await.context(
+        "searching with SearXNG, which needs the `json` format enabled in `search.formats`",
+    )?;
+    let response = serde_json::from_value::<SearxngResponse>(response)
+        .context("unexpected response from SearXNG")?;
+
+    Ok(WebSearchResponse {
+        results: response
+            .results
+            .into_iter()
+            .take(settings.max_results)
+            .map(|result| WebSearchResult {
+                title: if result.title.is_empty() {
+                    result.url.clone()
+                } else {
+                    result.title
+                },
+                url: result.url,
+                text: result.content,
+            })
+            .collect(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use http_client::{FakeHttpClient, Response};
+
+    #[gpui::test]
+    async fn test_searxng_search(cx: &mut TestAppContext) {
+        let http_client = FakeHttpClient::create(|request| async move {
+            assert_eq!(request.uri().path(), "/searxng/search");
+            assert_eq!(request.uri().query(), Some("q=zed+editor&format=json"));
+            assert!(request.headers().get("Authorization").is_none());
+            let body = serde_json::json!({
+                "query": "zed editor",
+                "results": [
+                    {
+                        "url": "https://zed.dev",
+                        "title": "Zed",
+                        "content": "A code editor",
+                        "engine": "duckduckgo",
+                    },
+                    { "url": "https://github.com/zed-industries/zed" },
+                    { "url": "https://zed.dev/docs", "title": "Docs" },
+                ],
+            });
+            Ok(Response::builder()
+                .status(200)
+                .body(body.to_string().into())
+                .unwrap())
+        });
+        let settings = SearxngSettings {
+            api_url: "http://localhost:8080/searxng/".into(),
+            max_results: 2,
+        };
+
+        let provider = cx
+            .update(|cx| SearxngWebSearchProvider::new(http_client.clone(), settings.clone(), cx));
+        let response = cx
+            .update(|cx| provider.search("zed editor".into(), cx))
+            .await
+            .unwrap();
+        let results = response
+            .results
+            .iter()
+            .map(|result| {
+                (
+                    result.title.as_str(),
+                    result.url.as_str(),
+                    result.text.as_str(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            results,
+            [
+                ("Zed", "https://zed.dev", "A code editor"),
+                (
+                    "https://github.com/zed-industries/zed",
+                    "https://github.com/zed-industries/zed",
+                    ""
+                ),
+            ]
+        );
+
+        let error = search_searxng(
+            FakeHttpClient::create(|request| async move {
+                assert_eq!(
+                    request.headers().get("Authorization").unwrap(),
+                    "Bearer secret"
+                );
+                Ok(Response::builder()
+                    .status(403)
+                    .body("Forbidden".into())
+                    .unwrap())
+            }),
+            &settings,
+            "zed editor",
+            Some("secret"),
+        )
+        .await
+        .unwrap_err();
+        assert!(format!("{error:#}").contains("Status: 403"));
+    }
+}
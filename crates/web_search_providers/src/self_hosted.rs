@@ -0,0 +1,92 @@
+use std::sync::Arc;
+
+use anyhow::{Context as _, Result};
+use futures::AsyncReadExt as _;
+use gpui::{App, AppContext as _, Entity, SharedString, Task};
+use http_client::{AsyncBody, HttpClient, Method, Url};
+use language_model::{ApiKeyState, EnvVar};
+use web_search::WebSearchApiKeyStatus;
+
+/// Creates the state of a self-hosted provider's API key. The key is read from
+/// the environment variable when it's set, and otherwise from the system
+/// keychain entry for the provider's URL.
+pub(crate) fn api_key_state(
+    api_url: &str,
+    env_var_name: &'static str,
+    cx: &mut App,
+) -> Entity<ApiKeyState> {
+    cx.new(|_| ApiKeyState::new(SharedString::new(api_url), EnvVar::new(env_var_name.into())))
+}
+
+/// Loads the API key, if there is one. Many self-hosted instances don't need one.
+pub(crate) fn load_api_key(state: &Entity<ApiKeyState>, cx: &mut App) -> Task<Option<Arc<str>>> {
+    let load = state.update(cx, |state, cx| {
+        let url = state.url.clone();
+        state.load_if_needed(url, |state| state, cx)
+    });
+    let state = state.clone();
+    cx.spawn(async move |cx| {
+        load.await.ok();
+        state.read_with(cx, |state, _| state.key(&state.url))
+    })
+}
+
+/// Loads the API key and reports whether it comes from the environment variable
+/// or the system keychain.
+pub(crate) fn api_key_status(
+    state: &Entity<ApiKeyState>,
+    cx: &mut App,
+) -> Task<WebSearchApiKeyStatus> {
+    let load = load_api_key(state, cx);
+    let state = state.clone();
+    cx.spawn(async move |cx| {
+        if load.await.is_none() {
+            WebSearchApiKeyStatus::NotSet
+        } else if state.read_with(cx, |state, _| state.is_from_env_var()) {
+            WebSearchApiKeyStatus::EnvVar
+        } else {
+            WebSearchApiKeyStatus::SystemKeychain
+        }
+    })
+}
+
+/// Saves the API key in the system keychain entry for the provider's URL, or
+/// deletes it when `api_key` is `None`.
+pub(crate) fn store_api_key(
+    state: &Entity<ApiKeyState>,
+    api_key: Option<String>,
+    cx: &mut App,
+) -> Task<Result<()>> {
+    state.update(cx, |state, cx| {
+        let url = state.url.clone();
+        state.store(url, api_key, |state| state, cx)
+    })
+}
+
+/// Sends a search request and parses the JSON response.
+pub(crate) async fn get_json(
+    http_client: Arc<dyn HttpClient>,
+    url: Url,
+    auth_header: Option<(&str, String)>,
+) -> Result<serde_json::Value> {
+    let mut request = http_client::Request::builder()
+        .method(Method::GET)
+        .uri(url.as_str())
+        .header("Accept", "application/json");
+    if let Some((name, value)) = auth_header {
+        request = request.header(name, value);
+    }
+    let mut response = http_client
+        .send(request.body(AsyncBody::default())?)
+        .await
+        .context("failed to send web search request")?;
+
+    let mut body = String::new();
+    response.body_mut().read_to_string(&mut body).await?;
+    anyhow::ensure!(
+        response.status().is_success(),
+        "error performing web search.\nStatus: {:?}\nBody: {body}",
+        response.status(),
+    );
+    serde_json::from_str(&body).context("the web search response isn't valid JSON")
+}
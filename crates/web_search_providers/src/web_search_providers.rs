@@ -1,11 +1,19 @@
 mod cloud;
+mod json;
+mod searxng;
+mod self_hosted;
+mod web_search_settings;
 
 use client::{Client, UserStore};
 use gpui::{App, Context, Entity};
+use http_client::HttpClient;
 use language_model::LanguageModelRegistry;
+use settings::{Settings as _, SettingsStore};
 use std::sync::Arc;
 use web_search::{WebSearchProviderId, WebSearchRegistry};
 
+pub use crate::web_search_settings::{JsonWebSearchSettings, SearxngSettings, WebSearchSettings};
+
 pub fn init(client: Arc<Client>, user_store: Entity<UserStore>, cx: &mut App) {
     let registry = WebSearchRegistry::global(cx);
     registry.update(cx, |registry, cx| {
@@ -19,6 +27,28 @@ fn register_web_search_providers(
     user_store: Entity<UserStore>,
     cx: &mut Context<WebSearchRegistry>,
 ) {
+    let http_client: Arc<dyn HttpClient> = client.http_client();
+    let mut web_search_settings = WebSearchSettings::get_global(cx).clone();
+    register_self_hosted_web_search_providers(
+        registry,
+        http_client.clone(),
+        &web_search_settings,
+        cx,
+    );
+    cx.observe_global::<SettingsStore>(move |this, cx| {
+        let new_settings = WebSearchSettings::get_global(cx);
+        if *new_settings != web_search_settings {
+            web_search_settings = new_settings.clone();
+            register_self_hosted_web_search_providers(
+                this,
+                http_client.clone(),
+                &web_search_settings,
+                cx,
+            );
+        }
+    })
+    .detach();
+
     register_zed_web_search_provider(
         registry,
         client.clone(),
@@ -66,3 +96,36 @@ fn register_zed_web_search_provider(
         ));
     }
 }
+
+/// Registers the self-hosted providers configured in the `web_search`
+/// settings. These take precedence over Zed's hosted web search, and SearXNG
+/// takes precedence over the generic JSON provider when both are configured.
+fn register_self_hosted_web_search_providers(
+    registry: &mut WebSearchRegistry,
+    http_client: Arc<dyn HttpClient>,
+    settings: &WebSearchSettings,
+    cx: &mut Context<WebSearchRegistry>,
+) {
+    registry.unregister_provider(WebSearchProviderId(
+        json::JSON_WEB_SEARCH_PROVIDER_ID.into(),
+    ));
+    registry.unregister_provider(WebSearchProviderId(
+        searxng::SEARXNG_WEB_SEARCH_PROVIDER_ID.into(),
+    ));
+
+    if let Some(json_settings) = settings.json.clone() {
+        registry.set_active_provider(Arc::new(json::JsonWebSearchProvider::new(
+            http_client.clone(),
+            json_settings,
+            cx,
+        )));
+    }
+    if let Some(searxng_settings) = settings.searxng.clone() {
+        registry.set_active_provider(Arc::new(searxng::SearxngWebSearchProvider::new(
+            http_client,
+            searxng_settings,
+            cx,
+        )));
+    }
+    cx.notify();
+}
@@ -0,0 +1,58 @@
+use settings::{RegisterSetting, Settings};
+
+#[derive(Clone, Debug, Default, PartialEq, RegisterSetting)]
+pub struct WebSearchSettings {
+    pub searxng: Option<SearxngSettings>,
+    pub json: Option<JsonWebSearchSettings>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearxngSettings {
+    pub api_url: String,
+    pub max_results: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct JsonWebSearchSettings {
+    pub api_url: String,
+    pub query_parameter: String,
+    pub api_key_header: Option<String>,
+    pub results_path: String,
+    pub title_field: String,
+    pub url_field: String,
+    pub text_field: String,
+    pub max_results: usize,
+}
+
+const DEFAULT_MAX_RESULTS: usize = 10;
+
+impl Settings for WebSearchSettings {
+    fn from_settings(content: &settings::SettingsContent) -> Self {
+        let Some(web_search) = content.web_search.clone() else {
+            return Self::default();
+        };
+
+        let searxng = web_search
+            .searxng
+            .filter(|searxng| !searxng.api_url.trim().is_empty())
+            .map(|searxng| SearxngSettings {
+                api_url: searxng.api_url,
+                max_results: searxng.max_results.unwrap_or(DEFAULT_MAX_RESULTS),
+            });
+        let json = web_search
+            .json
+            .filter(|json| !json.api_url.trim().is_empty())
+            .map(|json| JsonWebSearchSettings {
+                api_url: json.api_url,
+                query_parameter: json.query_parameter.unwrap_or_else(|| "q".into()),
+                api_key_header: json.api_key_header.filter(|header| !header.is_empty()),
+                results_path: json.results_path.unwrap_or_else(|| "results".into()),
+                title_field: json.title_field.unwrap_or_else(|| "title".into()),
+                url_field: json.url_field.unwrap_or_else(|| "url".into()),
+                text_field: json.text_field.unwrap_or_else(|| "snippet".into()),
+                max_results: json.max_results.unwrap_or(DEFAULT_MAX_RESULTS),
+            });
+
+        Self { searxng, json }
+    }
+}
@@ -27,7 +27,8 @@ use project::{
 
 use language::{LanguageName, Toolchain, ToolchainScope};
 use remote::{
-    DockerConnectionOptions, RemoteConnectionOptions, SshConnectionOptions, WslConnectionOptions,
+    DockerConnectionOptions, ExecConnectionOptions, RemoteConnectionOptions, SshConnectionOptions,
+    WslConnectionOptions,
 };
 use serde::{Deserialize, Serialize};
 use sqlez::{
@@ -974,6 +975,11 @@ impl Domain for WorkspaceDb {
         sql!(
             ALTER TABLE remote_connections ADD COLUMN remote_env TEXT;
         ),
+        sql!(
+            ALTER TABLE remote_connections ADD COLUMN interactive_command TEXT;
+            ALTER TABLE remote_connections ADD COLUMN variables TEXT;
+            ALTER TABLE remote_connections ADD COLUMN upload_binary_over_exec BOOLEAN;
+        ),
     ];
 
     // Allow recovering from bad migration that was initially shipped to nightly
@@ -986,6 +992,10 @@ impl Domain for WorkspaceDb {
 
 db::static_connection!(WorkspaceDb, []);
 
+/// The `interactive_command`, `variables` and `upload_binary_over_exec` columns of a remote
+/// connection, which only exec connections have.
+type ExecColumns = (Option<String>, Option<String>, Option<bool>);
+
 impl WorkspaceDb {
     /// Returns a serialized workspace for the given worktree_roots. If the passed array
     /// is empty, the most recent workspace is returned instead. If no workspace for the
@@ -1504,6 +1514,7 @@ impl WorkspaceDb {
         let mut container_id = None;
         let mut use_podman = None;
         let mut remote_env = None;
+        let mut exec_columns: ExecColumns = (None, None, None);
         match options {
             RemoteConnectionOptions::Ssh(options) => {
                 kind = RemoteConnectionKind::Ssh;
@@ -1524,6 +1535,18 @@ impl WorkspaceDb {
                 user = Some(options.remote_user);
                 remote_env = serde_json::to_string(&options.remote_env).ok();
             }
+            RemoteConnectionOptions::Exec(options) => {
+                kind = RemoteConnectionKind::Exec;
+                host = Some(options.command);
+                name = Some(options.name);
+                user = None;
+                exec_columns = (
+                    options.interactive_command,
+                    // Variables are sorted, so equal variables serialize the same.
+                    serde_json::to_string(&options.variables).ok(),
+                    Some(options.upload_binary_over_exec),
+                );
+            }
             #[cfg(any(test, feature = "test-support"))]
             RemoteConnectionOptions::Mock(options) => {
                 kind = RemoteConnectionKind::Ssh;
@@ -1542,6 +1565,7 @@ impl WorkspaceDb {
             container_id,
             use_podman,
             remote_env,
+            exec_columns,
         )
     }
 
@@ -1556,6 +1580,7 @@ impl WorkspaceDb {
         container_id: Option<String>,
         use_podman: Option<bool>,
         remote_env: Option<String>,
+        exec_columns: ExecColumns,
     ) -> Result<RemoteConnectionId> {
         if let Some(id) = this.select_row_bound(sql!(
             SELECT id
@@ -1567,16 +1592,22 @@ impl WorkspaceDb {
                 user IS ? AND
                 distro IS ? AND
                 name IS ? AND
-                container_id IS ?
+                container_id IS ? AND
+                interactive_command IS ? AND
+                variables IS ? AND
+                upload_binary_over_exec IS ?
             LIMIT 1
         ))?((
-            kind.serialize(),
-            host.clone(),
-            port,
-            user.clone(),
-            distro.clone(),
-            name.clone(),
-            container_id.clone(),
+            (
+                kind.serialize(),
+                host.clone(),
+                port,
+                user.clone(),
+                distro.clone(),
+                name.clone(),
+                container_id.clone(),
+            ),
+            exec_columns.clone(),
         ))? {
             Ok(RemoteConnectionId(id))
         } else {
@@ -1590,19 +1621,25 @@ impl WorkspaceDb {
                     name,
                     container_id,
                     use_podman,
-                    remote_env
-                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                    remote_env,
+                    interactive_command,
+                    variables,
+                    upload_binary_over_exec
+                    ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)
                 RETURNING id
             ))?((
-                kind.serialize(),
-                host,
-                port,
-                user,
-                distro,
-                name,
-                container_id,
-                use_podman,
-                remote_env,
+                (
+                    kind.serialize(),
+                    host,
+                    port,
+                    user,
+                    distro,
+                    name,
+                    container_id,
+                    use_podman,
+                    remote_env,
+                ),
+                exec_columns,
             ))?
             .context("failed to insert remote project")?;
             Ok(RemoteConnectionId(id))
@@ -1704,13 +1741,17 @@ impl WorkspaceDb {
     fn remote_connections(&self) -> Result<HashMap<RemoteConnectionId, RemoteConnectionOptions>> {
         Ok(self.select(sql!(
             SELECT
-                id, kind, host, port, user, distro, container_id, name, use_podman, remote_env
+                id, kind, host, port, user, distro, container_id, name, use_podman, remote_env,
+                interactive_command, variables, upload_binary_over_exec
             FROM
                 remote_connections
         ))?()?
         .into_iter()
         .filter_map(
-            |(id, kind, host, port, user, distro, container_id, name, use_podman, remote_env)| {
+            |(
+                (id, kind, host, port, user, distro, container_id, name, use_podman, remote_env),
+                exec_columns,
+            )| {
                 Some((
                     RemoteConnectionId(id),
                     Self::remote_connection_from_row(
@@ -1723,6 +1764,7 @@ impl WorkspaceDb {
                         name,
                         use_podman,
                         remote_env,
+                        exec_columns,
                     )?,
                 ))
             },
@@ -1734,13 +1776,17 @@ impl WorkspaceDb {
         &self,
         id: RemoteConnectionId,
     ) -> Result<RemoteConnectionOptions> {
-        let (kind, host, port, user, distro, container_id, name, use_podman, remote_env) =
-            self.select_row_bound(sql!(
-                SELECT kind, host, port, user, distro, container_id, name, use_podman, remote_env
-                FROM remote_connections
-                WHERE id = ?
-            ))?(id.0)?
-            .context("no such remote connection")?;
+        let (
+            (kind, host, port, user, distro, container_id, name, use_podman, remote_env),
+            exec_columns,
+        ) = self.select_row_bound(sql!(
+            SELECT
+                kind, host, port, user, distro, container_id, name, use_podman, remote_env,
+                interactive_command, variables, upload_binary_over_exec
+            FROM remote_connections
+            WHERE id = ?
+        ))?(id.0)?
+        .context("no such remote connection")?;
         Self::remote_connection_from_row(
             kind,
             host,
@@ -1751,6 +1797,7 @@ impl WorkspaceDb {
             name,
             use_podman,
             remote_env,
+            exec_columns,
         )
         .context("invalid remote_connection row")
     }
@@ -1765,6 +1812,7 @@ impl WorkspaceDb {
         name: Option<String>,
         use_podman: Option<bool>,
         remote_env: Option<String>,
+        exec_columns: ExecColumns,
     ) -> Option<RemoteConnectionOptions> {
         match RemoteConnectionKind::deserialize(&kind)? {
             RemoteConnectionKind::Wsl => Some(RemoteConnectionOptions::Wsl(WslConnectionOptions {
@@ -1789,6 +1837,16 @@ impl WorkspaceDb {
                     remote_env,
                 }))
             }
+            RemoteConnectionKind::Exec => {
+                let (interactive_command, variables, upload_binary_over_exec) = exec_columns;
+                Some(RemoteConnectionOptions::Exec(ExecConnectionOptions {
+                    name: name?,
+                    command: host?,
+                    interactive_command,
+                    variables: serde_json::from_str(&variables?).ok()?,
+                    upload_binary_over_exec: upload_binary_over_exec?,
+                }))
+            }
         }
     }
 
@@ -3617,6 +3675,53 @@ mod tests {
         assert_eq!(connection_id, same_connection_id);
     }
 
+    #[gpui::test]
+    async fn test_get_or_create_exec_connection() {
+        let db = WorkspaceDb::open_test_db("test_get_or_create_exec_connection").await;
+
+        let options = ExecConnectionOptions {
+            name: "web".to_string(),
+            command: "kubectl exec -i {pod} -- {cmd}".to_string(),
+            interactive_command: Some("kubectl exec -it {pod} -- {cmd}".to_string()),
+            variables: BTreeMap::from_iter([("pod".to_string(), "web-0".to_string())]),
+            upload_binary_over_exec: true,
+        };
+
+        let connection_id = db
+            .get_or_create_remote_connection(RemoteConnectionOptions::Exec(options.clone()))
+            .await
+            .unwrap();
+        let same_connection_id = db
+            .get_or_create_remote_connection(RemoteConnectionOptions::Exec(options.clone()))
+            .await
+            .unwrap();
+        assert_eq!(connection_id, same_connection_id);
+
+        // Connections running the same command with other variables are distinct.
+        let other_options = ExecConnectionOptions {
+            variables: BTreeMap::from_iter([("pod".to_string(), "web-1".to_string())]),
+            ..options.clone()
+        };
+        let other_connection_id = db
+            .get_or_create_remote_connection(RemoteConnectionOptions::Exec(other_options.clone()))
+            .await
+            .unwrap();
+        assert_ne!(connection_id, other_connection_id);
+
+        assert_eq!(
+            db.remote_connections().unwrap(),
+            [
+                (connection_id, RemoteConnectionOptions::Exec(options)),
+                (
+                    other_connection_id,
+                    RemoteConnectionOptions::Exec(other_options)
+                ),
+            ]
+            .into_iter()
+            .collect::<HashMap<_, _>>(),
+        );
+    }
+
     #[gpui::test]
     async fn test_get_remote_connections() {
         let db = WorkspaceDb::open_test_db("test_get_remote_connections").await;
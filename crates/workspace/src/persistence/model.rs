@@ -34,6 +34,7 @@ pub(crate) enum RemoteConnectionKind {
     Ssh,
     Wsl,
     Docker,
+    Exec,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -106,6 +107,7 @@ impl RemoteConnectionKind {
             RemoteConnectionKind::Ssh => "ssh",
             RemoteConnectionKind::Wsl => "wsl",
             RemoteConnectionKind::Docker => "docker",
+            RemoteConnectionKind::Exec => "exec",
         }
     }
 
@@ -114,6 +116,7 @@ impl RemoteConnectionKind {
             "ssh" => Some(Self::Ssh),
             "wsl" => Some(Self::Wsl),
             "docker" => Some(Self::Docker),
+            "exec" => Some(Self::Exec),
             _ => None,
         }
     }
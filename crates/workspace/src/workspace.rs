@@ -9105,6 +9105,9 @@ pub fn workspace_windows_for_location(
                 (RemoteConnectionOptions::Docker(a), RemoteConnectionOptions::Docker(b)) => {
                     a.container_id == b.container_id
                 }
+                (RemoteConnectionOptions::Exec(a), RemoteConnectionOptions::Exec(b)) => {
+                    (&a.command, &a.variables) == (&b.command, &b.variables)
+                }
                 #[cfg(any(test, feature = "test-support"))]
                 (RemoteConnectionOptions::Mock(a), RemoteConnectionOptions::Mock(b)) => {
                     a.id == b.id
@@ -21,6 +21,8 @@ pub struct WorktreeSettings {
     pub private_files: PathMatcher,
     pub hidden_files: PathMatcher,
     pub read_only_files: PathMatcher,
+    pub search_index: bool,
+    pub large_file_threshold: u64,
 }
 
 impl WorktreeSettings {
@@ -97,6 +99,8 @@ impl Settings for WorktreeSettings {
             read_only_files: path_matchers(read_only_files, "read_only_files")
                 .log_err()
                 .unwrap_or_default(),
+            search_index: worktree.search_index.unwrap_or_default(),
+            large_file_threshold: worktree.large_file_threshold.unwrap_or_default(),
         }
     }
 }
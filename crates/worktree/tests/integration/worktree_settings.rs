@@ -19,6 +19,8 @@ fn make_settings_with_read_only(patterns: &[&str]) -> WorktreeSettings {
             PathStyle::local(),
         )
         .unwrap(),
+        search_index: false,
+        large_file_threshold: 0,
     }
 }
 
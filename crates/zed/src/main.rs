@@ -697,6 +697,7 @@ fn main() {
 
         editor::init(cx);
         image_viewer::init(cx);
+        large_file_viewer::init(cx);
         repl::notebook::init(cx);
         diagnostics::init(cx);
 
@@ -730,6 +731,8 @@ fn main() {
         encoding_selector::init(cx);
         language_selector::init(cx);
         line_ending_selector::init(cx);
+        undo_tree::init(cx);
+        local_history::init(cx);
         toolchain_selector::init(cx);
         theme_selector::init(cx);
         settings_profile_selector::init(cx);
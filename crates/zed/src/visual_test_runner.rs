@@ -2704,6 +2704,7 @@ fn run_multi_workspace_sidebar_visual_tests(
                             title: title.to_string().into(),
                             messages: Vec::new(),
                             updated_at,
+                            compaction: None,
                             detailed_summary: None,
                             initial_project_snapshot: None,
                             cumulative_token_usage: Default::default(),
@@ -2712,6 +2713,7 @@ fn run_multi_workspace_sidebar_visual_tests(
                             profile: None,
                             imported: false,
                             subagent_context: None,
+                            forked_from: None,
                             speed: None,
                             thinking_enabled: false,
                             thinking_effort: None,
@@ -1826,6 +1826,12 @@ pub fn handle_keymap_file_changes(
     let mut old_vim_enabled = VimModeSetting::get_global(cx).0;
     let mut old_helix_enabled = vim_mode_setting::HelixModeSetting::get_global(cx).0;
 
+    let vimrc_keymap_tx = base_keymap_tx.clone();
+    cx.observe_global::<vim::LoadedVimrc>(move |_| {
+        vimrc_keymap_tx.unbounded_send(()).ok();
+    })
+    .detach();
+
     cx.observe_global::<SettingsStore>(move |cx| {
         let new_base_keymap = *BaseKeymap::get_global(cx);
         let new_vim_enabled = VimModeSetting::get_global(cx).0;
@@ -2038,6 +2044,7 @@ pub fn load_default_keymap(cx: &mut App) {
         cx.bind_keys(
             KeymapFile::load_asset(VIM_KEYMAP_PATH, Some(KeybindSource::Vim), cx).unwrap(),
         );
+        cx.bind_keys(vim::vimrc_key_bindings(cx));
     }
 }
 
@@ -4955,8 +4962,10 @@ mod tests {
                 "keymap_editor",
                 "keystroke_input",
                 "language_selector",
+                "large_file_viewer",
                 "welcome",
                 "line_ending_selector",
+                "local_history",
                 "lsp_tool",
                 "markdown",
                 "menu",
@@ -4993,6 +5002,7 @@ mod tests {
                 "theme_selector",
                 "toast",
                 "toolchain",
+                "undo_tree",
                 "variable_list",
                 "vim",
                 "window",